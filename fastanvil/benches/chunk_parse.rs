@@ -1,7 +1,15 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use fastanvil::JavaChunk;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use std::collections::HashMap;
+
+use fastanvil::{Heightmaps, JavaChunk, RegionBuffer};
+use fastnbt::LongArray;
+use serde::Deserialize;
 
 const CHUNK_RAW: &[u8] = include_bytes!("../resources/chunk.nbt");
+const REGION_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/resources/worlds/datapack-dimension/region/r.0.0.mca"
+);
 
 pub fn fastnbt_benchmark(c: &mut Criterion) {
     c.bench_function("chunk", |b| {
@@ -10,7 +18,196 @@ pub fn fastnbt_benchmark(c: &mut Criterion) {
             black_box(chunk);
         });
     });
+
+    // Everything but the heightmaps is skipped, including the block states
+    // and light arrays.
+    #[derive(Deserialize)]
+    struct HeightmapsOnly {
+        #[serde(rename = "Level")]
+        level: Level,
+    }
+
+    #[derive(Deserialize)]
+    struct Level {
+        #[serde(rename = "Heightmaps")]
+        heightmaps: Heightmaps,
+    }
+
+    c.bench_function("chunk heightmaps only", |b| {
+        b.iter(|| {
+            let chunk: HeightmapsOnly = fastnbt::de::from_bytes(CHUNK_RAW).unwrap();
+            black_box(chunk.level.heightmaps);
+        });
+    });
+
+    // Chunk-sized block state arrays, into the owned array type.
+    let arrays: HashMap<String, LongArray> = (0..24)
+        .map(|i| (i.to_string(), LongArray::new(vec![i; 4096])))
+        .collect();
+    let data = fastnbt::to_bytes(&arrays).unwrap();
+
+    c.bench_function("owned long arrays", |b| {
+        b.iter(|| {
+            let arrays: HashMap<String, LongArray> = fastnbt::de::from_bytes(&data).unwrap();
+            black_box(arrays);
+        });
+    });
+
+    // The same arrays borrowed, then copied out of the input.
+    let borrowed: HashMap<String, fastnbt::borrow::LongArray> =
+        fastnbt::de::from_bytes(&data).unwrap();
+
+    c.bench_function("borrowed long arrays to_vec", |b| {
+        b.iter(|| {
+            for array in borrowed.values() {
+                black_box(array.to_vec());
+            }
+        });
+    });
+
+    c.bench_function("borrowed long arrays copy_into", |b| {
+        let mut buf = Vec::new();
+        b.iter(|| {
+            for array in borrowed.values() {
+                array.copy_into(&mut buf);
+                black_box(&buf);
+            }
+        });
+    });
+
+    c.bench_function("borrowed long arrays copy_into_slice", |b| {
+        let mut buf = vec![0; 4096];
+        b.iter(|| {
+            for array in borrowed.values() {
+                array.copy_into_slice(&mut buf).unwrap();
+                black_box(&buf);
+            }
+        });
+    });
+
+    c.bench_function("borrowed long arrays iter collect", |b| {
+        b.iter(|| {
+            for array in borrowed.values() {
+                black_box(array.iter().collect::<Vec<i64>>());
+            }
+        });
+    });
+
+    // Writing the arrays out again. The borrowed arrays copy their data
+    // straight from the input, where the owned ones write each element.
+    let owned: HashMap<String, LongArray> = fastnbt::de::from_bytes(&data).unwrap();
+
+    c.bench_function("owned long arrays to_bytes", |b| {
+        b.iter(|| black_box(fastnbt::to_bytes(&owned).unwrap()));
+    });
+
+    c.bench_function("borrowed long arrays to_bytes", |b| {
+        b.iter(|| black_box(fastnbt::to_bytes(&borrowed).unwrap()));
+    });
+
+    c.bench_function("owned long arrays from reader", |b| {
+        b.iter(|| {
+            let arrays: HashMap<String, LongArray> =
+                fastnbt::de::from_reader(data.as_slice()).unwrap();
+            black_box(arrays);
+        });
+    });
+}
+
+pub fn value_benchmark(c: &mut Criterion) {
+    let file = std::fs::File::open(REGION_PATH).unwrap();
+    let mut chunks = vec![CHUNK_RAW.to_vec()];
+    RegionBuffer::new(file)
+        .for_each_chunk(|_, _, data| chunks.push(data.clone()))
+        .unwrap();
+
+    c.bench_function("region scan value", |b| {
+        b.iter(|| {
+            for data in &chunks {
+                let value: fastnbt::Value = fastnbt::de::from_bytes(data).unwrap();
+                black_box(value);
+            }
+        });
+    });
+
+    c.bench_function("region scan borrowed value", |b| {
+        b.iter(|| {
+            for data in &chunks {
+                let value: fastnbt::borrow::Value = fastnbt::de::from_bytes(data).unwrap();
+                black_box(value);
+            }
+        });
+    });
+
+    // Not timed. Deserializing allocates exactly what each value needs, so
+    // shrinking freshly parsed values should not save anything.
+    let mut values: Vec<fastnbt::Value> = chunks
+        .iter()
+        .map(|data| fastnbt::de::from_bytes(data).unwrap())
+        .collect();
+    let size = |values: &[fastnbt::Value]| -> usize {
+        values.iter().map(fastnbt::Value::estimated_size).sum()
+    };
+    let before = size(&values);
+    values.iter_mut().for_each(fastnbt::Value::shrink_to_fit);
+    println!(
+        "region values: {} bytes estimated, {} after shrink_to_fit",
+        before,
+        size(&values)
+    );
+
+    c.bench_function("region shrink value", |b| {
+        b.iter_batched(
+            || values.clone(),
+            |mut values| {
+                values.iter_mut().for_each(fastnbt::Value::shrink_to_fit);
+                black_box(values)
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    c.bench_function("region scan validate", |b| {
+        b.iter(|| {
+            for data in &chunks {
+                black_box(fastnbt::validate(data).unwrap());
+            }
+        });
+    });
+
+    // Only the status and data version, which newer chunks have at the root
+    // and older ones have in Level.
+    c.bench_function("region status borrowed value", |b| {
+        b.iter(|| {
+            for data in &chunks {
+                let value: fastnbt::borrow::Value = fastnbt::de::from_bytes(data).unwrap();
+                let level = value.get("Level").unwrap_or(&value);
+                black_box((level["Status"].as_str(), value["DataVersion"].as_i64()));
+            }
+        });
+    });
+
+    c.bench_function("region status borrowed compound", |b| {
+        b.iter(|| {
+            for data in &chunks {
+                let chunk: fastnbt::borrow::Compound = fastnbt::de::from_bytes(data).unwrap();
+                let level: Option<fastnbt::borrow::Compound> = chunk.get("Level").unwrap();
+                let status: Option<&str> = level.as_ref().unwrap_or(&chunk).get("Status").unwrap();
+                let version: Option<i32> = chunk.get("DataVersion").unwrap();
+                black_box((status, version));
+            }
+        });
+    });
+
+    c.bench_function("region scan get_path", |b| {
+        b.iter(|| {
+            for data in &chunks {
+                let version: Option<i32> = fastnbt::de::get_path(data, &["DataVersion"]).unwrap();
+                black_box(version);
+            }
+        });
+    });
 }
 
-criterion_group!(benches, fastnbt_benchmark);
+criterion_group!(benches, fastnbt_benchmark, value_benchmark);
 criterion_main!(benches);
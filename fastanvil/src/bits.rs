@@ -4,10 +4,7 @@ use bit_field::{BitArray, BitField};
 use fastnbt::LongArray;
 use serde::Deserialize;
 
-// Various data versions for the anvil format
-const V1_17_0: i32 = 2724;
-const V1_17_1: i32 = 2730;
-const SNAPSHOT_21W44A: i32 = 2845;
+use crate::support::heightmap_is_offset;
 
 /// PackedBits can be used in place of blockstates in chunks to avoid
 /// allocating memory for them when they might not be needed. This object by
@@ -17,51 +14,34 @@ const SNAPSHOT_21W44A: i32 = 2845;
 pub struct PackedBits(pub LongArray);
 
 impl PackedBits {
+    /// Unpack the blockstates into `buf`, working out from the length of the
+    /// data whether it uses the padded 1.16 layout or the 1.15 one. If the
+    /// data is too short for the buffer, the rest of the buffer is left as it
+    /// was. Does nothing if `bits_per_item` is not from 1 to 16.
     pub fn unpack_blockstates(&self, bits_per_item: usize, buf: &mut [u16]) {
-        let bpi = match self.0.len() {
-            256 => 4,
-            342 => 5,
-            410 => 6,
-            456 => 7,
-            512 => 8,
-            586 => 9,
-            _ => return self.unpack_1_15(bits_per_item, buf),
+        let (bpi, padded) = match self.0.len() {
+            256 => (4, true),
+            342 => (5, true),
+            410 => (6, true),
+            456 => (7, true),
+            512 => (8, true),
+            586 => (9, true),
+            // Beyond 9 bits the 1.16 lengths are ambiguous, eg 11 and 12 bits
+            // both take 820 longs, so we go by the palette.
+            _ if !(1..=16).contains(&bits_per_item) => return,
+            len if len == padded_len(bits_per_item) => (bits_per_item, true),
+            _ => (bits_per_item, false),
         };
 
-        self.unpack_1_16(bpi, buf)
-    }
-
-    fn unpack_1_16(&self, bits_per_item: usize, buf: &mut [u16]) {
-        let data = &*self.0;
-
-        let mut buf_i = 0;
-        let values_per_64bits = 64 / bits_per_item;
-
-        for datum in data {
-            let datum = *datum as u64;
-            for i in 0..values_per_64bits {
-                let v = datum.get_bits(i * bits_per_item..(i + 1) * bits_per_item);
-                buf[buf_i] = v as u16;
-                buf_i += 1;
-                if buf_i >= buf.len() {
-                    break;
-                }
-            }
-        }
-    }
-
-    fn unpack_1_15(&self, bits_per_item: usize, buf: &mut [u16]) {
-        // TODO: Get around having to allocate here.
-        let mut v: Vec<u64> = vec![];
-        for datum in &*self.0 {
-            v.push(*datum as u64);
-        }
+        let held = if padded {
+            self.0.len() * (64 / bpi)
+        } else {
+            self.0.len() * 64 / bpi
+        };
+        let len = held.min(buf.len());
 
-        for (i, val) in buf.iter_mut().enumerate() {
-            let begin = i * bits_per_item;
-            let end = begin + bits_per_item;
-            *val = v.get_bits(begin..end) as u16;
-        }
+        // Only the bits can be rejected, and those were checked above.
+        let _ = self.0.unpack_into(bpi, padded, &mut buf[..len]);
     }
 }
 
@@ -73,20 +53,44 @@ impl PackedBits {
 ////
 /// In 1.15 there is no padding, so blocks bleed into one another, so remainder bits are tracked and handled for you.
 /// In 1.16 padding bits are used so that a block is always in a single 64-bit int.
+///
+/// Panics if the palette is longer than [`MAX_PALETTE_LEN`]. See
+/// [`try_expand_blockstates`] for a version that does not.
 pub fn expand_blockstates(data: &[i64], palette_len: usize) -> Vec<u16> {
+    try_expand_blockstates(data, palette_len)
+        .unwrap_or_else(|| panic!("palette too long, len {}", palette_len))
+}
+
+/// Expand blockstate data so each block is an element of a `Vec`, returning
+/// None if the palette is longer than [`MAX_PALETTE_LEN`].
+pub fn try_expand_blockstates(data: &[i64], palette_len: usize) -> Option<Vec<u16>> {
+    if palette_len > MAX_PALETTE_LEN {
+        return None;
+    }
+
     let bits_per_item = bits_per_block(palette_len);
     let blocks_per_section = 16 * 16 * 16;
 
     // If it's tightly packed assume 1.15 format.
     if blocks_per_section * bits_per_item == data.len() * 64 {
-        expand_generic_1_15(data, bits_per_item)
+        Some(expand_generic_1_15(data, bits_per_item))
     } else {
-        expand_generic_1_16(data, bits_per_item)
+        Some(expand_generic_1_16(data, bits_per_item))
     }
 }
 
 /// Expand heightmap data. This is equivalent to `expand_generic(data, 9)`.
+///
+/// Panics if the data is not a length we understand. See
+/// [`try_expand_heightmap`] for a version that does not.
 pub fn expand_heightmap(data: &[i64], y_min: isize, data_version: i32) -> Vec<i16> {
+    try_expand_heightmap(data, y_min, data_version)
+        .unwrap_or_else(|| panic!("did not understand height format, len {}", data.len()))
+}
+
+/// Expand heightmap data into the 256 heights of a chunk, returning None if the
+/// data is not a length we understand.
+pub fn try_expand_heightmap(data: &[i64], y_min: isize, data_version: i32) -> Option<Vec<i16>> {
     let bits_per_item = 9;
 
     let _after1_17 = data_version >= 2695;
@@ -109,11 +113,11 @@ pub fn expand_heightmap(data: &[i64], y_min: isize, data_version: i32) -> Vec<i1
     // v.into_iter().map(|h| h as i16 + shift).collect()
 
     match data_version {
-        V1_17_0 | V1_17_1 | SNAPSHOT_21W44A.. => {
+        v if heightmap_is_offset(v) => {
             let bits_per = match data.len() {
                 43 => 10,
                 37 => 9,
-                len => panic!("Len of heightmap data: {}", len),
+                _ => return None,
             };
 
             // TODO: We need to know the minimum block in this world in order to
@@ -122,7 +126,7 @@ pub fn expand_heightmap(data: &[i64], y_min: isize, data_version: i32) -> Vec<i1
             v.resize(256, 0);
 
             // Reinterpret as signed.
-            v.into_iter().map(|h| (h as isize + y_min) as i16).collect()
+            Some(v.into_iter().map(|h| (h as isize + y_min) as i16).collect())
         }
         _ => match data.len() {
             LEN_1_16_TO_17 => {
@@ -135,13 +139,15 @@ pub fn expand_heightmap(data: &[i64], y_min: isize, data_version: i32) -> Vec<i1
                 v.resize(256, 0);
 
                 // Reinterpret as signed.
-                v.into_iter().map(|h| h as i16).collect()
+                Some(v.into_iter().map(|h| h as i16).collect())
             }
-            LEN_1_15 => expand_generic_1_15(data, bits_per_item)
-                .into_iter()
-                .map(|h| h as i16)
-                .collect(),
-            _ => panic!("did not understand height format, try calculated mode"),
+            LEN_1_15 => Some(
+                expand_generic_1_15(data, bits_per_item)
+                    .into_iter()
+                    .map(|h| h as i16)
+                    .collect(),
+            ),
+            _ => None,
         },
     }
 }
@@ -179,15 +185,48 @@ pub fn expand_generic_1_15(data: &[i64], bits: usize) -> Vec<u16> {
     result
 }
 
+/// The largest palette a section can have. A section has 4096 blocks, so a
+/// bigger palette could never be fully used, and is treated as malformed.
+pub const MAX_PALETTE_LEN: usize = 16 * 16 * 16;
+
 /// Get the number of bits that will be used in `Blockstates` per block.
 ///
+/// This is the number of bits needed to store the largest palette index, but
+/// never less than 4:
+///
+/// | palette length | bits per block |
+/// | -------------- | -------------- |
+/// | 0 to 16        | 4              |
+/// | 17 to 32       | 5              |
+/// | 33 to 64       | 6              |
+/// | 65 to 128      | 7              |
+/// | 129 to 256     | 8              |
+/// | 257 to 512     | 9              |
+/// | 513 to 1024    | 10             |
+/// | 1025 to 2048   | 11             |
+/// | 2049 to 4096   | 12             |
+///
+/// The mapping continues for larger lengths, but palettes longer than
+/// [`MAX_PALETTE_LEN`] are not valid.
+///
 /// See `anvil::expand_blockstates` for more information.
 pub fn bits_per_block(palette_len: usize) -> usize {
-    if palette_len < 16 {
-        4
-    } else {
-        std::cmp::max((palette_len as f64).log2().ceil() as usize, 4)
-    }
+    palette_bits(palette_len, 4)
+}
+
+/// Number of bits needed to store any index into a palette of the given
+/// length, but at least `min_bits`.
+pub(crate) fn palette_bits(palette_len: usize, min_bits: usize) -> usize {
+    let max_index = palette_len.saturating_sub(1);
+    let bits = (usize::BITS - max_index.leading_zeros()) as usize;
+    std::cmp::max(bits, min_bits)
+}
+
+/// Number of longs used to store a section's worth of items in the 1.16
+/// format, where items do not span longs.
+fn padded_len(bits_per_item: usize) -> usize {
+    let values_per_64bits = 64 / bits_per_item;
+    MAX_PALETTE_LEN.div_ceil(values_per_64bits)
 }
 
 #[cfg(test)]
@@ -302,6 +341,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn packed_bits_unpack_both_layouts() {
+        // The 1.15.2 overworld heightmap above, with 9 bit entries that
+        // straddle longs.
+        let data: Vec<i64> = vec![
+            1299610109330100808,
+            649787462479005732,
+            329397330866873490,
+            -9060925171218247159,
+            4692909455540619556,
+            2346453626107004050,
+        ];
+        let mut buf = [0; 32];
+        PackedBits(LongArray::new(data.clone())).unpack_blockstates(9, &mut buf);
+        assert_eq!(&expand_generic_1_15(&data, 9)[..32], &buf[..]);
+        assert_eq!(&[72, 73, 72, 72, 72, 73, 72, 72][..], &buf[..8]);
+
+        // 1.16 block states for a 5 bit palette.
+        let data: Vec<i64> = (0..342).map(|i| i * 0x0123_4567_89ab).collect();
+        let mut buf = [0; 4096];
+        PackedBits(LongArray::new(data.clone())).unpack_blockstates(5, &mut buf);
+        assert_eq!(&expand_generic_1_16(&data, 5)[..4096], &buf[..]);
+    }
+
     #[test]
     fn palette_size_checks() {
         assert_eq!(4, bits_per_block(2));
@@ -318,6 +381,23 @@ mod tests {
         assert_eq!(10, bits_per_block(1 << 10));
     }
 
+    #[test]
+    fn palette_size_boundaries() {
+        assert_eq!(4, bits_per_block(0));
+        assert_eq!(4, bits_per_block(1));
+        assert_eq!(5, bits_per_block(17));
+        assert_eq!(8, bits_per_block(255));
+        assert_eq!(8, bits_per_block(256));
+        assert_eq!(9, bits_per_block(257));
+        assert_eq!(12, bits_per_block(4095));
+        assert_eq!(12, bits_per_block(MAX_PALETTE_LEN));
+        assert_eq!(13, bits_per_block(MAX_PALETTE_LEN + 1));
+
+        // Biomes go down to a single bit.
+        assert_eq!(1, palette_bits(2, 1));
+        assert_eq!(2, palette_bits(3, 1));
+    }
+
     #[test]
     fn unpack_1_15_heightmap() {
         let height_data = vec![
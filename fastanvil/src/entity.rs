@@ -0,0 +1,186 @@
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+use fastnbt::Value;
+use serde::{de, Deserialize, Deserializer};
+
+/// An entity, such as a mob, item frame, or minecart. Only some common fields
+/// are captured here.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Entity {
+    #[serde(rename = "id")]
+    pub id: String,
+
+    pub pos: Pos,
+
+    pub rotation: Rotation,
+
+    /// Contents of entities that hold items, eg chest minecarts.
+    pub items: Option<Vec<ItemStack>>,
+}
+
+/// A stack of items, eg in an inventory or container.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ItemStack {
+    #[serde(rename = "id")]
+    pub id: String,
+
+    pub count: i8,
+
+    /// Slot the stack is in. Only present for stacks in a container.
+    pub slot: Option<i8>,
+
+    #[serde(rename = "tag")]
+    pub tag: Option<Value>,
+}
+
+/// Position of an entity, stored in NBT as a list of 3 doubles: x, y, z.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Pos(pub [f64; 3]);
+
+impl Pos {
+    pub fn x(&self) -> f64 {
+        self.0[0]
+    }
+
+    pub fn y(&self) -> f64 {
+        self.0[1]
+    }
+
+    pub fn z(&self) -> f64 {
+        self.0[2]
+    }
+
+    /// Straight line distance between two positions.
+    pub fn distance_to(&self, other: &Pos) -> f64 {
+        self.distance_squared_to(other).sqrt()
+    }
+
+    /// Square of the straight line distance between two positions. Cheaper
+    /// than [`Pos::distance_to`] if you only need to compare distances.
+    pub fn distance_squared_to(&self, other: &Pos) -> f64 {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum()
+    }
+}
+
+impl Display for Pos {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {}, {})", self.x(), self.y(), self.z())
+    }
+}
+
+impl<'de> Deserialize<'de> for Pos {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Self(fixed_list(d, "a list of 3 doubles")?))
+    }
+}
+
+/// Rotation of an entity in degrees, stored in NBT as a list of 2 floats: yaw
+/// then pitch.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rotation(pub [f32; 2]);
+
+impl Rotation {
+    /// Clockwise rotation around the Y axis, where 0 is facing south (+Z).
+    pub fn yaw(&self) -> f32 {
+        self.0[0]
+    }
+
+    /// Rotation above or below the horizon, where -90 is looking straight up.
+    pub fn pitch(&self) -> f32 {
+        self.0[1]
+    }
+}
+
+impl Display for Rotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "yaw {}, pitch {}", self.yaw(), self.pitch())
+    }
+}
+
+impl<'de> Deserialize<'de> for Rotation {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Self(fixed_list(d, "a list of 2 floats")?))
+    }
+}
+
+/// Deserialize a list that must have exactly `N` elements.
+fn fixed_list<'de, D, T, const N: usize>(d: D, expected: &'static str) -> Result<[T; N], D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let list: Vec<T> = Deserialize::deserialize(d)?;
+    let len = list.len();
+    <[T; N]>::try_from(list).map_err(|_| de::Error::invalid_length(len, &expected))
+}
+
+#[cfg(feature = "mint")]
+mod mint_interop {
+    use super::{Pos, Rotation};
+
+    impl From<Pos> for mint::Point3<f64> {
+        fn from(pos: Pos) -> Self {
+            pos.0.into()
+        }
+    }
+
+    impl From<mint::Point3<f64>> for Pos {
+        fn from(p: mint::Point3<f64>) -> Self {
+            Self(p.into())
+        }
+    }
+
+    impl From<Rotation> for mint::Vector2<f32> {
+        fn from(rot: Rotation) -> Self {
+            rot.0.into()
+        }
+    }
+
+    impl From<mint::Vector2<f32>> for Rotation {
+        fn from(v: mint::Vector2<f32>) -> Self {
+            Self(v.into())
+        }
+    }
+}
+
+#[cfg(feature = "glam")]
+mod glam_interop {
+    use super::{Pos, Rotation};
+
+    impl From<Pos> for glam::DVec3 {
+        fn from(pos: Pos) -> Self {
+            glam::DVec3::from_array(pos.0)
+        }
+    }
+
+    impl From<glam::DVec3> for Pos {
+        fn from(v: glam::DVec3) -> Self {
+            Self(v.to_array())
+        }
+    }
+
+    impl From<Rotation> for glam::Vec2 {
+        fn from(rot: Rotation) -> Self {
+            glam::Vec2::from_array(rot.0)
+        }
+    }
+
+    impl From<glam::Vec2> for Rotation {
+        fn from(v: glam::Vec2) -> Self {
+            Self(v.to_array())
+        }
+    }
+}
@@ -1,58 +1,63 @@
 use serde::de::DeserializeOwned;
 
-use crate::{Chunk, LoaderError};
+use crate::{Chunk, DirectorySource, RegionSource};
 use crate::{LoaderResult, RegionBuffer};
 use crate::{RCoord, Region, RegionLoader};
 use std::marker::PhantomData;
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
-pub struct RegionFileLoader<C: Chunk> {
-    region_dir: PathBuf,
+/// Loads regions from region files. By default these are the `.mca` files in a
+/// directory, but any [`RegionSource`] can be used, such as an archive.
+pub struct RegionFileLoader<C: Chunk, S: RegionSource = DirectorySource> {
+    source: S,
     _d: PhantomData<C>,
 }
 
 impl<C: Chunk> RegionFileLoader<C> {
     pub fn new(region_dir: PathBuf) -> Self {
+        Self::from_source(DirectorySource::new(region_dir))
+    }
+}
+
+impl<C: Chunk, S: RegionSource> RegionFileLoader<C, S> {
+    pub fn from_source(source: S) -> Self {
         Self {
-            region_dir,
+            source,
             _d: PhantomData,
         }
     }
+
+    pub(crate) fn source(&self) -> &S {
+        &self.source
+    }
 }
 
-impl<C: Chunk + DeserializeOwned> RegionLoader<C> for RegionFileLoader<C> {
+impl<C, S> RegionLoader<C> for RegionFileLoader<C, S>
+where
+    C: Chunk + DeserializeOwned,
+    S: RegionSource,
+    S::Reader: 'static,
+{
     fn region(&self, x: RCoord, z: RCoord) -> Option<Box<dyn Region<C>>> {
-        let path = self.region_dir.join(format!("r.{}.{}.mca", x.0, z.0));
-        let file = std::fs::File::open(path).ok()?;
-        let region = RegionBuffer::new(file);
+        span!(DEBUG, "region_open", x = x.0, z = z.0);
+        let reader = match self.source.open(x, z) {
+            Ok(reader) => reader?,
+            Err(_e) => {
+                event!(DEBUG, error = %_e, "could not open region");
+                return None;
+            }
+        };
+        let region = RegionBuffer::new(reader);
 
         Some(Box::new(region))
     }
 
     fn list(&self) -> LoaderResult<Vec<(RCoord, RCoord)>> {
-        let paths = std::fs::read_dir(&self.region_dir).map_err(|e| LoaderError(e.to_string()))?;
-
-        let paths = paths
-            .into_iter()
-            .filter_map(|path| path.ok())
-            .map(|path| path.path())
-            .filter(|path| path.is_file())
-            .filter(|path| {
-                let ext = path.extension();
-                ext.is_some() && ext.unwrap() == "mca"
-            })
-            .filter(|path| fs::metadata(path).unwrap().len() > 0)
-            .filter_map(|p| coords_from_region(&p))
-            .collect();
-
-        Ok(paths)
+        self.source.list()
     }
 }
 
-fn coords_from_region(region: &Path) -> Option<(RCoord, RCoord)> {
+pub(crate) fn coords_from_region(region: &Path) -> Option<(RCoord, RCoord)> {
     let filename = region.file_name()?.to_str()?;
     let mut parts = filename.split('.').skip(1);
     let x = parts.next()?.parse::<isize>().ok()?;
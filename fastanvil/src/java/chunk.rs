@@ -1,12 +1,14 @@
 use std::cell::RefCell;
+use std::convert::TryFrom;
 use std::ops::Range;
 
+use fastnbt::IntArray;
 use serde::Deserialize;
 
-use crate::{biome::Biome, Block, Chunk, HeightMode};
-use crate::{expand_heightmap, Heightmaps, Section, SectionTower};
+use crate::{biome::Biome, Block, Chunk, HeightMode, LightPresence};
+use crate::{try_expand_heightmap, Heightmaps, Section, SectionTower, MAX_PALETTE_LEN};
 
-use super::AIR;
+use super::{chunk_layout, ChunkLayout, AIR};
 
 impl Chunk for CurrentJavaChunk {
     fn status(&self) -> String {
@@ -14,6 +16,13 @@ impl Chunk for CurrentJavaChunk {
     }
 
     fn surface_height(&self, x: usize, z: usize, mode: HeightMode) -> isize {
+        assert!(
+            x < 16 && z < 16,
+            "surface_height out of range: ({}, {})",
+            x,
+            z
+        );
+
         if self.lazy_heightmap.borrow().is_none() {
             self.recalculate_heightmap(mode);
         }
@@ -22,6 +31,10 @@ impl Chunk for CurrentJavaChunk {
     }
 
     fn biome(&self, x: usize, y: isize, z: usize) -> Option<Biome> {
+        if x >= 16 || z >= 16 {
+            return None;
+        }
+
         // Going to be awkward because the biomes are now paletted, and so are
         // the string not a number.
 
@@ -29,14 +42,26 @@ impl Chunk for CurrentJavaChunk {
         let sec = sections.get_section_for_y(y)?;
         let sec_y = (y - sec.y as isize * 16) as usize;
 
-        sec.biomes.at(x, sec_y, z).cloned()
+        sec.biomes
+            .at(x, sec_y, z)
+            .cloned()
+            .or_else(|| self.chunk_level_biome(x, y, z))
     }
 
     fn block(&self, x: usize, y: isize, z: usize) -> Option<&Block> {
+        if x >= 16 || z >= 16 {
+            return None;
+        }
+
         let sections = self.sections.as_ref()?;
         let sec = sections.get_section_for_y(y)?;
         let sec_y = (y - sec.y as isize * 16) as usize;
 
+        // A longer palette can't be indexed by the packed data.
+        if sec.block_states.palette().len() > MAX_PALETTE_LEN {
+            return None;
+        }
+
         Some(sec.block_states.at(x, sec_y, z).unwrap_or(&AIR))
     }
 
@@ -52,25 +77,138 @@ impl Chunk for CurrentJavaChunk {
 }
 
 /// A Minecraft chunk.
-#[derive(Deserialize, Debug)]
+///
+/// As well as chunks from 1.18 onwards, this accepts the intermediate layouts
+/// written by the 21w37a to 21w43a snapshots, based on the chunk's
+/// DataVersion.
+#[derive(Debug)]
 pub struct CurrentJavaChunk {
-    #[serde(rename = "DataVersion")]
     pub data_version: i32,
 
     // Maybe put section and heightmaps together and serde flatten?
     pub sections: Option<SectionTower<Section>>,
 
-    #[serde(rename = "Heightmaps")]
     pub heightmaps: Option<Heightmaps>,
 
-    #[serde(rename = "Status")]
     pub status: String,
 
-    #[serde(skip)]
+    /// Chunk-level biomes. Only present in some 1.18 snapshot chunks, from
+    /// before biomes moved into the sections.
+    pub biomes: Option<IntArray>,
+
     lazy_heightmap: RefCell<Option<[i16; 256]>>,
 }
 
+/// The fields of a chunk as they might appear in any post-1.18 layout. Which
+/// ones we actually use depends on the DataVersion.
+#[derive(Deserialize)]
+struct CurrentJavaChunkRaw {
+    #[serde(rename = "DataVersion")]
+    data_version: i32,
+
+    sections: Option<SectionTower<Section>>,
+
+    #[serde(rename = "Heightmaps")]
+    heightmaps: Option<Heightmaps>,
+
+    #[serde(rename = "Status")]
+    status: Option<String>,
+
+    #[serde(rename = "Level")]
+    level: Option<SnapshotLevel>,
+}
+
+/// The `Level` compound still present in most of the 1.18 snapshots.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SnapshotLevel {
+    #[serde(alias = "sections")]
+    sections: Option<SectionTower<Section>>,
+
+    heightmaps: Option<Heightmaps>,
+
+    status: String,
+
+    biomes: Option<IntArray>,
+}
+
+impl<'de> Deserialize<'de> for CurrentJavaChunk {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw: CurrentJavaChunkRaw = Deserialize::deserialize(d)?;
+
+        let layout = chunk_layout(raw.data_version);
+
+        let (sections, heightmaps, status, biomes) = match (layout, raw.level) {
+            (ChunkLayout::Snapshot18, Some(level)) => {
+                (level.sections, level.heightmaps, level.status, level.biomes)
+            }
+            (ChunkLayout::Snapshot18 | ChunkLayout::Post18, None) => (
+                raw.sections,
+                raw.heightmaps,
+                raw.status
+                    .ok_or_else(|| serde::de::Error::missing_field("Status"))?,
+                None,
+            ),
+            (_, _) => {
+                return Err(serde::de::Error::custom(format!(
+                    "unexpected chunk layout for data version {}: {:?}",
+                    raw.data_version, layout
+                )))
+            }
+        };
+
+        Ok(Self {
+            data_version: raw.data_version,
+            sections,
+            heightmaps,
+            status,
+            biomes,
+            lazy_heightmap: Default::default(),
+        })
+    }
+}
+
 impl CurrentJavaChunk {
+    /// Which light arrays are stored for each section that can hold light,
+    /// from the bottom up. This is one more section either side of the
+    /// [`y_range`][`Chunk::y_range`].
+    pub fn light_sections(&self) -> Vec<(i8, LightPresence)> {
+        self.sections
+            .as_ref()
+            .map(|s| s.light_sections())
+            .unwrap_or_default()
+    }
+
+    /// The block light at the given coordinates, see
+    /// [`SectionTower::block_light`].
+    pub fn block_light(&self, x: usize, y: isize, z: usize) -> Option<u8> {
+        self.sections.as_ref()?.block_light(x, y, z)
+    }
+
+    /// The sky light at the given coordinates, see
+    /// [`SectionTower::sky_light`].
+    pub fn sky_light(&self, x: usize, y: isize, z: usize) -> Option<u8> {
+        self.sections.as_ref()?.sky_light(x, y, z)
+    }
+
+    /// Look up a biome in the chunk-level biomes of a snapshot chunk. These
+    /// are 4x4x4 cells covering the full height of the chunk.
+    fn chunk_level_biome(&self, x: usize, y: isize, z: usize) -> Option<Biome> {
+        let biomes = self.biomes.as_ref()?;
+        let range = self.y_range();
+        if !range.contains(&y) {
+            return None;
+        }
+
+        let y_shifted = (y - range.start) as usize;
+        let i = (z / 4) * 4 + (x / 4) + (y_shifted / 4) * 16;
+
+        Biome::try_from(*biomes.get(i)?).ok()
+    }
+
     pub fn recalculate_heightmap(&self, mode: HeightMode) {
         // TODO: Find top section and start there, pointless checking 320 down
         // if its a 1.16 chunk.
@@ -79,13 +217,15 @@ impl CurrentJavaChunk {
 
         match mode {
             HeightMode::Trust => {
+                // If the heightmap is missing or we don't understand it, fall
+                // through to calculating it.
                 let updated = self
                     .heightmaps
                     .as_ref()
                     .and_then(|hm| hm.motion_blocking.as_ref())
-                    .map(|hm| {
-                        let y_min = self.sections.as_ref().unwrap().y_min();
-                        expand_heightmap(hm.as_slice(), y_min, self.data_version)
+                    .zip(self.sections.as_ref())
+                    .and_then(|(hm, sections)| {
+                        try_expand_heightmap(hm.as_slice(), sections.y_min(), self.data_version)
                     })
                     .map(|hm| map.copy_from_slice(hm.as_slice()))
                     .is_some();
@@ -94,6 +234,12 @@ impl CurrentJavaChunk {
                     self.lazy_heightmap.replace(Some(map));
                     return;
                 }
+
+                event!(
+                    DEBUG,
+                    data_version = self.data_version,
+                    "heightmap missing or unrecognised, calculating it instead"
+                );
             }
             HeightMode::Calculate => {} // fall through to calc mode
         }
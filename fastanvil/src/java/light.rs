@@ -0,0 +1,19 @@
+/// Which light arrays a chunk section stores. Light is kept for one section
+/// below and one above the sections holding blocks, and sections without an
+/// array take an implied default instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LightPresence {
+    /// Whether the section has a `BlockLight` array.
+    pub block_light: bool,
+    /// Whether the section has a `SkyLight` array.
+    pub sky_light: bool,
+}
+
+/// Read the light level of a block from a section's light array, which packs
+/// two 4-bit levels per byte, the lower nibble first.
+pub(crate) fn light_at(data: &[i8], x: usize, sec_y: usize, z: usize) -> Option<u8> {
+    let i = sec_y * 256 + z * 16 + x;
+    let byte = *data.get(i / 2)? as u8;
+
+    Some(if i & 1 == 0 { byte & 0xf } else { byte >> 4 })
+}
@@ -7,6 +7,7 @@ pub mod pre18;
 mod block;
 mod chunk;
 mod heightmaps;
+mod light;
 mod section;
 mod section_data;
 mod section_tower;
@@ -14,11 +15,13 @@ mod section_tower;
 pub use block::*;
 pub use chunk::*;
 pub use heightmaps::*;
+pub use light::*;
 pub use section::*;
 pub use section_data::*;
 pub use section_tower::*;
 use serde::Deserialize;
 
+use crate::support::{SNAPSHOT_21W37A, SNAPSHOT_21W44A};
 use crate::{biome::Biome, Chunk, HeightMode};
 
 lazy_static! {
@@ -36,6 +39,34 @@ lazy_static! {
     };
 }
 
+/// The layout of chunk data written by a given DataVersion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ChunkLayout {
+    /// Everything under a `Level` compound, with `BlockStates` and `Palette`
+    /// directly in each section.
+    Pre18,
+    /// The 21w37a to 21w43a snapshots. Sections have the 1.18 `block_states`
+    /// container, but everything may still be under `Level`, biomes can still
+    /// be chunk-level, and the container keys can differ in case.
+    ///
+    /// Unverified: this is from the snapshot changelogs, and is only tested
+    /// against a 21w44a chunk rearranged by hand, not a chunk one of these
+    /// snapshots saved.
+    Snapshot18,
+    /// The layout released in 1.18, with no `Level` compound.
+    Post18,
+}
+
+/// Dispatch table from a chunk's DataVersion to the layout it was written
+/// with.
+pub(crate) fn chunk_layout(data_version: i32) -> ChunkLayout {
+    match data_version {
+        v if v < SNAPSHOT_21W37A => ChunkLayout::Pre18,
+        v if v < SNAPSHOT_21W44A => ChunkLayout::Snapshot18,
+        _ => ChunkLayout::Post18,
+    }
+}
+
 /// A Minecraft chunk.
 #[derive(Deserialize, Debug)]
 #[serde(untagged)]
@@ -44,6 +75,36 @@ pub enum JavaChunk {
     Pre18(pre18::JavaChunk),
 }
 
+impl JavaChunk {
+    /// Which light arrays are stored for each section that can hold light,
+    /// from the bottom up. This is one more section either side of the
+    /// [`y_range`][`Chunk::y_range`].
+    pub fn light_sections(&self) -> Vec<(i8, LightPresence)> {
+        match self {
+            JavaChunk::Post18(c) => c.light_sections(),
+            JavaChunk::Pre18(c) => c.light_sections(),
+        }
+    }
+
+    /// The block light at the given coordinates, see
+    /// [`SectionTower::block_light`].
+    pub fn block_light(&self, x: usize, y: isize, z: usize) -> Option<u8> {
+        match self {
+            JavaChunk::Post18(c) => c.block_light(x, y, z),
+            JavaChunk::Pre18(c) => c.block_light(x, y, z),
+        }
+    }
+
+    /// The sky light at the given coordinates, see
+    /// [`SectionTower::sky_light`].
+    pub fn sky_light(&self, x: usize, y: isize, z: usize) -> Option<u8> {
+        match self {
+            JavaChunk::Post18(c) => c.sky_light(x, y, z),
+            JavaChunk::Pre18(c) => c.sky_light(x, y, z),
+        }
+    }
+}
+
 // TODO: Find a better way to dispatch these methods.
 impl Chunk for JavaChunk {
     fn status(&self) -> String {
@@ -2,12 +2,15 @@ use std::cell::{Cell, RefCell};
 use std::convert::TryFrom;
 use std::ops::Range;
 
-use fastnbt::IntArray;
+use fastnbt::{ByteArray, IntArray};
 use serde::Deserialize;
 
 use crate::java::AIR;
-use crate::{biome::Biome, Block, Chunk, HeightMode};
-use crate::{bits_per_block, expand_heightmap, Heightmaps, PackedBits, SectionLike, SectionTower};
+use crate::{biome::Biome, Block, Chunk, HeightMode, LightPresence};
+use crate::{
+    bits_per_block, try_expand_heightmap, Heightmaps, PackedBits, SectionLike, SectionTower,
+    MAX_PALETTE_LEN,
+};
 
 /// A Minecraft chunk.
 #[derive(Deserialize, Debug)]
@@ -23,6 +26,13 @@ impl Chunk for JavaChunk {
     }
 
     fn surface_height(&self, x: usize, z: usize, mode: HeightMode) -> isize {
+        assert!(
+            x < 16 && z < 16,
+            "surface_height out of range: ({}, {})",
+            x,
+            z
+        );
+
         if self.level.lazy_heightmap.borrow().is_none() {
             self.recalculate_heightmap(mode);
         }
@@ -31,6 +41,10 @@ impl Chunk for JavaChunk {
     }
 
     fn biome(&self, x: usize, y: isize, z: usize) -> Option<Biome> {
+        if x >= 16 || z >= 16 {
+            return None;
+        }
+
         let biomes = self.level.biomes.as_ref()?;
 
         // After 1.15 Each biome in i32, biomes split into 4-wide cubes, so
@@ -43,12 +57,15 @@ impl Chunk for JavaChunk {
             V1_15 => {
                 // 1x1 columns stored z then x.
                 let i = z * 16 + x;
-                let biome = biomes[i];
+                let biome = *biomes.get(i)?;
                 Biome::try_from(biome).ok()
             }
             _ => {
                 // Assume latest
                 let range = self.y_range();
+                if range.is_empty() {
+                    return None;
+                }
                 let y_shifted = (y.clamp(range.start, range.end - 1) - range.start) as usize;
                 let i = (z / 4) * 4 + (x / 4) + (y_shifted / 4) * 16;
 
@@ -59,12 +76,18 @@ impl Chunk for JavaChunk {
     }
 
     fn block(&self, x: usize, y: isize, z: usize) -> Option<&Block> {
+        if x >= 16 || z >= 16 {
+            return None;
+        }
+
         let sec = self.level.sections.as_ref()?.get_section_for_y(y)?;
 
         // If a section is entirely air, then the block states are missing
         // entirely, presumably to save space.
         match &sec.block_states {
             None => Some(&AIR),
+            // A longer palette can't be indexed by the packed data.
+            Some(_) if sec.palette.len() > MAX_PALETTE_LEN => None,
             Some(blockstates) => {
                 let sec_y = (y - sec.y as isize * 16) as usize;
                 let pal_index = blockstates.state(x, sec_y, z, sec.palette.len());
@@ -111,6 +134,29 @@ pub struct Level {
 }
 
 impl JavaChunk {
+    /// Which light arrays are stored for each section that can hold light,
+    /// from the bottom up. This is one more section either side of the
+    /// [`y_range`][`Chunk::y_range`].
+    pub fn light_sections(&self) -> Vec<(i8, LightPresence)> {
+        self.level
+            .sections
+            .as_ref()
+            .map(|s| s.light_sections())
+            .unwrap_or_default()
+    }
+
+    /// The block light at the given coordinates, see
+    /// [`SectionTower::block_light`].
+    pub fn block_light(&self, x: usize, y: isize, z: usize) -> Option<u8> {
+        self.level.sections.as_ref()?.block_light(x, y, z)
+    }
+
+    /// The sky light at the given coordinates, see
+    /// [`SectionTower::sky_light`].
+    pub fn sky_light(&self, x: usize, y: isize, z: usize) -> Option<u8> {
+        self.level.sections.as_ref()?.sky_light(x, y, z)
+    }
+
     pub fn recalculate_heightmap(&self, mode: HeightMode) {
         // TODO: Find top section and start there, pointless checking 320 down
         // if its a 1.16 chunk.
@@ -119,15 +165,16 @@ impl JavaChunk {
 
         match mode {
             HeightMode::Trust => {
+                // If the heightmap is missing or we don't understand it, fall
+                // through to calculating it.
                 let updated = self
                     .level
                     .heightmaps
                     .as_ref()
                     .and_then(|hm| hm.motion_blocking.as_ref())
-                    .map(|hm| {
-                        // unwrap, if heightmaps exists, sections should... 🤞
-                        let y_min = self.level.sections.as_ref().unwrap().y_min();
-                        expand_heightmap(hm.as_slice(), y_min, self.data_version)
+                    .zip(self.level.sections.as_ref())
+                    .and_then(|(hm, sections)| {
+                        try_expand_heightmap(hm.as_slice(), sections.y_min(), self.data_version)
                     })
                     .map(|hm| map.copy_from_slice(hm.as_slice()))
                     .is_some();
@@ -136,6 +183,14 @@ impl JavaChunk {
                     self.level.lazy_heightmap.replace(Some(map));
                     return;
                 }
+
+                event!(
+                    DEBUG,
+                    x = self.level.x_pos,
+                    z = self.level.z_pos,
+                    data_version = self.data_version,
+                    "heightmap missing or unrecognised, calculating it instead"
+                );
             }
             HeightMode::Calculate => {} // fall through to calc mode
         }
@@ -179,6 +234,10 @@ pub struct Pre18Section {
 
     #[serde(default)]
     pub palette: Vec<Block>,
+
+    pub block_light: Option<ByteArray>,
+
+    pub sky_light: Option<ByteArray>,
 }
 
 impl SectionLike for Pre18Section {
@@ -189,6 +248,14 @@ impl SectionLike for Pre18Section {
     fn y(&self) -> i8 {
         self.y
     }
+
+    fn block_light(&self) -> Option<&[i8]> {
+        self.block_light.as_deref()
+    }
+
+    fn sky_light(&self) -> Option<&[i8]> {
+        self.sky_light.as_deref()
+    }
 }
 
 #[derive(Debug)]
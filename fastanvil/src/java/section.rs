@@ -1,10 +1,23 @@
+use fastnbt::ByteArray;
 use serde::Deserialize;
 
 use crate::{biome::Biome, BiomeData, Block, BlockData};
 
 pub trait SectionLike {
+    /// Whether the section has no blocks. These sections are only there to
+    /// hold light, and are not part of the chunk's block range.
     fn is_terminator(&self) -> bool;
     fn y(&self) -> i8;
+
+    /// The section's `BlockLight` array, if it has one.
+    fn block_light(&self) -> Option<&[i8]> {
+        None
+    }
+
+    /// The section's `SkyLight` array, if it has one.
+    fn sky_light(&self) -> Option<&[i8]> {
+        None
+    }
 }
 
 /// A vertical section of a chunk (ie a 16x16x16 block cube)
@@ -18,14 +31,29 @@ pub struct Section {
 
     #[serde(default)]
     pub biomes: BiomeData<Biome>,
+
+    #[serde(rename = "BlockLight")]
+    pub block_light: Option<ByteArray>,
+
+    #[serde(rename = "SkyLight")]
+    pub sky_light: Option<ByteArray>,
 }
 
 impl SectionLike for Section {
     fn is_terminator(&self) -> bool {
-        false
+        // Any stored block states have at least one palette entry.
+        self.block_states.palette().is_empty()
     }
 
     fn y(&self) -> i8 {
         self.y
     }
+
+    fn block_light(&self) -> Option<&[i8]> {
+        self.block_light.as_deref()
+    }
+
+    fn sky_light(&self) -> Option<&[i8]> {
+        self.sky_light.as_deref()
+    }
 }
@@ -4,6 +4,8 @@ use fastnbt::LongArray;
 use serde::Deserialize;
 use std::fmt::Debug;
 
+use crate::{bits::palette_bits, MAX_PALETTE_LEN};
+
 #[derive(Deserialize, Debug)]
 #[serde(transparent)]
 pub struct BlockData<T: Debug> {
@@ -21,6 +23,10 @@ impl<T: Debug> BlockData<T> {
         let state_index = (sec_y * 16 * 16) + z * 16 + x;
         self.inner.at(state_index, 4)
     }
+
+    pub fn palette(&self) -> &[T] {
+        &self.inner.palette
+    }
 }
 
 impl<T: Debug> BiomeData<T> {
@@ -38,7 +44,10 @@ impl<T: Debug> BiomeData<T> {
 
 #[derive(Deserialize, Debug)]
 struct DataInner<T: Debug> {
+    // Some 1.18 snapshots wrote these keys capitalised.
+    #[serde(alias = "Data")]
     data: Option<LongArray>,
+    #[serde(alias = "Palette")]
     palette: Vec<T>,
 }
 
@@ -50,11 +59,12 @@ impl<T: Debug> DataInner<T> {
 
         let data = self.data.as_ref()?;
 
+        if self.palette.len() > MAX_PALETTE_LEN {
+            return None;
+        }
+
         // TODO: Can potentially calculate this at deserialize time.
-        let bits = std::cmp::max(
-            (self.palette.len() as f64).log2().ceil() as usize,
-            min_bits_per_item,
-        );
+        let bits = palette_bits(self.palette.len(), min_bits_per_item);
 
         let values_per_64bits = 64 / bits;
 
@@ -65,7 +75,7 @@ impl<T: Debug> DataInner<T> {
         // Super important line: treat the i64 as an u64.
         // Bug 1: Kept i64 and the get_bits interprets as signed.
         // Bug 2: Went to usize, worked on 64bit platforms broke on 32 bit like WASM.
-        let long = data[long_index] as u64;
+        let long = *data.get(long_index)? as u64;
 
         let palette_index = long.get_bits(range);
 
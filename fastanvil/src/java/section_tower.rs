@@ -1,16 +1,26 @@
+use std::convert::TryFrom;
+use std::ops::Range;
+
 use serde::Deserialize;
 
-use crate::SectionLike;
+use crate::{light_at, LightPresence, SectionLike};
 
 /// SectionTower represents the set of sections that make up a Minecraft chunk.
 /// It has a custom deserialization in order to more efficiently lay out the
 /// sections for quick access.
+///
+/// Sections holding only light, one below and one above the sections with
+/// blocks, are kept for their light but are not part of the block range.
 #[derive(Debug)]
 pub struct SectionTower<S> {
     sections: Vec<S>,
     map: Vec<Option<usize>>,
     y_min: isize,
     y_max: isize,
+
+    // Every section by Y, including light-only ones, from `light_min`.
+    light_map: Vec<Option<usize>>,
+    light_min: isize,
 }
 
 impl<S> SectionTower<S> {
@@ -34,6 +44,83 @@ impl<S> SectionTower<S> {
     pub fn y_max(&self) -> isize {
         self.y_max
     }
+
+    /// The section with the given section Y, whether or not it has blocks.
+    fn light_section(&self, sec_y: isize) -> Option<&S> {
+        let index = usize::try_from(sec_y - self.light_min).ok()?;
+        self.sections.get((*self.light_map.get(index)?)?)
+    }
+
+    /// The range of section Ys that can hold light. This is one more section
+    /// either side of the sections with blocks.
+    fn light_range(&self) -> Range<isize> {
+        if self.y_min == self.y_max {
+            return 0..0;
+        }
+        (self.y_min >> 4) - 1..(self.y_max >> 4) + 1
+    }
+}
+
+impl<S: SectionLike> SectionTower<S> {
+    /// Which light arrays are stored for each section that can hold light,
+    /// from the bottom up. See [`LightPresence`].
+    pub fn light_sections(&self) -> Vec<(i8, LightPresence)> {
+        self.light_range()
+            .map(|sec_y| {
+                let presence = self
+                    .light_section(sec_y)
+                    .map(|sec| LightPresence {
+                        block_light: sec.block_light().is_some(),
+                        sky_light: sec.sky_light().is_some(),
+                    })
+                    .unwrap_or_default();
+
+                (sec_y as i8, presence)
+            })
+            .collect()
+    }
+
+    /// The block light at the given coordinates. Sections without stored block
+    /// light have none. None if the Y is outside of the sections that can
+    /// hold light.
+    pub fn block_light(&self, x: usize, y: isize, z: usize) -> Option<u8> {
+        let sec_y = y >> 4;
+        if x >= 16 || z >= 16 || !self.light_range().contains(&sec_y) {
+            return None;
+        }
+
+        match self.light_section(sec_y).and_then(|s| s.block_light()) {
+            Some(light) => light_at(light, x, (y & 15) as usize, z),
+            None => Some(0),
+        }
+    }
+
+    /// The sky light at the given coordinates. None if the Y is outside of the
+    /// sections that can hold light, or if no section has sky light, such as
+    /// in the nether.
+    ///
+    /// Like the game, a section without stored sky light takes the light from
+    /// the bottom of the nearest section above that has it, or full light if
+    /// there is none.
+    pub fn sky_light(&self, x: usize, y: isize, z: usize) -> Option<u8> {
+        let sec_y = y >> 4;
+        let range = self.light_range();
+        if x >= 16 || z >= 16 || !range.contains(&sec_y) {
+            return None;
+        }
+
+        if self.sections.iter().all(|s| s.sky_light().is_none()) {
+            return None;
+        }
+
+        if let Some(light) = self.light_section(sec_y).and_then(|s| s.sky_light()) {
+            return light_at(light, x, (y & 15) as usize, z);
+        }
+
+        (sec_y + 1..range.end)
+            .find_map(|above| self.light_section(above).and_then(|s| s.sky_light()))
+            .map_or(Some(15), |light| light_at(light, x, 0, z))
+    }
 }
 
 impl<'de, S: SectionLike + Deserialize<'de>> Deserialize<'de> for SectionTower<S> {
@@ -42,46 +129,34 @@ impl<'de, S: SectionLike + Deserialize<'de>> Deserialize<'de> for SectionTower<S
         D: serde::Deserializer<'de>,
     {
         let sections: Vec<S> = Deserialize::deserialize(deserializer)?;
-        if sections.is_empty() {
-            return Ok(Self {
-                sections,
-                map: vec![],
-                y_min: 0,
-                y_max: 0,
-            });
-        }
 
-        // We need to figure out how deep the world goes. Since 1.17 the depth
-        // of worlds can be customized. Each section in the chunk will have a
-        // 'y' value. We need to find the minimum value here, and that will tell
-        // us the minimum world y.
-        let lowest_section = sections
+        // Sections with no blocks, such as the 'null' section below the
+        // world before 1.18, or the light sections above and below it, aren't
+        // actually part of the world. Since 1.17 the depth of worlds can be
+        // customized, so the range comes from the sections with blocks.
+        let block_ys = sections
             .iter()
-            .min_by_key(|s| s.y())
-            .expect("checked no empty above");
-
-        // Sometimes the lowest section is a 'null' section. This isn't actually
-        // part of the world, it just indicates there no more sections below.
-        // You can tell if it's 'null terminated' by the palette and
-        // blockstates.
-        let null_term = lowest_section.is_terminator();
-
-        let min = if null_term {
-            lowest_section.y() as isize + 1
-        } else {
-            lowest_section.y() as isize
+            .filter(|s| !s.is_terminator())
+            .map(|s| s.y() as isize);
+
+        let (min, max) = match (block_ys.clone().min(), block_ys.max()) {
+            (Some(min), Some(max)) => (min, max),
+            _ => {
+                return Ok(Self {
+                    sections,
+                    map: vec![],
+                    y_min: 0,
+                    y_max: 0,
+                    light_map: vec![],
+                    light_min: 0,
+                })
+            }
         };
-        let max = sections
-            .iter()
-            .max_by_key(|s| s.y())
-            .map(|s| s.y())
-            .unwrap() as isize;
 
         let mut sparse_sections = vec![None; (1 + max - min) as usize];
 
         for (i, sec) in sections.iter().enumerate() {
-            // Don't bother adding the null section.
-            if sec.y() == lowest_section.y() && null_term {
+            if sec.is_terminator() {
                 continue;
             }
 
@@ -90,11 +165,28 @@ impl<'de, S: SectionLike + Deserialize<'de>> Deserialize<'de> for SectionTower<S
             sparse_sections[sec_index] = Some(i);
         }
 
+        // Light is kept for one section either side of the blocks. Any other
+        // sections are ignored.
+        let light_min = min - 1;
+        let mut light_map = vec![None; (3 + max - min) as usize];
+
+        for (i, sec) in sections.iter().enumerate() {
+            let index = sec.y() as isize - light_min;
+            if let Some(slot) = usize::try_from(index)
+                .ok()
+                .and_then(|index| light_map.get_mut(index))
+            {
+                *slot = Some(i);
+            }
+        }
+
         Ok(Self {
             sections,
             map: sparse_sections,
             y_min: 16 * min,
             y_max: 16 * (max + 1),
+            light_map,
+            light_min,
         })
     }
 }
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+
+use crate::{CCoord, LoaderError, LoaderResult};
+
+/// Some of the contents of a world's `level.dat`. This is the `Data` compound
+/// of the file, only the fields commonly needed by tools are captured.
+///
+/// ```no_run
+/// # use fastanvil::LevelDat;
+/// let data = std::fs::read("path/to/world/level.dat").unwrap();
+/// let level = LevelDat::from_bytes(&data).unwrap();
+///
+/// let border = level.world_border();
+/// println!("border is {} blocks wide", border.size);
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "LevelDatRaw")]
+pub struct LevelDat {
+    /// Missing for worlds last opened before 1.9.
+    pub data_version: Option<i32>,
+
+    pub level_name: String,
+
+    pub spawn_x: i32,
+    pub spawn_y: i32,
+    pub spawn_z: i32,
+
+    /// Game rules by name. The game stores every value as a string.
+    pub game_rules: HashMap<String, String>,
+
+    world_border: WorldBorder,
+}
+
+impl LevelDat {
+    /// Parse a `level.dat` file, which is GZip compressed.
+    pub fn from_bytes(data: &[u8]) -> LoaderResult<Self> {
+        let mut decoder = GzDecoder::new(data);
+        let mut buf = vec![];
+        decoder
+            .read_to_end(&mut buf)
+            .map_err(|e| LoaderError(format!("could not decompress level.dat: {}", e)))?;
+
+        let file: LevelDatFile = fastnbt::de::from_bytes(&buf)
+            .map_err(|e| LoaderError(format!("could not parse level.dat: {}", e)))?;
+
+        Ok(file.data)
+    }
+
+    /// The world border of the overworld.
+    pub fn world_border(&self) -> WorldBorder {
+        self.world_border
+    }
+
+    /// The chunk containing the world spawn.
+    pub fn spawn_chunk(&self) -> (CCoord, CCoord) {
+        (
+            CCoord(self.spawn_x.div_euclid(16) as isize),
+            CCoord(self.spawn_z.div_euclid(16) as isize),
+        )
+    }
+
+    /// The `spawnChunkRadius` game rule, added in 1.20.5. None for older
+    /// worlds, or if the rule is not a valid number.
+    pub fn spawn_chunk_radius(&self) -> Option<u32> {
+        self.game_rules.get("spawnChunkRadius")?.parse().ok()
+    }
+
+    /// The chunks within `radius` chunks of the spawn chunk, ie a square of
+    /// `2 * radius + 1` chunks a side centred on it, in row order.
+    ///
+    /// For 1.20.5 onwards the radius vanilla keeps loaded is the
+    /// [`spawn_chunk_radius`][`LevelDat::spawn_chunk_radius`], which defaults
+    /// to 2. Before that 23 by 23 chunks were kept loaded (radius 11), and the
+    /// central 19 by 19 (radius 9) were ticked.
+    pub fn spawn_chunks(&self, radius: u32) -> impl Iterator<Item = (CCoord, CCoord)> {
+        let (x, z) = self.spawn_chunk();
+        let r = radius as isize;
+
+        (z.0 - r..=z.0 + r)
+            .flat_map(move |cz| (x.0 - r..=x.0 + r).map(move |cx| (CCoord(cx), CCoord(cz))))
+    }
+}
+
+/// The world border, as stored in `level.dat`.
+///
+/// If the border is shrinking or growing, `size` is its size when the world
+/// was saved, and it is moving linearly towards `lerp_target`, reaching it in
+/// `lerp_time` milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldBorder {
+    /// The block X and Z of the centre of the border.
+    pub center: (f64, f64),
+
+    /// The width of the border in blocks. This is a diameter, the border
+    /// extends half of this either side of the centre.
+    pub size: f64,
+
+    /// The size the border is moving towards. Equal to `size` if it is not
+    /// moving.
+    pub lerp_target: f64,
+
+    /// Milliseconds until the border reaches `lerp_target`.
+    pub lerp_time: i64,
+
+    /// Distance outside the border a player can be before taking damage.
+    pub safe_zone: f64,
+
+    /// Damage per second, per block a player is past the safe zone.
+    pub damage_per_block: f64,
+
+    /// Distance from the border at which players are shown a warning.
+    pub warning_blocks: f64,
+
+    /// Seconds before a moving border reaches a player that they are shown a
+    /// warning.
+    pub warning_time: f64,
+}
+
+impl WorldBorder {
+    /// The largest possible border, and the default.
+    pub const MAX_SIZE: f64 = 59_999_968.0;
+
+    /// The lowest block X inside the border. Blocks are inside if any part of
+    /// them is, so this can be a fraction.
+    pub fn min_x(&self) -> f64 {
+        self.extent(self.center.0, -1.0)
+    }
+
+    pub fn max_x(&self) -> f64 {
+        self.extent(self.center.0, 1.0)
+    }
+
+    pub fn min_z(&self) -> f64 {
+        self.extent(self.center.1, -1.0)
+    }
+
+    pub fn max_z(&self) -> f64 {
+        self.extent(self.center.1, 1.0)
+    }
+
+    /// Whether the block at the given X and Z is inside the border. Like the
+    /// game, a block only partially inside the border counts as inside.
+    pub fn contains(&self, x: isize, z: isize) -> bool {
+        let (x, z) = (x as f64, z as f64);
+        x + 1.0 > self.min_x() && x < self.max_x() && z + 1.0 > self.min_z() && z < self.max_z()
+    }
+
+    /// Whether the border is growing or shrinking.
+    pub fn is_moving(&self) -> bool {
+        self.lerp_time > 0 && self.lerp_target != self.size
+    }
+
+    /// The size of the border after the world has run for the given number of
+    /// milliseconds.
+    pub fn size_after(&self, millis: i64) -> f64 {
+        if !self.is_moving() {
+            return self.size;
+        }
+
+        let progress = (millis as f64 / self.lerp_time as f64).clamp(0.0, 1.0);
+        self.size + (self.lerp_target - self.size) * progress
+    }
+
+    fn extent(&self, center: f64, direction: f64) -> f64 {
+        let max = Self::MAX_SIZE / 2.0;
+        (center + direction * self.size / 2.0).clamp(-max, max)
+    }
+}
+
+/// The vanilla border, used for any fields missing from `level.dat`.
+impl Default for WorldBorder {
+    fn default() -> Self {
+        Self {
+            center: (0.0, 0.0),
+            size: Self::MAX_SIZE,
+            lerp_target: Self::MAX_SIZE,
+            lerp_time: 0,
+            safe_zone: 5.0,
+            damage_per_block: 0.2,
+            warning_blocks: 5.0,
+            warning_time: 15.0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LevelDatFile {
+    #[serde(rename = "Data")]
+    data: LevelDat,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LevelDatRaw {
+    data_version: Option<i32>,
+    #[serde(default)]
+    level_name: String,
+    spawn_x: i32,
+    spawn_y: i32,
+    spawn_z: i32,
+    #[serde(default)]
+    game_rules: HashMap<String, String>,
+
+    border_center_x: Option<f64>,
+    border_center_z: Option<f64>,
+    border_size: Option<f64>,
+    border_size_lerp_target: Option<f64>,
+    border_size_lerp_time: Option<i64>,
+    border_safe_zone: Option<f64>,
+    border_damage_per_block: Option<f64>,
+    border_warning_blocks: Option<f64>,
+    border_warning_time: Option<f64>,
+}
+
+impl From<LevelDatRaw> for LevelDat {
+    fn from(raw: LevelDatRaw) -> Self {
+        let default = WorldBorder::default();
+        let size = raw.border_size.unwrap_or(default.size);
+
+        let world_border = WorldBorder {
+            center: (
+                raw.border_center_x.unwrap_or(default.center.0),
+                raw.border_center_z.unwrap_or(default.center.1),
+            ),
+            size,
+            lerp_target: raw.border_size_lerp_target.unwrap_or(size),
+            lerp_time: raw.border_size_lerp_time.unwrap_or(default.lerp_time),
+            safe_zone: raw.border_safe_zone.unwrap_or(default.safe_zone),
+            damage_per_block: raw
+                .border_damage_per_block
+                .unwrap_or(default.damage_per_block),
+            warning_blocks: raw.border_warning_blocks.unwrap_or(default.warning_blocks),
+            warning_time: raw.border_warning_time.unwrap_or(default.warning_time),
+        };
+
+        Self {
+            data_version: raw.data_version,
+            level_name: raw.level_name,
+            spawn_x: raw.spawn_x,
+            spawn_y: raw.spawn_y,
+            spawn_z: raw.spawn_z,
+            game_rules: raw.game_rules,
+            world_border,
+        }
+    }
+}
@@ -1,6 +1,15 @@
 //! For handling Minecraft's region format, Anvil.
 //!
 //! `anvil::Region` can be given a `Read` and `Seek` type eg a file in order to extract chunk data.
+//!
+//! With the `tracing` feature enabled, opening regions and reading,
+//! decompressing and parsing chunks are instrumented with `tracing` spans.
+//! Events are also emitted for anomalies such as chunks falling back from
+//! trusting their heightmap to calculating it.
+//!
+//! With the `zip` feature, regions can be loaded straight out of a zip archive
+//! with `ZipSource`. With the `tar` feature, the regions in a tar archive can be
+//! scanned with `for_each_tar_region`.
 
 use byteorder::{BigEndian, ReadBytesExt};
 use fastnbt::de::from_bytes;
@@ -17,22 +26,39 @@ pub const SECTOR_SIZE: usize = 4096;
 /// the size of the region file header.
 pub const HEADER_SIZE: usize = 2 * SECTOR_SIZE;
 
+#[macro_use]
+mod trace;
+
 pub mod biome;
+pub mod stats;
+pub mod support;
 pub mod tex;
 
 mod bits;
 mod dimension;
+mod entity;
 mod files;
 mod java;
+mod level;
+mod pipeline;
+mod region_writer;
 mod render;
 mod rendered_palette;
+mod source;
+mod world;
 
 pub use bits::*;
 pub use dimension::*;
+pub use entity::*;
 pub use files::*;
 pub use java::*;
+pub use level::*;
+pub use pipeline::*;
+pub use region_writer::*;
 pub use render::*;
 pub use rendered_palette::*;
+pub use source::*;
+pub use world::*;
 
 #[cfg(test)]
 mod test;
@@ -57,11 +83,12 @@ impl<S: Seek + Read, C: Chunk + DeserializeOwned> Region<C> for RegionBuffer<S>
 
         let data = self.load_chunk(loc.x, loc.z).ok()?;
 
+        span!(DEBUG, "chunk_parse", x = loc.x, z = loc.z, len = data.len());
         let res = from_bytes::<C>(&data);
 
-        match &res {
-            Ok(_) => {}
-            Err(e) => println!("{}", e),
+        #[cfg(feature = "tracing")]
+        if let Err(e) = &res {
+            event!(WARN, error = %e, "failed to parse chunk");
         }
 
         res.ok()
@@ -136,6 +163,20 @@ impl<S: Seek + Read> RegionBuffer<S> {
         })
     }
 
+    /// Return when the chunk at the (region-relative) location (x, z) was last
+    /// saved, in seconds since the Unix epoch, from the region header. Zero if
+    /// the chunk has never been saved.
+    pub fn chunk_timestamp(&self, x: usize, z: usize) -> Result<u32> {
+        if x >= 32 || z >= 32 {
+            return Err(Error::InvalidOffset(x, z));
+        }
+
+        let pos = SECTOR_SIZE + 4 * (x + z * 32);
+        let mut data = self.data.borrow_mut();
+        data.seek(SeekFrom::Start(pos as u64))?;
+        Ok(data.read_u32::<BigEndian>()?)
+    }
+
     /// Return the raw, uncompressed NBT data for a chunk at the
     /// (region-relative) Chunk location (x, z). Region's hold 32 by 32 chunks.
     ///
@@ -144,6 +185,7 @@ impl<S: Seek + Read> RegionBuffer<S> {
     ///
     /// [`stream::Parser`]: ../stream/struct.Parser.html
     pub fn load_chunk(&self, x: usize, z: usize) -> Result<Vec<u8>> {
+        span!(DEBUG, "chunk_load", x, z);
         let data = self.load_raw_chunk_at(x, z)?;
         decompress_chunk(&data)
     }
@@ -178,6 +220,12 @@ impl<S: Seek + Read> RegionBuffer<S> {
 
     /// Return the raw, compressed data for a chunk at ChunkLocation
     fn load_raw_chunk(&self, offset: &ChunkLocation, dest: &mut Vec<u8>) -> Result<()> {
+        span!(
+            DEBUG,
+            "chunk_read",
+            begin_sector = offset.begin_sector,
+            sector_count = offset.sector_count
+        );
         self.data.borrow_mut().seek(SeekFrom::Start(
             offset.begin_sector as u64 * SECTOR_SIZE as u64,
         ))?;
@@ -185,6 +233,21 @@ impl<S: Seek + Read> RegionBuffer<S> {
         dest.resize(5, 0);
         self.data.borrow_mut().read_exact(&mut dest[0..5])?;
         let metadata = ChunkMeta::new(&dest[..5])?;
+        event!(
+            TRACE,
+            compressed_len = metadata.compressed_len,
+            scheme = ?metadata.compression_scheme
+        );
+
+        #[cfg(feature = "tracing")]
+        if metadata.compressed_len as usize + 5 > offset.sector_count * SECTOR_SIZE {
+            event!(
+                WARN,
+                compressed_len = metadata.compressed_len,
+                sector_count = offset.sector_count,
+                "chunk is larger than its allocated sectors"
+            );
+        }
 
         dest.resize(5 + metadata.compressed_len as usize, 0u8);
 
@@ -209,6 +272,8 @@ impl<S: Seek + Read> RegionBuffer<S> {
 
 // Read Information Bytes of Minecraft Chunk and decompress it
 fn decompress_chunk(data: &[u8]) -> Result<Vec<u8>> {
+    span!(DEBUG, "chunk_decompress", compressed_len = data.len());
+
     // Metadata encodes the length in bytes and the compression type
     let meta = ChunkMeta::new(data).unwrap();
 
@@ -221,6 +286,7 @@ fn decompress_chunk(data: &[u8]) -> Result<Vec<u8>> {
     let mut outbuf = Vec::new();
     // read the whole Chunk
     decoder.read_to_end(&mut outbuf)?;
+    event!(TRACE, decompressed_len = outbuf.len());
     Ok(outbuf)
 }
 
@@ -231,6 +297,7 @@ pub enum Error {
     InvalidOffset(usize, usize),
     InvalidChunkMeta,
     ChunkNotFound,
+    ChunkTooLarge(usize, usize),
 }
 
 impl From<std::io::Error> for Error {
@@ -253,6 +320,10 @@ impl std::fmt::Display for Error {
                 f.write_str("compression scheme was not recognised for chunk")
             }
             Error::ChunkNotFound => f.write_str("chunk not found in region"),
+            Error::ChunkTooLarge(x, z) => f.write_fmt(format_args!(
+                "chunk too large for a region: x = {}, z = {}",
+                x, z
+            )),
         }
     }
 }
@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Seek};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+
+use crate::{decompress_chunk, RegionBuffer, Result};
+
+/// A chunk read from the region but not yet decompressed.
+struct Job {
+    seq: usize,
+    x: usize,
+    z: usize,
+    raw: Vec<u8>,
+}
+
+/// A decompressed chunk waiting to be handed to the consumer.
+struct Done {
+    seq: usize,
+    x: usize,
+    z: usize,
+    data: Result<Vec<u8>>,
+}
+
+impl<S: Seek + Read> RegionBuffer<S> {
+    /// Decompress every non-empty chunk in the region on `parallelism`
+    /// threads, holding at most `capacity` decompressed chunks that have not
+    /// yet been taken from the returned iterator. Workers wait for the
+    /// consumer once the limit is reached, so a slow consumer keeps memory use
+    /// bounded rather than letting decompressed chunks pile up.
+    ///
+    /// The compressed chunks are read up front on the calling thread. Chunks
+    /// are produced in whatever order they finish decompressing, call
+    /// [`ordered`][`ChunkPipeline::ordered`] for row order instead.
+    ///
+    /// ```no_run
+    /// # use fastanvil::RegionBuffer;
+    /// let file = std::fs::File::open("r.0.0.mca").unwrap();
+    /// let mut region = RegionBuffer::new(file);
+    ///
+    /// for chunk in region.pipelined_chunks(4, 16).unwrap() {
+    ///     let (x, z, data) = chunk.unwrap();
+    ///     println!("chunk {}, {} is {} bytes", x, z, data.len());
+    /// }
+    /// ```
+    pub fn pipelined_chunks(
+        &mut self,
+        parallelism: usize,
+        capacity: usize,
+    ) -> Result<ChunkPipeline> {
+        let mut jobs = Vec::new();
+
+        for z in 0..32 {
+            for x in 0..32 {
+                let loc = self.chunk_location(x, z)?;
+
+                // 0,0 chunk location means the chunk isn't present.
+                if loc.begin_sector != 0 && loc.sector_count != 0 {
+                    let mut raw = Vec::new();
+                    self.load_raw_chunk(&loc, &mut raw)?;
+                    jobs.push(Job {
+                        seq: jobs.len(),
+                        x,
+                        z,
+                        raw,
+                    });
+                }
+            }
+        }
+
+        Ok(ChunkPipeline {
+            parallelism: parallelism.max(1),
+            capacity: capacity.max(1),
+            ordered: false,
+            total: jobs.len(),
+            jobs: Some(jobs),
+            gate: Arc::new(Gate::default()),
+            running: None,
+            pending: BTreeMap::new(),
+            yielded: 0,
+        })
+    }
+}
+
+/// Iterator over the decompressed chunks of a region, decompressing them in
+/// the background. Items are `(x, z, data)` like
+/// [`for_each_chunk`][`RegionBuffer::for_each_chunk`]. Created by
+/// [`RegionBuffer::pipelined_chunks`].
+///
+/// The worker threads start on the first call to `next`, and stop once the
+/// pipeline is dropped.
+pub struct ChunkPipeline {
+    parallelism: usize,
+    capacity: usize,
+    ordered: bool,
+    total: usize,
+    jobs: Option<Vec<Job>>,
+    gate: Arc<Gate>,
+    running: Option<Running>,
+
+    // Chunks finished ahead of their turn, when ordered.
+    pending: BTreeMap<usize, Done>,
+    yielded: usize,
+}
+
+struct Running {
+    results: Receiver<Done>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ChunkPipeline {
+    /// Produce chunks in row order, all of `z = 0` by increasing `x`, then
+    /// `z = 1` and so on. Chunks that finish early wait their turn, and count
+    /// towards the capacity while they do.
+    pub fn ordered(mut self) -> Self {
+        self.ordered = true;
+        self
+    }
+
+    /// The most decompressed chunks held by the pipeline at any one time so
+    /// far. Never more than the capacity.
+    pub fn peak_buffered(&self) -> usize {
+        self.gate.state.lock().unwrap().peak
+    }
+
+    fn start(&mut self, jobs: Vec<Job>) -> Running {
+        let jobs = Arc::new(Mutex::new(jobs.into_iter()));
+        let (tx, results) = channel();
+
+        let workers = (0..self.parallelism)
+            .map(|_| {
+                let jobs = Arc::clone(&jobs);
+                let gate = Arc::clone(&self.gate);
+                let tx = tx.clone();
+                let (ordered, capacity) = (self.ordered, self.capacity);
+
+                std::thread::spawn(move || loop {
+                    // Take jobs in order, so that when ordered the next chunk
+                    // to be yielded is always being worked on.
+                    let job = match jobs.lock().unwrap().next() {
+                        Some(job) => job,
+                        None => return,
+                    };
+
+                    if !gate.acquire(job.seq, ordered, capacity) {
+                        return;
+                    }
+
+                    let done = Done {
+                        seq: job.seq,
+                        x: job.x,
+                        z: job.z,
+                        data: decompress_chunk(&job.raw),
+                    };
+
+                    if tx.send(done).is_err() {
+                        return;
+                    }
+                })
+            })
+            .collect();
+
+        Running { results, workers }
+    }
+
+    /// Wait for a worker to finish a chunk. None if they have all stopped.
+    fn recv(&mut self) -> Option<Done> {
+        let running = self.running.as_mut()?;
+        match running.results.recv() {
+            Ok(done) => Some(done),
+            Err(_) => {
+                // Every worker has exited but chunks are missing, so one of
+                // them panicked. Pass the panic on rather than quietly ending
+                // early.
+                let running = self.running.take()?;
+                for worker in running.workers {
+                    if let Err(panic) = worker.join() {
+                        std::panic::resume_unwind(panic);
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+impl Iterator for ChunkPipeline {
+    type Item = Result<(usize, usize, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.yielded == self.total {
+            return None;
+        }
+
+        if let Some(jobs) = self.jobs.take() {
+            self.running = Some(self.start(jobs));
+        }
+
+        let done = if self.ordered {
+            loop {
+                if let Some(done) = self.pending.remove(&self.yielded) {
+                    break done;
+                }
+                let done = self.recv()?;
+                self.pending.insert(done.seq, done);
+            }
+        } else {
+            self.recv()?
+        };
+
+        self.yielded += 1;
+        self.gate.release();
+
+        let Done { x, z, data, .. } = done;
+        Some(data.map(|data| (x, z, data)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.total - self.yielded;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for ChunkPipeline {}
+
+impl Drop for ChunkPipeline {
+    fn drop(&mut self) {
+        let mut state = self.gate.state.lock().unwrap();
+        state.cancelled = true;
+        self.gate.cond.notify_all();
+    }
+}
+
+/// Limits how many decompressed chunks exist before the consumer takes them.
+#[derive(Default)]
+struct Gate {
+    state: Mutex<GateState>,
+    cond: Condvar,
+}
+
+#[derive(Default)]
+struct GateState {
+    buffered: usize,
+    yielded: usize,
+    peak: usize,
+    cancelled: bool,
+}
+
+impl Gate {
+    /// Wait until the chunk `seq` can be decompressed. False if the pipeline
+    /// was dropped while waiting.
+    ///
+    /// When ordered, only the `capacity` chunks after the last one yielded are
+    /// allowed. Otherwise chunks that finish early could fill the capacity
+    /// while waiting for one that cannot start.
+    fn acquire(&self, seq: usize, ordered: bool, capacity: usize) -> bool {
+        let mut state = self.state.lock().unwrap();
+
+        loop {
+            if state.cancelled {
+                return false;
+            }
+
+            let allowed = if ordered {
+                seq < state.yielded + capacity
+            } else {
+                state.buffered < capacity
+            };
+
+            if allowed {
+                break;
+            }
+            state = self.cond.wait(state).unwrap();
+        }
+
+        state.buffered += 1;
+        state.peak = state.peak.max(state.buffered);
+        true
+    }
+
+    /// A chunk has been handed to the consumer.
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.buffered -= 1;
+        state.yielded += 1;
+        self.cond.notify_all();
+    }
+}
@@ -0,0 +1,101 @@
+use std::io::{Seek, SeekFrom, Write};
+
+use flate2::{write::ZlibEncoder, Compression};
+
+use crate::{CompressionScheme, Error, Result, HEADER_SIZE, SECTOR_SIZE};
+
+/// Writes a region file from chunks already serialized to NBT, eg with
+/// `fastnbt::to_bytes`. Chunks are compressed with zlib, as Minecraft does,
+/// and written one after the other. The header, with where each chunk is and
+/// when it was saved, is written by [`finish`][Self::finish].
+///
+/// Writing the same chunk twice leaves the first copy in the file unused.
+///
+/// ```no_run
+/// # use fastanvil::{RegionBuffer, RegionWriter};
+/// # fn main() -> fastanvil::Result<()> {
+/// let region = RegionBuffer::new(std::fs::File::open("r.0.0.mca")?);
+/// let mut writer = RegionWriter::new(std::fs::File::create("copy.mca")?)?;
+/// for z in 0..32 {
+///     for x in 0..32 {
+///         if let Ok(data) = region.load_chunk(x, z) {
+///             writer.write_chunk(x, z, &data, region.chunk_timestamp(x, z)?)?;
+///         }
+///     }
+/// }
+/// writer.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RegionWriter<W: Write + Seek> {
+    out: W,
+    locations: Vec<u32>,
+    timestamps: Vec<u32>,
+    next_sector: usize,
+    buf: Vec<u8>,
+}
+
+impl<W: Write + Seek> RegionWriter<W> {
+    /// Start a region, leaving room for the header.
+    pub fn new(mut out: W) -> Result<Self> {
+        out.seek(SeekFrom::Start(0))?;
+        out.write_all(&[0; HEADER_SIZE])?;
+
+        Ok(Self {
+            out,
+            locations: vec![0; 1024],
+            timestamps: vec![0; 1024],
+            next_sector: HEADER_SIZE / SECTOR_SIZE,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Compress and write the uncompressed NBT of the chunk at the
+    /// (region-relative) location (x, z), saved at `timestamp` seconds since
+    /// the Unix epoch.
+    pub fn write_chunk(&mut self, x: usize, z: usize, data: &[u8], timestamp: u32) -> Result<()> {
+        if x >= 32 || z >= 32 {
+            return Err(Error::InvalidOffset(x, z));
+        }
+
+        // The length includes the compression scheme byte.
+        self.buf.clear();
+        self.buf.extend_from_slice(&[0; 5]);
+        let mut encoder = ZlibEncoder::new(&mut self.buf, Compression::default());
+        encoder.write_all(data)?;
+        encoder.finish()?;
+        let len = self.buf.len() - 4;
+        self.buf[..4].copy_from_slice(&(len as u32).to_be_bytes());
+        self.buf[4] = CompressionScheme::Zlib as u8;
+
+        // Minecraft moves chunks that don't fit into a file of their own.
+        let sectors = self.buf.len().div_ceil(SECTOR_SIZE);
+        if sectors > u8::MAX as usize {
+            return Err(Error::ChunkTooLarge(x, z));
+        }
+        self.buf.resize(sectors * SECTOR_SIZE, 0);
+
+        self.out
+            .seek(SeekFrom::Start((self.next_sector * SECTOR_SIZE) as u64))?;
+        self.out.write_all(&self.buf)?;
+
+        let i = x + z * 32;
+        self.locations[i] = (self.next_sector as u32) << 8 | sectors as u32;
+        self.timestamps[i] = timestamp;
+        self.next_sector += sectors;
+        Ok(())
+    }
+
+    /// Write the header, returning the output.
+    pub fn finish(mut self) -> Result<W> {
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        for v in self.locations.iter().chain(&self.timestamps) {
+            header.extend_from_slice(&v.to_be_bytes());
+        }
+
+        self.out.seek(SeekFrom::Start(0))?;
+        self.out.write_all(&header)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
@@ -0,0 +1,204 @@
+use std::fs::{self, File};
+use std::io::{Read, Seek};
+use std::path::PathBuf;
+
+use crate::files::coords_from_region;
+use crate::{LoaderError, LoaderResult, RCoord};
+
+/// Provides the region files of a dimension, for use with
+/// [`RegionFileLoader::from_source`][`crate::RegionFileLoader::from_source`].
+///
+/// [`DirectorySource`] reads them from a directory on disk. With the `zip`
+/// feature `ZipSource` reads them straight out of a zip archive. Tar archives
+/// can only be read in order, so they are not a source, see
+/// `for_each_tar_region` with the `tar` feature instead.
+pub trait RegionSource {
+    type Reader: Read + Seek;
+
+    /// Open the region file for the given region. `Ok(None)` if the region
+    /// does not exist.
+    fn open(&self, x: RCoord, z: RCoord) -> LoaderResult<Option<Self::Reader>>;
+
+    /// List the regions that exist. Empty region files are skipped.
+    fn list(&self) -> LoaderResult<Vec<(RCoord, RCoord)>>;
+}
+
+/// The `.mca` region files in a directory, eg `world/region`.
+pub struct DirectorySource {
+    region_dir: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new(region_dir: PathBuf) -> Self {
+        Self { region_dir }
+    }
+}
+
+impl RegionSource for DirectorySource {
+    type Reader = File;
+
+    fn open(&self, x: RCoord, z: RCoord) -> LoaderResult<Option<File>> {
+        let path = self.region_dir.join(format!("r.{}.{}.mca", x.0, z.0));
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(_e) => {
+                event!(DEBUG, path = %path.display(), error = %_e, "could not open region file");
+                return Ok(None);
+            }
+        };
+        event!(TRACE, path = %path.display(), len = ?file.metadata().map(|m| m.len()).ok());
+
+        Ok(Some(file))
+    }
+
+    fn list(&self) -> LoaderResult<Vec<(RCoord, RCoord)>> {
+        let paths = fs::read_dir(&self.region_dir).map_err(|e| LoaderError(e.to_string()))?;
+
+        let paths = paths
+            .into_iter()
+            .filter_map(|path| path.ok())
+            .map(|path| path.path())
+            .filter(|path| path.is_file())
+            .filter(|path| {
+                let ext = path.extension();
+                ext.is_some() && ext.unwrap() == "mca"
+            })
+            .filter(|path| fs::metadata(path).unwrap().len() > 0)
+            .filter_map(|p| coords_from_region(&p))
+            .collect();
+
+        Ok(paths)
+    }
+}
+
+#[cfg(feature = "zip")]
+pub use self::zip_source::ZipSource;
+
+#[cfg(feature = "zip")]
+mod zip_source {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::io::{Cursor, Read, Seek};
+    use std::path::{Path, PathBuf};
+
+    use zip::result::ZipError;
+    use zip::ZipArchive;
+
+    use crate::files::coords_from_region;
+    use crate::{LoaderError, LoaderResult, RCoord, RegionSource};
+
+    /// The region files in a directory of a zip archive, eg
+    /// `world/region` in a zipped backup. Requires the `zip` feature.
+    ///
+    /// Each region is decompressed into memory when opened.
+    ///
+    /// ```no_run
+    /// # use fastanvil::{JavaChunk, RegionFileLoader, ZipSource};
+    /// let file = std::fs::File::open("backup.zip").unwrap();
+    /// let source = ZipSource::new(file, "world/region").unwrap();
+    /// let loader = RegionFileLoader::<JavaChunk, _>::from_source(source);
+    /// ```
+    pub struct ZipSource<R: Read + Seek> {
+        archive: RefCell<ZipArchive<R>>,
+        regions: HashMap<(RCoord, RCoord), usize>,
+    }
+
+    impl<R: Read + Seek> ZipSource<R> {
+        /// Open a zip archive, finding the region files in `region_dir` within
+        /// it.
+        pub fn new(reader: R, region_dir: impl Into<PathBuf>) -> LoaderResult<Self> {
+            let region_dir = region_dir.into();
+            let mut archive = ZipArchive::new(reader).map_err(zip_error)?;
+            let mut regions = HashMap::new();
+
+            for i in 0..archive.len() {
+                let entry = archive.by_index_raw(i).map_err(zip_error)?;
+                let path = Path::new(entry.name());
+
+                if entry.is_file()
+                    && entry.size() > 0
+                    && path.parent() == Some(&region_dir)
+                    && path.extension().is_some_and(|ext| ext == "mca")
+                {
+                    if let Some(coords) = coords_from_region(path) {
+                        regions.insert(coords, i);
+                    }
+                }
+            }
+
+            Ok(Self {
+                archive: RefCell::new(archive),
+                regions,
+            })
+        }
+    }
+
+    impl<R: Read + Seek> RegionSource for ZipSource<R> {
+        type Reader = Cursor<Vec<u8>>;
+
+        fn open(&self, x: RCoord, z: RCoord) -> LoaderResult<Option<Self::Reader>> {
+            let index = match self.regions.get(&(x, z)) {
+                Some(index) => *index,
+                None => return Ok(None),
+            };
+
+            let mut archive = self.archive.borrow_mut();
+            let mut entry = archive.by_index(index).map_err(zip_error)?;
+            let mut data = Vec::with_capacity(entry.size() as usize);
+            entry
+                .read_to_end(&mut data)
+                .map_err(|e| LoaderError(e.to_string()))?;
+
+            Ok(Some(Cursor::new(data)))
+        }
+
+        fn list(&self) -> LoaderResult<Vec<(RCoord, RCoord)>> {
+            let mut regions: Vec<_> = self.regions.keys().copied().collect();
+            regions.sort();
+            Ok(regions)
+        }
+    }
+
+    fn zip_error(e: ZipError) -> LoaderError {
+        LoaderError(format!("could not read zip archive: {}", e))
+    }
+}
+
+/// Call `f` with each non-empty region file in `region_dir` of a tar archive,
+/// in the order they are stored. Requires the `tar` feature.
+///
+/// Tar archives have no index, so regions cannot be opened individually like
+/// a [`RegionSource`]. Each region is read into memory in turn, and can be
+/// scanned with [`RegionBuffer::for_each_chunk`][`crate::RegionBuffer::for_each_chunk`]
+/// or [`RegionBuffer::pipelined_chunks`][`crate::RegionBuffer::pipelined_chunks`].
+/// For a `.tar.gz`, wrap the reader in a `flate2::read::GzDecoder`.
+#[cfg(feature = "tar")]
+pub fn for_each_tar_region<R: Read>(
+    reader: R,
+    region_dir: impl AsRef<std::path::Path>,
+    mut f: impl FnMut(RCoord, RCoord, crate::RegionBuffer<std::io::Cursor<Vec<u8>>>),
+) -> LoaderResult<()> {
+    let tar_error = |e: std::io::Error| LoaderError(format!("could not read tar archive: {}", e));
+    let region_dir = region_dir.as_ref();
+    let mut archive = tar::Archive::new(reader);
+
+    for entry in archive.entries().map_err(tar_error)? {
+        let mut entry = entry.map_err(tar_error)?;
+        if !entry.header().entry_type().is_file() || entry.size() == 0 {
+            continue;
+        }
+
+        let path = entry.path().map_err(tar_error)?.into_owned();
+        if path.parent() != Some(region_dir) || path.extension().is_none_or(|ext| ext != "mca") {
+            continue;
+        }
+
+        if let Some((x, z)) = coords_from_region(&path) {
+            let mut data = Vec::with_capacity(entry.size() as usize);
+            entry.read_to_end(&mut data).map_err(tar_error)?;
+            f(x, z, crate::RegionBuffer::new(std::io::Cursor::new(data)));
+        }
+    }
+
+    Ok(())
+}
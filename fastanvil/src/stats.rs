@@ -0,0 +1,418 @@
+//! A cache of statistics about each chunk of a dimension, so that tools
+//! scanning whole worlds only need to look at the chunks that changed since
+//! their last scan.
+//!
+//! ```no_run
+//! use fastanvil::{stats::Cache, DimensionId, JavaChunk, WorldMeta};
+//!
+//! let world = WorldMeta::discover("path/to/world").unwrap();
+//! let loader = world.loader::<JavaChunk>(&DimensionId::Overworld).unwrap();
+//!
+//! let mut cache = Cache::load("stats.nbt").unwrap_or_default();
+//! let update = cache.update_from(&loader).unwrap();
+//! println!("recomputed {} chunks", update.recomputed.len());
+//! println!("{} diamond ore", cache.block_count("minecraft:diamond_ore"));
+//! cache.save("stats.nbt").unwrap();
+//! ```
+//!
+//! The cache is saved as GZip compressed NBT. Block names are stored once, in
+//! a palette, with each chunk holding the palette indices and counts of its
+//! blocks. The file records its [`SCHEMA_VERSION`], and a cache saved with
+//! any other version loads as empty, so the next update rebuilds it.
+
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CCoord, Chunk, JavaChunk, LoaderError, LoaderResult, RegionBuffer, RegionFileLoader,
+    RegionSource,
+};
+
+/// The version of the cache format, bumped whenever the format or what is
+/// computed for each chunk changes.
+pub const SCHEMA_VERSION: i32 = 1;
+
+/// What is cached about a chunk.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ChunkStats {
+    /// When the chunk was last saved, in seconds since the Unix epoch, from
+    /// the region header.
+    pub timestamp: u32,
+
+    /// The [`content_hash`][`fastnbt::Value::content_hash`] of the chunk NBT.
+    pub hash: u64,
+
+    /// How long players have spent in the chunk, in ticks.
+    pub inhabited_time: i64,
+
+    /// How many of each block the chunk has, by block name. Sections missing
+    /// from the chunk count as air.
+    pub blocks: HashMap<String, u32>,
+}
+
+impl ChunkStats {
+    /// Compute the statistics of a chunk from its uncompressed NBT.
+    pub fn from_bytes(data: &[u8], timestamp: u32) -> LoaderResult<Self> {
+        let value: fastnbt::Value = fastnbt::de::from_bytes(data)
+            .map_err(|e| LoaderError(format!("could not parse chunk: {}", e)))?;
+        Self::compute(&value, timestamp)
+    }
+
+    /// Compute the statistics from the already parsed chunk, so the hash and
+    /// the blocks come from the same parse.
+    fn compute(value: &fastnbt::Value, timestamp: u32) -> LoaderResult<Self> {
+        let chunk: JavaChunk = fastnbt::from_value(value)
+            .map_err(|e| LoaderError(format!("could not parse chunk: {}", e)))?;
+
+        let mut blocks = HashMap::new();
+        for y in chunk.y_range() {
+            for z in 0..16 {
+                for x in 0..16 {
+                    let name = chunk.block(x, y, z).map_or("minecraft:air", |b| b.name());
+                    match blocks.get_mut(name) {
+                        Some(count) => *count += 1,
+                        None => {
+                            blocks.insert(name.to_owned(), 1);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Chunks before 1.18 keep everything under `Level`.
+        let level = value.get("Level").unwrap_or(value);
+        let inhabited_time = level
+            .get("InhabitedTime")
+            .and_then(fastnbt::Value::as_i64)
+            .unwrap_or(0);
+
+        Ok(Self {
+            timestamp,
+            hash: value.content_hash(),
+            inhabited_time,
+            blocks,
+        })
+    }
+
+    /// How many of the named block the chunk has.
+    pub fn block_count(&self, name: &str) -> u32 {
+        self.blocks.get(name).copied().unwrap_or(0)
+    }
+}
+
+/// What changed in an [`update`][Cache::update_from].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Update {
+    /// Chunks that were new or had changed, so had their statistics computed.
+    pub recomputed: Vec<(CCoord, CCoord)>,
+
+    /// Chunks saved since the last update but with the same contents, which
+    /// only had their timestamp updated.
+    pub touched: usize,
+
+    /// Chunks with the same timestamp, assumed unchanged without reading them.
+    pub unchanged: usize,
+
+    /// Chunks no longer in the dimension, removed from the cache.
+    pub removed: usize,
+
+    /// Chunks that could not be read or parsed. These are left out of the
+    /// cache, so they are tried again on the next update.
+    pub failed: Vec<(CCoord, CCoord)>,
+}
+
+/// Statistics for every chunk of a dimension. See the [module
+/// documentation][self].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Cache {
+    chunks: HashMap<(CCoord, CCoord), ChunkStats>,
+}
+
+impl Cache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a cache saved with [`save`][Self::save]. A cache saved with a
+    /// different [`SCHEMA_VERSION`] loads as empty.
+    pub fn load(path: impl AsRef<Path>) -> LoaderResult<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .map_err(|e| LoaderError(format!("could not read {}: {}", path.display(), e)))?;
+        Self::from_bytes(&data)
+    }
+
+    /// Save the cache to a file, replacing it if it exists.
+    pub fn save(&self, path: impl AsRef<Path>) -> LoaderResult<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_bytes()?)
+            .map_err(|e| LoaderError(format!("could not write {}: {}", path.display(), e)))
+    }
+
+    /// Parse a cache from the contents of a file written by
+    /// [`save`][Self::save].
+    pub fn from_bytes(data: &[u8]) -> LoaderResult<Self> {
+        let mut buf = vec![];
+        GzDecoder::new(data)
+            .read_to_end(&mut buf)
+            .map_err(|e| LoaderError(format!("could not decompress stats cache: {}", e)))?;
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Version {
+            schema_version: i32,
+        }
+
+        let parse_error = |e| LoaderError(format!("could not parse stats cache: {}", e));
+        let version: Version = fastnbt::de::from_bytes(&buf).map_err(parse_error)?;
+        if version.schema_version != SCHEMA_VERSION {
+            return Ok(Self::new());
+        }
+
+        let file: CacheFile = fastnbt::de::from_bytes(&buf).map_err(parse_error)?;
+        let palette = file.blocks;
+        let mut chunks = HashMap::with_capacity(file.chunks.len());
+        for entry in file.chunks {
+            if entry.block_ids.len() != entry.block_counts.len() {
+                return Err(LoaderError(format!(
+                    "stats cache has mismatched block counts for chunk {}, {}",
+                    entry.x, entry.z
+                )));
+            }
+
+            let mut blocks = HashMap::with_capacity(entry.block_ids.len());
+            for (&id, &count) in entry.block_ids.iter().zip(entry.block_counts.iter()) {
+                let name = usize::try_from(id)
+                    .ok()
+                    .and_then(|id| palette.get(id))
+                    .ok_or_else(|| {
+                        LoaderError(format!("stats cache has unknown block id {}", id))
+                    })?;
+                blocks.insert(name.clone(), count as u32);
+            }
+
+            let stats = ChunkStats {
+                timestamp: entry.timestamp as u32,
+                hash: entry.hash as u64,
+                inhabited_time: entry.inhabited_time,
+                blocks,
+            };
+            chunks.insert((CCoord(entry.x as isize), CCoord(entry.z as isize)), stats);
+        }
+
+        Ok(Self { chunks })
+    }
+
+    /// The cache as it is saved by [`save`][Self::save].
+    pub fn to_bytes(&self) -> LoaderResult<Vec<u8>> {
+        let mut palette: HashMap<&str, i32> = HashMap::new();
+        let mut blocks = vec![];
+        let mut coords: Vec<_> = self.chunks.keys().collect();
+        coords.sort();
+
+        let mut entries = Vec::with_capacity(coords.len());
+        for &(x, z) in coords {
+            let stats = &self.chunks[&(x, z)];
+            let mut counts: Vec<_> = stats.blocks.iter().collect();
+            counts.sort();
+
+            let mut block_ids = Vec::with_capacity(counts.len());
+            let mut block_counts = Vec::with_capacity(counts.len());
+            for (name, &count) in counts {
+                let id = *palette.entry(name).or_insert_with(|| {
+                    blocks.push(name.clone());
+                    blocks.len() as i32 - 1
+                });
+                block_ids.push(id);
+                block_counts.push(count as i32);
+            }
+
+            entries.push(ChunkEntry {
+                x: x.0 as i32,
+                z: z.0 as i32,
+                timestamp: stats.timestamp as i64,
+                hash: stats.hash as i64,
+                inhabited_time: stats.inhabited_time,
+                block_ids: fastnbt::IntArray::new(block_ids),
+                block_counts: fastnbt::IntArray::new(block_counts),
+            });
+        }
+
+        let file = CacheFile {
+            schema_version: SCHEMA_VERSION,
+            blocks,
+            chunks: entries,
+        };
+        let nbt = fastnbt::to_bytes(&file)
+            .map_err(|e| LoaderError(format!("could not serialize stats cache: {}", e)))?;
+
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder
+            .write_all(&nbt)
+            .and_then(|_| encoder.finish())
+            .map_err(|e| LoaderError(format!("could not compress stats cache: {}", e)))
+    }
+
+    /// Bring the cache up to date with the chunks of a dimension.
+    ///
+    /// Chunks whose timestamp in the region header is the same as when they
+    /// were cached are assumed unchanged, without reading them. Others are
+    /// read and hashed, and only recomputed if their contents changed.
+    /// Timestamps only have a resolution of a second, so a chunk saved twice
+    /// in the same second as it was cached may be missed.
+    pub fn update_from<S: RegionSource>(
+        &mut self,
+        loader: &RegionFileLoader<JavaChunk, S>,
+    ) -> LoaderResult<Update> {
+        let mut update = Update::default();
+        let mut seen = HashSet::with_capacity(self.chunks.len());
+
+        for (rx, rz) in loader.source().list()? {
+            let region = match loader.source().open(rx, rz)? {
+                Some(reader) => RegionBuffer::new(reader),
+                None => continue,
+            };
+
+            for z in 0..32 {
+                for x in 0..32 {
+                    let loc = region
+                        .chunk_location(x, z)
+                        .map_err(|e| LoaderError(e.to_string()))?;
+                    if loc.begin_sector == 0 || loc.sector_count == 0 {
+                        continue;
+                    }
+
+                    let key = (
+                        CCoord(rx.0 * 32 + x as isize),
+                        CCoord(rz.0 * 32 + z as isize),
+                    );
+                    let timestamp = region
+                        .chunk_timestamp(x, z)
+                        .map_err(|e| LoaderError(e.to_string()))?;
+                    seen.insert(key);
+
+                    match self.update_chunk(&region, key, x, z, timestamp) {
+                        Some(Changed::No) => update.unchanged += 1,
+                        Some(Changed::Timestamp) => update.touched += 1,
+                        Some(Changed::Contents) => update.recomputed.push(key),
+                        None => {
+                            self.chunks.remove(&key);
+                            update.failed.push(key);
+                        }
+                    }
+                }
+            }
+        }
+
+        let before = self.chunks.len();
+        self.chunks.retain(|key, _| seen.contains(key));
+        update.removed = before - self.chunks.len();
+
+        Ok(update)
+    }
+
+    /// Update the cached chunk at `key` from its region. None if the chunk
+    /// could not be read.
+    fn update_chunk<R: Read + std::io::Seek>(
+        &mut self,
+        region: &RegionBuffer<R>,
+        key: (CCoord, CCoord),
+        x: usize,
+        z: usize,
+        timestamp: u32,
+    ) -> Option<Changed> {
+        if let Some(stats) = self.chunks.get(&key) {
+            if stats.timestamp == timestamp {
+                return Some(Changed::No);
+            }
+        }
+
+        let data = region.load_chunk(x, z).ok()?;
+        let value: fastnbt::Value = fastnbt::de::from_bytes(&data).ok()?;
+
+        if let Some(stats) = self.chunks.get_mut(&key) {
+            if stats.hash == value.content_hash() {
+                stats.timestamp = timestamp;
+                return Some(Changed::Timestamp);
+            }
+        }
+
+        let stats = ChunkStats::compute(&value, timestamp).ok()?;
+        self.chunks.insert(key, stats);
+        Some(Changed::Contents)
+    }
+
+    /// The statistics of the chunk at the given chunk coordinates.
+    pub fn chunk(&self, x: CCoord, z: CCoord) -> Option<&ChunkStats> {
+        self.chunks.get(&(x, z))
+    }
+
+    /// Every cached chunk, in no particular order.
+    pub fn chunks(&self) -> impl Iterator<Item = ((CCoord, CCoord), &ChunkStats)> {
+        self.chunks.iter().map(|(&k, v)| (k, v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// How many of the named block there are across every cached chunk.
+    pub fn block_count(&self, name: &str) -> u64 {
+        self.chunks
+            .values()
+            .map(|stats| stats.block_count(name) as u64)
+            .sum()
+    }
+
+    /// How many of each block there are across every cached chunk.
+    pub fn block_totals(&self) -> HashMap<&str, u64> {
+        let mut totals = HashMap::new();
+        for stats in self.chunks.values() {
+            for (name, &count) in &stats.blocks {
+                *totals.entry(name.as_str()).or_insert(0) += count as u64;
+            }
+        }
+        totals
+    }
+
+    /// The total time players have spent in the cached chunks, in ticks.
+    pub fn inhabited_time(&self) -> i64 {
+        self.chunks.values().map(|stats| stats.inhabited_time).sum()
+    }
+}
+
+enum Changed {
+    No,
+    Timestamp,
+    Contents,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CacheFile {
+    schema_version: i32,
+    /// The palette of block names that chunks refer to by index.
+    blocks: Vec<String>,
+    chunks: Vec<ChunkEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ChunkEntry {
+    x: i32,
+    z: i32,
+    timestamp: i64,
+    hash: i64,
+    inhabited_time: i64,
+    block_ids: fastnbt::IntArray,
+    block_counts: fastnbt::IntArray,
+}
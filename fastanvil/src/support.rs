@@ -0,0 +1,192 @@
+//! Which features of this crate work for chunks of a given DataVersion, so
+//! that tools can tell users when a world is too old or too new.
+//!
+//! ```
+//! use fastanvil::support::{check, Support};
+//!
+//! let report = check(1343); // 1.12.2
+//! assert_eq!(Support::Unsupported, report.blocks);
+//! ```
+//!
+//! The DataVersion constants here are the ones the chunk parsers dispatch on,
+//! so the report always matches what parsing actually does.
+
+use std::fmt::Display;
+
+use crate::java::{chunk_layout, ChunkLayout};
+
+/// 17w47a, the first snapshot of the 1.13 "flattening". Chunks before this
+/// store numeric block IDs, which are not supported.
+pub const FLATTENING: i32 = 1451;
+
+/// 19w36a, from when biomes are stored in 4x4x4 cells rather than per column.
+pub const BIOMES_3D: i32 = 2203;
+
+/// 1.16.5, the last release before the 1.17 snapshots.
+pub const V1_16_5: i32 = 2586;
+
+/// 20w45a, from when entities are stored in their own region files rather
+/// than in the chunk.
+pub const ENTITIES_SPLIT: i32 = 2681;
+
+pub const V1_17_0: i32 = 2724;
+pub const V1_17_1: i32 = 2730;
+
+/// The first 1.18 snapshot with the new section layout.
+pub const SNAPSHOT_21W37A: i32 = 2834;
+
+/// The first 1.18 snapshot with the layout 1.18 released with.
+pub const SNAPSHOT_21W44A: i32 = 2845;
+
+/// The newest DataVersion this crate has been tested against, 1.20.4. Newer
+/// worlds will likely work, but are reported as degraded.
+pub const NEWEST_KNOWN: i32 = 3700;
+
+// The dispatch code relies on these being in order.
+const _: () = assert!(
+    FLATTENING < BIOMES_3D
+        && BIOMES_3D < V1_16_5
+        && V1_16_5 < ENTITIES_SPLIT
+        && ENTITIES_SPLIT < V1_17_0
+        && V1_17_0 < V1_17_1
+        && V1_17_1 < SNAPSHOT_21W37A
+        && SNAPSHOT_21W37A < SNAPSHOT_21W44A
+        && SNAPSHOT_21W44A < NEWEST_KNOWN
+);
+
+/// How well a capability works for a DataVersion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Support {
+    Full,
+    /// Works, but with a caveat.
+    Degraded(Reason),
+    Unsupported,
+}
+
+/// Why a capability is [`Support::Degraded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reason {
+    /// Biomes are stored per column, so are the same at every height.
+    ColumnBiomes,
+
+    /// The heightmap format of this snapshot is not known. Stored heightmaps
+    /// are read as 1.16 ones, which may be wrong if the world extends below
+    /// zero, or are calculated from the blocks if they do not fit.
+    SnapshotHeightmaps,
+
+    /// The chunk layout of this snapshot is only known from its changelog. No
+    /// chunk saved by the snapshot has been tested, so blocks may be misread.
+    UnverifiedLayout,
+
+    /// Entities are stored inside the chunk, which the chunk types do not
+    /// expose. They need deserializing from the chunk NBT directly.
+    EntitiesInChunk,
+
+    /// The DataVersion is newer than [`NEWEST_KNOWN`].
+    NewerThanKnown,
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Reason::ColumnBiomes => "biomes do not vary with height",
+            Reason::SnapshotHeightmaps => "heightmap format of this snapshot is not known",
+            Reason::UnverifiedLayout => "chunk layout of this snapshot is untested",
+            Reason::EntitiesInChunk => "entities are stored in chunks, not entity regions",
+            Reason::NewerThanKnown => "newer than any version this was tested with",
+        })
+    }
+}
+
+/// What works for chunks of a particular DataVersion. See [`check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SupportReport {
+    pub data_version: i32,
+
+    /// Reading blocks with [`Chunk::block`][`crate::Chunk::block`].
+    pub blocks: Support,
+
+    /// Reading biomes with [`Chunk::biome`][`crate::Chunk::biome`].
+    pub biomes: Support,
+
+    /// Using stored heightmaps for
+    /// [`Chunk::surface_height`][`crate::Chunk::surface_height`].
+    pub heightmaps: Support,
+
+    /// Reading entities with [`Entity`][`crate::Entity`] from the entity
+    /// region files.
+    pub entities: Support,
+
+    /// Writing modified chunks back. Not supported by this crate yet.
+    pub write_back: Support,
+}
+
+/// Report what works for chunks of the given DataVersion.
+pub fn check(data_version: i32) -> SupportReport {
+    let v = data_version;
+
+    if v < FLATTENING {
+        return SupportReport {
+            data_version,
+            blocks: Support::Unsupported,
+            biomes: Support::Unsupported,
+            heightmaps: Support::Unsupported,
+            entities: Support::Unsupported,
+            write_back: Support::Unsupported,
+        };
+    }
+
+    let newer = |support: Support| match support {
+        Support::Full if v > NEWEST_KNOWN => Support::Degraded(Reason::NewerThanKnown),
+        s => s,
+    };
+
+    // Every layout has a parser, but only some have been checked against
+    // chunks the game saved.
+    let blocks = match chunk_layout(v) {
+        ChunkLayout::Pre18 | ChunkLayout::Post18 => Support::Full,
+        ChunkLayout::Snapshot18 => Support::Degraded(Reason::UnverifiedLayout),
+    };
+
+    let biomes = if v < BIOMES_3D {
+        Support::Degraded(Reason::ColumnBiomes)
+    } else {
+        Support::Full
+    };
+
+    let heightmaps = if heightmap_understood(v) {
+        Support::Full
+    } else {
+        Support::Degraded(Reason::SnapshotHeightmaps)
+    };
+
+    let entities = if v < ENTITIES_SPLIT {
+        Support::Degraded(Reason::EntitiesInChunk)
+    } else {
+        Support::Full
+    };
+
+    SupportReport {
+        data_version,
+        blocks: newer(blocks),
+        biomes: newer(biomes),
+        heightmaps: newer(heightmaps),
+        entities: newer(entities),
+        write_back: Support::Unsupported,
+    }
+}
+
+/// Whether [`try_expand_heightmap`][`crate::try_expand_heightmap`] understands
+/// the heightmaps of a DataVersion. Releases up to 1.16.5 store unshifted
+/// heights, and 1.17 and 1.18 onwards store heights offset by the bottom of
+/// the world. The snapshots between changed the format without a release, and
+/// are not understood.
+pub(crate) fn heightmap_understood(data_version: i32) -> bool {
+    data_version <= V1_16_5 || heightmap_is_offset(data_version)
+}
+
+/// Whether heightmaps of a DataVersion store heights offset by the bottom of
+/// the world.
+pub(crate) fn heightmap_is_offset(data_version: i32) -> bool {
+    matches!(data_version, V1_17_0 | V1_17_1 | SNAPSHOT_21W37A..)
+}
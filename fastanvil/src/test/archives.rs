@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::{RCoord, RegionBuffer, RegionFileLoader, RegionSource};
+
+type Scan = BTreeMap<(isize, isize, usize, usize), Vec<u8>>;
+
+// Zip and tar.gz archives of the `datapack-dimension` world, containing the
+// `datapack-dimension` directory itself.
+fn worlds() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/worlds")
+}
+
+fn scan_region<S: std::io::Read + std::io::Seek>(
+    scan: &mut Scan,
+    x: RCoord,
+    z: RCoord,
+    mut region: RegionBuffer<S>,
+) {
+    region
+        .for_each_chunk(|cx, cz, data| {
+            scan.insert((x.0, z.0, cx, cz), data.clone());
+        })
+        .unwrap();
+}
+
+fn scan_source<S: RegionSource>(source: &S) -> Scan {
+    let mut scan = Scan::new();
+    for (x, z) in source.list().unwrap() {
+        let reader = source.open(x, z).unwrap().unwrap();
+        scan_region(&mut scan, x, z, RegionBuffer::new(reader));
+    }
+    scan
+}
+
+fn scan_dir(region_dir: &str) -> Scan {
+    let source = crate::DirectorySource::new(worlds().join(region_dir));
+    let scan = scan_source(&source);
+    assert!(!scan.is_empty());
+    scan
+}
+
+#[cfg(feature = "zip")]
+mod zip {
+    use std::fs::File;
+
+    use super::*;
+    use crate::{CCoord, Chunk, JavaChunk, RegionLoader, ZipSource};
+
+    fn zip_source(region_dir: &str) -> ZipSource<File> {
+        let file = File::open(worlds().join("datapack-dimension.zip")).unwrap();
+        ZipSource::new(file, region_dir).unwrap()
+    }
+
+    #[test]
+    fn matches_directory() {
+        for dir in &[
+            "datapack-dimension/region",
+            "datapack-dimension/dimensions/example/sky/region",
+        ] {
+            assert_eq!(scan_dir(dir), scan_source(&zip_source(dir)));
+        }
+    }
+
+    #[test]
+    fn only_lists_regions_in_the_directory() {
+        let source = zip_source("datapack-dimension/region");
+        assert_eq!(vec![(RCoord(0), RCoord(0))], source.list().unwrap());
+        assert!(source.open(RCoord(1), RCoord(0)).unwrap().is_none());
+
+        // Not the region directory of any dimension.
+        let source = zip_source("datapack-dimension");
+        assert!(source.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn loader_from_zip() {
+        let loader =
+            RegionFileLoader::<JavaChunk, _>::from_source(zip_source("datapack-dimension/region"));
+
+        assert_eq!(vec![(RCoord(0), RCoord(0))], loader.list().unwrap());
+        assert!(loader.region(RCoord(0), RCoord(1)).is_none());
+
+        let region = loader.region(RCoord(0), RCoord(0)).unwrap();
+        let chunk = region.chunk(CCoord(0), CCoord(0)).unwrap();
+        assert_eq!("full", chunk.status());
+    }
+
+    #[test]
+    fn not_a_zip() {
+        let file = File::open(worlds().join("datapack-dimension/level.dat")).unwrap();
+        assert!(ZipSource::new(file, "region").is_err());
+    }
+}
+
+#[cfg(feature = "tar")]
+mod tar {
+    use std::fs::File;
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+    use crate::for_each_tar_region;
+
+    fn scan_tar(region_dir: &str) -> Scan {
+        let file = File::open(worlds().join("datapack-dimension.tar.gz")).unwrap();
+        let mut scan = Scan::new();
+        for_each_tar_region(GzDecoder::new(file), region_dir, |x, z, region| {
+            scan_region(&mut scan, x, z, region)
+        })
+        .unwrap();
+        scan
+    }
+
+    #[test]
+    fn matches_directory() {
+        for dir in &[
+            "datapack-dimension/region",
+            "datapack-dimension/dimensions/example/sky/region",
+        ] {
+            assert_eq!(scan_dir(dir), scan_tar(dir));
+        }
+    }
+
+    #[test]
+    fn only_regions_in_the_directory() {
+        assert!(scan_tar("datapack-dimension").is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+use std::path::Path;
+
+use fastnbt::de::from_bytes;
+
+use crate::{Entity, Pos, Rotation};
+
+fn fixture(name: &str) -> Vec<u8> {
+    std::fs::read(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/entities")
+            .join(name),
+    )
+    .unwrap()
+}
+
+#[test]
+fn zombie() {
+    let zombie: Entity = from_bytes(&fixture("zombie.nbt")).unwrap();
+
+    assert_eq!("minecraft:zombie", zombie.id);
+    assert_eq!(Pos([-12.5, 64.0, 203.30000001192093]), zombie.pos);
+    assert_eq!(Rotation([90.0, -12.5]), zombie.rotation);
+    assert_eq!(90.0, zombie.rotation.yaw());
+    assert_eq!(-12.5, zombie.rotation.pitch());
+    assert!(zombie.items.is_none());
+}
+
+#[test]
+fn chest_minecart_items() {
+    let cart: Entity = from_bytes(&fixture("chest_minecart.nbt")).unwrap();
+    let items = cart.items.unwrap();
+
+    assert_eq!(2, items.len());
+    assert_eq!("minecraft:rail", items[0].id);
+    assert_eq!(16, items[0].count);
+    assert_eq!(Some(0), items[0].slot);
+    assert_eq!("minecraft:diamond", items[1].id);
+    assert_eq!(Some(13), items[1].slot);
+}
+
+#[test]
+fn short_pos_is_an_error() {
+    let res = from_bytes::<Entity>(&fixture("short-pos.nbt"));
+    let err = res.unwrap_err().to_string();
+    assert!(err.contains("3 doubles"), "{}", err);
+}
+
+#[test]
+fn long_rotation_is_an_error() {
+    let res = from_bytes::<Entity>(&fixture("long-rotation.nbt"));
+    let err = res.unwrap_err().to_string();
+    assert!(err.contains("2 floats"), "{}", err);
+}
+
+#[test]
+fn pos_distance() {
+    let a = Pos([1.0, 2.0, 3.0]);
+    let b = Pos([4.0, 6.0, 3.0]);
+
+    assert_eq!(5.0, a.distance_to(&b));
+    assert_eq!(25.0, a.distance_squared_to(&b));
+    assert_eq!(0.0, a.distance_to(&a));
+}
+
+#[test]
+fn display() {
+    assert_eq!("(1, 2.5, -3)", Pos([1.0, 2.5, -3.0]).to_string());
+    assert_eq!("yaw 90, pitch -12.5", Rotation([90.0, -12.5]).to_string());
+}
+
+#[cfg(feature = "mint")]
+#[test]
+fn mint_conversions() {
+    let pos = Pos([1.0, 2.0, 3.0]);
+    let point: mint::Point3<f64> = pos.into();
+    assert_eq!((1.0, 2.0, 3.0), (point.x, point.y, point.z));
+    assert_eq!(pos, Pos::from(point));
+
+    let rot = Rotation([90.0, -12.5]);
+    let v: mint::Vector2<f32> = rot.into();
+    assert_eq!((90.0, -12.5), (v.x, v.y));
+    assert_eq!(rot, Rotation::from(v));
+}
+
+#[cfg(feature = "glam")]
+#[test]
+fn glam_conversions() {
+    let pos = Pos([1.0, 2.0, 3.0]);
+    let v: glam::DVec3 = pos.into();
+    assert_eq!(glam::DVec3::new(1.0, 2.0, 3.0), v);
+    assert_eq!(pos, Pos::from(v));
+    assert_eq!(
+        Pos([4.0, 6.0, 3.0]).distance_to(&pos),
+        glam::DVec3::new(4.0, 6.0, 3.0).distance(v)
+    );
+
+    let rot = Rotation([90.0, -12.5]);
+    let v: glam::Vec2 = rot.into();
+    assert_eq!(glam::Vec2::new(90.0, -12.5), v);
+    assert_eq!(rot, Rotation::from(v));
+}
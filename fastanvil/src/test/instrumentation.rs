@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+use crate::{CCoord, Chunk, HeightMode, JavaChunk, RCoord, RegionFileLoader, RegionLoader};
+
+/// A span or event that was recorded, with its fields formatted with Debug.
+#[derive(Debug, Clone)]
+struct Recorded {
+    name: String,
+    fields: HashMap<String, String>,
+}
+
+impl Recorded {
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+}
+
+impl Visit for Recorded {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.fields
+            .insert(field.name().to_owned(), format!("{:?}", value));
+    }
+}
+
+/// Subscriber that records every span and event it sees.
+#[derive(Default, Clone)]
+struct Capture {
+    next_id: Arc<AtomicU64>,
+    spans: Arc<Mutex<Vec<Recorded>>>,
+    events: Arc<Mutex<Vec<Recorded>>>,
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut recorded = Recorded {
+            name: span.metadata().name().to_owned(),
+            fields: HashMap::new(),
+        };
+        span.record(&mut recorded);
+        self.spans.lock().unwrap().push(recorded);
+
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut recorded = Recorded {
+            name: event.metadata().name().to_owned(),
+            fields: HashMap::new(),
+        };
+        event.record(&mut recorded);
+        self.events.lock().unwrap().push(recorded);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+fn region_dir() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/worlds/datapack-dimension/region")
+}
+
+#[test]
+fn chunk_load_fires_spans() {
+    let capture = Capture::default();
+
+    tracing::subscriber::with_default(capture.clone(), || {
+        let loader = RegionFileLoader::<JavaChunk>::new(region_dir());
+        let region = loader.region(RCoord(0), RCoord(0)).unwrap();
+        region.chunk(CCoord(0), CCoord(0)).unwrap();
+    });
+
+    let spans = capture.spans.lock().unwrap();
+    let find = |name: &str| {
+        spans
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("no {} span in {:?}", name, spans))
+    };
+
+    let open = find("region_open");
+    assert_eq!(Some("0"), open.field("x"));
+    assert_eq!(Some("0"), open.field("z"));
+
+    let load = find("chunk_load");
+    assert_eq!(Some("0"), load.field("x"));
+    assert_eq!(Some("0"), load.field("z"));
+
+    let read = find("chunk_read");
+    assert!(read.field("begin_sector").is_some());
+    assert!(read.field("sector_count").is_some());
+
+    let decompress = find("chunk_decompress");
+    assert!(decompress.field("compressed_len").is_some());
+
+    let parse = find("chunk_parse");
+    assert_eq!(Some("0"), parse.field("x"));
+    assert_ne!(Some("0"), parse.field("len"));
+}
+
+#[test]
+fn missing_region_fires_event() {
+    let capture = Capture::default();
+
+    tracing::subscriber::with_default(capture.clone(), || {
+        let loader = RegionFileLoader::<JavaChunk>::new(region_dir());
+        assert!(loader.region(RCoord(5), RCoord(5)).is_none());
+    });
+
+    let events = capture.events.lock().unwrap();
+    assert!(events
+        .iter()
+        .any(|e| e.field("message") == Some("could not open region file")));
+}
+
+#[test]
+fn heightmap_fallback_fires_event() {
+    let capture = Capture::default();
+
+    // A heightmap we don't understand, so Trust has to fall back to calculating.
+    let data = std::fs::read(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/malformed/post18-undersized-heightmap.nbt"),
+    )
+    .unwrap();
+    let chunk: JavaChunk = fastnbt::de::from_bytes(&data).unwrap();
+
+    tracing::subscriber::with_default(capture.clone(), || {
+        chunk.surface_height(0, 0, HeightMode::Trust);
+    });
+
+    let events = capture.events.lock().unwrap();
+    assert!(
+        events.iter().any(|e| e
+            .field("message")
+            .is_some_and(|m| m.contains("heightmap missing"))),
+        "{:?}",
+        events
+    );
+}
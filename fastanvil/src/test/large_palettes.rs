@@ -0,0 +1,228 @@
+use fastnbt::{de::from_bytes, to_bytes, LongArray};
+use serde::Serialize;
+
+use crate::{
+    bits_per_block, expand_blockstates, try_expand_blockstates, Chunk, JavaChunk, PackedBits,
+    MAX_PALETTE_LEN,
+};
+
+// Palette sizes either side of the interesting boundaries: 8 to 9 bits, and
+// the largest palette a section can have.
+const SIZES: &[usize] = &[255, 256, 257, 4095, 4096];
+
+const BLOCKS: usize = 16 * 16 * 16;
+
+fn indices(palette_len: usize) -> Vec<u16> {
+    (0..BLOCKS).map(|i| (i % palette_len) as u16).collect()
+}
+
+/// Pack in the 1.15 format, where values can span two longs.
+fn pack_tight(values: &[u16], bits: usize) -> Vec<i64> {
+    let mut data = vec![0u64; values.len() * bits / 64];
+    for (i, &v) in values.iter().enumerate() {
+        for b in 0..bits {
+            if v >> b & 1 == 1 {
+                let bit = i * bits + b;
+                data[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+    }
+    data.into_iter().map(|l| l as i64).collect()
+}
+
+/// Pack in the 1.16 format, where values never span longs.
+fn pack_padded(values: &[u16], bits: usize) -> Vec<i64> {
+    let per_long = 64 / bits;
+    values
+        .chunks(per_long)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u64, |long, (i, &v)| long | (v as u64) << (i * bits)) as i64
+        })
+        .collect()
+}
+
+fn packings(palette_len: usize) -> Vec<(&'static str, Vec<i64>)> {
+    let values = indices(palette_len);
+    let bits = bits_per_block(palette_len);
+    vec![
+        ("tight", pack_tight(&values, bits)),
+        ("padded", pack_padded(&values, bits)),
+    ]
+}
+
+fn palette(len: usize) -> Vec<BlockNbt> {
+    (0..len)
+        .map(|i| BlockNbt {
+            name: format!("minecraft:block_{}", i),
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct BlockNbt {
+    name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Pre18Nbt {
+    data_version: i32,
+    level: Pre18Level,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Pre18Level {
+    #[serde(rename = "xPos")]
+    x_pos: i32,
+    #[serde(rename = "zPos")]
+    z_pos: i32,
+    sections: Vec<Pre18SectionNbt>,
+    status: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Pre18SectionNbt {
+    y: i8,
+    palette: Vec<BlockNbt>,
+    block_states: LongArray,
+}
+
+#[derive(Serialize)]
+struct Post18Nbt {
+    #[serde(rename = "DataVersion")]
+    data_version: i32,
+    sections: Vec<Post18SectionNbt>,
+    #[serde(rename = "Status")]
+    status: String,
+}
+
+#[derive(Serialize)]
+struct Post18SectionNbt {
+    #[serde(rename = "Y")]
+    y: i8,
+    block_states: BlockStatesNbt,
+}
+
+#[derive(Serialize)]
+struct BlockStatesNbt {
+    palette: Vec<BlockNbt>,
+    data: LongArray,
+}
+
+fn pre18_chunk(data_version: i32, palette_len: usize, data: Vec<i64>) -> JavaChunk {
+    let nbt = Pre18Nbt {
+        data_version,
+        level: Pre18Level {
+            x_pos: 0,
+            z_pos: 0,
+            sections: vec![Pre18SectionNbt {
+                y: 0,
+                palette: palette(palette_len),
+                block_states: LongArray::new(data),
+            }],
+            status: "full".to_owned(),
+        },
+    };
+
+    from_bytes(&to_bytes(&nbt).unwrap()).unwrap()
+}
+
+fn post18_chunk(palette_len: usize, data: Vec<i64>) -> JavaChunk {
+    let nbt = Post18Nbt {
+        data_version: 2860,
+        sections: vec![Post18SectionNbt {
+            y: 0,
+            block_states: BlockStatesNbt {
+                palette: palette(palette_len),
+                data: LongArray::new(data),
+            },
+        }],
+        status: "full".to_owned(),
+    };
+
+    from_bytes(&to_bytes(&nbt).unwrap()).unwrap()
+}
+
+fn assert_blocks(chunk: &JavaChunk, palette_len: usize, desc: &str) {
+    for (i, expected) in indices(palette_len).into_iter().enumerate() {
+        let (x, y, z) = (i % 16, i / 256, (i / 16) % 16);
+        let block = chunk.block(x, y as isize, z).unwrap();
+        assert_eq!(
+            format!("minecraft:block_{}", expected),
+            block.name(),
+            "{} at {:?}",
+            desc,
+            (x, y, z)
+        );
+    }
+}
+
+#[test]
+fn expand_round_trips() {
+    for &len in SIZES {
+        for (packing, data) in packings(len) {
+            let expanded = expand_blockstates(&data, len);
+            assert_eq!(
+                indices(len),
+                expanded[..BLOCKS],
+                "palette {} {}",
+                len,
+                packing
+            );
+        }
+    }
+}
+
+#[test]
+fn unpack_round_trips() {
+    for &len in SIZES {
+        for (packing, data) in packings(len) {
+            let mut buf = [0u16; BLOCKS];
+            PackedBits(LongArray::new(data)).unpack_blockstates(bits_per_block(len), &mut buf);
+            assert_eq!(indices(len), buf, "palette {} {}", len, packing);
+        }
+    }
+}
+
+#[test]
+fn pre18_blocks() {
+    for &len in SIZES {
+        let values = indices(len);
+        let bits = bits_per_block(len);
+
+        let chunk = pre18_chunk(2230, len, pack_tight(&values, bits));
+        assert_blocks(&chunk, len, "1.15");
+
+        let chunk = pre18_chunk(2586, len, pack_padded(&values, bits));
+        assert_blocks(&chunk, len, "1.16");
+    }
+}
+
+#[test]
+fn post18_blocks() {
+    for &len in SIZES {
+        let data = pack_padded(&indices(len), bits_per_block(len));
+        let chunk = post18_chunk(len, data);
+        assert_blocks(&chunk, len, "1.18");
+    }
+}
+
+#[test]
+fn oversized_palette_is_rejected() {
+    let len = MAX_PALETTE_LEN + 1;
+    let data = pack_padded(&indices(len), bits_per_block(len));
+
+    assert!(try_expand_blockstates(&data, len).is_none());
+
+    let chunk = pre18_chunk(2586, len, data.clone());
+    assert!(chunk.block(0, 0, 0).is_none());
+
+    let chunk = post18_chunk(len, data);
+    assert!(chunk.block(0, 0, 0).is_none());
+}
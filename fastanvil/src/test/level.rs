@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use crate::{CCoord, LevelDat, WorldBorder, WorldMeta};
+
+fn resources() -> &'static Path {
+    Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/resources"))
+}
+
+// Border centred on 8.5, -120 with a size of 1000, shrinking to 200 over the
+// next minute. Spawn is at 100, 64, -37, with a spawnChunkRadius of 3.
+fn level() -> LevelDat {
+    WorldMeta::discover(resources().join("worlds/datapack-dimension"))
+        .unwrap()
+        .level_dat()
+        .unwrap()
+}
+
+// A level.dat from before world borders, data versions, and game rules.
+fn old_level() -> LevelDat {
+    let data = std::fs::read(resources().join("level/no-border.dat")).unwrap();
+    LevelDat::from_bytes(&data).unwrap()
+}
+
+#[test]
+fn fields() {
+    let level = level();
+    assert_eq!(Some(3700), level.data_version);
+    assert_eq!("Border test", level.level_name);
+    assert_eq!(
+        (100, 64, -37),
+        (level.spawn_x, level.spawn_y, level.spawn_z)
+    );
+    assert_eq!(
+        Some("true"),
+        level.game_rules.get("doDaylightCycle").map(String::as_str)
+    );
+    assert_eq!(Some(3), level.spawn_chunk_radius());
+}
+
+#[test]
+fn border_from_fixture() {
+    let border = level().world_border();
+
+    assert_eq!((8.5, -120.0), border.center);
+    assert_eq!(1000.0, border.size);
+    assert_eq!(200.0, border.lerp_target);
+    assert_eq!(60000, border.lerp_time);
+    assert_eq!(5.0, border.safe_zone);
+    assert_eq!(0.2, border.damage_per_block);
+
+    // Size is a diameter.
+    assert_eq!(-491.5, border.min_x());
+    assert_eq!(508.5, border.max_x());
+    assert_eq!(-620.0, border.min_z());
+    assert_eq!(380.0, border.max_z());
+}
+
+#[test]
+fn border_contains() {
+    let border = level().world_border();
+
+    assert!(border.contains(8, -120));
+
+    // Partially inside counts as inside.
+    assert!(border.contains(-492, 0));
+    assert!(!border.contains(-493, 0));
+    assert!(border.contains(508, 0));
+    assert!(!border.contains(509, 0));
+
+    // Exactly on the edge, the block at -620 starts inside and the block at
+    // 380 starts outside.
+    assert!(border.contains(0, -620));
+    assert!(!border.contains(0, -621));
+    assert!(border.contains(0, 379));
+    assert!(!border.contains(0, 380));
+}
+
+#[test]
+fn border_lerp() {
+    let border = level().world_border();
+
+    assert!(border.is_moving());
+    assert_eq!(1000.0, border.size_after(0));
+    assert_eq!(600.0, border.size_after(30000));
+    assert_eq!(200.0, border.size_after(60000));
+    assert_eq!(200.0, border.size_after(120000));
+
+    let still = WorldBorder {
+        lerp_time: 0,
+        ..border
+    };
+    assert!(!still.is_moving());
+    assert_eq!(1000.0, still.size_after(30000));
+}
+
+#[test]
+fn border_defaults() {
+    let level = old_level();
+    let border = level.world_border();
+
+    assert_eq!(WorldBorder::default(), border);
+    assert_eq!(None, level.data_version);
+    assert_eq!(None, level.spawn_chunk_radius());
+
+    // The default border covers the whole world, clamped to its limits.
+    assert_eq!(-29_999_984.0, border.min_x());
+    assert_eq!(29_999_984.0, border.max_z());
+    assert!(border.contains(29_999_983, -29_999_984));
+    assert!(!border.contains(29_999_984, 0));
+}
+
+#[test]
+fn spawn_chunks() {
+    let level = level();
+    assert_eq!((CCoord(6), CCoord(-3)), level.spawn_chunk());
+
+    let chunks: Vec<_> = level.spawn_chunks(3).collect();
+    assert_eq!(49, chunks.len());
+    assert_eq!((CCoord(3), CCoord(-6)), chunks[0]);
+    assert_eq!((CCoord(9), CCoord(0)), chunks[48]);
+    assert!(chunks.contains(&level.spawn_chunk()));
+
+    let just_spawn: Vec<_> = level.spawn_chunks(0).collect();
+    assert_eq!(vec![level.spawn_chunk()], just_spawn);
+
+    // Pre 1.20.5 loaded area.
+    assert_eq!(23 * 23, level.spawn_chunks(11).count());
+}
+
+#[test]
+fn spawn_chunk_negative_spawn() {
+    // Spawn at -1, 16 is in chunk -1, 1.
+    assert_eq!((CCoord(-1), CCoord(1)), old_level().spawn_chunk());
+}
+
+#[test]
+fn missing_level_dat_is_an_error() {
+    let world = WorldMeta::discover(resources().join("worlds/datapack-dimension/dimensions"));
+    assert!(world.unwrap().level_dat().is_err());
+    assert!(LevelDat::from_bytes(b"not gzip").is_err());
+}
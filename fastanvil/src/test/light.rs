@@ -0,0 +1,107 @@
+use fastnbt::de::from_bytes;
+
+use crate::{Chunk, JavaChunk, LightPresence};
+
+// The 21w44a test chunk, with sections -4 to 19 holding blocks, plus light
+// only sections at -5 and 20. Section -5 has block light of 7 at its lowest
+// corner and 3 at its highest. Section 20 has sky light of 15, except 9 at
+// 3, 0, 4 and 12 at its highest corner.
+const LIGHT_SECTIONS: &[u8] = include_bytes!("../../resources/light-sections.nbt");
+const CHUNK_21W44A: &[u8] = include_bytes!("../../resources/21w44a-test1.nbt");
+const ETHO: &[u8] = include_bytes!("../../resources/etho.chunk");
+
+fn presence(block_light: bool, sky_light: bool) -> LightPresence {
+    LightPresence {
+        block_light,
+        sky_light,
+    }
+}
+
+#[test]
+fn light_only_sections_not_in_block_range() {
+    let chunk: JavaChunk = from_bytes(LIGHT_SECTIONS).unwrap();
+    let original: JavaChunk = from_bytes(CHUNK_21W44A).unwrap();
+
+    assert_eq!(-64..320, chunk.y_range());
+    assert_eq!(original.y_range(), chunk.y_range());
+    assert!(chunk.block(0, -65, 0).is_none());
+    assert!(chunk.block(0, 320, 0).is_none());
+}
+
+#[test]
+fn light_sections_cover_one_beyond_blocks() {
+    let chunk: JavaChunk = from_bytes(LIGHT_SECTIONS).unwrap();
+    let sections = chunk.light_sections();
+
+    let ys: Vec<_> = sections.iter().map(|(y, _)| *y).collect();
+    assert_eq!((-5..=20).collect::<Vec<i8>>(), ys);
+
+    let get = |y: i8| sections.iter().find(|(sy, _)| *sy == y).unwrap().1;
+    assert_eq!(presence(true, false), get(-5));
+    assert_eq!(presence(true, false), get(-4));
+    assert_eq!(presence(false, false), get(2));
+    assert_eq!(presence(false, true), get(3));
+    assert_eq!(presence(false, false), get(19));
+    assert_eq!(presence(false, true), get(20));
+}
+
+#[test]
+fn block_light_at_extremes() {
+    let chunk: JavaChunk = from_bytes(LIGHT_SECTIONS).unwrap();
+
+    assert_eq!(Some(7), chunk.block_light(0, -80, 0));
+    assert_eq!(Some(3), chunk.block_light(15, -65, 15));
+    assert_eq!(Some(0), chunk.block_light(1, -80, 0));
+    assert_eq!(Some(15), chunk.block_light(14, -57, 13));
+
+    // No block light stored at the top, so none.
+    assert_eq!(Some(0), chunk.block_light(0, 335, 0));
+
+    assert_eq!(None, chunk.block_light(0, -81, 0));
+    assert_eq!(None, chunk.block_light(0, 336, 0));
+    assert_eq!(None, chunk.block_light(16, 0, 0));
+}
+
+#[test]
+fn sky_light_at_extremes() {
+    let chunk: JavaChunk = from_bytes(LIGHT_SECTIONS).unwrap();
+
+    assert_eq!(Some(9), chunk.sky_light(3, 320, 4));
+    assert_eq!(Some(12), chunk.sky_light(15, 335, 15));
+    assert_eq!(Some(15), chunk.sky_light(0, 320, 0));
+
+    assert_eq!(None, chunk.sky_light(0, -81, 0));
+    assert_eq!(None, chunk.sky_light(0, 336, 0));
+}
+
+#[test]
+fn missing_sky_light_comes_from_above() {
+    let chunk: JavaChunk = from_bytes(LIGHT_SECTIONS).unwrap();
+
+    // Sections 5 to 19 have no sky light, so take the bottom of section 20.
+    assert_eq!(Some(9), chunk.sky_light(3, 160, 4));
+    assert_eq!(Some(9), chunk.sky_light(3, 319, 4));
+    assert_eq!(Some(15), chunk.sky_light(0, 160, 0));
+
+    // Section 2 has none, and the bottom of section 3 is dark here.
+    assert_eq!(Some(0), chunk.sky_light(3, 40, 4));
+    assert_eq!(Some(0), chunk.sky_light(3, -80, 4));
+}
+
+#[test]
+fn pre18_light_sections() {
+    // Sections 0 to 4 have blocks, -1 is empty, and 5 only has sky light.
+    let chunk: JavaChunk = from_bytes(ETHO).unwrap();
+    assert_eq!(0..80, chunk.y_range());
+
+    let sections = chunk.light_sections();
+    let ys: Vec<_> = sections.iter().map(|(y, _)| *y).collect();
+    assert_eq!((-1..=5).collect::<Vec<i8>>(), ys);
+    assert_eq!((-1, presence(false, false)), sections[0]);
+    assert_eq!((5, presence(false, true)), sections[6]);
+
+    assert!(chunk.sky_light(0, 95, 0).is_some());
+    assert_eq!(None, chunk.sky_light(0, 96, 0));
+    assert_eq!(Some(0), chunk.block_light(0, -16, 0));
+    assert_eq!(None, chunk.block_light(0, -17, 0));
+}
@@ -0,0 +1,88 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+use fastnbt::de::from_bytes;
+
+use crate::{Chunk, HeightMode, JavaChunk};
+
+// Each file in resources/malformed is a small chunk that deserializes fine but
+// contains data that used to cause a panic in one of the Chunk methods, eg
+// palette indices past the end of the palette, block data too short for its
+// palette, heightmaps of unexpected lengths, heightmaps without sections, and
+// sections at the extremes of the section Y range. Files are prefixed with
+// `pre18-` or `post18-` for the chunk format they use.
+fn corpus() -> Vec<(String, Vec<u8>)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/malformed");
+    let mut files: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "nbt"))
+        .map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            (name, std::fs::read(path).unwrap())
+        })
+        .collect();
+
+    files.sort();
+    files
+}
+
+fn exercise(chunk: &JavaChunk, mode: HeightMode) {
+    chunk.status();
+    let range = chunk.y_range();
+
+    for z in 0..16 {
+        for x in 0..16 {
+            chunk.surface_height(x, z, mode);
+
+            for y in range.start - 16..range.end + 16 {
+                chunk.block(x, y, z);
+                chunk.biome(x, y, z);
+            }
+        }
+    }
+
+    for &(x, z) in &[(16, 0), (0, 16), (usize::MAX, usize::MAX)] {
+        assert!(chunk.block(x, 0, z).is_none());
+        assert!(chunk.biome(x, 0, z).is_none());
+    }
+
+    for &y in &[isize::MIN, isize::MAX] {
+        assert!(chunk.block(0, y, 0).is_none());
+        chunk.biome(0, y, 0);
+    }
+}
+
+#[test]
+fn corpus_is_present() {
+    let files = corpus();
+    assert!(files.iter().any(|(name, _)| name.starts_with("pre18-")));
+    assert!(files.iter().any(|(name, _)| name.starts_with("post18-")));
+}
+
+#[test]
+fn malformed_chunks_do_not_panic() {
+    for (name, data) in corpus() {
+        for &mode in &[HeightMode::Trust, HeightMode::Calculate] {
+            // Parse for each mode since the heightmap is cached on first use.
+            let chunk: JavaChunk = from_bytes(&data).unwrap();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| exercise(&chunk, mode)));
+            if result.is_err() {
+                panic!("{} panicked", name);
+            }
+        }
+    }
+}
+
+#[test]
+fn surface_height_panics_out_of_range() {
+    for (name, data) in corpus() {
+        let chunk: JavaChunk = from_bytes(&data).unwrap();
+        for &(x, z) in &[(16, 0), (0, 16), (usize::MAX, usize::MAX)] {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                chunk.surface_height(x, z, HeightMode::Calculate)
+            }));
+            assert!(result.is_err(), "{} gave a height for ({}, {})", name, x, z);
+        }
+    }
+}
@@ -1,3 +1,18 @@
+#[cfg(any(feature = "zip", feature = "tar"))]
+mod archives;
+mod entity;
+#[cfg(feature = "tracing")]
+mod instrumentation;
+mod large_palettes;
+mod level;
+mod light;
+mod malformed_chunks;
+mod pipeline;
 mod rogue_chunks;
-mod unicode_chunk;
+mod roundtrip;
+mod snapshot_chunks;
 mod standard_chunks;
+mod stats;
+mod support;
+mod unicode_chunk;
+mod world;
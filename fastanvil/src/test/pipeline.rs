@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+use std::io::{Cursor, Write};
+use std::time::Duration;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::{RegionBuffer, SECTOR_SIZE};
+
+fn payload(x: usize, z: usize) -> Vec<u8> {
+    format!("chunk {} {};", x, z)
+        .repeat(100 + x * z)
+        .into_bytes()
+}
+
+/// A region with the given chunks present, each zlib compressed into its
+/// own sectors.
+fn region(chunks: &[(usize, usize)]) -> RegionBuffer<Cursor<Vec<u8>>> {
+    let mut header = vec![0u8; 2 * SECTOR_SIZE];
+    let mut body = Vec::new();
+
+    for &(x, z) in chunks {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&payload(x, z)).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&(compressed.len() as u32 + 1).to_be_bytes());
+        data.push(2);
+        data.extend_from_slice(&compressed);
+        let sectors = data.len().div_ceil(SECTOR_SIZE);
+        data.resize(sectors * SECTOR_SIZE, 0);
+
+        let offset = 2 + body.len() / SECTOR_SIZE;
+        let pos = 4 * (x + z * 32);
+        header[pos..pos + 3].copy_from_slice(&(offset as u32).to_be_bytes()[1..]);
+        header[pos + 3] = sectors as u8;
+
+        body.extend_from_slice(&data);
+    }
+
+    header.extend_from_slice(&body);
+    RegionBuffer::new(Cursor::new(header))
+}
+
+// Stored out of row order, so that file order and row order differ.
+fn chunks() -> Vec<(usize, usize)> {
+    let mut chunks = vec![];
+    for x in (0..32).step_by(3) {
+        for z in (0..32).rev().step_by(5) {
+            chunks.push((x, z));
+        }
+    }
+    chunks
+}
+
+fn row_order(mut chunks: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    chunks.sort_by_key(|&(x, z)| (z, x));
+    chunks
+}
+
+#[test]
+fn ordered_is_row_order() {
+    let mut region = region(&chunks());
+
+    for &(parallelism, capacity) in &[(1, 1), (4, 2), (8, 16)] {
+        let seen: Vec<_> = region
+            .pipelined_chunks(parallelism, capacity)
+            .unwrap()
+            .ordered()
+            .map(|c| {
+                let (x, z, data) = c.unwrap();
+                assert_eq!(payload(x, z), data);
+                (x, z)
+            })
+            .collect();
+
+        assert_eq!(row_order(chunks()), seen);
+    }
+}
+
+#[test]
+fn unordered_has_every_chunk() {
+    let mut region = region(&chunks());
+    let pipeline = region.pipelined_chunks(4, 3).unwrap();
+    assert_eq!(chunks().len(), pipeline.len());
+
+    let seen: HashSet<_> = pipeline
+        .map(|c| {
+            let (x, z, data) = c.unwrap();
+            assert_eq!(payload(x, z), data);
+            (x, z)
+        })
+        .collect();
+
+    assert_eq!(chunks().into_iter().collect::<HashSet<_>>(), seen);
+}
+
+#[test]
+fn slow_consumer_is_bounded() {
+    let mut region = region(&chunks());
+
+    for &ordered in &[false, true] {
+        let mut pipeline = region.pipelined_chunks(8, 3).unwrap();
+        if ordered {
+            pipeline = pipeline.ordered();
+        }
+
+        while let Some(chunk) = pipeline.next() {
+            chunk.unwrap();
+            std::thread::sleep(Duration::from_millis(2));
+            assert!(pipeline.peak_buffered() <= 3);
+        }
+
+        assert!(pipeline.peak_buffered() >= 1);
+        assert!(pipeline.peak_buffered() <= 3);
+    }
+}
+
+#[test]
+fn dropping_early_stops_workers() {
+    let mut region = region(&chunks());
+    let first: Vec<_> = region
+        .pipelined_chunks(4, 2)
+        .unwrap()
+        .ordered()
+        .take(3)
+        .map(|c| c.unwrap().0)
+        .collect();
+
+    assert_eq!(vec![0, 3, 6], first);
+}
+
+#[test]
+fn empty_region() {
+    let mut region = region(&[]);
+    assert_eq!(0, region.pipelined_chunks(4, 4).unwrap().count());
+}
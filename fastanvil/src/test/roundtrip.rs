@@ -0,0 +1,239 @@
+use std::io::Cursor;
+use std::path::Path;
+
+use fastnbt::{de::from_bytes, Value};
+
+use crate::{Chunk, Error, HeightMode, JavaChunk, RegionBuffer, RegionWriter};
+
+// Chunks re-serialized with fastnbt, and regions written with them, should read
+// back identically.
+
+const FIXTURES: &[(&str, &[u8])] = &[
+    (
+        "etho-old-heightmaps",
+        include_bytes!("../../resources/etho-old-heightmaps.chunk"),
+    ),
+    ("etho", include_bytes!("../../resources/etho.chunk")),
+    ("1.17.0", include_bytes!("../../resources/1.17.0.chunk")),
+    (
+        "1.17.1-custom-heights",
+        include_bytes!("../../resources/1.17.1-custom-heights.chunk"),
+    ),
+    ("unicode", include_bytes!("../../resources/unicode.chunk")),
+    (
+        "21w39a-synthetic",
+        include_bytes!("../../resources/21w39a-synthetic.nbt"),
+    ),
+    ("21w44a", include_bytes!("../../resources/21w44a-test1.nbt")),
+];
+
+/// The path to the first value that differs between `a` and `b`, if any.
+fn first_difference(a: &Value, b: &Value, path: &str) -> Option<String> {
+    match (a, b) {
+        (Value::Compound(a), Value::Compound(b)) => {
+            let mut keys: Vec<_> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            keys.into_iter().find_map(|key| {
+                let path = format!("{}.{}", path, key);
+                match (a.get(key), b.get(key)) {
+                    (Some(a), Some(b)) => first_difference(a, b, &path),
+                    _ => Some(path),
+                }
+            })
+        }
+        (Value::List(a), Value::List(b)) if a.len() == b.len() => a
+            .iter()
+            .zip(b)
+            .enumerate()
+            .find_map(|(i, (a, b))| first_difference(a, b, &format!("{}[{}]", path, i))),
+        _ if a == b => None,
+        _ => Some(path.to_owned()),
+    }
+}
+
+/// Re-serialize a chunk, and check the result matches the original both as
+/// NBT and as a parsed chunk. Returns the re-serialized chunk.
+fn assert_round_trips(name: &str, data: &[u8]) -> Vec<u8> {
+    let value: Value = from_bytes(data).unwrap();
+    let written = fastnbt::to_bytes(&value).unwrap();
+    assert_same_chunk(name, data, &written);
+
+    // The same values, even if reordered, take the same space.
+    assert_eq!(data.len(), written.len(), "chunk {}: size changed", name);
+    written
+}
+
+fn assert_same_chunk(name: &str, before: &[u8], after: &[u8]) {
+    let value: Value = from_bytes(before).unwrap();
+    let reread: Value = from_bytes(after).unwrap();
+    if let Some(path) = first_difference(&value, &reread, "") {
+        panic!("chunk {}: first differs at {}", name, path);
+    }
+
+    let before: JavaChunk = from_bytes(before).unwrap();
+    let after: JavaChunk = from_bytes(after).unwrap();
+    assert_chunks_match(name, &before, &after);
+}
+
+fn assert_chunks_match(name: &str, before: &JavaChunk, after: &JavaChunk) {
+    assert_eq!(before.y_range(), after.y_range(), "chunk {}", name);
+    assert_eq!(before.status(), after.status(), "chunk {}", name);
+
+    for z in 0..16 {
+        for x in 0..16 {
+            assert_eq!(
+                before.surface_height(x, z, HeightMode::Trust),
+                after.surface_height(x, z, HeightMode::Trust),
+                "chunk {}: height at {}, {}",
+                name,
+                x,
+                z
+            );
+
+            for y in before.y_range() {
+                assert_eq!(
+                    before.block(x, y, z).map(|b| b.encoded_description()),
+                    after.block(x, y, z).map(|b| b.encoded_description()),
+                    "chunk {}: block at {}, {}, {}",
+                    name,
+                    x,
+                    y,
+                    z
+                );
+                assert_eq!(
+                    before.biome(x, y, z).map(i32::from),
+                    after.biome(x, y, z).map(i32::from),
+                    "chunk {}: biome at {}, {}, {}",
+                    name,
+                    x,
+                    y,
+                    z
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn fixtures_round_trip() {
+    for (name, data) in FIXTURES {
+        assert_round_trips(name, data);
+    }
+}
+
+#[test]
+fn region_round_trips() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources/worlds/datapack-dimension/region/r.0.0.mca");
+    let original = std::fs::read(&path).unwrap();
+    let mut region = RegionBuffer::new(Cursor::new(&original));
+
+    let mut chunks = Vec::new();
+    region
+        .for_each_chunk(|x, z, data| chunks.push((x, z, data.clone())))
+        .unwrap();
+    assert!(!chunks.is_empty());
+
+    let mut writer = RegionWriter::new(Cursor::new(Vec::new())).unwrap();
+    for (x, z, data) in &chunks {
+        let written = assert_round_trips(&format!("{}, {}", x, z), data);
+        let timestamp = region.chunk_timestamp(*x, *z).unwrap();
+        writer.write_chunk(*x, *z, &written, timestamp).unwrap();
+    }
+    let written = writer.finish().unwrap().into_inner();
+
+    let reread = RegionBuffer::new(Cursor::new(&written));
+    for (x, z, data) in &chunks {
+        let name = format!("{}, {}", x, z);
+        assert_same_chunk(&name, data, &reread.load_chunk(*x, *z).unwrap());
+        assert_eq!(
+            region.chunk_timestamp(*x, *z).unwrap(),
+            reread.chunk_timestamp(*x, *z).unwrap(),
+            "chunk {}: timestamp",
+            name
+        );
+    }
+
+    // Compressed the same way Minecraft does, so about the same size.
+    let factor = written.len() as f64 / original.len() as f64;
+    assert!((0.8..=1.25).contains(&factor), "size factor {}", factor);
+}
+
+#[test]
+fn region_writer_places_every_chunk() {
+    // Each fixture at its own location, saved at its own time.
+    let place = |i: usize| (i * 7 % 32, i * 3 + 2);
+    let mut writer = RegionWriter::new(Cursor::new(Vec::new())).unwrap();
+    for (i, (_, data)) in FIXTURES.iter().enumerate() {
+        let (x, z) = place(i);
+        writer
+            .write_chunk(x, z, data, 1_600_000_000 + i as u32)
+            .unwrap();
+    }
+    let written = writer.finish().unwrap().into_inner();
+    assert_eq!(0, written.len() % crate::SECTOR_SIZE);
+
+    let region = RegionBuffer::new(Cursor::new(written));
+    for (i, (name, data)) in FIXTURES.iter().enumerate() {
+        let (x, z) = place(i);
+        assert_same_chunk(name, data, &region.load_chunk(x, z).unwrap());
+        assert_eq!(
+            1_600_000_000 + i as u32,
+            region.chunk_timestamp(x, z).unwrap(),
+            "chunk {}: timestamp",
+            name
+        );
+    }
+
+    assert!(matches!(
+        region.load_chunk(31, 31),
+        Err(Error::ChunkNotFound)
+    ));
+    assert_eq!(0, region.chunk_timestamp(31, 31).unwrap());
+    assert!(matches!(
+        region.chunk_timestamp(32, 0),
+        Err(Error::InvalidOffset(32, 0))
+    ));
+}
+
+#[test]
+fn region_writer_rejects_bad_chunks() {
+    let mut writer = RegionWriter::new(Cursor::new(Vec::new())).unwrap();
+    assert!(matches!(
+        writer.write_chunk(0, 32, FIXTURES[0].1, 0),
+        Err(Error::InvalidOffset(0, 32))
+    ));
+
+    // Random bytes don't compress, so more than 255 sectors of them can't
+    // be stored in the region.
+    let mut state = 1u32;
+    let noise: Vec<u8> = (0..256 * crate::SECTOR_SIZE)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect();
+    assert!(matches!(
+        writer.write_chunk(1, 1, &noise, 0),
+        Err(Error::ChunkTooLarge(1, 1))
+    ));
+}
+
+#[test]
+fn first_difference_paths() {
+    let a: Value = from_bytes(FIXTURES[0].1).unwrap();
+    assert_eq!(None, first_difference(&a, &a, ""));
+
+    let mut b = a.clone();
+    if let Value::Compound(root) = &mut b {
+        root.insert("DataVersion".to_owned(), Value::Int(-1));
+    }
+    assert_eq!(
+        Some(".DataVersion".to_owned()),
+        first_difference(&a, &b, "")
+    );
+}
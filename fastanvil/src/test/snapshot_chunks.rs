@@ -0,0 +1,79 @@
+use fastnbt::de::from_bytes;
+
+use crate::java::{chunk_layout, ChunkLayout};
+use crate::{biome::Biome, Chunk, HeightMode, JavaChunk};
+
+const CHUNK_21W44A_1: &[u8] = include_bytes!("../../resources/21w44a-test1.nbt");
+
+// The 21w44a chunk above rearranged into the layout of the earlier 1.18
+// snapshots: everything under `Level`, capitalised `Sections`, `Palette` and
+// `Data` keys in the block state containers, and chunk-level biomes (all
+// plains) rather than biomes in each section. It is not a chunk saved by a
+// 21w39a to 21w43a snapshot, so these tests only check the parser handles the
+// layout as we understand it, which is why the layout is reported as
+// unverified.
+const CHUNK_21W39A_SYNTHETIC: &[u8] = include_bytes!("../../resources/21w39a-synthetic.nbt");
+
+#[test]
+fn layout_dispatch_table() {
+    assert_eq!(ChunkLayout::Pre18, chunk_layout(2586)); // 1.16.5
+    assert_eq!(ChunkLayout::Pre18, chunk_layout(2730)); // 1.17.1
+    assert_eq!(ChunkLayout::Pre18, chunk_layout(2833));
+    assert_eq!(ChunkLayout::Snapshot18, chunk_layout(2834)); // 21w37a
+    assert_eq!(ChunkLayout::Snapshot18, chunk_layout(2836)); // 21w39a
+    assert_eq!(ChunkLayout::Snapshot18, chunk_layout(2844)); // 21w43a
+    assert_eq!(ChunkLayout::Post18, chunk_layout(2845)); // 21w44a
+    assert_eq!(ChunkLayout::Post18, chunk_layout(2860)); // 1.18
+}
+
+#[test]
+fn snapshot_chunk_parses_as_post18() {
+    let chunk: JavaChunk = from_bytes(CHUNK_21W39A_SYNTHETIC).unwrap();
+
+    match chunk {
+        JavaChunk::Post18(c) => {
+            assert_eq!(2836, c.data_version);
+            assert_eq!("full", c.status);
+            assert!(c.heightmaps.is_some());
+            assert!(c.biomes.is_some());
+        }
+        JavaChunk::Pre18(_) => panic!("expected snapshot chunk to use the post-1.18 parser"),
+    }
+}
+
+#[test]
+fn snapshot_chunk_has_same_blocks_as_release_layout() {
+    let snapshot: JavaChunk = from_bytes(CHUNK_21W39A_SYNTHETIC).unwrap();
+    let release: JavaChunk = from_bytes(CHUNK_21W44A_1).unwrap();
+
+    assert_eq!(release.y_range(), snapshot.y_range());
+
+    for y in release.y_range() {
+        for z in 0..16 {
+            for x in 0..16 {
+                let expected = release.block(x, y, z).map(|b| b.encoded_description());
+                let actual = snapshot.block(x, y, z).map(|b| b.encoded_description());
+                assert_eq!(expected, actual, "block at {}, {}, {}", x, y, z);
+            }
+        }
+    }
+
+    for z in 0..16 {
+        for x in 0..16 {
+            assert_eq!(
+                release.surface_height(x, z, HeightMode::Trust),
+                snapshot.surface_height(x, z, HeightMode::Trust)
+            );
+        }
+    }
+}
+
+#[test]
+fn snapshot_chunk_falls_back_to_chunk_level_biomes() {
+    let chunk: JavaChunk = from_bytes(CHUNK_21W39A_SYNTHETIC).unwrap();
+
+    assert!(matches!(chunk.biome(0, -64, 0), Some(Biome::Plains)));
+    assert!(matches!(chunk.biome(15, 100, 15), Some(Biome::Plains)));
+    assert!(matches!(chunk.biome(15, 319, 15), Some(Biome::Plains)));
+    assert!(chunk.biome(0, 320, 0).is_none());
+}
@@ -0,0 +1,232 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use fastnbt::Value;
+
+use crate::stats::{Cache, SCHEMA_VERSION};
+use crate::{CCoord, Chunk, JavaChunk, RegionBuffer, RegionFileLoader, RegionWriter};
+
+const CHUNK_1_17_0: &[u8] = include_bytes!("../../resources/1.17.0.chunk");
+
+/// The chunk at 0,0 of the fixture world's overworld.
+fn fixture_chunk() -> Vec<u8> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources/worlds/datapack-dimension/region/r.0.0.mca");
+    RegionBuffer::new(File::open(path).unwrap())
+        .load_chunk(0, 0)
+        .unwrap()
+}
+
+fn write_region(path: &Path, chunks: &[(usize, usize, &[u8], u32)]) {
+    let mut writer = RegionWriter::new(File::create(path).unwrap()).unwrap();
+    for &(x, z, data, timestamp) in chunks {
+        writer.write_chunk(x, z, data, timestamp).unwrap();
+    }
+    writer.finish().unwrap();
+}
+
+fn with_inhabited_time(data: &[u8], ticks: i64) -> Vec<u8> {
+    let mut value: Value = fastnbt::de::from_bytes(data).unwrap();
+    if let Value::Compound(root) = &mut value {
+        root.insert("InhabitedTime".to_owned(), Value::Long(ticks));
+    }
+    fastnbt::to_bytes(&value).unwrap()
+}
+
+fn coords(list: &[(isize, isize)]) -> Vec<(CCoord, CCoord)> {
+    list.iter().map(|&(x, z)| (CCoord(x), CCoord(z))).collect()
+}
+
+struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn update_recomputes_only_changed_chunks() {
+    let dir = TempDir(std::env::temp_dir().join(format!("fastanvil-stats-{}", std::process::id())));
+    std::fs::create_dir_all(&dir.0).unwrap();
+    let loader = RegionFileLoader::<JavaChunk>::new(dir.0.clone());
+
+    // The fixture world's chunk copied around two regions, and a 1.17 chunk.
+    let chunk = fixture_chunk();
+    let r00 = dir.0.join("r.0.0.mca");
+    write_region(
+        &r00,
+        &[
+            (0, 0, &chunk, 1000),
+            (1, 0, &chunk, 1000),
+            (0, 1, &chunk, 1000),
+            (2, 2, CHUNK_1_17_0, 1000),
+        ],
+    );
+    let r10 = dir.0.join("r.-1.0.mca");
+    write_region(&r10, &[(31, 0, &chunk, 1000)]);
+
+    let mut cache = Cache::new();
+    let mut update = cache.update_from(&loader).unwrap();
+    update.recomputed.sort();
+    assert_eq!(
+        coords(&[(-1, 0), (0, 0), (0, 1), (1, 0), (2, 2)]),
+        update.recomputed
+    );
+    assert_eq!(5, cache.len());
+    assert!(update.failed.is_empty());
+
+    // Every block of the chunk is counted.
+    let parsed: JavaChunk = fastnbt::de::from_bytes(&chunk).unwrap();
+    let stats = cache.chunk(CCoord(0), CCoord(0)).unwrap();
+    let total: u32 = stats.blocks.values().sum();
+    assert_eq!(16 * 16 * parsed.y_range().len() as u32, total);
+    assert_eq!(1000, stats.timestamp);
+    assert_eq!(
+        4 * stats.block_count("minecraft:stone") as u64
+            + cache
+                .chunk(CCoord(2), CCoord(2))
+                .unwrap()
+                .block_count("minecraft:stone") as u64,
+        cache.block_count("minecraft:stone")
+    );
+
+    // Nothing changed, so nothing is read.
+    let update = cache.update_from(&loader).unwrap();
+    assert!(update.recomputed.is_empty());
+    assert_eq!(5, update.unchanged);
+
+    // Modify one chunk, and save another again without changing it.
+    let modified = with_inhabited_time(&chunk, 12345);
+    write_region(
+        &r00,
+        &[
+            (0, 0, &chunk, 1000),
+            (1, 0, &modified, 2000),
+            (0, 1, &chunk, 2000),
+            (2, 2, CHUNK_1_17_0, 1000),
+        ],
+    );
+
+    let before = cache.clone();
+    let update = cache.update_from(&loader).unwrap();
+    assert_eq!(coords(&[(1, 0)]), update.recomputed);
+    assert_eq!(1, update.touched);
+    assert_eq!(3, update.unchanged);
+    assert_eq!(0, update.removed);
+
+    let stats = cache.chunk(CCoord(1), CCoord(0)).unwrap();
+    assert_eq!(12345, stats.inhabited_time);
+    assert_eq!(2000, stats.timestamp);
+    assert_eq!(
+        before.chunk(CCoord(1), CCoord(0)).unwrap().blocks,
+        stats.blocks
+    );
+    assert_eq!(
+        before.chunk(CCoord(0), CCoord(1)).unwrap().hash,
+        cache.chunk(CCoord(0), CCoord(1)).unwrap().hash
+    );
+    assert_eq!(
+        before.inhabited_time() - before.chunk(CCoord(1), CCoord(0)).unwrap().inhabited_time
+            + 12345,
+        cache.inhabited_time()
+    );
+
+    // Chunks that are gone are dropped.
+    std::fs::remove_file(&r10).unwrap();
+    let update = cache.update_from(&loader).unwrap();
+    assert_eq!(1, update.removed);
+    assert_eq!(None, cache.chunk(CCoord(-1), CCoord(0)));
+    assert_eq!(4, cache.len());
+}
+
+#[test]
+fn unreadable_chunks_are_left_out() {
+    let dir = TempDir(
+        std::env::temp_dir().join(format!("fastanvil-stats-unreadable-{}", std::process::id())),
+    );
+    std::fs::create_dir_all(&dir.0).unwrap();
+    let loader = RegionFileLoader::<JavaChunk>::new(dir.0.clone());
+
+    let chunk = fixture_chunk();
+    write_region(
+        &dir.0.join("r.0.0.mca"),
+        &[(0, 0, &chunk, 1000), (1, 0, b"not nbt", 1000)],
+    );
+
+    let mut cache = Cache::new();
+    let update = cache.update_from(&loader).unwrap();
+    assert_eq!(coords(&[(0, 0)]), update.recomputed);
+    assert_eq!(coords(&[(1, 0)]), update.failed);
+    assert_eq!(1, cache.len());
+
+    // And tried again next time.
+    let update = cache.update_from(&loader).unwrap();
+    assert_eq!(coords(&[(1, 0)]), update.failed);
+}
+
+#[test]
+fn cache_saves_and_loads() {
+    let chunk = fixture_chunk();
+    let dir =
+        TempDir(std::env::temp_dir().join(format!("fastanvil-stats-save-{}", std::process::id())));
+    std::fs::create_dir_all(&dir.0).unwrap();
+    write_region(
+        &dir.0.join("r.0.0.mca"),
+        &[(0, 0, &chunk, 1000), (5, 7, CHUNK_1_17_0, 2000)],
+    );
+
+    let mut cache = Cache::new();
+    cache
+        .update_from(&RegionFileLoader::<JavaChunk>::new(dir.0.clone()))
+        .unwrap();
+
+    let path = dir.0.join("stats.nbt");
+    cache.save(&path).unwrap();
+    assert_eq!(cache, Cache::load(&path).unwrap());
+    assert!(Cache::load(dir.0.join("missing.nbt")).is_err());
+
+    // Block names are only stored once.
+    let saved = std::fs::read(&path).unwrap();
+    let mut nbt = Vec::new();
+    std::io::Read::read_to_end(&mut flate2::read::GzDecoder::new(&saved[..]), &mut nbt).unwrap();
+    let value: Value = fastnbt::de::from_bytes(&nbt).unwrap();
+    let blocks = match &value["Blocks"] {
+        Value::List(blocks) => blocks,
+        v => panic!("{:?}", v),
+    };
+    assert_eq!(cache.block_totals().len(), blocks.len());
+    assert_eq!(Some(SCHEMA_VERSION as i64), value["SchemaVersion"].as_i64());
+}
+
+#[test]
+fn other_schema_versions_load_empty() {
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Old {
+        schema_version: i32,
+        chunks: Vec<i32>,
+    }
+
+    let gzip = |v: &Old| {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &fastnbt::to_bytes(v).unwrap()).unwrap();
+        encoder.finish().unwrap()
+    };
+
+    for version in [0, SCHEMA_VERSION + 1] {
+        let data = gzip(&Old {
+            schema_version: version,
+            chunks: vec![1, 2, 3],
+        });
+        assert!(Cache::from_bytes(&data).unwrap().is_empty());
+    }
+
+    // But the current version must be in the current format.
+    let data = gzip(&Old {
+        schema_version: SCHEMA_VERSION,
+        chunks: vec![1, 2, 3],
+    });
+    assert!(Cache::from_bytes(&data).is_err());
+    assert!(Cache::from_bytes(b"not gzip").is_err());
+}
@@ -0,0 +1,128 @@
+use crate::java::{chunk_layout, ChunkLayout};
+use crate::support::*;
+use crate::try_expand_heightmap;
+
+#[test]
+fn pre_flattening_unsupported() {
+    for v in [0, 1343, FLATTENING - 1] {
+        let report = check(v);
+        assert_eq!(v, report.data_version);
+        assert_eq!(Support::Unsupported, report.blocks);
+        assert_eq!(Support::Unsupported, report.biomes);
+        assert_eq!(Support::Unsupported, report.heightmaps);
+        assert_eq!(Support::Unsupported, report.entities);
+    }
+}
+
+#[test]
+fn flattening() {
+    let report = check(FLATTENING);
+    assert_eq!(Support::Full, report.blocks);
+    assert_eq!(Support::Degraded(Reason::ColumnBiomes), report.biomes);
+    assert_eq!(Support::Full, report.heightmaps);
+    assert_eq!(Support::Degraded(Reason::EntitiesInChunk), report.entities);
+}
+
+#[test]
+fn biomes_3d() {
+    assert_eq!(
+        Support::Degraded(Reason::ColumnBiomes),
+        check(BIOMES_3D - 1).biomes
+    );
+    assert_eq!(Support::Full, check(BIOMES_3D).biomes);
+}
+
+#[test]
+fn entities_split() {
+    assert_eq!(
+        Support::Degraded(Reason::EntitiesInChunk),
+        check(ENTITIES_SPLIT - 1).entities
+    );
+    assert_eq!(Support::Full, check(ENTITIES_SPLIT).entities);
+}
+
+#[test]
+fn snapshot_heightmaps() {
+    let degraded = Support::Degraded(Reason::SnapshotHeightmaps);
+
+    assert_eq!(Support::Full, check(V1_16_5).heightmaps);
+    assert_eq!(degraded, check(V1_16_5 + 1).heightmaps);
+    assert_eq!(degraded, check(V1_17_0 - 1).heightmaps);
+    assert_eq!(Support::Full, check(V1_17_0).heightmaps);
+    assert_eq!(degraded, check(V1_17_0 + 1).heightmaps);
+    assert_eq!(Support::Full, check(V1_17_1).heightmaps);
+    assert_eq!(degraded, check(SNAPSHOT_21W37A - 1).heightmaps);
+    assert_eq!(Support::Full, check(SNAPSHOT_21W37A).heightmaps);
+}
+
+#[test]
+fn releases_fully_supported() {
+    // 1.16.5, 1.17.1, 1.18.2, 1.19.4, 1.20.4
+    for v in [V1_16_5, V1_17_1, 2975, 3337, NEWEST_KNOWN] {
+        let report = check(v);
+        assert_eq!(Support::Full, report.blocks, "{}", v);
+        assert_eq!(Support::Full, report.biomes, "{}", v);
+        assert_eq!(Support::Full, report.heightmaps, "{}", v);
+        // 1.16.5 still keeps entities in the chunk.
+        if v >= ENTITIES_SPLIT {
+            assert_eq!(Support::Full, report.entities, "{}", v);
+        }
+    }
+}
+
+#[test]
+fn newer_than_known() {
+    let report = check(NEWEST_KNOWN + 1);
+    let newer = Support::Degraded(Reason::NewerThanKnown);
+    assert_eq!(newer, report.blocks);
+    assert_eq!(newer, report.biomes);
+    assert_eq!(newer, report.heightmaps);
+    assert_eq!(newer, report.entities);
+}
+
+#[test]
+fn write_back_unsupported() {
+    for v in [1343, FLATTENING, V1_17_1, NEWEST_KNOWN, NEWEST_KNOWN + 1] {
+        assert_eq!(Support::Unsupported, check(v).write_back);
+    }
+}
+
+#[test]
+fn agrees_with_chunk_layout() {
+    assert_eq!(ChunkLayout::Pre18, chunk_layout(SNAPSHOT_21W37A - 1));
+    assert_eq!(ChunkLayout::Snapshot18, chunk_layout(SNAPSHOT_21W37A));
+    assert_eq!(ChunkLayout::Snapshot18, chunk_layout(SNAPSHOT_21W44A - 1));
+    assert_eq!(ChunkLayout::Post18, chunk_layout(SNAPSHOT_21W44A));
+
+    for v in [SNAPSHOT_21W37A - 1, SNAPSHOT_21W44A] {
+        assert_eq!(Support::Full, check(v).blocks);
+    }
+    let unverified = Support::Degraded(Reason::UnverifiedLayout);
+    assert_eq!(unverified, check(SNAPSHOT_21W37A).blocks);
+    assert_eq!(unverified, check(SNAPSHOT_21W44A - 1).blocks);
+}
+
+#[test]
+fn agrees_with_heightmap_parsing() {
+    // A heightmap of all 1s, 7 to a long.
+    let data = vec![0x0040_2010_0804_0201; 37];
+    let first = |v| try_expand_heightmap(&data, -64, v).unwrap()[0];
+
+    // Fully supported 1.17 onwards offsets by the bottom of the world.
+    assert_eq!(1, first(V1_16_5));
+    assert_eq!(-63, first(V1_17_0));
+    assert_eq!(-63, first(V1_17_1));
+    assert_eq!(-63, first(SNAPSHOT_21W37A));
+
+    // The snapshots between are read as 1.16.
+    assert_eq!(1, first(V1_17_0 + 1));
+    assert_eq!(1, first(SNAPSHOT_21W37A - 1));
+}
+
+#[test]
+fn reasons_display() {
+    assert_eq!(
+        "biomes do not vary with height",
+        Reason::ColumnBiomes.to_string()
+    );
+}
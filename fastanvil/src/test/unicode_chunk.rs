@@ -12,3 +12,59 @@ fn unicode_chunk() {
     let c: Result<JavaChunk> = fastnbt::de::from_bytes(UNICODE_CHUNK);
     assert!(c.is_ok());
 }
+
+#[test]
+fn block_entities_as_tagged_enum() {
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "id")]
+    enum BlockEntity {
+        #[serde(rename = "minecraft:shulker_box", rename_all = "camelCase")]
+        ShulkerBox {
+            #[serde(rename = "CustomName")]
+            custom_name: String,
+            #[serde(with = "fastnbt::flag")]
+            keep_packed: bool,
+            y: i32,
+        },
+
+        #[serde(other)]
+        Unknown,
+    }
+
+    #[derive(Deserialize)]
+    struct Chunk {
+        #[serde(rename = "Level")]
+        level: Level,
+    }
+
+    #[derive(Deserialize)]
+    struct Level {
+        #[serde(rename = "TileEntities")]
+        block_entities: Vec<BlockEntity>,
+    }
+
+    let chunk: Chunk = fastnbt::de::from_bytes(UNICODE_CHUNK).unwrap();
+    let entities = chunk.level.block_entities;
+
+    assert_eq!(4, entities.len());
+    let names: Vec<String> = entities
+        .into_iter()
+        .map(|entity| match entity {
+            BlockEntity::ShulkerBox {
+                custom_name,
+                keep_packed,
+                y,
+            } => {
+                assert!(!keep_packed);
+                assert_eq!(63, y);
+                custom_name
+            }
+            BlockEntity::Unknown => panic!("expected shulker box"),
+        })
+        .collect();
+
+    assert!(names.iter().any(|name| name.contains("日本語")));
+    assert!(names.iter().any(|name| name.contains("😈")));
+}
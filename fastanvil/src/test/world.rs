@@ -0,0 +1,131 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    CCoord, Chunk, DimensionId, JavaChunk, RCoord, RegionLoader, ResourceLocation, WorldMeta,
+    WorldSpec,
+};
+
+// A world with an overworld region and a single datapack dimension,
+// `example:sky`, whose dimension type is defined in an unzipped datapack. Both
+// regions contain the 21w44a chunk at 0,0.
+fn world_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/worlds/datapack-dimension")
+}
+
+fn sky() -> DimensionId {
+    DimensionId::Custom(ResourceLocation::new("example", "sky"))
+}
+
+#[test]
+fn discovers_datapack_dimension() {
+    let world = WorldMeta::discover(world_dir()).unwrap();
+    let dims: Vec<_> = world.dimensions().cloned().collect();
+    assert_eq!(vec![DimensionId::Overworld, sky()], dims);
+}
+
+#[test]
+fn reads_chunk_from_datapack_dimension() {
+    let world = WorldMeta::discover(world_dir()).unwrap();
+    let loader = world.loader::<JavaChunk>(&sky()).unwrap();
+
+    assert_eq!(vec![(RCoord(0), RCoord(0))], loader.list().unwrap());
+
+    let region = loader.region(RCoord(0), RCoord(0)).unwrap();
+    let chunk = region.chunk(CCoord(0), CCoord(0)).unwrap();
+    assert_eq!("full", chunk.status());
+    assert_eq!(-64..320, chunk.y_range());
+}
+
+fn spec(y_range: Range<isize>) -> WorldSpec {
+    WorldSpec {
+        y_range,
+        region_dir: None,
+    }
+}
+
+#[test]
+fn spec_from_datapack_dimension_type() {
+    let world = WorldMeta::discover(world_dir()).unwrap();
+    assert_eq!(Some(&spec(-64..320)), world.spec(&sky()));
+    assert_eq!(None, world.spec(&DimensionId::Overworld));
+}
+
+#[test]
+fn spec_can_be_overridden() {
+    let mut world = WorldMeta::discover(world_dir()).unwrap();
+    world.set_spec(&sky(), spec(0..256));
+    assert_eq!(Some(&spec(0..256)), world.spec(&sky()));
+
+    // Dimensions not in the world are ignored.
+    world.set_spec(&DimensionId::Nether, spec(0..256));
+    assert_eq!(None, world.spec(&DimensionId::Nether));
+}
+
+#[test]
+fn spec_region_dir_is_read_by_loader() {
+    // The sky's region, moved to a directory of its own at other coordinates.
+    let dir = std::env::temp_dir().join(format!("fastanvil-world-spec-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(
+        sky().region_dir(&world_dir()).join("r.0.0.mca"),
+        dir.join("r.1.-1.mca"),
+    )
+    .unwrap();
+
+    let mut world = WorldMeta::discover(world_dir()).unwrap();
+    world.set_spec(
+        &sky(),
+        WorldSpec {
+            y_range: -64..320,
+            region_dir: Some(dir.clone()),
+        },
+    );
+    let loader = world.loader::<JavaChunk>(&sky()).unwrap();
+    let listed = loader.list().unwrap();
+    let status = loader
+        .region(RCoord(1), RCoord(-1))
+        .and_then(|region| region.chunk(CCoord(0), CCoord(0)))
+        .map(|chunk| chunk.status());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(vec![(RCoord(1), RCoord(-1))], listed);
+    assert_eq!(Some("full".to_owned()), status);
+}
+
+#[test]
+fn no_loader_for_missing_dimension() {
+    let world = WorldMeta::discover(world_dir()).unwrap();
+    assert!(world.loader::<JavaChunk>(&DimensionId::End).is_none());
+}
+
+#[test]
+fn region_dirs() {
+    let world = Path::new("world");
+    assert_eq!(
+        world.join("region"),
+        DimensionId::Overworld.region_dir(world)
+    );
+    assert_eq!(
+        world.join("DIM-1/region"),
+        DimensionId::Nether.region_dir(world)
+    );
+    assert_eq!(
+        world.join("DIM1/region"),
+        DimensionId::End.region_dir(world)
+    );
+    assert_eq!(
+        world.join("dimensions/example/islands/sky/region"),
+        DimensionId::Custom(ResourceLocation::new("example", "islands/sky")).region_dir(world)
+    );
+}
+
+#[test]
+fn parse_dimension_id() {
+    assert_eq!(DimensionId::Overworld, "overworld".parse().unwrap());
+    assert_eq!(DimensionId::Nether, "nether".parse().unwrap());
+    assert_eq!(DimensionId::End, "minecraft:the_end".parse().unwrap());
+    assert_eq!(sky(), "example:sky".parse().unwrap());
+    assert!("example:".parse::<DimensionId>().is_err());
+    assert_eq!("example:sky", sky().to_string());
+}
@@ -0,0 +1,19 @@
+//! Optional instrumentation using `tracing`. With the `tracing` feature
+//! disabled these macros expand to nothing, so none of the field values are
+//! evaluated.
+
+/// Enter a span at the given level for the rest of the enclosing block.
+macro_rules! span {
+    ($level:ident, $($args:tt)+) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::span!(tracing::Level::$level, $($args)+).entered();
+    };
+}
+
+/// Emit an event at the given level.
+macro_rules! event {
+    ($level:ident, $($args:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::event!(tracing::Level::$level, $($args)+);
+    };
+}
@@ -0,0 +1,353 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::Deserialize;
+
+use crate::{Chunk, LevelDat, LoaderError, LoaderResult, RegionFileLoader};
+
+/// A namespaced identifier, eg `minecraft:the_nether`. If no namespace is
+/// given when parsing, `minecraft` is assumed like the game does.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ResourceLocation {
+    namespace: String,
+    path: String,
+}
+
+impl ResourceLocation {
+    pub fn new(namespace: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            path: path.into(),
+        }
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// The path part of the location. This can contain `/`, eg for a
+    /// dimension stored in `dimensions/example/islands/sky`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl FromStr for ResourceLocation {
+    type Err = LoaderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (namespace, path) = match s.split_once(':') {
+            Some((namespace, path)) => (namespace, path),
+            None => ("minecraft", s),
+        };
+
+        if namespace.is_empty() || path.is_empty() {
+            return Err(LoaderError(format!("invalid resource location: {:?}", s)));
+        }
+
+        Ok(Self::new(namespace, path))
+    }
+}
+
+impl Display for ResourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.path)
+    }
+}
+
+/// Identifies a dimension of a world. The vanilla dimensions are stored in
+/// their own folders for backwards compatibility, other dimensions (eg from
+/// datapacks in 1.16+) are stored under `dimensions/<namespace>/<path>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DimensionId {
+    Overworld,
+    Nether,
+    End,
+    Custom(ResourceLocation),
+}
+
+impl DimensionId {
+    /// The resource location the game uses for this dimension.
+    pub fn resource_location(&self) -> ResourceLocation {
+        match self {
+            DimensionId::Overworld => ResourceLocation::new("minecraft", "overworld"),
+            DimensionId::Nether => ResourceLocation::new("minecraft", "the_nether"),
+            DimensionId::End => ResourceLocation::new("minecraft", "the_end"),
+            DimensionId::Custom(loc) => loc.clone(),
+        }
+    }
+
+    /// The directory containing the region files of this dimension, given the
+    /// directory of the world.
+    pub fn region_dir(&self, world_dir: &Path) -> PathBuf {
+        match self {
+            DimensionId::Overworld => world_dir.join("region"),
+            DimensionId::Nether => world_dir.join("DIM-1").join("region"),
+            DimensionId::End => world_dir.join("DIM1").join("region"),
+            DimensionId::Custom(loc) => {
+                let mut dir = world_dir.join("dimensions").join(loc.namespace());
+                dir.extend(loc.path().split('/'));
+                dir.join("region")
+            }
+        }
+    }
+}
+
+impl From<ResourceLocation> for DimensionId {
+    fn from(loc: ResourceLocation) -> Self {
+        match (loc.namespace(), loc.path()) {
+            ("minecraft", "overworld") => DimensionId::Overworld,
+            ("minecraft", "the_nether") => DimensionId::Nether,
+            ("minecraft", "the_end") => DimensionId::End,
+            _ => DimensionId::Custom(loc),
+        }
+    }
+}
+
+/// Parses either a resource location, or one of the short names `overworld`,
+/// `nether` or `end`.
+impl FromStr for DimensionId {
+    type Err = LoaderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nether" => Ok(DimensionId::Nether),
+            "end" => Ok(DimensionId::End),
+            _ => Ok(s.parse::<ResourceLocation>()?.into()),
+        }
+    }
+}
+
+impl Display for DimensionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.resource_location().fmt(f)
+    }
+}
+
+/// Properties of a dimension that cannot always be determined from its
+/// chunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldSpec {
+    /// The range of valid Y values in the dimension.
+    pub y_range: Range<isize>,
+    /// The directory to read the dimension's regions from, if not the usual
+    /// one from [`DimensionId::region_dir`]. Relative paths are relative to
+    /// the world directory.
+    pub region_dir: Option<PathBuf>,
+}
+
+/// WorldMeta describes the dimensions present in a world directory.
+///
+/// ```no_run
+/// # use fastanvil::{JavaChunk, RegionLoader, WorldMeta};
+/// let world = WorldMeta::discover("path/to/world").unwrap();
+///
+/// for id in world.dimensions() {
+///     let loader = world.loader::<JavaChunk>(id).unwrap();
+///     println!("{}: {} regions", id, loader.list().unwrap().len());
+/// }
+/// ```
+pub struct WorldMeta {
+    dir: PathBuf,
+    dimensions: BTreeMap<DimensionId, Option<WorldSpec>>,
+}
+
+impl WorldMeta {
+    /// Find the dimensions present in the given world directory. A dimension is
+    /// present if its region directory exists.
+    ///
+    /// For custom dimensions the [`WorldSpec`] is read from the dimension type
+    /// in the world's datapacks if possible. Zipped datapacks and dimensions
+    /// using a vanilla dimension type are not inspected, see
+    /// [`set_spec`][`WorldMeta::set_spec`] to provide the spec yourself.
+    pub fn discover(dir: impl Into<PathBuf>) -> LoaderResult<Self> {
+        let dir = dir.into();
+        if !dir.is_dir() {
+            return Err(LoaderError(format!(
+                "world directory {} does not exist",
+                dir.display()
+            )));
+        }
+
+        let mut dimensions = BTreeMap::new();
+
+        for id in [
+            DimensionId::Overworld,
+            DimensionId::Nether,
+            DimensionId::End,
+        ] {
+            if id.region_dir(&dir).is_dir() {
+                dimensions.insert(id, None);
+            }
+        }
+
+        for namespace in read_dirs(&dir.join("dimensions"))? {
+            let ns = match namespace.file_name().and_then(|n| n.to_str()) {
+                Some(ns) => ns.to_owned(),
+                None => continue,
+            };
+
+            let mut paths = vec![];
+            find_dimension_paths(&namespace, "", &mut paths)?;
+
+            for path in paths {
+                let loc = ResourceLocation::new(ns.clone(), path);
+                let spec = datapack_spec(&dir, &loc);
+                dimensions.insert(DimensionId::Custom(loc), spec);
+            }
+        }
+
+        Ok(Self { dir, dimensions })
+    }
+
+    /// The directory of the world.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The dimensions found in the world, vanilla dimensions first.
+    pub fn dimensions(&self) -> impl Iterator<Item = &DimensionId> {
+        self.dimensions.keys()
+    }
+
+    /// Get the spec for the dimension, if known.
+    pub fn spec(&self, id: &DimensionId) -> Option<&WorldSpec> {
+        self.dimensions.get(id)?.as_ref()
+    }
+
+    /// Override the spec of a dimension, for when it cannot be found from the
+    /// world's datapacks. Has no effect if the dimension is not in the world.
+    pub fn set_spec(&mut self, id: &DimensionId, spec: WorldSpec) {
+        if let Some(s) = self.dimensions.get_mut(id) {
+            *s = Some(spec);
+        }
+    }
+
+    /// Read the world's `level.dat`.
+    pub fn level_dat(&self) -> LoaderResult<LevelDat> {
+        let path = self.dir.join("level.dat");
+        let data = fs::read(&path)
+            .map_err(|e| LoaderError(format!("could not read {}: {}", path.display(), e)))?;
+        LevelDat::from_bytes(&data)
+    }
+
+    /// Create a loader for the regions of the given dimension, from the region
+    /// directory of its spec if it has one. Returns None if the dimension is
+    /// not in the world.
+    pub fn loader<C: Chunk>(&self, id: &DimensionId) -> Option<RegionFileLoader<C>> {
+        let spec = self.dimensions.get(id)?;
+        let region_dir = match spec.as_ref().and_then(|s| s.region_dir.as_ref()) {
+            Some(dir) => self.dir.join(dir),
+            None => id.region_dir(&self.dir),
+        };
+        Some(RegionFileLoader::new(region_dir))
+    }
+}
+
+/// List the directories within `dir`. A missing `dir` is treated as empty.
+fn read_dirs(dir: &Path) -> LoaderResult<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let entries = fs::read_dir(dir).map_err(|e| LoaderError(e.to_string()))?;
+    let mut dirs: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Dimension paths can contain `/`, so any directory below a namespace that
+/// contains a `region` directory is a dimension.
+fn find_dimension_paths(dir: &Path, prefix: &str, out: &mut Vec<String>) -> LoaderResult<()> {
+    for sub in read_dirs(dir)? {
+        let name = match sub.file_name().and_then(|n| n.to_str()) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        if name == "region" {
+            if !prefix.is_empty() {
+                out.push(prefix.to_owned());
+            }
+            continue;
+        }
+
+        let path = if prefix.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+
+        find_dimension_paths(&sub, &path, out)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct DimensionJson {
+    #[serde(rename = "type")]
+    dimension_type: DimensionTypeRef,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DimensionTypeRef {
+    Named(String),
+    Inline(DimensionTypeJson),
+}
+
+#[derive(Deserialize)]
+struct DimensionTypeJson {
+    #[serde(default)]
+    min_y: isize,
+    height: isize,
+}
+
+/// Find the spec for a custom dimension from the world's datapacks. Any
+/// problem reading the datapacks just means we don't know the spec.
+fn datapack_spec(world_dir: &Path, loc: &ResourceLocation) -> Option<WorldSpec> {
+    let dim: DimensionJson = read_datapack_json(world_dir, loc, "dimension")?;
+
+    let dim_type = match dim.dimension_type {
+        DimensionTypeRef::Inline(t) => t,
+        DimensionTypeRef::Named(name) => {
+            let type_loc: ResourceLocation = name.parse().ok()?;
+            read_datapack_json(world_dir, &type_loc, "dimension_type")?
+        }
+    };
+
+    Some(WorldSpec {
+        y_range: dim_type.min_y..dim_type.min_y + dim_type.height,
+        region_dir: None,
+    })
+}
+
+fn read_datapack_json<T: serde::de::DeserializeOwned>(
+    world_dir: &Path,
+    loc: &ResourceLocation,
+    kind: &str,
+) -> Option<T> {
+    read_dirs(&world_dir.join("datapacks"))
+        .ok()?
+        .into_iter()
+        .find_map(|pack| {
+            let mut path = pack.join("data").join(loc.namespace()).join(kind);
+            path.extend(loc.path().split('/'));
+            path.set_extension("json");
+
+            let file = fs::File::open(path).ok()?;
+            serde_json::from_reader(file).ok()
+        })
+}
@@ -0,0 +1,93 @@
+//! Uses fastnbt from a `#![no_std]` crate, with fastnbt's default `std`
+//! feature off. Build or test this crate on its own, eg
+//! `cargo test -p fastnbt-no-std`, as building it along with the rest of the
+//! workspace turns `std` back on for fastnbt.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
+
+use fastnbt::{borrow, de::from_bytes, error::Result, LongArray, Value};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Level {
+    pub name: String,
+    pub data_version: i32,
+    pub block_states: LongArray,
+    pub sections: Vec<i8>,
+}
+
+/// Read a level with typed fields.
+pub fn level(data: &[u8]) -> Result<Level> {
+    from_bytes(data)
+}
+
+/// Read the name of a level through [`Value`].
+pub fn name_from_value(data: &[u8]) -> Result<Option<String>> {
+    let value: Value = from_bytes(data)?;
+    Ok(value
+        .as_compound()
+        .and_then(|c| c.get("Name"))
+        .and_then(|name| name.as_str())
+        .map(String::from))
+}
+
+/// Read the data version of a level through [`borrow::Compound`], only
+/// parsing the entry that is needed.
+pub fn data_version_borrowed(data: &[u8]) -> Result<Option<i32>> {
+    let compound: borrow::Compound = from_bytes(data)?;
+    compound.get("DataVersion")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A compound with `Name: "world"`, `DataVersion: 2586`,
+    /// `BlockStates: [L; 1, -1]` and `Sections: [0b, 1b]`. Written out by
+    /// hand, as the serializer needs std.
+    #[rustfmt::skip]
+    const LEVEL: &[u8] = &[
+        10, 0, 0,
+        8, 0, 4, b'N', b'a', b'm', b'e', 0, 5, b'w', b'o', b'r', b'l', b'd',
+        3, 0, 11, b'D', b'a', b't', b'a', b'V', b'e', b'r', b's', b'i', b'o', b'n', 0, 0, 0x0a, 0x1a,
+        12, 0, 11, b'B', b'l', b'o', b'c', b'k', b'S', b't', b'a', b't', b'e', b's', 0, 0, 0, 2,
+        0, 0, 0, 0, 0, 0, 0, 1,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        9, 0, 8, b'S', b'e', b'c', b't', b'i', b'o', b'n', b's', 1, 0, 0, 0, 2, 0, 1,
+        0,
+    ];
+
+    #[test]
+    fn typed() {
+        let level = level(LEVEL).unwrap();
+        assert_eq!("world", level.name);
+        assert_eq!(2586, level.data_version);
+        assert_eq!(&[1, -1], &*level.block_states);
+        assert_eq!(alloc::vec![0, 1], level.sections);
+    }
+
+    #[test]
+    fn value() {
+        assert_eq!(Some("world".into()), name_from_value(LEVEL).unwrap());
+    }
+
+    #[test]
+    fn borrowed() {
+        assert_eq!(Some(2586), data_version_borrowed(LEVEL).unwrap());
+
+        let value: borrow::Value = from_bytes(LEVEL).unwrap();
+        let sections = value.as_compound().and_then(|c| c.get("Sections"));
+        assert_eq!(Some(2), sections.and_then(|s| s.as_list()).map(|l| l.len()));
+    }
+
+    #[test]
+    fn errors() {
+        let err = from_bytes::<Value>(&LEVEL[..6]).unwrap_err();
+        assert!(err.is_eof());
+    }
+}
@@ -1,40 +1,107 @@
-use std::ops::Deref;
+use alloc::format;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt;
+use core::iter::FromIterator;
+use core::marker::PhantomData;
+use core::ops::{Deref, Index};
+use core::slice::SliceIndex;
 
+use serde::de::{self, IntoDeserializer};
 use serde::{Deserialize, Serialize};
 
-use crate::{CompTag, BYTE_ARRAY_TAG, INT_ARRAY_TAG, LONG_ARRAY_TAG};
+use crate::{
+    CompTag, BYTE_ARRAY_CHUNK_TOKEN, BYTE_ARRAY_TAG, BYTE_ARRAY_TOKEN, INT_ARRAY_CHUNK_TOKEN,
+    INT_ARRAY_TAG, INT_ARRAY_TOKEN, LONG_ARRAY_CHUNK_TOKEN, LONG_ARRAY_TAG, LONG_ARRAY_TOKEN,
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+// The deserialize names let the deserializer tell these apart from other
+// structs, so that it can accept a List in their place when asked to. See
+// `DeOpts::lenient_arrays`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename(deserialize = "__fastnbt_byte_array"))]
 pub struct ByteArray {
     tag: CompTag<BYTE_ARRAY_TAG>,
+    #[serde(deserialize_with = "deserialize_data")]
     data: Vec<i8>,
 }
 
-impl Deref for ByteArray {
-    type Target = Vec<i8>;
+impl ByteArray {
+    pub fn new(data: Vec<i8>) -> Self {
+        Self {
+            tag: CompTag::<BYTE_ARRAY_TAG>,
+            data,
+        }
+    }
+
+    /// Whether bit `index` is set, treating the array as a bitset as
+    /// Minecraft does for carving masks. Bits are numbered from the least
+    /// significant bit of the first byte. None if `index` is past the end.
+    pub fn bit(&self, index: usize) -> Option<bool> {
+        self.data
+            .get(index / 8)
+            .map(|&b| bit_of(b as u8, index % 8))
+    }
+
+    /// Every bit of the array, in the order of [`bit`][Self::bit].
+    pub fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        self.data.iter().flat_map(|&b| bits_of(b as u8))
+    }
+
+    /// The number of bits set.
+    pub fn count_ones(&self) -> usize {
+        self.data.iter().map(|&b| b.count_ones() as usize).sum()
+    }
+}
+
+pub(crate) fn bit_of(byte: u8, bit: usize) -> bool {
+    (byte >> bit) & 1 == 1
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.data
+pub(crate) fn bits_of(byte: u8) -> impl Iterator<Item = bool> {
+    (0..8).map(move |bit| bit_of(byte, bit))
+}
+
+impl Serialize for ByteArray {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(BYTE_ARRAY_TOKEN, &self.data)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename(deserialize = "__fastnbt_int_array"))]
 pub struct IntArray {
     tag: CompTag<INT_ARRAY_TAG>,
+    #[serde(deserialize_with = "deserialize_data")]
     data: Vec<i32>,
 }
 
-impl Deref for IntArray {
-    type Target = Vec<i32>;
+impl IntArray {
+    pub fn new(data: Vec<i32>) -> Self {
+        Self {
+            tag: CompTag::<INT_ARRAY_TAG>,
+            data,
+        }
+    }
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.data
+impl Serialize for IntArray {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(INT_ARRAY_TOKEN, &self.data)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename(deserialize = "__fastnbt_long_array"))]
 pub struct LongArray {
     tag: CompTag<LONG_ARRAY_TAG>,
+    #[serde(deserialize_with = "deserialize_data")]
     data: Vec<i64>,
 }
 
@@ -45,12 +112,336 @@ impl LongArray {
             data,
         }
     }
+
+    /// Unpack bit-packed entries of `bits_per_entry` bits each, such as block
+    /// states or heightmaps, filling `out`. Entries start from the least
+    /// significant bit of the first long.
+    ///
+    /// Since Minecraft 1.16 entries are `padded`, so that none crosses from
+    /// one long into the next, leaving any spare bits at the top of each long
+    /// unused. Before then entries follow on directly, so one can straddle
+    /// two longs.
+    ///
+    /// Errors if `bits_per_entry` is not from 1 to 16, or if the array holds
+    /// fewer entries than `out` needs. Entries past the end of `out`, such as
+    /// the padding at the end of 1.16 block states, are ignored.
+    pub fn unpack_into(
+        &self,
+        bits_per_entry: usize,
+        padded: bool,
+        out: &mut [u16],
+    ) -> crate::error::Result<()> {
+        unpack_bits(
+            self.data.iter().copied(),
+            self.len(),
+            bits_per_entry,
+            padded,
+            out,
+        )
+    }
+}
+
+/// Unpack entries of `bits` bits from `len` longs, for `unpack_into` on
+/// either long array.
+pub(crate) fn unpack_bits(
+    mut longs: impl Iterator<Item = i64>,
+    len: usize,
+    bits: usize,
+    padded: bool,
+    out: &mut [u16],
+) -> crate::error::Result<()> {
+    if !(1..=16).contains(&bits) {
+        return Err(crate::error::Error::bespoke(format!(
+            "cannot unpack {} bit entries, entries must be 1 to 16 bits",
+            bits
+        )));
+    }
+
+    let needed = if padded {
+        out.len().div_ceil(64 / bits)
+    } else {
+        (out.len() * bits).div_ceil(64)
+    };
+    if needed > len {
+        return Err(crate::error::Error::bespoke(format!(
+            "{} longs of {} bit entries is too few to fill {} entries",
+            len,
+            bits,
+            out.len()
+        )));
+    }
+
+    let mask = (1 << bits) - 1;
+    if padded {
+        for (entries, long) in out.chunks_mut(64 / bits).zip(longs) {
+            let mut long = long as u64;
+            for entry in entries {
+                *entry = (long & mask) as u16;
+                long >>= bits;
+            }
+        }
+    } else {
+        // Bits not yet unpacked, always fewer than one entry's worth before
+        // the next long is added.
+        let mut pending: u128 = 0;
+        let mut pending_bits = 0;
+        for entry in out {
+            if pending_bits < bits {
+                pending |= (longs.next().unwrap_or(0) as u64 as u128) << pending_bits;
+                pending_bits += 64;
+            }
+            *entry = (pending as u64 & mask) as u16;
+            pending >>= bits;
+            pending_bits -= bits;
+        }
+    }
+    Ok(())
+}
+
+impl Serialize for LongArray {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(LONG_ARRAY_TOKEN, &self.data)
+    }
+}
+
+/// The conversions and collection traits of one of the owned array types.
+macro_rules! array_impls {
+    ($array:ident, $element:ty) => {
+        impl $array {
+            /// The number of elements.
+            pub fn len(&self) -> usize {
+                self.data.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.data.is_empty()
+            }
+
+            pub fn as_slice(&self) -> &[$element] {
+                &self.data
+            }
+
+            /// Release any spare capacity the array holds.
+            pub fn shrink_to_fit(&mut self) {
+                self.data.shrink_to_fit()
+            }
+
+            /// Take the elements out of the array.
+            pub fn into_inner(self) -> Vec<$element> {
+                self.data
+            }
+        }
+
+        impl Deref for $array {
+            type Target = [$element];
+
+            fn deref(&self) -> &Self::Target {
+                &self.data
+            }
+        }
+
+        impl<I: SliceIndex<[$element]>> Index<I> for $array {
+            type Output = I::Output;
+
+            fn index(&self, index: I) -> &Self::Output {
+                &self.data[index]
+            }
+        }
+
+        impl From<Vec<$element>> for $array {
+            fn from(data: Vec<$element>) -> Self {
+                Self::new(data)
+            }
+        }
+
+        impl From<$array> for Vec<$element> {
+            fn from(array: $array) -> Self {
+                array.data
+            }
+        }
+
+        impl FromIterator<$element> for $array {
+            fn from_iter<T: IntoIterator<Item = $element>>(iter: T) -> Self {
+                Self::new(iter.into_iter().collect())
+            }
+        }
+
+        impl IntoIterator for $array {
+            type Item = $element;
+            type IntoIter = alloc::vec::IntoIter<$element>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.data.into_iter()
+            }
+        }
+
+        impl<'a> IntoIterator for &'a $array {
+            type Item = &'a $element;
+            type IntoIter = core::slice::Iter<'a, $element>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.data.iter()
+            }
+        }
+    };
+}
+
+array_impls!(ByteArray, i8);
+array_impls!(IntArray, i32);
+array_impls!(LongArray, i64);
+
+/// The element type of one of the owned array types.
+pub(crate) trait Element: Sized + for<'de> Deserialize<'de> {
+    /// The newtype struct name elements are asked for with, so that our
+    /// deserializer can recognise them and hand over a chunk of them at once.
+    const CHUNK_TOKEN: &'static str;
+
+    /// Decode a chunk of big-endian elements onto the end of `data`.
+    fn extend_from_be(data: &mut Vec<Self>, chunk: &[u8]);
+}
+
+impl Element for i8 {
+    const CHUNK_TOKEN: &'static str = BYTE_ARRAY_CHUNK_TOKEN;
+
+    fn extend_from_be(data: &mut Vec<Self>, chunk: &[u8]) {
+        data.extend(chunk.iter().map(|&b| b as i8));
+    }
+}
+
+impl Element for i32 {
+    const CHUNK_TOKEN: &'static str = INT_ARRAY_CHUNK_TOKEN;
+
+    fn extend_from_be(data: &mut Vec<Self>, chunk: &[u8]) {
+        data.extend(
+            chunk
+                .chunks_exact(4)
+                .map(|b| i32::from_be_bytes(b.try_into().unwrap())),
+        );
+    }
+}
+
+impl Element for i64 {
+    const CHUNK_TOKEN: &'static str = LONG_ARRAY_CHUNK_TOKEN;
+
+    fn extend_from_be(data: &mut Vec<Self>, chunk: &[u8]) {
+        data.extend(
+            chunk
+                .chunks_exact(8)
+                .map(|b| i64::from_be_bytes(b.try_into().unwrap())),
+        );
+    }
+}
+
+// Block states and light arrays are the bulk of a chunk. Left to serde, each
+// element would be a separate read and visit, so instead our deserializer
+// hands over big-endian elements a chunk at a time, which are decoded straight
+// into the array. Other deserializers, and other endians, see an ordinary
+// sequence of elements.
+fn deserialize_data<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Element,
+{
+    deserializer.deserialize_seq(DataVisitor(PhantomData))
+}
+
+struct DataVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Element> de::Visitor<'de> for DataVisitor<T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        // Like serde's own Vec, don't trust the size too far up front, it may
+        // not have been checked against the input.
+        let max = 1024 * 1024 / core::mem::size_of::<T>();
+        let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(max));
+        while seq.next_element_seed(Extend(&mut data))?.is_some() {}
+        Ok(data)
+    }
+}
+
+/// Adds one element, or a chunk of them, to an array.
+struct Extend<'a, T>(&'a mut Vec<T>);
+
+impl<'a, 'de, T: Element> de::DeserializeSeed<'de> for Extend<'a, T> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(T::CHUNK_TOKEN, self)
+    }
+}
+
+macro_rules! visit_element {
+    ($($visit:ident: $ty:ty),*) => {
+        $(
+            fn $visit<E: de::Error>(self, v: $ty) -> Result<(), E> {
+                self.0.push(T::deserialize(v.into_deserializer())?);
+                Ok(())
+            }
+        )*
+    };
 }
 
-impl Deref for LongArray {
-    type Target = Vec<i64>;
+impl<'a, 'de, T: Element> de::Visitor<'de> for Extend<'a, T> {
+    type Value = ();
 
-    fn deref(&self) -> &Self::Target {
-        &self.data
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an integer")
     }
+
+    // Only our deserializer visits bytes, with a chunk of elements.
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<(), E> {
+        T::extend_from_be(self.0, v);
+        Ok(())
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        self.0.push(T::deserialize(deserializer)?);
+        Ok(())
+    }
+
+    visit_element! {
+        visit_i8: i8, visit_i16: i16, visit_i32: i32, visit_i64: i64,
+        visit_u8: u8, visit_u16: u16, visit_u32: u32, visit_u64: u64
+    }
+}
+
+// 128-bit integers, such as the UUIDs of entities, are stored as an IntArray
+// of four ints, most significant first.
+
+pub(crate) fn int_array_to_u128(ints: [i32; 4]) -> u128 {
+    ints.iter()
+        .fold(0, |acc, &int| (acc << 32) | int as u32 as u128)
+}
+
+pub(crate) fn u128_to_int_array(v: u128) -> [i32; 4] {
+    [
+        (v >> 96) as u32 as i32,
+        (v >> 64) as u32 as i32,
+        (v >> 32) as u32 as i32,
+        v as u32 as i32,
+    ]
+}
+
+pub(crate) fn u128_array_len_error(len: impl core::fmt::Display) -> crate::error::Error {
+    crate::error::Error::bespoke(format!(
+        "expected an IntArray of length 4 for a 128-bit integer, found length {}",
+        len
+    ))
 }
@@ -16,6 +16,18 @@
 //! The `iter()` methods return an iterator to the values read on demand from an
 //! internal reference to the input data.
 //!
+//! The arrays serialize as NBT array tags with our serializer, copying the
+//! data straight to the output when it is already big-endian. Other
+//! serializers see a sequence of the elements like the owned arrays.
+//!
+//! [`Str`] is a string that borrows from the input where it can.
+//!
+//! [`Value`] is a version of [`crate::Value`] that borrows its strings,
+//! arrays and compound keys, for scanning lots of NBT without knowing its
+//! structure.
+//!
+//! [`Compound`] skips over a compound, parsing only the entries asked for.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -40,106 +52,1339 @@
 //!     }
 //!# }
 
-use std::{borrow::Cow, fmt};
+use alloc::borrow::{Cow, ToOwned};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::{TryFrom, TryInto};
+use core::fmt;
+use core::iter::FusedIterator;
+use core::ops::Deref;
 
-use byteorder::{BigEndian, ReadBytesExt};
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Serialize};
 
-use crate::{CompTag, BYTE_ARRAY_TAG, INT_ARRAY_TAG, LONG_ARRAY_TAG};
+use crate::arrays::{bit_of, bits_of, Element};
+use crate::{
+    CompTag, Endian, Map, Tag, BYTE_ARRAY_TAG, BYTE_ARRAY_TOKEN, INT_ARRAY_TAG, INT_ARRAY_TOKEN,
+    LONG_ARRAY_TAG, LONG_ARRAY_TOKEN,
+};
 
 /// ByteArray can be used to deserialize the NBT data of the same name. This
 /// borrows from the original input data when deserializing. The carving masks
 /// in a chunk use this type, for example.
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename(deserialize = "__fastnbt_byte_array"))]
 pub struct ByteArray<'a> {
-    tag: CompTag<BYTE_ARRAY_TAG>,
+    /// Only checked when deserializing, so that one type of array can't be
+    /// read as another.
+    #[serde(rename = "tag")]
+    _tag: CompTag<BYTE_ARRAY_TAG>,
     data: &'a [u8],
 }
 
 impl<'a> ByteArray<'a> {
+    /// An array over existing data, for example to write it out again.
+    pub fn from_be_bytes(data: &'a [u8]) -> Self {
+        Self {
+            _tag: CompTag::<BYTE_ARRAY_TAG>,
+            data,
+        }
+    }
+
+    /// The array's data, exactly as it was in the NBT. Bytes are the same in
+    /// either endian.
+    pub fn as_be_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The number of bytes.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The size of the array's data in the NBT, which for bytes is the same
+    /// as its [`len`][Self::len].
+    pub fn size_in_bytes(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The element at `index`, read directly from the data rather than
+    /// iterating to it.
+    pub fn get(&self, index: usize) -> Option<i8> {
+        self.data.get(index).map(|&b| b as i8)
+    }
+
+    /// The element at `index`, like indexing a slice.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of range.
+    pub fn at(&self, index: usize) -> i8 {
+        match self.get(index) {
+            Some(v) => v,
+            None => out_of_range(index, self.len()),
+        }
+    }
+
+    /// Decode every element at once, into a `Vec` that no longer borrows
+    /// from the input.
+    pub fn to_vec(&self) -> Vec<i8> {
+        let mut data = Vec::with_capacity(self.len());
+        self.copy_into(&mut data);
+        data
+    }
+
+    /// Replace the contents of `out` with every element, so that one buffer
+    /// can be reused for many arrays.
+    pub fn copy_into(&self, out: &mut Vec<i8>) {
+        out.clear();
+        i8::extend_from_be(out, self.data);
+    }
+
+    /// Fill `out` with every element. It must be the same length as the
+    /// array.
+    pub fn copy_into_slice(&self, out: &mut [i8]) -> crate::error::Result<()> {
+        check_copy_len(self.len(), out.len())?;
+        for (o, &b) in out.iter_mut().zip(self.data) {
+            *o = b as i8;
+        }
+        Ok(())
+    }
+
+    /// Copy into the owned [`ByteArray`][crate::ByteArray], which no longer
+    /// borrows from the input.
+    pub fn to_owned(&self) -> crate::ByteArray {
+        crate::ByteArray::new(self.to_vec())
+    }
+
+    /// Whether bit `index` is set, treating the array as a bitset as
+    /// Minecraft does for carving masks. Bits are numbered from the least
+    /// significant bit of the first byte. None if `index` is past the end.
+    pub fn bit(&self, index: usize) -> Option<bool> {
+        self.data.get(index / 8).map(|&b| bit_of(b, index % 8))
+    }
+
+    /// Every bit of the array, in the order of [`bit`][Self::bit].
+    pub fn bits(&self) -> impl Iterator<Item = bool> + 'a {
+        self.data.iter().flat_map(|&b| bits_of(b))
+    }
+
+    /// The number of bits set.
+    pub fn count_ones(&self) -> usize {
+        self.data.iter().map(|&b| b.count_ones() as usize).sum()
+    }
+
     /// Create an iterator over the bytes.
     pub fn iter(&self) -> ByteIter<'a> {
         ByteIter(*self)
     }
+
+    /// Iterate over the bytes along with their offsets within the array's
+    /// data, which for bytes are their indices. See
+    /// [`LongArray::iter_with_offsets`].
+    pub fn iter_with_offsets(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (usize, i8)> + DoubleEndedIterator + 'a {
+        self.iter().enumerate()
+    }
+
+    /// Split the array into arrays of `n` bytes, with any left over in a
+    /// shorter last array. See [`IntArray::chunks`].
+    ///
+    /// # Panics
+    ///
+    /// If `n` is 0.
+    pub fn chunks(
+        &self,
+        n: usize,
+    ) -> impl ExactSizeIterator<Item = ByteArray<'a>> + DoubleEndedIterator + 'a {
+        let array = *self;
+        self.data
+            .chunks(n)
+            .map(move |data| ByteArray { data, ..array })
+    }
+
+    /// The bytes of one section of data stacked a section at a time. See
+    /// [`IntArray::take_section`].
+    pub fn take_section(&self, section_index: usize, elements_per_section: usize) -> Option<Self> {
+        let data = section::<1>(self.data, section_index, elements_per_section)?;
+        Some(ByteArray { data, ..*self })
+    }
 }
 
+#[derive(Clone)]
 pub struct ByteIter<'a>(ByteArray<'a>);
 
 impl<'a> Iterator for ByteIter<'a> {
     type Item = i8;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.data.read_i8().ok()
+        take(&mut self.0.data).map(i8::from_be_bytes)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.len();
+        (len, Some(len))
     }
 }
 
+impl<'a> DoubleEndedIterator for ByteIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        take_back(&mut self.0.data).map(i8::from_be_bytes)
+    }
+}
+
+impl<'a> ExactSizeIterator for ByteIter<'a> {}
+
+impl<'a> FusedIterator for ByteIter<'a> {}
+
 /// IntArray can be used to deserialize the NBT data of the same name. This
 /// borrows from the original input data when deserializing. Biomes in the chunk
 /// format are an example of this data type.
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename(deserialize = "__fastnbt_int_array"))]
 pub struct IntArray<'a> {
-    tag: CompTag<INT_ARRAY_TAG>,
+    /// Only checked when deserializing, so that one type of array can't be
+    /// read as another.
+    #[serde(rename = "tag")]
+    _tag: CompTag<INT_ARRAY_TAG>,
+    #[serde(borrow, deserialize_with = "whole_elements::<_, 4>")]
     data: &'a [u8],
+    /// Set by the deserializer for little-endian NBT.
+    #[serde(default)]
+    little_endian: bool,
 }
 
 impl<'a> IntArray<'a> {
+    /// An array over existing big-endian data, for example to write it out
+    /// again. The data must be a whole number of 4 byte ints.
+    pub fn from_be_bytes(data: &'a [u8]) -> crate::error::Result<Self> {
+        if !is_whole::<4>(data) {
+            return Err(crate::error::Error::bespoke(format!(
+                "{} bytes is not a whole number of ints",
+                data.len()
+            )));
+        }
+        Ok(Self {
+            _tag: CompTag::<INT_ARRAY_TAG>,
+            data,
+            little_endian: false,
+        })
+    }
+
+    /// The array's data, exactly as it was in the NBT, without the tag or
+    /// length. None for arrays read from little-endian NBT.
+    pub fn as_be_bytes(&self) -> Option<&'a [u8]> {
+        (!self.little_endian).then_some(self.data)
+    }
+
+    /// The number of i32s, found without reading them.
+    pub fn len(&self) -> usize {
+        self.data.len() / 4
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The size of the array's data in the NBT, 4 bytes per element.
+    pub fn size_in_bytes(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The element at `index`, read directly from the data rather than
+    /// iterating to it.
+    pub fn get(&self, index: usize) -> Option<i32> {
+        let start = index.checked_mul(4)?;
+        let mut data = self.data.get(start..)?;
+        take(&mut data).map(|b| self.read(b))
+    }
+
+    /// The element at `index`, like indexing a slice.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of range.
+    pub fn at(&self, index: usize) -> i32 {
+        match self.get(index) {
+            Some(v) => v,
+            None => out_of_range(index, self.len()),
+        }
+    }
+
+    /// Decode every element at once, into a `Vec` that no longer borrows
+    /// from the input.
+    pub fn to_vec(&self) -> Vec<i32> {
+        let mut data = Vec::with_capacity(self.len());
+        self.copy_into(&mut data);
+        data
+    }
+
+    /// Replace the contents of `out` with every element, so that one buffer
+    /// can be reused for many arrays.
+    pub fn copy_into(&self, out: &mut Vec<i32>) {
+        out.clear();
+        if self.little_endian {
+            out.extend(self.iter());
+        } else {
+            i32::extend_from_be(out, self.data);
+        }
+    }
+
+    /// Fill `out` with every element. It must be the same length as the
+    /// array.
+    pub fn copy_into_slice(&self, out: &mut [i32]) -> crate::error::Result<()> {
+        check_copy_len(self.len(), out.len())?;
+        let elements = self.data.chunks_exact(4).map(|b| b.try_into().unwrap());
+        // Deciding the endian once, outside the loop, lets it be vectorized.
+        if self.little_endian {
+            for (o, b) in out.iter_mut().zip(elements) {
+                *o = i32::from_le_bytes(b);
+            }
+        } else {
+            for (o, b) in out.iter_mut().zip(elements) {
+                *o = i32::from_be_bytes(b);
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy into the owned [`IntArray`][crate::IntArray], which no longer
+    /// borrows from the input.
+    pub fn to_owned(&self) -> crate::IntArray {
+        crate::IntArray::new(self.to_vec())
+    }
+
     /// Create an iterator over the i32s
     pub fn iter(&self) -> IntIter<'a> {
         IntIter(*self)
     }
+
+    /// Iterate over the i32s along with the offset of each within the
+    /// array's data, 4 bytes apart. See [`LongArray::iter_with_offsets`].
+    pub fn iter_with_offsets(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (usize, i32)> + DoubleEndedIterator + 'a {
+        self.iter().enumerate().map(|(i, v)| (i * 4, v))
+    }
+
+    /// Split the array into arrays of `n` i32s, with any left over in a
+    /// shorter last array, like [`slice::chunks`]. Each is read on demand
+    /// like the whole array, with [`iter`][Self::iter] or
+    /// [`get`][Self::get].
+    ///
+    /// ```
+    /// # use fastnbt::borrow::IntArray;
+    /// # let data = [0; 4 * 64];
+    /// # let biomes = IntArray::from_be_bytes(&data).unwrap();
+    /// // Biomes are stored as 4x4x4 cells, x fastest then z then y.
+    /// for (y, layer) in biomes.chunks(16).enumerate() {
+    ///     for (i, biome) in layer.iter().enumerate() {
+    ///         let (x, z) = (i % 4, i / 4);
+    ///         # let _ = (x, y, z, biome);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `n` is 0.
+    pub fn chunks(
+        &self,
+        n: usize,
+    ) -> impl ExactSizeIterator<Item = IntArray<'a>> + DoubleEndedIterator + 'a {
+        let array = *self;
+        self.data
+            .chunks(n.saturating_mul(4))
+            .map(move |data| IntArray { data, ..array })
+    }
+
+    /// The i32s of one section of data stacked a section at a time, such as
+    /// the biomes of the sections of a chunk from the bottom up. This is the
+    /// elements from `section_index * elements_per_section`, or None if the
+    /// array doesn't hold that whole section.
+    pub fn take_section(&self, section_index: usize, elements_per_section: usize) -> Option<Self> {
+        let data = section::<4>(self.data, section_index, elements_per_section)?;
+        Some(IntArray { data, ..*self })
+    }
+
+    fn read(&self, bytes: [u8; 4]) -> i32 {
+        if self.little_endian {
+            i32::from_le_bytes(bytes)
+        } else {
+            i32::from_be_bytes(bytes)
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct IntIter<'a>(IntArray<'a>);
 
 impl<'a> Iterator for IntIter<'a> {
     type Item = i32;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.data.read_i32::<BigEndian>().ok()
+        let bytes = take(&mut self.0.data)?;
+        Some(self.0.read(bytes))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.len();
+        (len, Some(len))
     }
 }
 
+impl<'a> DoubleEndedIterator for IntIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let bytes = take_back(&mut self.0.data)?;
+        Some(self.0.read(bytes))
+    }
+}
+
+impl<'a> ExactSizeIterator for IntIter<'a> {}
+
+impl<'a> FusedIterator for IntIter<'a> {}
+
 /// LongArray can be used to deserialize the NBT data of the same name. This
 /// borrows from the original input data when deserializing. Block states
 /// (storage of all the blocks in a chunk) are an exmple of when this is used.
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename(deserialize = "__fastnbt_long_array"))]
 pub struct LongArray<'a> {
-    tag: CompTag<LONG_ARRAY_TAG>,
+    /// Only checked when deserializing, so that one type of array can't be
+    /// read as another.
+    #[serde(rename = "tag")]
+    _tag: CompTag<LONG_ARRAY_TAG>,
+    #[serde(borrow, deserialize_with = "whole_elements::<_, 8>")]
     data: &'a [u8],
+    /// Set by the deserializer for little-endian NBT.
+    #[serde(default)]
+    little_endian: bool,
 }
 
 impl<'a> LongArray<'a> {
+    /// An array over existing big-endian data, for example to write it out
+    /// again. The data must be a whole number of 8 byte longs.
+    pub fn from_be_bytes(data: &'a [u8]) -> crate::error::Result<Self> {
+        if !is_whole::<8>(data) {
+            return Err(crate::error::Error::bespoke(format!(
+                "{} bytes is not a whole number of longs",
+                data.len()
+            )));
+        }
+        Ok(Self {
+            _tag: CompTag::<LONG_ARRAY_TAG>,
+            data,
+            little_endian: false,
+        })
+    }
+
+    /// The array's data, exactly as it was in the NBT, without the tag or
+    /// length. None for arrays read from little-endian NBT.
+    pub fn as_be_bytes(&self) -> Option<&'a [u8]> {
+        (!self.little_endian).then_some(self.data)
+    }
+
+    /// The number of i64s, found without reading them.
+    pub fn len(&self) -> usize {
+        self.data.len() / 8
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The size of the array's data in the NBT, 8 bytes per element.
+    pub fn size_in_bytes(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The element at `index`, read directly from the data rather than
+    /// iterating to it.
+    pub fn get(&self, index: usize) -> Option<i64> {
+        let start = index.checked_mul(8)?;
+        let mut data = self.data.get(start..)?;
+        take(&mut data).map(|b| self.read(b))
+    }
+
+    /// The element at `index`, like indexing a slice.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of range.
+    pub fn at(&self, index: usize) -> i64 {
+        match self.get(index) {
+            Some(v) => v,
+            None => out_of_range(index, self.len()),
+        }
+    }
+
+    /// Decode every element at once, into a `Vec` that no longer borrows
+    /// from the input.
+    pub fn to_vec(&self) -> Vec<i64> {
+        let mut data = Vec::with_capacity(self.len());
+        self.copy_into(&mut data);
+        data
+    }
+
+    /// Replace the contents of `out` with every element, so that one buffer
+    /// can be reused for many arrays.
+    pub fn copy_into(&self, out: &mut Vec<i64>) {
+        out.clear();
+        if self.little_endian {
+            out.extend(self.iter());
+        } else {
+            i64::extend_from_be(out, self.data);
+        }
+    }
+
+    /// Fill `out` with every element. It must be the same length as the
+    /// array.
+    pub fn copy_into_slice(&self, out: &mut [i64]) -> crate::error::Result<()> {
+        check_copy_len(self.len(), out.len())?;
+        let elements = self.data.chunks_exact(8).map(|b| b.try_into().unwrap());
+        // Deciding the endian once, outside the loop, lets it be vectorized.
+        if self.little_endian {
+            for (o, b) in out.iter_mut().zip(elements) {
+                *o = i64::from_le_bytes(b);
+            }
+        } else {
+            for (o, b) in out.iter_mut().zip(elements) {
+                *o = i64::from_be_bytes(b);
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy into the owned [`LongArray`][crate::LongArray], which no longer
+    /// borrows from the input.
+    pub fn to_owned(&self) -> crate::LongArray {
+        crate::LongArray::new(self.to_vec())
+    }
+
     /// Create an iterator over the i64s
     pub fn iter(&self) -> LongIter<'a> {
         LongIter(*self)
     }
+
+    /// Iterate over the i64s along with the offset of each within the
+    /// array's data, 8 bytes apart. Adding the offset of the data in the
+    /// input, such as from [`Parser::array_offset`], finds a bad value in the
+    /// original input.
+    ///
+    /// [`Parser::array_offset`]: crate::stream::Parser::array_offset
+    pub fn iter_with_offsets(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (usize, i64)> + DoubleEndedIterator + 'a {
+        self.iter().enumerate().map(|(i, v)| (i * 8, v))
+    }
+
+    /// Split the array into arrays of `n` i64s, with any left over in a
+    /// shorter last array. See [`IntArray::chunks`].
+    ///
+    /// # Panics
+    ///
+    /// If `n` is 0.
+    pub fn chunks(
+        &self,
+        n: usize,
+    ) -> impl ExactSizeIterator<Item = LongArray<'a>> + DoubleEndedIterator + 'a {
+        let array = *self;
+        self.data
+            .chunks(n.saturating_mul(8))
+            .map(move |data| LongArray { data, ..array })
+    }
+
+    /// The i64s of one section of data stacked a section at a time. See
+    /// [`IntArray::take_section`].
+    pub fn take_section(&self, section_index: usize, elements_per_section: usize) -> Option<Self> {
+        let data = section::<8>(self.data, section_index, elements_per_section)?;
+        Some(LongArray { data, ..*self })
+    }
+
+    /// Unpack bit-packed entries, such as block states, filling `out`. See
+    /// [`crate::LongArray::unpack_into`].
+    pub fn unpack_into(
+        &self,
+        bits_per_entry: usize,
+        padded: bool,
+        out: &mut [u16],
+    ) -> crate::error::Result<()> {
+        crate::arrays::unpack_bits(self.iter(), self.len(), bits_per_entry, padded, out)
+    }
+
+    fn read(&self, bytes: [u8; 8]) -> i64 {
+        if self.little_endian {
+            i64::from_le_bytes(bytes)
+        } else {
+            i64::from_be_bytes(bytes)
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct LongIter<'a>(LongArray<'a>);
 
 impl<'a> Iterator for LongIter<'a> {
     type Item = i64;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.data.read_i64::<BigEndian>().ok()
+        let bytes = take(&mut self.0.data)?;
+        Some(self.0.read(bytes))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a> DoubleEndedIterator for LongIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let bytes = take_back(&mut self.0.data)?;
+        Some(self.0.read(bytes))
+    }
+}
+
+impl<'a> ExactSizeIterator for LongIter<'a> {}
+
+impl<'a> FusedIterator for LongIter<'a> {}
+
+// Serialized under the same tokens as the owned arrays, so our serializer
+// writes the array tags. Only our serializer is given the big-endian data as
+// bytes, which it copies straight to the output. Every other serializer, and
+// ours for little-endian data, sees a sequence of the elements.
+macro_rules! serialize_elements {
+    ($($array:ident => $token:ident),*) => {
+        $(
+            impl<'a> Serialize for $array<'a> {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    let be: Option<&[u8]> = self.as_be_bytes().into();
+                    serializer.serialize_newtype_struct(
+                        $token,
+                        &ArrayData {
+                            be,
+                            elements: Elements(self.iter()),
+                        },
+                    )
+                }
+            }
+        )*
+    };
+}
+
+serialize_elements!(
+    ByteArray => BYTE_ARRAY_TOKEN,
+    IntArray => INT_ARRAY_TOKEN,
+    LongArray => LONG_ARRAY_TOKEN
+);
+
+// Arrays compare by their elements, so the same values read from big and
+// little-endian NBT are equal, and they can be checked against plain slices.
+macro_rules! elements_eq {
+    ($($array:ident => $element:ty),*) => {
+        $(
+            impl<'a, 'b> PartialEq<$array<'b>> for $array<'a> {
+                fn eq(&self, other: &$array<'b>) -> bool {
+                    self.len() == other.len() && self.iter().eq(other.iter())
+                }
+            }
+
+            impl<'a> Eq for $array<'a> {}
+
+            impl<'a> PartialEq<[$element]> for $array<'a> {
+                fn eq(&self, other: &[$element]) -> bool {
+                    self.len() == other.len() && self.iter().eq(other.iter().copied())
+                }
+            }
+
+            impl<'a, 'b> PartialEq<&'b [$element]> for $array<'a> {
+                fn eq(&self, other: &&'b [$element]) -> bool {
+                    *self == **other
+                }
+            }
+
+            impl<'a, const N: usize> PartialEq<[$element; N]> for $array<'a> {
+                fn eq(&self, other: &[$element; N]) -> bool {
+                    *self == other[..]
+                }
+            }
+
+            impl<'a> PartialEq<Vec<$element>> for $array<'a> {
+                fn eq(&self, other: &Vec<$element>) -> bool {
+                    *self == other[..]
+                }
+            }
+
+            impl<'a> PartialEq<crate::$array> for $array<'a> {
+                fn eq(&self, other: &crate::$array) -> bool {
+                    *self == other[..]
+                }
+            }
+
+            impl<'a> PartialEq<$array<'a>> for [$element] {
+                fn eq(&self, other: &$array<'a>) -> bool {
+                    *other == *self
+                }
+            }
+
+            impl<'a, 'b> PartialEq<$array<'a>> for &'b [$element] {
+                fn eq(&self, other: &$array<'a>) -> bool {
+                    *other == **self
+                }
+            }
+
+            impl<'a, const N: usize> PartialEq<$array<'a>> for [$element; N] {
+                fn eq(&self, other: &$array<'a>) -> bool {
+                    *other == self[..]
+                }
+            }
+
+            impl<'a> PartialEq<$array<'a>> for Vec<$element> {
+                fn eq(&self, other: &$array<'a>) -> bool {
+                    *other == self[..]
+                }
+            }
+
+            impl<'a> PartialEq<$array<'a>> for crate::$array {
+                fn eq(&self, other: &$array<'a>) -> bool {
+                    *other == self[..]
+                }
+            }
+        )*
+    };
+}
+
+elements_eq!(ByteArray => i8, IntArray => i32, LongArray => i64);
+
+struct Elements<I>(I);
+
+impl<I> Serialize for Elements<I>
+where
+    I: ExactSizeIterator + Clone,
+    I::Item: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.0.clone())
+    }
+}
+
+/// The data of a borrowed array, as bytes if our serializer asked for them
+/// with [`offer_be_bytes`], or else as its elements.
+struct ArrayData<'a, I> {
+    be: Option<&'a [u8]>,
+    elements: Elements<I>,
+}
+
+impl<'a, I> Serialize for ArrayData<'a, I>
+where
+    I: ExactSizeIterator + Clone,
+    I::Item: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.be {
+            Some(data) if take_be_bytes_offer() => serializer.serialize_bytes(data),
+            _ => self.elements.serialize(serializer),
+        }
     }
 }
 
-struct CowStr<'a>(Cow<'a, str>);
+// Serde has no way for a value to tell which serializer it is given, and a
+// token would be seen by every serializer alike. So our serializer instead
+// leaves a note for the next array on this thread that it will take bytes.
+#[cfg(feature = "std")]
+std::thread_local! {
+    static BE_BYTES_OFFERED: core::cell::Cell<bool> = const { core::cell::Cell::new(false) };
+}
+
+/// Serialize the data of an array with `f`, which accepts the big-endian
+/// bytes of a borrowed array as well as a sequence of elements.
+#[cfg(feature = "std")]
+pub(crate) fn offer_be_bytes<R>(f: impl FnOnce() -> R) -> R {
+    BE_BYTES_OFFERED.with(|offered| offered.set(true));
+    let result = f();
+    // The data may have been something other than a borrowed array.
+    BE_BYTES_OFFERED.with(|offered| offered.set(false));
+    result
+}
+
+/// Whether the array being serialized was offered to take bytes, which only
+/// the first array to ask can use.
+#[cfg(feature = "std")]
+fn take_be_bytes_offer() -> bool {
+    BE_BYTES_OFFERED.with(|offered| offered.replace(false))
+}
+
+#[cfg(not(feature = "std"))]
+fn take_be_bytes_offer() -> bool {
+    false
+}
+
+/// Deserialize the data of an array with `N` byte elements, which must not
+/// end part way through an element. Our deserializer always gives whole
+/// elements, but other deserializers could give any bytes.
+fn whole_elements<'de, D, const N: usize>(de: D) -> Result<&'de [u8], D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    let data = <&[u8]>::deserialize(de)?;
+    if !is_whole::<N>(data) {
+        return Err(de::Error::invalid_length(data.len(), &WholeElements(N)));
+    }
+    Ok(data)
+}
+
+/// Whether array data ends on an element boundary.
+fn is_whole<const N: usize>(data: &[u8]) -> bool {
+    data.chunks_exact(N).remainder().is_empty()
+}
+
+struct WholeElements(usize);
+
+impl de::Expected for WholeElements {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a multiple of {} bytes", self.0)
+    }
+}
+
+fn check_copy_len(len: usize, out: usize) -> crate::error::Result<()> {
+    if len != out {
+        return Err(crate::error::Error::bespoke(format!(
+            "cannot copy an NBT array of length {} into a slice of length {}",
+            len, out
+        )));
+    }
+    Ok(())
+}
+
+/// The data of section `index` of `per_section` elements of `N` bytes.
+fn section<const N: usize>(data: &[u8], index: usize, per_section: usize) -> Option<&[u8]> {
+    let len = per_section.checked_mul(N)?;
+    let start = index.checked_mul(len)?;
+    data.get(start..start.checked_add(len)?)
+}
+
+#[cold]
+fn out_of_range(index: usize, len: usize) -> ! {
+    panic!(
+        "index {} is out of range for an NBT array of length {}",
+        index, len
+    )
+}
+
+/// Take the next `N` bytes off the front of some array data.
+fn take<const N: usize>(data: &mut &[u8]) -> Option<[u8; N]> {
+    if data.len() < N {
+        return None;
+    }
+    let (bytes, rest) = data.split_at(N);
+    *data = rest;
+    bytes.try_into().ok()
+}
+
+/// Take the last `N` bytes off the end of some array data.
+fn take_back<const N: usize>(data: &mut &[u8]) -> Option<[u8; N]> {
+    let at = data.len().checked_sub(N)?;
+    let (rest, bytes) = data.split_at(at);
+    *data = rest;
+    bytes.try_into().ok()
+}
+
+/// A complete NBT value like [`crate::Value`], but with strings and compound
+/// keys borrowed from the input where possible. Deserializing a document with
+/// many compounds into this makes far fewer allocations, since every key
+/// would otherwise be its own `String`.
+///
+/// Strings need copying if they contain characters outside of the Basic
+/// Multilingual Plane (see [`de`][`crate::de`]). The NBT arrays are borrowed
+/// like [`ByteArray`], [`IntArray`] and [`LongArray`], and only decoded when
+/// used. So like them, this can only be deserialized from a byte slice, and
+/// not from Bedrock network NBT with int or long arrays, whose elements are
+/// VarInts.
+///
+/// ```
+/// # use fastnbt::{borrow, Value};
+/// # use std::collections::HashMap;
+/// # let data = fastnbt::to_bytes(&HashMap::from([("Name", Value::String("Steve".into()))])).unwrap();
+/// let value: borrow::Value = fastnbt::de::from_bytes(&data).unwrap();
+/// if let borrow::Value::Compound(compound) = &value {
+///     assert_eq!(Some(&borrow::Value::String("Steve".into())), compound.get("Name"));
+/// }
+///
+/// // Convert to the owned form once it needs to outlive the input.
+/// let owned: Value = value.into();
+/// ```
+///
+/// It can be indexed in the same way as [`crate::Value`], and serializes with
+/// the same tags it was read with, so a document can be edited and written
+/// back with [`to_bytes`][crate::to_bytes] without converting it to the owned
+/// form first.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Value<'a> {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Double(f64),
+    Float(f32),
+    String(Cow<'a, str>),
+    ByteArray(ByteArray<'a>),
+    IntArray(IntArray<'a>),
+    LongArray(LongArray<'a>),
+    List(Vec<Value<'a>>),
+    Compound(Map<Cow<'a, str>, Value<'a>>),
+}
+
+impl<'a> Value<'a> {
+    /// Copy this into an owned [`crate::Value`], which can outlive the input,
+    /// such as to keep part of a document after the rest is dropped. This is
+    /// where the NBT arrays are decoded.
+    ///
+    /// This takes the place of [`ToOwned::to_owned`], which would only clone
+    /// the `borrow::Value`. To avoid copying a value that is no longer
+    /// needed, convert it with `From` instead.
+    ///
+    /// ```
+    /// # use fastnbt::{borrow, Value};
+    /// # use std::collections::HashMap;
+    /// let owned = {
+    ///     let data = fastnbt::to_bytes(&HashMap::from([("Name", "Steve")]))?;
+    ///     let value: borrow::Value = fastnbt::de::from_bytes(&data)?;
+    ///     value["Name"].to_owned()
+    /// };
+    /// assert_eq!(Value::String("Steve".to_owned()), owned);
+    /// # Ok::<(), fastnbt::error::Error>(())
+    /// ```
+    pub fn to_owned(&self) -> crate::Value {
+        match self {
+            Value::Byte(v) => crate::Value::Byte(*v),
+            Value::Short(v) => crate::Value::Short(*v),
+            Value::Int(v) => crate::Value::Int(*v),
+            Value::Long(v) => crate::Value::Long(*v),
+            Value::Double(v) => crate::Value::Double(*v),
+            Value::Float(v) => crate::Value::Float(*v),
+            Value::String(v) => crate::Value::String(String::from(&**v)),
+            Value::ByteArray(v) => crate::Value::ByteArray(v.to_owned()),
+            Value::IntArray(v) => crate::Value::IntArray(v.to_owned()),
+            Value::LongArray(v) => crate::Value::LongArray(v.to_owned()),
+            Value::List(v) => crate::Value::List(v.iter().map(Value::to_owned).collect()),
+            Value::Compound(v) => crate::Value::Compound(
+                v.iter()
+                    .map(|(k, v)| (String::from(&**k), v.to_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> From<Value<'a>> for crate::Value {
+    fn from(value: Value<'a>) -> Self {
+        match value {
+            Value::Byte(v) => crate::Value::Byte(v),
+            Value::Short(v) => crate::Value::Short(v),
+            Value::Int(v) => crate::Value::Int(v),
+            Value::Long(v) => crate::Value::Long(v),
+            Value::Double(v) => crate::Value::Double(v),
+            Value::Float(v) => crate::Value::Float(v),
+            Value::String(v) => crate::Value::String(v.into_owned()),
+            Value::ByteArray(v) => crate::Value::ByteArray(v.to_owned()),
+            Value::IntArray(v) => crate::Value::IntArray(v.to_owned()),
+            Value::LongArray(v) => crate::Value::LongArray(v.to_owned()),
+            Value::List(v) => crate::Value::List(v.into_iter().map(Into::into).collect()),
+            Value::Compound(v) => crate::Value::Compound(
+                v.into_iter()
+                    .map(|(k, v)| (k.into_owned(), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match Parsed::deserialize(deserializer)? {
+            Parsed::Value(v) => Ok(v),
+            Parsed::ArrayTag(_) => Err(de::Error::custom("unexpected array tag")),
+        }
+    }
+}
+
+/// What the deserializer presents. NBT arrays are presented as a map of their
+/// tag as a `u8`, then their data, which would otherwise look like a compound.
+/// No NBT value is a `u8`, so seeing one means the map is an array.
+enum Parsed<'a> {
+    Value(Value<'a>),
+    ArrayTag(u8),
+}
+
+impl<'de> Deserialize<'de> for Parsed<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+macro_rules! visit_value {
+    ($($method:ident($ty:ty) => $variant:ident),* $(,)?) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                Ok(Parsed::Value(Value::$variant(v.into())))
+            }
+        )*
+    };
+}
+
+impl<'de> de::Visitor<'de> for ValueVisitor {
+    type Value = Parsed<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an NBT value")
+    }
+
+    visit_value! {
+        visit_i8(i8) => Byte,
+        visit_i16(i16) => Short,
+        visit_i32(i32) => Int,
+        visit_i64(i64) => Long,
+        visit_f32(f32) => Float,
+        visit_f64(f64) => Double,
+        visit_borrowed_str(&'de str) => String,
+        visit_string(String) => String,
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Parsed::Value(Value::String(Cow::Owned(v.to_owned()))))
+    }
+
+    fn visit_u8<E: de::Error>(self, v: u8) -> Result<Self::Value, E> {
+        Ok(Parsed::ArrayTag(v))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut list = Vec::with_capacity(cautious_capacity::<Value>(seq.size_hint()));
+        while let Some(v) = seq.next_element()? {
+            list.push(v);
+        }
+        Ok(Parsed::Value(Value::List(list)))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        #[cfg(feature = "std")]
+        let mut compound = Map::with_capacity(cautious_capacity::<(&str, Value)>(map.size_hint()));
+        #[cfg(not(feature = "std"))]
+        let mut compound = Map::new();
+
+        while let Some(Str(key)) = map.next_key()? {
+            let value = match map.next_value()? {
+                Parsed::Value(value) => value,
+                Parsed::ArrayTag(tag) => return visit_array(tag, map).map(Parsed::Value),
+            };
+            compound.insert(key, value);
+        }
+
+        Ok(Parsed::Value(Value::Compound(compound)))
+    }
+}
+
+/// How much to preallocate for a list or compound of the hinted size. The size
+/// comes from the input, so don't trust it with more than about a MiB.
+fn cautious_capacity<T>(hint: Option<usize>) -> usize {
+    let max = 1024 * 1024 / core::mem::size_of::<T>().max(1);
+    hint.unwrap_or(0).min(max)
+}
+
+/// Finish reading an NBT array, once its tag has been read.
+fn visit_array<'de, A>(tag: u8, mut map: A) -> Result<Value<'de>, A::Error>
+where
+    A: de::MapAccess<'de>,
+{
+    if map.next_key::<Str>()?.is_none() {
+        return Err(de::Error::missing_field("data"));
+    }
+    let data: &'de [u8] = map.next_value()?;
+
+    // The deserializer only says which order the data is in when it isn't
+    // big-endian.
+    let little_endian = match map.next_key::<Str>()? {
+        Some(_) => map.next_value()?,
+        None => false,
+    };
+
+    let element_len = match tag {
+        BYTE_ARRAY_TAG => 1,
+        INT_ARRAY_TAG => 4,
+        LONG_ARRAY_TAG => 8,
+        _ => return Err(de::Error::custom(format!("invalid array tag {}", tag))),
+    };
+    if !data.chunks_exact(element_len).remainder().is_empty() {
+        return Err(de::Error::invalid_length(
+            data.len(),
+            &WholeElements(element_len),
+        ));
+    }
+
+    Ok(match tag {
+        BYTE_ARRAY_TAG => Value::ByteArray(ByteArray::from_be_bytes(data)),
+        INT_ARRAY_TAG => Value::IntArray(IntArray {
+            _tag: CompTag::<INT_ARRAY_TAG>,
+            data,
+            little_endian,
+        }),
+        _ => Value::LongArray(LongArray {
+            _tag: CompTag::<LONG_ARRAY_TAG>,
+            data,
+            little_endian,
+        }),
+    })
+}
+
+/// A compound whose entries are only parsed when asked for, for reading a few
+/// values out of a lot of NBT. Deserializing one skips over the values of the
+/// compound, noting where each is, then [`get`][Self::get] parses just the
+/// value of one entry. Nested compounds can be read the same way.
+///
+/// ```
+/// # use fastnbt::{borrow, Map, Value};
+/// # fn main() -> fastnbt::error::Result<()> {
+/// # let level = Map::from([("Status".to_owned(), Value::String("full".into()))]);
+/// # let data = fastnbt::to_bytes(&Map::from([
+/// #     ("DataVersion".to_owned(), Value::Int(2586)),
+/// #     ("Level".to_owned(), Value::Compound(level)),
+/// # ])).unwrap();
+/// let chunk: borrow::Compound = fastnbt::de::from_bytes(&data)?;
+/// let version: Option<i32> = chunk.get("DataVersion")?;
+/// let level: borrow::Compound = chunk.get("Level")?.unwrap();
+/// let status: Option<&str> = level.get("Status")?;
+/// # assert_eq!((Some(2586), Some("full")), (version, status));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The values stay in the input, so this can only be deserialized from a
+/// byte slice, not from a reader.
+#[derive(Debug, Clone)]
+pub struct Compound<'a> {
+    entries: Vec<Entry<'a>>,
+    endian: Endian,
+}
+
+/// The name, tag and payload of an entry, as our deserializer presents them.
+#[derive(Deserialize, Debug, Clone)]
+struct Entry<'a> {
+    #[serde(borrow, deserialize_with = "deserialize_cow_str")]
+    key: Cow<'a, str>,
+    #[serde(deserialize_with = "tag_from_u8")]
+    tag: Tag,
+    payload: &'a [u8],
+}
+
+fn tag_from_u8<'de, D>(deserializer: D) -> Result<Tag, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let tag = u8::deserialize(deserializer)?;
+    Tag::try_from(tag).map_err(|_| de::Error::custom(format!("invalid tag {}", tag)))
+}
+
+impl<'a> Compound<'a> {
+    /// Deserialize the value of `key`, or None if there is no such entry.
+    /// Errors give the path to the value from this compound.
+    pub fn get<T>(&self, key: &str) -> crate::error::Result<Option<T>>
+    where
+        T: Deserialize<'a>,
+    {
+        self.entry(key)
+            .map(|e| crate::de::from_entry_payload(e.payload, self.endian, e.tag, e.key.clone()))
+            .transpose()
+    }
+
+    /// The tag of the value of `key`, found without parsing it.
+    pub fn tag(&self, key: &str) -> Option<Tag> {
+        self.entry(key).map(|e| e.tag)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entry(key).is_some()
+    }
+
+    /// The keys of the entries, in the order they were in the input.
+    pub fn keys(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries.iter().map(|e| &*e.key)
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // The last of any duplicate keys wins, as when deserializing a map.
+    fn entry(&self, key: &str) -> Option<&Entry<'a>> {
+        self.entries.iter().rev().find(|e| e.key == key)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Compound<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = RawCompound::deserialize(deserializer)?;
+        let endian = match (raw.little_endian, raw.varint) {
+            (_, true) => Endian::VarInt,
+            (true, _) => Endian::Little,
+            _ => Endian::Big,
+        };
+        Ok(Compound {
+            entries: raw.entries,
+            endian,
+        })
+    }
+}
+
+/// How our deserializer presents a compound to [`Compound`], with its byte
+/// order if that is not big-endian.
+#[derive(Deserialize)]
+#[serde(rename = "__fastnbt_compound")]
+struct RawCompound<'a> {
+    #[serde(borrow)]
+    entries: Vec<Entry<'a>>,
+    #[serde(default)]
+    little_endian: bool,
+    #[serde(default)]
+    varint: bool,
+}
+
+/// A string that borrows from the input where it can, like a `Cow<'a, str>`
+/// field with `#[serde(borrow)]`, but usable anywhere a type is, such as in
+/// an enum, a `Vec` or a map key, where `deserialize_with` can't reach.
+///
+/// Strings are copied when they contain characters that are encoded
+/// differently in Java's CESU-8 (see [`de`][`crate::de`]), or when
+/// deserializing from a reader.
+///
+/// ```
+/// # use fastnbt::borrow::Str;
+/// # use serde::Deserialize;
+/// # use std::collections::HashMap;
+/// #[derive(Deserialize)]
+/// struct Block<'a> {
+///     #[serde(rename = "Name", borrow)]
+///     name: Str<'a>,
+///     #[serde(rename = "Properties", default, borrow)]
+///     properties: HashMap<Str<'a>, Str<'a>>,
+/// }
+/// # let data = fastnbt::to_bytes(&HashMap::from([("Name", "minecraft:chest")])).unwrap();
+/// let block: Block = fastnbt::de::from_bytes(&data).unwrap();
+/// assert_eq!("minecraft:chest", &*block.name);
+/// assert!(block.name.is_borrowed());
+/// # assert!(block.properties.is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Str<'a>(pub Cow<'a, str>);
+
+impl<'a> Str<'a> {
+    /// Whether the string borrows from the input.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.0, Cow::Borrowed(_))
+    }
+
+    /// Take the string, copying it if it was borrowed.
+    pub fn into_owned(self) -> String {
+        self.0.into_owned()
+    }
+}
+
+impl<'a> Deref for Str<'a> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> core::borrow::Borrow<str> for Str<'a> {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> From<&'a str> for Str<'a> {
+    fn from(s: &'a str) -> Self {
+        Str(Cow::Borrowed(s))
+    }
+}
+
+impl<'a> From<String> for Str<'a> {
+    fn from(s: String) -> Self {
+        Str(Cow::Owned(s))
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for Str<'a> {
+    fn from(s: Cow<'a, str>) -> Self {
+        Str(s)
+    }
+}
+
+impl<'a> fmt::Display for Str<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'a> Serialize for Str<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
 
-impl<'de> serde::Deserialize<'de> for CowStr<'de> {
+impl<'de: 'a, 'a> serde::Deserialize<'de> for Str<'a> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        deserializer.deserialize_str(CowStrVisitor)
+        deserializer.deserialize_str(StrVisitor)
     }
 }
 
-struct CowStrVisitor;
+struct StrVisitor;
 
-impl<'de> serde::de::Visitor<'de> for CowStrVisitor {
-    type Value = CowStr<'de>;
+impl<'de> serde::de::Visitor<'de> for StrVisitor {
+    type Value = Str<'de>;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         formatter.write_str("a string")
@@ -151,7 +1396,7 @@ impl<'de> serde::de::Visitor<'de> for CowStrVisitor {
     where
         E: serde::de::Error,
     {
-        Ok(CowStr(Cow::Borrowed(value)))
+        Ok(Str(Cow::Borrowed(value)))
     }
 
     // A string that currently only lives in a temporary buffer -- we need a copy
@@ -160,7 +1405,7 @@ impl<'de> serde::de::Visitor<'de> for CowStrVisitor {
     where
         E: serde::de::Error,
     {
-        Ok(CowStr(Cow::Owned(value.to_owned())))
+        Ok(Str(Cow::Owned(value.to_owned())))
     }
 
     // An optimisation of visit_str for situations where the deserializer has
@@ -169,14 +1414,16 @@ impl<'de> serde::de::Visitor<'de> for CowStrVisitor {
     where
         E: serde::de::Error,
     {
-        Ok(CowStr(Cow::Owned(value)))
+        Ok(Str(Cow::Owned(value)))
     }
 }
 
+/// Deserialize a `Cow<str>` that borrows from the input where it can, for use
+/// with `#[serde(deserialize_with)]`. [`Str`] does the same as a type.
 pub fn deserialize_cow_str<'de, D>(deserializer: D) -> Result<Cow<'de, str>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let wrapper = CowStr::deserialize(deserializer)?;
+    let wrapper = Str::deserialize(deserializer)?;
     Ok(wrapper.0)
 }
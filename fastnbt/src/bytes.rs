@@ -0,0 +1,101 @@
+//! Helper for `#[serde(serialize_with)]` that writes bytes as a Byte Array.
+//!
+//! A Byte Array can be deserialized straight into a `&'a [u8]`, borrowing it
+//! from the input, or into a [`Cow<'a, [u8]>`][`alloc::borrow::Cow`] which
+//! falls back to owning the bytes when they cannot be borrowed, such as when
+//! reading from a [`Deserializer::from_reader`][`crate::de::Deserializer`].
+//! The `Cow` needs `#[serde(borrow)]` to borrow at all, and so does a slice
+//! inside an `Option`.
+//!
+//! Serializing them is different. Serde serializes a `&[u8]` as a sequence of
+//! `u8`, the same as a `Vec<u8>`, so it would become a List of Bytes. Use
+//! [`serialize`] to write a Byte Array instead:
+//!
+//! ```
+//! # use fastnbt::{de::from_bytes, to_bytes, Value};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Level<'a> {
+//!     #[serde(borrow, serialize_with = "fastnbt::bytes::serialize")]
+//!     #[serde(rename = "Biomes")]
+//!     biomes: Option<&'a [u8]>,
+//! }
+//!
+//! let data = to_bytes(&Level { biomes: Some(&[1, 2, 255]) }).unwrap();
+//! let value: Value = from_bytes(&data).unwrap();
+//! assert!(matches!(&value, Value::Compound(c) if matches!(c["Biomes"], Value::ByteArray(_))));
+//!
+//! let level: Level = from_bytes(&data).unwrap();
+//! assert_eq!(Some(&[1, 2, 255][..]), level.biomes);
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+use serde::{Serialize, Serializer};
+
+/// Serialize bytes as a Byte Array. Works for `[u8]`, `Vec<u8>`, `[u8; N]`,
+/// `Cow<[u8]>`, references to them and `Option`s of them. A `None` is skipped
+/// like any other `None` in a compound.
+pub fn serialize<T, S>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + AsByteArray,
+    S: Serializer,
+{
+    bytes.serialize_byte_array(serializer)
+}
+
+/// Types that [`serialize`] can write as a Byte Array.
+pub trait AsByteArray {
+    #[doc(hidden)]
+    fn serialize_byte_array<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+}
+
+impl AsByteArray for [u8] {
+    fn serialize_byte_array<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self)
+    }
+}
+
+impl AsByteArray for Vec<u8> {
+    fn serialize_byte_array<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self)
+    }
+}
+
+impl<const N: usize> AsByteArray for [u8; N] {
+    fn serialize_byte_array<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self)
+    }
+}
+
+impl AsByteArray for Cow<'_, [u8]> {
+    fn serialize_byte_array<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self)
+    }
+}
+
+impl<T: ?Sized + AsByteArray> AsByteArray for &T {
+    fn serialize_byte_array<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize_byte_array(serializer)
+    }
+}
+
+impl<T: AsByteArray> AsByteArray for Option<T> {
+    fn serialize_byte_array<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Some(bytes) => serializer.serialize_some(&Wrapper(bytes)),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Lets an `Option` pass its contents back through [`AsByteArray`].
+struct Wrapper<'a, T: ?Sized>(&'a T);
+
+impl<T: ?Sized + AsByteArray> Serialize for Wrapper<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_byte_array(serializer)
+    }
+}
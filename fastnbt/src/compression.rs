@@ -0,0 +1,34 @@
+//! Detecting how NBT data is compressed. Files like `level.dat` are gzip
+//! compressed, chunks in region files are usually zlib compressed, and some
+//! NBT is stored raw. See [`from_compressed_reader`][`crate::de::from_compressed_reader`]
+//! and [`from_maybe_compressed_bytes`][`crate::de::from_maybe_compressed_bytes`].
+
+/// How some NBT data is compressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    Gzip,
+    Zlib,
+    Uncompressed,
+}
+
+impl Compression {
+    /// Detect the compression of some data from its first two bytes. Anything
+    /// without a gzip or zlib header is assumed to be uncompressed.
+    ///
+    /// Uncompressed NBT starts with the compound tag, 10, so cannot be
+    /// mistaken for either header.
+    pub fn detect(data: &[u8]) -> Self {
+        match data {
+            [0x1f, 0x8b, ..] => Compression::Gzip,
+            [cmf, flg, ..] if is_zlib_header(*cmf, *flg) => Compression::Zlib,
+            _ => Compression::Uncompressed,
+        }
+    }
+}
+
+/// A zlib stream starts with a compression method of 8 (deflate), a window
+/// size of at most 32 KiB, and a check value making the pair of bytes a
+/// multiple of 31. In practice the first byte is almost always 0x78.
+fn is_zlib_header(cmf: u8, flg: u8) -> bool {
+    cmf & 0x0f == 8 && cmf >> 4 <= 7 && (u16::from(cmf) << 8 | u16::from(flg)) % 31 == 0
+}
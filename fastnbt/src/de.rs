@@ -5,11 +5,22 @@
 //! * an arbitrary [`Value`](../enum.Value.html).
 //! * enums. For NBT typically you want either internally or untagged enums.
 //!
-//! This deserializer only supports [`from_bytes`](fn.from_bytes.html). This is
-//! usually fine as most structures stored in this format are reasonably small,
-//! the largest likely being an individual Chunk which maxes out at 1 MiB
+//! This deserializer mainly works with [`from_bytes`](fn.from_bytes.html). This
+//! is usually fine as most structures stored in this format are reasonably
+//! small, the largest likely being an individual Chunk which maxes out at 1 MiB
 //! compressed. This enables zero-copy deserialization in places.
 //!
+//! To deserialize straight from a reader, such as a decompressor, use
+//! [`from_reader`]. This can only deserialize owned types.
+//!
+//! To read just one value, such as a chunk's `DataVersion`, use [`get_path`].
+//! Everything before it is skipped over without being deserialized.
+//!
+//! For input that may or may not be compressed, such as `level.dat` files and
+//! chunk payloads, [`from_compressed_reader`] and
+//! [`from_maybe_compressed_bytes`] detect gzip and zlib compression and
+//! decompress as needed.
+//!
 //! # Avoiding allocations
 //!
 //! Due to having all the input in memory, we can avoid allocations for things
@@ -29,7 +40,7 @@
 //! | Double | `f64` | use owned |
 //! | String | `String` | [`Cow<'a, str>`][`std::borrow::Cow`] (see below) |
 //! | List | `Vec<T>` | use owned |
-//! | Byte Array | [`ByteArray`][`crate::ByteArray`] | [`borrow::ByteArray`][`crate::borrow::ByteArray`] |
+//! | Byte Array | [`ByteArray`][`crate::ByteArray`] | [`borrow::ByteArray`][`crate::borrow::ByteArray`] or `&'a [u8]` (see below) |
 //! | Int Array | [`IntArray`][`crate::IntArray`] | [`borrow::IntArray`][`crate::borrow::IntArray`] |
 //! | Long Array | [`LongArray`][`crate::LongArray`] | [`borrow::LongArray`][`crate::borrow::LongArray`] |
 //!
@@ -39,6 +50,15 @@
 //! possible due to alignment requirements of those types. It likely wouldn't be
 //! faster/smaller anyway.
 //!
+//! Integers are converted by value, so any integer tag can be deserialized
+//! into any integer type as long as the value fits. To accept only tags no
+//! larger than the type, see [`widen`][`crate::widen`].
+//!
+//! An Int Array of exactly four ints can also be deserialized into an `i128` or
+//! `u128`, most significant int first. Minecraft stores UUIDs this way, so an
+//! entity's `UUID` can be a single integer field. Arrays of any other length
+//! are an error.
+//!
 //! ## Strings
 //!
 //! For strings, we cannot know ahead of time whether the data can be borrowed
@@ -56,6 +76,14 @@
 //! underyling data and decodes when needed. Please open an issue if this is
 //! important to you.
 //!
+//! ## Byte Arrays as bytes
+//!
+//! A Byte Array can be deserialized into a `&'a [u8]`, borrowing the payload
+//! from the input. A reader has nothing to borrow from, so to handle both use a
+//! [`Cow<'a, [u8]>`][`std::borrow::Cow`] with `#[serde(borrow)]`, which
+//! borrows when possible and owns the bytes otherwise. To serialize them back to a Byte
+//! Array rather than a List, see [`bytes`][`crate::bytes`].
+//!
 //! ## Representation of NBT arrays
 //!
 //! In order for [`Value`][`crate::Value`] to preserve all NBT information, the
@@ -94,14 +122,109 @@
 //!   does not apply to deserializing lists of integrals to `u8` slice or
 //!   vectors.
 //! * Any integral value from NBT can be deserialized to bool. Any non-zero
-//!   value becomes `true`.
+//!   value becomes `true`. The serializer writes `bool` as a Byte of 0 or 1,
+//!   which is how Minecraft stores flags such as `hardcore` in `level.dat`.
+//!   Game rules are the exception, they are stored as the strings `"true"` and
+//!   `"false"` and so need deserializing to `String`.
+//! * Maps with integer keys, such as `HashMap<i32, T>`, parse the names of the
+//!   compound's entries as integers. A name that is not a valid integer of the
+//!   key's type is an error.
+//! * The deserializer is not human readable, so types with a compact form,
+//!   such as `Ipv4Addr`, expect it. See [`DeOpts::human_readable`] to change
+//!   this.
 //! * You can deserialize a field to the unit type `()`. This ignores the value
 //!   but ensures that it existed.
+//! * Values that your type has no field for are skipped without being
+//!   decoded. Arrays, strings and lists of numbers are passed over by their
+//!   length, so an invalid string in a skipped value is not an error.
 //! * You cannot deserialize into anything other than a `struct` or similar
 //!   container eg `HashMap`. This is due to a misalignment between the NBT
 //!   format and Rust's types. Attempting to will give a `NoRootCompound` error.
 //!   This means you can never do `let s: String = from_bytes(...)`.
 //!
+//! # Little-endian NBT
+//!
+//! *Minecraft: Bedrock Edition* stores NBT on disk little-endian, and with
+//! strings in UTF-8. Use [`from_bytes_le`] or [`Deserializer::from_bytes_le`]
+//! to read it. Everything else works the same, including the borrowed
+//! [`borrow::IntArray`][`crate::borrow::IntArray`] and
+//! [`borrow::LongArray`][`crate::borrow::LongArray`], which remember to decode
+//! their elements as little-endian. Bedrock's 8 byte file header, eg in
+//! `level.dat`, must be stripped first.
+//!
+//! # Network NBT
+//!
+//! Since 1.20.2 the Java Edition protocol sends NBT without a name for the
+//! root compound. Use [`from_bytes_network`] or
+//! [`Deserializer::from_bytes_network`] to read it.
+//!
+//! The *Bedrock Edition* protocol sends little-endian NBT where ints, longs
+//! and all lengths are VarInts. Use [`from_bytes_bedrock_network`] or
+//! [`Deserializer::from_bytes_bedrock_network`] to read it. Since the elements
+//! of its int and long arrays differ in size, the borrowed
+//! [`borrow::IntArray`][`crate::borrow::IntArray`] and
+//! [`borrow::LongArray`][`crate::borrow::LongArray`] cannot be used with it.
+//!
+//! # Untrusted input
+//!
+//! Deserialization recurses for each compound or list nested in the input, so
+//! deeply nested input could overflow the stack. Nesting deeper than
+//! [`DeOpts::DEFAULT_MAX_DEPTH`] is an error instead. Use
+//! [`from_bytes_with_opts`] or [`Deserializer::with_opts`] to change the limit.
+//!
+//! # Stateful deserialization
+//!
+//! `&mut Deserializer` implements serde's `Deserializer`, so a
+//! [`DeserializeSeed`][`serde::de::DeserializeSeed`] can be given one
+//! directly. This lets deserialization use state of your own, eg to intern
+//! block names as they are read.
+//!
+//! The `'de` lifetime of a [`Deserializer::from_bytes`] is that of the input,
+//! so a seed can keep anything it borrows from the input after the
+//! deserializer is gone. A [`Deserializer::from_reader`] has nothing to
+//! borrow, and borrowing types must be owned instead.
+//!
+//! ```rust
+//! use std::{collections::HashSet, fmt};
+//! use fastnbt::de::Deserializer;
+//! use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, Visitor};
+//!
+//! /// Collects the keys of the root compound, borrowed from the input.
+//! struct Keys<'a, 'de>(&'a mut HashSet<&'de str>);
+//!
+//! impl<'a, 'de> DeserializeSeed<'de> for Keys<'a, 'de> {
+//!     type Value = ();
+//!
+//!     fn deserialize<D: serde::Deserializer<'de>>(self, d: D) -> Result<(), D::Error> {
+//!         d.deserialize_map(self)
+//!     }
+//! }
+//!
+//! impl<'a, 'de> Visitor<'de> for Keys<'a, 'de> {
+//!     type Value = ();
+//!
+//!     fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+//!         f.write_str("compound")
+//!     }
+//!
+//!     fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+//!         while let Some(key) = map.next_key()? {
+//!             self.0.insert(key);
+//!             map.next_value::<IgnoredAny>()?;
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # let data = fastnbt::to_bytes(&std::collections::HashMap::from([("a", 1), ("b", 2)])).unwrap();
+//! let mut keys = HashSet::new();
+//! let mut de = Deserializer::from_bytes(&data);
+//! Keys(&mut keys).deserialize(&mut de).unwrap();
+//! de.end().unwrap();
+//!
+//! assert_eq!(HashSet::from(["a", "b"]), keys);
+//! ```
+//!
 //! # Example Minecraft types
 //!
 //! This section demonstrates writing types for a few real Minecraft structures.
@@ -135,6 +258,23 @@
 //! }
 //! ```
 //!
+//! Serde reads the whole compound before it knows the variant, and buffers
+//! the values in its own format. A few types do not survive this:
+//!
+//! * `bool` cannot be read from a buffered integer. Use
+//!   `#[serde(with = "fastnbt::flag")]`, see [`flag`][`crate::flag`].
+//! * `u128` and `i128` cannot be buffered at all. Use
+//!   [`Uuid`][`crate::Uuid`] for UUIDs.
+//! * The [`borrow`][`crate::borrow`] array types cannot borrow from a buffer.
+//!   The owned [`ByteArray`][`crate::ByteArray`],
+//!   [`IntArray`][`crate::IntArray`] and [`LongArray`][`crate::LongArray`]
+//!   work as normal.
+//!
+//! The same applies to structs with a `#[serde(flatten)]` field and to
+//! untagged enums. Buffered arrays are still told apart from lists, so a field
+//! whose type changed between versions can be an untagged enum of eg
+//! [`IntArray`][`crate::IntArray`], `Vec<i32>` and `i32`.
+//!
 //! ## Capture unknown entities
 //!
 //! If you need to capture all entity types, but do not wish to manually type
@@ -237,15 +377,33 @@
 //! }
 //! ```
 
-use std::borrow::Cow;
-use std::convert::{TryFrom, TryInto};
-use std::ops::Range;
-
-use crate::de_arrays::ArrayWrapperAccess;
+use alloc::borrow::{Cow, ToOwned};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::{TryFrom, TryInto};
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::io::Read;
+
+use crate::arrays::{int_array_to_u128, u128_array_len_error};
+use crate::de_arrays::{element_size, ArrayAccess, ArrayWrapperAccess};
 use crate::error::{Error, Result};
-use crate::Tag;
-use byteorder::{BigEndian, ReadBytesExt};
-
+#[cfg(feature = "std")]
+use crate::Compression;
+use crate::{
+    Endian, Set, Tag, BYTE_ARRAY_TOKEN, COMPOUND_TOKEN, INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN,
+};
+#[cfg(feature = "std")]
+use flate2::read::{GzDecoder, ZlibDecoder};
+
+#[cfg(feature = "async")]
+pub use crate::de_async::from_async_reader;
+pub use crate::de_input::Input;
+#[cfg(feature = "std")]
+pub use crate::de_input::IoRead;
+
+use serde::de::IntoDeserializer;
 use serde::{de, forward_to_deserialize_any};
 
 /// Deserialize into a `T` from some NBT data. See the [`de`] module for more
@@ -285,16 +443,483 @@ where
 
     let mut des = Deserializer::from_bytes(input);
     let t = T::deserialize(&mut des)?;
+    des.end()?;
+    Ok(t)
+}
+
+/// Deserialize into a `T` from some NBT data, also returning the name of the
+/// root compound. This is usually empty, but some files use it.
+///
+/// ```
+/// # use fastnbt::Value;
+/// # let data = fastnbt::to_bytes_named("Level", &Value::Compound(Default::default())).unwrap();
+/// let (name, _): (String, Value) = fastnbt::de::from_bytes_named(&data).unwrap();
+/// assert_eq!("Level", name);
+/// ```
+pub fn from_bytes_named<'a, T>(input: &'a [u8]) -> Result<(String, T)>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut des = Deserializer::from_bytes(input);
+    let t = T::deserialize(&mut des)?;
+    des.end()?;
+
+    let name = des.root_name.take().ok_or_else(Error::no_root_compound)?;
+    Ok((name, t))
+}
+
+/// Deserialize into a `T` from some NBT data, with options such as the maximum
+/// nesting depth. See [`DeOpts`].
+///
+/// ```
+/// # use fastnbt::{de::DeOpts, error::ErrorKind, Map, Value};
+/// // A compound within the root compound, nested 2 deep.
+/// let inner = Value::Compound(Map::new());
+/// let root = Value::Compound(Map::from([("inner".to_string(), inner)]));
+/// let data = fastnbt::to_bytes(&root).unwrap();
+///
+/// let opts = DeOpts::new().max_depth(1);
+/// let err = fastnbt::de::from_bytes_with_opts::<Value>(&data, opts).unwrap_err();
+/// assert_eq!(&ErrorKind::DepthLimitExceeded, err.kind());
+/// ```
+pub fn from_bytes_with_opts<'a, T>(input: &'a [u8], opts: DeOpts) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut des = Deserializer::from_bytes(input).with_opts(opts);
+    let t = T::deserialize(&mut des)?;
+    if !opts.allow_trailing {
+        des.end()?;
+    }
+    Ok(t)
+}
+
+/// Deserialize into a `T` from the start of some NBT data, returning the rest
+/// of the input after the end of the NBT. This allows reading documents that
+/// follow one another, and `T` can still borrow from the input.
+///
+/// ```
+/// # use fastnbt::Value;
+/// # let doc = fastnbt::to_bytes(&Value::Compound(Default::default())).unwrap();
+/// // Several documents one after another.
+/// let data = [doc.as_slice(), &doc, &doc].concat();
+///
+/// let mut rest = data.as_slice();
+/// let mut docs = vec![];
+/// while !rest.is_empty() {
+///     let (doc, tail): (Value, _) = fastnbt::de::from_bytes_partial(rest).unwrap();
+///     docs.push(doc);
+///     rest = tail;
+/// }
+/// assert_eq!(3, docs.len());
+/// ```
+pub fn from_bytes_partial<'a, T>(input: &'a [u8]) -> Result<(T, &'a [u8])>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut des = Deserializer::from_bytes(input);
+    let t = T::deserialize(&mut des)?;
+    Ok((t, des.into_inner()))
+}
+
+/// Deserialize only the value at `path` in some NBT into a `T`, skipping over
+/// everything before it. The path is the names of the compound entries and
+/// indices of the list elements leading to the value, starting inside the root
+/// compound. Returns None if there is no value at the path.
+///
+/// Skipped values are not deserialized at all, and arrays are skipped by their
+/// length, so this is much faster than deserializing a whole chunk when only a
+/// field or two is needed. The input after the value is not looked at.
+///
+/// ```
+/// # use fastnbt::{Map, Value};
+/// # use std::collections::HashMap;
+/// # let section = Value::Compound(Map::from([("Y".to_owned(), Value::Byte(3))]));
+/// # let level = Map::from([
+/// #     ("xPos".to_owned(), Value::Int(-2)),
+/// #     ("Sections".to_owned(), Value::List(vec![section.clone(), section])),
+/// # ]);
+/// # let data = fastnbt::to_bytes(&HashMap::from([("Level", level)])).unwrap();
+/// let x: Option<i32> = fastnbt::de::get_path(&data, &["Level", "xPos"])?;
+/// assert_eq!(Some(-2), x);
+///
+/// let y: Option<i8> = fastnbt::de::get_path(&data, &["Level", "Sections", "1", "Y"])?;
+/// assert_eq!(Some(3), y);
+///
+/// let missing: Option<i32> = fastnbt::de::get_path(&data, &["Level", "zPos"])?;
+/// assert_eq!(None, missing);
+/// # Ok::<(), fastnbt::error::Error>(())
+/// ```
+pub fn get_path<'a, T>(input: &'a [u8], path: &[&str]) -> Result<Option<T>>
+where
+    T: de::Deserialize<'a>,
+{
+    Deserializer::from_bytes(input).deserialize_path(path)
+}
+
+/// Deserialize into a `T` from some little-endian NBT data, as used by
+/// *Minecraft: Bedrock Edition*. See the [`de`] module for more information.
+///
+/// [`de`]: ./index.html
+pub fn from_bytes_le<'a, T>(input: &'a [u8]) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut des = Deserializer::from_bytes_le(input);
+    let t = T::deserialize(&mut des)?;
+    des.end()?;
+    Ok(t)
+}
+
+/// Deserialize into a `T` from some network NBT data, which has no root name.
+/// See the [`de`] module for more information.
+///
+/// [`de`]: ./index.html
+pub fn from_bytes_network<'a, T>(input: &'a [u8]) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut des = Deserializer::from_bytes_network(input);
+    let t = T::deserialize(&mut des)?;
+    des.end()?;
+    Ok(t)
+}
+
+/// Deserialize into a `T` from the network NBT of *Minecraft: Bedrock
+/// Edition*, which uses VarInts. See the [`de`] module for more information.
+///
+/// ```
+/// # use std::collections::HashMap;
+/// let data = fastnbt::to_bytes_bedrock_network(&HashMap::from([("Count", -1)])).unwrap();
+/// let value: HashMap<String, i32> = fastnbt::de::from_bytes_bedrock_network(&data).unwrap();
+/// assert_eq!(-1, value["Count"]);
+/// ```
+///
+/// [`de`]: ./index.html
+pub fn from_bytes_bedrock_network<'a, T>(input: &'a [u8]) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut des = Deserializer::from_bytes_bedrock_network(input);
+    let t = T::deserialize(&mut des)?;
+    des.end()?;
+    Ok(t)
+}
+
+/// Deserialize into a `T` from NBT data read from a reader, eg a
+/// `flate2::read::GzDecoder`, without reading it all into memory first.
+///
+/// ```no_run
+/// # use fastnbt::Value;
+/// # use flate2::read::GzDecoder;
+/// # fn main() -> fastnbt::error::Result<()> {
+/// let file = std::fs::File::open("level.dat")?;
+/// let val: Value = fastnbt::de::from_reader(GzDecoder::new(file))?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Nothing can borrow from a reader, so only types that own all of their data
+/// can be deserialized, such as `String` rather than `&str`, and
+/// [`LongArray`][`crate::LongArray`] rather than
+/// [`borrow::LongArray`][`crate::borrow::LongArray`]. Borrowing types will not
+/// compile:
+///
+/// ```compile_fail
+/// # use serde::Deserialize;
+/// #[derive(Deserialize)]
+/// struct Section<'a> {
+///     #[serde(borrow)]
+///     block_states: fastnbt::borrow::LongArray<'a>,
+/// }
+///
+/// let data: &[u8] = &[];
+/// let section: Section = fastnbt::de::from_reader(data).unwrap();
+/// ```
+///
+/// The owned array types are read from the reader a few thousand elements at
+/// a time, straight into the array, so are not buffered twice.
+///
+/// Reading from a slice this way is slower than [`from_bytes`], since every
+/// string and array is copied.
+///
+/// Reading stops at the end of the NBT. Unlike [`from_bytes`], anything after
+/// it is not an error, and is left in the reader.
+#[cfg(feature = "std")]
+pub fn from_reader<R, T>(reader: R) -> Result<T>
+where
+    R: Read,
+    T: de::DeserializeOwned,
+{
+    let mut des = Deserializer::from_reader(reader);
+    let t = T::deserialize(&mut des)?;
     Ok(t)
 }
 
+/// Deserialize into a `T` from NBT data read from a reader, which may be gzip
+/// compressed, zlib compressed, or uncompressed. Returns the compression that
+/// was detected along with the value, so that it can be written back the same
+/// way. See [`Compression::detect`].
+///
+/// ```no_run
+/// # use fastnbt::{Compression, Value};
+/// # fn main() -> fastnbt::error::Result<()> {
+/// let file = std::fs::File::open("level.dat")?;
+/// let (val, compression): (Value, _) = fastnbt::de::from_compressed_reader(file)?;
+/// assert_eq!(Compression::Gzip, compression);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub fn from_compressed_reader<R, T>(mut reader: R) -> Result<(T, Compression)>
+where
+    R: Read,
+    T: de::DeserializeOwned,
+{
+    // Read the header, then put it back in front of the rest of the input.
+    let mut header = Vec::with_capacity(2);
+    (&mut reader).take(2).read_to_end(&mut header)?;
+    let compression = Compression::detect(&header);
+    let reader = header.as_slice().chain(reader);
+
+    let t = match compression {
+        Compression::Gzip => from_reader(GzDecoder::new(reader))?,
+        Compression::Zlib => from_reader(ZlibDecoder::new(reader))?,
+        Compression::Uncompressed => from_reader(reader)?,
+    };
+    Ok((t, compression))
+}
+
+/// Deserialize into a `T` from some NBT data, which may be gzip compressed,
+/// zlib compressed, or uncompressed. Returns the compression that was detected
+/// along with the value. See [`from_compressed_reader`].
+///
+/// Compressed data is decompressed into a buffer first, so `T` cannot borrow
+/// from the input.
+#[cfg(feature = "std")]
+pub fn from_maybe_compressed_bytes<T>(input: &[u8]) -> Result<(T, Compression)>
+where
+    T: de::DeserializeOwned,
+{
+    let compression = Compression::detect(input);
+    let mut buf = Vec::new();
+
+    let data = match compression {
+        Compression::Gzip => {
+            GzDecoder::new(input).read_to_end(&mut buf)?;
+            &buf
+        }
+        Compression::Zlib => {
+            ZlibDecoder::new(input).read_to_end(&mut buf)?;
+            &buf
+        }
+        Compression::Uncompressed => input,
+    };
+
+    Ok((from_bytes(data)?, compression))
+}
+
+/// Options for a [`Deserializer`]. See [`from_bytes_with_opts`] and
+/// [`Deserializer::with_opts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeOpts {
+    max_depth: usize,
+    allow_trailing: bool,
+    lenient_arrays: bool,
+    lossy_strings: bool,
+    on_duplicate_key: DuplicateKeyPolicy,
+    human_readable: bool,
+}
+
+/// What to do when a compound contains the same key more than once. See
+/// [`DeOpts::on_duplicate_key`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// Pass every entry on to the type being deserialized. Maps such as
+    /// [`Value`][`crate::Value`]'s compounds keep the last value, like the
+    /// game does. Structs derived with serde reject a field that appears twice
+    /// themselves, so use one of the other policies to handle them the same.
+    LastWins,
+
+    /// Keep the first entry with each key, and skip over any later ones.
+    FirstWins,
+
+    /// Fail with a [`DuplicateKey`][`crate::error::ErrorKind::DuplicateKey`]
+    /// error.
+    Error,
+}
+
+impl DeOpts {
+    /// The default maximum depth, the same as the game's own limit, so any NBT
+    /// the game writes can be read. Debug builds use far more stack for each
+    /// level, and may need a thread with a bigger stack than the default to
+    /// reach it.
+    pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+    pub fn new() -> Self {
+        Self {
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            allow_trailing: false,
+            lenient_arrays: false,
+            lossy_strings: false,
+            on_duplicate_key: DuplicateKeyPolicy::LastWins,
+            human_readable: false,
+        }
+    }
+
+    /// The deepest compounds and lists can be nested, counting the root
+    /// compound as 1. Deeper input is a [`DepthLimitExceeded`] error.
+    ///
+    /// Deserialization recurses for each level of nesting, so without a limit
+    /// a small malicious input can overflow the stack. Raising the limit far
+    /// beyond the default may need a thread with a larger stack.
+    ///
+    /// [`DepthLimitExceeded`]: crate::error::ErrorKind::DepthLimitExceeded
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Whether [`from_bytes_with_opts`] allows more input after the end of the
+    /// NBT. By default this is a [`TrailingBytes`] error, as it usually means
+    /// the input was not what was expected.
+    ///
+    /// To find where the NBT ended, use [`from_bytes_partial`], or a
+    /// [`Deserializer`] and call [`into_inner`][`Deserializer::into_inner`] to
+    /// get the rest of the input.
+    ///
+    /// [`TrailingBytes`]: crate::error::ErrorKind::TrailingBytes
+    pub fn allow_trailing(mut self, allow_trailing: bool) -> Self {
+        self.allow_trailing = allow_trailing;
+        self
+    }
+
+    /// Whether NBT arrays and lists of the same element type can stand in for
+    /// each other. Different versions of the game, and mods, store some values
+    /// as an Int Array in one place and a List of Int in another.
+    ///
+    /// When enabled, a List of Byte, Int or Long is accepted where a
+    /// [`ByteArray`][`crate::ByteArray`], [`IntArray`][`crate::IntArray`] or
+    /// [`LongArray`][`crate::LongArray`] (or their [`borrow`][`crate::borrow`]
+    /// versions) is expected. NBT arrays are also accepted for sequences such
+    /// as `Vec<i32>`, which is otherwise an error. The element types must still
+    /// match, a List of Int is never a [`LongArray`][`crate::LongArray`].
+    ///
+    /// Fixed-size arrays such as `[i32; 4]` must have the same length as the
+    /// NBT array, otherwise the error is
+    /// [`ErrorKind::ArrayLengthMismatch`][`crate::error::ErrorKind::ArrayLengthMismatch`].
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use fastnbt::{de::DeOpts, IntArray, Value};
+    /// # use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Biomes {
+    ///     biomes: IntArray,
+    /// }
+    ///
+    /// let list = Value::List(vec![Value::Int(1), Value::Int(2)]);
+    /// let data = fastnbt::to_bytes(&HashMap::from([("biomes", list)])).unwrap();
+    ///
+    /// let opts = DeOpts::new().lenient_arrays(true);
+    /// let biomes: Biomes = fastnbt::de::from_bytes_with_opts(&data, opts).unwrap();
+    /// assert_eq!(&[1, 2], biomes.biomes.as_slice());
+    /// ```
+    pub fn lenient_arrays(mut self, lenient_arrays: bool) -> Self {
+        self.lenient_arrays = lenient_arrays;
+        self
+    }
+
+    /// Whether strings that are not valid Java CESU-8 (or UTF-8 for
+    /// little-endian NBT) are decoded anyway, with each invalid sequence
+    /// replaced by U+FFFD. By default they are a [`Nonunicode`] error.
+    ///
+    /// Strings that had replacements are always copied rather than borrowed.
+    ///
+    /// [`Nonunicode`]: crate::error::ErrorKind::Nonunicode
+    pub fn lossy_strings(mut self, lossy_strings: bool) -> Self {
+        self.lossy_strings = lossy_strings;
+        self
+    }
+
+    /// What to do when a compound contains the same key more than once, which
+    /// the game never writes but corrupt or modded data might. By default this
+    /// is [`LastWins`][`DuplicateKeyPolicy::LastWins`], which costs nothing.
+    /// The other policies keep track of the keys of each compound.
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use fastnbt::de::{DeOpts, DuplicateKeyPolicy};
+    /// # let data = [10, 0, 0, 1, 0, 1, b'a', 1, 1, 0, 1, b'a', 2, 0];
+    /// // A compound with the entry "a" twice, with the values 1 then 2.
+    /// let compound: HashMap<String, i8> = fastnbt::de::from_bytes(&data).unwrap();
+    /// assert_eq!(2, compound["a"]);
+    ///
+    /// let opts = DeOpts::new().on_duplicate_key(DuplicateKeyPolicy::FirstWins);
+    /// let compound: HashMap<String, i8> = fastnbt::de::from_bytes_with_opts(&data, opts).unwrap();
+    /// assert_eq!(1, compound["a"]);
+    ///
+    /// let opts = DeOpts::new().on_duplicate_key(DuplicateKeyPolicy::Error);
+    /// assert!(fastnbt::de::from_bytes_with_opts::<HashMap<String, i8>>(&data, opts).is_err());
+    /// ```
+    pub fn on_duplicate_key(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.on_duplicate_key = policy;
+        self
+    }
+
+    /// What the deserializer answers to serde's
+    /// [`is_human_readable`][`serde::Deserializer::is_human_readable`]. Some
+    /// types, such as IP addresses, are read as a string when it is true and
+    /// in a compact binary form when it is false.
+    ///
+    /// NBT is a binary format, so this is false by default. Set it to true to
+    /// read data written by a serializer that was also set to be human
+    /// readable, see [`Serializer::human_readable`][`crate::ser::Serializer::human_readable`].
+    /// Compound names are always read as if human readable, as they are
+    /// always strings.
+    pub fn human_readable(mut self, human_readable: bool) -> Self {
+        self.human_readable = human_readable;
+        self
+    }
+}
+
+impl Default for DeOpts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Deserializer for NBT data. See the [`de`] module for more information.
 ///
 /// [`de`]: ./index.html
-pub struct Deserializer<'de> {
-    pub(crate) input: InputHelper<'de>,
-    layers: Vec<Layer>,
+///
+/// The input `I` is either a byte slice, or an [`IoRead`] when deserializing
+/// from a reader.
+///
+/// Use `&mut Deserializer` with a `Deserialize` type or a `DeserializeSeed`,
+/// see [Stateful deserialization](./index.html#stateful-deserialization).
+pub struct Deserializer<'de, I = &'de [u8]> {
+    pub(crate) input: InputHelper<I>,
+    layers: Vec<Layer<'de>>,
     last_hint: Option<&'static str>,
+    named_root: bool,
+    root_name: Option<String>,
+    max_depth: usize,
+    lenient_arrays: bool,
+    /// Names of the current entries that had to be copied, eg when reading
+    /// from a reader, indexed by depth. Kept between compounds to reuse the
+    /// allocations.
+    owned_keys: Vec<String>,
+    on_duplicate_key: DuplicateKeyPolicy,
+    /// Keys of the compounds currently being read, indexed by depth. Only
+    /// kept for policies other than [`DuplicateKeyPolicy::LastWins`].
+    seen_keys: Vec<Set<String>>,
+    /// Counts the compound entries and list or array elements started, so
+    /// that a list can tell whether an error from one of its elements came
+    /// from inside it.
+    pub(crate) entries_read: usize,
+    human_readable: bool,
+    _de: PhantomData<&'de ()>,
 }
 
 impl<'de> Deserializer<'de> {
@@ -304,27 +929,687 @@ impl<'de> Deserializer<'de> {
     /// [`de`]: ./index.html
     pub fn from_bytes(input: &'de [u8]) -> Self {
         Self {
-            input: InputHelper(input),
-            layers: vec![],
+            input: InputHelper::new(input, Endian::Big),
+            layers: Vec::with_capacity(LAYERS_CAPACITY),
+            last_hint: None,
+            named_root: true,
+            root_name: None,
+            max_depth: DeOpts::DEFAULT_MAX_DEPTH,
+            lenient_arrays: false,
+            owned_keys: Vec::new(),
+            on_duplicate_key: DuplicateKeyPolicy::LastWins,
+            seen_keys: Vec::new(),
+            entries_read: 0,
+            human_readable: false,
+            _de: PhantomData,
+        }
+    }
+
+    /// Create Deserializer for a `T` from some little-endian NBT data. See the
+    /// [`de`] module for more information.
+    ///
+    /// [`de`]: ./index.html
+    pub fn from_bytes_le(input: &'de [u8]) -> Self {
+        Self {
+            input: InputHelper::new(input, Endian::Little),
+            ..Self::from_bytes(input)
+        }
+    }
+
+    /// Create Deserializer for a `T` from the network NBT of *Minecraft:
+    /// Bedrock Edition*, which uses VarInts. See the [`de`] module for more
+    /// information.
+    ///
+    /// [`de`]: ./index.html
+    pub fn from_bytes_bedrock_network(input: &'de [u8]) -> Self {
+        Self {
+            input: InputHelper::new(input, Endian::VarInt),
+            ..Self::from_bytes(input)
+        }
+    }
+
+    /// Create Deserializer for a `T` from some network NBT data, which has no
+    /// root name. See the [`de`] module for more information.
+    ///
+    /// [`de`]: ./index.html
+    pub fn from_bytes_network(input: &'de [u8]) -> Self {
+        Self {
+            named_root: false,
+            ..Self::from_bytes(input)
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> {
+    /// Check that all of the input has been used, so that nothing follows the
+    /// NBT. This is done by [`from_bytes`].
+    ///
+    /// ```
+    /// # use fastnbt::{de::Deserializer, error::ErrorKind, Value};
+    /// # use serde::Deserialize;
+    /// let mut data = fastnbt::to_bytes(&Value::Compound(Default::default())).unwrap();
+    /// data.push(0);
+    ///
+    /// let mut de = Deserializer::from_bytes(&data);
+    /// Value::deserialize(&mut de).unwrap();
+    /// assert_eq!(&ErrorKind::TrailingBytes(1), de.end().unwrap_err().kind());
+    /// ```
+    pub fn end(&self) -> Result<()> {
+        match self.input.0.len() {
+            0 => Ok(()),
+            n => Err(Error::trailing_bytes(n)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de, R: Read> Deserializer<'de, IoRead<R>> {
+    /// Create Deserializer for a `T` from NBT data read from a reader. See
+    /// [`from_reader`] for more information.
+    pub fn from_reader(reader: R) -> Self {
+        Self {
+            input: InputHelper::new(IoRead::new(reader), Endian::Big),
+            layers: Vec::with_capacity(LAYERS_CAPACITY),
             last_hint: None,
+            named_root: true,
+            root_name: None,
+            max_depth: DeOpts::DEFAULT_MAX_DEPTH,
+            lenient_arrays: false,
+            owned_keys: Vec::new(),
+            on_duplicate_key: DuplicateKeyPolicy::LastWins,
+            seen_keys: Vec::new(),
+            entries_read: 0,
+            human_readable: false,
+            _de: PhantomData,
         }
     }
 }
 
+impl<'de, I: Input<'de>> Deserializer<'de, I> {
+    /// Use the given options, see [`DeOpts`].
+    pub fn with_opts(mut self, opts: DeOpts) -> Self {
+        self.max_depth = opts.max_depth;
+        self.lenient_arrays = opts.lenient_arrays;
+        self.input.3 = opts.lossy_strings;
+        self.on_duplicate_key = opts.on_duplicate_key;
+        self.human_readable = opts.human_readable;
+        self
+    }
+
+    /// Get the input back, with the NBT read so far removed. For a byte slice
+    /// this is the rest of the slice, so any following data can be read.
+    ///
+    /// ```
+    /// # use fastnbt::{de::Deserializer, Value};
+    /// # use serde::Deserialize;
+    /// # let nbt = fastnbt::to_bytes(&Value::Compound(Default::default())).unwrap();
+    /// // Some NBT followed by other data.
+    /// let data = [nbt.as_slice(), b"more"].concat();
+    ///
+    /// let mut de = Deserializer::from_bytes(&data);
+    /// Value::deserialize(&mut de).unwrap();
+    /// assert_eq!(b"more", de.into_inner());
+    /// ```
+    pub fn into_inner(self) -> I {
+        self.input.0
+    }
+
+    /// The name of the root compound, once it has been read. None before then,
+    /// or for network NBT which has no root name.
+    pub fn root_name(&self) -> Option<&str> {
+        self.root_name.as_deref()
+    }
+
+    /// Deserialize only the value at `path` into a `T`, skipping over
+    /// everything before it. See [`get_path`].
+    pub fn deserialize_path<T>(&mut self, path: &[&str]) -> Result<Option<T>>
+    where
+        T: de::Deserialize<'de>,
+    {
+        let (tag, display) = match self.seek_path(path) {
+            Ok(Some(found)) => found,
+            Ok(None) => return Ok(None),
+            Err(e) => return Err(e.at_offset(self.input.offset())),
+        };
+
+        // Deserialize the value as if it were the only entry of a compound,
+        // named with the whole path for errors.
+        self.push_layer(Layer::Compound {
+            current_tag: Some(tag),
+            stage: Stage::Value,
+            key: None,
+        })?;
+        remember_owned_key(&mut self.owned_keys, 1, &display);
+
+        let t = T::deserialize(&mut *self)
+            .map_err(|e| e.at_offset(self.input.offset()).at_path(self.path()))?;
+        self.layers.pop();
+        Ok(Some(t))
+    }
+
+    /// Skip over the input up to the payload of the value at `path`, returning
+    /// its tag and the path as it appears in errors, eg `Level.Sections[3].Y`.
+    /// None if there is no value at the path.
+    fn seek_path(&mut self, path: &[&str]) -> Result<Option<(Tag, String)>> {
+        self.consume_root_header()?;
+
+        let mut tag = Tag::Compound;
+        let mut display = String::new();
+        for (depth, segment) in (1..).zip(path) {
+            match tag {
+                Tag::Compound => loop {
+                    let entry_tag = self.input.consume_tag()?;
+                    if entry_tag == Tag::End {
+                        return Ok(None);
+                    }
+
+                    if self.input.consume_name()? == *segment {
+                        if !display.is_empty() {
+                            display.push('.');
+                        }
+                        display.push_str(segment);
+                        tag = entry_tag;
+                        break;
+                    }
+                    self.input.ignore_value(entry_tag, depth, self.max_depth)?;
+                },
+                Tag::List => {
+                    let element_tag = self.input.consume_tag()?;
+                    let size = self
+                        .input
+                        .consume_size(self.input.min_payload_len(element_tag))?;
+
+                    let index = match segment.parse::<i32>() {
+                        Ok(index) if (0..size).contains(&index) => index,
+                        _ => return Ok(None),
+                    };
+                    for _ in 0..index {
+                        self.input
+                            .ignore_value(element_tag, depth, self.max_depth)?;
+                    }
+                    display.push_str(&format!("[{}]", index));
+                    tag = element_tag;
+                }
+                // Only compounds and lists have anything inside them.
+                _ => return Ok(None),
+            }
+        }
+
+        Ok(Some((tag, display)))
+    }
+
+    /// Read the tag of the root compound and the name that follows it, which
+    /// is kept for [`root_name`][Self::root_name]. Network NBT has no name.
+    fn consume_root_header(&mut self) -> Result<()> {
+        let tag = self.input.consume_tag()?;
+        if tag != Tag::Compound {
+            return Err(Error::no_root_compound());
+        }
+
+        if self.named_root {
+            self.root_name = Some(self.input.consume_name()?.into_owned());
+        }
+        Ok(())
+    }
+
+    /// Deserialize the root compound. We need to get the tag and the following
+    /// name and discard it.
+    fn deserialize_root<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.consume_root_header()?;
+
+        self.push_layer(Layer::Compound {
+            current_tag: None,
+            stage: Stage::Tag,
+            key: None,
+        })?;
+
+        match self.on_duplicate_key {
+            DuplicateKeyPolicy::LastWins => visitor.visit_map(CompoundAccess::new(self)),
+            _ => visitor.visit_map(UniqueCompoundAccess::new(self)),
+        }
+    }
+
+    /// The path to the value being deserialized, from the compound entries and
+    /// list elements we are in. Only built for errors, so that keeping track
+    /// of it costs little more than the layers already do.
+    #[cold]
+    fn path(&self) -> String {
+        self.path_to(self.layers.len())
+    }
+
+    /// The path through the first `depth` layers.
+    #[cold]
+    fn path_to(&self, depth: usize) -> String {
+        let mut path = String::new();
+        for (i, layer) in self.layers[..depth].iter().enumerate() {
+            match layer {
+                // No tag means we are between entries, eg reading the next tag.
+                Layer::Compound {
+                    current_tag: Some(_),
+                    key,
+                    ..
+                } => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    match key {
+                        Some(key) => path.push_str(key),
+                        None => path.push_str(&self.owned_keys[i]),
+                    }
+                }
+                Layer::Compound { .. } => {}
+                Layer::List {
+                    remaining_elements,
+                    size,
+                    ..
+                } => {
+                    // The element being read has already been counted off.
+                    let index = size - remaining_elements - 1;
+                    if index >= 0 {
+                        path.push_str(&format!("[{}]", index));
+                    }
+                }
+            }
+        }
+        path
+    }
+
+    /// Report a type mismatch of an element of the list at `depth` as the
+    /// list being of the wrong type, at the path of the list itself. Only if
+    /// nothing was read from inside the element since `entries_read`, as
+    /// otherwise the mismatch is in some value within it.
+    #[cold]
+    #[inline(never)]
+    fn list_element_error(&self, err: Error, depth: usize, entries_read: usize) -> Error {
+        if self.entries_read != entries_read {
+            return err;
+        }
+        let expected = match err.expected() {
+            Some(expected) => expected,
+            None => return err,
+        };
+        match &self.layers[depth - 1] {
+            Layer::List {
+                element_tag, size, ..
+            } => Error::unexpected_list(*element_tag, *size, expected)
+                .at_path(self.path_to(depth - 1)),
+            Layer::Compound { .. } => err,
+        }
+    }
+
+    /// Enter a compound or list, if that would not nest too deep.
+    fn push_layer(&mut self, layer: Layer<'de>) -> Result<()> {
+        if self.layers.len() >= self.max_depth {
+            return Err(Error::depth_limit_exceeded(self.max_depth));
+        }
+        self.layers.push(layer);
+        Ok(())
+    }
+
+    /// If the next value is an IntArray, consume it as a 128-bit integer.
+    /// Returns None for any other value, leaving it unconsumed.
+    fn consume_u128(&mut self) -> Result<Option<u128>> {
+        let tag = match self.layers.last() {
+            Some(Layer::Compound {
+                current_tag: Some(tag),
+                stage: Stage::Value,
+                ..
+            }) => *tag,
+            Some(Layer::List { element_tag, .. }) => *element_tag,
+            _ => return Ok(None),
+        };
+
+        if tag != Tag::IntArray {
+            return Ok(None);
+        }
+
+        if let Some(Layer::Compound { stage, .. }) = self.layers.last_mut() {
+            *stage = Stage::Tag;
+        }
+
+        let size = self.input.consume_list_size()?;
+        if size != 4 {
+            return Err(u128_array_len_error(size));
+        }
+
+        let mut ints = [0; 4];
+        for int in &mut ints {
+            *int = self.input.read_i32()?;
+        }
+
+        Ok(Some(int_array_to_u128(ints)))
+    }
+
+    /// If the next value is a List that can be read as the given array type,
+    /// consume its header and return its size. The elements of a List and an
+    /// array are laid out the same, so the rest can be read as the array.
+    /// Returns None for any other value, leaving it unconsumed.
+    fn consume_list_as_array(&mut self, array_tag: Tag) -> Result<Option<i32>> {
+        let tag = match self.layers.last() {
+            Some(Layer::Compound {
+                current_tag: Some(tag),
+                stage: Stage::Value,
+                ..
+            }) => *tag,
+            Some(Layer::List { element_tag, .. }) => *element_tag,
+            _ => return Ok(None),
+        };
+
+        if tag != Tag::List {
+            return Ok(None);
+        }
+
+        if let Some(Layer::Compound { stage, .. }) = self.layers.last_mut() {
+            *stage = Stage::Tag;
+        }
+
+        let element_tag = self.input.consume_tag()?;
+        let size = self
+            .input
+            .consume_size(self.input.min_payload_len(element_tag))?;
+
+        // Empty lists are often stored as a list of End.
+        let matches = match (array_tag, element_tag) {
+            (_, Tag::End) => size == 0,
+            (Tag::ByteArray, Tag::Byte) => true,
+            (Tag::IntArray, Tag::Int) => true,
+            (Tag::LongArray, Tag::Long) => true,
+            _ => false,
+        };
+
+        if !matches {
+            return Err(Error::bespoke(format!(
+                "expected {:?}, found List of {:?}",
+                array_tag, element_tag
+            )));
+        }
+
+        Ok(Some(size))
+    }
+
+    /// If the next value is an NBT array and lenient arrays are enabled,
+    /// consume its header for a fixed-size array of `len` elements, and
+    /// return its tag and size. Errors if it does not have exactly `len`
+    /// elements. Returns None for any other value, leaving it unconsumed.
+    fn consume_array_as_tuple(&mut self, len: usize) -> Result<Option<(Tag, i32)>> {
+        let tag = match self.layers.last() {
+            Some(Layer::Compound {
+                current_tag: Some(tag),
+                stage: Stage::Value,
+                ..
+            }) => *tag,
+            Some(Layer::List { element_tag, .. }) => *element_tag,
+            _ => return Ok(None),
+        };
+
+        if !self.lenient_arrays || !matches!(tag, Tag::ByteArray | Tag::IntArray | Tag::LongArray) {
+            return Ok(None);
+        }
+
+        if let Some(Layer::Compound { stage, .. }) = self.layers.last_mut() {
+            *stage = Stage::Tag;
+        }
+
+        let size = self.input.consume_size(self.input.min_element_len(tag))?;
+        let found = usize::try_from(size).map_err(|_| Error::invalid_size(size))?;
+        if found != len {
+            return Err(Error::array_length_mismatch(len, found));
+        }
+
+        Ok(Some((tag, size)))
+    }
+
+    /// Read a compound as the entries of a
+    /// [`borrow::Compound`][crate::borrow::Compound]: the name and tag of
+    /// each, with its payload skipped over and borrowed from the input to be
+    /// parsed later.
+    fn deserialize_raw_compound<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.input.0.as_slice().is_none() {
+            return Err(not_a_slice());
+        }
+
+        let tag = match self.layers.last_mut() {
+            None => {
+                // Nothing else annotates errors at the root.
+                return self
+                    .consume_root_header()
+                    .and_then(|()| visitor.visit_map(RawCompoundAccess::new(self)))
+                    .map_err(|e| e.at_offset(self.input.offset()));
+            }
+            Some(Layer::Compound {
+                current_tag: Some(tag),
+                stage: stage @ Stage::Value,
+                ..
+            }) => {
+                *stage = Stage::Tag;
+                *tag
+            }
+            Some(Layer::List { element_tag, .. }) => *element_tag,
+            _ => return de::Deserializer::deserialize_any(self, visitor),
+        };
+
+        if tag != Tag::Compound {
+            return Err(Error::bespoke(format!(
+                "expected Compound, found {:?}",
+                tag
+            )));
+        }
+
+        visitor.visit_map(RawCompoundAccess::new(self))
+    }
+}
+
+#[cold]
+fn not_a_slice() -> Error {
+    Error::bespoke("a borrowed Compound can only be read from a byte slice".to_owned())
+}
+
+/// Deserialize a `T` from the payload of an entry kept by a
+/// [`borrow::Compound`][crate::borrow::Compound], as if it were the value of
+/// that entry. Paths in errors start from the compound.
+pub(crate) fn from_entry_payload<'de, T>(
+    payload: &'de [u8],
+    endian: Endian,
+    tag: Tag,
+    key: Cow<'de, str>,
+) -> Result<T>
+where
+    T: de::Deserialize<'de>,
+{
+    let mut de = Deserializer {
+        input: InputHelper::new(payload, endian),
+        ..Deserializer::from_bytes(payload)
+    };
+    let key = match key {
+        Cow::Borrowed(key) => Some(key),
+        Cow::Owned(key) => {
+            remember_owned_key(&mut de.owned_keys, 1, &key);
+            None
+        }
+    };
+    de.layers.push(Layer::Compound {
+        current_tag: Some(tag),
+        stage: Stage::Value,
+        key,
+    });
+
+    T::deserialize(&mut de).map_err(|e| e.at_path(de.path()))
+}
+
+/// Presents a compound to a [`borrow::Compound`][crate::borrow::Compound] as a
+/// struct of its `entries`. Like the borrowed arrays, it is only told the byte
+/// order when it is not the usual big-endian.
+struct RawCompoundAccess<'a, 'de, I> {
+    de: &'a mut Deserializer<'de, I>,
+    entries_read: bool,
+    endian: Option<&'static str>,
+}
+
+impl<'a, 'de, I: Input<'de>> RawCompoundAccess<'a, 'de, I> {
+    fn new(de: &'a mut Deserializer<'de, I>) -> Self {
+        let endian = match de.input.1 {
+            Endian::Big => None,
+            Endian::Little => Some("little_endian"),
+            Endian::VarInt => Some("varint"),
+        };
+        Self {
+            de,
+            entries_read: false,
+            endian,
+        }
+    }
+}
+
+impl<'a, 'de, I: Input<'de>> de::MapAccess<'de> for RawCompoundAccess<'a, 'de, I> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match (self.entries_read, self.endian) {
+            (false, _) => seed.deserialize("entries".into_deserializer()).map(Some),
+            (true, Some(endian)) => seed.deserialize(endian.into_deserializer()).map(Some),
+            (true, None) => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        if !self.entries_read {
+            self.entries_read = true;
+            return seed.deserialize(RawEntries(&mut *self.de));
+        }
+        self.endian = None;
+        seed.deserialize(true.into_deserializer())
+    }
+}
+
+/// The entries of a compound, as a sequence of (name, tag, payload).
+struct RawEntries<'a, 'de, I>(&'a mut Deserializer<'de, I>);
+
+impl<'a, 'de, I: Input<'de>> de::Deserializer<'de> for RawEntries<'a, 'de, I> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_seq(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'a, 'de, I: Input<'de>> de::SeqAccess<'de> for RawEntries<'a, 'de, I> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let de = &mut *self.0;
+        let tag = de.input.consume_tag()?;
+        if tag == Tag::End {
+            return Ok(None);
+        }
+        let name = de.input.consume_name()?;
+
+        let rest = de.input.0.as_slice().ok_or_else(not_a_slice)?;
+        let marker = de.input.0.marker();
+        de.input
+            .ignore_value(tag, de.layers.len() + 1, de.max_depth)?;
+        let payload = &rest[..de.input.0.offset_from(marker)];
+
+        seed.deserialize(RawEntry {
+            name: Some(name),
+            tag: Some(tag.into()),
+            payload: Some(payload),
+        })
+        .map(Some)
+    }
+}
+
+/// One entry of a compound, as a tuple of its name, tag and payload.
+struct RawEntry<'de> {
+    name: Option<Cow<'de, str>>,
+    tag: Option<u8>,
+    payload: Option<&'de [u8]>,
+}
+
+impl<'de> de::Deserializer<'de> for RawEntry<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_seq(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for RawEntry<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if let Some(name) = self.name.take() {
+            return seed.deserialize(KeyDeserializer(name)).map(Some);
+        }
+        if let Some(tag) = self.tag.take() {
+            return seed.deserialize(tag.into_deserializer()).map(Some);
+        }
+        match self.payload.take() {
+            Some(payload) => seed
+                .deserialize(de::value::BorrowedBytesDeserializer::new(payload))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Initial capacity of the layer stack. Chunks nest around 10 deep, so this
+/// avoids repeatedly growing the stack while parsing them.
+const LAYERS_CAPACITY: usize = 16;
+
 enum Stage {
     Tag,
     Name,
     Value,
 }
 
-enum Layer {
+enum Layer<'de> {
     List {
         remaining_elements: i32, // would make more sense as usize, but format is i32.
         element_tag: Tag,
+        size: i32,
     },
     Compound {
         current_tag: Option<Tag>,
         stage: Stage,
+        /// Name of the current entry, for the path in errors. None if the name
+        /// could not be borrowed, see [`Deserializer::owned_keys`].
+        key: Option<&'de str>,
     },
 }
 
@@ -332,7 +1617,26 @@ enum Layer {
 /// input. If we wrote the helper functions as part of the Deserializer impl, it
 /// would force borrowing the entire deserializer mutably. This helper allows us
 /// to borrow just the input, making us free to also borrow/mutate the layers.
-pub(crate) struct InputHelper<'de>(pub(crate) &'de [u8]);
+///
+/// The third field marks where the input started, see [`Input::marker`]. The
+/// last is whether invalid strings are decoded lossily, see
+/// [`DeOpts::lossy_strings`].
+pub(crate) struct InputHelper<I>(pub(crate) I, pub(crate) Endian, usize, bool);
+
+/// Read a number in the input's byte order. These are fixed size in every
+/// format, see [`InputHelper::read_i32`] for those that are not.
+macro_rules! read_num {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            pub(crate) fn $method(&mut self) -> Result<$ty> {
+                Ok(match self.1 {
+                    Endian::Big => <$ty>::from_be_bytes(self.read_bytes()?),
+                    Endian::Little | Endian::VarInt => <$ty>::from_le_bytes(self.read_bytes()?),
+                })
+            }
+        )*
+    };
+}
 
 fn visit_cow_str<'de, V>(v: V, s: Cow<'de, str>) -> Result<V::Value>
 where
@@ -344,32 +1648,137 @@ where
     }
 }
 
-fn consume_value<'de, V>(de: &mut Deserializer<'de>, visitor: V, tag: Tag) -> Result<V::Value>
+/// Deserializer for the name of a compound entry. Names are always strings in
+/// NBT, but can be read as integers for maps with integer keys.
+pub(crate) struct KeyDeserializer<'de>(pub(crate) Cow<'de, str>);
+
+/// Parse the key as the integer type the visitor asked for.
+macro_rules! parse_int_key {
+    ($($method:ident($ty:ty) => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value>
+            where
+                V: de::Visitor<'de>,
+            {
+                let n = self.0.parse::<$ty>().map_err(|_| {
+                    Error::bespoke(format!(
+                        "compound key {:?} is not a valid {}",
+                        self.0,
+                        stringify!($ty)
+                    ))
+                })?;
+                visitor.$visit(n)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer<'de> {
+    type Error = Error;
+
+    // Names are strings whatever the options, so types that read themselves
+    // differently for human readable formats should read their string form.
+    fn is_human_readable(&self) -> bool {
+        true
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visit_cow_str(visitor, self.0)
+    }
+
+    parse_int_key! {
+        deserialize_i8(i8) => visit_i8,
+        deserialize_i16(i16) => visit_i16,
+        deserialize_i32(i32) => visit_i32,
+        deserialize_i64(i64) => visit_i64,
+        deserialize_u8(u8) => visit_u8,
+        deserialize_u16(u16) => visit_u16,
+        deserialize_u32(u32) => visit_u32,
+        deserialize_u64(u64) => visit_u64,
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.0 {
+            Cow::Borrowed(s) => {
+                de::value::BorrowedStrDeserializer::new(s).deserialize_enum(name, variants, visitor)
+            }
+            Cow::Owned(s) => s
+                .into_deserializer()
+                .deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i128 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        newtype_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Copy the name of an entry at the given depth, reusing the previous name's
+/// allocation.
+fn remember_owned_key(owned_keys: &mut Vec<String>, depth: usize, name: &str) {
+    if owned_keys.len() < depth {
+        owned_keys.resize_with(depth, String::new);
+    }
+    let key = &mut owned_keys[depth - 1];
+    key.clear();
+    key.push_str(name);
+}
+
+fn visit_cow_bytes<'de, V>(v: V, b: Cow<'de, [u8]>) -> Result<V::Value>
+where
+    V: de::Visitor<'de>,
+{
+    match b {
+        Cow::Borrowed(b) => v.visit_borrowed_bytes(b),
+        Cow::Owned(b) => v.visit_byte_buf(b),
+    }
+}
+
+fn consume_value<'de, I, V>(de: &mut Deserializer<'de, I>, visitor: V, tag: Tag) -> Result<V::Value>
 where
+    I: Input<'de>,
     V: de::Visitor<'de>,
 {
     let last_hint = de.last_hint;
     de.last_hint = None;
 
     match tag {
-        Tag::Byte => visitor.visit_i8(de.input.0.read_i8()?),
-        Tag::Short => visitor.visit_i16(de.input.0.read_i16::<BigEndian>()?),
-        Tag::Int => visitor.visit_i32(de.input.0.read_i32::<BigEndian>()?),
-        Tag::Long => visitor.visit_i64(de.input.0.read_i64::<BigEndian>()?),
+        Tag::Byte => visitor.visit_i8(de.input.read_i8()?),
+        Tag::Short => visitor.visit_i16(de.input.read_i16()?),
+        Tag::Int => visitor.visit_i32(de.input.read_i32()?),
+        Tag::Long => visitor.visit_i64(de.input.read_i64()?),
         Tag::String => visit_cow_str(visitor, de.input.consume_size_prefixed_string()?),
         Tag::Float => visitor.visit_f32(de.input.consume_float()?),
         Tag::Double => visitor.visit_f64(de.input.consume_double()?),
         Tag::Compound => {
-            de.layers.push(Layer::Compound {
+            de.push_layer(Layer::Compound {
                 current_tag: None,
                 stage: Stage::Tag,
-            });
+                key: None,
+            })?;
 
-            visitor.visit_map(CompoundAccess::new(de))
+            match de.on_duplicate_key {
+                DuplicateKeyPolicy::LastWins => visitor.visit_map(CompoundAccess::new(de)),
+                _ => visitor.visit_map(UniqueCompoundAccess::new(de)),
+            }
         }
         Tag::List => {
             let element_tag = de.input.consume_tag()?;
-            let size = de.input.consume_list_size()?;
+            let size = de
+                .input
+                .consume_size(de.input.min_payload_len(element_tag))?;
 
             // End values have no payload. An end tag on it's own is the payload
             // of an empty compound. A logical interpretation is that this could
@@ -377,29 +1786,38 @@ where
             // malicious payload of a massive list taking up lots of memory (as
             // the Value type's unit variant would not be zero sized.
             //
-            // Some old chunks store empty lists as as 'list of end', so if the
-            // size is zero we let it slide.
+            // Vanilla stores empty lists as a 'list of end', so if the size is
+            // zero we let it slide.
             if element_tag == Tag::End && size != 0 {
                 return Err(Error::bespoke(
                     "unexpected list of type 'end', which is not supported".into(),
                 ));
             }
 
-            de.layers.push(Layer::List {
+            de.push_layer(Layer::List {
                 remaining_elements: size,
                 element_tag,
-            });
+                size,
+            })?;
 
-            visitor.visit_seq(ListAccess::new(de, size))
+            let depth = de.layers.len();
+            let value = visitor.visit_seq(ListAccess::new(de, size))?;
+            end_list(de, depth, size)?;
+            Ok(value)
         }
         Tag::ByteArray | Tag::IntArray | Tag::LongArray => {
             if last_hint == Some("seq") {
-                return Err(Error::bespoke(
-                    "expected NBT Array, found seq: use ByteArray, IntArray or LongArray types"
-                        .into(),
-                ));
+                if !de.lenient_arrays {
+                    return Err(Error::bespoke(
+                        "expected NBT Array, found seq: use ByteArray, IntArray or LongArray types"
+                            .into(),
+                    ));
+                }
+
+                let size = de.input.consume_size(de.input.min_element_len(tag))?;
+                return visitor.visit_seq(ArrayAccess::new(de, tag, size));
             }
-            let size = de.input.consume_list_size()?;
+            let size = de.input.consume_size(de.input.min_element_len(tag))?;
             visitor.visit_map(ArrayWrapperAccess::new(de, size, tag))
         }
         // This would really only occur when we encounter a list where the
@@ -411,67 +1829,261 @@ where
     }
 }
 
-impl<'de> InputHelper<'de> {
-    // Safely get a subslice, erroring if there's not enough input.
-    pub(crate) fn subslice(&self, r: Range<usize>) -> Result<&'de [u8]> {
-        if r.end <= self.0.len() {
-            Ok(&self.0[r])
-        } else {
-            Err(Error::unexpected_eof())
+/// Close the list at `depth` if its visitor left it open, as visitors for
+/// tuples stop at the number of elements they want. Kept out of
+/// [`consume_value`] so as not to grow its stack frame, which every level of
+/// nesting pays for.
+#[inline(never)]
+fn end_list<'de, I: Input<'de>>(
+    de: &mut Deserializer<'de, I>,
+    depth: usize,
+    size: i32,
+) -> Result<()> {
+    if de.layers.len() != depth {
+        return Ok(());
+    }
+
+    match de.layers.pop() {
+        Some(Layer::List {
+            remaining_elements, ..
+        }) if remaining_elements > 0 => Err(Error::bespoke(format!(
+            "expected a list of {} elements, found {}",
+            size - remaining_elements,
+            size
+        ))),
+        _ => Ok(()),
+    }
+}
+
+impl<'de, I: Input<'de>> InputHelper<I> {
+    pub(crate) fn new(input: I, endian: Endian) -> Self {
+        let start = input.marker();
+        Self(input, endian, start, false)
+    }
+
+    /// How many bytes have been read since the start of the input.
+    pub(crate) fn offset(&self) -> usize {
+        self.0.offset_from(self.2)
+    }
+
+    fn read_bytes<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut buf = [0; N];
+        self.0.read_into(&mut buf)?;
+        Ok(buf)
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes::<1>()?[0])
+    }
+
+    pub(crate) fn read_i8(&mut self) -> Result<i8> {
+        Ok(self.read_bytes::<1>()?[0] as i8)
+    }
+
+    read_num! {
+        read_i16(i16),
+        read_u16(u16),
+        read_f32(f32),
+        read_f64(f64),
+    }
+
+    /// Read an int, which Bedrock network NBT writes as a ZigZag VarInt.
+    pub(crate) fn read_i32(&mut self) -> Result<i32> {
+        Ok(match self.1 {
+            Endian::Big => i32::from_be_bytes(self.read_bytes()?),
+            Endian::Little => i32::from_le_bytes(self.read_bytes()?),
+            Endian::VarInt => zigzag_decode(self.read_var_u64(32)?) as i32,
+        })
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32> {
+        self.read_i32().map(|v| v as u32)
+    }
+
+    /// Read a long, which Bedrock network NBT writes as a ZigZag VarLong.
+    pub(crate) fn read_i64(&mut self) -> Result<i64> {
+        Ok(match self.1 {
+            Endian::Big => i64::from_be_bytes(self.read_bytes()?),
+            Endian::Little => i64::from_le_bytes(self.read_bytes()?),
+            Endian::VarInt => zigzag_decode(self.read_var_u64(64)?),
+        })
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<u64> {
+        self.read_i64().map(|v| v as u64)
+    }
+
+    /// Read an unsigned VarInt holding at most `bits` bits. Each byte holds 7
+    /// bits, least significant first, with the top bit set on all but the
+    /// last byte. Bits that would not fit are an error rather than dropped.
+    fn read_var_u64(&mut self, bits: u32) -> Result<u64> {
+        let start = self.offset();
+        let mut value = 0;
+        let mut shift = 0;
+
+        while shift < bits {
+            let byte = self.read_u8()?;
+            let part = u64::from(byte & 0x7f);
+            if bits - shift < 7 && part >> (bits - shift) != 0 {
+                break;
+            }
+
+            value |= part << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
         }
+
+        Err(Error::varint_too_long(bits).at_offset(start))
     }
 
-    fn consume_tag(&mut self) -> Result<Tag> {
-        let tag_byte = self.0.read_u8()?;
-        Tag::try_from(tag_byte).map_err(|_| Error::invalid_tag(tag_byte))
+    pub(crate) fn consume_tag(&mut self) -> Result<Tag> {
+        let tag_byte = self.read_u8()?;
+        Tag::try_from(tag_byte)
+            .map_err(|_| Error::invalid_tag(tag_byte).at_offset(self.offset() - 1))
     }
 
-    fn consume_name(&mut self) -> Result<Cow<'de, str>> {
+    pub(crate) fn consume_name(&mut self) -> Result<Cow<'de, str>> {
         self.consume_size_prefixed_string()
     }
 
-    fn consume_size_prefixed_string(&mut self) -> Result<Cow<'de, str>> {
-        let len = self.0.read_u16::<BigEndian>()? as usize;
-        let str_data = self.subslice(0..len)?;
-        let s = cesu8::from_java_cesu8(str_data)
-            .map_err(|_| Error::nonunicode_string(&self.0[..len]))?;
+    pub(crate) fn consume_size_prefixed_string(&mut self) -> Result<Cow<'de, str>> {
+        let len = match self.1 {
+            Endian::VarInt => self.read_var_u64(32)? as usize,
+            _ => self.read_u16()? as usize,
+        };
+        let endian = self.1;
+        let lossy = self.3;
+
+        match self.0.consume_bytes(len)? {
+            Cow::Borrowed(data) => decode_str(data, endian, lossy),
+            Cow::Owned(data) => {
+                decode_str(&data, endian, lossy).map(|s| Cow::Owned(s.into_owned()))
+            }
+        }
+        .map_err(|e| e.at_offset(self.offset() - len))
+    }
+
+    /// Take the data of `size` elements of `element_len` bytes each.
+    fn consume_bytes(&mut self, size: i32, element_len: usize) -> Result<Cow<'de, [u8]>> {
+        let len = byte_len(size, element_len)?;
+        self.0.consume_bytes(len)
+    }
+
+    /// Skip over the data of `size` elements of `element_len` bytes each.
+    pub(crate) fn skip_bytes(&mut self, size: i32, element_len: usize) -> Result<()> {
+        let len = byte_len(size, element_len)?;
+        self.0.skip_bytes(len)
+    }
+
+    pub(crate) fn consume_list_size(&mut self) -> Result<i32> {
+        self.read_i32()
+    }
+
+    /// Read the size of a list or array whose elements take at least
+    /// `element_len` bytes each. If we know how much input is left, check that
+    /// it could hold that many elements, so that a corrupt size cannot make us
+    /// allocate far more than the input.
+    pub(crate) fn consume_size(&mut self, element_len: usize) -> Result<i32> {
+        let start = self.offset();
+        let size = self.consume_list_size()?;
+        self.check_size(size, element_len, start)
+    }
+
+    /// Check a size read at `start` as [`consume_size`][`Self::consume_size`]
+    /// does.
+    pub(crate) fn check_size(&self, size: i32, element_len: usize, start: usize) -> Result<i32> {
+        if let (Ok(len), Some(remaining)) = (usize::try_from(size), self.0.remaining()) {
+            if len.saturating_mul(element_len) > remaining {
+                return Err(Error::invalid_size(size).at_offset(start));
+            }
+        }
+
+        Ok(size)
+    }
+
+    /// The fewest bytes that the payload of a value with this tag can take.
+    pub(crate) fn min_payload_len(&self, tag: Tag) -> usize {
+        match (self.1, tag) {
+            // A VarInt can be a single byte, including the lengths of strings,
+            // lists and arrays.
+            (
+                Endian::VarInt,
+                Tag::Int
+                | Tag::Long
+                | Tag::String
+                | Tag::ByteArray
+                | Tag::IntArray
+                | Tag::LongArray,
+            ) => 1,
+            (Endian::VarInt, Tag::List) => 2,
+            _ => min_payload_len(tag),
+        }
+    }
 
-        self.0 = &self.0[len..];
-        Ok(s)
+    /// The length of every payload with this tag, if they are all the same.
+    fn fixed_payload_len(&self, tag: Tag) -> Option<usize> {
+        match (self.1, tag) {
+            (Endian::VarInt, Tag::Int | Tag::Long) => None,
+            (_, Tag::Byte | Tag::Short | Tag::Int | Tag::Long | Tag::Float | Tag::Double) => {
+                Some(min_payload_len(tag))
+            }
+            _ => None,
+        }
     }
 
-    fn consume_bytes_unchecked(&mut self, size: i32) -> Result<&'de [u8]> {
-        let size: usize = size.try_into().map_err(|_| Error::invalid_size(size))?;
-        let bs = &self.0[..size];
-        self.0 = &self.0[size..];
-        Ok(bs)
+    /// The fewest bytes that an element of an array with this tag can take.
+    fn min_element_len(&self, tag: Tag) -> usize {
+        match (self.1, tag) {
+            (Endian::VarInt, Tag::IntArray | Tag::LongArray) => 1,
+            _ => element_size(tag),
+        }
     }
 
-    fn consume_list_size(&mut self) -> Result<i32> {
-        Ok(self.0.read_i32::<BigEndian>()?)
+    /// Fail if the elements of an array or list of this tag are VarInts, so
+    /// the raw bytes could not be decoded later.
+    pub(crate) fn check_fixed_size(&self, element_tag: Tag) -> Result<()> {
+        if self.1 == Endian::VarInt
+            && matches!(
+                element_tag,
+                Tag::Int | Tag::Long | Tag::IntArray | Tag::LongArray
+            )
+        {
+            return Err(Error::bespoke(format!(
+                "cannot borrow the bytes of {:?}, its elements are VarInts in Bedrock network NBT",
+                element_tag
+            )));
+        }
+        Ok(())
     }
 
     fn consume_float(&mut self) -> Result<f32> {
-        Ok(self.0.read_f32::<BigEndian>()?)
+        self.read_f32()
     }
 
     fn consume_double(&mut self) -> Result<f64> {
-        Ok(self.0.read_f64::<BigEndian>()?)
+        self.read_f64()
     }
 
-    fn ignore_value(&mut self, tag: Tag) -> Result<()> {
+    /// Skip over a value, found inside `depth` compounds and lists.
+    pub(crate) fn ignore_value(&mut self, tag: Tag, depth: usize, max_depth: usize) -> Result<()> {
+        if matches!(tag, Tag::Compound | Tag::List) && depth >= max_depth {
+            return Err(Error::depth_limit_exceeded(max_depth));
+        }
+
         match tag {
             Tag::Byte => {
-                self.0.read_i8()?;
+                self.read_i8()?;
             }
             Tag::Short => {
-                self.0.read_i16::<BigEndian>()?;
+                self.read_i16()?;
             }
             Tag::Int => {
-                self.0.read_i32::<BigEndian>()?;
+                self.read_i32()?;
             }
             Tag::Long => {
-                self.0.read_i64::<BigEndian>()?;
+                self.read_i64()?;
             }
             Tag::Float => {
                 self.consume_float()?;
@@ -480,19 +2092,27 @@ impl<'de> InputHelper<'de> {
                 self.consume_double()?;
             }
             Tag::String => {
-                self.consume_size_prefixed_string()?;
+                // Not decoded, so an unwanted string costs no more than its
+                // length to skip.
+                let len = match self.1 {
+                    Endian::VarInt => self.read_var_u64(32)? as usize,
+                    _ => self.read_u16()? as usize,
+                };
+                self.0.skip_bytes(len)?;
             }
-            Tag::ByteArray => {
-                let size = self.consume_list_size()?;
-                self.consume_bytes_unchecked(size)?;
-            }
-            Tag::IntArray => {
-                let size = self.consume_list_size()?;
-                self.consume_bytes_unchecked(size * 4)?;
+            Tag::IntArray | Tag::LongArray if self.1 == Endian::VarInt => {
+                let size = self.consume_size(1)?;
+                for _ in 0..size {
+                    match tag {
+                        Tag::IntArray => self.read_i32().map(drop)?,
+                        _ => self.read_i64().map(drop)?,
+                    }
+                }
             }
-            Tag::LongArray => {
-                let size = self.consume_list_size()?;
-                self.consume_bytes_unchecked(size * 8)?;
+            Tag::ByteArray | Tag::IntArray | Tag::LongArray => {
+                let element_len = element_size(tag);
+                let size = self.consume_size(element_len)?;
+                self.skip_bytes(size, element_len)?;
             }
             Tag::Compound => {
                 // Need to loop and ignore each value until we reach an end tag.
@@ -505,24 +2125,27 @@ impl<'de> InputHelper<'de> {
                     }
 
                     self.consume_name()?;
-                    self.ignore_value(tag)?;
+                    self.ignore_value(tag, depth + 1, max_depth)?;
                 }
             }
             Tag::List => {
                 let element_tag = self.consume_tag()?;
-                let size = self.consume_list_size()?;
+                let size = self.consume_size(self.min_payload_len(element_tag))?;
+                if element_tag == Tag::End && size > 0 {
+                    return Err(Error::bespoke(
+                        "unexpected list of type 'end', which is not supported".into(),
+                    ));
+                }
+                if let Some(element_len) = self.fixed_payload_len(element_tag) {
+                    return self.skip_bytes(size, element_len);
+                }
                 for _ in 0..size {
-                    self.ignore_value(element_tag)?;
+                    self.ignore_value(element_tag, depth + 1, max_depth)?;
                 }
             }
             Tag::End => {
-                // If we are trying to ignore a list of empty compounds, that
-                // list might be indicated by a series of End tags. If this
-                // occurs then we should end the Compound branch of this match
-                // statement, where the end tag will be consumed. So we should
-                // never reach here.
-                //
-                // TODO: Write an explicit test for ignored list of compound.
+                // Compounds consume their own End tag, and non-empty lists of
+                // End are rejected above, so we never get here.
                 unreachable!()
             }
         }
@@ -531,72 +2154,178 @@ impl<'de> InputHelper<'de> {
     }
 }
 
-impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+/// Undo the ZigZag encoding of a signed VarInt, which interleaves negative and
+/// positive numbers so that small negative numbers stay short.
+fn zigzag_decode(n: u64) -> i64 {
+    (n >> 1) as i64 ^ -((n & 1) as i64)
+}
+
+/// The length in bytes of `size` elements of `element_len` bytes each.
+fn byte_len(size: i32, element_len: usize) -> Result<usize> {
+    usize::try_from(size)
+        .ok()
+        .and_then(|size| size.checked_mul(element_len))
+        .ok_or_else(|| Error::invalid_size(size))
+}
+
+/// The fewest bytes that the payload of a value with this tag can take, with
+/// fixed size numbers. See [`InputHelper::min_payload_len`].
+fn min_payload_len(tag: Tag) -> usize {
+    match tag {
+        Tag::End => 0,
+        // A compound can be just its End tag.
+        Tag::Byte | Tag::Compound => 1,
+        Tag::Short | Tag::String => 2,
+        Tag::Int | Tag::Float => 4,
+        // Arrays have an i32 size, lists also have the element tag.
+        Tag::ByteArray | Tag::IntArray | Tag::LongArray => 4,
+        Tag::List => 5,
+        Tag::Long | Tag::Double => 8,
+    }
+}
+
+/// Java writes CESU-8, Bedrock writes UTF-8.
+fn decode_str(data: &[u8], endian: Endian, lossy: bool) -> Result<Cow<'_, str>> {
+    let decoded = match endian {
+        Endian::Big => crate::java_cesu8::decode(data),
+        Endian::Little | Endian::VarInt => core::str::from_utf8(data).ok().map(Cow::Borrowed),
+    };
+
+    match (decoded, lossy) {
+        (Some(s), _) => Ok(s),
+        (None, true) => Ok(Cow::Owned(match endian {
+            Endian::Big => crate::java_cesu8::decode_lossy(data),
+            Endian::Little | Endian::VarInt => String::from_utf8_lossy(data).into_owned(),
+        })),
+        (None, false) => Err(Error::nonunicode_string(data)),
+    }
+}
+
+impl<'de, I: Input<'de>> Deserializer<'de, I> {
+    /// Read the name of the next compound entry, if that is what comes next.
+    fn consume_key(&mut self) -> Option<Result<Cow<'de, str>>> {
+        let depth = self.layers.len();
+        let (current_tag, stage, key) = match self.layers.last_mut() {
+            Some(Layer::Compound {
+                current_tag,
+                stage: stage @ (Stage::Tag | Stage::Name),
+                key,
+            }) => (current_tag, stage, key),
+            _ => return None,
+        };
+
+        let name = match stage {
+            Stage::Tag => match self.input.consume_tag() {
+                Ok(tag) => {
+                    *current_tag = Some(tag);
+                    self.input.consume_name()
+                }
+                Err(e) => Err(e),
+            },
+            _ => self.input.consume_name(),
+        };
+        *stage = Stage::Value;
+
+        let name = match name {
+            Ok(name) => name,
+            Err(e) => {
+                // Don't blame the entry before this one.
+                *current_tag = None;
+                return Some(Err(e));
+            }
+        };
+
+        match &name {
+            Cow::Borrowed(name) => *key = Some(*name),
+            Cow::Owned(name) => {
+                *key = None;
+                remember_owned_key(&mut self.owned_keys, depth, name);
+            }
+        }
+
+        Some(Ok(name))
+    }
+}
+
+/// Integers are read from the payload as normal, or parsed from the name of an
+/// entry when they are the keys of a map.
+macro_rules! deserialize_int_key {
+    ($($method:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value>
+            where
+                V: de::Visitor<'de>,
+            {
+                match self.consume_key() {
+                    Some(name) => KeyDeserializer(name?).$method(visitor),
+                    None => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de, 'a, I: Input<'de>> de::Deserializer<'de> for &'a mut Deserializer<'de, I> {
     type Error = Error;
 
-    forward_to_deserialize_any!(struct map identifier i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 str string tuple);
+    forward_to_deserialize_any!(map identifier f32 f64 str string);
 
     fn is_human_readable(&self) -> bool {
-        false
+        // Like the KeyDeserializer, a compound's next name is always a string.
+        self.human_readable
+            || matches!(
+                self.layers.last(),
+                Some(Layer::Compound {
+                    stage: Stage::Tag | Stage::Name,
+                    ..
+                })
+            )
     }
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
+        if let Some(name) = self.consume_key() {
+            return visit_cow_str(visitor, name?);
+        }
+
         let tag = match self.layers.last_mut().as_mut() {
             None => {
                 // No existing layers. This means we should be at the start of
-                // parsing, and we should be parsing a Compound. We need to get
-                // the tag and the following name and discard it.
-                let tag = self.input.consume_tag()?;
-                if tag != Tag::Compound {
-                    return Err(Error::no_root_compound());
-                }
-
-                self.input.consume_name()?;
-
-                self.layers.push(Layer::Compound {
-                    current_tag: None,
-                    stage: Stage::Tag,
-                });
-
-                return visitor.visit_map(CompoundAccess::new(self));
-            }
-            Some(layer) => {
-                // Pick what we do based on the stage of parsing.
-                match layer {
-                    Layer::Compound {
-                        ref mut current_tag,
-                        ref mut stage,
-                    } => match stage {
-                        Stage::Tag => {
-                            *current_tag = Some(self.input.consume_tag()?);
-                            *stage = Stage::Value;
-                            return visit_cow_str(visitor, self.input.consume_name()?);
-                        }
-                        Stage::Name => {
-                            *stage = Stage::Value;
-                            return visit_cow_str(visitor, self.input.consume_name()?);
-                        }
-                        Stage::Value => {
-                            *stage = Stage::Tag;
+                // parsing. Nothing is read after an error, so this is where
+                // the input stopped.
+                return self
+                    .deserialize_root(visitor)
+                    .map_err(|e| e.at_offset(self.input.offset()).at_path(self.path()));
+            }
+            Some(Layer::Compound {
+                ref mut current_tag,
+                ref mut stage,
+                ..
+            }) => {
+                *stage = Stage::Tag;
 
-                            // TODO: Remove unwrap
-                            current_tag.unwrap()
-                        }
-                    },
-                    Layer::List {
-                        remaining_elements: _,
-                        element_tag,
-                    } => *element_tag,
-                }
+                // TODO: Remove unwrap
+                current_tag.unwrap()
             }
+            Some(Layer::List { element_tag, .. }) => *element_tag,
         };
 
         consume_value(self, visitor, tag)
     }
 
+    deserialize_int_key! {
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+    }
+
     #[inline]
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
     where
@@ -613,16 +2342,65 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         }?;
 
         match tag {
-            Tag::Byte => visitor.visit_bool(self.input.0.read_i8()? != 0),
-            Tag::Short => visitor.visit_bool(self.input.0.read_i16::<BigEndian>()? != 0),
-            Tag::Int => visitor.visit_bool(self.input.0.read_i32::<BigEndian>()? != 0),
-            Tag::Long => visitor.visit_bool(self.input.0.read_i64::<BigEndian>()? != 0),
+            Tag::Byte => visitor.visit_bool(self.input.read_i8()? != 0),
+            Tag::Short => visitor.visit_bool(self.input.read_i16()? != 0),
+            Tag::Int => visitor.visit_bool(self.input.read_i32()? != 0),
+            Tag::Long => visitor.visit_bool(self.input.read_i64()? != 0),
             _ => Err(Error::bespoke(
                 "deserialize bool: expected integral value".to_string(),
             )),
         }
     }
 
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == COMPOUND_TOKEN {
+            return self.deserialize_raw_compound(visitor);
+        }
+
+        let array_tag = match name {
+            BYTE_ARRAY_TOKEN => Some(Tag::ByteArray),
+            INT_ARRAY_TOKEN => Some(Tag::IntArray),
+            LONG_ARRAY_TOKEN => Some(Tag::LongArray),
+            _ => None,
+        };
+
+        if let (true, Some(array_tag)) = (self.lenient_arrays, array_tag) {
+            if let Some(size) = self.consume_list_as_array(array_tag)? {
+                return visitor.visit_map(ArrayWrapperAccess::new(self, size, array_tag));
+            }
+        }
+
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.consume_u128()? {
+            Some(v) => visitor.visit_i128(v as i128),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.consume_u128()? {
+            Some(v) => visitor.visit_u128(v),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
     #[inline]
     fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value>
     where
@@ -659,24 +2437,27 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 ..
             } => {
                 let el = self.input.consume_tag()?;
-                let size = self.input.consume_list_size()?;
+                self.input.check_fixed_size(el)?;
+                let size = self.input.consume_size(self.input.min_payload_len(el))?;
 
                 match el {
+                    // Empty lists are often stored as a list of End.
+                    Tag::End if size == 0 => visitor.visit_borrowed_bytes(&[]),
                     Tag::Byte => {
-                        let bs = self.input.consume_bytes_unchecked(size)?;
-                        visitor.visit_borrowed_bytes(bs)
+                        let bs = self.input.consume_bytes(size, 1)?;
+                        visit_cow_bytes(visitor, bs)
                     }
                     Tag::Short => {
-                        let bs = self.input.consume_bytes_unchecked(size * 2)?;
-                        visitor.visit_borrowed_bytes(bs)
+                        let bs = self.input.consume_bytes(size, 2)?;
+                        visit_cow_bytes(visitor, bs)
                     }
                     Tag::Int => {
-                        let bs = self.input.consume_bytes_unchecked(size * 4)?;
-                        visitor.visit_borrowed_bytes(bs)
+                        let bs = self.input.consume_bytes(size, 4)?;
+                        visit_cow_bytes(visitor, bs)
                     }
                     Tag::Long => {
-                        let bs = self.input.consume_bytes_unchecked(size * 8)?;
-                        visitor.visit_borrowed_bytes(bs)
+                        let bs = self.input.consume_bytes(size, 8)?;
+                        visit_cow_bytes(visitor, bs)
                     }
                     _ => Err(Error::bespoke(format!(
                         "expected bytes, got [{:?}; {}]",
@@ -688,21 +2469,13 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 current_tag: Some(tag),
                 ..
             } => match tag {
-                Tag::ByteArray => {
-                    let size = self.input.consume_list_size()?;
-                    let bs = self.input.consume_bytes_unchecked(size)?;
-                    visitor.visit_borrowed_bytes(bs)
-                }
-                Tag::IntArray => {
-                    let size = self.input.consume_list_size()?;
-                    let bs = self.input.consume_bytes_unchecked(size * 4i32)?;
-                    visitor.visit_borrowed_bytes(bs)
-                }
                 // This allows us to borrow blockstates rather than copy them.
-                Tag::LongArray => {
-                    let size = self.input.consume_list_size()?;
-                    let bs = self.input.consume_bytes_unchecked(size * 8i32)?;
-                    visitor.visit_borrowed_bytes(bs)
+                Tag::ByteArray | Tag::IntArray | Tag::LongArray => {
+                    self.input.check_fixed_size(*tag)?;
+                    let element_len = element_size(*tag);
+                    let size = self.input.consume_size(element_len)?;
+                    let bs = self.input.consume_bytes(size, element_len)?;
+                    visit_cow_bytes(visitor, bs)
                 }
                 _ => Err(Error::bespoke(format!("expected bytes, found {:?}", tag))),
             },
@@ -710,12 +2483,13 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     }
 
     #[inline]
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        // How do we even get here? Vec and slices don't call this.
-        unimplemented!("byte_buf")
+        // Vec and slices don't call this, but byte buffer types do. Visitors
+        // for owned bytes accept borrowed ones too.
+        self.deserialize_bytes(visitor)
     }
 
     #[inline]
@@ -741,7 +2515,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             )),
         }?;
 
-        self.input.ignore_value(*tag)?;
+        self.input
+            .ignore_value(*tag, self.layers.len(), self.max_depth)?;
         visitor.visit_unit()
     }
 
@@ -766,12 +2541,12 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         self,
         _name: &'static str,
         _len: usize,
-        _visitor: V,
+        visitor: V,
     ) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        todo!("tuple_struct")
+        self.deserialize_seq(visitor)
     }
 
     #[inline]
@@ -784,6 +2559,29 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
+        // Unit variants are strings, and the others are a compound with the
+        // variant's name as its only entry, as the serializer writes them.
+        let tag = match self.layers.last() {
+            Some(Layer::Compound {
+                current_tag,
+                stage: Stage::Value,
+                ..
+            }) => *current_tag,
+            Some(Layer::List { element_tag, .. }) => Some(*element_tag),
+            _ => None,
+        };
+
+        if tag == Some(Tag::Compound) {
+            self.push_layer(Layer::Compound {
+                current_tag: None,
+                stage: Stage::Tag,
+                key: None,
+            })?;
+            return visitor.visit_enum(VariantCompoundAccess {
+                access: CompoundAccess::new(self),
+            });
+        }
+
         visitor.visit_enum(UnitVariantAccess { de: self })
     }
 
@@ -804,17 +2602,13 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             Layer::Compound {
                 current_tag: Some(tag),
                 stage: Stage::Value,
+                ..
             } => {
-                self.input.ignore_value(*tag)?;
+                self.input
+                    .ignore_value(*tag, self.layers.len(), self.max_depth)?;
             }
-            Layer::Compound {
-                current_tag: _,
-                stage: _,
-            } => todo!("compound(none)"), // ???
-            Layer::List {
-                remaining_elements: _,
-                element_tag: _,
-            } => {
+            Layer::Compound { .. } => todo!("compound(none)"), // ???
+            Layer::List { .. } => {
                 todo!();
             }
         }
@@ -831,33 +2625,49 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         self.last_hint = Some("seq");
         self.deserialize_any(visitor)
     }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Fixed-size arrays like `[i32; 4]` are read from NBT arrays in the
+        // same way as other sequences, but we can check the length up front.
+        match self.consume_array_as_tuple(len)? {
+            Some((tag, size)) => visitor.visit_seq(ArrayAccess::new(self, tag, size)),
+            None => self.deserialize_seq(visitor),
+        }
+    }
 }
 
-struct CompoundAccess<'a, 'de> {
-    de: &'a mut Deserializer<'de>,
+struct CompoundAccess<'a, 'de, I> {
+    de: &'a mut Deserializer<'de, I>,
 }
 
-impl<'a, 'de> CompoundAccess<'a, 'de> {
-    fn new(de: &'a mut Deserializer<'de>) -> Self {
+impl<'a, 'de, I: Input<'de>> CompoundAccess<'a, 'de, I> {
+    fn new(de: &'a mut Deserializer<'de, I>) -> Self {
         Self { de }
     }
-}
-
-impl<'a, 'de> de::MapAccess<'de> for CompoundAccess<'a, 'de> {
-    type Error = Error;
 
+    /// Read the tag of the next entry, and expect its name next. None at the
+    /// end of the compound.
     #[inline]
-    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
-    where
-        K: serde::de::DeserializeSeed<'de>,
-    {
-        // Need to read the tag of the key.
-        let tag = self.de.input.consume_tag()?;
+    fn consume_entry_tag(&mut self) -> Result<Option<Tag>> {
+        let tag = match self.de.input.consume_tag() {
+            Ok(tag) => tag,
+            Err(e) => {
+                // Don't blame the entry before this one.
+                if let Some(Layer::Compound { current_tag, .. }) = self.de.layers.last_mut() {
+                    *current_tag = None;
+                }
+                return Err(e);
+            }
+        };
 
         if tag == Tag::End {
             self.de.layers.pop();
             return Ok(None);
         }
+        self.de.entries_read = self.de.entries_read.wrapping_add(1);
 
         // Set the current layers next expected type.
         // TODO: Can probably do this by mutating top layer rather than pop/push.
@@ -865,8 +2675,25 @@ impl<'a, 'de> de::MapAccess<'de> for CompoundAccess<'a, 'de> {
         self.de.layers.push(Layer::Compound {
             current_tag: Some(tag),
             stage: Stage::Name,
+            key: None,
         });
 
+        Ok(Some(tag))
+    }
+}
+
+impl<'a, 'de, I: Input<'de>> de::MapAccess<'de> for CompoundAccess<'a, 'de, I> {
+    type Error = Error;
+
+    #[inline]
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: serde::de::DeserializeSeed<'de>,
+    {
+        if self.consume_entry_tag()?.is_none() {
+            return Ok(None);
+        }
+
         // Should just be ready to read the name.
         seed.deserialize(&mut *self.de).map(Some)
     }
@@ -880,18 +2707,91 @@ impl<'a, 'de> de::MapAccess<'de> for CompoundAccess<'a, 'de> {
     }
 }
 
-struct ListAccess<'a, 'de> {
-    de: &'a mut Deserializer<'de>,
+/// A [`CompoundAccess`] that checks for duplicate keys, for any
+/// [`DuplicateKeyPolicy`] other than the default. Kept apart so that the
+/// default pays nothing for it.
+struct UniqueCompoundAccess<'a, 'de, I>(CompoundAccess<'a, 'de, I>);
+
+impl<'a, 'de, I: Input<'de>> UniqueCompoundAccess<'a, 'de, I> {
+    fn new(de: &'a mut Deserializer<'de, I>) -> Self {
+        // Forget the keys of any earlier compound at this depth.
+        let depth = de.layers.len();
+        if de.seen_keys.len() < depth {
+            de.seen_keys.resize_with(depth, Set::new);
+        }
+        de.seen_keys[depth - 1].clear();
+
+        Self(CompoundAccess::new(de))
+    }
+
+    /// Read the name of the current entry, then check it against the earlier
+    /// entries of this compound according to the [`DuplicateKeyPolicy`].
+    /// Returns None if the entry should be skipped.
+    fn consume_unique_key(&mut self) -> Result<Option<Cow<'de, str>>> {
+        // Reads the name through deserialize_any, so that it is tracked for
+        // the path in errors like any other.
+        let name = crate::borrow::deserialize_cow_str(&mut *self.0.de)?;
+
+        let depth = self.0.de.layers.len();
+        let seen = &mut self.0.de.seen_keys[depth - 1];
+        if !seen.contains(name.as_ref()) {
+            seen.insert(name.clone().into_owned());
+            return Ok(Some(name));
+        }
+
+        match self.0.de.on_duplicate_key {
+            DuplicateKeyPolicy::Error => Err(Error::duplicate_key(&name)),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<'a, 'de, I: Input<'de>> de::MapAccess<'de> for UniqueCompoundAccess<'a, 'de, I> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: serde::de::DeserializeSeed<'de>,
+    {
+        loop {
+            let tag = match self.0.consume_entry_tag()? {
+                Some(tag) => tag,
+                None => return Ok(None),
+            };
+
+            match self.consume_unique_key()? {
+                Some(name) => return seed.deserialize(KeyDeserializer(name)).map(Some),
+                None => {
+                    let depth = self.0.de.layers.len();
+                    self.0
+                        .de
+                        .input
+                        .ignore_value(tag, depth, self.0.de.max_depth)?;
+                }
+            }
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        self.0.next_value_seed(seed)
+    }
+}
+
+struct ListAccess<'a, 'de, I> {
+    de: &'a mut Deserializer<'de, I>,
     hint: i32,
 }
 
-impl<'a, 'de> ListAccess<'a, 'de> {
-    fn new(de: &'a mut Deserializer<'de>, hint: i32) -> Self {
+impl<'a, 'de, I: Input<'de>> ListAccess<'a, 'de, I> {
+    fn new(de: &'a mut Deserializer<'de, I>, hint: i32) -> Self {
         Self { de, hint }
     }
 }
 
-impl<'a, 'de> de::SeqAccess<'de> for ListAccess<'a, 'de> {
+impl<'a, 'de, I: Input<'de>> de::SeqAccess<'de> for ListAccess<'a, 'de, I> {
     type Error = Error;
 
     fn size_hint(&self) -> Option<usize> {
@@ -911,22 +2811,23 @@ impl<'a, 'de> de::SeqAccess<'de> for ListAccess<'a, 'de> {
 
         match layer {
             Layer::List {
-                remaining_elements,
-                element_tag: _,
+                remaining_elements, ..
             } => {
                 if *remaining_elements > 0 {
                     *remaining_elements -= 1;
-                    let val = seed.deserialize(&mut *self.de)?;
+                    let depth = self.de.layers.len();
+                    self.de.entries_read = self.de.entries_read.wrapping_add(1);
+                    let entries_read = self.de.entries_read;
+                    let val = seed
+                        .deserialize(&mut *self.de)
+                        .map_err(|e| self.de.list_element_error(e, depth, entries_read))?;
                     Ok(Some(val))
                 } else {
                     self.de.layers.pop();
                     Ok(None)
                 }
             }
-            Layer::Compound {
-                current_tag,
-                stage: _,
-            } => Err(Error::bespoke(format!(
+            Layer::Compound { current_tag, .. } => Err(Error::bespoke(format!(
                 "expected to be in list, but was in compound {:?}",
                 current_tag
             ))),
@@ -934,11 +2835,11 @@ impl<'a, 'de> de::SeqAccess<'de> for ListAccess<'a, 'de> {
     }
 }
 
-struct UnitVariantAccess<'a, 'de> {
-    de: &'a mut Deserializer<'de>,
+struct UnitVariantAccess<'a, 'de, I> {
+    de: &'a mut Deserializer<'de, I>,
 }
 
-impl<'a, 'de> de::EnumAccess<'de> for UnitVariantAccess<'a, 'de> {
+impl<'a, 'de, I: Input<'de>> de::EnumAccess<'de> for UnitVariantAccess<'a, 'de, I> {
     type Error = Error;
     type Variant = Self;
 
@@ -951,7 +2852,7 @@ impl<'a, 'de> de::EnumAccess<'de> for UnitVariantAccess<'a, 'de> {
     }
 }
 
-impl<'a, 'de> de::VariantAccess<'de> for UnitVariantAccess<'a, 'de> {
+impl<'a, 'de, I: Input<'de>> de::VariantAccess<'de> for UnitVariantAccess<'a, 'de, I> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
@@ -979,3 +2880,84 @@ impl<'a, 'de> de::VariantAccess<'de> for UnitVariantAccess<'a, 'de> {
         todo!("unit variant: struct variant")
     }
 }
+
+/// An enum variant with data, read from a compound of one entry named after
+/// the variant.
+struct VariantCompoundAccess<'a, 'de, I> {
+    access: CompoundAccess<'a, 'de, I>,
+}
+
+impl<'a, 'de, I: Input<'de>> VariantCompoundAccess<'a, 'de, I> {
+    /// Expect the end of the compound after the variant's value.
+    fn end(mut self) -> Result<()> {
+        match self.access.consume_entry_tag()? {
+            None => Ok(()),
+            Some(_) => {
+                // The name of the extra entry is not read.
+                if let Some(Layer::Compound { current_tag, .. }) = self.access.de.layers.last_mut()
+                {
+                    *current_tag = None;
+                }
+                Err(Error::bespoke(
+                    "expected the end of an enum variant's compound, found another entry"
+                        .to_owned(),
+                ))
+            }
+        }
+    }
+}
+
+impl<'a, 'de, I: Input<'de>> de::EnumAccess<'de> for VariantCompoundAccess<'a, 'de, I> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        if self.access.consume_entry_tag()?.is_none() {
+            return Err(Error::bespoke(
+                "expected an enum variant, found an empty compound".to_owned(),
+            ));
+        }
+
+        let variant = seed.deserialize(&mut *self.access.de)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'a, 'de, I: Input<'de>> de::VariantAccess<'de> for VariantCompoundAccess<'a, 'de, I> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        de::Deserializer::deserialize_ignored_any(&mut *self.access.de, de::IgnoredAny)?;
+        self.end()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        let value = seed.deserialize(&mut *self.access.de)?;
+        self.end()?;
+        Ok(value)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let value = de::Deserializer::deserialize_seq(&mut *self.access.de, visitor)?;
+        self.end()?;
+        Ok(value)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let value = de::Deserializer::deserialize_map(&mut *self.access.de, visitor)?;
+        self.end()?;
+        Ok(value)
+    }
+}
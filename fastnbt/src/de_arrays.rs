@@ -1,29 +1,32 @@
+use alloc::borrow::Cow;
+use alloc::string::ToString;
+use core::convert::TryInto;
+use core::num::TryFromIntError;
 use core::panic;
-use std::convert::TryInto;
-use std::num::TryFromIntError;
 
-use byteorder::{BigEndian, ReadBytesExt};
 use serde::de::{self, IntoDeserializer};
 use serde::forward_to_deserialize_any;
 
+use crate::de::{Deserializer, Input};
 use crate::error::{Error, Result};
-use crate::{de::Deserializer, Tag};
+use crate::{Endian, Tag, BYTE_ARRAY_CHUNK_TOKEN, INT_ARRAY_CHUNK_TOKEN, LONG_ARRAY_CHUNK_TOKEN};
 
 enum ArrWrapStage {
     Tag,
     Data,
+    LittleEndian,
     Done,
 }
 
-pub(crate) struct ArrayWrapperAccess<'a, 'de> {
-    de: &'a mut Deserializer<'de>,
+pub(crate) struct ArrayWrapperAccess<'a, 'de, I> {
+    de: &'a mut Deserializer<'de, I>,
     stage: ArrWrapStage,
     tag: Tag,
     size: i32,
 }
 
-impl<'a, 'de> ArrayWrapperAccess<'a, 'de> {
-    pub(crate) fn new(de: &'a mut Deserializer<'de>, size: i32, tag: Tag) -> Self {
+impl<'a, 'de, I: Input<'de>> ArrayWrapperAccess<'a, 'de, I> {
+    pub(crate) fn new(de: &'a mut Deserializer<'de, I>, size: i32, tag: Tag) -> Self {
         Self {
             de,
             tag,
@@ -33,7 +36,7 @@ impl<'a, 'de> ArrayWrapperAccess<'a, 'de> {
     }
 }
 
-impl<'a, 'de> de::MapAccess<'de> for ArrayWrapperAccess<'a, 'de> {
+impl<'a, 'de, I: Input<'de>> de::MapAccess<'de> for ArrayWrapperAccess<'a, 'de, I> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
@@ -43,6 +46,9 @@ impl<'a, 'de> de::MapAccess<'de> for ArrayWrapperAccess<'a, 'de> {
         match self.stage {
             ArrWrapStage::Tag => seed.deserialize("tag".into_deserializer()).map(Some),
             ArrWrapStage::Data => seed.deserialize("data".into_deserializer()).map(Some),
+            ArrWrapStage::LittleEndian => seed
+                .deserialize("little_endian".into_deserializer())
+                .map(Some),
             ArrWrapStage::Done => Ok(None),
         }
     }
@@ -58,27 +64,37 @@ impl<'a, 'de> de::MapAccess<'de> for ArrayWrapperAccess<'a, 'de> {
                 seed.deserialize(t.into_deserializer())
             }
             ArrWrapStage::Data => {
-                self.stage = ArrWrapStage::Done;
+                // Borrowed arrays keep the raw bytes, so they need to know
+                // which order to decode them in. Only tell them when it isn't
+                // the usual big-endian, so nothing else sees an extra key.
+                self.stage = match self.de.input.1 {
+                    Endian::Big => ArrWrapStage::Done,
+                    Endian::Little | Endian::VarInt => ArrWrapStage::LittleEndian,
+                };
                 seed.deserialize(ArrayDeserializer {
                     de: &mut *self.de,
                     size: self.size,
                     tag: self.tag,
                 })
             }
+            ArrWrapStage::LittleEndian => {
+                self.stage = ArrWrapStage::Done;
+                seed.deserialize(true.into_deserializer())
+            }
             ArrWrapStage::Done => panic!("extra key"),
         }
     }
 }
 
-struct ArrayAccess<'a, 'de> {
-    de: &'a mut Deserializer<'de>,
+pub(crate) struct ArrayAccess<'a, 'de, I> {
+    de: &'a mut Deserializer<'de, I>,
     hint: i32,
     remaining: i32,
     tag: Tag,
 }
 
-impl<'a, 'de> ArrayAccess<'a, 'de> {
-    fn new(de: &'a mut Deserializer<'de>, tag: Tag, size: i32) -> Self {
+impl<'a, 'de, I: Input<'de>> ArrayAccess<'a, 'de, I> {
+    pub(crate) fn new(de: &'a mut Deserializer<'de, I>, tag: Tag, size: i32) -> Self {
         Self {
             de,
             hint: size,
@@ -88,7 +104,7 @@ impl<'a, 'de> ArrayAccess<'a, 'de> {
     }
 }
 
-impl<'a, 'de> de::SeqAccess<'de> for ArrayAccess<'a, 'de> {
+impl<'a, 'de, I: Input<'de>> de::SeqAccess<'de> for ArrayAccess<'a, 'de, I> {
     type Error = Error;
 
     fn size_hint(&self) -> Option<usize> {
@@ -101,9 +117,11 @@ impl<'a, 'de> de::SeqAccess<'de> for ArrayAccess<'a, 'de> {
     {
         if self.remaining > 0 {
             self.remaining -= 1;
+            self.de.entries_read = self.de.entries_read.wrapping_add(1);
             let val = seed.deserialize(ArrayElementDeserializer {
                 de: self.de,
                 tag: self.tag,
+                remaining: &mut self.remaining,
             })?;
             Ok(Some(val))
         } else {
@@ -112,20 +130,47 @@ impl<'a, 'de> de::SeqAccess<'de> for ArrayAccess<'a, 'de> {
     }
 }
 
-pub(crate) struct ArrayElementDeserializer<'a, 'de> {
-    pub(crate) de: &'a mut Deserializer<'de>,
+pub(crate) struct ArrayElementDeserializer<'a, 'de, I> {
+    pub(crate) de: &'a mut Deserializer<'de, I>,
     pub(crate) tag: Tag,
+    /// The elements left after this one, which may be taken along with it
+    /// when asked for a chunk.
+    pub(crate) remaining: &'a mut i32,
 }
 
-impl<'a, 'de> serde::Deserializer<'de> for ArrayElementDeserializer<'a, 'de> {
+/// The most bytes of elements handed over at once to the owned array types.
+const CHUNK_LEN: usize = 8 * 1024;
+
+impl<'a, 'de, I: Input<'de>> serde::Deserializer<'de> for ArrayElementDeserializer<'a, 'de, I> {
     type Error = Error;
 
     forward_to_deserialize_any! {
         bool i16 i128 u16  u128 f32 f64 char str string seq
-        bytes byte_buf option unit unit_struct newtype_struct tuple
+        bytes byte_buf option unit unit_struct tuple
         tuple_struct map struct enum identifier ignored_any
     }
 
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // The owned array types ask for a chunk of elements, which are read
+        // straight into a buffer for them to decode. Elements of other endians
+        // are read one at a time as usual.
+        if name != chunk_token(self.tag) || self.de.input.1 != Endian::Big {
+            return self.deserialize_any(visitor);
+        }
+
+        let size = element_size(self.tag);
+        let count = (*self.remaining as usize + 1).min(CHUNK_LEN / size);
+        *self.remaining -= count as i32 - 1;
+
+        let mut buf = [0; CHUNK_LEN];
+        let chunk = &mut buf[..count * size];
+        self.de.input.0.read_into(chunk)?;
+        visitor.visit_bytes(chunk)
+    }
+
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
@@ -147,7 +192,7 @@ impl<'a, 'de> serde::Deserializer<'de> for ArrayElementDeserializer<'a, 'de> {
     where
         V: de::Visitor<'de>,
     {
-        let val = self.de.input.0.read_i8()?;
+        let val = self.de.input.read_i8()?;
         visitor.visit_i8(val)
     }
 
@@ -155,7 +200,7 @@ impl<'a, 'de> serde::Deserializer<'de> for ArrayElementDeserializer<'a, 'de> {
     where
         V: de::Visitor<'de>,
     {
-        let val = self.de.input.0.read_u8()?;
+        let val = self.de.input.read_u8()?;
         visitor.visit_u8(val)
     }
 
@@ -163,7 +208,7 @@ impl<'a, 'de> serde::Deserializer<'de> for ArrayElementDeserializer<'a, 'de> {
     where
         V: de::Visitor<'de>,
     {
-        let val = self.de.input.0.read_i32::<BigEndian>()?;
+        let val = self.de.input.read_i32()?;
         visitor.visit_i32(val)
     }
 
@@ -171,7 +216,7 @@ impl<'a, 'de> serde::Deserializer<'de> for ArrayElementDeserializer<'a, 'de> {
     where
         V: de::Visitor<'de>,
     {
-        let val = self.de.input.0.read_u32::<BigEndian>()?;
+        let val = self.de.input.read_u32()?;
         visitor.visit_u32(val)
     }
 
@@ -179,7 +224,7 @@ impl<'a, 'de> serde::Deserializer<'de> for ArrayElementDeserializer<'a, 'de> {
     where
         V: de::Visitor<'de>,
     {
-        let val = self.de.input.0.read_i64::<BigEndian>()?;
+        let val = self.de.input.read_i64()?;
         visitor.visit_i64(val)
     }
 
@@ -187,20 +232,20 @@ impl<'a, 'de> serde::Deserializer<'de> for ArrayElementDeserializer<'a, 'de> {
     where
         V: de::Visitor<'de>,
     {
-        let val = self.de.input.0.read_u64::<BigEndian>()?;
+        let val = self.de.input.read_u64()?;
         visitor.visit_u64(val)
     }
 }
 
-pub(crate) struct ArrayDeserializer<'a, 'de> {
-    pub(crate) de: &'a mut Deserializer<'de>,
+pub(crate) struct ArrayDeserializer<'a, 'de, I> {
+    pub(crate) de: &'a mut Deserializer<'de, I>,
     pub(crate) size: i32,
     pub(crate) tag: Tag,
 }
 
 // Job is to start deserializing a Seq which is a *Array type, and to actually
 // deserialize the elements.
-impl<'a, 'de> serde::Deserializer<'de> for ArrayDeserializer<'a, 'de> {
+impl<'a, 'de, I: Input<'de>> serde::Deserializer<'de> for ArrayDeserializer<'a, 'de, I> {
     type Error = Error;
 
     forward_to_deserialize_any! {
@@ -221,24 +266,37 @@ impl<'a, 'de> serde::Deserializer<'de> for ArrayDeserializer<'a, 'de> {
         V: de::Visitor<'de>,
     {
         // This code path happens when we're deserializing borrow:*Array types.
+        self.de.input.check_fixed_size(self.tag)?;
         let len: usize = self
             .size
             .try_into()
             .map_err(|e: TryFromIntError| Error::bespoke(e.to_string()))?;
 
-        let total_bytes = len * element_size(self.tag);
+        let total_bytes = len
+            .checked_mul(element_size(self.tag))
+            .ok_or_else(|| Error::invalid_size(self.size))?;
+
+        match self.de.input.0.consume_bytes(total_bytes)? {
+            Cow::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
+            Cow::Owned(bytes) => visitor.visit_byte_buf(bytes),
+        }
+    }
+}
 
-        let res = &self.de.input.subslice(0..total_bytes)?;
-        self.de.input.0 = &self.de.input.0[total_bytes..];
-        visitor.visit_borrowed_bytes(res)
+fn chunk_token(tag: Tag) -> &'static str {
+    match tag {
+        Tag::ByteArray => BYTE_ARRAY_CHUNK_TOKEN,
+        Tag::IntArray => INT_ARRAY_CHUNK_TOKEN,
+        Tag::LongArray => LONG_ARRAY_CHUNK_TOKEN,
+        _ => panic!("chunk token of non-array type"),
     }
 }
 
-fn element_size(tag: Tag) -> usize {
+pub(crate) fn element_size(tag: Tag) -> usize {
     match tag {
-        Tag::ByteArray => std::mem::size_of::<i8>(),
-        Tag::IntArray => std::mem::size_of::<i32>(),
-        Tag::LongArray => std::mem::size_of::<i64>(),
+        Tag::ByteArray => core::mem::size_of::<i8>(),
+        Tag::IntArray => core::mem::size_of::<i32>(),
+        Tag::LongArray => core::mem::size_of::<i64>(),
         _ => panic!("element size of non-array type"),
     }
 }
@@ -0,0 +1,177 @@
+use std::convert::TryFrom;
+
+use serde::de::DeserializeOwned;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::de::{from_bytes, DeOpts};
+use crate::error::Result;
+use crate::Tag;
+
+/// Deserialize into a `T` from NBT data read from a tokio
+/// [`AsyncRead`][`tokio::io::AsyncRead`], eg a region file being fetched over
+/// the network. Requires the `async` feature.
+///
+/// Serde deserializes synchronously, so the bytes of the NBT are first read
+/// as they arrive, following the tags and lengths to find where it ends. They
+/// are then deserialized by the same deserializer as [`from_bytes`], so errors
+/// are reported in the same way, with their offset and path. As with
+/// [`from_reader`][`crate::de::from_reader`], only types that own all of their
+/// data can be deserialized.
+///
+/// ```no_run
+/// # use fastnbt::Value;
+/// # async fn f(stream: impl tokio::io::AsyncRead + Unpin) -> fastnbt::error::Result<()> {
+/// let reader = tokio::io::BufReader::new(stream);
+/// let val: Value = fastnbt::de::from_async_reader(reader).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Reading stops at the end of the NBT, anything after it is left in the
+/// reader. If the future is dropped before it completes, whatever had been
+/// read is lost, and the reader is left part way through the NBT.
+pub async fn from_async_reader<R, T>(mut reader: R) -> Result<T>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let mut frame = Frame {
+        reader: &mut reader,
+        buf: Vec::new(),
+    };
+    frame.read_document().await?;
+    from_bytes(&frame.buf)
+}
+
+enum Layer {
+    Compound,
+    List { tag: Tag, remaining: i32 },
+}
+
+/// Reads the bytes of a single document.
+struct Frame<'r, R> {
+    reader: &'r mut R,
+    buf: Vec<u8>,
+}
+
+impl<'r, R: AsyncRead + Unpin> Frame<'r, R> {
+    /// Read up to the end of the document. Anything malformed, including the
+    /// input ending early, just stops the reading, leaving the deserializer to
+    /// find the problem in what was read.
+    async fn read_document(&mut self) -> Result<()> {
+        if self.tag().await? != Some(Tag::Compound) || !self.name().await? {
+            return Ok(());
+        }
+
+        let mut layers = vec![Layer::Compound];
+        while let Some(layer) = layers.last_mut() {
+            let tag = match layer {
+                Layer::Compound => match self.tag().await? {
+                    Some(Tag::End) => {
+                        layers.pop();
+                        continue;
+                    }
+                    Some(tag) if self.name().await? => tag,
+                    _ => return Ok(()),
+                },
+                Layer::List { remaining: 0, .. } => {
+                    layers.pop();
+                    continue;
+                }
+                Layer::List { tag, remaining } => {
+                    *remaining -= 1;
+                    *tag
+                }
+            };
+
+            let complete = match tag {
+                Tag::Byte => self.read(1).await?,
+                Tag::Short => self.read(2).await?,
+                Tag::Int | Tag::Float => self.read(4).await?,
+                Tag::Long | Tag::Double => self.read(8).await?,
+                Tag::String => match self.read(2).await? {
+                    true => self.read(self.last_u16() as usize).await?,
+                    false => false,
+                },
+                Tag::ByteArray => self.array(1).await?,
+                Tag::IntArray => self.array(4).await?,
+                Tag::LongArray => self.array(8).await?,
+                Tag::List => match self.tag().await? {
+                    Some(element_tag) if self.read(4).await? => {
+                        let size = self.last_i32();
+                        let valid = size >= 0 && !(element_tag == Tag::End && size > 0);
+                        if valid {
+                            layers.push(Layer::List {
+                                tag: element_tag,
+                                remaining: size,
+                            });
+                        }
+                        valid
+                    }
+                    _ => false,
+                },
+                Tag::Compound => {
+                    layers.push(Layer::Compound);
+                    true
+                }
+                Tag::End => false,
+            };
+
+            if !complete || layers.len() > DeOpts::DEFAULT_MAX_DEPTH {
+                return Ok(());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Append the next `len` bytes to the buffer, returning whether there
+    /// were that many. The buffer only grows as the bytes arrive, so a corrupt
+    /// length cannot allocate more than the input holds.
+    async fn read(&mut self, len: usize) -> Result<bool> {
+        let read = (&mut *self.reader)
+            .take(len as u64)
+            .read_to_end(&mut self.buf)
+            .await?;
+        Ok(read == len)
+    }
+
+    async fn tag(&mut self) -> Result<Option<Tag>> {
+        if !self.read(1).await? {
+            return Ok(None);
+        }
+        Ok(Tag::try_from(self.buf[self.buf.len() - 1]).ok())
+    }
+
+    async fn name(&mut self) -> Result<bool> {
+        Ok(self.read(2).await? && self.read(self.last_u16() as usize).await?)
+    }
+
+    async fn array(&mut self, element_len: usize) -> Result<bool> {
+        if !self.read(4).await? {
+            return Ok(false);
+        }
+        let len = usize::try_from(self.last_i32())
+            .ok()
+            .and_then(|size| size.checked_mul(element_len));
+        match len {
+            Some(len) => self.read(len).await,
+            None => Ok(false),
+        }
+    }
+
+    fn last_u16(&self) -> u16 {
+        let n = self.buf.len();
+        u16::from_be_bytes([self.buf[n - 2], self.buf[n - 1]])
+    }
+
+    fn last_i32(&self) -> i32 {
+        let n = self.buf.len();
+        i32::from_be_bytes([
+            self.buf[n - 4],
+            self.buf[n - 3],
+            self.buf[n - 2],
+            self.buf[n - 1],
+        ])
+    }
+}
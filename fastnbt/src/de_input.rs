@@ -0,0 +1,171 @@
+use alloc::borrow::Cow;
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+
+use crate::error::{Error, Result};
+
+/// Where a [`Deserializer`][`crate::de::Deserializer`] reads NBT from. This is
+/// either a byte slice, which values can borrow from, or an `IoRead` wrapping
+/// any `std::io::Read`.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait Input<'de>: private::Sealed {
+    /// Fill `buf` with the next bytes.
+    #[doc(hidden)]
+    fn read_into(&mut self, buf: &mut [u8]) -> Result<()>;
+
+    /// Take the next `len` bytes, borrowing them from the input if possible.
+    #[doc(hidden)]
+    fn consume_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>>;
+
+    /// Skip over the next `len` bytes.
+    #[doc(hidden)]
+    fn skip_bytes(&mut self, len: usize) -> Result<()>;
+
+    /// Mark the current position, to later find how far the input has moved
+    /// on with [`offset_from`][`Input::offset_from`].
+    #[doc(hidden)]
+    fn marker(&self) -> usize;
+
+    /// How many bytes have been read since the marker was taken.
+    #[doc(hidden)]
+    fn offset_from(&self, marker: usize) -> usize;
+
+    /// How many bytes are left, if known. Used to reject sizes that the input
+    /// could not hold before allocating for them.
+    #[doc(hidden)]
+    fn remaining(&self) -> Option<usize>;
+
+    /// The rest of the input, if it can be borrowed from.
+    #[doc(hidden)]
+    fn as_slice(&self) -> Option<&'de [u8]>;
+}
+
+impl<'de> Input<'de> for &'de [u8] {
+    fn read_into(&mut self, buf: &mut [u8]) -> Result<()> {
+        let bytes = self.consume_bytes(buf.len())?;
+        buf.copy_from_slice(&bytes);
+        Ok(())
+    }
+
+    fn consume_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>> {
+        if len > self.len() {
+            return Err(Error::unexpected_eof());
+        }
+
+        let (bytes, rest) = self.split_at(len);
+        *self = rest;
+        Ok(Cow::Borrowed(bytes))
+    }
+
+    fn skip_bytes(&mut self, len: usize) -> Result<()> {
+        self.consume_bytes(len).map(|_| ())
+    }
+
+    // A slice does not know where it started, but its length shrinks as it
+    // is read.
+    fn marker(&self) -> usize {
+        self.len()
+    }
+
+    fn offset_from(&self, marker: usize) -> usize {
+        marker - self.len()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn as_slice(&self) -> Option<&'de [u8]> {
+        Some(self)
+    }
+}
+
+/// Input for a [`Deserializer`][`crate::de::Deserializer`] that reads from an
+/// [`io::Read`][`Read`], see [`from_reader`][`crate::de::from_reader`]. Nothing
+/// can be borrowed from it, so every string and array is copied.
+#[cfg(feature = "std")]
+pub struct IoRead<R: Read> {
+    reader: R,
+    consumed: usize,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> IoRead<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            reader,
+            consumed: 0,
+        }
+    }
+
+    /// Get the wrapped reader back.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Read for IoRead<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.consumed += read;
+        Ok(read)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de, R: Read> Input<'de> for IoRead<R> {
+    fn read_into(&mut self, buf: &mut [u8]) -> Result<()> {
+        Ok(self.read_exact(buf)?)
+    }
+
+    fn consume_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>> {
+        // Don't trust the length enough to allocate it up front, a corrupt or
+        // malicious length could be up to 8 GiB for a long array.
+        let mut buf = Vec::new();
+        let read = (&mut self.reader).take(len as u64).read_to_end(&mut buf)?;
+        self.consumed += read;
+        if read != len {
+            return Err(Error::unexpected_eof());
+        }
+        Ok(Cow::Owned(buf))
+    }
+
+    fn skip_bytes(&mut self, len: usize) -> Result<()> {
+        let skipped = io::copy(&mut (&mut self.reader).take(len as u64), &mut io::sink())?;
+        self.consumed += skipped as usize;
+        if skipped != len as u64 {
+            return Err(Error::unexpected_eof());
+        }
+        Ok(())
+    }
+
+    fn marker(&self) -> usize {
+        self.consumed
+    }
+
+    fn offset_from(&self, marker: usize) -> usize {
+        self.consumed - marker
+    }
+
+    // A reader could be endless, so sizes are instead guarded by reading the
+    // data bit by bit rather than allocating for it all up front.
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
+
+    fn as_slice(&self) -> Option<&'de [u8]> {
+        None
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for &[u8] {}
+    #[cfg(feature = "std")]
+    impl<R: std::io::Read> Sealed for super::IoRead<R> {}
+}
@@ -0,0 +1,39 @@
+//! Tools for finding out what is wrong with some NBT.
+//!
+//! When deserializing fails deep inside a document, the error's offset and
+//! path say where, but not what the NBT around it looked like. [`trace`]
+//! writes out every value's header with its offset, up to the point where the
+//! NBT stops making sense.
+
+use std::io::Write;
+
+use crate::error::Result;
+use crate::validate::{Stats, Validator};
+
+/// Walk some NBT as [`validate`][`crate::validate()`] does, writing a line to
+/// `out` for each value as it is read: its offset, tag, tag byte, and name or
+/// index, along with the declared length of strings, arrays and lists.
+///
+/// If the NBT is malformed, the lines stop at the value containing the
+/// problem, followed by a line with the offset and the error, and the error is
+/// returned. Offsets are those of the value's tag, or of its payload for the
+/// elements of lists.
+///
+/// ```
+/// # use fastnbt::{Map, Value};
+/// # let level = Map::from([("xPos".to_string(), Value::Int(3))]);
+/// # let chunk = Map::from([("Level".to_string(), Value::Compound(level))]);
+/// # let data = fastnbt::to_bytes(&chunk).unwrap();
+/// let mut out = Vec::new();
+/// fastnbt::debug::trace(&data, &mut out).unwrap();
+///
+/// assert_eq!(
+///     "       0  Compound (10) \"\"\n\
+///      \x20      3    Compound (10) \"Level\"\n\
+///      \x20     11      Int (3) \"xPos\"\n",
+///     String::from_utf8(out).unwrap()
+/// );
+/// ```
+pub fn trace<W: Write>(input: &[u8], out: &mut W) -> Result<Stats> {
+    Validator::new(input, Some(out)).run()
+}
@@ -1,58 +1,480 @@
-//! Contains the Error and Result type used by the deserializer.
-use std::fmt::Display;
+//! Contains the Error and Result type used by the serializer and deserializer.
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt::Display;
+#[cfg(feature = "std")]
+use std::sync::Arc;
 
-/// Various errors that can occur during deserialization.
+use crate::Tag;
+
+/// Various errors that can occur during serialization or deserialization.
+///
+/// Other serializers and deserializers of NBT can make the same errors as
+/// fastnbt does with the constructors, such as [`Error::invalid_tag`]. Each
+/// makes an error of the matching [`ErrorKind`], with the message fastnbt
+/// uses for it.
 #[derive(Debug, Clone)]
-pub struct Error(String);
+pub struct Error {
+    msg: Cow<'static, str>,
+    kind: ErrorKind,
+    offset: Option<usize>,
+    /// Boxed to keep errors small, as they are returned through every level
+    /// of nesting.
+    path: Option<Box<str>>,
+    /// Where what serde expected starts in `msg`, for errors from
+    /// [`serde::de::Error::invalid_type`]. A list uses it to report that its
+    /// elements are the wrong type.
+    expected_at: Option<u32>,
+    /// The error that caused this one, such as an io error.
+    #[cfg(feature = "std")]
+    source: Option<Arc<dyn std::error::Error + Send + Sync>>,
+}
+
+/// The kind of an [`Error`]. Every variant but [`Other`][Self::Other] is
+/// meant to be matched on, but new variants can be added in minor releases.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Any other errors. Users should not match on this variant and should
+    /// instead use a wildcard `_`. Errors in this category may be moved to new
+    /// variants.
+    Other,
+
+    /// Input ran out part way through some NBT value.
+    UnexpectedEof,
+
+    /// A tag byte that is not a valid NBT tag.
+    InvalidTag(u8),
+
+    /// A negative size for a list or array, or one larger than the rest of
+    /// the input could hold.
+    InvalidSize(i32),
+
+    /// The input did not start with a compound.
+    NoRootCompound,
+
+    /// A string was not valid Java CESU-8. Contains the invalid data.
+    Nonunicode(Vec<u8>),
+
+    /// An error from the underlying reader or writer.
+    Io,
+
+    /// When serializing or building a list, an element of a list had a
+    /// different tag to the elements before it. NBT lists can only contain one
+    /// type.
+    HeterogeneousList {
+        /// Index of the offending element in the list.
+        index: usize,
+        /// Tag of the first element of the list.
+        expected: Tag,
+        /// Tag of the offending element.
+        found: Tag,
+    },
+
+    /// Compounds and lists were nested deeper than the deserializer allows.
+    /// See [`DeOpts::max_depth`][`crate::de::DeOpts::max_depth`].
+    DepthLimitExceeded,
+
+    /// There was more input after the end of the NBT. Contains the number of
+    /// bytes left over. See [`DeOpts::allow_trailing`][`crate::de::DeOpts::allow_trailing`].
+    TrailingBytes(usize),
+
+    /// A compound contained the same key more than once. Contains the key.
+    /// See [`DeOpts::on_duplicate_key`][`crate::de::DeOpts::on_duplicate_key`].
+    DuplicateKey(String),
+
+    /// A VarInt in Bedrock network NBT held more bits than its type, or did
+    /// not end within the most bytes its type can take. See
+    /// [`from_bytes_bedrock_network`][`crate::de::from_bytes_bedrock_network`].
+    VarIntTooLong,
+
+    /// When serializing, the top level value was not compound-like, such as a
+    /// struct or map. NBT must have a compound at the top level. The reading
+    /// equivalent is [`NoRootCompound`][Self::NoRootCompound].
+    RootMustBeCompound,
+
+    /// When serializing, a map key was not a string or integer, so could not
+    /// be the name of a compound entry. Contains the tag the key would have
+    /// as a value, or End for keys with no data of their own, such as `()`,
+    /// `None` or a unit enum variant.
+    InvalidKeyType(Tag),
+
+    /// An NBT array was read into a fixed-size array, such as `[i32; 4]`, of
+    /// a different length. See
+    /// [`DeOpts::lenient_arrays`][`crate::de::DeOpts::lenient_arrays`].
+    ArrayLengthMismatch {
+        /// The length of the fixed-size array.
+        expected: usize,
+        /// The length of the NBT array.
+        found: usize,
+    },
+
+    /// The elements of a list were the wrong type for what they were read
+    /// into, such as a list of strings read into a `Vec` of structs. Every
+    /// element of a list has the same tag, so this is reported for the list
+    /// as a whole rather than its first element.
+    UnexpectedList {
+        /// Tag of the elements of the list.
+        element_tag: Tag,
+        /// Length of the list.
+        size: i32,
+        /// What the elements were read into, as serde describes it, eg
+        /// `struct Section`.
+        expected: String,
+    },
+}
+
+/// Broad categories of [`Error`], for deciding what to do about one. See
+/// [`Error::category`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCategory {
+    /// The input is not valid NBT, such as an invalid tag or size. Reading
+    /// it again will fail in the same way.
+    Corrupt,
+
+    /// The input ran out part way through the NBT. It may be valid once more
+    /// of it is available.
+    Incomplete,
+
+    /// The NBT is valid, but does not match the type it was read into, or
+    /// the value being written cannot be NBT. This includes errors from
+    /// serde such as unknown or missing fields.
+    Schema,
+
+    /// The underlying reader or writer failed.
+    Io,
+}
 
 /// Convenience type for Result.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
-impl std::error::Error for Error {}
+// This is `std::error::Error` with std, and its equivalent without.
+impl serde::de::StdError for Error {
+    #[cfg(feature = "std")]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|e| e as _)
+    }
+}
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.0)
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}: ", path)?;
+        }
+        f.write_str(&self.msg)?;
+        if let Some(offset) = self.offset {
+            write!(f, " (at byte {})", offset)?;
+        }
+        Ok(())
     }
 }
 
 impl serde::de::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
-        Error(msg.to_string())
+        Error::bespoke(msg.to_string())
+    }
+
+    fn invalid_type(unexp: serde::de::Unexpected, exp: &dyn serde::de::Expected) -> Self {
+        // The same message as serde's own, keeping what was expected in case
+        // this turns out to be about a whole list.
+        let msg = format!("invalid type: {}, expected ", unexp);
+        let expected_at = msg.len().try_into().ok();
+        let mut err = Error::bespoke(format!("{}{}", msg, exp));
+        err.expected_at = expected_at;
+        err
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::bespoke(msg.to_string())
     }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for Error {
     fn from(e: std::io::Error) -> Self {
-        Error(format!("io error: {}", e))
+        // The io error is left out of the message, as it is the source.
+        let mut err = match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => Error::unexpected_eof(),
+            _ => Error::new("io error", ErrorKind::Io),
+        };
+        err.source = Some(Arc::new(e));
+        err
     }
 }
 
 impl Error {
-    pub(crate) fn invalid_tag(tag: u8) -> Error {
-        Error(format!("invalid nbt tag value: {}", tag))
+    /// Get the kind of error.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Which broad category the error falls in, to tell corrupt input from
+    /// input that was cut short, or from NBT that just doesn't match the type
+    /// being read.
+    ///
+    /// A list or array size larger than the rest of the input counts as
+    /// corrupt, as it cannot be told apart from input that was cut short.
+    pub fn category(&self) -> ErrorCategory {
+        match self.kind {
+            ErrorKind::InvalidTag(_)
+            | ErrorKind::InvalidSize(_)
+            | ErrorKind::NoRootCompound
+            | ErrorKind::Nonunicode(_)
+            | ErrorKind::DepthLimitExceeded
+            | ErrorKind::TrailingBytes(_)
+            | ErrorKind::DuplicateKey(_)
+            | ErrorKind::VarIntTooLong => ErrorCategory::Corrupt,
+            ErrorKind::UnexpectedEof => ErrorCategory::Incomplete,
+            ErrorKind::Other
+            | ErrorKind::HeterogeneousList { .. }
+            | ErrorKind::RootMustBeCompound
+            | ErrorKind::InvalidKeyType(_)
+            | ErrorKind::ArrayLengthMismatch { .. }
+            | ErrorKind::UnexpectedList { .. } => ErrorCategory::Schema,
+            ErrorKind::Io => ErrorCategory::Io,
+        }
+    }
+
+    /// The kind of the io error that caused this error, for errors from the
+    /// underlying reader or writer. The io error itself is the error's
+    /// [`source`][std::error::Error::source].
+    #[cfg(feature = "std")]
+    pub fn io_kind(&self) -> Option<std::io::ErrorKind> {
+        let source = self.source.as_deref()?;
+        source.downcast_ref::<std::io::Error>().map(|e| e.kind())
     }
 
-    pub(crate) fn invalid_size(size: i32) -> Error {
-        Error(format!("invalid nbt list/array size: {}", size))
+    /// Whether the input ran out part way through the NBT, whether it was a
+    /// slice or a reader. Unlike other errors, this can be because the input
+    /// was cut short rather than corrupt, so reading it again once more of it
+    /// is available may succeed.
+    ///
+    /// Use this rather than [`io_kind`][Self::io_kind] to spot the end of a
+    /// reader, as running out part way through a string or array is found
+    /// from its length rather than by an io error.
+    pub fn is_eof(&self) -> bool {
+        self.kind == ErrorKind::UnexpectedEof
     }
 
-    pub(crate) fn no_root_compound() -> Error {
-        Error("invalid nbt: no root compound".to_owned())
+    /// The offset into the input where deserializing failed, if known. Errors
+    /// from serializing do not have one.
+    ///
+    /// For an invalid tag, string, size or VarInt this is the offset of the
+    /// tag, string, size or VarInt itself. For other errors, it is the offset just after the last input
+    /// that was read, which is usually the end of the value that could not be
+    /// deserialized.
+    ///
+    /// For [`from_reader`][`crate::de::from_reader`] this counts the bytes
+    /// read from the reader.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
     }
 
-    pub(crate) fn nonunicode_string(data: &[u8]) -> Error {
-        Error(format!(
-            "invalid nbt string: nonunicode: {}",
-            String::from_utf8_lossy(data)
-        ))
+    /// Record where in the input this error happened, unless it already has a
+    /// more precise offset.
+    pub(crate) fn at_offset(mut self, offset: usize) -> Error {
+        self.offset.get_or_insert(offset);
+        self
     }
 
-    pub(crate) fn unexpected_eof() -> Error {
-        Error("eof: unexpectedly ran out of input".to_owned())
+    /// Where in the NBT deserializing failed, as the names of compound entries
+    /// and indices of list elements leading to it, eg
+    /// `Level.Sections[3].BlockStates`. None for errors outside of the root
+    /// compound, and for errors from serializing.
+    ///
+    /// Errors from [`Value::is_valid_nbt`][crate::Value::is_valid_nbt] have
+    /// the path of the invalid value in the same form.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Record where in the NBT this error happened, if it was inside the root
+    /// compound.
+    pub(crate) fn at_path(mut self, path: String) -> Error {
+        if !path.is_empty() {
+            self.path.get_or_insert(path.into_boxed_str());
+        }
+        self
+    }
+
+    /// An [`ErrorKind::InvalidTag`] error, for a tag byte that is not a valid
+    /// NBT tag.
+    pub fn invalid_tag(tag: u8) -> Error {
+        Error::new(
+            format!("invalid nbt tag value: {}", tag),
+            ErrorKind::InvalidTag(tag),
+        )
+    }
+
+    /// An [`ErrorKind::InvalidSize`] error, for a list or array size that is
+    /// negative or larger than the input could hold.
+    pub fn invalid_size(size: i32) -> Error {
+        Error::new(
+            format!("invalid nbt list/array size: {}", size),
+            ErrorKind::InvalidSize(size),
+        )
+    }
+
+    /// An [`ErrorKind::NoRootCompound`] error, for input that does not start
+    /// with a compound.
+    pub fn no_root_compound() -> Error {
+        Error::new("invalid nbt: no root compound", ErrorKind::NoRootCompound)
+    }
+
+    /// An [`ErrorKind::Nonunicode`] error, for string data that is not valid
+    /// Java CESU-8.
+    pub fn nonunicode_string(data: &[u8]) -> Error {
+        Error::new(
+            format!(
+                "invalid nbt string: nonunicode: {}",
+                String::from_utf8_lossy(data)
+            ),
+            ErrorKind::Nonunicode(data.to_vec()),
+        )
+    }
+
+    /// An [`ErrorKind::UnexpectedEof`] error, for input that ran out part way
+    /// through a value.
+    pub fn unexpected_eof() -> Error {
+        Error::new(
+            "eof: unexpectedly ran out of input",
+            ErrorKind::UnexpectedEof,
+        )
+    }
+
+    /// An [`ErrorKind::HeterogeneousList`] error, for element `index` of a list
+    /// having a different tag to the elements before it.
+    pub fn heterogeneous_list(index: usize, expected: Tag, found: Tag) -> Error {
+        Error::new(
+            format!(
+                "list element {} is {:?}, but the list's elements are {:?}: NBT lists can only contain one type",
+                index, found, expected
+            ),
+            ErrorKind::HeterogeneousList {
+                index,
+                expected,
+                found,
+            },
+        )
+    }
+
+    /// An [`ErrorKind::DepthLimitExceeded`] error, for compounds and lists
+    /// nested deeper than `max_depth`.
+    pub fn depth_limit_exceeded(max_depth: usize) -> Error {
+        Error::new(
+            format!(
+                "invalid nbt: compounds and lists nested more than {} deep",
+                max_depth
+            ),
+            ErrorKind::DepthLimitExceeded,
+        )
+    }
+
+    /// An [`ErrorKind::TrailingBytes`] error, for `len` bytes of input left
+    /// over after the end of the NBT.
+    pub fn trailing_bytes(len: usize) -> Error {
+        Error::new(
+            format!("invalid nbt: {} bytes left over after the end", len),
+            ErrorKind::TrailingBytes(len),
+        )
+    }
+
+    /// An [`ErrorKind::DuplicateKey`] error, for a compound that has `key`
+    /// more than once.
+    pub fn duplicate_key(key: &str) -> Error {
+        Error::new(
+            format!("invalid nbt: duplicate key in compound: {}", key),
+            ErrorKind::DuplicateKey(key.to_owned()),
+        )
+    }
+
+    /// An [`ErrorKind::VarIntTooLong`] error, for a VarInt that does not fit
+    /// in a `bits`-bit integer.
+    pub fn varint_too_long(bits: u32) -> Error {
+        Error::new(
+            format!("invalid nbt: VarInt too long for a {}-bit integer", bits),
+            ErrorKind::VarIntTooLong,
+        )
+    }
+
+    /// An [`ErrorKind::RootMustBeCompound`] error, for serializing a value
+    /// other than a compound at the top level.
+    pub fn root_must_be_compound() -> Error {
+        Error::new(
+            "cannot serialize a non-compound at the top level: NBT must be a compound",
+            ErrorKind::RootMustBeCompound,
+        )
+    }
+
+    /// An [`ErrorKind::InvalidKeyType`] error, for a map key that would have
+    /// been a `tag` rather than a string.
+    pub fn invalid_key_type(tag: Tag) -> Error {
+        Error::new(
+            format!("compound keys must be strings or integers, found {:?}", tag),
+            ErrorKind::InvalidKeyType(tag),
+        )
+    }
+
+    /// An [`ErrorKind::ArrayLengthMismatch`] error, for an NBT array of length
+    /// `found` read into a fixed-size array of length `expected`.
+    pub fn array_length_mismatch(expected: usize, found: usize) -> Error {
+        Error::new(
+            format!(
+                "expected an NBT array of length {}, found length {}",
+                expected, found
+            ),
+            ErrorKind::ArrayLengthMismatch { expected, found },
+        )
+    }
+
+    /// An [`ErrorKind::UnexpectedList`] error, for a list of `size` elements
+    /// tagged `element_tag` read into elements of the type described by
+    /// `expected`.
+    pub fn unexpected_list(element_tag: Tag, size: i32, expected: &str) -> Error {
+        Error::new(
+            format!(
+                "expected a List of {}, found a List of {:?} of length {}",
+                expected, element_tag, size
+            ),
+            ErrorKind::UnexpectedList {
+                element_tag,
+                size,
+                expected: expected.to_owned(),
+            },
+        )
+    }
+
+    /// An [`ErrorKind::Other`] error with a fixed message, which unlike
+    /// [`serde::de::Error::custom`] does not need to allocate.
+    pub fn custom_static(msg: &'static str) -> Error {
+        Error::new(msg, ErrorKind::Other)
+    }
+
+    /// What serde expected, if this came from a value of the wrong type.
+    pub(crate) fn expected(&self) -> Option<&str> {
+        self.msg.get(self.expected_at? as usize..)
     }
 
     pub(crate) fn bespoke(msg: String) -> Error {
-        Error(msg)
+        Error::new(msg, ErrorKind::Other)
+    }
+
+    fn new(msg: impl Into<Cow<'static, str>>, kind: ErrorKind) -> Error {
+        Error {
+            msg: msg.into(),
+            kind,
+            offset: None,
+            path: None,
+            expected_at: None,
+            #[cfg(feature = "std")]
+            source: None,
+        }
     }
 }
@@ -0,0 +1,68 @@
+//! Read a `bool` from any integer. Use with
+//! `#[serde(with = "fastnbt::flag")]`.
+//!
+//! Minecraft stores flags like a shulker box's `keepPacked` as a Byte. A plain
+//! `bool` field handles this, except inside an internally tagged enum or a
+//! flattened struct. Serde buffers the values of those before deserializing
+//! them, and a buffered integer cannot become a `bool`. This module accepts
+//! either, any non-zero integer being `true`.
+//!
+//! ```
+//! # use fastnbt::Value;
+//! # use serde::Deserialize;
+//! # use std::collections::HashMap;
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! #[serde(tag = "id")]
+//! enum BlockEntity {
+//!     #[serde(rename = "minecraft:shulker_box")]
+//!     ShulkerBox {
+//!         #[serde(rename = "keepPacked", with = "fastnbt::flag")]
+//!         keep_packed: bool,
+//!     },
+//! }
+//!
+//! let data = fastnbt::to_bytes(&HashMap::from([
+//!     ("id", Value::String("minecraft:shulker_box".into())),
+//!     ("keepPacked", Value::Byte(1)),
+//! ]))
+//! .unwrap();
+//!
+//! let shulker: BlockEntity = fastnbt::de::from_bytes(&data).unwrap();
+//! assert_eq!(BlockEntity::ShulkerBox { keep_packed: true }, shulker);
+//! ```
+
+use core::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::Serializer;
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    struct FlagVisitor;
+
+    impl<'de> Visitor<'de> for FlagVisitor {
+        type Value = bool;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an integer or bool")
+        }
+
+        fn visit_bool<E: de::Error>(self, v: bool) -> Result<bool, E> {
+            Ok(v)
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<bool, E> {
+            Ok(v != 0)
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<bool, E> {
+            Ok(v != 0)
+        }
+    }
+
+    deserializer.deserialize_any(FlagVisitor)
+}
+
+/// Serialize the flag as a `bool`, which NBT stores as a Byte of 0 or 1.
+pub fn serialize<S: Serializer>(flag: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bool(*flag)
+}
@@ -0,0 +1,136 @@
+//! Java's 'modified UTF-8', the encoding of NBT strings. It is UTF-8, except
+//! that NUL is written as the two bytes `C0 80`, and characters outside of the
+//! Basic Multilingual Plane are written as a UTF-16 surrogate pair, each half
+//! encoded as its own 3 byte character.
+//!
+//! This matches the `cesu8` crate's Java functions, which do not support
+//! `no_std`.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+use core::str;
+
+/// Decode a Java CESU-8 string, or `None` if it is not valid. Plain UTF-8 is
+/// accepted as it is, and borrowed.
+pub(crate) fn decode(data: &[u8]) -> Option<Cow<'_, str>> {
+    if let Ok(s) = str::from_utf8(data) {
+        return Some(Cow::Borrowed(s));
+    }
+
+    let mut out = String::with_capacity(data.len());
+    let mut rest = data;
+
+    while !rest.is_empty() {
+        let (c, len) = match rest {
+            // Java never writes NUL as a single byte.
+            [0x00, ..] => return None,
+            [a @ 0x01..=0x7f, ..] => (*a as u32, 1),
+            [0xc0, 0x80, ..] => (0, 2),
+            [a @ 0xc2..=0xdf, b @ 0x80..=0xbf, ..] => {
+                ((*a as u32 & 0x1f) << 6 | (*b as u32 & 0x3f), 2)
+            }
+            _ => match three_byte(rest)? {
+                high @ 0xd800..=0xdbff => match three_byte(&rest[3..])? {
+                    low @ 0xdc00..=0xdfff => (0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00), 6),
+                    _ => return None,
+                },
+                // char::from_u32 rejects a low surrogate on its own.
+                c => (c, 3),
+            },
+        };
+
+        out.push(char::from_u32(c)?);
+        rest = &rest[len..];
+    }
+
+    Some(Cow::Owned(out))
+}
+
+/// Decode Java CESU-8, replacing anything invalid with U+FFFD. Only used
+/// once strict decoding has failed, so this favours simplicity over speed.
+#[cold]
+pub(crate) fn decode_lossy(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len());
+    let mut rest = data;
+
+    while !rest.is_empty() {
+        let (c, len) = match rest {
+            [a @ 0x00..=0x7f, ..] => (Some(*a as u32), 1),
+            [a @ 0xc0..=0xdf, b @ 0x80..=0xbf, ..] => {
+                let c = (*a as u32 & 0x1f) << 6 | (*b as u32 & 0x3f);
+                // Java writes NUL as two bytes, anything else must be at
+                // least 0x80 to need them.
+                (Some(c).filter(|&c| c == 0 || c >= 0x80), 2)
+            }
+            _ => match three_byte(rest) {
+                Some(high @ 0xd800..=0xdbff) => match three_byte(&rest[3..]) {
+                    Some(low @ 0xdc00..=0xdfff) => {
+                        (Some(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)), 6)
+                    }
+                    // A high surrogate on its own.
+                    _ => (None, 3),
+                },
+                // char::from_u32 rejects a low surrogate on its own.
+                Some(c) => (Some(c), 3),
+                // Skip the first byte of the invalid sequence, the rest might
+                // start a valid one.
+                None => (None, 1),
+            },
+        };
+
+        out.push(
+            c.and_then(char::from_u32)
+                .unwrap_or(char::REPLACEMENT_CHARACTER),
+        );
+        rest = &rest[len..];
+    }
+
+    out
+}
+
+/// Encode a string as Java CESU-8, borrowing it if it is the same as UTF-8.
+#[cfg(feature = "std")]
+pub(crate) fn encode(text: &str) -> Cow<'_, [u8]> {
+    // NUL and 4 byte characters are the only ones encoded differently.
+    if !text.bytes().any(|b| b == 0 || b >= 0xf0) {
+        return Cow::Borrowed(text.as_bytes());
+    }
+
+    let mut encoded = Vec::with_capacity(text.len() + text.len() / 4);
+    for c in text.chars() {
+        match c as u32 {
+            0 => encoded.extend_from_slice(&[0xc0, 0x80]),
+            c @ 0x10000.. => {
+                let c = c - 0x10000;
+                push_three(&mut encoded, 0xd800 | c >> 10);
+                push_three(&mut encoded, 0xdc00 | (c & 0x3ff));
+            }
+            _ => encoded.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+
+    Cow::Owned(encoded)
+}
+
+/// Decode the 3 byte sequence at the start of `data`, if there is one.
+fn three_byte(data: &[u8]) -> Option<u32> {
+    match data {
+        [a @ 0xe0..=0xef, b @ 0x80..=0xbf, c @ 0x80..=0xbf, ..] => {
+            let c = (*a as u32 & 0x0f) << 12 | (*b as u32 & 0x3f) << 6 | (*c as u32 & 0x3f);
+            // Reject overlong encodings.
+            Some(c).filter(|&c| c >= 0x800)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(feature = "std")]
+fn push_three(encoded: &mut Vec<u8>, c: u32) {
+    encoded.extend_from_slice(&[
+        0xe0 | (c >> 12) as u8,
+        0x80 | (c >> 6 & 0x3f) as u8,
+        0x80 | (c & 0x3f) as u8,
+    ]);
+}
@@ -0,0 +1,116 @@
+//! Conversions between [`Value`] and [`serde_json::Value`]. Enabled with the
+//! `json` feature.
+
+use std::convert::TryFrom;
+
+use crate::error::{Error, Result};
+use crate::Value;
+
+/// The largest magnitude integer that an `f64` can represent exactly, and
+/// therefore the largest that JavaScript can handle without losing precision.
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// Convert NBT into JSON. This is lossy:
+///
+/// * All numeric types become JSON numbers, and all of the array types become
+///   JSON arrays of numbers. The distinction between them is lost.
+/// * Longs (including elements of long arrays) outside of ±(2^53 - 1) become
+///   JSON *strings* of the decimal value. JSON numbers are typically parsed
+///   as `f64` (eg in JavaScript), which would silently round these.
+/// * Non-finite floats (NaN and the infinities) have no JSON representation
+///   and become `null`.
+impl From<Value> for serde_json::Value {
+    fn from(value: Value) -> Self {
+        use serde_json::Value as Json;
+
+        match value {
+            Value::Byte(v) => Json::from(v),
+            Value::Short(v) => Json::from(v),
+            Value::Int(v) => Json::from(v),
+            Value::Long(v) => long_to_json(v),
+            Value::Float(v) => float_to_json(v as f64),
+            Value::Double(v) => float_to_json(v),
+            Value::String(v) => Json::String(v),
+            Value::ByteArray(v) => Json::Array(v.iter().map(|b| Json::from(*b)).collect()),
+            Value::IntArray(v) => Json::Array(v.iter().map(|i| Json::from(*i)).collect()),
+            Value::LongArray(v) => Json::Array(v.iter().map(|l| long_to_json(*l)).collect()),
+            Value::List(v) => Json::Array(v.into_iter().map(Json::from).collect()),
+            Value::Compound(v) => {
+                Json::Object(v.into_iter().map(|(k, v)| (k, Json::from(v))).collect())
+            }
+        }
+    }
+}
+
+/// Convert JSON into NBT. Since JSON carries less type information than NBT
+/// the following rules are used:
+///
+/// * `null` is an error, NBT has no equivalent.
+/// * Booleans become `Byte` 0 or 1, the way Minecraft stores them.
+/// * Integers become `Int` if they fit in an `i32`, otherwise `Long`. Integers
+///   that do not fit in an `i64` are an error. `Byte` and `Short` are never
+///   produced from numbers.
+/// * Any other number becomes a `Double`.
+/// * Arrays become `List`, never one of the NBT array types. The elements are
+///   not checked to all be the same type.
+/// * Objects become `Compound`.
+///
+/// Strings are never interpreted as numbers, so a long that was converted to
+/// a string by the `From<Value>` conversion comes back as a `String`.
+impl TryFrom<serde_json::Value> for Value {
+    type Error = Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self> {
+        use serde_json::Value as Json;
+
+        Ok(match value {
+            Json::Null => {
+                return Err(Error::bespoke(
+                    "cannot convert JSON null to NBT: NBT has no null value".to_owned(),
+                ))
+            }
+            Json::Bool(b) => Value::Byte(b as i8),
+            Json::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    match i32::try_from(i) {
+                        Ok(i) => Value::Int(i),
+                        Err(_) => Value::Long(i),
+                    }
+                } else if n.is_u64() {
+                    return Err(Error::bespoke(format!(
+                        "cannot convert JSON number {} to NBT: out of range of a long",
+                        n
+                    )));
+                } else {
+                    // Not an integer, so will be a float.
+                    Value::Double(n.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            Json::String(s) => Value::String(s),
+            Json::Array(vs) => Value::List(
+                vs.into_iter()
+                    .map(Value::try_from)
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            Json::Object(m) => Value::Compound(
+                m.into_iter()
+                    .map(|(k, v)| Ok((k, Value::try_from(v)?)))
+                    .collect::<Result<_>>()?,
+            ),
+        })
+    }
+}
+
+fn long_to_json(v: i64) -> serde_json::Value {
+    if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&v) {
+        serde_json::Value::from(v)
+    } else {
+        serde_json::Value::String(v.to_string())
+    }
+}
+
+fn float_to_json(v: f64) -> serde_json::Value {
+    serde_json::Number::from_f64(v)
+        .map(serde_json::Value::Number)
+        .unwrap_or(serde_json::Value::Null)
+}
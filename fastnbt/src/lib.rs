@@ -3,9 +3,37 @@
 //! the world data and player inventories.
 //!
 //! * For documentation and examples of serde deserialization, see [`de`].
-//! * For a `serde_json`-like `Value` type see [`Value`].
+//! * For serializing to NBT, see [`ser`] and [`to_bytes`].
+//! * For a `serde_json`-like `Value` type see [`Value`]. A `&Value` is itself
+//!   a deserializer, see [`from_value`].
 //! * For NBT array types see [`ByteArray`], [`IntArray`], and [`LongArray`].
-//! * For 'zero-copy' NBT array types see [`borrow`].
+//! * For 'zero-copy' NBT array types, and a `Value` that borrows its strings
+//!   and arrays, see [`borrow`]. Byte Arrays can also be borrowed as a plain
+//!   `&[u8]`, see [`bytes`].
+//! * For UUIDs in either their modern or legacy form, see [`Uuid`].
+//! * For the little-endian NBT of *Bedrock Edition*, see [`to_bytes_le`] and
+//!   [`de::from_bytes_le`].
+//! * For the network NBT of the Java protocol since 1.20.2, which has no root
+//!   name, see [`to_bytes_network`] and [`de::from_bytes_network`].
+//! * For the network NBT of the Bedrock protocol, which uses VarInts, see
+//!   [`to_bytes_bedrock_network`] and [`de::from_bytes_bedrock_network`].
+//! * To read or write the name of the root compound, see
+//!   [`de::from_bytes_named`] and [`to_bytes_named`].
+//! * To read a single value without deserializing everything, see
+//!   [`de::get_path`].
+//! * To change a single number or string without deserializing everything,
+//!   see [`patch`].
+//! * To check that NBT is well-formed and count what it contains, see
+//!   [`validate()`]. To see the headers of the values leading up to a
+//!   problem, see [`debug::trace`].
+//! * To accept integers stored with a smaller tag than expected, see [`widen`].
+//! * For bools inside internally tagged enums and flattened structs, see
+//!   [`flag`].
+//! * With the `async` feature, NBT can be read from a tokio `AsyncRead`, see
+//!   `de::from_async_reader`.
+//! * With the `json` feature, [`Value`] can be converted to and from
+//!   `serde_json::Value`. See the `From`/`TryFrom` impls on [`Value`] for the
+//!   conversion rules.
 //!
 //! Both this and related crates are under one [fastnbt Github
 //! repository](https://github.com/owengage/fastnbt)
@@ -119,29 +147,115 @@
 //! the `Read` trait on the input. This parser however doesn't support
 //! deserializing to Rust objects directly.
 //!
+//! # `no_std`
+//!
+//! Without the default `std` feature, fastnbt only needs `alloc`:
+//!
+//! ```toml
+//! [dependencies]
+//! fastnbt = { version = "1", default-features = false }
+//! ```
+//!
+//! Deserializing from bytes with [`de::from_bytes`] and friends, [`Value`],
+//! the array types and the [`borrow`] module are all available. Compounds in
+//! [`Value`] and [`borrow::Value`] are a `BTreeMap` rather than a `HashMap`.
+//! Anything that reads, writes or compresses, including serializing, needs
+//! `std`.
+//!
+//! # Key order
+//!
+//! Compounds in [`Value`] are a `HashMap` by default, so keys come back out
+//! in no particular order. With the `preserve_order` feature they are an
+//! `IndexMap`, keeping the order they were read in, and serializing writes
+//! them in that order. This keeps diffs of re-serialized files small. Use
+//! [`Map`] to build compounds that work either way.
+//!
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 use serde::{Deserialize, Serialize};
 
 pub mod borrow;
+pub mod bytes;
+pub mod compression;
 pub mod de;
+#[cfg(feature = "std")]
+pub mod debug;
 pub mod error;
+pub mod flag;
+#[cfg(feature = "std")]
+pub mod patch;
+#[cfg(feature = "std")]
+pub mod ser;
+#[cfg(feature = "std")]
 pub mod stream;
+pub mod uuid;
+#[cfg(feature = "std")]
+pub mod validate;
+pub mod widen;
 
 mod arrays;
 mod value;
 
+#[cfg(feature = "json")]
+mod json;
+
 pub use arrays::*;
+pub use compression::Compression;
+#[cfg(feature = "std")]
+pub use ser::{
+    to_bytes, to_bytes_bedrock_network, to_bytes_le, to_bytes_named, to_bytes_network, to_writer,
+    to_writer_bedrock_network, to_writer_le, to_writer_named, to_writer_network,
+};
+pub use uuid::Uuid;
+#[cfg(feature = "std")]
+pub use validate::validate;
 pub use value::*;
 
 pub(crate) mod de_arrays;
+#[cfg(feature = "async")]
+mod de_async;
+mod de_input;
+mod java_cesu8;
 
-#[cfg(test)]
+// The tests build NBT with the serializer and read files, so need std. The
+// fastnbt-no-std crate checks fastnbt without it.
+#[cfg(all(test, feature = "std"))]
 mod test;
 
-use std::convert::{TryFrom, TryInto};
+use alloc::format;
+use core::convert::{TryFrom, TryInto};
+
+/// The map in [`Value::Compound`] and [`borrow::Value::Compound`]. This is a
+/// `HashMap`, or an `IndexMap` keeping the order the keys were read or
+/// inserted in if the `preserve_order` feature is enabled. Without `std` it is
+/// a `BTreeMap`.
+#[cfg(all(feature = "std", not(feature = "preserve_order")))]
+pub use std::collections::HashMap as Map;
+
+/// The map in [`Value::Compound`], keeping the order of its keys.
+#[cfg(feature = "preserve_order")]
+pub use indexmap::IndexMap as Map;
+
+/// The map in [`Value::Compound`], sorted by key without `std`.
+#[cfg(not(feature = "std"))]
+pub use alloc::collections::BTreeMap as Map;
+
+#[cfg(not(feature = "std"))]
+pub(crate) use alloc::collections::{btree_map as map, BTreeSet as Set};
+#[cfg(all(feature = "std", not(feature = "preserve_order")))]
+pub(crate) use std::collections::{hash_map as map, HashSet as Set};
+#[cfg(feature = "preserve_order")]
+pub(crate) use {indexmap::map, std::collections::HashSet as Set};
 
 /// An NBT tag. This does not carry the value or the name of the data.
-#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+///
+/// Converts to and from its byte in NBT with [`u8::from`] and
+/// [`Tag::try_from`], and displays as its name in the NBT specification, eg
+/// `TAG_Long_Array`.
+#[derive(Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
 #[repr(u8)]
 pub enum Tag {
     /// Represents the end of a Compound object.
@@ -162,7 +276,7 @@ pub enum Tag {
     ByteArray = 7,
     /// Represents a Unicode string.
     String = 8,
-    /// Represents a list of other objects, elements are not required to be the same type.
+    /// Represents a list of other objects, all with the same tag.
     List = 9,
     /// Represents a struct-like structure.
     Compound = 10,
@@ -172,10 +286,37 @@ pub enum Tag {
     LongArray = 12,
 }
 
+/// Byte order of the lengths and numbers in NBT. Java Edition uses big-endian,
+/// Bedrock Edition uses little-endian on disk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Endian {
+    Big,
+    Little,
+    /// Bedrock Edition's network protocol. Little-endian, except that ints,
+    /// longs and all lengths are VarInts.
+    VarInt,
+}
+
 pub(crate) const BYTE_ARRAY_TAG: u8 = 7;
 pub(crate) const INT_ARRAY_TAG: u8 = 11;
 pub(crate) const LONG_ARRAY_TAG: u8 = 12;
 
+// Newtype struct names the array types serialize with. Our serializer uses
+// these to write the array tags, other serializers just see a sequence.
+pub(crate) const BYTE_ARRAY_TOKEN: &str = "__fastnbt_byte_array";
+pub(crate) const INT_ARRAY_TOKEN: &str = "__fastnbt_int_array";
+pub(crate) const LONG_ARRAY_TOKEN: &str = "__fastnbt_long_array";
+
+// Newtype struct names the owned array types ask for their elements with, so
+// that our deserializer can give them several elements at once.
+pub(crate) const BYTE_ARRAY_CHUNK_TOKEN: &str = "__fastnbt_byte_array_chunk";
+pub(crate) const INT_ARRAY_CHUNK_TOKEN: &str = "__fastnbt_int_array_chunk";
+pub(crate) const LONG_ARRAY_CHUNK_TOKEN: &str = "__fastnbt_long_array_chunk";
+
+// Struct name `borrow::Compound` deserializes with, so that our deserializer
+// can hand it the raw payload of a compound rather than its entries.
+pub(crate) const COMPOUND_TOKEN: &str = "__fastnbt_compound";
+
 // Crates exist to generate this code for us, but would add to our compile
 // times, so we instead right it out manually, the tags will very rarely change
 // so isn't a massive burden, but saves a significant amount of compile time.
@@ -223,9 +364,55 @@ impl From<Tag> for u8 {
     }
 }
 
+impl Tag {
+    /// The tag `value` is written with.
+    pub fn of(value: &Value) -> Tag {
+        value.tag()
+    }
+
+    /// Whether this is one of the tags for numbers, Byte through Double.
+    pub fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            Tag::Byte | Tag::Short | Tag::Int | Tag::Long | Tag::Float | Tag::Double
+        )
+    }
+
+    /// Whether this is ByteArray, IntArray or LongArray. Lists are not
+    /// arrays.
+    pub fn is_array(self) -> bool {
+        matches!(self, Tag::ByteArray | Tag::IntArray | Tag::LongArray)
+    }
+
+    /// The name of the tag in the NBT specification, eg `TAG_Long_Array`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Tag::End => "TAG_End",
+            Tag::Byte => "TAG_Byte",
+            Tag::Short => "TAG_Short",
+            Tag::Int => "TAG_Int",
+            Tag::Long => "TAG_Long",
+            Tag::Float => "TAG_Float",
+            Tag::Double => "TAG_Double",
+            Tag::ByteArray => "TAG_Byte_Array",
+            Tag::String => "TAG_String",
+            Tag::List => "TAG_List",
+            Tag::Compound => "TAG_Compound",
+            Tag::IntArray => "TAG_Int_Array",
+            Tag::LongArray => "TAG_Long_Array",
+        }
+    }
+}
+
+impl core::fmt::Display for Tag {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 /// Compile time NBT tag type. Useful for forcing a custom type to have a field
 /// that must be a given tag. Used for the Array types.
-#[derive(Serialize, Clone, Copy, PartialEq)]
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct CompTag<const N: u8>;
 
 impl<'de, const N: u8> Deserialize<'de> for CompTag<N> {
@@ -235,15 +422,22 @@ impl<'de, const N: u8> Deserialize<'de> for CompTag<N> {
     {
         let tag: u8 = Deserialize::deserialize(deserializer)?;
         if tag != N {
-            Err(serde::de::Error::custom("unexpected array type"))
+            let found = match Tag::try_from(tag) {
+                Ok(tag) => format!("{:?}", tag),
+                Err(_) => format!("InvalidTag({})", tag),
+            };
+            Err(serde::de::Error::custom(format!(
+                "expected {:?}, found {}",
+                Self, found
+            )))
         } else {
             Ok(Self)
         }
     }
 }
 
-impl<const N: u8> std::fmt::Debug for CompTag<N> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<const N: u8> core::fmt::Debug for CompTag<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let tag = <u8 as TryInto<Tag>>::try_into(N);
         match tag {
             Ok(tag) => tag.fmt(f),
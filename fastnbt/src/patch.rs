@@ -0,0 +1,180 @@
+//! Change a single value in some NBT without deserializing all of it.
+//!
+//! For bulk edits across many chunks, such as setting every chunk's `Status`,
+//! finding the value by skipping over everything before it and splicing in the
+//! new payload is much cheaper than a full deserialize and serialize.
+//!
+//! ```
+//! # use fastnbt::{patch::{set_scalar, ScalarValue}, Map, Value};
+//! # use std::collections::HashMap;
+//! # let level = Map::from([("Status".to_string(), Value::String("empty".into()))]);
+//! # let chunk = Map::from([("Level".to_string(), Value::Compound(level))]);
+//! # let data = fastnbt::to_bytes(&chunk).unwrap();
+//! let status = ScalarValue::String("full".into());
+//! let patched = set_scalar(&data, &["Level", "Status"], status).unwrap();
+//!
+//! let chunk: HashMap<String, HashMap<String, String>> = fastnbt::de::from_bytes(&patched).unwrap();
+//! assert_eq!("full", chunk["Level"]["Status"]);
+//! ```
+//!
+//! Only big-endian NBT with a named root is supported.
+
+use std::convert::TryFrom;
+use std::ops::Range;
+
+use crate::de::{DeOpts, InputHelper};
+use crate::error::{Error, Result};
+use crate::{Endian, Tag};
+
+/// A new value for [`set_scalar`]. It must have the same tag as the value it
+/// replaces.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScalarValue {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+}
+
+impl ScalarValue {
+    fn tag(&self) -> Tag {
+        match self {
+            ScalarValue::Byte(_) => Tag::Byte,
+            ScalarValue::Short(_) => Tag::Short,
+            ScalarValue::Int(_) => Tag::Int,
+            ScalarValue::Long(_) => Tag::Long,
+            ScalarValue::Float(_) => Tag::Float,
+            ScalarValue::Double(_) => Tag::Double,
+            ScalarValue::String(_) => Tag::String,
+        }
+    }
+
+    /// The payload as it is written in NBT.
+    fn payload(&self) -> Result<Vec<u8>> {
+        Ok(match self {
+            ScalarValue::Byte(v) => v.to_be_bytes().to_vec(),
+            ScalarValue::Short(v) => v.to_be_bytes().to_vec(),
+            ScalarValue::Int(v) => v.to_be_bytes().to_vec(),
+            ScalarValue::Long(v) => v.to_be_bytes().to_vec(),
+            ScalarValue::Float(v) => v.to_be_bytes().to_vec(),
+            ScalarValue::Double(v) => v.to_be_bytes().to_vec(),
+            ScalarValue::String(s) => {
+                let encoded = crate::java_cesu8::encode(s);
+                let len = u16::try_from(encoded.len()).map_err(|_| {
+                    Error::bespoke(format!(
+                        "string of {} bytes is too long for NBT",
+                        encoded.len()
+                    ))
+                })?;
+
+                let mut payload = len.to_be_bytes().to_vec();
+                payload.extend_from_slice(&encoded);
+                payload
+            }
+        })
+    }
+}
+
+/// Replace the scalar value at `path` in some NBT, returning the patched NBT.
+/// The path is the names of the compounds leading to the value, starting
+/// inside the root compound, then the name of the value itself. Values inside
+/// lists cannot be reached.
+///
+/// It is an error if the value is missing, or if it has a different tag to
+/// `new_value`. See [`set_scalar_in_place`] to avoid copying the input.
+pub fn set_scalar(input: &[u8], path: &[&str], new_value: ScalarValue) -> Result<Vec<u8>> {
+    let mut output = input.to_vec();
+    set_scalar_in_place(&mut output, path, new_value)?;
+    Ok(output)
+}
+
+/// Replace the scalar value at `path` in some NBT, see [`set_scalar`].
+///
+/// When the new payload is the same size as the old, which is always true
+/// except for strings, it is overwritten in place and the rest of the data is
+/// untouched. Otherwise everything after the value is moved.
+pub fn set_scalar_in_place(
+    data: &mut Vec<u8>,
+    path: &[&str],
+    new_value: ScalarValue,
+) -> Result<()> {
+    let (found, range) = locate(data, path)?;
+
+    if found != new_value.tag() {
+        return Err(Error::bespoke(format!(
+            "cannot patch {}: it is {:?}, not {:?}",
+            path.join("."),
+            found,
+            new_value.tag()
+        )));
+    }
+
+    let payload = new_value.payload()?;
+    if payload.len() == range.len() {
+        data[range].copy_from_slice(&payload);
+    } else {
+        data.splice(range, payload);
+    }
+
+    Ok(())
+}
+
+/// Find the tag and payload byte range of the value at `path`.
+fn locate(data: &[u8], path: &[&str]) -> Result<(Tag, Range<usize>)> {
+    let not_found = || Error::bespoke(format!("no value at {}", path.join(".")));
+
+    if path.is_empty() {
+        return Err(Error::bespoke("cannot patch an empty path".to_owned()));
+    }
+
+    let mut input = InputHelper::new(data, Endian::Big);
+    if input.consume_tag()? != Tag::Compound {
+        return Err(Error::no_root_compound());
+    }
+    input.consume_name()?;
+
+    // Values off the path are skipped without being read, so an array costs
+    // nothing but a check that its length fits in the input.
+    let mut depth = 0;
+    loop {
+        let tag = input.consume_tag()?;
+        if tag == Tag::End {
+            // The compound on the path ended without the next name.
+            return Err(not_found());
+        }
+
+        if input.consume_name()? != path[depth] {
+            input.ignore_value(tag, depth + 1, DeOpts::DEFAULT_MAX_DEPTH)?;
+            continue;
+        }
+
+        if depth + 1 < path.len() {
+            if tag != Tag::Compound {
+                return Err(Error::bespoke(format!(
+                    "cannot patch {}: {} is not a compound",
+                    path.join("."),
+                    path[..=depth].join(".")
+                )));
+            }
+            depth += 1;
+            continue;
+        }
+
+        if matches!(
+            tag,
+            Tag::ByteArray | Tag::IntArray | Tag::LongArray | Tag::List | Tag::Compound
+        ) {
+            return Err(Error::bespoke(format!(
+                "cannot patch {}: only numbers and strings can be patched",
+                path.join(".")
+            )));
+        }
+
+        let start = input.offset();
+        input.ignore_value(tag, depth + 1, DeOpts::DEFAULT_MAX_DEPTH)?;
+        return Ok((tag, start..input.offset()));
+    }
+}
@@ -0,0 +1,322 @@
+use std::io::Write;
+
+use serde::{
+    ser::{self, Impossible},
+    Serialize, Serializer,
+};
+
+use crate::error::{Error, Result};
+use crate::Tag;
+
+use super::write_nbt::NbtWriter;
+
+fn not_an_array(tag: Tag) -> Error {
+    Error::bespoke(format!("expected a sequence of elements for NBT {:?}", tag))
+}
+
+fn wrong_element(tag: Tag) -> Error {
+    Error::bespoke(format!("invalid element type for NBT {:?}", tag))
+}
+
+/// Serializes the payload of an NBT array. The header has already been
+/// written, so this writes the length followed by the elements.
+pub(crate) struct ArraySerializer<'a, W: Write> {
+    writer: &'a mut NbtWriter<W>,
+    tag: Tag,
+}
+
+impl<'a, W: Write> ArraySerializer<'a, W> {
+    pub(crate) fn new(writer: &'a mut NbtWriter<W>, tag: Tag) -> Self {
+        Self { writer, tag }
+    }
+}
+
+impl<'a, W: Write> Serializer for ArraySerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = ArrayElements<'a, W>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        let len = len.ok_or_else(|| not_an_array(self.tag))?;
+        self.writer.write_len(len)?;
+        Ok(ArrayElements {
+            writer: self.writer,
+            tag: self.tag,
+            len,
+            index: 0,
+        })
+    }
+
+    /// Bytes are the elements as they are laid out in big-endian NBT, which
+    /// lets the borrowed arrays be copied straight to the output.
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        let size = match self.tag {
+            Tag::ByteArray => 1,
+            Tag::IntArray => 4,
+            Tag::LongArray => 8,
+            _ => return Err(not_an_array(self.tag)),
+        };
+        if !v.chunks_exact(size).remainder().is_empty() {
+            return Err(Error::bespoke(format!(
+                "{} bytes is not a whole number of elements for NBT {:?}",
+                v.len(),
+                self.tag
+            )));
+        }
+        self.writer.write_len(v.len() / size)?;
+        self.writer.write_be_elements(v, size)
+    }
+
+    unsupported! { |this| not_an_array(this.tag) =>
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<()> {
+        Err(not_an_array(self.tag))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _value: &T,
+    ) -> Result<()> {
+        Err(not_an_array(self.tag))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()> {
+        Err(not_an_array(self.tag))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(not_an_array(self.tag))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(not_an_array(self.tag))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(not_an_array(self.tag))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(not_an_array(self.tag))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(not_an_array(self.tag))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(not_an_array(self.tag))
+    }
+}
+
+/// Serializes the elements of an NBT array.
+pub(crate) struct ArrayElements<'a, W: Write> {
+    writer: &'a mut NbtWriter<W>,
+    tag: Tag,
+    len: usize,
+    index: usize,
+}
+
+impl<'a, W: Write> ser::SerializeSeq for ArrayElements<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        if self.index >= self.len {
+            return Err(Error::bespoke(format!(
+                "array was serialized with more than its length of {} elements",
+                self.len
+            )));
+        }
+
+        self.index += 1;
+        value.serialize(ElementSerializer {
+            writer: &mut *self.writer,
+            tag: self.tag,
+        })
+    }
+
+    fn end(self) -> Result<()> {
+        if self.index != self.len {
+            return Err(Error::bespoke(format!(
+                "array was serialized with {} elements but a length of {}",
+                self.index, self.len
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Serializes a single element of an NBT array, which must match the array's
+/// element type exactly.
+struct ElementSerializer<'a, W: Write> {
+    writer: &'a mut NbtWriter<W>,
+    tag: Tag,
+}
+
+impl<'a, W: Write> Serializer for ElementSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        if self.tag != Tag::ByteArray {
+            return Err(wrong_element(self.tag));
+        }
+        self.writer.write_i8(v)?;
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        if self.tag != Tag::IntArray {
+            return Err(wrong_element(self.tag));
+        }
+        self.writer.write_i32(v)?;
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        if self.tag != Tag::LongArray {
+            return Err(wrong_element(self.tag));
+        }
+        self.writer.write_i64(v)?;
+        Ok(())
+    }
+
+    unsupported! { |this| wrong_element(this.tag) =>
+        serialize_bool(bool),
+        serialize_i16(i16),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<()> {
+        Err(wrong_element(self.tag))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _value: &T,
+    ) -> Result<()> {
+        Err(wrong_element(self.tag))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()> {
+        Err(wrong_element(self.tag))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(wrong_element(self.tag))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(wrong_element(self.tag))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(wrong_element(self.tag))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(wrong_element(self.tag))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(wrong_element(self.tag))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(wrong_element(self.tag))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(wrong_element(self.tag))
+    }
+}
@@ -0,0 +1,393 @@
+//! This module contains a serde serializer for NBT data. This should be able to
+//! serialize most structures to NBT. Use [`to_bytes`] or [`to_writer`].
+//!
+//! ```
+//! use fastnbt::LongArray;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! #[serde(rename_all = "PascalCase")]
+//! struct Section {
+//!     y: i8,
+//!     block_states: LongArray,
+//!     palette: Vec<Block>,
+//! }
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! #[serde(rename_all = "PascalCase")]
+//! struct Block {
+//!     name: String,
+//! }
+//!
+//! let section = Section {
+//!     y: 4,
+//!     block_states: LongArray::new(vec![0; 256]),
+//!     palette: vec![Block { name: "minecraft:air".to_owned() }],
+//! };
+//!
+//! let bytes = fastnbt::to_bytes(&section).unwrap();
+//! let back: Section = fastnbt::de::from_bytes(&bytes).unwrap();
+//! assert_eq!(section, back);
+//! ```
+//!
+//! # Mapping to NBT
+//!
+//! * Structs and maps become compounds. Map keys must be strings, or integers
+//!   which are written as strings.
+//! * Sequences, tuples and tuple structs become lists. All elements of a list
+//!   must serialize to the same NBT tag, otherwise an error with kind
+//!   [`HeterogeneousList`][`crate::error::ErrorKind::HeterogeneousList`] is
+//!   returned. The length of a sequence must be known up front.
+//! * [`ByteArray`][`crate::ByteArray`], [`IntArray`][`crate::IntArray`] and
+//!   [`LongArray`][`crate::LongArray`] become the matching NBT array. Byte
+//!   slices serialized with `serialize_bytes` (eg with `serde_bytes`) also
+//!   become a byte array.
+//! * `bool` becomes a byte of 0 or 1, like Minecraft stores them.
+//! * Unsigned integers become the signed NBT type of the same width. It is an
+//!   error if the value does not fit, matching the deserializer's behaviour for
+//!   negative values.
+//! * `i128` and `u128` become an int array of four ints, most significant
+//!   first. This is how Minecraft stores UUIDs.
+//! * `char` becomes a string. Strings are written in Java's CESU-8.
+//! * `Some` becomes the inner value. `None` fields of structs and maps are
+//!   skipped entirely, mirroring how a missing key deserializes to `None`.
+//!   `None` in a list is an error, since lists cannot have missing elements.
+//! * Unit enum variants become a string of the variant's name. Other variants
+//!   become a compound with a single key of the variant's name, like
+//!   `serde_json`.
+//! * The serializer is not human readable, so types with a compact form, such
+//!   as `Ipv4Addr`, use it. See [`Serializer::human_readable`] to change this.
+//!
+//! The top level value must serialize to a compound, since that is what all
+//! NBT documents are. It is written with an empty name, use [`to_bytes_named`]
+//! or [`to_writer_named`] to give it a name.
+//!
+//! Some types cannot be represented in NBT. This includes `()` and unit
+//! structs.
+//!
+//! # Little-endian NBT
+//!
+//! *Minecraft: Bedrock Edition* stores NBT on disk with the same tags and
+//! structure, but with lengths and numbers little-endian, and strings in
+//! UTF-8 rather than CESU-8. [`to_bytes_le`] and [`to_writer_le`] write this
+//! form, and [`from_bytes_le`][`crate::de::from_bytes_le`] reads it back.
+//!
+//! Files like Bedrock's `level.dat` start with an 8 byte header before the
+//! NBT. This is not handled here, strip it before deserializing and add it
+//! back after serializing.
+//!
+//! # Network NBT
+//!
+//! Since 1.20.2 the Java Edition protocol sends NBT without a name for the
+//! root compound, the root tag is followed directly by the first child.
+//! [`to_bytes_network`] and [`to_writer_network`] write this form, and
+//! [`from_bytes_network`][`crate::de::from_bytes_network`] reads it back.
+//!
+//! # Bedrock network NBT
+//!
+//! The *Minecraft: Bedrock Edition* protocol sends little-endian NBT where
+//! ints and longs are ZigZag VarInts, list and array lengths are ZigZag
+//! VarInts, and string lengths are unsigned VarInts. The root compound keeps
+//! its name. [`to_bytes_bedrock_network`] and [`to_writer_bedrock_network`]
+//! write this form, and
+//! [`from_bytes_bedrock_network`][`crate::de::from_bytes_bedrock_network`]
+//! reads it back.
+
+use std::io::Write;
+
+use serde::{ser::Impossible, Serialize};
+
+use crate::error::{Error, Result};
+use crate::Endian;
+
+/// Implement serializer methods that always fail with the given error. The
+/// error can refer to the serializer with the `|this| error` form.
+macro_rules! unsupported {
+    (|$this:ident| $err:expr => $($method:ident($($ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $ty),*) -> Result<Self::Ok> {
+                let $this = self;
+                Err($err)
+            }
+        )*
+    };
+    ($err:expr => $($method:ident($($ty:ty),*)),* $(,)?) => {
+        unsupported! { |_this| $err => $($method($($ty),*)),* }
+    };
+}
+
+mod array_serializer;
+mod name_serializer;
+mod serializer;
+mod write_nbt;
+
+pub use serializer::CompoundSerializer;
+use serializer::{Header, ValueSerializer};
+use write_nbt::NbtWriter;
+
+/// Serialize some `T` into NBT data. See the [`ser`](self) module for more
+/// information.
+pub fn to_bytes<T: Serialize>(v: &T) -> Result<Vec<u8>> {
+    let mut result = vec![];
+    to_writer(&mut result, v)?;
+    Ok(result)
+}
+
+/// Serialize some `T` into NBT data, writing it to a [`Write`]. See the
+/// [`ser`](self) module for more information.
+pub fn to_writer<T: Serialize, W: Write>(writer: W, v: &T) -> Result<()> {
+    let mut serializer = Serializer::new(writer);
+    v.serialize(&mut serializer)
+}
+
+/// Serialize some `T` into NBT data, with the given name for the root
+/// compound. Most NBT has an empty root name, but some files use it.
+///
+/// ```
+/// # use std::collections::HashMap;
+/// let data = fastnbt::to_bytes_named("Schematic", &HashMap::<String, i32>::new()).unwrap();
+/// let (name, _): (String, HashMap<String, i32>) = fastnbt::de::from_bytes_named(&data).unwrap();
+/// assert_eq!("Schematic", name);
+/// ```
+pub fn to_bytes_named<T: Serialize>(name: &str, v: &T) -> Result<Vec<u8>> {
+    let mut result = vec![];
+    to_writer_named(&mut result, name, v)?;
+    Ok(result)
+}
+
+/// Serialize some `T` into NBT data, with the given name for the root
+/// compound, writing it to a [`Write`]. See [`to_bytes_named`].
+pub fn to_writer_named<T: Serialize, W: Write>(writer: W, name: &str, v: &T) -> Result<()> {
+    let mut serializer = Serializer::new(writer).with_root_name(name);
+    v.serialize(&mut serializer)
+}
+
+/// Serialize some `T` into little-endian NBT data, as used by *Minecraft:
+/// Bedrock Edition*. See [Little-endian NBT](self#little-endian-nbt).
+pub fn to_bytes_le<T: Serialize>(v: &T) -> Result<Vec<u8>> {
+    let mut result = vec![];
+    to_writer_le(&mut result, v)?;
+    Ok(result)
+}
+
+/// Serialize some `T` into little-endian NBT data, writing it to a [`Write`].
+/// See [Little-endian NBT](self#little-endian-nbt).
+pub fn to_writer_le<T: Serialize, W: Write>(writer: W, v: &T) -> Result<()> {
+    let mut serializer = Serializer::new_le(writer);
+    v.serialize(&mut serializer)
+}
+
+/// Serialize some `T` into network NBT data, which has no root name. See
+/// [Network NBT](self#network-nbt).
+pub fn to_bytes_network<T: Serialize>(v: &T) -> Result<Vec<u8>> {
+    let mut result = vec![];
+    to_writer_network(&mut result, v)?;
+    Ok(result)
+}
+
+/// Serialize some `T` into network NBT data, writing it to a [`Write`]. See
+/// [Network NBT](self#network-nbt).
+pub fn to_writer_network<T: Serialize, W: Write>(writer: W, v: &T) -> Result<()> {
+    let mut serializer = Serializer::new_network(writer);
+    v.serialize(&mut serializer)
+}
+
+/// Serialize some `T` into the network NBT of *Minecraft: Bedrock Edition*,
+/// which uses VarInts. See [Bedrock network NBT](self#bedrock-network-nbt).
+pub fn to_bytes_bedrock_network<T: Serialize>(v: &T) -> Result<Vec<u8>> {
+    let mut result = vec![];
+    to_writer_bedrock_network(&mut result, v)?;
+    Ok(result)
+}
+
+/// Serialize some `T` into the network NBT of *Minecraft: Bedrock Edition*,
+/// writing it to a [`Write`]. See [Bedrock network
+/// NBT](self#bedrock-network-nbt).
+pub fn to_writer_bedrock_network<T: Serialize, W: Write>(writer: W, v: &T) -> Result<()> {
+    let mut serializer = Serializer::new_bedrock_network(writer);
+    v.serialize(&mut serializer)
+}
+
+/// Serializer for NBT data. See the [`ser`](self) module for more information.
+pub struct Serializer<W: Write> {
+    writer: NbtWriter<W>,
+    root_name: Option<String>,
+}
+
+impl<W: Write> Serializer<W> {
+    /// Create a serializer that writes NBT to the given writer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: NbtWriter::new(writer, Endian::Big),
+            root_name: Some(String::new()),
+        }
+    }
+
+    /// Create a serializer that writes little-endian NBT to the given writer.
+    pub fn new_le(writer: W) -> Self {
+        Self {
+            writer: NbtWriter::new(writer, Endian::Little),
+            root_name: Some(String::new()),
+        }
+    }
+
+    /// Create a serializer that writes network NBT, with no root name, to the
+    /// given writer.
+    pub fn new_network(writer: W) -> Self {
+        Self {
+            root_name: None,
+            ..Self::new(writer)
+        }
+    }
+
+    /// Create a serializer that writes the network NBT of *Minecraft: Bedrock
+    /// Edition* to the given writer.
+    pub fn new_bedrock_network(writer: W) -> Self {
+        Self {
+            writer: NbtWriter::new(writer, Endian::VarInt),
+            root_name: Some(String::new()),
+        }
+    }
+
+    /// Write the root compound with the given name, rather than an empty one.
+    pub fn with_root_name(mut self, name: &str) -> Self {
+        self.root_name = Some(name.to_owned());
+        self
+    }
+
+    /// What the serializer answers to serde's
+    /// [`is_human_readable`][`serde::Serializer::is_human_readable`]. Some
+    /// types, such as IP addresses, are written as a string when it is true
+    /// and in a compact binary form when it is false.
+    ///
+    /// NBT is a binary format, so this is false by default. Data written with
+    /// it set to true must be read with
+    /// [`DeOpts::human_readable`][`crate::de::DeOpts::human_readable`] set to
+    /// true too. Compound names are always written as if human readable, as
+    /// they are always strings.
+    pub fn human_readable(mut self, human_readable: bool) -> Self {
+        self.writer.human_readable = human_readable;
+        self
+    }
+
+    /// Get back the writer.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    fn root(&mut self) -> ValueSerializer<'_, '_, W> {
+        ValueSerializer::new(&mut self.writer, Header::Root(self.root_name.as_deref()))
+    }
+}
+
+/// The top level of the NBT. This only allows compound-like values.
+impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = CompoundSerializer<'a, W>;
+    type SerializeStruct = CompoundSerializer<'a, W>;
+    type SerializeStructVariant = CompoundSerializer<'a, W>;
+
+    unsupported! { Error::root_must_be_compound() =>
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        serde::Serializer::serialize_newtype_variant(
+            self.root(),
+            name,
+            variant_index,
+            variant,
+            value,
+        )
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::root_must_be_compound())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::root_must_be_compound())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::root_must_be_compound())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::root_must_be_compound())
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+        serde::Serializer::serialize_map(self.root(), len)
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        serde::Serializer::serialize_struct(self.root(), name, len)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        serde::Serializer::serialize_struct_variant(self.root(), name, variant_index, variant, len)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.writer.human_readable
+    }
+}
@@ -0,0 +1,523 @@
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::io::Write;
+
+use serde::{ser, Serialize};
+
+use crate::arrays::u128_to_int_array;
+use crate::error::{Error, Result};
+use crate::{Tag, BYTE_ARRAY_TOKEN, INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN};
+
+use super::array_serializer::ArraySerializer;
+use super::name_serializer::NameSerializer;
+use super::write_nbt::NbtWriter;
+
+/// The tag a list with no elements is written with.
+const EMPTY_LIST_TAG: Tag = Tag::End;
+
+/// What needs to be written before a value's payload. This depends on where
+/// the value is in the NBT.
+pub(crate) enum Header<'h> {
+    /// The top level value, which must be a compound. Network NBT has no root
+    /// name.
+    Root(Option<&'h str>),
+
+    /// A value inside a compound, written as its tag then its name.
+    Named(&'h str),
+
+    /// An element of a list. Elements have no header of their own, but the
+    /// first element decides the element tag of the list.
+    ListElement(&'h mut ListState),
+}
+
+/// Tracks the elements of a list as they are serialized.
+pub(crate) struct ListState {
+    len: usize,
+    index: usize,
+    tag: Option<Tag>,
+}
+
+impl ListState {
+    fn new(len: usize) -> Self {
+        Self {
+            len,
+            index: 0,
+            tag: None,
+        }
+    }
+
+    fn element<W: Write>(&mut self, writer: &mut NbtWriter<W>, tag: Tag) -> Result<()> {
+        if self.index >= self.len {
+            return Err(Error::bespoke(format!(
+                "list was serialized with more than its length of {} elements",
+                self.len
+            )));
+        }
+
+        match self.tag {
+            None => {
+                writer.write_tag(tag)?;
+                writer.write_len(self.len)?;
+                self.tag = Some(tag);
+            }
+            Some(expected) if expected != tag => {
+                return Err(Error::heterogeneous_list(self.index, expected, tag));
+            }
+            Some(_) => {}
+        }
+
+        self.index += 1;
+        Ok(())
+    }
+}
+
+fn out_of_range(v: impl Display, tag: Tag) -> Error {
+    Error::bespoke(format!("{} is out of range for NBT {:?}", v, tag))
+}
+
+fn unrepresentable(what: &str) -> Error {
+    Error::bespoke(format!("cannot serialize {}: NBT has no equivalent", what))
+}
+
+/// Serializes a single value, including whatever header it needs.
+pub(crate) struct ValueSerializer<'a, 'h, W: Write> {
+    writer: &'a mut NbtWriter<W>,
+    header: Header<'h>,
+}
+
+impl<'a, 'h, W: Write> ValueSerializer<'a, 'h, W> {
+    pub(crate) fn new(writer: &'a mut NbtWriter<W>, header: Header<'h>) -> Self {
+        Self { writer, header }
+    }
+
+    fn write_header(&mut self, tag: Tag) -> Result<()> {
+        match &mut self.header {
+            Header::Root(name) => {
+                if tag != Tag::Compound {
+                    return Err(Error::root_must_be_compound());
+                }
+                self.writer.write_tag(tag)?;
+                match *name {
+                    Some(name) => self.writer.write_size_prefixed_str(name),
+                    None => Ok(()),
+                }
+            }
+            Header::Named(name) => {
+                self.writer.write_tag(tag)?;
+                self.writer.write_size_prefixed_str(name)
+            }
+            Header::ListElement(state) => state.element(self.writer, tag),
+        }
+    }
+}
+
+impl<'a, 'h, W: Write> ser::Serializer for ValueSerializer<'a, 'h, W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = ListSerializer<'a, W>;
+    type SerializeTuple = ListSerializer<'a, W>;
+    type SerializeTupleStruct = ListSerializer<'a, W>;
+    type SerializeTupleVariant = ListSerializer<'a, W>;
+    type SerializeMap = CompoundSerializer<'a, W>;
+    type SerializeStruct = CompoundSerializer<'a, W>;
+    type SerializeStructVariant = CompoundSerializer<'a, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.serialize_i8(v as i8)
+    }
+
+    fn serialize_i8(mut self, v: i8) -> Result<()> {
+        self.write_header(Tag::Byte)?;
+        self.writer.write_i8(v)?;
+        Ok(())
+    }
+
+    fn serialize_i16(mut self, v: i16) -> Result<()> {
+        self.write_header(Tag::Short)?;
+        self.writer.write_i16(v)?;
+        Ok(())
+    }
+
+    fn serialize_i32(mut self, v: i32) -> Result<()> {
+        self.write_header(Tag::Int)?;
+        self.writer.write_i32(v)?;
+        Ok(())
+    }
+
+    fn serialize_i64(mut self, v: i64) -> Result<()> {
+        self.write_header(Tag::Long)?;
+        self.writer.write_i64(v)?;
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        let v = i8::try_from(v).map_err(|_| out_of_range(v, Tag::Byte))?;
+        self.serialize_i8(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        let v = i16::try_from(v).map_err(|_| out_of_range(v, Tag::Short))?;
+        self.serialize_i16(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        let v = i32::try_from(v).map_err(|_| out_of_range(v, Tag::Int))?;
+        self.serialize_i32(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        let v = i64::try_from(v).map_err(|_| out_of_range(v, Tag::Long))?;
+        self.serialize_i64(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        self.serialize_u128(v as u128)
+    }
+
+    fn serialize_u128(mut self, v: u128) -> Result<()> {
+        self.write_header(Tag::IntArray)?;
+        self.writer.write_len(4)?;
+        for int in &u128_to_int_array(v) {
+            self.writer.write_i32(*int)?;
+        }
+        Ok(())
+    }
+
+    fn serialize_f32(mut self, v: f32) -> Result<()> {
+        self.write_header(Tag::Float)?;
+        self.writer.write_f32(v)?;
+        Ok(())
+    }
+
+    fn serialize_f64(mut self, v: f64) -> Result<()> {
+        self.write_header(Tag::Double)?;
+        self.writer.write_f64(v)?;
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(mut self, v: &str) -> Result<()> {
+        self.write_header(Tag::String)?;
+        self.writer.write_size_prefixed_str(v)
+    }
+
+    fn serialize_bytes(mut self, v: &[u8]) -> Result<()> {
+        self.write_header(Tag::ByteArray)?;
+        self.writer.write_len(v.len())?;
+        self.writer.write_all(v)?;
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        // NBT has no null. A missing key is how a compound says None, so we
+        // just skip the field. Lists can't have holes though.
+        match self.header {
+            Header::Root(_) => Err(Error::root_must_be_compound()),
+            Header::Named(_) => Ok(()),
+            Header::ListElement(state) => Err(Error::bespoke(format!(
+                "list element {} is None: NBT lists cannot have missing elements",
+                state.index
+            ))),
+        }
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Err(unrepresentable("()"))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<()> {
+        Err(unrepresentable(&format!("unit struct {}", name)))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        let tag = match name {
+            BYTE_ARRAY_TOKEN => Tag::ByteArray,
+            INT_ARRAY_TOKEN => Tag::IntArray,
+            LONG_ARRAY_TOKEN => Tag::LongArray,
+            _ => return value.serialize(self),
+        };
+
+        self.write_header(tag)?;
+        let writer = self.writer;
+        crate::borrow::offer_be_bytes(|| value.serialize(ArraySerializer::new(writer, tag)))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        mut self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.write_header(Tag::Compound)?;
+        value.serialize(ValueSerializer::new(
+            &mut *self.writer,
+            Header::Named(variant),
+        ))?;
+        self.writer.write_tag(Tag::End)
+    }
+
+    fn serialize_seq(mut self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        let len = len.ok_or_else(|| {
+            Error::bespoke("cannot serialize a sequence of unknown length to NBT".to_owned())
+        })?;
+        self.write_header(Tag::List)?;
+        Ok(ListSerializer::new(self.writer, len, false))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        mut self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        self.write_header(Tag::Compound)?;
+        self.writer.write_tag(Tag::List)?;
+        self.writer.write_size_prefixed_str(variant)?;
+        Ok(ListSerializer::new(self.writer, len, true))
+    }
+
+    fn serialize_map(mut self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        self.write_header(Tag::Compound)?;
+        Ok(CompoundSerializer::new(self.writer, false))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        mut self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        self.write_header(Tag::Compound)?;
+        self.writer.write_tag(Tag::Compound)?;
+        self.writer.write_size_prefixed_str(variant)?;
+        Ok(CompoundSerializer::new(self.writer, true))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.writer.human_readable
+    }
+}
+
+/// Serializes the elements of an NBT list.
+pub(crate) struct ListSerializer<'a, W: Write> {
+    writer: &'a mut NbtWriter<W>,
+    state: ListState,
+
+    /// Whether the list is wrapped in a compound for an enum variant.
+    in_variant: bool,
+}
+
+impl<'a, W: Write> ListSerializer<'a, W> {
+    fn new(writer: &'a mut NbtWriter<W>, len: usize, in_variant: bool) -> Self {
+        Self {
+            writer,
+            state: ListState::new(len),
+            in_variant,
+        }
+    }
+
+    fn element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        value.serialize(ValueSerializer::new(
+            &mut *self.writer,
+            Header::ListElement(&mut self.state),
+        ))
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.state.index != self.state.len {
+            return Err(Error::bespoke(format!(
+                "list was serialized with {} elements but a length of {}",
+                self.state.index, self.state.len
+            )));
+        }
+
+        if self.state.tag.is_none() {
+            self.writer.write_tag(EMPTY_LIST_TAG)?;
+            self.writer.write_len(0)?;
+        }
+
+        if self.in_variant {
+            self.writer.write_tag(Tag::End)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> ser::SerializeSeq for ListSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W: Write> ser::SerializeTuple for ListSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W: Write> ser::SerializeTupleStruct for ListSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W: Write> ser::SerializeTupleVariant for ListSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+/// Serializes the fields of an NBT compound.
+pub struct CompoundSerializer<'a, W: Write> {
+    writer: &'a mut NbtWriter<W>,
+    key: Option<String>,
+
+    /// Whether the compound is wrapped in another compound for an enum
+    /// variant.
+    in_variant: bool,
+}
+
+impl<'a, W: Write> CompoundSerializer<'a, W> {
+    fn new(writer: &'a mut NbtWriter<W>, in_variant: bool) -> Self {
+        Self {
+            writer,
+            key: None,
+            in_variant,
+        }
+    }
+
+    fn field<T: ?Sized + Serialize>(&mut self, name: &str, value: &T) -> Result<()> {
+        value.serialize(ValueSerializer::new(&mut *self.writer, Header::Named(name)))
+    }
+
+    fn finish(self) -> Result<()> {
+        self.writer.write_tag(Tag::End)?;
+        if self.in_variant {
+            self.writer.write_tag(Tag::End)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> ser::SerializeMap for CompoundSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(NameSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let name = self.key.take().ok_or_else(|| {
+            Error::bespoke("serialize_value called before serialize_key".to_owned())
+        })?;
+        self.field(&name, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W: Write> ser::SerializeStruct for CompoundSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W: Write> ser::SerializeStructVariant for CompoundSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
@@ -0,0 +1,156 @@
+use std::convert::{TryFrom, TryInto};
+use std::io::Write;
+
+use byteorder::{BigEndian, LittleEndian, WriteBytesExt};
+
+use crate::error::{Error, Result};
+use crate::{Endian, Tag};
+
+/// Writer for the building blocks of NBT, writing numbers in the given byte
+/// order.
+pub(crate) struct NbtWriter<W: Write> {
+    inner: W,
+    endian: Endian,
+    /// What serializers writing to this answer to `is_human_readable`.
+    pub(crate) human_readable: bool,
+}
+
+/// Write a number in the writer's byte order. These are fixed size in every
+/// format, see [`NbtWriter::write_i32`] for those that are not.
+macro_rules! write_num {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            pub(crate) fn $method(&mut self, v: $ty) -> Result<()> {
+                match self.endian {
+                    Endian::Big => self.inner.$method::<BigEndian>(v)?,
+                    Endian::Little | Endian::VarInt => self.inner.$method::<LittleEndian>(v)?,
+                }
+                Ok(())
+            }
+        )*
+    };
+}
+
+impl<W: Write> NbtWriter<W> {
+    pub(crate) fn new(inner: W, endian: Endian) -> Self {
+        Self {
+            inner,
+            endian,
+            human_readable: false,
+        }
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.inner
+    }
+
+    write_num! {
+        write_i16(i16),
+        write_u16(u16),
+        write_f32(f32),
+        write_f64(f64),
+    }
+
+    /// Write an int, which Bedrock network NBT writes as a ZigZag VarInt.
+    pub(crate) fn write_i32(&mut self, v: i32) -> Result<()> {
+        match self.endian {
+            Endian::Big => self.inner.write_i32::<BigEndian>(v)?,
+            Endian::Little => self.inner.write_i32::<LittleEndian>(v)?,
+            Endian::VarInt => self.write_var_u64(zigzag_encode(v.into()))?,
+        }
+        Ok(())
+    }
+
+    /// Write a long, which Bedrock network NBT writes as a ZigZag VarLong.
+    pub(crate) fn write_i64(&mut self, v: i64) -> Result<()> {
+        match self.endian {
+            Endian::Big => self.inner.write_i64::<BigEndian>(v)?,
+            Endian::Little => self.inner.write_i64::<LittleEndian>(v)?,
+            Endian::VarInt => self.write_var_u64(zigzag_encode(v))?,
+        }
+        Ok(())
+    }
+
+    /// Write an unsigned VarInt, 7 bits at a time.
+    fn write_var_u64(&mut self, mut v: u64) -> Result<()> {
+        while v >= 0x80 {
+            self.inner.write_u8(v as u8 | 0x80)?;
+            v >>= 7;
+        }
+        self.inner.write_u8(v as u8)?;
+        Ok(())
+    }
+
+    pub(crate) fn write_i8(&mut self, v: i8) -> Result<()> {
+        self.inner.write_i8(v)?;
+        Ok(())
+    }
+
+    pub(crate) fn write_all(&mut self, data: &[u8]) -> Result<()> {
+        self.inner.write_all(data)?;
+        Ok(())
+    }
+
+    /// Write array elements of `size` bytes given in big-endian order. The
+    /// data is written as is unless the output needs another byte order.
+    pub(crate) fn write_be_elements(&mut self, data: &[u8], size: usize) -> Result<()> {
+        match (self.endian, size) {
+            (Endian::Big, _) | (_, 1) => self.write_all(data),
+            (_, 4) => data
+                .chunks_exact(4)
+                .try_for_each(|c| self.write_i32(i32::from_be_bytes(c.try_into().unwrap()))),
+            _ => data
+                .chunks_exact(8)
+                .try_for_each(|c| self.write_i64(i64::from_be_bytes(c.try_into().unwrap()))),
+        }
+    }
+
+    pub(crate) fn write_tag(&mut self, tag: Tag) -> Result<()> {
+        self.inner.write_u8(tag.into())?;
+        Ok(())
+    }
+
+    /// Write the length of a list or array.
+    pub(crate) fn write_len(&mut self, len: usize) -> Result<()> {
+        let len = i32::try_from(len).map_err(|_| {
+            Error::bespoke(format!(
+                "length {} is too large for an NBT list or array",
+                len
+            ))
+        })?;
+        self.write_i32(len)
+    }
+
+    /// Write a string prefixed with its length. Java uses CESU-8, Bedrock
+    /// uses UTF-8.
+    pub(crate) fn write_size_prefixed_str(&mut self, s: &str) -> Result<()> {
+        let data = match self.endian {
+            Endian::Big => crate::java_cesu8::encode(s),
+            Endian::Little | Endian::VarInt => s.as_bytes().into(),
+        };
+        // Bedrock network NBT has a VarInt length rather than a u16.
+        let max_len = match self.endian {
+            Endian::VarInt => u32::MAX as usize,
+            _ => u16::MAX as usize,
+        };
+        if data.len() > max_len {
+            return Err(Error::bespoke(format!(
+                "string of {} bytes is too long for NBT, the maximum is {}",
+                data.len(),
+                max_len
+            )));
+        }
+
+        match self.endian {
+            Endian::VarInt => self.write_var_u64(data.len() as u64)?,
+            _ => self.write_u16(data.len() as u16)?,
+        }
+        self.write_all(&data)
+    }
+}
+
+/// ZigZag encode a signed number for a VarInt, so that small negative numbers
+/// stay short.
+fn zigzag_encode(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
@@ -1,8 +1,16 @@
 //! Allows streaming of NBT data without prior knowledge of the structure.
+//!
+//! [`Parser`] returns a [`Value`] for each tag in turn, like a pull parser,
+//! for tools that only need to scan the structure. Uninteresting compounds and
+//! lists can be passed over with [`Parser::skip_value`].
 
 use super::Tag;
 use byteorder::{BigEndian, ReadBytesExt};
-use std::{convert::TryFrom, io::Read, str};
+use std::{
+    convert::{TryFrom, TryInto},
+    io::{self, Read},
+    str,
+};
 
 /// An optional `String`.
 pub type Name = Option<String>;
@@ -96,6 +104,13 @@ impl Error {
         }
     }
 
+    fn unexpected_eof() -> Self {
+        Self {
+            msg: "unexpected EOF".into(),
+            kind: ErrorKind::UnexpectedEof,
+        }
+    }
+
     fn eof() -> Self {
         Self {
             msg: "EOF".into(),
@@ -209,28 +224,187 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// # }
 /// ```
 pub struct Parser<R: Read> {
-    reader: R,
+    reader: Counted<R>,
     layers: Vec<Layer>,
+    array_offset: Option<u64>,
+}
+
+/// Counts the bytes read through it, so the parser knows where it is.
+struct Counted<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for Counted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
 }
 
 impl<R: Read> Parser<R> {
     /// Create new parser for the given reader.
     pub fn new(reader: R) -> Self {
         Self {
-            reader,
+            reader: Counted {
+                inner: reader,
+                count: 0,
+            },
             layers: Vec::new(),
+            array_offset: None,
         }
     }
 
+    /// Get a reference to the underlying reader. For a byte slice this is the
+    /// input that has not been parsed yet.
+    pub fn get_ref(&self) -> &R {
+        &self.reader.inner
+    }
+
+    /// The number of bytes of input read so far.
+    pub fn position(&self) -> u64 {
+        self.reader.count
+    }
+
+    /// Where the elements of an array start in the input, if the value last
+    /// returned by [`next`][`Parser::next`] was an array. This is just after
+    /// the array's length, so element `i` of a [`Value::LongArray`] starts at
+    /// `offset + i * 8`, which locates a bad value in the original data.
+    ///
+    /// ```
+    /// # use fastnbt::stream::{Parser, Value};
+    /// # use fastnbt::LongArray;
+    /// # use std::collections::HashMap;
+    /// # let data = fastnbt::to_bytes(&HashMap::from([("BlockStates", LongArray::new(vec![1, 2]))])).unwrap();
+    /// let mut parser = Parser::new(data.as_slice());
+    /// parser.next()?;
+    /// assert!(matches!(parser.next()?, Value::LongArray(..)));
+    ///
+    /// let offset = parser.array_offset().unwrap() as usize;
+    /// assert_eq!(2i64.to_be_bytes(), data[offset + 8..offset + 16]);
+    /// # Ok::<(), fastnbt::stream::Error>(())
+    /// ```
+    pub fn array_offset(&self) -> Option<u64> {
+        self.array_offset
+    }
+
     /// Parse the next value from the input.
     #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Result<Value> {
         self.next_inner()
     }
 
+    /// Skip the rest of the compound or list the parser is currently inside,
+    /// including its end. Called straight after a [`Value::Compound`] or
+    /// [`Value::List`], this skips that whole value, and the next value is the
+    /// one after it. No [`Value::CompoundEnd`] or [`Value::ListEnd`] is
+    /// returned for what was skipped.
+    ///
+    /// This reads past the contents without decoding them, which is cheaper
+    /// than calling [`next`][`Parser::next`] for each, since strings and
+    /// arrays are never allocated.
+    ///
+    /// ```
+    /// # use fastnbt::stream::{Parser, Value};
+    /// # use std::collections::HashMap;
+    /// # let data = fastnbt::to_bytes(&HashMap::from([("Sections", vec![1, 2, 3])])).unwrap();
+    /// let mut parser = Parser::new(data.as_slice());
+    /// assert_eq!(Value::Compound(Some("".to_owned())), parser.next()?);
+    ///
+    /// // Don't care about the list of sections.
+    /// assert!(matches!(parser.next()?, Value::List(..)));
+    /// parser.skip_value()?;
+    ///
+    /// assert_eq!(Value::CompoundEnd, parser.next()?);
+    /// # Ok::<(), fastnbt::stream::Error>(())
+    /// ```
+    pub fn skip_value(&mut self) -> Result<()> {
+        self.array_offset = None;
+        let layer = self
+            .layers
+            .pop()
+            .ok_or_else(|| Error::bespoke("not inside a compound or list to skip"))?;
+
+        // Keep our own stack of what is being skipped, rather than recursing,
+        // so that deeply nested input cannot overflow the stack.
+        let mut stack = vec![layer];
+        while let Some(layer) = stack.last_mut() {
+            let tag = match layer {
+                Layer::List(_, remaining) if *remaining <= 0 => {
+                    stack.pop();
+                    continue;
+                }
+                Layer::List(tag, remaining) => {
+                    *remaining -= 1;
+                    *tag
+                }
+                Layer::Compound => {
+                    let tag = u8_to_tag(self.reader.read_u8()?)?;
+                    if tag == Tag::End {
+                        stack.pop();
+                        continue;
+                    }
+                    let name_len = self.reader.read_u16::<BigEndian>()?;
+                    self.skip_bytes(name_len.into())?;
+                    tag
+                }
+            };
+
+            match tag {
+                Tag::Compound => stack.push(Layer::Compound),
+                Tag::List => {
+                    let element_tag = u8_to_tag(self.reader.read_u8()?)?;
+                    let size = self.reader.read_i32::<BigEndian>()?;
+                    stack.push(Layer::List(element_tag, size));
+                }
+                _ => self.skip_scalar(tag)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Skip the payload of anything other than a compound or list.
+    fn skip_scalar(&mut self, tag: Tag) -> Result<()> {
+        let len = match tag {
+            Tag::Byte => 1,
+            Tag::Short => 2,
+            Tag::Int | Tag::Float => 4,
+            Tag::Long | Tag::Double => 8,
+            Tag::String => self.reader.read_u16::<BigEndian>()?.into(),
+            Tag::ByteArray | Tag::IntArray | Tag::LongArray => {
+                let size = self.reader.read_i32::<BigEndian>()?;
+                let element_len = match tag {
+                    Tag::ByteArray => 1,
+                    Tag::IntArray => 4,
+                    _ => 8,
+                };
+                u64::try_from(size)
+                    .map_err(|_| Error::bespoke(format!("invalid array size: {}", size)))?
+                    * element_len
+            }
+            // Only elements of a list could have no tag, and they have no
+            // payload to skip past.
+            Tag::End => return Err(Error::bespoke("unexpected list of End with elements")),
+            Tag::Compound | Tag::List => unreachable!(),
+        };
+
+        self.skip_bytes(len)
+    }
+
+    fn skip_bytes(&mut self, len: u64) -> Result<()> {
+        let skipped = io::copy(&mut (&mut self.reader).take(len), &mut io::sink())?;
+        if skipped != len {
+            return Err(Error::unexpected_eof());
+        }
+        Ok(())
+    }
+
     /// Get the next value from the reader. Returns EOF if the stream ended sucessfully, and
     /// IO(err) for any other IO error.
     fn next_inner(&mut self) -> Result<Value> {
+        self.array_offset = None;
         let last_layer = self.layers.last().map(|l| (*l).clone());
         match last_layer {
             Some(Layer::List(_, 0)) => {
@@ -292,11 +466,30 @@ impl<R: Read> Parser<R> {
         let mut buf = vec![0; name_len];
         self.reader.read_exact(&mut buf[..])?;
 
-        Ok(cesu8::from_java_cesu8(&buf[..])
-            .map_err(|_| Error::nonunicode(Vec::from(&buf[..])))?
+        Ok(crate::java_cesu8::decode(&buf[..])
+            .ok_or_else(|| Error::nonunicode(Vec::from(&buf[..])))?
             .into_owned())
     }
 
+    /// Read the size of an array, then its data of `element_len` bytes for each
+    /// element. The buffer grows as the data arrives rather than being
+    /// allocated for the size up front, as a corrupt size could be up to 16 GiB
+    /// for a long array.
+    fn read_array(&mut self, element_len: u64) -> Result<Vec<u8>> {
+        let size = self.reader.read_i32::<BigEndian>()?;
+        let len = u64::try_from(size)
+            .map_err(|_| Error::bespoke(format!("invalid array size: {}", size)))?
+            * element_len;
+        self.array_offset = Some(self.reader.count);
+
+        let mut buf = Vec::new();
+        let read = (&mut self.reader).take(len).read_to_end(&mut buf)?;
+        if read as u64 != len {
+            return Err(Error::unexpected_eof());
+        }
+        Ok(buf)
+    }
+
     fn read_payload(&mut self, tag: Tag, name: Name) -> Result<Value> {
         match tag {
             Tag::Byte => Ok(Value::Byte(name, self.reader.read_i8()?)),
@@ -319,28 +512,24 @@ impl<R: Read> Parser<R> {
             }
             Tag::String => Ok(Value::String(name, self.read_size_prefixed_string()?)),
             Tag::ByteArray => {
-                let size = self.reader.read_i32::<BigEndian>()?;
-                let mut buf = vec![0u8; size as usize];
-                self.reader.read_exact(&mut buf[..])?;
+                let buf = self.read_array(1)?;
                 Ok(Value::ByteArray(name, vec_u8_into_i8(buf)))
             }
             Tag::IntArray => {
-                let size = self.reader.read_i32::<BigEndian>()?;
-                let mut buf = vec![0i32; size as usize];
-                for i in 0..size {
-                    buf[i as usize] = self.reader.read_i32::<BigEndian>()?;
-                }
-
-                Ok(Value::IntArray(name, buf))
+                let buf = self.read_array(4)?;
+                let ints = buf
+                    .chunks_exact(4)
+                    .map(|c| i32::from_be_bytes(c.try_into().unwrap()))
+                    .collect();
+                Ok(Value::IntArray(name, ints))
             }
             Tag::LongArray => {
-                let size = self.reader.read_i32::<BigEndian>()?;
-                let mut buf = vec![0i64; size as usize];
-                for i in 0..size {
-                    buf[i as usize] = self.reader.read_i64::<BigEndian>()?;
-                }
-
-                Ok(Value::LongArray(name, buf))
+                let buf = self.read_array(8)?;
+                let longs = buf
+                    .chunks_exact(8)
+                    .map(|c| i64::from_be_bytes(c.try_into().unwrap()))
+                    .collect();
+                Ok(Value::LongArray(name, longs))
             }
         }
     }
@@ -0,0 +1,211 @@
+//! Counts heap allocations made while deserializing into borrowed types, and
+//! the bytes still allocated afterwards. The counting allocator is installed
+//! for the whole test binary, but counts are kept per thread so that tests
+//! running in parallel do not interfere.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use serde::Deserialize;
+
+use crate::{borrow, de::from_bytes};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static LIVE_BYTES: Cell<isize> = const { Cell::new(0) };
+}
+
+fn add_live_bytes(n: isize) {
+    let _ = LIVE_BYTES.try_with(|c| c.set(c.get() + n));
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // try_with: the thread local may already be destroyed at thread exit.
+        let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+        add_live_bytes(layout.size() as isize);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        add_live_bytes(-(layout.size() as isize));
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+        add_live_bytes(new_size as isize - layout.size() as isize);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(|c| c.get());
+    let res = f();
+    let after = ALLOCATIONS.with(|c| c.get());
+    (res, after - before)
+}
+
+/// The bytes allocated by `f` and not freed by the time it returns.
+fn measure_live_bytes<T>(f: impl FnOnce() -> T) -> (T, isize) {
+    let before = LIVE_BYTES.with(|c| c.get());
+    let res = f();
+    let after = LIVE_BYTES.with(|c| c.get());
+    (res, after - before)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Chunk<'a> {
+    #[serde(borrow)]
+    level: Level<'a>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Level<'a> {
+    #[serde(rename = "xPos")]
+    x_pos: i32,
+    status: &'a str,
+    #[serde(borrow)]
+    biomes: Option<borrow::IntArray<'a>>,
+    #[serde(borrow)]
+    sections: Vec<Section<'a>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Section<'a> {
+    y: i8,
+    #[serde(borrow)]
+    block_states: Option<borrow::LongArray<'a>>,
+    #[serde(borrow)]
+    palette: Option<Vec<Block<'a>>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Block<'a> {
+    name: &'a str,
+}
+
+#[test]
+fn borrowed_chunk_allocates_only_vecs() {
+    let (chunk, allocations) = count_allocations(|| from_bytes::<Chunk>(CHUNK_RAW).unwrap());
+
+    // The deserializer's layer stack, the Vec<Section> backbone, plus one
+    // exactly sized palette per section that has one. Everything else,
+    // including unknown fields we skip over, must borrow from the input.
+    let palettes = chunk
+        .level
+        .sections
+        .iter()
+        .filter(|s| s.palette.is_some())
+        .count();
+    let expected = 2 + palettes;
+
+    assert!(
+        allocations <= expected,
+        "expected at most {} allocations, got {}",
+        expected,
+        allocations
+    );
+
+    // Make sure the data was actually parsed.
+    assert_eq!(16, chunk.level.x_pos);
+    assert_eq!("full", chunk.level.status);
+    assert_eq!(1024, chunk.level.biomes.unwrap().iter().count());
+    let section = chunk.level.sections.iter().find(|s| s.y == 0).unwrap();
+    assert_eq!(342, section.block_states.unwrap().iter().count());
+    assert_eq!("minecraft:air", section.palette.as_ref().unwrap()[0].name);
+}
+
+#[test]
+fn vec_of_sections_is_exactly_sized() {
+    let (chunk, _) = count_allocations(|| from_bytes::<Chunk>(CHUNK_RAW).unwrap());
+    let sections = &chunk.level.sections;
+    assert_eq!(sections.len(), sections.capacity());
+
+    for palette in sections.iter().filter_map(|s| s.palette.as_ref()) {
+        assert_eq!(palette.len(), palette.capacity());
+    }
+}
+
+#[test]
+fn borrowed_value_allocates_less_than_value() {
+    let (owned, owned_allocations) =
+        count_allocations(|| from_bytes::<crate::Value>(CHUNK_RAW).unwrap());
+    let (borrowed, borrowed_allocations) =
+        count_allocations(|| from_bytes::<borrow::Value>(CHUNK_RAW).unwrap());
+
+    println!(
+        "Value: {} allocations, borrow::Value: {} allocations",
+        owned_allocations, borrowed_allocations
+    );
+    assert!(
+        borrowed_allocations * 2 < owned_allocations,
+        "expected borrow::Value to make under half the {} allocations of Value, got {}",
+        owned_allocations,
+        borrowed_allocations
+    );
+
+    assert_eq!(owned, crate::Value::from(borrowed));
+}
+
+#[test]
+fn value_estimated_size_is_close_to_allocated() {
+    let (value, live) = measure_live_bytes(|| from_bytes::<crate::Value>(CHUNK_RAW).unwrap());
+    let heap = (value.estimated_size() - std::mem::size_of::<crate::Value>()) as isize;
+
+    println!("estimated {} bytes, allocated {} bytes", heap, live);
+    assert!(
+        heap * 2 > live && heap < live * 2,
+        "estimated {} bytes, but {} were allocated",
+        heap,
+        live
+    );
+
+    let sections = &value["Level"]["Sections"];
+    assert!(sections.estimated_size() < value.estimated_size());
+}
+
+#[test]
+fn shrink_to_fit_releases_spare_capacity() {
+    let mut name = String::with_capacity(64);
+    name.push_str("minecraft:chest");
+    let mut items = Vec::with_capacity(64);
+    items.push(crate::Value::String(name.clone()));
+    let mut value: crate::Value = vec![
+        (name, crate::Value::List(items)),
+        ("n".to_owned(), crate::Value::from(vec![1i64; 8])),
+    ]
+    .into_iter()
+    .collect();
+    let expected = value.clone();
+
+    let before = value.estimated_size();
+    let ((), freed) = measure_live_bytes(|| value.shrink_to_fit());
+    println!("shrinking freed {} bytes", -freed);
+
+    assert_eq!(expected, value);
+    assert!(freed < 0, "shrinking allocated {} bytes", freed);
+    assert!(value.estimated_size() < before);
+}
+
+#[test]
+fn shrink_to_fit_chunk() {
+    let mut value: crate::Value = from_bytes(CHUNK_RAW).unwrap();
+    let expected = value.clone();
+    let ((), freed) = measure_live_bytes(|| value.shrink_to_fit());
+    println!("shrinking a chunk freed {} bytes", -freed);
+
+    assert_eq!(expected, value);
+    assert!(freed <= 0, "shrinking allocated {} bytes", freed);
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{de::from_bytes, to_bytes, ByteArray, IntArray, LongArray, Value};
+
+use super::builder::Builder;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct Arrays {
+    bytes: ByteArray,
+    ints: IntArray,
+    longs: LongArray,
+}
+
+fn payload() -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .byte_array("bytes", &[1, -2, 3])
+        .int_array("ints", &[i32::MIN, 0, i32::MAX])
+        .long_array("longs", &[-1, 1 << 40])
+        .end_compound()
+        .build()
+}
+
+#[test]
+fn constructors_match_deserialized() {
+    let deserialized: Arrays = from_bytes(&payload()).unwrap();
+
+    let from_vecs = Arrays {
+        bytes: ByteArray::from(vec![1, -2, 3]),
+        ints: vec![i32::MIN, 0, i32::MAX].into(),
+        longs: LongArray::new(vec![-1, 1 << 40]),
+    };
+    let collected = Arrays {
+        bytes: [1i8, -2, 3].iter().copied().collect(),
+        ints: deserialized.ints.iter().copied().collect(),
+        longs: vec![-1, 1 << 40].into_iter().collect(),
+    };
+
+    assert_eq!(deserialized, from_vecs);
+    assert_eq!(deserialized, collected);
+    assert_eq!(payload(), to_bytes(&from_vecs).unwrap());
+    assert_eq!(payload(), to_bytes(&collected).unwrap());
+}
+
+#[test]
+fn values_match_deserialized() {
+    let value: Value = from_bytes(&payload()).unwrap();
+    assert_eq!(Value::ByteArray(vec![1, -2, 3].into()), value["bytes"]);
+    assert_eq!(
+        Value::IntArray(vec![i32::MIN, 0, i32::MAX].into()),
+        value["ints"]
+    );
+    assert_eq!(
+        Value::LongArray([-1, 1 << 40].iter().copied().collect()),
+        value["longs"]
+    );
+    let bytes = to_bytes(&value).unwrap();
+    assert_eq!(value, from_bytes::<Value>(&bytes).unwrap());
+}
+
+#[test]
+fn slices_and_indexing() {
+    let arrays: Arrays = from_bytes(&payload()).unwrap();
+
+    assert_eq!(3, arrays.bytes.len());
+    assert!(!arrays.bytes.is_empty());
+    assert!(ByteArray::new(vec![]).is_empty());
+
+    assert_eq!(-2, arrays.bytes[1]);
+    assert_eq!(&[0, i32::MAX], &arrays.ints[1..]);
+    assert_eq!(Some(&(1 << 40)), arrays.longs.last());
+    assert_eq!(&[-1, 1 << 40][..], arrays.longs.as_slice());
+
+    // Deref to a slice gives the slice methods.
+    let ints: &[i32] = &arrays.ints;
+    assert_eq!(3, ints.len());
+    assert!(arrays.ints.contains(&0));
+}
+
+#[test]
+fn iteration() {
+    let arrays: Arrays = from_bytes(&payload()).unwrap();
+
+    let mut sum = 0i64;
+    for &l in &arrays.longs {
+        sum += l;
+    }
+    assert_eq!((1 << 40) - 1, sum);
+
+    let bytes: Vec<i8> = arrays.bytes.clone().into_iter().rev().collect();
+    assert_eq!(vec![3, -2, 1], bytes);
+
+    assert_eq!(vec![i32::MIN, 0, i32::MAX], arrays.ints.into_inner());
+    assert_eq!(vec![1, -2, 3], Vec::from(arrays.bytes));
+}
+
+#[test]
+fn unpack_bits() {
+    // 5 bit entries packed without padding, so some straddle two longs.
+    let entries: Vec<u16> = (0..100).map(|i| (i * 7 % 32) as u16).collect();
+    let mut longs = vec![0u64; (entries.len() * 5).div_ceil(64)];
+    for (i, entry) in entries.iter().enumerate() {
+        let bit = i * 5;
+        longs[bit / 64] |= (*entry as u64) << (bit % 64);
+        if bit % 64 > 59 {
+            longs[bit / 64 + 1] |= (*entry as u64) >> (64 - bit % 64);
+        }
+    }
+    let longs = LongArray::new(longs.into_iter().map(|l| l as i64).collect());
+
+    let mut out = [0; 100];
+    longs.unpack_into(5, false, &mut out).unwrap();
+    assert_eq!(entries[..], out[..]);
+
+    // The same longs padded give 12 entries per long instead.
+    longs.unpack_into(5, true, &mut out[..96]).unwrap();
+    assert_eq!(entries[..12], out[..12]);
+    assert_eq!(longs[1] as u16 & 31, out[12]);
+
+    // Too few longs, or bits we cannot unpack.
+    assert!(longs.unpack_into(5, true, &mut out).is_err());
+    assert!(longs.unpack_into(5, false, &mut [0; 103]).is_err());
+    assert!(longs.unpack_into(0, false, &mut out).is_err());
+    assert!(longs.unpack_into(17, false, &mut out).is_err());
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+
+use crate::{
+    de::{from_async_reader, from_bytes, DeOpts},
+    error::ErrorKind,
+    to_bytes, LongArray, Tag, Value,
+};
+
+use super::{builder::Builder, with_stack};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+#[derive(Deserialize, Debug)]
+struct Chunk {
+    #[serde(rename = "Level")]
+    level: Level,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Level {
+    status: String,
+    sections: Vec<Section>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Section {
+    block_states: Option<LongArray>,
+}
+
+#[tokio::test]
+async fn chunk_through_buf_reader() {
+    let read: Value = from_async_reader(BufReader::new(CHUNK_RAW)).await.unwrap();
+    assert_eq!(from_bytes::<Value>(CHUNK_RAW).unwrap(), read);
+
+    let chunk: Chunk = from_async_reader(BufReader::new(CHUNK_RAW)).await.unwrap();
+    assert_eq!("full", chunk.level.status);
+    assert!(chunk
+        .level
+        .sections
+        .iter()
+        .any(|s| s.block_states.is_some()));
+}
+
+#[tokio::test]
+async fn bytes_arriving_a_few_at_a_time() {
+    let (mut tx, rx) = tokio::io::duplex(7);
+    let write = async move {
+        for part in CHUNK_RAW.chunks(100) {
+            tx.write_all(part).await.unwrap();
+            tokio::task::yield_now().await;
+        }
+    };
+
+    let (_, read) = tokio::join!(write, from_async_reader::<_, Value>(rx));
+    assert_eq!(from_bytes::<Value>(CHUNK_RAW).unwrap(), read.unwrap());
+}
+
+#[tokio::test]
+async fn stops_at_end_of_document() {
+    let first = to_bytes(&HashMap::from([("a", 1)])).unwrap();
+    let second = to_bytes(&HashMap::from([("b", 2)])).unwrap();
+    let input = [first, second, b"after".to_vec()].concat();
+    let mut reader = input.as_slice();
+
+    let v: HashMap<String, i32> = from_async_reader(&mut reader).await.unwrap();
+    assert_eq!(1, v["a"]);
+    let v: HashMap<String, i32> = from_async_reader(&mut reader).await.unwrap();
+    assert_eq!(2, v["b"]);
+
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).await.unwrap();
+    assert_eq!(b"after", rest.as_slice());
+}
+
+#[tokio::test]
+async fn every_tag() {
+    let data = Builder::new()
+        .start_compound("")
+        .byte("b", 1)
+        .short("s", 2)
+        .int("i", 3)
+        .long("l", 4)
+        .float("f", 5.0)
+        .double("d", 6.0)
+        .string("str", "seven")
+        .byte_array("ba", &[1, 2])
+        .int_array("ia", &[1, 2, 3])
+        .long_array("la", &[1])
+        .start_list("list", Tag::Compound, 2)
+        .start_list("inner", Tag::Short, 1)
+        .short_payload(1)
+        .end_compound()
+        .end_compound()
+        .start_list("empty", Tag::End, 0)
+        .end_compound()
+        .build();
+
+    let read: Value = from_async_reader(data.as_slice()).await.unwrap();
+    assert_eq!(from_bytes::<Value>(&data).unwrap(), read);
+}
+
+#[tokio::test]
+async fn errors_match_the_deserializer() {
+    for len in [0, 1, 10, CHUNK_RAW.len() / 2, CHUNK_RAW.len() - 1] {
+        let err = from_async_reader::<_, Value>(&CHUNK_RAW[..len])
+            .await
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnexpectedEof), "{}", len);
+        assert_eq!(
+            from_bytes::<Value>(&CHUNK_RAW[..len]).unwrap_err().offset(),
+            err.offset()
+        );
+    }
+
+    let data = Builder::new()
+        .start_compound("")
+        .start_compound("Level")
+        .raw_bytes(&[20])
+        .end_compound()
+        .end_compound()
+        .build();
+    let err = from_async_reader::<_, Value>(data.as_slice())
+        .await
+        .unwrap_err();
+    assert_eq!(&ErrorKind::InvalidTag(20), err.kind());
+    assert_eq!(Some("Level"), err.path());
+
+    // A long array claiming i32::MAX elements, about 16 GiB, with no data.
+    let data = [10, 0, 0, 12, 0, 1, b'a', 0x7f, 0xff, 0xff, 0xff];
+    let err = from_async_reader::<_, Value>(&data[..]).await.unwrap_err();
+    assert_eq!(from_bytes::<Value>(&data).unwrap_err().kind(), err.kind());
+}
+
+#[test]
+fn too_deep() {
+    let mut b = Builder::new().start_compound("");
+    for _ in 0..DeOpts::DEFAULT_MAX_DEPTH {
+        b = b.start_compound("a");
+    }
+    for _ in 0..=DeOpts::DEFAULT_MAX_DEPTH {
+        b = b.end_compound();
+    }
+    let data = b.build();
+
+    with_stack(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let err = runtime
+            .block_on(from_async_reader::<_, Value>(data.as_slice()))
+            .unwrap_err();
+        assert_eq!(&ErrorKind::DepthLimitExceeded, err.kind());
+    });
+}
+
+#[tokio::test]
+async fn cancelled_part_way_through() {
+    let (mut tx, mut rx) = tokio::io::duplex(CHUNK_RAW.len());
+    let (start, end) = CHUNK_RAW.split_at(CHUNK_RAW.len() / 2);
+    tx.write_all(start).await.unwrap();
+
+    // The rest has not arrived, so the read is still waiting when it is
+    // cancelled. Dropping it part way must be fine.
+    let read = from_async_reader::<_, Value>(&mut rx);
+    let timed_out = tokio::time::timeout(Duration::from_millis(10), read).await;
+    assert!(timed_out.is_err());
+
+    // What was read is gone, the rest is left in the reader.
+    tx.write_all(end).await.unwrap();
+    drop(tx);
+    let mut rest = Vec::new();
+    rx.read_to_end(&mut rest).await.unwrap();
+    assert!(rest.len() < CHUNK_RAW.len());
+    assert!(CHUNK_RAW.ends_with(&rest));
+
+    // Other reads are unaffected.
+    let read: Value = from_async_reader(CHUNK_RAW).await.unwrap();
+    assert_eq!(from_bytes::<Value>(CHUNK_RAW).unwrap(), read);
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    borrow,
+    de::{from_bytes_bedrock_network, from_bytes_le, DeOpts, Deserializer},
+    error::ErrorKind,
+    to_bytes_bedrock_network, to_bytes_le, ByteArray, IntArray, LongArray, Value,
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct BlockState {
+    name: String,
+    states: HashMap<String, String>,
+    version: i32,
+}
+
+// A block state as sent in the block palette of a Bedrock server's start game
+// packet.
+#[rustfmt::skip]
+const STONE: &[u8] = &[
+    10, 0,
+        8, 4, b'n', b'a', b'm', b'e', 15,
+            b'm', b'i', b'n', b'e', b'c', b'r', b'a', b'f', b't', b':',
+            b's', b't', b'o', b'n', b'e',
+        10, 6, b's', b't', b'a', b't', b'e', b's',
+            8, 10, b's', b't', b'o', b'n', b'e', b'_', b't', b'y', b'p', b'e',
+                5, b's', b't', b'o', b'n', b'e',
+        0,
+        3, 7, b'v', b'e', b'r', b's', b'i', b'o', b'n', 0x82, 0xa8, 0x90, 0x11,
+    0,
+];
+
+fn stone() -> BlockState {
+    BlockState {
+        name: "minecraft:stone".to_owned(),
+        states: HashMap::from([("stone_type".to_owned(), "stone".to_owned())]),
+        version: 17959425,
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Everything {
+    byte: i8,
+    short: i16,
+    int: i32,
+    long: i64,
+    float: f32,
+    double: f64,
+    string: String,
+    bytes: ByteArray,
+    ints: IntArray,
+    longs: LongArray,
+    list: Vec<i64>,
+    nested: Vec<HashMap<String, i32>>,
+}
+
+fn everything() -> Everything {
+    Everything {
+        byte: -1,
+        short: -300,
+        int: i32::MIN,
+        long: i64::MAX,
+        float: 1.5,
+        double: -2.25,
+        // Long enough for the length to take two bytes.
+        string: "🦀".repeat(40),
+        bytes: ByteArray::new(vec![1, -1]),
+        ints: IntArray::new(vec![0, -1, i32::MAX, i32::MIN]),
+        longs: LongArray::new(vec![0, -1, i64::MAX, i64::MIN]),
+        list: vec![1, -64, 1 << 40],
+        nested: vec![HashMap::from([("a".to_owned(), -5)]), HashMap::new()],
+    }
+}
+
+#[test]
+fn block_state() {
+    let state: BlockState = from_bytes_bedrock_network(STONE).unwrap();
+    assert_eq!(stone(), state);
+}
+
+#[test]
+fn block_state_exact_bytes() {
+    assert_eq!(STONE, to_bytes_bedrock_network(&stone()).unwrap());
+}
+
+#[test]
+fn round_trip() {
+    let bytes = to_bytes_bedrock_network(&everything()).unwrap();
+    assert_eq!(everything(), from_bytes_bedrock_network(&bytes).unwrap());
+
+    let value: Value = from_bytes_bedrock_network(&bytes).unwrap();
+    let again = to_bytes_bedrock_network(&value).unwrap();
+    assert_eq!(value, from_bytes_bedrock_network::<Value>(&again).unwrap());
+
+    // The int and long arrays can't be borrowed, as their elements are
+    // VarInts.
+    let err = from_bytes_bedrock_network::<borrow::Value>(&bytes).unwrap_err();
+    assert!(err.to_string().contains("VarInts"), "{}", err);
+}
+
+#[test]
+fn not_little_endian() {
+    let bytes = to_bytes_bedrock_network(&everything()).unwrap();
+    assert_ne!(bytes, to_bytes_le(&everything()).unwrap());
+    assert!(from_bytes_le::<Everything>(&bytes).is_err());
+}
+
+#[test]
+fn ignored_values_are_skipped() {
+    #[derive(Deserialize)]
+    struct JustString {
+        string: String,
+    }
+
+    let bytes = to_bytes_bedrock_network(&everything()).unwrap();
+    let v: JustString = from_bytes_bedrock_network(&bytes).unwrap();
+    assert_eq!(everything().string, v.string);
+}
+
+#[test]
+fn lenient_arrays() {
+    #[derive(Serialize)]
+    struct Listed {
+        ints: Vec<i32>,
+    }
+
+    #[derive(Deserialize)]
+    struct Array {
+        ints: IntArray,
+    }
+
+    let bytes = to_bytes_bedrock_network(&Listed { ints: vec![1, -2] }).unwrap();
+    let mut de = Deserializer::from_bytes_bedrock_network(&bytes)
+        .with_opts(DeOpts::new().lenient_arrays(true));
+    let v = Array::deserialize(&mut de).unwrap();
+    assert_eq!(vec![1, -2], *v.ints);
+}
+
+#[test]
+fn borrowed_byte_array() {
+    #[derive(Deserialize)]
+    struct Borrowed<'a> {
+        #[serde(borrow)]
+        bytes: borrow::ByteArray<'a>,
+    }
+
+    let bytes = to_bytes_bedrock_network(&everything()).unwrap();
+    let v: Borrowed = from_bytes_bedrock_network(&bytes).unwrap();
+    assert_eq!(vec![1, -1], v.bytes.iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn borrowed_int_array_is_an_error() {
+    #[derive(Deserialize)]
+    struct Borrowed<'a> {
+        #[serde(borrow)]
+        #[allow(dead_code)]
+        ints: borrow::IntArray<'a>,
+    }
+
+    let bytes = to_bytes_bedrock_network(&everything()).unwrap();
+    assert!(from_bytes_bedrock_network::<Borrowed>(&bytes).is_err());
+}
+
+#[test]
+fn varint_too_many_bytes() {
+    #[rustfmt::skip]
+    let data = [
+        10, 0,
+            3, 1, b'a', 0x80, 0x80, 0x80, 0x80, 0x80, 0x01,
+        0,
+    ];
+
+    let err = from_bytes_bedrock_network::<Value>(&data).unwrap_err();
+    assert_eq!(&ErrorKind::VarIntTooLong, err.kind());
+    assert_eq!(Some(5), err.offset());
+    assert_eq!(Some("a"), err.path());
+}
+
+#[test]
+fn varint_too_large() {
+    // Five bytes, but with more than 32 bits.
+    #[rustfmt::skip]
+    let data = [
+        10, 0,
+            3, 1, b'a', 0xff, 0xff, 0xff, 0xff, 0x1f,
+        0,
+    ];
+
+    let err = from_bytes_bedrock_network::<Value>(&data).unwrap_err();
+    assert_eq!(&ErrorKind::VarIntTooLong, err.kind());
+
+    // The largest that fits.
+    #[rustfmt::skip]
+    let data = [
+        10, 0,
+            3, 1, b'a', 0xff, 0xff, 0xff, 0xff, 0x0f,
+        0,
+    ];
+
+    let v: HashMap<String, i32> = from_bytes_bedrock_network(&data).unwrap();
+    assert_eq!(i32::MIN, v["a"]);
+}
+
+#[test]
+fn varlong_limits() {
+    #[rustfmt::skip]
+    let mut data = vec![
+        10, 0,
+            4, 1, b'a', 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01,
+        0,
+    ];
+
+    let v: HashMap<String, i64> = from_bytes_bedrock_network(&data).unwrap();
+    assert_eq!(i64::MIN, v["a"]);
+
+    data[14] = 0x03;
+    let err = from_bytes_bedrock_network::<Value>(&data).unwrap_err();
+    assert_eq!(&ErrorKind::VarIntTooLong, err.kind());
+}
+
+#[test]
+fn varint_eof() {
+    let data = [10, 0, 3, 1, b'a', 0x80];
+    let err = from_bytes_bedrock_network::<Value>(&data).unwrap_err();
+    assert_eq!(&ErrorKind::UnexpectedEof, err.kind());
+}
+
+#[test]
+fn list_size_larger_than_input() {
+    // A list of 1000 bytes, with none of them present.
+    let data = [10, 0, 9, 1, b'a', 1, 0xd0, 0x0f, 0];
+    let err = from_bytes_bedrock_network::<Value>(&data).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidSize(1000), err.kind());
+    assert_eq!(Some(6), err.offset());
+}
@@ -0,0 +1,125 @@
+use serde::Deserialize;
+
+use crate::{
+    borrow,
+    de::{from_bytes, from_bytes_bedrock_network, from_bytes_le, Deserializer},
+    to_bytes_bedrock_network, to_bytes_le, Tag, Value,
+};
+
+use super::builder::{level, Builder};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+#[test]
+fn get_parses_entries_on_demand() {
+    let data = level();
+    let root: borrow::Compound = from_bytes(&data).unwrap();
+    assert_eq!(
+        vec!["DataVersion", "Level"],
+        root.keys().collect::<Vec<_>>()
+    );
+    assert_eq!(Some(2586), root.get::<i32>("DataVersion").unwrap());
+    assert_eq!(None, root.get::<i32>("Missing").unwrap());
+    assert_eq!(Some(Tag::Compound), root.tag("Level"));
+    assert!(root.contains_key("DataVersion"));
+
+    let level: borrow::Compound = root.get("Level").unwrap().unwrap();
+    assert_eq!(4, level.len());
+    assert_eq!(Some("full"), level.get::<&str>("Status").unwrap());
+    assert_eq!(Some(-3), level.get::<i32>("xPos").unwrap());
+    let states: borrow::LongArray = level.get("BlockStates").unwrap().unwrap();
+    assert!(states.iter().eq([1, 2, 3]));
+
+    let sections: Vec<borrow::Compound> = level.get("Sections").unwrap().unwrap();
+    assert_eq!(2, sections.len());
+    assert_eq!(Some(1), sections[1].get::<i8>("Y").unwrap());
+    assert_eq!(None, sections[0].get::<&str>("Name").unwrap());
+    assert_eq!(Some("second"), sections[1].get::<&str>("Name").unwrap());
+}
+
+#[test]
+fn nested_in_structs() {
+    #[derive(Deserialize)]
+    struct Chunk<'a> {
+        #[serde(rename = "Level", borrow)]
+        level: borrow::Compound<'a>,
+    }
+
+    let data = level();
+    let chunk: Chunk = from_bytes(&data).unwrap();
+    assert_eq!(Some("full"), chunk.level.get::<&str>("Status").unwrap());
+}
+
+#[test]
+fn same_as_full_value() {
+    let full: Value = from_bytes(CHUNK_RAW).unwrap();
+    let root: borrow::Compound = from_bytes(CHUNK_RAW).unwrap();
+    let level: borrow::Compound = root.get("Level").unwrap().unwrap();
+
+    let full_level = full["Level"].as_compound().unwrap();
+    assert_eq!(full_level.len(), level.len());
+    for key in level.keys() {
+        let value: Value = level.get(key).unwrap().unwrap();
+        assert_eq!(full_level[key], value, "{}", key);
+        assert_eq!(Some(value.tag()), level.tag(key));
+    }
+}
+
+#[test]
+fn errors_give_the_path_from_the_compound() {
+    let data = level();
+    let root: borrow::Compound = from_bytes(&data).unwrap();
+
+    let err = root.get::<String>("DataVersion").unwrap_err();
+    assert_eq!(Some("DataVersion"), err.path());
+
+    let level: borrow::Compound = root.get("Level").unwrap().unwrap();
+    #[derive(Deserialize, Debug)]
+    struct Section {
+        #[serde(rename = "Name")]
+        _name: String,
+    }
+    let err = level.get::<Vec<Section>>("Sections").unwrap_err();
+    assert_eq!(Some("Sections[0]"), err.path());
+
+    let err = root.get::<borrow::Compound>("DataVersion").unwrap_err();
+    assert!(err.to_string().contains("expected Compound"), "{}", err);
+}
+
+#[test]
+fn other_formats() {
+    let value: Value = from_bytes(&level()).unwrap();
+
+    let data = to_bytes_le(&value).unwrap();
+    let root: borrow::Compound = from_bytes_le(&data).unwrap();
+    assert_eq!(Some(2586), root.get::<i32>("DataVersion").unwrap());
+    let level: borrow::Compound = root.get("Level").unwrap().unwrap();
+    let states: borrow::LongArray = level.get("BlockStates").unwrap().unwrap();
+    assert!(states.iter().eq([1, 2, 3]));
+
+    let data = to_bytes_bedrock_network(&value).unwrap();
+    let root: borrow::Compound = from_bytes_bedrock_network(&data).unwrap();
+    assert_eq!(Some(2586), root.get::<i32>("DataVersion").unwrap());
+    let level: borrow::Compound = root.get("Level").unwrap().unwrap();
+    assert_eq!(Some(-3), level.get::<i32>("xPos").unwrap());
+}
+
+#[test]
+fn duplicate_keys_last_wins() {
+    let data = Builder::new()
+        .start_compound("")
+        .int("a", 1)
+        .int("a", 2)
+        .end_compound()
+        .build();
+    let root: borrow::Compound = from_bytes(&data).unwrap();
+    assert_eq!(Some(2), root.get::<i32>("a").unwrap());
+}
+
+#[test]
+fn not_from_a_reader() {
+    let data = level();
+    let mut de = Deserializer::from_reader(&data[..]);
+    let err = borrow::Compound::deserialize(&mut de).unwrap_err();
+    assert!(err.to_string().contains("byte slice"), "{}", err);
+}
@@ -0,0 +1,222 @@
+use std::borrow::Cow;
+
+use crate::{borrow, de::from_bytes, from_value, to_bytes, to_bytes_le, Map, Tag, Value};
+
+use super::builder::Builder;
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+fn compound<'a, 'b>(value: &'b borrow::Value<'a>) -> &'b Map<Cow<'a, str>, borrow::Value<'a>> {
+    match value {
+        borrow::Value::Compound(c) => c,
+        other => panic!("expected compound, got {:?}", other),
+    }
+}
+
+#[test]
+fn every_tag() {
+    let payload = Builder::new()
+        .start_compound("")
+        .byte("byte", 1)
+        .short("short", 2)
+        .int("int", 3)
+        .long("long", 4)
+        .float("float", 5.0)
+        .double("double", 6.0)
+        .string("string", "seven")
+        .byte_array("bytes", &[8])
+        .int_array("ints", &[9])
+        .long_array("longs", &[10])
+        .start_list("list", Tag::Int, 1)
+        .int_payload(11)
+        .start_compound("compound")
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let value: borrow::Value = from_bytes(&payload).unwrap();
+    let c = compound(&value);
+
+    assert_eq!(borrow::Value::Byte(1), c["byte"]);
+    assert_eq!(borrow::Value::Short(2), c["short"]);
+    assert_eq!(borrow::Value::Int(3), c["int"]);
+    assert_eq!(borrow::Value::Long(4), c["long"]);
+    assert_eq!(borrow::Value::Float(5.0), c["float"]);
+    assert_eq!(borrow::Value::Double(6.0), c["double"]);
+    assert_eq!(borrow::Value::String("seven".into()), c["string"]);
+    assert_eq!(c["bytes"].as_byte_array().unwrap(), &[8]);
+    assert_eq!(c["ints"].as_int_array().unwrap(), &[9]);
+    assert_eq!(c["longs"].as_long_array().unwrap(), &[10]);
+    assert_eq!(borrow::Value::List(vec![borrow::Value::Int(11)]), c["list"]);
+    assert_eq!(0, compound(&c["compound"]).len());
+}
+
+#[test]
+fn keys_and_strings_borrow_from_input() {
+    let payload = Builder::new()
+        .start_compound("")
+        .string("name", "steve")
+        .end_compound()
+        .build();
+
+    let value: borrow::Value = from_bytes(&payload).unwrap();
+    let (key, value) = compound(&value).iter().next().unwrap();
+
+    assert!(matches!(key, Cow::Borrowed("name")));
+    assert!(matches!(
+        value,
+        borrow::Value::String(Cow::Borrowed("steve"))
+    ));
+}
+
+#[test]
+fn non_bmp_strings_are_owned() {
+    let value = Value::Compound(Map::from([(
+        "😈".to_owned(),
+        Value::String("😈".to_owned()),
+    )]));
+    let payload = to_bytes(&value).unwrap();
+
+    let value: borrow::Value = from_bytes(&payload).unwrap();
+    let (key, value) = compound(&value).iter().next().unwrap();
+
+    assert!(matches!(key, Cow::Owned(k) if k == "😈"));
+    assert!(matches!(value, borrow::Value::String(Cow::Owned(s)) if s == "😈"));
+}
+
+#[test]
+fn a_compound_that_looks_like_an_array_is_a_compound() {
+    let payload = Builder::new()
+        .start_compound("")
+        .byte("tag", 11)
+        .int_array("data", &[1])
+        .end_compound()
+        .build();
+
+    let value: borrow::Value = from_bytes(&payload).unwrap();
+    let c = compound(&value);
+
+    assert_eq!(borrow::Value::Byte(11), c["tag"]);
+    assert_eq!(c["data"].as_int_array().unwrap(), &[1]);
+}
+
+#[test]
+fn chunk_matches_owned_value() {
+    let owned: Value = from_bytes(CHUNK_RAW).unwrap();
+    let borrowed: borrow::Value = from_bytes(CHUNK_RAW).unwrap();
+    assert_eq!(owned, Value::from(borrowed));
+}
+
+#[test]
+fn to_owned_copies_deeply() {
+    let owned: Value = from_bytes(CHUNK_RAW).unwrap();
+    let borrowed: borrow::Value = from_bytes(CHUNK_RAW).unwrap();
+    assert_eq!(owned, borrowed.to_owned());
+    assert_eq!(owned, Value::from(borrowed));
+
+    // A subtree can outlive the input it borrowed from.
+    let data = CHUNK_RAW.to_vec();
+    let sections = {
+        let borrowed: borrow::Value = from_bytes(&data).unwrap();
+        borrowed["Level"]["Sections"].to_owned()
+    };
+    drop(data);
+    assert_eq!(owned["Level"]["Sections"], sections);
+    assert!(matches!(sections, Value::List(l) if !l.is_empty()));
+}
+
+#[test]
+fn serializes_like_owned_value() {
+    let borrowed: borrow::Value = from_bytes(CHUNK_RAW).unwrap();
+    let bytes = to_bytes(&borrowed).unwrap();
+
+    let owned: Value = from_bytes(CHUNK_RAW).unwrap();
+    let back: Value = from_bytes(&bytes).unwrap();
+    assert_eq!(owned, back);
+
+    // Every tag is written as it was read, so only the order of compound keys
+    // can differ. See preserve_order.rs for an exact copy.
+    assert_eq!(CHUNK_RAW.len(), bytes.len());
+}
+
+#[test]
+fn edits_are_written_back() {
+    let mut borrowed: borrow::Value = from_bytes(CHUNK_RAW).unwrap();
+    borrowed["Level"]["Status"] = borrow::Value::String("empty".into());
+    borrowed["Level"]["xPos"] = borrow::Value::Int(-7);
+    let bytes = to_bytes(&borrowed).unwrap();
+
+    let mut expected: Value = from_bytes(CHUNK_RAW).unwrap();
+    expected["Level"]["Status"] = Value::String("empty".to_owned());
+    expected["Level"]["xPos"] = Value::Int(-7);
+    assert_eq!(expected, from_bytes::<Value>(&bytes).unwrap());
+}
+
+#[test]
+fn from_little_endian_and_value() {
+    let value = Value::Compound(Map::from([
+        ("a".to_owned(), Value::Int(1)),
+        (
+            "b".to_owned(),
+            Value::LongArray(crate::LongArray::new(vec![2])),
+        ),
+    ]));
+
+    let le = to_bytes_le(&value).unwrap();
+    let borrowed: borrow::Value = crate::de::from_bytes_le(&le).unwrap();
+    assert_eq!(value, Value::from(borrowed));
+
+    // An owned value has no bytes for the arrays to borrow.
+    assert!(from_value::<borrow::Value>(&value).is_err());
+    let without_arrays = Value::Compound(Map::from([("a".to_owned(), Value::Int(1))]));
+    let borrowed: borrow::Value = from_value(&without_arrays).unwrap();
+    assert_eq!(without_arrays, Value::from(borrowed));
+}
+
+#[test]
+fn accessors_match_owned_value() {
+    let value: borrow::Value = from_bytes(CHUNK_RAW).unwrap();
+    let owned: Value = from_bytes(CHUNK_RAW).unwrap();
+
+    let level = &value["Level"];
+    assert_eq!(owned["Level"]["Status"].as_str(), level["Status"].as_str());
+    assert_eq!(owned["Level"]["xPos"].as_i64(), level["xPos"].as_i64());
+    assert_eq!(owned["Level"]["xPos"].as_f64(), level["xPos"].as_f64());
+    assert_eq!(
+        owned["Level"]["Biomes"].as_int_array().unwrap(),
+        level["Biomes"].as_int_array().unwrap()
+    );
+    assert_eq!(
+        owned["Level"]["Sections"].as_list().map(<[_]>::len),
+        level["Sections"].as_list().map(<[_]>::len)
+    );
+
+    let compound = value.as_compound().unwrap();
+    assert!(compound.contains_key("Level"));
+    assert!(value.is_compound());
+    assert!(level["xPos"].is_number());
+    assert!(!level["Status"].is_number());
+    assert_eq!(None, level["Status"].as_i64());
+}
+
+#[test]
+fn numeric_coercions_match_owned_value() {
+    let values = [
+        Value::Byte(-1),
+        Value::Byte(1),
+        Value::Short(0),
+        Value::Int(i32::MAX),
+        Value::Long(i64::MIN),
+        Value::Float(1.0),
+        Value::String("1".to_owned()),
+    ];
+    for owned in &values {
+        let borrowed: borrow::Value = from_value(owned).unwrap();
+        assert_eq!(owned.as_bool(), borrowed.as_bool());
+        assert_eq!(owned.as_u64(), borrowed.as_u64());
+        assert_eq!(owned.as_usize(), borrowed.as_usize());
+    }
+
+    assert_eq!(borrow::Value::Byte(1), borrow::Value::from(true));
+    assert_eq!(Some(false), borrow::Value::from(false).as_bool());
+}
@@ -167,3 +167,24 @@ impl Builder {
         self.payload
     }
 }
+
+/// A small chunk-like compound, with a nested compound, an array and a list of
+/// compounds.
+pub fn level() -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .int("DataVersion", 2586)
+        .start_compound("Level")
+        .long_array("BlockStates", &[1, 2, 3])
+        .string("Status", "full")
+        .start_list("Sections", Tag::Compound, 2)
+        .byte("Y", 0)
+        .end_compound()
+        .byte("Y", 1)
+        .string("Name", "second")
+        .end_compound()
+        .int("xPos", -3)
+        .end_compound()
+        .end_compound()
+        .build()
+}
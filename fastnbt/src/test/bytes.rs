@@ -0,0 +1,219 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    de::{from_bytes, from_reader},
+    to_bytes, Tag, Value,
+};
+
+use super::builder::Builder;
+
+const CHUNK_1_14_RAW: &[u8] = include_bytes!("resources/chunk1.14.nbt");
+
+/// Deserialize through a reader, where nothing can be borrowed. Unlike
+/// `from_reader` this allows types that could borrow.
+fn read<'de, T: Deserialize<'de>>(data: &[u8]) -> crate::error::Result<T> {
+    T::deserialize(&mut crate::de::Deserializer::from_reader(data))
+}
+
+fn within(input: &[u8], bytes: &[u8]) -> bool {
+    let range = input.as_ptr_range();
+    range.contains(&bytes.as_ptr()) && bytes.as_ptr_range().end <= range.end
+}
+
+#[derive(Deserialize)]
+struct Chunk<'a> {
+    #[serde(borrow, rename = "Level")]
+    level: Level<'a>,
+}
+
+#[derive(Deserialize)]
+struct Level<'a> {
+    #[serde(borrow, rename = "Biomes")]
+    biomes: Option<&'a [u8]>,
+    #[serde(borrow, rename = "Sections")]
+    sections: Vec<Section<'a>>,
+}
+
+#[derive(Deserialize)]
+struct Section<'a> {
+    #[serde(borrow, rename = "SkyLight")]
+    sky_light: Option<&'a [u8]>,
+}
+
+#[test]
+fn borrows_light_from_real_chunk() {
+    let chunk: Chunk = from_bytes(CHUNK_1_14_RAW).unwrap();
+    // Biomes are an Int Array since 1.13, so there is nothing to borrow.
+    let value: Value = from_bytes(CHUNK_1_14_RAW).unwrap();
+    assert!(matches!(
+        &value,
+        Value::Compound(c) if matches!(&c["Level"], Value::Compound(l) if matches!(l["Biomes"], Value::IntArray(_)))
+    ));
+
+    let lights: Vec<&[u8]> = chunk
+        .level
+        .sections
+        .iter()
+        .filter_map(|s| s.sky_light)
+        .collect();
+    assert!(!lights.is_empty());
+    for light in lights {
+        assert_eq!(2048, light.len());
+        assert!(within(CHUNK_1_14_RAW, light));
+    }
+}
+
+#[test]
+fn borrows_pre_1_13_biomes() {
+    let biomes: Vec<i8> = (0..256).map(|i| i as i8).collect();
+    let data = Builder::new()
+        .start_compound("")
+        .start_compound("Level")
+        .byte_array("Biomes", &biomes)
+        .start_list("Sections", Tag::Compound, 0)
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let chunk: Chunk = from_bytes(&data).unwrap();
+    let borrowed = chunk.level.biomes.unwrap();
+    assert!(within(&data, borrowed));
+    assert_eq!((0..=255).collect::<Vec<u8>>(), borrowed);
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct MaybeBorrowed<'a> {
+    #[serde(borrow, serialize_with = "crate::bytes::serialize")]
+    biomes: Cow<'a, [u8]>,
+}
+
+#[test]
+fn reader_falls_back_to_owned_bytes() {
+    let data = Builder::new()
+        .start_compound("")
+        .byte_array("biomes", &[1, 2, -1])
+        .end_compound()
+        .build();
+
+    let v: MaybeBorrowed = from_bytes(&data).unwrap();
+    assert!(matches!(v.biomes, Cow::Borrowed(b) if within(&data, b)));
+
+    let v: MaybeBorrowed = read(&data).unwrap();
+    assert!(matches!(v.biomes, Cow::Owned(_)));
+    assert_eq!(&[1, 2, 255], &*v.biomes);
+
+    // A plain slice can't own anything.
+    #[derive(Deserialize, Debug)]
+    struct Borrowed<'a> {
+        #[allow(dead_code)]
+        biomes: &'a [u8],
+    }
+    assert!(from_bytes::<Borrowed>(&data).is_ok());
+    assert!(read::<Borrowed>(&data).is_err());
+}
+
+#[test]
+fn serializes_as_byte_array() {
+    #[derive(Serialize)]
+    struct V<'a> {
+        #[serde(serialize_with = "crate::bytes::serialize")]
+        slice: &'a [u8],
+        #[serde(serialize_with = "crate::bytes::serialize")]
+        vec: Vec<u8>,
+        #[serde(serialize_with = "crate::bytes::serialize")]
+        array: [u8; 2],
+        #[serde(serialize_with = "crate::bytes::serialize")]
+        some: Option<&'a [u8]>,
+        #[serde(serialize_with = "crate::bytes::serialize")]
+        none: Option<Vec<u8>>,
+        plain: &'a [u8],
+    }
+
+    let all: Vec<u8> = (0..=255).collect();
+    let data = to_bytes(&V {
+        slice: &all,
+        vec: vec![7],
+        array: [200, 100],
+        some: Some(&[]),
+        none: None,
+        plain: &[1, 2],
+    })
+    .unwrap();
+
+    let value: Value = from_bytes(&data).unwrap();
+    let c = match value {
+        Value::Compound(c) => c,
+        _ => panic!(),
+    };
+    let bytes = |key: &str| match &c[key] {
+        Value::ByteArray(b) => b.iter().map(|&b| b as u8).collect::<Vec<u8>>(),
+        other => panic!("{}: {:?}", key, other),
+    };
+    assert_eq!(all, bytes("slice"));
+    assert_eq!(vec![7], bytes("vec"));
+    assert_eq!(vec![200, 100], bytes("array"));
+    assert_eq!(Vec::<u8>::new(), bytes("some"));
+    assert!(!c.contains_key("none"));
+    // Without the helper serde calls it a sequence.
+    assert!(matches!(&c["plain"], Value::List(_)));
+}
+
+#[test]
+fn round_trips_through_cow() {
+    let v = MaybeBorrowed {
+        biomes: Cow::Owned((0..=255).collect()),
+    };
+    let data = to_bytes(&v).unwrap();
+    assert_eq!(v, from_bytes(&data).unwrap());
+    assert_eq!(v, read(&data).unwrap());
+}
+
+/// Like `serde_bytes::ByteBuf`, which asks for a byte buffer.
+#[derive(Debug, PartialEq)]
+struct ByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = ByteBuf;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("bytes")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<ByteBuf, E> {
+                Ok(ByteBuf(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<ByteBuf, E> {
+                Ok(ByteBuf(v))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(Visitor)
+    }
+}
+
+#[test]
+fn byte_buf() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct V {
+        biomes: ByteBuf,
+    }
+
+    let data = Builder::new()
+        .start_compound("")
+        .byte_array("biomes", &[1, -1])
+        .end_compound()
+        .build();
+    let expected = V {
+        biomes: ByteBuf(vec![1, 255]),
+    };
+    assert_eq!(expected, from_bytes(&data).unwrap());
+    assert_eq!(expected, from_reader(data.as_slice()).unwrap());
+}
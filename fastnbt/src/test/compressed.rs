@@ -0,0 +1,111 @@
+use std::io::Write;
+
+use flate2::write::{GzEncoder, ZlibEncoder};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    de::{from_compressed_reader, from_maybe_compressed_bytes},
+    to_bytes, Compression, Value,
+};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Level {
+    name: String,
+    seed: i64,
+    spawn: Vec<i32>,
+}
+
+fn level() -> Level {
+    Level {
+        name: "New World".to_owned(),
+        seed: -4172144997902289642,
+        spawn: vec![128, 64, -64],
+    }
+}
+
+fn encodings(raw: &[u8]) -> Vec<(Compression, Vec<u8>)> {
+    let mut gz = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gz.write_all(raw).unwrap();
+
+    let mut zlib = ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+    zlib.write_all(raw).unwrap();
+
+    vec![
+        (Compression::Gzip, gz.finish().unwrap()),
+        (Compression::Zlib, zlib.finish().unwrap()),
+        (Compression::Uncompressed, raw.to_vec()),
+    ]
+}
+
+#[test]
+fn detect() {
+    for (expected, data) in encodings(&to_bytes(&level()).unwrap()) {
+        assert_eq!(expected, Compression::detect(&data));
+    }
+
+    // Other zlib compression levels have different second bytes.
+    assert_eq!(Compression::Zlib, Compression::detect(&[0x78, 0x01]));
+    assert_eq!(Compression::Zlib, Compression::detect(&[0x78, 0x9c]));
+    assert_eq!(Compression::Zlib, Compression::detect(&[0x78, 0xda]));
+    assert_eq!(Compression::Zlib, Compression::detect(&[0x58, 0x85]));
+
+    // A bad check value, or not deflate.
+    assert_eq!(
+        Compression::Uncompressed,
+        Compression::detect(&[0x78, 0x9d])
+    );
+    assert_eq!(
+        Compression::Uncompressed,
+        Compression::detect(&[0x79, 0x9c])
+    );
+
+    assert_eq!(Compression::Uncompressed, Compression::detect(&[0x1f]));
+    assert_eq!(Compression::Uncompressed, Compression::detect(&[]));
+}
+
+#[test]
+fn all_encodings_from_reader() {
+    for (expected, data) in encodings(&to_bytes(&level()).unwrap()) {
+        let (v, compression): (Level, _) = from_compressed_reader(data.as_slice()).unwrap();
+        assert_eq!(level(), v);
+        assert_eq!(expected, compression);
+    }
+}
+
+#[test]
+fn all_encodings_from_bytes() {
+    for (expected, data) in encodings(&to_bytes(&level()).unwrap()) {
+        let (v, compression): (Level, _) = from_maybe_compressed_bytes(&data).unwrap();
+        assert_eq!(level(), v);
+        assert_eq!(expected, compression);
+    }
+}
+
+#[test]
+fn chunk() {
+    let expected: Value = crate::de::from_bytes(CHUNK_RAW).unwrap();
+
+    for (compression, data) in encodings(CHUNK_RAW) {
+        let (v, detected): (Value, _) = from_compressed_reader(data.as_slice()).unwrap();
+        assert_eq!(expected, v);
+        assert_eq!(compression, detected);
+
+        let (v, detected): (Value, _) = from_maybe_compressed_bytes(&data).unwrap();
+        assert_eq!(expected, v);
+        assert_eq!(compression, detected);
+    }
+}
+
+#[test]
+fn truncated() {
+    for (_, mut data) in encodings(&to_bytes(&level()).unwrap()) {
+        data.truncate(data.len() / 2);
+        assert!(from_compressed_reader::<_, Level>(data.as_slice()).is_err());
+        assert!(from_maybe_compressed_bytes::<Level>(&data).is_err());
+    }
+
+    assert!(from_compressed_reader::<_, Level>(&[][..]).is_err());
+    assert!(from_maybe_compressed_bytes::<Level>(&[]).is_err());
+}
@@ -1,7 +1,7 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 
-use crate::error::{Error, Result};
+use crate::error::{Error, ErrorKind, Result};
 use crate::{de::from_bytes, Value};
 use crate::{ByteArray, IntArray, LongArray, Tag};
 
@@ -895,6 +895,68 @@ fn ignore_integral_arrays() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn ignore_lists_of_numbers() -> Result<()> {
+    #[derive(Deserialize)]
+    struct V {
+        last: i8,
+    }
+
+    let payload = Builder::new()
+        .start_compound("object")
+        .start_list("a", Tag::Short, 2)
+        .short_payload(1)
+        .short_payload(2)
+        .start_list("b", Tag::Long, 1)
+        .long_payload(3)
+        .start_list("c", Tag::Double, 2)
+        .double_payload(4.0)
+        .double_payload(5.0)
+        .byte("last", 6)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(payload.as_slice())?;
+    assert_eq!(6, v.last);
+
+    let v: V = crate::de::from_reader(payload.as_slice())?;
+    assert_eq!(6, v.last);
+
+    // Too few elements for the size is still noticed.
+    let truncated = Builder::new()
+        .start_compound("object")
+        .start_list("a", Tag::Long, 2)
+        .long_payload(1)
+        .end_compound()
+        .build();
+
+    assert!(from_bytes::<V>(truncated.as_slice()).is_err());
+    Ok(())
+}
+
+#[test]
+fn ignored_strings_are_not_decoded() -> Result<()> {
+    #[derive(Deserialize)]
+    struct V {
+        last: i8,
+    }
+
+    // Not valid CESU-8, but never looked at.
+    let payload = Builder::new()
+        .start_compound("object")
+        .tag(Tag::String)
+        .name("a")
+        .raw_len(2)
+        .raw_bytes(&[0xff, 0xfe])
+        .byte("last", 6)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(payload.as_slice())?;
+    assert_eq!(6, v.last);
+    Ok(())
+}
+
 #[test]
 fn fixed_array() -> Result<()> {
     #[derive(Deserialize)]
@@ -926,6 +988,29 @@ fn fixed_array() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn fixed_array_then_field() -> Result<()> {
+    #[derive(Deserialize)]
+    pub struct Level {
+        a: [i32; 2],
+        b: i32,
+    }
+
+    let payload = Builder::new()
+        .start_compound("object")
+        .start_list("a", Tag::Int, 2)
+        .int_payload(1)
+        .int_payload(2)
+        .int("b", 3)
+        .end_compound()
+        .build();
+
+    let v: Level = from_bytes(payload.as_slice())?;
+    assert_eq!([1, 2], v.a);
+    assert_eq!(3, v.b);
+    Ok(())
+}
+
 #[test]
 fn type_mismatch_string() -> Result<()> {
     #[derive(Deserialize, Debug)]
@@ -1148,11 +1233,10 @@ fn int_array_in_fullvalue() {
 
 #[test]
 fn trailing_bytes() {
-    // Can't really see a way to assert that there are no trailing bytes. We
-    // don't return how far in to the input we got.
     let mut input = Builder::new().start_compound("").end_compound().build();
     input.push(1);
-    let _v: Value = from_bytes(&input).unwrap();
+    let err = from_bytes::<Value>(&input).unwrap_err();
+    assert_eq!(&ErrorKind::TrailingBytes(1), err.kind());
 }
 
 #[test]
@@ -1219,6 +1303,84 @@ fn can_borrow_cesu8_if_same_repr() {
     assert_eq!("abc", v.unwrap().name);
 }
 
+#[test]
+fn borrowed_str_in_enums_and_collections() {
+    use crate::borrow::Str;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(tag = "id")]
+    enum Entity<'a> {
+        #[serde(rename = "minecraft:zombie")]
+        Zombie {
+            #[serde(rename = "CustomName", borrow)]
+            name: Str<'a>,
+        },
+    }
+
+    #[derive(Deserialize, Debug)]
+    pub struct V<'a> {
+        #[serde(borrow)]
+        entity: Entity<'a>,
+        #[serde(borrow)]
+        tags: Vec<Str<'a>>,
+        #[serde(borrow)]
+        props: HashMap<Str<'a>, Str<'a>>,
+    }
+
+    let modified_unicode_str = cesu8::to_java_cesu8("😈");
+    let input = Builder::new()
+        .start_compound("")
+        .start_compound("entity")
+        .string("id", "minecraft:zombie")
+        .string("CustomName", "Bob")
+        .end_compound()
+        .start_list("tags", Tag::String, 2)
+        .string_payload("a")
+        .raw_len(modified_unicode_str.len())
+        .raw_bytes(&modified_unicode_str)
+        .start_compound("props")
+        .string("facing", "north")
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&input).unwrap();
+    let Entity::Zombie { name } = &v.entity;
+    assert_eq!("Bob", &**name);
+    assert!(name.is_borrowed());
+    assert_eq!(
+        vec!["a", "😈"],
+        v.tags.iter().map(|s| &**s).collect::<Vec<_>>()
+    );
+    assert!(v.tags[0].is_borrowed());
+    // Encoded differently in CESU-8, so it has to be copied.
+    assert!(!v.tags[1].is_borrowed());
+    assert_eq!("north", &*v.props["facing"]);
+
+    // Nothing can be borrowed from a reader, but the same type still works.
+    let mut de = crate::de::Deserializer::from_reader(input.as_slice());
+    let v = V::deserialize(&mut de).unwrap();
+    let Entity::Zombie { name } = &v.entity;
+    assert_eq!(&Str::from(String::from("Bob")), name);
+    assert!(v
+        .tags
+        .iter()
+        .chain(v.props.values())
+        .chain(Some(name))
+        .all(|s| !s.is_borrowed()));
+}
+
+#[test]
+fn borrowed_str_serializes_as_a_string() {
+    use crate::borrow::Str;
+
+    let map = HashMap::from([(Str::from("Name"), Str::from(String::from("Steve")))]);
+    let bytes = crate::to_bytes(&map).unwrap();
+    let value: Value = from_bytes(&bytes).unwrap();
+    assert_eq!(Some("Steve"), value["Name"].as_str());
+    assert_eq!("Steve", map["Name"].to_string());
+}
+
 #[test]
 fn can_cow_cesu8() {
     #[derive(Deserialize, Debug)]
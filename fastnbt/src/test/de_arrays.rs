@@ -1,11 +1,15 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::borrow;
-use crate::error::Result;
+use crate::error::{ErrorKind, Result};
 use crate::ByteArray;
 use crate::IntArray;
 use crate::LongArray;
-use crate::{de::from_bytes, test::builder::Builder};
+use crate::{
+    de::{from_bytes, from_bytes_le, from_bytes_with_opts, DeOpts},
+    test::builder::Builder,
+    to_bytes, to_bytes_le, Tag, Value,
+};
 
 #[test]
 fn byte_array() -> Result<()> {
@@ -184,6 +188,694 @@ fn array_subslice_doesnt_panic() {
     assert!(matches!(from_bytes::<V>(&payload[..20]), Err(_)));
 }
 
+#[test]
+fn borrowed_array_lengths() {
+    #[derive(Deserialize)]
+    struct V<'a> {
+        #[serde(borrow)]
+        bytes: borrow::ByteArray<'a>,
+        ints: borrow::IntArray<'a>,
+        longs: borrow::LongArray<'a>,
+        empty: borrow::LongArray<'a>,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .byte_array("bytes", &[1, 2, 3])
+        .int_array("ints", &[1, 2, 3, 4])
+        .long_array("longs", &[1, 2, 3, 4, 5])
+        .long_array("empty", &[])
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&payload).unwrap();
+    assert_eq!((3, 3), (v.bytes.len(), v.bytes.size_in_bytes()));
+    assert_eq!((4, 16), (v.ints.len(), v.ints.size_in_bytes()));
+    assert_eq!((5, 40), (v.longs.len(), v.longs.size_in_bytes()));
+    assert_eq!(v.longs.len(), v.longs.iter().count());
+    assert!(!v.longs.is_empty());
+    assert!(v.empty.is_empty());
+    assert_eq!(0, v.empty.len());
+}
+
+#[test]
+fn borrowed_array_iterators_are_exact_size_and_reversible() {
+    #[derive(Deserialize)]
+    struct V<'a> {
+        #[serde(borrow)]
+        bytes: borrow::ByteArray<'a>,
+        ints: borrow::IntArray<'a>,
+        longs: borrow::LongArray<'a>,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .byte_array("bytes", &[1, -2, 3])
+        .int_array("ints", &[1, -2, 3, 4])
+        .long_array("longs", &[1, -2, 3, 4, i64::MIN])
+        .end_compound()
+        .build();
+    let v: V = from_bytes(&payload).unwrap();
+
+    let mut longs = v.longs.iter();
+    assert_eq!((5, Some(5)), longs.size_hint());
+    assert_eq!(Some(1), longs.next());
+    assert_eq!(4, longs.len());
+    assert_eq!(Some(i64::MIN), longs.next_back());
+    assert_eq!(3, longs.len());
+    assert!(longs.by_ref().eq([-2, 3, 4]));
+    assert_eq!(0, longs.len());
+    assert_eq!(None, longs.next());
+    assert_eq!(None, longs.next_back());
+
+    let mut forward: Vec<i64> = v.longs.iter().collect();
+    forward.reverse();
+    assert_eq!(forward, v.longs.iter().rev().collect::<Vec<_>>());
+    assert!(v.ints.iter().rev().eq([4, 3, -2, 1]));
+    assert!(v.bytes.iter().rev().eq([3, -2, 1]));
+
+    let mut ints = v.ints.iter();
+    ints.nth(1);
+    assert_eq!(2, ints.len());
+    let mut bytes = v.bytes.iter();
+    bytes.next_back();
+    assert_eq!(2, bytes.len());
+}
+
+#[test]
+fn borrowed_array_get_matches_iteration() {
+    #[derive(Deserialize)]
+    struct V<'a> {
+        #[serde(borrow)]
+        bytes: borrow::ByteArray<'a>,
+        ints: borrow::IntArray<'a>,
+        longs: borrow::LongArray<'a>,
+    }
+
+    let ints: Vec<i32> = (0..1024).map(|i| i * 7 - 3000).collect();
+    let payload = Builder::new()
+        .start_compound("")
+        .byte_array("bytes", &[1, -2, 3])
+        .int_array("ints", &ints)
+        .long_array("longs", &[1, -2, i64::MAX, i64::MIN])
+        .end_compound()
+        .build();
+    let v: V = from_bytes(&payload).unwrap();
+
+    // Every index, and a couple past the end.
+    for i in 0..v.ints.len() + 2 {
+        assert_eq!(v.ints.iter().nth(i), v.ints.get(i), "{}", i);
+    }
+    for i in 0..v.longs.len() + 2 {
+        assert_eq!(v.longs.iter().nth(i), v.longs.get(i), "{}", i);
+    }
+    for i in 0..v.bytes.len() + 2 {
+        assert_eq!(v.bytes.iter().nth(i), v.bytes.get(i), "{}", i);
+    }
+
+    assert_eq!(ints[731], v.ints.at(731));
+    assert_eq!(ints[1023], v.ints.at(1023));
+    assert_eq!(i64::MIN, v.longs.at(3));
+    assert_eq!(-2, v.bytes.at(1));
+    assert_eq!(None, v.ints.get(usize::MAX));
+    assert_eq!(None, v.longs.get(usize::MAX / 4));
+    assert_eq!(None, v.bytes.get(usize::MAX));
+}
+
+#[test]
+#[should_panic(expected = "index 4 is out of range for an NBT array of length 4")]
+fn borrowed_array_at_out_of_range() {
+    #[derive(Deserialize)]
+    struct V<'a> {
+        #[serde(borrow)]
+        longs: borrow::LongArray<'a>,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .long_array("longs", &[1, 2, 3, 4])
+        .end_compound()
+        .build();
+    let v: V = from_bytes(&payload).unwrap();
+    v.longs.at(4);
+}
+
+#[test]
+fn borrowed_arrays_to_owned() {
+    #[derive(Deserialize)]
+    struct V<'a> {
+        #[serde(borrow)]
+        bytes: borrow::ByteArray<'a>,
+        ints: borrow::IntArray<'a>,
+        longs: borrow::LongArray<'a>,
+    }
+
+    let longs: Vec<i64> = (0..256).map(|i| i * 0x0101_0101_0101 - 99).collect();
+    let payload = Builder::new()
+        .start_compound("")
+        .byte_array("bytes", &[1, -2, 3])
+        .int_array("ints", &[1, -2, i32::MIN])
+        .long_array("longs", &longs)
+        .end_compound()
+        .build();
+
+    let (bytes, ints, owned) = {
+        let v: V = from_bytes(&payload).unwrap();
+        assert_eq!(v.longs.iter().collect::<Vec<_>>(), v.longs.to_vec());
+        (v.bytes.to_owned(), v.ints.to_owned(), v.longs.to_owned())
+    };
+    drop(payload);
+
+    assert_eq!(ByteArray::new(vec![1, -2, 3]), bytes);
+    assert_eq!(IntArray::new(vec![1, -2, i32::MIN]), ints);
+    assert_eq!(LongArray::new(longs), owned);
+}
+
+#[test]
+fn borrowed_arrays_copy_into_buffers() {
+    #[derive(Deserialize)]
+    struct V<'a> {
+        #[serde(borrow)]
+        bytes: borrow::ByteArray<'a>,
+        ints: borrow::IntArray<'a>,
+        long: borrow::LongArray<'a>,
+        longer: borrow::LongArray<'a>,
+    }
+
+    let longer: Vec<i64> = (0..300).map(|i| i * -0x0102_0304_0506).collect();
+    let payload = Builder::new()
+        .start_compound("")
+        .byte_array("bytes", &[1, -2, 3])
+        .int_array("ints", &[1, -2, i32::MAX])
+        .long_array("long", &[i64::MIN, 2])
+        .long_array("longer", &longer)
+        .end_compound()
+        .build();
+    let v: V = from_bytes(&payload).unwrap();
+
+    // A buffer reused for several arrays only holds the latest.
+    let mut buf = vec![7; 10];
+    v.long.copy_into(&mut buf);
+    assert_eq!(v.long.iter().collect::<Vec<_>>(), buf);
+    v.longer.copy_into(&mut buf);
+    assert_eq!(v.longer.iter().collect::<Vec<_>>(), buf);
+    v.long.copy_into(&mut buf);
+    assert_eq!(vec![i64::MIN, 2], buf);
+
+    let mut slice = [0; 300];
+    v.longer.copy_into_slice(&mut slice).unwrap();
+    assert_eq!(longer, slice);
+    let mut ints = [0; 3];
+    v.ints.copy_into_slice(&mut ints).unwrap();
+    assert!(v.ints.iter().eq(ints));
+    let mut bytes = [0; 3];
+    v.bytes.copy_into_slice(&mut bytes).unwrap();
+    assert!(v.bytes.iter().eq(bytes));
+
+    // The slice must be exactly the right length, and is left alone if not.
+    let mut short = [5; 299];
+    let err = v.longer.copy_into_slice(&mut short).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("array of length 300 into a slice of length 299"),
+        "{}",
+        err
+    );
+    assert!(short.iter().all(|&l| l == 5));
+    assert!(v.ints.copy_into_slice(&mut [0; 4]).is_err());
+}
+
+#[derive(Deserialize, Serialize)]
+struct BorrowedArrays<'a> {
+    #[serde(borrow)]
+    bytes: borrow::ByteArray<'a>,
+    #[serde(borrow)]
+    ints: borrow::IntArray<'a>,
+    #[serde(borrow)]
+    longs: borrow::LongArray<'a>,
+}
+
+fn borrowed_arrays_payload() -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .byte_array("bytes", &[1, -2, 3])
+        .int_array("ints", &[1, -2, i32::MAX])
+        .long_array("longs", &[i64::MIN, 2])
+        .end_compound()
+        .build()
+}
+
+#[test]
+fn borrowed_arrays_serialize_as_array_tags() {
+    let payload = borrowed_arrays_payload();
+    let v: BorrowedArrays = from_bytes(&payload).unwrap();
+    assert_eq!(payload, to_bytes(&v).unwrap());
+
+    let value: Value = from_bytes(&to_bytes(&v).unwrap()).unwrap();
+    assert_eq!(
+        Value::ByteArray(ByteArray::new(vec![1, -2, 3])),
+        value["bytes"]
+    );
+    assert_eq!(
+        Value::LongArray(LongArray::new(vec![i64::MIN, 2])),
+        value["longs"]
+    );
+
+    // Written in the endian of the output, not of the input.
+    let le = to_bytes_le(&v).unwrap();
+    let v: BorrowedArrays = from_bytes_le(&le).unwrap();
+    assert!(v.ints.iter().eq([1, -2, i32::MAX]));
+    assert_eq!(le, to_bytes_le(&v).unwrap());
+    assert_eq!(payload, to_bytes(&v).unwrap());
+}
+
+#[test]
+fn borrowed_arrays_compare_by_element() {
+    let payload = borrowed_arrays_payload();
+    let v: BorrowedArrays = from_bytes(&payload).unwrap();
+
+    assert_eq!(v.bytes, [1, -2, 3]);
+    assert_eq!(v.bytes, [1, -2, 3][..]);
+    assert_eq!(v.bytes, &[1, -2, 3][..]);
+    assert_eq!(v.bytes, vec![1, -2, 3]);
+    assert_eq!(v.bytes, ByteArray::new(vec![1, -2, 3]));
+    assert_eq!(v.ints, [1, -2, i32::MAX]);
+    assert_eq!(v.ints, vec![1, -2, i32::MAX]);
+    assert_eq!(v.ints, IntArray::new(vec![1, -2, i32::MAX]));
+    assert_eq!(v.longs, [i64::MIN, 2]);
+    assert_eq!(v.longs, vec![i64::MIN, 2]);
+    assert_eq!(v.longs, LongArray::new(vec![i64::MIN, 2]));
+
+    // And the other way around.
+    assert_eq!([1, -2, 3], v.bytes);
+    assert_eq!([1, -2, 3][..], v.bytes);
+    assert_eq!(&[1, -2, 3][..], v.bytes);
+    assert_eq!(vec![1, -2, i32::MAX], v.ints);
+    assert_eq!(LongArray::new(vec![i64::MIN, 2]), v.longs);
+
+    // Elements that differ, including only in their low bytes, and lengths
+    // that differ, either way around.
+    assert_ne!(v.bytes, [1, -2, 4]);
+    assert_ne!(v.ints, [1, -2, i32::MAX - 1]);
+    assert_ne!(v.longs, [i64::MIN + 1, 2]);
+    assert_ne!(v.longs, [i64::MIN, 2, 0]);
+    assert_ne!(v.longs, [i64::MIN]);
+    assert_ne!(v.ints, Vec::<i32>::new());
+    assert_ne!(IntArray::new(vec![1, -2]), v.ints);
+    assert_ne!(vec![1, -2, 3, 4], v.bytes);
+
+    // Borrowed arrays compare with each other by value too.
+    let same = borrowed_arrays_payload();
+    let w: BorrowedArrays = from_bytes(&same).unwrap();
+    assert_eq!(v.longs, w.longs);
+    assert_eq!(v.ints, w.ints);
+    assert_ne!(v.ints, w.ints.chunks(2).next().unwrap());
+    assert_eq!(
+        borrow::ByteArray::from_be_bytes(&[]),
+        borrow::ByteArray::from_be_bytes(&[])
+    );
+}
+
+#[test]
+fn borrowed_arrays_serialize_as_sequences_elsewhere() {
+    let payload = borrowed_arrays_payload();
+    let v: BorrowedArrays = from_bytes(&payload).unwrap();
+    assert_eq!(
+        serde_json::json!({
+            "bytes": [1, -2, 3],
+            "ints": [1, -2, i32::MAX],
+            "longs": [i64::MIN, 2],
+        }),
+        serde_json::to_value(&v).unwrap()
+    );
+}
+
+/// A compact serializer like bincode, which writes out the shape of integers,
+/// bytes and sequences it is given.
+struct Compact;
+
+macro_rules! compact_ints {
+    ($($method:ident($ty:ty)),*) => {
+        $(
+            fn $method(self, v: $ty) -> std::result::Result<String, Self::Error> {
+                Ok(v.to_string())
+            }
+        )*
+    };
+}
+
+macro_rules! compact_unsupported {
+    ($($method:ident($($arg:ty),*) -> $ok:ty),*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> std::result::Result<$ok, Self::Error> {
+                Err(serde::ser::Error::custom(stringify!($method)))
+            }
+        )*
+    };
+}
+
+impl serde::Serializer for Compact {
+    type Ok = String;
+    type Error = serde::de::value::Error;
+    type SerializeSeq = CompactSeq;
+    type SerializeTuple = serde::ser::Impossible<String, Self::Error>;
+    type SerializeTupleStruct = serde::ser::Impossible<String, Self::Error>;
+    type SerializeTupleVariant = serde::ser::Impossible<String, Self::Error>;
+    type SerializeMap = serde::ser::Impossible<String, Self::Error>;
+    type SerializeStruct = serde::ser::Impossible<String, Self::Error>;
+    type SerializeStructVariant = serde::ser::Impossible<String, Self::Error>;
+
+    compact_ints!(serialize_i8(i8), serialize_i32(i32), serialize_i64(i64));
+
+    fn serialize_bytes(self, v: &[u8]) -> std::result::Result<String, Self::Error> {
+        Ok(format!("bytes {:?}", v))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> std::result::Result<String, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> std::result::Result<CompactSeq, Self::Error> {
+        Ok(CompactSeq(Vec::new()))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    compact_unsupported!(
+        serialize_bool(bool) -> String,
+        serialize_i16(i16) -> String,
+        serialize_u8(u8) -> String,
+        serialize_u16(u16) -> String,
+        serialize_u32(u32) -> String,
+        serialize_u64(u64) -> String,
+        serialize_f32(f32) -> String,
+        serialize_f64(f64) -> String,
+        serialize_char(char) -> String,
+        serialize_str(&str) -> String,
+        serialize_none() -> String,
+        serialize_unit() -> String,
+        serialize_unit_struct(&'static str) -> String,
+        serialize_unit_variant(&'static str, u32, &'static str) -> String,
+        serialize_tuple(usize) -> Self::SerializeTuple,
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct,
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant,
+        serialize_map(Option<usize>) -> Self::SerializeMap,
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct,
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant
+    );
+
+    fn serialize_some<T: ?Sized + Serialize>(
+        self,
+        _value: &T,
+    ) -> std::result::Result<String, Self::Error> {
+        Err(serde::ser::Error::custom("serialize_some"))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> std::result::Result<String, Self::Error> {
+        Err(serde::ser::Error::custom("serialize_newtype_variant"))
+    }
+}
+
+struct CompactSeq(Vec<String>);
+
+impl serde::ser::SerializeSeq for CompactSeq {
+    type Ok = String;
+    type Error = serde::de::value::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> std::result::Result<(), Self::Error> {
+        self.0.push(value.serialize(Compact)?);
+        Ok(())
+    }
+
+    fn end(self) -> std::result::Result<String, Self::Error> {
+        Ok(format!("[{}]", self.0.join(", ")))
+    }
+}
+
+#[test]
+fn borrowed_arrays_serialize_as_sequences_in_compact_formats() {
+    let payload = borrowed_arrays_payload();
+    let v: BorrowedArrays = from_bytes(&payload).unwrap();
+
+    // Only our serializer is given the raw bytes, even after it has just
+    // written some arrays.
+    to_bytes(&v).unwrap();
+    to_bytes(&std::collections::HashMap::from([(
+        "a",
+        LongArray::new(vec![1]),
+    )]))
+    .unwrap();
+    assert_eq!("[1, -2, 3]", v.bytes.serialize(Compact).unwrap());
+    assert_eq!(
+        format!("[1, -2, {}]", i32::MAX),
+        v.ints.serialize(Compact).unwrap()
+    );
+    assert_eq!(
+        format!("[{}, 2]", i64::MIN),
+        v.longs.serialize(Compact).unwrap()
+    );
+}
+
+#[test]
+fn borrowed_arrays_raw_bytes_are_the_payload() {
+    #[derive(Deserialize, Serialize)]
+    struct V<'a> {
+        #[serde(borrow)]
+        data: borrow::LongArray<'a>,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .long_array("data", &[1, -2, 3])
+        .end_compound()
+        .build();
+    let v: V = from_bytes(&payload).unwrap();
+
+    // The longs are last, before the compound's end tag.
+    let start = payload.len() - 1 - 24;
+    let raw = v.data.as_be_bytes().unwrap();
+    assert_eq!(&payload[start..payload.len() - 1], raw);
+    assert_eq!(payload[start..].as_ptr(), raw.as_ptr());
+    assert_eq!([0, 0, 0, 0, 0, 0, 0, 1], raw[..8]);
+
+    // Read from little-endian NBT, the data is not big-endian.
+    let le = to_bytes_le(&v).unwrap();
+    let v: V = from_bytes_le(&le).unwrap();
+    assert_eq!(None, v.data.as_be_bytes());
+}
+
+#[test]
+fn borrowed_arrays_from_be_bytes() {
+    let raw = [0, 0, 0, 0, 0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff];
+    let longs = borrow::LongArray::from_be_bytes(&raw[..8]).unwrap();
+    assert!(longs.iter().eq([1]));
+    let ints = borrow::IntArray::from_be_bytes(&raw).unwrap();
+    assert!(ints.iter().eq([0, 1, -1]));
+    assert_eq!(Some(&raw[..]), ints.as_be_bytes());
+    let bytes = borrow::ByteArray::from_be_bytes(&raw[7..9]);
+    assert!(bytes.iter().eq([1, -1]));
+    assert_eq!(&raw[7..9], bytes.as_be_bytes());
+
+    assert!(borrow::LongArray::from_be_bytes(&raw).is_err());
+    assert!(borrow::IntArray::from_be_bytes(&raw[..7]).is_err());
+    assert!(borrow::LongArray::from_be_bytes(&[]).unwrap().is_empty());
+
+    // Built over existing data, they write out as arrays like any other.
+    #[derive(Serialize)]
+    struct V<'a> {
+        data: borrow::LongArray<'a>,
+    }
+    let expected = Builder::new()
+        .start_compound("")
+        .long_array("data", &[1])
+        .end_compound()
+        .build();
+    assert_eq!(expected, to_bytes(&V { data: longs }).unwrap());
+}
+
+#[test]
+fn carving_mask_bits() {
+    // Chunks before 1.18 store carving masks as a bitset of every block in
+    // the bottom 256 blocks, indexed by x | z << 4 | y << 8, as written by
+    // Java's BitSet::toByteArray.
+    let carved = [(0, 0, 0), (3, 40, 5), (4, 40, 5), (7, 41, 5), (15, 255, 15)];
+    let index = |(x, y, z): (usize, usize, usize)| x | z << 4 | y << 8;
+    let mut mask = vec![0i8; 8192];
+    for &block in &carved {
+        let i = index(block);
+        mask[i / 8] |= (1u8 << (i % 8)) as i8;
+    }
+
+    #[derive(Deserialize)]
+    struct Level<'a> {
+        #[serde(rename = "CarvingMasks", borrow)]
+        carving_masks: Masks<'a>,
+    }
+    #[derive(Deserialize)]
+    struct Masks<'a> {
+        #[serde(rename = "AIR", borrow)]
+        air: borrow::ByteArray<'a>,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .start_compound("CarvingMasks")
+        .byte_array("AIR", &mask)
+        .end_compound()
+        .end_compound()
+        .build();
+    let level: Level = from_bytes(&payload).unwrap();
+    let air = level.carving_masks.air;
+    let owned = ByteArray::new(mask);
+
+    assert_eq!(carved.len(), air.count_ones());
+    assert_eq!(carved.len(), owned.count_ones());
+    for &block in &carved {
+        assert_eq!(Some(true), air.bit(index(block)), "{:?}", block);
+        assert_eq!(Some(true), owned.bit(index(block)), "{:?}", block);
+    }
+    assert_eq!(Some(false), air.bit(index((5, 40, 5))));
+    assert_eq!(Some(false), owned.bit(index((3, 40, 6))));
+    assert_eq!(None, air.bit(65536));
+    assert_eq!(None, owned.bit(65536));
+
+    let set: Vec<usize> = air
+        .bits()
+        .enumerate()
+        .filter(|&(_, bit)| bit)
+        .map(|(i, _)| i)
+        .collect();
+    let mut expected: Vec<usize> = carved.iter().copied().map(index).collect();
+    expected.sort_unstable();
+    assert_eq!(expected, set);
+    assert_eq!(65536, owned.bits().count());
+    assert!(air.bits().eq(owned.bits()));
+}
+
+#[test]
+fn borrowed_array_offsets() {
+    let payload = borrowed_arrays_payload();
+    let v: BorrowedArrays = from_bytes(&payload).unwrap();
+
+    let raw = v.longs.as_be_bytes().unwrap();
+    let offsets: Vec<_> = v.longs.iter_with_offsets().collect();
+    assert_eq!(vec![(0, i64::MIN), (8, 2)], offsets);
+    for (at, long) in offsets {
+        assert_eq!(long.to_be_bytes(), raw[at..at + 8]);
+    }
+
+    let raw = v.ints.as_be_bytes().unwrap();
+    for (at, int) in v.ints.iter_with_offsets().rev() {
+        assert_eq!(int.to_be_bytes(), raw[at..at + 4]);
+    }
+    assert_eq!(3, v.ints.iter_with_offsets().len());
+    assert!(v.bytes.iter_with_offsets().eq([(0, 1), (1, -2), (2, 3)]));
+}
+
+#[test]
+fn borrowed_array_sections() {
+    // Biomes of a 1.18 chunk 384 blocks high: 24 sections of 4x4x4 cells.
+    let biomes: Vec<i32> = (0..1536).map(|i| i * 7 - 3000).collect();
+    let payload = Builder::new()
+        .start_compound("")
+        .int_array("biomes", &biomes)
+        .end_compound()
+        .build();
+
+    #[derive(Deserialize)]
+    struct V<'a> {
+        #[serde(borrow)]
+        biomes: borrow::IntArray<'a>,
+    }
+    let v: V = from_bytes(&payload).unwrap();
+
+    for section in 0..24 {
+        let cells = v.biomes.take_section(section, 64).unwrap();
+        assert_eq!(64, cells.len());
+        for (i, biome) in cells.iter().enumerate() {
+            let (x, z, y) = (i % 4, i / 4 % 4, i / 16);
+            assert_eq!(biomes[section * 64 + y * 16 + z * 4 + x], biome);
+        }
+    }
+    assert!(v.biomes.take_section(24, 64).is_none());
+    assert!(v.biomes.take_section(usize::MAX, 64).is_none());
+
+    let sections: Vec<_> = v.biomes.chunks(64).collect();
+    assert_eq!(24, sections.len());
+    for (i, section) in sections.iter().enumerate() {
+        assert!(section
+            .iter()
+            .eq(v.biomes.take_section(i, 64).unwrap().iter()));
+    }
+
+    // A short last chunk holds what is left over.
+    let chunks: Vec<_> = v.biomes.chunks(1000).map(|c| c.len()).collect();
+    assert_eq!(vec![1000, 536], chunks);
+    assert_eq!(
+        Some(biomes[1535]),
+        v.biomes.chunks(1000).last().unwrap().get(535)
+    );
+
+    let payload = borrowed_arrays_payload();
+    let v: BorrowedArrays = from_bytes(&payload).unwrap();
+    assert!(v.longs.take_section(1, 1).unwrap().iter().eq([2]));
+    assert!(v.longs.chunks(1).rev().map(|c| c.at(0)).eq([2, i64::MIN]));
+    assert!(v.bytes.take_section(0, 2).unwrap().iter().eq([1, -2]));
+    assert!(v.bytes.take_section(1, 2).is_none());
+    assert_eq!(2, v.bytes.chunks(2).len());
+}
+
+#[test]
+fn truncated_borrowed_arrays_are_invalid_size() {
+    #[derive(Deserialize, Debug)]
+    struct V<'a> {
+        #[serde(borrow)]
+        _data: borrow::LongArray<'a>,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .long_array("_data", &[1, 2, 3, 4])
+        .end_compound()
+        .build();
+
+    // Cut off part way through the third long.
+    let end = payload.len() - 1 - 8 - 5;
+    let err = from_bytes::<V>(&payload[..end]).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidSize(4), err.kind());
+}
+
+#[test]
+fn borrowed_arrays_from_other_formats_must_hold_whole_elements() {
+    // Other deserializers can give any number of bytes for the data.
+    let json = r#"{"tag": 12, "data": "twelve bytes"}"#;
+    let err = serde_json::from_str::<borrow::LongArray>(json).unwrap_err();
+    assert!(
+        err.to_string().contains("expected a multiple of 8 bytes"),
+        "{}",
+        err
+    );
+
+    let json = r#"{"tag": 11, "data": "twelve bytes"}"#;
+    let ints = serde_json::from_str::<borrow::IntArray>(json).unwrap();
+    assert_eq!(3, ints.len());
+}
+
 #[test]
 fn nice_error_if_deserialize_array_to_seq() {
     // Since the handling of NBT Arrays is a bit surprising, we want to make
@@ -206,3 +898,251 @@ fn nice_error_if_deserialize_array_to_seq() {
         Err(e) => assert!(e.to_string().contains("Array")),
     }
 }
+
+fn lenient() -> DeOpts {
+    DeOpts::new().lenient_arrays(true)
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Arrays {
+    bytes: ByteArray,
+    ints: IntArray,
+    longs: LongArray,
+}
+
+#[test]
+fn lenient_arrays_accept_arrays_and_lists() {
+    let arrays = Builder::new()
+        .start_compound("")
+        .byte_array("bytes", &[1, 2])
+        .int_array("ints", &[3, 4])
+        .long_array("longs", &[5, 6])
+        .end_compound()
+        .build();
+
+    let lists = Builder::new()
+        .start_compound("")
+        .start_list("bytes", Tag::Byte, 2)
+        .byte_payload(1)
+        .byte_payload(2)
+        .start_list("ints", Tag::Int, 2)
+        .int_payload(3)
+        .int_payload(4)
+        .start_list("longs", Tag::Long, 2)
+        .long_payload(5)
+        .long_payload(6)
+        .end_compound()
+        .build();
+
+    let from_arrays: Arrays = from_bytes_with_opts(&arrays, lenient()).unwrap();
+    let from_lists: Arrays = from_bytes_with_opts(&lists, lenient()).unwrap();
+    assert_eq!(from_arrays, from_lists);
+    assert_eq!(&[3, 4], from_lists.ints.as_slice());
+
+    // Lists are still rejected by default.
+    assert!(from_bytes::<Arrays>(&lists).is_err());
+}
+
+#[test]
+fn lenient_arrays_accept_empty_list_of_end() {
+    #[derive(Deserialize)]
+    struct V {
+        ints: IntArray,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("ints", Tag::End, 0)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes_with_opts(&payload, lenient()).unwrap();
+    assert!(v.ints.is_empty());
+}
+
+#[test]
+fn lenient_arrays_check_element_type() {
+    #[derive(Deserialize, Debug)]
+    struct V {
+        _longs: LongArray,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("_longs", Tag::Int, 1)
+        .int_payload(1)
+        .end_compound()
+        .build();
+
+    let err = from_bytes_with_opts::<V>(&payload, lenient()).unwrap_err();
+    assert!(err.to_string().contains("List of Int"), "{}", err);
+}
+
+#[test]
+fn lenient_arrays_list_into_borrowed_array() {
+    #[derive(Deserialize)]
+    struct V<'a> {
+        #[serde(borrow)]
+        data: borrow::IntArray<'a>,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("data", Tag::Int, 3)
+        .int_payload(1)
+        .int_payload(-2)
+        .int_payload(3)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes_with_opts(&payload, lenient()).unwrap();
+    assert!(v.data.iter().eq([1, -2, 3]));
+}
+
+#[test]
+fn lenient_arrays_array_into_seq() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct V {
+        ints: Vec<i32>,
+        longs: Vec<i64>,
+        bytes: Vec<i8>,
+    }
+
+    let arrays = Builder::new()
+        .start_compound("")
+        .int_array("ints", &[1, 2])
+        .long_array("longs", &[3])
+        .byte_array("bytes", &[-4])
+        .end_compound()
+        .build();
+
+    let lists = Builder::new()
+        .start_compound("")
+        .start_list("ints", Tag::Int, 2)
+        .int_payload(1)
+        .int_payload(2)
+        .start_list("longs", Tag::Long, 1)
+        .long_payload(3)
+        .start_list("bytes", Tag::Byte, 1)
+        .byte_payload(-4)
+        .end_compound()
+        .build();
+
+    let from_arrays: V = from_bytes_with_opts(&arrays, lenient()).unwrap();
+    let from_lists: V = from_bytes_with_opts(&lists, lenient()).unwrap();
+    assert_eq!(from_arrays, from_lists);
+    assert_eq!(vec![1, 2], from_arrays.ints);
+}
+
+#[test]
+fn lenient_arrays_array_into_fixed_size_array() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct V {
+        uuid: [i32; 4],
+        heights: [i16; 3],
+        positions: Vec<[i64; 2]>,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .int_array("uuid", &[1, -2, 3, i32::MAX])
+        .int_array("heights", &[64, 70, -10])
+        .start_list("positions", Tag::LongArray, 2)
+        .int_payload(2)
+        .long_payload(5)
+        .long_payload(6)
+        .int_payload(2)
+        .long_payload(-7)
+        .long_payload(8)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes_with_opts(&payload, lenient()).unwrap();
+    assert_eq!(
+        V {
+            uuid: [1, -2, 3, i32::MAX],
+            heights: [64, 70, -10],
+            positions: vec![[5, 6], [-7, 8]],
+        },
+        v
+    );
+
+    let err = from_bytes::<V>(&payload).unwrap_err();
+    assert!(err.to_string().contains("expected NBT Array"), "{}", err);
+}
+
+#[test]
+fn lenient_arrays_fixed_size_array_length_mismatch() {
+    #[derive(Deserialize, Debug)]
+    struct V {
+        _pos: [i32; 4],
+    }
+
+    for ints in [&[1, 2, 3][..], &[1, 2, 3, 4, 5][..]] {
+        let payload = Builder::new()
+            .start_compound("")
+            .int_array("_pos", ints)
+            .end_compound()
+            .build();
+
+        let err = from_bytes_with_opts::<V>(&payload, lenient()).unwrap_err();
+        assert_eq!(
+            &ErrorKind::ArrayLengthMismatch {
+                expected: 4,
+                found: ints.len()
+            },
+            err.kind()
+        );
+        assert_eq!(Some("_pos"), err.path());
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Sections {
+        _sections: Vec<V>,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("_sections", Tag::Compound, 2)
+        .int_array("_pos", &[1, 2, 3, 4])
+        .end_compound()
+        .int_array("_pos", &[1, 2])
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let err = from_bytes_with_opts::<Sections>(&payload, lenient()).unwrap_err();
+    assert_eq!(
+        &ErrorKind::ArrayLengthMismatch {
+            expected: 4,
+            found: 2
+        },
+        err.kind()
+    );
+    assert_eq!(Some("_sections[1]._pos"), err.path());
+}
+
+#[test]
+fn lenient_arrays_list_of_lists() {
+    #[derive(Deserialize)]
+    struct V {
+        positions: Vec<IntArray>,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("positions", Tag::List, 2)
+        .tag(Tag::Int)
+        .int_payload(1)
+        .int_payload(7)
+        .tag(Tag::Int)
+        .int_payload(2)
+        .int_payload(8)
+        .int_payload(9)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes_with_opts(&payload, lenient()).unwrap();
+    assert_eq!(&[7], v.positions[0].as_slice());
+    assert_eq!(&[8, 9], v.positions[1].as_slice());
+}
@@ -0,0 +1,118 @@
+use crate::{debug::trace, error::ErrorKind, Tag};
+
+use super::builder::Builder;
+
+fn lines(out: &[u8]) -> Vec<&str> {
+    std::str::from_utf8(out).unwrap().lines().collect()
+}
+
+#[test]
+fn every_header() {
+    let data = Builder::new()
+        .start_compound("root")
+        .byte("b", 1)
+        .string("s", "hi")
+        .int_array("ia", &[1, 2, 3])
+        .start_list("l", Tag::Short, 2)
+        .short_payload(1)
+        .short_payload(2)
+        .end_compound()
+        .build();
+
+    let mut out = Vec::new();
+    let stats = trace(&data, &mut out).unwrap();
+    assert_eq!(1, stats.count(Tag::Byte));
+
+    assert_eq!(
+        vec![
+            "       0  Compound (10) \"root\"",
+            "       7    Byte (1) \"b\"",
+            "      12    String (8) \"s\", 2 bytes",
+            "      20    IntArray (11) \"ia\", 3 elements",
+            "      41    List (9) \"l\", 2 Short elements",
+            "      50      Short (2) [0]",
+            "      52      Short (2) [1]",
+        ],
+        lines(&out)
+    );
+}
+
+#[test]
+fn stops_at_corrupt_list_length() {
+    let data = Builder::new()
+        .start_compound("")
+        .start_compound("Level")
+        .int("xPos", 1)
+        .start_list("Entities", Tag::Long, 1000)
+        .long_payload(1)
+        .end_compound()
+        .end_compound()
+        .build();
+
+    // The list's tag, then name, then element tag, then the length.
+    let list_offset = 3 + 8 + 11;
+    let length_offset = list_offset + 3 + "Entities".len() + 1;
+
+    let mut out = Vec::new();
+    let err = trace(&data, &mut out).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidSize(1000), err.kind());
+    assert_eq!(Some(length_offset), err.offset());
+
+    let lines = lines(&out);
+    assert_eq!(5, lines.len());
+    assert_eq!(
+        format!(
+            "{:>8}      List (9) \"Entities\", 1000 Long elements",
+            list_offset
+        ),
+        lines[3]
+    );
+    assert!(lines[4].starts_with(&format!("{:>8}  error: ", length_offset)));
+    assert!(lines[4].ends_with(&format!("(at byte {})", length_offset)));
+}
+
+#[test]
+fn stops_at_invalid_tag() {
+    let data = Builder::new()
+        .start_compound("")
+        .int("ok", 1)
+        .raw_bytes(&[20])
+        .end_compound()
+        .build();
+
+    let mut out = Vec::new();
+    let err = trace(&data, &mut out).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidTag(20), err.kind());
+
+    let lines = lines(&out);
+    assert_eq!(3, lines.len());
+    assert!(lines[1].contains("Int (3) \"ok\""));
+    assert!(lines[2].starts_with(&format!("{:>8}  error: ", data.len() - 2)));
+}
+
+#[test]
+fn stops_when_cut_short() {
+    let data = Builder::new()
+        .start_compound("")
+        .string("s", "a long string")
+        .end_compound()
+        .build();
+    let data = &data[..data.len() - 5];
+
+    let mut out = Vec::new();
+    assert!(trace(data, &mut out).is_err());
+
+    // The string's declared length is still shown.
+    let lines = lines(&out);
+    assert_eq!(3, lines.len());
+    assert!(lines[1].ends_with("\"s\", 13 bytes"));
+    assert!(lines[2].contains("error: "));
+}
+
+#[test]
+fn valid_input_has_no_error_line() {
+    let data = include_bytes!("resources/chunk.nbt");
+    let mut out = Vec::new();
+    trace(data, &mut out).unwrap();
+    assert!(!lines(&out).iter().any(|l| l.contains("error")));
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{
+    de::{from_bytes, from_bytes_with_opts, from_reader, DeOpts},
+    error::ErrorKind,
+    Tag, Value,
+};
+
+use super::with_stack;
+
+/// A root compound with the field `a`, holding lists nested `lists` deep with
+/// an empty list innermost.
+fn nested_lists(lists: usize) -> Vec<u8> {
+    let mut data = vec![Tag::Compound as u8, 0, 0];
+    data.extend([Tag::List as u8, 0, 1, b'a']);
+
+    for _ in 1..lists {
+        data.push(Tag::List as u8);
+        data.extend(1i32.to_be_bytes());
+    }
+    data.push(Tag::End as u8);
+    data.extend(0i32.to_be_bytes());
+
+    data.push(Tag::End as u8);
+    data
+}
+
+#[derive(Deserialize, Debug)]
+struct Nested(Vec<Nested>);
+
+impl Nested {
+    fn depth(&self) -> usize {
+        1 + self.0.iter().map(Nested::depth).max().unwrap_or(0)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Typed {
+    a: Nested,
+}
+
+#[derive(Deserialize, Debug)]
+struct Ignored {}
+
+#[derive(Deserialize, Debug)]
+struct Unit {
+    #[allow(dead_code)]
+    a: (),
+}
+
+fn assert_depth_error<T: std::fmt::Debug>(res: crate::error::Result<T>) {
+    assert_eq!(&ErrorKind::DepthLimitExceeded, res.unwrap_err().kind());
+}
+
+#[test]
+fn very_deep_value() {
+    with_stack(|| {
+        let data = nested_lists(10_000);
+        assert_depth_error(from_bytes::<Value>(&data));
+        assert_depth_error(from_reader::<_, Value>(data.as_slice()));
+    });
+}
+
+#[test]
+fn very_deep_typed() {
+    with_stack(|| {
+        let data = nested_lists(10_000);
+        assert_depth_error(from_bytes::<Typed>(&data));
+        assert_depth_error(from_bytes::<HashMap<String, Nested>>(&data));
+    });
+}
+
+#[test]
+fn very_deep_ignored() {
+    with_stack(|| {
+        let data = nested_lists(10_000);
+        assert_depth_error(from_bytes::<Ignored>(&data));
+        assert_depth_error(from_bytes::<Unit>(&data));
+    });
+}
+
+#[test]
+fn default_limit() {
+    with_stack(|| {
+        // The root compound counts towards the depth.
+        let max = DeOpts::DEFAULT_MAX_DEPTH;
+        let data = nested_lists(max - 1);
+        from_bytes::<Value>(&data).unwrap();
+        assert_eq!(max - 1, from_bytes::<Typed>(&data).unwrap().a.depth());
+        from_bytes::<Ignored>(&data).unwrap();
+
+        let data = nested_lists(max);
+        assert_depth_error(from_bytes::<Value>(&data));
+        assert_depth_error(from_bytes::<Typed>(&data));
+        assert_depth_error(from_bytes::<Ignored>(&data));
+    });
+}
+
+#[test]
+fn custom_limit() {
+    with_stack(|| {
+        let data = nested_lists(3);
+        let opts = DeOpts::new().max_depth(4);
+        from_bytes_with_opts::<Value>(&data, opts).unwrap();
+        from_bytes_with_opts::<Unit>(&data, opts).unwrap();
+
+        let opts = DeOpts::new().max_depth(3);
+        assert_depth_error(from_bytes_with_opts::<Value>(&data, opts));
+        assert_depth_error(from_bytes_with_opts::<Unit>(&data, opts));
+
+        // Raising the limit allows deeper input.
+        let data = nested_lists(200);
+        let opts = DeOpts::new().max_depth(201);
+        from_bytes_with_opts::<Value>(&data, opts).unwrap();
+    });
+}
+
+#[test]
+fn zero_limit() {
+    with_stack(|| {
+        let opts = DeOpts::new().max_depth(0);
+        let data = [Tag::Compound as u8, 0, 0, Tag::End as u8];
+        assert_depth_error(from_bytes_with_opts::<Value>(&data, opts));
+    });
+}
+
+#[test]
+fn error_message() {
+    with_stack(|| {
+        let err = from_bytes_with_opts::<Value>(&nested_lists(3), DeOpts::new().max_depth(2))
+            .unwrap_err();
+        assert_eq!(
+            "a[0]: invalid nbt: compounds and lists nested more than 2 deep (at byte 17)",
+            err.to_string()
+        );
+    });
+}
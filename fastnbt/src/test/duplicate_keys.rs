@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{
+    borrow,
+    de::{from_bytes, from_bytes_with_opts, DeOpts, Deserializer, DuplicateKeyPolicy},
+    error::ErrorKind,
+    Tag, Value,
+};
+
+use super::builder::Builder;
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Chunk {
+    #[serde(rename = "xPos")]
+    x_pos: i32,
+    #[serde(rename = "Status")]
+    status: String,
+}
+
+fn duplicate_at_top_level() -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .int("xPos", 1)
+        .string("Status", "full")
+        .int("xPos", 2)
+        .end_compound()
+        .build()
+}
+
+/// Sections where only the second has a duplicated key.
+fn duplicate_in_list() -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .start_list("Sections", Tag::Compound, 2)
+        .byte("Y", 0)
+        .end_compound()
+        .byte("Y", 1)
+        .start_compound("Y")
+        .end_compound()
+        .byte("Y", 2)
+        .end_compound()
+        .end_compound()
+        .build()
+}
+
+fn opts(policy: DuplicateKeyPolicy) -> DeOpts {
+    DeOpts::new().on_duplicate_key(policy)
+}
+
+fn get<'a>(value: &'a Value, key: &str) -> &'a Value {
+    match value {
+        Value::Compound(c) => &c[key],
+        _ => panic!("not a compound: {:?}", value),
+    }
+}
+
+#[test]
+fn last_wins_by_default() {
+    let data = duplicate_at_top_level();
+
+    let value: Value = from_bytes(&data).unwrap();
+    assert_eq!(&Value::Int(2), get(&value, "xPos"));
+
+    let value: borrow::Value = from_bytes(&data).unwrap();
+    assert_eq!(&Value::Int(2), get(&value.into(), "xPos"));
+
+    // Derived structs reject the second xPos themselves.
+    assert!(from_bytes::<Chunk>(&data).is_err());
+}
+
+#[test]
+fn first_wins() {
+    let data = duplicate_at_top_level();
+    let opts = opts(DuplicateKeyPolicy::FirstWins);
+
+    let value: Value = from_bytes_with_opts(&data, opts).unwrap();
+    assert_eq!(&Value::Int(1), get(&value, "xPos"));
+    assert_eq!(&Value::String("full".to_owned()), get(&value, "Status"));
+
+    let value: borrow::Value = from_bytes_with_opts(&data, opts).unwrap();
+    assert_eq!(&Value::Int(1), get(&value.into(), "xPos"));
+
+    let chunk: Chunk = from_bytes_with_opts(&data, opts).unwrap();
+    assert_eq!(
+        Chunk {
+            x_pos: 1,
+            status: "full".to_owned()
+        },
+        chunk
+    );
+
+    let mut de = Deserializer::from_reader(data.as_slice()).with_opts(opts);
+    let chunk = Chunk::deserialize(&mut de).unwrap();
+    assert_eq!(1, chunk.x_pos);
+}
+
+#[test]
+fn first_wins_in_list() {
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "PascalCase")]
+    struct Chunk {
+        sections: Vec<Section>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "PascalCase")]
+    struct Section {
+        y: i8,
+    }
+
+    let data = duplicate_in_list();
+    let chunk: Chunk = from_bytes_with_opts(&data, opts(DuplicateKeyPolicy::FirstWins)).unwrap();
+
+    // The skipped duplicates include a compound, which should be skipped whole.
+    let ys: Vec<_> = chunk.sections.iter().map(|s| s.y).collect();
+    assert_eq!(vec![0, 1], ys);
+}
+
+#[test]
+fn error() {
+    let data = duplicate_at_top_level();
+    let opts = opts(DuplicateKeyPolicy::Error);
+
+    for err in [
+        from_bytes_with_opts::<Value>(&data, opts).unwrap_err(),
+        from_bytes_with_opts::<Chunk>(&data, opts).unwrap_err(),
+        from_bytes_with_opts::<HashMap<String, Value>>(&data, opts).unwrap_err(),
+    ] {
+        assert_eq!(&ErrorKind::DuplicateKey("xPos".to_owned()), err.kind());
+        assert_eq!(Some("xPos"), err.path());
+        assert!(err.to_string().contains("duplicate key"), "{}", err);
+    }
+
+    let mut de = Deserializer::from_reader(data.as_slice()).with_opts(opts);
+    let err = Value::deserialize(&mut de).unwrap_err();
+    assert_eq!(&ErrorKind::DuplicateKey("xPos".to_owned()), err.kind());
+}
+
+#[test]
+fn error_in_list() {
+    let data = duplicate_in_list();
+    let err = from_bytes_with_opts::<Value>(&data, opts(DuplicateKeyPolicy::Error)).unwrap_err();
+    assert_eq!(&ErrorKind::DuplicateKey("Y".to_owned()), err.kind());
+    assert_eq!(Some("Sections[1].Y"), err.path());
+}
+
+#[test]
+fn same_key_in_different_compounds() {
+    // Each section has a Y, and the nested compound shares its key with the
+    // outer one. None of these are duplicates.
+    let data = Builder::new()
+        .start_compound("")
+        .start_list("Sections", Tag::Compound, 2)
+        .byte("Y", 0)
+        .end_compound()
+        .byte("Y", 1)
+        .end_compound()
+        .start_compound("a")
+        .byte("a", 1)
+        .end_compound()
+        .byte("b", 2)
+        .end_compound()
+        .build();
+
+    for policy in [DuplicateKeyPolicy::FirstWins, DuplicateKeyPolicy::Error] {
+        let value: Value = from_bytes_with_opts(&data, opts(policy)).unwrap();
+        assert_eq!(value, from_bytes::<Value>(&data).unwrap());
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    borrow,
+    de::{from_bytes, from_bytes_bedrock_network, from_bytes_le, from_reader},
+    from_value, to_bytes, to_bytes_bedrock_network, to_bytes_le, Tag, Value,
+};
+
+use super::builder::Builder;
+
+// Vanilla writes an empty list with an element tag of End, whatever the list
+// would hold if it had anything in it.
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct Section {
+    #[serde(rename = "Y")]
+    y: i8,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct Level {
+    sections: Vec<Section>,
+    entities: Vec<String>,
+    tile_entities: Vec<HashMap<String, Value>>,
+    lights: Vec<Vec<i16>>,
+}
+
+/// A freshly generated chunk, with nothing in any of its lists.
+fn fresh_level() -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .start_list("Sections", Tag::End, 0)
+        .start_list("Entities", Tag::End, 0)
+        .start_list("TileEntities", Tag::End, 0)
+        .start_list("Lights", Tag::List, 2)
+        .tag(Tag::End)
+        .int_payload(0)
+        .tag(Tag::End)
+        .int_payload(0)
+        .end_compound()
+        .build()
+}
+
+fn empty_level() -> Level {
+    Level {
+        sections: vec![],
+        entities: vec![],
+        tile_entities: vec![],
+        lights: vec![vec![], vec![]],
+    }
+}
+
+#[test]
+fn into_typed_vecs() {
+    let data = fresh_level();
+    assert_eq!(empty_level(), from_bytes(&data).unwrap());
+    assert_eq!(empty_level(), from_reader(data.as_slice()).unwrap());
+
+    let value: Value = from_bytes(&data).unwrap();
+    assert_eq!(empty_level(), from_value(&value).unwrap());
+}
+
+#[test]
+fn typed_vecs_serialize_as_list_of_end() {
+    let data = to_bytes(&Level {
+        sections: vec![],
+        entities: vec![],
+        tile_entities: vec![],
+        lights: vec![],
+    })
+    .unwrap();
+    let value: Value = from_bytes(&data).unwrap();
+    let compound = match value {
+        Value::Compound(c) => c,
+        _ => panic!(),
+    };
+    assert_eq!(4, compound.len());
+    assert!(compound.values().all(|v| *v == Value::List(vec![])));
+
+    // Byte for byte, with a single field so the order is known.
+    #[derive(Serialize)]
+    struct One {
+        #[serde(rename = "Entities")]
+        entities: Vec<String>,
+    }
+    let expected = Builder::new()
+        .start_compound("")
+        .start_list("Entities", Tag::End, 0)
+        .end_compound()
+        .build();
+    assert_eq!(expected, to_bytes(&One { entities: vec![] }).unwrap());
+}
+
+#[test]
+fn value_round_trips_exactly() {
+    let in_compound = Builder::new()
+        .start_compound("")
+        .start_compound("Level")
+        .start_list("Entities", Tag::End, 0)
+        .end_compound()
+        .end_compound()
+        .build();
+    let value: Value = from_bytes(&in_compound).unwrap();
+    assert_eq!(in_compound, to_bytes(&value).unwrap());
+
+    let in_list = Builder::new()
+        .start_compound("")
+        .start_list("Lights", Tag::List, 3)
+        .tag(Tag::End)
+        .int_payload(0)
+        .tag(Tag::Short)
+        .int_payload(1)
+        .short_payload(7)
+        .tag(Tag::End)
+        .int_payload(0)
+        .end_compound()
+        .build();
+    let value: Value = from_bytes(&in_list).unwrap();
+    assert_eq!(
+        Value::Compound(crate::Map::from([(
+            "Lights".to_owned(),
+            Value::List(vec![
+                Value::List(vec![]),
+                Value::List(vec![Value::Short(7)]),
+                Value::List(vec![]),
+            ])
+        )])),
+        value
+    );
+    assert_eq!(in_list, to_bytes(&value).unwrap());
+
+    let borrowed: borrow::Value = from_bytes(&in_list).unwrap();
+    assert_eq!(in_list, to_bytes(&borrowed).unwrap());
+}
+
+#[test]
+fn other_endians() {
+    let value: Value = from_bytes(&fresh_level()).unwrap();
+
+    let le = to_bytes_le(&value).unwrap();
+    assert_eq!(value, from_bytes_le::<Value>(&le).unwrap());
+    assert_eq!(empty_level(), from_bytes_le(&le).unwrap());
+
+    let network = to_bytes_bedrock_network(&value).unwrap();
+    assert_eq!(
+        value,
+        from_bytes_bedrock_network::<Value>(&network).unwrap()
+    );
+    assert_eq!(empty_level(), from_bytes_bedrock_network(&network).unwrap());
+}
+
+#[test]
+fn into_borrowed_bytes() {
+    #[derive(Deserialize)]
+    struct V<'a> {
+        #[serde(borrow)]
+        biomes: &'a [u8],
+    }
+
+    let data = Builder::new()
+        .start_compound("")
+        .start_list("biomes", Tag::End, 0)
+        .end_compound()
+        .build();
+    assert!(from_bytes::<V>(&data).unwrap().biomes.is_empty());
+}
+
+#[test]
+fn non_empty_list_of_end_is_an_error() {
+    let data = Builder::new()
+        .start_compound("")
+        .start_list("Entities", Tag::End, 1)
+        .end_compound()
+        .build();
+    assert!(from_bytes::<Level>(&data).is_err());
+    assert!(from_bytes::<Value>(&data).is_err());
+}
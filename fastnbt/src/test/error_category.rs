@@ -0,0 +1,152 @@
+use std::io::{self, Read};
+
+use serde::Deserialize;
+
+use crate::{
+    de::{from_bytes, from_bytes_with_opts, from_reader, DeOpts},
+    error::ErrorCategory,
+    to_bytes, Tag, Value,
+};
+
+use super::builder::Builder;
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Level {
+    #[serde(rename = "DataVersion")]
+    _data_version: i32,
+}
+
+fn level() -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .int("DataVersion", 2586)
+        .end_compound()
+        .build()
+}
+
+#[test]
+fn corrupt() {
+    let err = from_bytes::<Value>(&[10, 0, 0, 99]).unwrap_err();
+    assert_eq!(ErrorCategory::Corrupt, err.category());
+
+    let bad_size = Builder::new()
+        .start_compound("")
+        .start_list("list", Tag::Int, 1_000_000)
+        .end_compound()
+        .build();
+    let err = from_bytes::<Value>(&bad_size).unwrap_err();
+    assert_eq!(ErrorCategory::Corrupt, err.category());
+
+    let err = from_bytes::<Value>(&[8, 0, 0]).unwrap_err();
+    assert_eq!(ErrorCategory::Corrupt, err.category());
+}
+
+#[test]
+fn incomplete() {
+    let data = level();
+    let short = &data[..data.len() - 2];
+
+    let err = from_bytes::<Level>(short).unwrap_err();
+    assert_eq!(ErrorCategory::Incomplete, err.category());
+
+    let err = from_reader::<_, Level>(short).unwrap_err();
+    assert_eq!(ErrorCategory::Incomplete, err.category());
+}
+
+#[test]
+fn schema() {
+    // Errors raised by serde itself come through Error::custom.
+    let unknown = Builder::new()
+        .start_compound("")
+        .int("DataVersion", 2586)
+        .string("Status", "full")
+        .end_compound()
+        .build();
+    let err = from_bytes::<Level>(&unknown).unwrap_err();
+    assert!(err.to_string().contains("unknown field"), "{}", err);
+    assert_eq!(ErrorCategory::Schema, err.category());
+
+    let wrong_type = Builder::new()
+        .start_compound("")
+        .string("DataVersion", "2586")
+        .end_compound()
+        .build();
+    let err = from_bytes::<Level>(&wrong_type).unwrap_err();
+    assert_eq!(ErrorCategory::Schema, err.category());
+
+    #[derive(Deserialize, Debug)]
+    struct Uuid {
+        #[serde(rename = "UUID")]
+        _uuid: [i32; 4],
+    }
+    let short_uuid = Builder::new()
+        .start_compound("")
+        .int_array("UUID", &[1, 2, 3])
+        .end_compound()
+        .build();
+    let opts = DeOpts::new().lenient_arrays(true);
+    let err = from_bytes_with_opts::<Uuid>(&short_uuid, opts).unwrap_err();
+    assert_eq!(ErrorCategory::Schema, err.category());
+
+    // Writing a value that cannot be NBT.
+    let err = to_bytes(&Value::List(vec![Value::Int(1), Value::Byte(2)])).unwrap_err();
+    assert_eq!(ErrorCategory::Schema, err.category());
+}
+
+#[test]
+fn io() {
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "denied"))
+        }
+    }
+
+    let err = from_reader::<_, Level>(Broken).unwrap_err();
+    assert_eq!(ErrorCategory::Io, err.category());
+}
+
+#[test]
+fn public_constructors() {
+    use crate::error::{Error, ErrorKind};
+
+    let cases = [
+        (Error::invalid_tag(99), ErrorCategory::Corrupt),
+        (Error::invalid_size(-1), ErrorCategory::Corrupt),
+        (Error::no_root_compound(), ErrorCategory::Corrupt),
+        (Error::nonunicode_string(&[0xff]), ErrorCategory::Corrupt),
+        (Error::depth_limit_exceeded(512), ErrorCategory::Corrupt),
+        (Error::trailing_bytes(3), ErrorCategory::Corrupt),
+        (Error::duplicate_key("a"), ErrorCategory::Corrupt),
+        (Error::varint_too_long(32), ErrorCategory::Corrupt),
+        (Error::unexpected_eof(), ErrorCategory::Incomplete),
+        (
+            Error::heterogeneous_list(1, Tag::Int, Tag::Byte),
+            ErrorCategory::Schema,
+        ),
+        (Error::root_must_be_compound(), ErrorCategory::Schema),
+        (Error::invalid_key_type(Tag::Float), ErrorCategory::Schema),
+        (Error::array_length_mismatch(4, 3), ErrorCategory::Schema),
+        (
+            Error::unexpected_list(Tag::String, 24, "struct Section"),
+            ErrorCategory::Schema,
+        ),
+        (Error::custom_static("bad chunk"), ErrorCategory::Schema),
+    ];
+    for (err, category) in &cases {
+        assert_eq!(*category, err.category(), "{}", err);
+        assert_eq!(None, err.offset());
+        assert_eq!(None, err.path());
+    }
+
+    assert_eq!(&ErrorKind::InvalidTag(99), cases[0].0.kind());
+    assert_eq!(&ErrorKind::Other, cases[14].0.kind());
+    assert_eq!("bad chunk", cases[14].0.to_string());
+
+    // The same as fastnbt's own errors, other than where they happened.
+    let err = from_bytes::<Value>(&[10, 0, 0, 99]).unwrap_err();
+    assert_eq!(cases[0].0.kind(), err.kind());
+    assert!(err.to_string().starts_with(&cases[0].0.to_string()));
+}
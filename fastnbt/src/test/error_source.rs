@@ -0,0 +1,123 @@
+use std::error::Error as _;
+use std::io::{self, Read, Write};
+
+use crate::{
+    de::{from_bytes, from_reader},
+    error::{Error, ErrorKind},
+    to_bytes, to_writer, Value,
+};
+
+use super::builder::Builder;
+
+fn payload() -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .string("name", "level")
+        .end_compound()
+        .build()
+}
+
+/// Reads `data` and then fails with an io error of `kind`. Writes always fail.
+struct Failing<'a> {
+    data: &'a [u8],
+    kind: io::ErrorKind,
+}
+
+impl Read for Failing<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.data.is_empty() {
+            return Err(io::Error::new(self.kind, "connection lost"));
+        }
+        self.data.read(buf)
+    }
+}
+
+impl Write for Failing<'_> {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(self.kind, "disk full"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn io_errors_are_the_source() {
+    let data = payload();
+    let reader = Failing {
+        data: &data[..5],
+        kind: io::ErrorKind::ConnectionReset,
+    };
+    let err = from_reader::<_, Value>(reader).unwrap_err();
+
+    assert_eq!(&ErrorKind::Io, err.kind());
+    assert_eq!(Some(io::ErrorKind::ConnectionReset), err.io_kind());
+    assert!(!err.is_eof());
+    let source = err.source().unwrap();
+    assert_eq!("connection lost", source.to_string());
+    assert!(source.downcast_ref::<io::Error>().is_some());
+
+    // The message leaves the cause to the chain, so it is not repeated.
+    let chain = format!("{:#}", anyhow::Error::new(err.clone()));
+    assert_eq!(format!("{}: connection lost", err), chain);
+    assert_eq!(1, chain.matches("connection lost").count());
+
+    let err = anyhow::Error::new(err).context("reading level.dat");
+    assert!(
+        format!("{:#}", err).starts_with("reading level.dat: io error"),
+        "{:#}",
+        err
+    );
+    assert!(format!("{:#}", err).ends_with(": connection lost"));
+}
+
+#[test]
+fn writer_errors_are_the_source() {
+    let writer = Failing {
+        data: &[],
+        kind: io::ErrorKind::Other,
+    };
+    let value: Value = from_bytes(&payload()).unwrap();
+    let err = to_writer(writer, &value).unwrap_err();
+
+    assert_eq!(Some(io::ErrorKind::Other), err.io_kind());
+    assert_eq!("disk full", err.source().unwrap().to_string());
+}
+
+#[test]
+fn eof_from_reader_or_slice() {
+    let data = payload();
+
+    // Part way through the tag of the first entry, which the reader finds.
+    let err = from_reader::<_, Value>(&data[..3]).unwrap_err();
+    assert!(err.is_eof());
+    assert_eq!(Some(io::ErrorKind::UnexpectedEof), err.io_kind());
+    assert!(err.source().is_some());
+
+    // Part way through a string, which we find from its length.
+    let short = &data[..data.len() - 3];
+    let err = from_reader::<_, Value>(short).unwrap_err();
+    assert!(err.is_eof());
+
+    // Reading a slice never involves an io error, but is still an eof.
+    let err = from_bytes::<Value>(short).unwrap_err();
+    assert!(err.is_eof());
+    assert_eq!(None, err.io_kind());
+    assert!(err.source().is_none());
+}
+
+#[test]
+fn other_errors_have_no_source() {
+    let err = from_bytes::<Value>(&[10, 0, 0, 99]).unwrap_err();
+    assert!(!err.is_eof());
+    assert_eq!(None, err.io_kind());
+    assert!(err.source().is_none());
+
+    let err: Error = to_bytes(&1u8).unwrap_err();
+    assert!(err.source().is_none());
+    assert_eq!(
+        format!("{}", err),
+        format!("{:#}", anyhow::Error::new(err.clone()))
+    );
+}
@@ -1,4 +1,12 @@
-use crate::{de::from_bytes, error::Result, test::builder::Builder, Tag, Value};
+use serde::Deserialize;
+
+use crate::{
+    borrow,
+    de::{from_bytes, from_reader},
+    error::{ErrorKind, Result},
+    test::builder::Builder,
+    ByteArray, IntArray, LongArray, Tag, Value,
+};
 
 /// Bugs found via cargo-fuzz.
 
@@ -21,3 +29,150 @@ fn list_of_end() {
     let v: Result<Value> = from_bytes(&input);
     assert!(v.is_err());
 }
+
+const ABSURD: i32 = 1 << 30;
+
+/// A compound with a value called "a" of the given tag, where the value is
+/// just a size of `ABSURD` followed by a few bytes.
+fn absurd_size(tag: Tag) -> Vec<u8> {
+    let builder = Builder::new().start_compound("").tag(tag).name("a");
+    let builder = match tag {
+        Tag::List => builder.tag(Tag::Long),
+        _ => builder,
+    };
+    builder
+        .int_payload(ABSURD)
+        .raw_bytes(&[0; 16])
+        .end_compound()
+        .build()
+}
+
+fn assert_absurd_size_rejected<'a, T>(input: &'a [u8])
+where
+    T: Deserialize<'a> + std::fmt::Debug,
+{
+    let err = from_bytes::<T>(input).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidSize(ABSURD), err.kind(), "{}", err);
+    assert_eq!(Some(7), err.offset());
+}
+
+#[test]
+fn absurd_array_sizes() {
+    #[derive(Deserialize, Debug)]
+    struct Arrays {
+        #[allow(dead_code)]
+        a: Option<ByteArray>,
+        #[allow(dead_code)]
+        b: Option<IntArray>,
+        #[allow(dead_code)]
+        c: Option<LongArray>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Skipped {}
+
+    for tag in [Tag::ByteArray, Tag::IntArray, Tag::LongArray] {
+        let input = absurd_size(tag);
+        assert_absurd_size_rejected::<Value>(&input);
+        assert_absurd_size_rejected::<borrow::Value>(&input);
+        assert_absurd_size_rejected::<Arrays>(&input);
+        assert_absurd_size_rejected::<Skipped>(&input);
+
+        // A reader's length is not known, but it should still run out of
+        // input rather than allocate for the whole size.
+        assert!(from_reader::<_, Value>(input.as_slice()).is_err());
+        assert!(from_reader::<_, Arrays>(input.as_slice()).is_err());
+        assert!(from_reader::<_, Skipped>(input.as_slice()).is_err());
+    }
+}
+
+#[test]
+fn absurd_array_size_as_bytes() {
+    #[derive(Deserialize, Debug)]
+    struct Bytes<'a> {
+        #[allow(dead_code)]
+        a: &'a [u8],
+    }
+
+    for tag in [Tag::ByteArray, Tag::IntArray, Tag::LongArray] {
+        let input = absurd_size(tag);
+        let err = from_bytes::<Bytes>(&input).unwrap_err();
+        assert_eq!(&ErrorKind::InvalidSize(ABSURD), err.kind(), "{}", err);
+    }
+}
+
+#[test]
+fn absurd_list_size() {
+    #[derive(Deserialize, Debug)]
+    struct List {
+        #[allow(dead_code)]
+        a: Vec<i64>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Skipped {}
+
+    let input = absurd_size(Tag::List);
+    let err = from_bytes::<Value>(&input).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidSize(ABSURD), err.kind(), "{}", err);
+    assert_eq!(Some(8), err.offset());
+
+    for result in [
+        from_bytes::<borrow::Value>(&input).map(|_| ()),
+        from_bytes::<List>(&input).map(|_| ()),
+        from_bytes::<Skipped>(&input).map(|_| ()),
+    ] {
+        assert_eq!(&ErrorKind::InvalidSize(ABSURD), result.unwrap_err().kind());
+    }
+
+    assert!(from_reader::<_, Value>(input.as_slice()).is_err());
+    assert!(from_reader::<_, List>(input.as_slice()).is_err());
+    assert!(from_reader::<_, Skipped>(input.as_slice()).is_err());
+}
+
+#[test]
+fn absurd_list_of_compounds() {
+    // Even empty compounds take a byte each.
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("a", Tag::Compound, ABSURD)
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<Value>(&input).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidSize(ABSURD), err.kind(), "{}", err);
+    assert!(from_reader::<_, Value>(input.as_slice()).is_err());
+}
+
+#[test]
+fn absurd_string_length() {
+    // String lengths are only 16 bits, so are never worth rejecting early.
+    let input = Builder::new()
+        .start_compound("")
+        .tag(Tag::String)
+        .name("a")
+        .raw_len(u16::MAX as usize)
+        .raw_bytes(b"abc")
+        .build();
+
+    let err = from_bytes::<Value>(&input).unwrap_err();
+    assert_eq!(&ErrorKind::UnexpectedEof, err.kind());
+    let err = from_reader::<_, Value>(input.as_slice()).unwrap_err();
+    assert_eq!(&ErrorKind::UnexpectedEof, err.kind());
+}
+
+#[test]
+fn skipped_list_of_end() {
+    #[derive(Deserialize, Debug)]
+    struct Skipped {}
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("", Tag::End, 1)
+        .tag(Tag::End)
+        .end_compound()
+        .build();
+
+    assert!(from_bytes::<Skipped>(&input).is_err());
+}
@@ -0,0 +1,147 @@
+use std::borrow::Cow;
+
+use serde::Deserialize;
+
+use crate::{
+    borrow,
+    de::{from_bytes, get_path, Deserializer},
+    LongArray, Value,
+};
+
+use super::builder::level;
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+fn get<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, segment| match value {
+        Value::Compound(c) => c.get(*segment),
+        Value::List(l) => l.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+#[test]
+fn same_as_full_value() {
+    let full: Value = from_bytes(CHUNK_RAW).unwrap();
+
+    for path in [
+        &["DataVersion"][..],
+        &["Level", "xPos"],
+        &["Level", "zPos"],
+        &["Level", "Status"],
+    ] {
+        let value: Value = get_path(CHUNK_RAW, path).unwrap().unwrap();
+        assert_eq!(get(&full, path).unwrap(), &value, "{:?}", path);
+    }
+
+    let sections = match get(&full, &["Level", "Sections"]) {
+        Some(Value::List(sections)) => sections.len(),
+        _ => panic!("no sections"),
+    };
+    for i in 0..sections {
+        let i = i.to_string();
+        let path = ["Level", "Sections", &i, "Y"];
+        let y: Value = get_path(CHUNK_RAW, &path).unwrap().unwrap();
+        assert_eq!(get(&full, &path).unwrap(), &y);
+    }
+}
+
+#[test]
+fn typed_values() {
+    let data = level();
+
+    assert_eq!(
+        Some(2586),
+        get_path::<i32>(&data, &["DataVersion"]).unwrap()
+    );
+    assert_eq!(
+        Some(-3),
+        get_path::<i64>(&data, &["Level", "xPos"]).unwrap()
+    );
+    assert_eq!(
+        Some("full"),
+        get_path::<&str>(&data, &["Level", "Status"]).unwrap()
+    );
+    assert_eq!(
+        Some(1),
+        get_path::<u8>(&data, &["Level", "Sections", "1", "Y"]).unwrap()
+    );
+
+    let states: LongArray = get_path(&data, &["Level", "BlockStates"]).unwrap().unwrap();
+    assert_eq!(&[1, 2, 3], &states[..]);
+
+    let states: borrow::LongArray = get_path(&data, &["Level", "BlockStates"]).unwrap().unwrap();
+    assert_eq!(vec![1, 2, 3], states.iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn compounds_and_lists() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "PascalCase")]
+    struct Section<'a> {
+        y: i8,
+        name: Option<Cow<'a, str>>,
+    }
+
+    let data = level();
+
+    let section: Section = get_path(&data, &["Level", "Sections", "1"])
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        Section {
+            y: 1,
+            name: Some("second".into())
+        },
+        section
+    );
+
+    let sections: Vec<Section> = get_path(&data, &["Level", "Sections"]).unwrap().unwrap();
+    assert_eq!(2, sections.len());
+
+    // The empty path is the root compound.
+    let root: Value = get_path(&data, &[]).unwrap().unwrap();
+    assert_eq!(from_bytes::<Value>(&data).unwrap(), root);
+}
+
+#[test]
+fn missing_values() {
+    let data = level();
+
+    for path in [
+        &["zPos"][..],
+        &["Level", "zPos"],
+        &["Level", "Sections", "2", "Y"],
+        &["Level", "Sections", "-1", "Y"],
+        &["Level", "Sections", "Y"],
+        &["Level", "Status", "Y"],
+        &["Level", "BlockStates", "0"],
+    ] {
+        assert_eq!(None, get_path::<Value>(&data, path).unwrap(), "{:?}", path);
+    }
+}
+
+#[test]
+fn wrong_type_is_an_error() {
+    let data = level();
+
+    let err = get_path::<i32>(&data, &["Level", "Sections", "1", "Name"]).unwrap_err();
+    assert_eq!(Some("Level.Sections[1].Name"), err.path());
+    assert!(err.offset().is_some());
+}
+
+#[test]
+fn truncated_input_is_an_error() {
+    let data = level();
+    assert!(get_path::<i32>(&data[..20], &["Level", "xPos"]).is_err());
+}
+
+#[test]
+fn from_reader() {
+    let data = level();
+    let mut de = Deserializer::from_reader(data.as_slice());
+    let name: Option<String> = de
+        .deserialize_path(&["Level", "Sections", "1", "Name"])
+        .unwrap();
+    assert_eq!(Some("second".to_owned()), name);
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    de::{from_bytes, from_bytes_with_opts, DeOpts, DuplicateKeyPolicy},
+    ser::Serializer,
+    to_bytes, Tag, Value,
+};
+
+use super::builder::Builder;
+
+// Ipv4Addr is a string when human readable, and a tuple of its four bytes
+// when not.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Server {
+    ip: Ipv4Addr,
+}
+
+fn server() -> Server {
+    Server {
+        ip: Ipv4Addr::new(127, 0, 0, 1),
+    }
+}
+
+fn to_bytes_readable<T: Serialize>(v: &T) -> Vec<u8> {
+    let mut ser = Serializer::new(Vec::new()).human_readable(true);
+    v.serialize(&mut ser).unwrap();
+    ser.into_inner()
+}
+
+#[test]
+fn binary_by_default() {
+    let data = to_bytes(&server()).unwrap();
+    let expected = Builder::new()
+        .start_compound("")
+        .start_list("ip", Tag::Byte, 4)
+        .byte_payload(127)
+        .byte_payload(0)
+        .byte_payload(0)
+        .byte_payload(1)
+        .end_compound()
+        .build();
+
+    assert_eq!(expected, data);
+    assert_eq!(server(), from_bytes(&data).unwrap());
+}
+
+#[test]
+fn human_readable() {
+    let data = to_bytes_readable(&server());
+    let expected = Builder::new()
+        .start_compound("")
+        .string("ip", "127.0.0.1")
+        .end_compound()
+        .build();
+
+    assert_eq!(expected, data);
+
+    let opts = DeOpts::new().human_readable(true);
+    assert_eq!(server(), from_bytes_with_opts(&data, opts).unwrap());
+
+    // The value is the same whatever type reads it.
+    let value: Value = from_bytes_with_opts(&data, opts).unwrap();
+    assert_eq!(value, from_bytes::<Value>(&data).unwrap());
+}
+
+#[test]
+fn mismatched_modes_are_an_error() {
+    let data = to_bytes_readable(&server());
+    assert!(from_bytes::<Server>(&data).is_err());
+
+    let data = to_bytes(&server()).unwrap();
+    let opts = DeOpts::new().human_readable(true);
+    assert!(from_bytes_with_opts::<Server>(&data, opts).is_err());
+}
+
+#[test]
+fn names_are_always_human_readable() {
+    let servers = HashMap::from([(Ipv4Addr::new(10, 0, 0, 1), 25565)]);
+    let expected = Builder::new()
+        .start_compound("")
+        .int("10.0.0.1", 25565)
+        .end_compound()
+        .build();
+
+    let data = to_bytes(&servers).unwrap();
+    assert_eq!(expected, data);
+    assert_eq!(expected, to_bytes_readable(&servers));
+
+    assert_eq!(servers, from_bytes(&data).unwrap());
+    let opts = DeOpts::new().on_duplicate_key(DuplicateKeyPolicy::Error);
+    assert_eq!(servers, from_bytes_with_opts(&data, opts).unwrap());
+    let opts = DeOpts::new().human_readable(true);
+    assert_eq!(servers, from_bytes_with_opts(&data, opts).unwrap());
+}
@@ -0,0 +1,146 @@
+use std::borrow::Cow;
+
+use crate::{borrow, de::from_bytes, LongArray, Map, Value};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+fn empty() -> Value {
+    Value::List(vec![])
+}
+
+#[test]
+fn chained_through_chunk() {
+    let chunk: Value = from_bytes(CHUNK_RAW).unwrap();
+    assert_eq!(Value::String("full".to_owned()), chunk["Level"]["Status"]);
+
+    let sections = match &chunk["Level"]["Sections"] {
+        Value::List(sections) => sections,
+        other => panic!("{:?}", other),
+    };
+    assert!(!sections.is_empty());
+    for (i, section) in sections.iter().enumerate() {
+        assert_eq!(&section["Y"], &chunk["Level"]["Sections"][i]["Y"]);
+        assert!(matches!(section["Y"], Value::Byte(_)));
+    }
+}
+
+#[test]
+fn missing_paths_give_empty_list() {
+    let chunk: Value = from_bytes(CHUNK_RAW).unwrap();
+    assert_eq!(empty(), chunk["Missing"]);
+    assert_eq!(empty(), chunk["Missing"]["Deeper"][12]["Still"]);
+    assert_eq!(empty(), chunk["Level"]["Sections"][10_000]);
+    assert_eq!(empty(), chunk["Level"]["Sections"][10_000]["Y"]);
+}
+
+#[test]
+fn wrong_variant_gives_empty_list() {
+    let chunk: Value = from_bytes(CHUNK_RAW).unwrap();
+    // A compound by position, a list by key, and a scalar either way.
+    assert_eq!(empty(), chunk[0]);
+    assert_eq!(empty(), chunk["Level"]["Sections"]["Y"]);
+    assert_eq!(empty(), chunk["Level"]["Status"]["full"]);
+    assert_eq!(empty(), chunk["Level"]["xPos"][0]);
+    assert_eq!(empty(), chunk["Level"]["Biomes"][0]);
+}
+
+#[test]
+fn get_tells_missing_from_empty() {
+    let value = Value::Compound(Map::from([("empty".to_owned(), empty())]));
+    assert_eq!(Some(&empty()), value.get("empty"));
+    assert_eq!(None, value.get("missing"));
+    assert_eq!(None, value.get(0));
+    assert_eq!(None, empty().get(0));
+}
+
+#[test]
+fn string_keys() {
+    let value = Value::Compound(Map::from([("a".to_owned(), Value::Int(1))]));
+    let key = "a".to_owned();
+    assert_eq!(Value::Int(1), value[&key]);
+    assert_eq!(Value::Int(1), value[key.clone()]);
+    assert_eq!(Some(&Value::Int(1)), value.get(&key));
+}
+
+#[test]
+fn index_mut_changes_in_place() {
+    let mut chunk: Value = from_bytes(CHUNK_RAW).unwrap();
+    chunk["Level"]["Status"] = Value::String("empty".to_owned());
+    chunk["Level"]["Sections"][0]["Y"] = Value::Byte(-4);
+    assert_eq!(Value::String("empty".to_owned()), chunk["Level"]["Status"]);
+    assert_eq!(Value::Byte(-4), chunk["Level"]["Sections"][0]["Y"]);
+
+    if let Some(Value::Int(x)) = chunk["Level"].get_mut("xPos") {
+        *x += 1;
+    }
+    let original: Value = from_bytes(CHUNK_RAW).unwrap();
+    match (&chunk["Level"]["xPos"], &original["Level"]["xPos"]) {
+        (Value::Int(x), Value::Int(before)) => assert_eq!(before + 1, *x),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn index_mut_inserts_missing_keys() {
+    let mut value = Value::Compound(Map::new());
+    value["Level"]["Heightmaps"]["OCEAN_FLOOR"] = Value::LongArray(LongArray::new(vec![1]));
+    value["Level"]["xPos"] = Value::Int(3);
+
+    let expected = Value::Compound(Map::from([(
+        "Level".to_owned(),
+        Value::Compound(Map::from([
+            (
+                "Heightmaps".to_owned(),
+                Value::Compound(Map::from([(
+                    "OCEAN_FLOOR".to_owned(),
+                    Value::LongArray(LongArray::new(vec![1])),
+                )])),
+            ),
+            ("xPos".to_owned(), Value::Int(3)),
+        ])),
+    )]));
+    assert_eq!(expected, value);
+
+    // Just looking mutably inserts an empty compound.
+    let _ = &mut value["Other"];
+    assert_eq!(Some(&Value::Compound(Map::new())), value.get("Other"));
+}
+
+#[test]
+#[should_panic(expected = "cannot index a List of length 1 with 1")]
+fn index_mut_out_of_range_panics() {
+    let mut value = Value::List(vec![Value::Int(1)]);
+    value[1] = Value::Int(2);
+}
+
+#[test]
+#[should_panic(expected = "cannot index an Int with \"a\"")]
+fn index_mut_key_of_scalar_panics() {
+    let mut value = Value::Int(1);
+    value["a"] = Value::Int(2);
+}
+
+#[test]
+#[should_panic(expected = "cannot index a Compound with 0")]
+fn index_mut_position_of_compound_panics() {
+    let mut value = Value::Compound(Map::new());
+    value[0] = Value::Int(2);
+}
+
+#[test]
+fn borrowed_value() {
+    let mut chunk: borrow::Value = from_bytes(CHUNK_RAW).unwrap();
+    assert_eq!(
+        borrow::Value::String(Cow::Borrowed("full")),
+        chunk["Level"]["Status"]
+    );
+    assert!(matches!(
+        chunk["Level"]["Sections"][0]["Y"],
+        borrow::Value::Byte(_)
+    ));
+    assert_eq!(borrow::Value::List(vec![]), chunk["Missing"]["Deeper"][12]);
+    assert_eq!(None, chunk.get("Missing"));
+
+    chunk["Level"]["Added"]["Value"] = borrow::Value::Int(1);
+    assert_eq!(borrow::Value::Int(1), chunk["Level"]["Added"]["Value"]);
+}
@@ -0,0 +1,96 @@
+use crate::java_cesu8::{decode, decode_lossy, encode};
+
+const STRINGS: &[&str] = &[
+    "",
+    "minecraft:stone",
+    "nul \0 in the middle",
+    "\0",
+    "é and ✓",
+    "😈",
+    "a😈b\0c🦀",
+    "\u{7f}\u{80}\u{7ff}\u{800}\u{ffff}\u{10000}\u{10ffff}",
+];
+
+#[test]
+fn encode_matches_cesu8_crate() {
+    for s in STRINGS {
+        assert_eq!(cesu8::to_java_cesu8(s), encode(s), "{:?}", s);
+    }
+}
+
+#[test]
+fn decode_matches_cesu8_crate() {
+    for s in STRINGS {
+        let encoded = cesu8::to_java_cesu8(s);
+        assert_eq!(Some(*s), decode(&encoded).as_deref(), "{:?}", s);
+        assert_eq!(*s, decode_lossy(&encoded));
+    }
+}
+
+#[test]
+fn plain_utf8_is_borrowed() {
+    assert!(matches!(encode("abc ✓"), std::borrow::Cow::Borrowed(_)));
+    // Java would never write a 4 byte character, but it is still accepted.
+    assert!(matches!(
+        decode("🦀".as_bytes()),
+        Some(std::borrow::Cow::Borrowed("🦀"))
+    ));
+}
+
+#[test]
+fn invalid_input_matches_cesu8_crate() {
+    let invalid: &[&[u8]] = &[
+        // Overlong encodings, other than C0 80 for NUL.
+        &[0xc0, 0x81],
+        &[0xc1, 0xbf],
+        &[0xe0, 0x80, 0x80],
+        // A raw NUL alongside something that is not UTF-8.
+        &[0x00, 0xed, 0xa0, 0xbd],
+        // Surrogates on their own or the wrong way round.
+        &[0xed, 0xa0, 0xbd],
+        &[0xed, 0xb8, 0x88],
+        &[0xed, 0xb8, 0x88, 0xed, 0xa0, 0xbd],
+        &[0xed, 0xa0, 0xbd, 0x41],
+        // Truncated sequences.
+        &[0xc3],
+        &[0xe2, 0x9c],
+        &[0xff],
+    ];
+
+    for data in invalid {
+        assert!(cesu8::from_java_cesu8(data).is_err(), "{:x?}", data);
+        assert_eq!(None, decode(data), "{:x?}", data);
+        assert!(decode_lossy(data).contains(char::REPLACEMENT_CHARACTER));
+    }
+}
+
+#[test]
+fn random_bytes_match_cesu8_crate() {
+    // A small xorshift, biased toward the bytes that start and continue
+    // multi-byte sequences.
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    for _ in 0..20_000 {
+        let len = (next() % 12) as usize;
+        let data: Vec<u8> = (0..len)
+            .map(|_| match next() % 4 {
+                0 => next() as u8,
+                1 => 0x80 | (next() as u8 & 0x3f),
+                2 => [0x00, 0xc0, 0xed, 0xe0][(next() % 4) as usize],
+                _ => 0xa0 | (next() as u8 & 0x1f),
+            })
+            .collect();
+
+        let expected = cesu8::from_java_cesu8(&data).ok();
+        assert_eq!(expected, decode(&data), "{:x?}", data);
+        if let Some(s) = expected {
+            assert_eq!(s, decode_lossy(&data));
+        }
+    }
+}
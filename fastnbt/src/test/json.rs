@@ -0,0 +1,182 @@
+use std::convert::TryFrom;
+
+use serde_json::json;
+
+use crate::{de::from_bytes, Map, Tag, Value};
+
+use super::builder::Builder;
+
+#[test]
+fn chunk_like_value_to_json() {
+    let input = Builder::new()
+        .start_compound("")
+        .byte("byte", 1)
+        .short("short", 2)
+        .int("int", 3)
+        .long("long", 4)
+        .float("float", 0.5)
+        .double("double", 0.25)
+        .string("string", "minecraft:stone")
+        .byte_array("bytes", &[1, 2])
+        .int_array("ints", &[3, 4])
+        .long_array("longs", &[5, 6])
+        .start_list("list", Tag::Int, 2)
+        .int_payload(7)
+        .int_payload(8)
+        .start_compound("nested")
+        .string("a", "b")
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let v: Value = from_bytes(&input).unwrap();
+    let actual = serde_json::Value::from(v);
+
+    let expected = json!({
+        "byte": 1,
+        "short": 2,
+        "int": 3,
+        "long": 4,
+        "float": 0.5,
+        "double": 0.25,
+        "string": "minecraft:stone",
+        "bytes": [1, 2],
+        "ints": [3, 4],
+        "longs": [5, 6],
+        "list": [7, 8],
+        "nested": { "a": "b" },
+    });
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn long_outside_safe_range_becomes_string() {
+    let max_safe = (1i64 << 53) - 1;
+
+    assert_eq!(
+        json!(max_safe),
+        serde_json::Value::from(Value::Long(max_safe))
+    );
+    assert_eq!(
+        json!(-max_safe),
+        serde_json::Value::from(Value::Long(-max_safe))
+    );
+    assert_eq!(
+        json!("9007199254740992"),
+        serde_json::Value::from(Value::Long(max_safe + 1))
+    );
+    assert_eq!(
+        json!(i64::MIN.to_string()),
+        serde_json::Value::from(Value::Long(i64::MIN))
+    );
+}
+
+#[test]
+fn long_array_elements_follow_long_policy() {
+    let input = Builder::new()
+        .start_compound("")
+        .long_array("longs", &[1, i64::MAX])
+        .end_compound()
+        .build();
+
+    let v: Value = from_bytes(&input).unwrap();
+
+    assert_eq!(
+        json!({"longs": [1, i64::MAX.to_string()]}),
+        serde_json::Value::from(v)
+    );
+}
+
+#[test]
+fn non_finite_floats_become_null() {
+    assert_eq!(json!(null), serde_json::Value::from(Value::Float(f32::NAN)));
+    assert_eq!(
+        json!(null),
+        serde_json::Value::from(Value::Double(f64::INFINITY))
+    );
+}
+
+#[test]
+fn json_integers_pick_int_or_long() {
+    assert_eq!(Value::Int(0), Value::try_from(json!(0)).unwrap());
+    assert_eq!(
+        Value::Int(i32::MAX),
+        Value::try_from(json!(i32::MAX)).unwrap()
+    );
+    assert_eq!(
+        Value::Long(i32::MAX as i64 + 1),
+        Value::try_from(json!(i32::MAX as i64 + 1)).unwrap()
+    );
+    assert_eq!(
+        Value::Long(i64::MIN),
+        Value::try_from(json!(i64::MIN)).unwrap()
+    );
+    assert!(Value::try_from(json!(u64::MAX)).is_err());
+}
+
+#[test]
+fn json_bool_and_float() {
+    assert_eq!(Value::Byte(1), Value::try_from(json!(true)).unwrap());
+    assert_eq!(Value::Byte(0), Value::try_from(json!(false)).unwrap());
+    assert_eq!(Value::Double(1.5), Value::try_from(json!(1.5)).unwrap());
+}
+
+#[test]
+fn json_null_rejected_even_when_nested() {
+    assert!(Value::try_from(json!(null)).is_err());
+    assert!(Value::try_from(json!({"a": [1, null]})).is_err());
+}
+
+#[test]
+fn round_trip_through_json() {
+    let mut inner = Map::new();
+    inner.insert(
+        "Name".to_owned(),
+        Value::String("minecraft:stone".to_owned()),
+    );
+    inner.insert("Count".to_owned(), Value::Int(3));
+
+    let mut compound = Map::new();
+    compound.insert("Pos".to_owned(), Value::List(vec![Value::Double(1.5)]));
+    compound.insert("Item".to_owned(), Value::Compound(inner));
+    compound.insert("Time".to_owned(), Value::Long(1 << 40));
+    let original = Value::Compound(compound);
+
+    let json = serde_json::Value::from(original.clone());
+    let back = Value::try_from(json).unwrap();
+
+    assert_eq!(original, back);
+}
+
+#[test]
+fn lossy_round_trip_corners() {
+    // Narrow integer types widen to Int.
+    let json = serde_json::Value::from(Value::Byte(5));
+    assert_eq!(Value::Int(5), Value::try_from(json).unwrap());
+
+    // Floats widen to Double.
+    let json = serde_json::Value::from(Value::Float(0.5));
+    assert_eq!(Value::Double(0.5), Value::try_from(json).unwrap());
+
+    // Array types become lists.
+    let input = Builder::new()
+        .start_compound("")
+        .int_array("a", &[1, 2])
+        .end_compound()
+        .build();
+    let v: Value = from_bytes(&input).unwrap();
+    let back = Value::try_from(serde_json::Value::from(v)).unwrap();
+    assert_eq!(json!({"a": [1, 2]}), serde_json::Value::from(back.clone()));
+    match back {
+        Value::Compound(c) => assert_eq!(Value::List(vec![Value::Int(1), Value::Int(2)]), c["a"]),
+        _ => panic!("expected compound"),
+    }
+
+    // Unsafe longs come back as strings.
+    let json = serde_json::Value::from(Value::Long(i64::MAX));
+    assert_eq!(
+        Value::String(i64::MAX.to_string()),
+        Value::try_from(json).unwrap()
+    );
+}
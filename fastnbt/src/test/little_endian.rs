@@ -0,0 +1,234 @@
+use std::convert::TryInto;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    borrow,
+    de::{from_bytes, from_bytes_le, DeOpts, Deserializer},
+    to_bytes, to_bytes_le, IntArray, LongArray, Map, Value,
+};
+
+// A Bedrock level.dat, including the 8 byte header of a storage version and
+// the length of the NBT that follows.
+const LEVEL_DAT: &[u8] = include_bytes!("resources/bedrock_level.dat");
+
+fn strip_header(data: &[u8]) -> &[u8] {
+    let len = i32::from_le_bytes(data[4..8].try_into().unwrap());
+    assert_eq!(len as usize, data.len() - 8);
+    &data[8..]
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct LevelDat {
+    level_name: String,
+    random_seed: i64,
+    spawn_x: i32,
+    spawn_y: i32,
+    spawn_z: i32,
+    storage_version: i32,
+    #[serde(rename = "abilities")]
+    abilities: Abilities,
+    #[serde(rename = "lastOpenedWithVersion")]
+    last_opened_with_version: Vec<i32>,
+    #[serde(rename = "currentTick")]
+    current_tick: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Abilities {
+    fly_speed: f32,
+    mayfly: bool,
+    walk_speed: f32,
+}
+
+#[test]
+fn bedrock_level_dat() {
+    let level: LevelDat = from_bytes_le(strip_header(LEVEL_DAT)).unwrap();
+
+    assert_eq!("Bedrock level ✓", level.level_name);
+    assert_eq!(-4172144997902289642, level.random_seed);
+    assert_eq!(
+        (128, 32767, -64),
+        (level.spawn_x, level.spawn_y, level.spawn_z)
+    );
+    assert_eq!(10, level.storage_version);
+    assert_eq!(0.05, level.abilities.fly_speed);
+    assert!(level.abilities.mayfly);
+    assert_eq!(vec![1, 20, 30, 2, 0], level.last_opened_with_version);
+    assert_eq!(123456, level.current_tick);
+}
+
+#[test]
+fn bedrock_level_dat_is_not_big_endian() {
+    assert!(from_bytes::<LevelDat>(strip_header(LEVEL_DAT)).is_err());
+}
+
+#[test]
+fn value_round_trip() {
+    let nbt = strip_header(LEVEL_DAT);
+    let value: Value = from_bytes_le(nbt).unwrap();
+
+    let bytes = to_bytes_le(&value).unwrap();
+    assert_eq!(nbt.len(), bytes.len());
+    assert_eq!(value, from_bytes_le::<Value>(&bytes).unwrap());
+
+    // The same value written big-endian reads back the same.
+    let be = to_bytes(&value).unwrap();
+    assert_ne!(be, bytes);
+    assert_eq!(value, from_bytes::<Value>(&be).unwrap());
+}
+
+#[test]
+fn struct_round_trip() {
+    let level: LevelDat = from_bytes_le(strip_header(LEVEL_DAT)).unwrap();
+    let bytes = to_bytes_le(&level).unwrap();
+    assert_eq!(level, from_bytes_le(&bytes).unwrap());
+}
+
+#[test]
+fn exact_bytes() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct V {
+        s: i16,
+        name: String,
+        list: Vec<i32>,
+        longs: LongArray,
+        f: f32,
+    }
+
+    let v = V {
+        s: 0x0102,
+        name: "🦀".to_owned(),
+        list: vec![3],
+        longs: LongArray::new(vec![-2]),
+        f: 1.0,
+    };
+
+    #[rustfmt::skip]
+    let expected: &[u8] = &[
+        10, 0, 0,
+            2, 1, 0, b's', 2, 1,
+            8, 4, 0, b'n', b'a', b'm', b'e', 4, 0, 0xf0, 0x9f, 0xa6, 0x80,
+            9, 4, 0, b'l', b'i', b's', b't', 3, 1, 0, 0, 0, 3, 0, 0, 0,
+            12, 5, 0, b'l', b'o', b'n', b'g', b's', 1, 0, 0, 0,
+                0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            5, 1, 0, b'f', 0, 0, 0x80, 0x3f,
+        0,
+    ];
+
+    assert_eq!(expected, to_bytes_le(&v).unwrap());
+    assert_eq!(v, from_bytes_le(expected).unwrap());
+}
+
+#[test]
+fn borrowed_arrays() {
+    #[derive(Serialize)]
+    struct Owned {
+        ints: IntArray,
+        longs: LongArray,
+    }
+
+    #[derive(Deserialize)]
+    struct Borrowed<'a> {
+        #[serde(borrow)]
+        ints: borrow::IntArray<'a>,
+        #[serde(borrow)]
+        longs: borrow::LongArray<'a>,
+    }
+
+    let bytes = to_bytes_le(&Owned {
+        ints: IntArray::new(vec![1, -2, 0x01020304]),
+        longs: LongArray::new(vec![1, -2, 0x0102030405060708]),
+    })
+    .unwrap();
+
+    let borrowed: Borrowed = from_bytes_le(&bytes).unwrap();
+    assert_eq!(
+        vec![1, -2, 0x01020304],
+        borrowed.ints.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![1, -2, 0x0102030405060708],
+        borrowed.longs.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![0x01020304, -2, 1],
+        borrowed.ints.iter().rev().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![0x0102030405060708, -2, 1],
+        borrowed.longs.iter().rev().collect::<Vec<_>>()
+    );
+    assert_eq!(Some(0x01020304), borrowed.ints.get(2));
+    assert_eq!(0x0102030405060708, borrowed.longs.at(2));
+    assert_eq!(vec![1, -2, 0x01020304], borrowed.ints.to_vec());
+    assert_eq!(
+        Some(0x01020304),
+        borrowed.ints.chunks(2).last().unwrap().get(0)
+    );
+    assert!(borrowed
+        .longs
+        .take_section(2, 1)
+        .unwrap()
+        .iter()
+        .eq([0x0102030405060708]));
+    let mut longs = [0; 3];
+    borrowed.longs.copy_into_slice(&mut longs).unwrap();
+    assert_eq!([1, -2, 0x0102030405060708], longs);
+    assert_eq!(
+        LongArray::new(vec![1, -2, 0x0102030405060708]),
+        borrowed.longs.to_owned()
+    );
+
+    // Compared by value, so equal to the same array read as big-endian.
+    let be = borrow::LongArray::from_be_bytes(&[
+        0, 0, 0, 0, 0, 0, 0, 1, 255, 255, 255, 255, 255, 255, 255, 254, 1, 2, 3, 4, 5, 6, 7, 8,
+    ])
+    .unwrap();
+    assert_eq!(be, borrowed.longs);
+    assert_eq!(borrowed.ints, [1, -2, 0x01020304]);
+    assert_ne!(borrowed.ints, [0x01000000, -2, 0x01020304]);
+}
+
+#[test]
+fn borrowed_arrays_from_lists() {
+    #[derive(Serialize)]
+    struct Listed {
+        longs: Vec<i64>,
+    }
+
+    #[derive(Deserialize)]
+    struct Borrowed<'a> {
+        #[serde(borrow)]
+        longs: borrow::LongArray<'a>,
+    }
+
+    let bytes = to_bytes_le(&Listed { longs: vec![3, -4] }).unwrap();
+
+    let mut de = Deserializer::from_bytes_le(&bytes).with_opts(DeOpts::new().lenient_arrays(true));
+    let borrowed = Borrowed::deserialize(&mut de).unwrap();
+    assert_eq!(vec![3, -4], borrowed.longs.iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn arrays_into_values() {
+    let value = Value::Compound(Map::from([
+        (
+            "ints".to_owned(),
+            Value::IntArray(IntArray::new(vec![1, -2])),
+        ),
+        (
+            "longs".to_owned(),
+            Value::LongArray(LongArray::new(vec![3, -4])),
+        ),
+    ]));
+
+    let bytes = to_bytes_le(&value).unwrap();
+    assert_eq!(value, from_bytes_le::<Value>(&bytes).unwrap());
+    assert_eq!(
+        value,
+        from_bytes_le::<borrow::Value>(&bytes).unwrap().into()
+    );
+}
@@ -0,0 +1,156 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    de::{from_bytes, from_bytes_with_opts, from_reader, DeOpts, DuplicateKeyPolicy},
+    from_value, to_bytes, Value,
+};
+
+use super::builder::Builder;
+
+fn forced() -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .start_compound("forced")
+        .string("0", "spawn")
+        .string("-12", "farm")
+        .string("9000000000", "far away")
+        .end_compound()
+        .end_compound()
+        .build()
+}
+
+#[test]
+fn integer_keys() {
+    #[derive(Deserialize)]
+    struct V {
+        forced: HashMap<i64, Value>,
+    }
+
+    let v: V = from_bytes(&forced()).unwrap();
+    assert_eq!(
+        HashMap::from([
+            (0, Value::String("spawn".to_owned())),
+            (-12, Value::String("farm".to_owned())),
+            (9000000000, Value::String("far away".to_owned())),
+        ]),
+        v.forced
+    );
+
+    let v: V = from_reader(forced().as_slice()).unwrap();
+    assert_eq!(3, v.forced.len());
+}
+
+#[test]
+fn invalid_integer_key_is_an_error() {
+    #[derive(Deserialize, Debug)]
+    struct V {
+        #[allow(dead_code)]
+        forced: HashMap<i64, Value>,
+    }
+
+    let data = Builder::new()
+        .start_compound("")
+        .start_compound("forced")
+        .string("1", "fine")
+        .string("spawn", "not a number")
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<V>(&data).unwrap_err();
+    assert!(err.to_string().contains("\"spawn\""), "{}", err);
+    assert_eq!(Some("forced.spawn"), err.path());
+}
+
+#[test]
+fn integer_key_out_of_range_is_an_error() {
+    #[derive(Deserialize, Debug)]
+    struct V {
+        #[allow(dead_code)]
+        forced: HashMap<i32, Value>,
+    }
+
+    assert!(from_bytes::<V>(&forced()).is_err());
+}
+
+#[test]
+fn round_trip() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "PascalCase")]
+    struct Section {
+        block_light: Vec<i8>,
+    }
+
+    let sections = BTreeMap::from([
+        (
+            -4i32,
+            Section {
+                block_light: vec![],
+            },
+        ),
+        (
+            3,
+            Section {
+                block_light: vec![1, 2],
+            },
+        ),
+    ]);
+
+    let data = to_bytes(&sections).unwrap();
+    assert_eq!(sections, from_bytes(&data).unwrap());
+
+    // The keys are strings in the NBT itself.
+    let value: Value = from_bytes(&data).unwrap();
+    match &value {
+        Value::Compound(c) => assert!(c.contains_key("-4") && c.contains_key("3")),
+        _ => panic!("expected compound"),
+    }
+    assert_eq!(sections, from_value(&value).unwrap());
+}
+
+#[test]
+fn integer_keys_checked_for_duplicates() {
+    let data = Builder::new()
+        .start_compound("")
+        .int("1", 1)
+        .int("2", 2)
+        .int("1", 3)
+        .end_compound()
+        .build();
+
+    let opts = DeOpts::new().on_duplicate_key(DuplicateKeyPolicy::FirstWins);
+    let v: HashMap<u8, i32> = from_bytes_with_opts(&data, opts).unwrap();
+    assert_eq!(HashMap::from([(1, 1), (2, 2)]), v);
+
+    let opts = DeOpts::new().on_duplicate_key(DuplicateKeyPolicy::Error);
+    assert!(from_bytes_with_opts::<HashMap<u8, i32>>(&data, opts).is_err());
+}
+
+#[test]
+fn enum_keys() {
+    #[derive(Deserialize, Debug, PartialEq, Eq, Hash)]
+    #[serde(rename_all = "snake_case")]
+    enum Dimension {
+        Overworld,
+        TheNether,
+    }
+
+    let data = Builder::new()
+        .start_compound("")
+        .int("overworld", 1)
+        .int("the_nether", 2)
+        .end_compound()
+        .build();
+
+    let v: HashMap<Dimension, i32> = from_bytes(&data).unwrap();
+    assert_eq!(
+        HashMap::from([(Dimension::Overworld, 1), (Dimension::TheNether, 2)]),
+        v
+    );
+
+    let opts = DeOpts::new().on_duplicate_key(DuplicateKeyPolicy::Error);
+    let v: HashMap<Dimension, i32> = from_bytes_with_opts(&data, opts).unwrap();
+    assert_eq!(2, v.len());
+}
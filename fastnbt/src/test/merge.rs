@@ -0,0 +1,158 @@
+use std::convert::TryInto;
+
+use crate::{de::from_bytes_le, ByteArray, ListMerge, MergeConflict, MergeStrategy, Value};
+
+const LEVEL_DAT: &[u8] = include_bytes!("resources/bedrock_level.dat");
+
+fn level() -> Value {
+    let len = i32::from_le_bytes(LEVEL_DAT[4..8].try_into().unwrap());
+    from_bytes_le(&LEVEL_DAT[8..8 + len as usize]).unwrap()
+}
+
+fn compound<const N: usize>(entries: [(&str, Value); N]) -> Value {
+    Value::Compound(
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect(),
+    )
+}
+
+fn strict() -> MergeStrategy {
+    MergeStrategy::new().on_conflict(MergeConflict::Error)
+}
+
+#[test]
+fn game_rules_into_level_dat() {
+    // Bedrock keeps its game rules at the top of the level.dat.
+    let mut level = level();
+    let before = level.clone();
+    let patch = compound([
+        ("commandsEnabled", Value::Byte(0)),
+        ("keepinventory", Value::Byte(1)),
+        ("abilities", compound([("mayfly", Value::Byte(0))])),
+    ]);
+
+    level.merge(&patch, strict()).unwrap();
+
+    assert_eq!(Some(0), level["commandsEnabled"].as_i64());
+    assert_eq!(Some(1), level["keepinventory"].as_i64());
+    assert_eq!(Some(0), level["abilities"]["mayfly"].as_i64());
+
+    // Everything not in the patch is untouched.
+    assert_eq!(
+        before["abilities"]["flySpeed"],
+        level["abilities"]["flySpeed"]
+    );
+    assert_eq!(before["LevelName"], level["LevelName"]);
+    let len = |v: &Value| v.as_compound().unwrap().len();
+    assert_eq!(len(&before) + 1, len(&level));
+
+    // Merging again changes nothing.
+    let merged = level.clone();
+    level.merge(&patch, strict()).unwrap();
+    assert_eq!(merged, level);
+}
+
+#[test]
+fn type_conflict_is_an_error() {
+    let mut level = level();
+    let before = level.clone();
+    let patch = compound([
+        ("keepinventory", Value::Byte(1)),
+        ("abilities", compound([("flySpeed", Value::Double(0.1))])),
+    ]);
+
+    let err = level.merge(&patch, strict()).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("cannot merge a Double into a Float at `abilities/flySpeed`"),
+        "{}",
+        err
+    );
+    // Nothing was applied, not even the entries before the conflict.
+    assert_eq!(before, level);
+
+    // By default the patch wins.
+    level.merge(&patch, MergeStrategy::new()).unwrap();
+    assert_eq!(Value::Double(0.1), level["abilities"]["flySpeed"]);
+}
+
+#[test]
+fn lists() {
+    let mut level = level();
+    let patch = compound([("lastOpenedWithVersion", Value::List(vec![Value::Int(7)]))]);
+
+    let mut replaced = level.clone();
+    replaced.merge(&patch, strict()).unwrap();
+    assert_eq!(
+        Some(&[Value::Int(7)][..]),
+        replaced["lastOpenedWithVersion"].as_list()
+    );
+
+    let concat = strict().lists(ListMerge::Concat);
+    level.merge(&patch, concat).unwrap();
+    let versions: Vec<_> = level["lastOpenedWithVersion"]
+        .as_list()
+        .unwrap()
+        .iter()
+        .filter_map(Value::as_i64)
+        .collect();
+    assert_eq!(vec![1, 20, 30, 2, 0, 7], versions);
+
+    // Concatenating different types of element would not be valid NBT.
+    let patch = compound([(
+        "lastOpenedWithVersion",
+        Value::List(vec![Value::String("1.21".to_owned())]),
+    )]);
+    let err = level.merge(&patch, concat).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("a List holding a String into a List holding an Int"),
+        "{}",
+        err
+    );
+    level
+        .merge(&patch, concat.on_conflict(MergeConflict::Replace))
+        .unwrap();
+    assert_eq!(
+        patch["lastOpenedWithVersion"],
+        level["lastOpenedWithVersion"]
+    );
+
+    // An empty list goes with anything.
+    let mut empty = compound([("lastOpenedWithVersion", Value::List(vec![]))]);
+    empty.merge(&patch, concat).unwrap();
+    assert_eq!(patch, empty);
+}
+
+#[test]
+fn arrays_are_replaced_whole() {
+    let bytes = |b: Vec<i8>| Value::ByteArray(ByteArray::new(b));
+    let mut value = compound([("a", bytes(vec![1, 2, 3]))]);
+    let patch = compound([("a", bytes(vec![4]))]);
+
+    let concat = strict().lists(ListMerge::Concat);
+    value.merge(&patch, concat).unwrap();
+    assert_eq!(bytes(vec![4]), value["a"]);
+
+    let patch = compound([("a", Value::List(vec![Value::Byte(5)]))]);
+    assert!(value.merge(&patch, concat).is_err());
+}
+
+#[test]
+fn escaped_conflict_path() {
+    let mut value = compound([("a/b", compound([("~", Value::Int(1))]))]);
+    let patch = compound([("a/b", compound([("~", Value::String("x".into()))]))]);
+    let err = value.merge(&patch, strict()).unwrap_err();
+    assert!(err.to_string().contains("`a~1b/~0`"), "{}", err);
+    assert!(value.get_path("a~1b/~0").is_some());
+
+    // A patch that isn't a compound replaces the whole value.
+    let mut value = compound([]);
+    assert!(value.merge(&Value::Int(1), strict()).is_err());
+    value
+        .merge(&Value::Int(1), MergeStrategy::default())
+        .unwrap();
+    assert_eq!(Value::Int(1), value);
+}
@@ -1,4 +1,4 @@
-use crate::{de::from_bytes, Value};
+use crate::{borrow, de::from_bytes, LongArray, Value};
 use serde::Deserialize;
 
 const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
@@ -115,3 +115,56 @@ fn avoiding_alloc_with_chunk() {
 
     let _chunk: Chunk = from_bytes(CHUNK_RAW).unwrap();
 }
+
+#[test]
+fn unpack_block_states() {
+    #[derive(Deserialize)]
+    struct Chunk<'a> {
+        #[serde(rename = "Level", borrow)]
+        level: Level<'a>,
+    }
+
+    #[derive(Deserialize)]
+    struct Level<'a> {
+        #[serde(rename = "Sections", borrow)]
+        sections: Vec<Section<'a>>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Section<'a> {
+        #[serde(default)]
+        palette: Vec<Value>,
+        #[serde(borrow)]
+        block_states: Option<borrow::LongArray<'a>>,
+    }
+
+    // A 1.16 chunk, so entries are padded to stay within a long.
+    let chunk: Chunk = from_bytes(CHUNK_RAW).unwrap();
+    let mut unpacked = 0;
+    for section in &chunk.level.sections {
+        let states = match &section.block_states {
+            Some(states) => states,
+            None => continue,
+        };
+        let bits = (usize::BITS - (section.palette.len() - 1).leading_zeros()).max(4) as usize;
+        let per_long = 64 / bits;
+
+        let mut blocks = [0; 16 * 16 * 16];
+        states.unpack_into(bits, true, &mut blocks).unwrap();
+        for (i, block) in blocks.iter().enumerate() {
+            let long = states.get(i / per_long).unwrap() as u64;
+            let expected = (long >> (i % per_long * bits)) & ((1 << bits) - 1);
+            assert_eq!(expected as u16, *block);
+            assert!((*block as usize) < section.palette.len());
+        }
+
+        let mut owned = [0; 16 * 16 * 16];
+        LongArray::new(states.iter().collect())
+            .unpack_into(bits, true, &mut owned)
+            .unwrap();
+        assert_eq!(blocks, owned);
+        unpacked += 1;
+    }
+    assert!(unpacked > 0);
+}
@@ -8,11 +8,72 @@ mod de;
 #[allow(clippy::float_cmp)]
 mod value;
 
+mod value_builder;
+mod value_de;
+mod value_path;
+
+mod alloc;
+mod arrays;
+#[cfg(feature = "async")]
+mod async_reader;
+mod bedrock_network;
+mod borrowed_compound;
+mod borrowed_value;
 mod builder;
+mod bytes;
+mod compressed;
 mod de_arrays;
+mod debug;
+mod depth;
+mod duplicate_keys;
+mod empty_lists;
+mod error_category;
+mod error_source;
 mod fuzz;
+mod get_path;
+mod human_readable;
+mod index;
+mod java_cesu8;
+#[cfg(feature = "json")]
+mod json;
+mod little_endian;
+mod map_keys;
+mod merge;
 mod minecraft_chunk;
+mod network;
+mod offset;
+mod patch;
+mod path;
+#[cfg(feature = "preserve_order")]
+mod preserve_order;
+mod reader;
+mod root_name;
+mod seed;
+mod ser;
 mod stream;
+mod strings;
+mod tag;
+mod tagged_enum;
+mod trailing;
+mod unknown_fields;
+mod untagged_enum;
+mod uuid;
+mod validate;
+mod widen;
+mod zero_copy;
+
+/// Run `f` with a stack big enough for the default depth limit in debug
+/// builds, which need far more stack for each level of nesting than release
+/// builds do.
+fn with_stack(f: impl FnOnce() + Send + 'static) {
+    let thread = std::thread::Builder::new()
+        .stack_size(64 * 1024 * 1024)
+        .spawn(f)
+        .unwrap();
+    if let Err(e) = thread.join() {
+        std::panic::resume_unwind(e);
+    }
+}
 
 fn assert_try_into(tag: Tag) {
     assert_eq!(tag, (tag as u8).try_into().unwrap());
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    de::{from_bytes, from_bytes_network},
+    to_bytes, to_bytes_network, Tag, Value,
+};
+
+// A registry data payload in the form sent by a 1.20.2 server, holding the
+// dimension_type and damage_type registries.
+const REGISTRY_DATA: &[u8] = include_bytes!("resources/registry_data.nbt");
+
+#[derive(Deserialize, Debug)]
+struct RegistryData {
+    #[serde(rename = "minecraft:dimension_type")]
+    dimension_type: Registry<DimensionType>,
+    #[serde(rename = "minecraft:damage_type")]
+    damage_type: Registry<DamageType>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Registry<T> {
+    #[serde(rename = "type")]
+    kind: String,
+    value: Vec<Entry<T>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Entry<T> {
+    name: String,
+    id: i32,
+    element: T,
+}
+
+#[derive(Deserialize, Debug)]
+struct DimensionType {
+    fixed_time: Option<i64>,
+    has_skylight: bool,
+    min_y: i32,
+    height: i32,
+    effects: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct DamageType {
+    message_id: String,
+    exhaustion: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Simple {
+    a: i8,
+    b: String,
+}
+
+fn simple() -> Simple {
+    Simple {
+        a: 7,
+        b: "hello".to_owned(),
+    }
+}
+
+#[test]
+fn no_root_name() {
+    let bytes = to_bytes_network(&simple()).unwrap();
+
+    assert_eq!(Tag::Compound as u8, bytes[0]);
+    // Straight into the first child, with no name length for the root.
+    assert_eq!(Tag::Byte as u8, bytes[1]);
+    assert_eq!([0, 1, b'a', 7], bytes[2..6]);
+
+    assert_eq!(simple(), from_bytes_network(&bytes).unwrap());
+}
+
+#[test]
+fn same_as_named_without_the_name() {
+    let named = to_bytes(&simple()).unwrap();
+    let network = to_bytes_network(&simple()).unwrap();
+
+    // The root's empty name is the 2 byte length after the tag.
+    assert_eq!([0, 0], named[1..3]);
+    assert_eq!(named[..1], network[..1]);
+    assert_eq!(named[3..], network[1..]);
+}
+
+#[test]
+fn root_must_still_be_compound() {
+    assert!(to_bytes_network(&1i32).is_err());
+    assert!(from_bytes_network::<Value>(&[Tag::Int as u8, 0, 0, 0, 1]).is_err());
+}
+
+#[test]
+fn registry_data() {
+    let data: RegistryData = from_bytes_network(REGISTRY_DATA).unwrap();
+
+    let dimensions = &data.dimension_type;
+    assert_eq!("minecraft:dimension_type", dimensions.kind);
+    assert_eq!(2, dimensions.value.len());
+
+    let overworld = &dimensions.value[0];
+    assert_eq!("minecraft:overworld", overworld.name);
+    assert_eq!(0, overworld.id);
+    assert_eq!(None, overworld.element.fixed_time);
+    assert!(overworld.element.has_skylight);
+    assert_eq!(
+        (-64, 384),
+        (overworld.element.min_y, overworld.element.height)
+    );
+    assert_eq!("minecraft:overworld", overworld.element.effects);
+
+    let end = &dimensions.value[1];
+    assert_eq!("minecraft:the_end", end.name);
+    assert_eq!(Some(6000), end.element.fixed_time);
+    assert!(!end.element.has_skylight);
+
+    let damage: HashMap<_, _> = data
+        .damage_type
+        .value
+        .iter()
+        .map(|e| (e.name.as_str(), (e.id, &e.element)))
+        .collect();
+    assert_eq!(3, damage.len());
+    let (id, arrow) = damage["minecraft:arrow"];
+    assert_eq!(0, id);
+    assert_eq!("arrow", arrow.message_id);
+    assert_eq!(0.1, arrow.exhaustion);
+}
+
+#[test]
+fn registry_data_round_trip() {
+    let value: Value = from_bytes_network(REGISTRY_DATA).unwrap();
+
+    let bytes = to_bytes_network(&value).unwrap();
+    assert_eq!(REGISTRY_DATA.len(), bytes.len());
+    assert_eq!(value, from_bytes_network::<Value>(&bytes).unwrap());
+
+    // Written with a name, it reads back with the normal deserializer.
+    let named = to_bytes(&value).unwrap();
+    assert_eq!(REGISTRY_DATA.len() + 2, named.len());
+    assert_eq!(value, from_bytes::<Value>(&named).unwrap());
+}
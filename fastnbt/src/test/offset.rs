@@ -0,0 +1,105 @@
+use serde::Deserialize;
+
+use crate::{
+    de::{from_bytes, from_reader},
+    error::ErrorKind,
+    to_bytes, Value,
+};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+fn find(data: &[u8], needle: &[u8]) -> usize {
+    data.windows(needle.len())
+        .position(|w| w == needle)
+        .unwrap()
+}
+
+#[test]
+fn invalid_tag_in_chunk() {
+    // The Int tag of the chunk's xPos.
+    let at = find(CHUNK_RAW, b"\x03\x00\x04xPos");
+    let mut data = CHUNK_RAW.to_vec();
+    data[at] = 57;
+
+    let err = from_bytes::<Value>(&data).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidTag(57), err.kind());
+    assert_eq!(Some(at), err.offset());
+    assert!(
+        err.to_string().ends_with(&format!("(at byte {})", at)),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn invalid_tag_in_chunk_from_reader() {
+    let at = find(CHUNK_RAW, b"\x03\x00\x04xPos");
+    let mut data = CHUNK_RAW.to_vec();
+    data[at] = 57;
+
+    let err = from_reader::<_, Value>(data.as_slice()).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidTag(57), err.kind());
+    assert_eq!(Some(at), err.offset());
+}
+
+#[test]
+fn invalid_tag_while_skipping() {
+    #[derive(Deserialize, Debug)]
+    struct Chunk {}
+
+    // Unknown fields are skipped rather than deserialized, which should not
+    // lose track of where the input is.
+    let at = find(CHUNK_RAW, b"\x03\x00\x04xPos");
+    let mut data = CHUNK_RAW.to_vec();
+    data[at] = 57;
+
+    let err = from_bytes::<Chunk>(&data).unwrap_err();
+    assert_eq!(Some(at), err.offset());
+
+    let err = from_reader::<_, Chunk>(data.as_slice()).unwrap_err();
+    assert_eq!(Some(at), err.offset());
+}
+
+#[test]
+fn invalid_string_in_chunk() {
+    // The start of the Status string, after its name and length.
+    let at = find(CHUNK_RAW, b"\x08\x00\x06Status") + 9 + 2;
+    let mut data = CHUNK_RAW.to_vec();
+    data[at] = 0xff;
+
+    let err = from_bytes::<Value>(&data).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Nonunicode(_)), "{}", err);
+    assert_eq!(Some(at), err.offset());
+
+    let err = from_reader::<_, Value>(data.as_slice()).unwrap_err();
+    assert_eq!(Some(at), err.offset());
+}
+
+#[test]
+fn type_errors_are_after_the_value() {
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "PascalCase")]
+    struct Chunk {
+        #[allow(dead_code)]
+        level: Level,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Level {
+        #[serde(rename = "xPos")]
+        #[allow(dead_code)]
+        x_pos: String,
+    }
+
+    // The tag, the name, then the int itself.
+    let end = find(CHUNK_RAW, b"\x03\x00\x04xPos") + 7 + 4;
+
+    let err = from_bytes::<Chunk>(CHUNK_RAW).unwrap_err();
+    assert_eq!(Some(end), err.offset());
+}
+
+#[test]
+fn serialize_errors_have_no_offset() {
+    let err = to_bytes(&Value::Int(1)).unwrap_err();
+    assert_eq!(None, err.offset());
+}
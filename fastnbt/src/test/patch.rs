@@ -0,0 +1,219 @@
+use serde::Deserialize;
+
+use crate::{
+    de::from_bytes,
+    error::ErrorKind,
+    patch::{set_scalar, set_scalar_in_place, ScalarValue},
+    Tag, Value,
+};
+
+use super::builder::Builder;
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+#[derive(Deserialize)]
+struct Chunk {
+    #[serde(rename = "Level")]
+    level: Level,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Level {
+    status: String,
+    inhabited_time: i64,
+    last_update: i64,
+    #[serde(rename = "xPos")]
+    x_pos: i32,
+}
+
+fn level(data: &[u8]) -> Level {
+    from_bytes::<Chunk>(data).unwrap().level
+}
+
+/// The offset of the first byte that differs, and the offset from the end of
+/// the last byte that differs.
+fn differing_bytes(a: &[u8], b: &[u8]) -> (usize, usize) {
+    let start = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+    let end = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (start, end)
+}
+
+#[test]
+fn patch_inhabited_time() {
+    let patched = set_scalar(CHUNK_RAW, &["Level", "InhabitedTime"], ScalarValue::Long(0)).unwrap();
+
+    let before = level(CHUNK_RAW);
+    let after = level(&patched);
+    assert_eq!(15910433, before.inhabited_time);
+    assert_eq!(0, after.inhabited_time);
+    assert_eq!(before.last_update, after.last_update);
+    assert_eq!(before.status, after.status);
+
+    // Only the 8 bytes of the long changed.
+    assert_eq!(CHUNK_RAW.len(), patched.len());
+    let (start, end) = differing_bytes(CHUNK_RAW, &patched);
+    assert!(CHUNK_RAW.len() - start - end <= 8);
+}
+
+#[test]
+fn patch_status_same_length() {
+    let patched = set_scalar(
+        CHUNK_RAW,
+        &["Level", "Status"],
+        ScalarValue::String("fuel".into()),
+    )
+    .unwrap();
+
+    assert_eq!("fuel", level(&patched).status);
+    assert_eq!(CHUNK_RAW.len(), patched.len());
+    let (start, end) = differing_bytes(CHUNK_RAW, &patched);
+    assert!(CHUNK_RAW.len() - start - end <= 4);
+}
+
+#[test]
+fn patch_status_resizes() {
+    let patched = set_scalar(
+        CHUNK_RAW,
+        &["Level", "Status"],
+        ScalarValue::String("minecraft:full".into()),
+    )
+    .unwrap();
+
+    let after = level(&patched);
+    assert_eq!("minecraft:full", after.status);
+    assert_eq!(16, after.x_pos);
+    assert_eq!(15910433, after.inhabited_time);
+    assert_eq!(CHUNK_RAW.len() + 10, patched.len());
+
+    // Everything before and after the string is untouched.
+    let (start, end) = differing_bytes(CHUNK_RAW, &patched);
+    assert!(patched.len() - start - end <= 2 + "minecraft:full".len());
+
+    let before: Value = from_bytes(CHUNK_RAW).unwrap();
+    let mut after: Value = from_bytes(&patched).unwrap();
+    if let Value::Compound(root) = &mut after {
+        if let Some(Value::Compound(level)) = root.get_mut("Level") {
+            level.insert("Status".into(), Value::String("full".into()));
+        }
+    }
+    assert_eq!(before, after);
+}
+
+#[test]
+fn patch_in_place() {
+    let mut data = CHUNK_RAW.to_vec();
+    set_scalar_in_place(&mut data, &["DataVersion"], ScalarValue::Int(3700)).unwrap();
+    set_scalar_in_place(&mut data, &["Level", "xPos"], ScalarValue::Int(-1)).unwrap();
+
+    assert_eq!(-1, level(&data).x_pos);
+    let version: Value = from_bytes(&data).unwrap();
+    match version {
+        Value::Compound(root) => assert_eq!(Some(&Value::Int(3700)), root.get("DataVersion")),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn changing_tag_is_an_error() {
+    let err = set_scalar(CHUNK_RAW, &["Level", "InhabitedTime"], ScalarValue::Int(0)).unwrap_err();
+    assert_eq!(
+        "cannot patch Level.InhabitedTime: it is Long, not Int",
+        err.to_string()
+    );
+}
+
+#[test]
+fn missing_value_is_an_error() {
+    let err = set_scalar(CHUNK_RAW, &["Level", "Nope"], ScalarValue::Int(0)).unwrap_err();
+    assert_eq!("no value at Level.Nope", err.to_string());
+
+    let err = set_scalar(CHUNK_RAW, &["Nope", "Status"], ScalarValue::Int(0)).unwrap_err();
+    assert_eq!("no value at Nope.Status", err.to_string());
+}
+
+#[test]
+fn non_scalar_is_an_error() {
+    assert!(set_scalar(CHUNK_RAW, &["Level", "Sections"], ScalarValue::Int(0)).is_err());
+    assert!(set_scalar(CHUNK_RAW, &["Level", "Heightmaps"], ScalarValue::Int(0)).is_err());
+
+    let err = set_scalar(
+        CHUNK_RAW,
+        &["Level", "Status", "Inner"],
+        ScalarValue::Int(0),
+    )
+    .unwrap_err();
+    assert_eq!(
+        "cannot patch Level.Status.Inner: Level.Status is not a compound",
+        err.to_string()
+    );
+}
+
+#[test]
+fn names_in_other_compounds_do_not_match() {
+    // Values with the target's name, but nested elsewhere or in a list, are
+    // skipped.
+    let build = |a: i32| {
+        Builder::new()
+            .start_compound("")
+            .start_compound("other")
+            .int("a", 1)
+            .end_compound()
+            .start_list("list", Tag::Compound, 1)
+            .int("a", 2)
+            .end_compound()
+            .int("a", a)
+            .end_compound()
+            .build()
+    };
+
+    let patched = set_scalar(&build(3), &["a"], ScalarValue::Int(4)).unwrap();
+    assert_eq!(build(4), patched);
+}
+
+#[test]
+fn plain_utf8_strings_are_replaced_whole() {
+    // Strings written as UTF-8 rather than Java's CESU-8 read the same, but
+    // are a different length when encoded again.
+    let build = |s: &[u8]| {
+        Builder::new()
+            .start_compound("")
+            .tag(Tag::String)
+            .name("s")
+            .raw_len(s.len())
+            .raw_bytes(s)
+            .int("after", 1)
+            .end_compound()
+            .build()
+    };
+
+    for s in ["😀", "a\0b"] {
+        let patched = set_scalar(
+            &build(s.as_bytes()),
+            &["s"],
+            ScalarValue::String("x".into()),
+        );
+        assert_eq!(build(b"x"), patched.unwrap());
+    }
+}
+
+#[test]
+fn arrays_are_skipped_without_reading_them() {
+    // Claims to be 16 GiB of longs.
+    let data = Builder::new()
+        .start_compound("")
+        .tag(Tag::LongArray)
+        .name("a")
+        .int_payload(i32::MAX)
+        .int("b", 1)
+        .end_compound()
+        .build();
+
+    let err = set_scalar(&data, &["b"], ScalarValue::Int(2)).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidSize(i32::MAX), err.kind());
+}
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{
+    de::{from_bytes, from_bytes_with_opts, from_reader, DeOpts},
+    error::ErrorKind,
+    LongArray, Tag, Value,
+};
+
+use super::builder::Builder;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Chunk {
+    #[allow(dead_code)]
+    level: Level,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Level {
+    #[allow(dead_code)]
+    sections: Vec<Section>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Section {
+    #[allow(dead_code)]
+    y: i8,
+    #[allow(dead_code)]
+    block_states: Option<LongArray>,
+}
+
+/// A chunk whose fourth section has its block states stored as the wrong type.
+fn mistyped_chunk() -> Vec<u8> {
+    let mut builder = Builder::new()
+        .start_compound("")
+        .start_compound("Level")
+        .start_list("Sections", Tag::Compound, 4);
+
+    for y in 0..3 {
+        builder = builder
+            .byte("Y", y)
+            .long_array("BlockStates", &[1, 2])
+            .end_compound();
+    }
+
+    builder
+        .byte("Y", 3)
+        .int_array("BlockStates", &[1, 2])
+        .end_compound()
+        .end_compound()
+        .end_compound()
+        .build()
+}
+
+#[test]
+fn mistyped_field_in_list() {
+    let err = from_bytes::<Chunk>(&mistyped_chunk()).unwrap_err();
+    assert_eq!(Some("Level.Sections[3].BlockStates"), err.path());
+    assert!(
+        err.to_string()
+            .starts_with("Level.Sections[3].BlockStates: expected LongArray, found IntArray"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn mistyped_field_in_list_from_reader() {
+    let data = mistyped_chunk();
+    let err = from_reader::<_, Chunk>(data.as_slice()).unwrap_err();
+    assert_eq!(Some("Level.Sections[3].BlockStates"), err.path());
+}
+
+#[test]
+fn mistyped_lenient_array() {
+    let payload = Builder::new()
+        .start_compound("")
+        .start_compound("Level")
+        .start_list("Sections", Tag::Compound, 1)
+        .byte("Y", 0)
+        .start_list("BlockStates", Tag::Int, 1)
+        .int_payload(1)
+        .end_compound()
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let opts = DeOpts::new().lenient_arrays(true);
+    let err = from_bytes_with_opts::<Chunk>(&payload, opts).unwrap_err();
+    assert_eq!(Some("Level.Sections[0].BlockStates"), err.path());
+}
+
+#[test]
+fn mistyped_list_names_its_field() {
+    // A list of the wrong element type, and a compound where a list belongs.
+    let wrong_elements = Builder::new()
+        .start_compound("")
+        .start_compound("Level")
+        .start_list("Sections", Tag::Int, 1)
+        .int_payload(1)
+        .end_compound()
+        .end_compound()
+        .build();
+    let not_a_list = Builder::new()
+        .start_compound("")
+        .start_compound("Level")
+        .start_compound("Sections")
+        .end_compound()
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<Chunk>(&wrong_elements).unwrap_err();
+    assert_eq!(Some("Level.Sections"), err.path());
+    assert!(err.offset().is_some());
+    assert!(
+        err.to_string().starts_with(
+            "Level.Sections: expected a List of struct Section, found a List of Int of length 1"
+        ),
+        "{}",
+        err
+    );
+
+    let err = from_bytes::<Chunk>(&not_a_list).unwrap_err();
+    assert_eq!(Some("Level.Sections"), err.path());
+    assert!(err.offset().is_some());
+    assert!(
+        err.to_string()
+            .starts_with("Level.Sections: invalid type: map, expected a sequence"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn missing_field_is_at_the_compound() {
+    let payload = Builder::new()
+        .start_compound("")
+        .start_compound("Level")
+        .start_list("Sections", Tag::Compound, 2)
+        .byte("Y", 0)
+        .end_compound()
+        .long_array("BlockStates", &[])
+        .end_compound()
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<Chunk>(&payload).unwrap_err();
+    assert_eq!(Some("Level.Sections[1]"), err.path());
+}
+
+#[test]
+fn invalid_tag_is_not_blamed_on_the_entry_before() {
+    let mut payload = Builder::new()
+        .start_compound("")
+        .start_compound("Level")
+        .int("xPos", 1)
+        .end_compound()
+        .end_compound()
+        .build();
+
+    // The end of Level.
+    let at = payload.len() - 2;
+    payload[at] = 57;
+
+    let err = from_bytes::<Value>(&payload).unwrap_err();
+    assert_eq!(Some("Level"), err.path());
+}
+
+#[test]
+fn nested_lists() {
+    #[derive(Deserialize, Debug)]
+    struct Lists {
+        #[allow(dead_code)]
+        a: Vec<Vec<String>>,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("a", Tag::List, 2)
+        .tag(Tag::String)
+        .int_payload(0)
+        .tag(Tag::Int)
+        .int_payload(2)
+        .int_payload(1)
+        .int_payload(2)
+        .end_compound()
+        .build();
+
+    // The inner list is of the wrong type as a whole.
+    let err = from_bytes::<Lists>(&payload).unwrap_err();
+    assert_eq!(Some("a[1]"), err.path());
+    assert_eq!(
+        &ErrorKind::UnexpectedList {
+            element_tag: Tag::Int,
+            size: 2,
+            expected: "a string".to_owned(),
+        },
+        err.kind()
+    );
+}
+
+/// A compound with a list `list` of `size` strings.
+fn string_list(size: i32) -> Vec<u8> {
+    let mut builder = Builder::new()
+        .start_compound("")
+        .start_list("list", Tag::String, size);
+    for _ in 0..size {
+        builder = builder.string_payload("x");
+    }
+    builder.end_compound().build()
+}
+
+#[test]
+fn mistyped_list_elements() {
+    #[derive(Deserialize, Debug)]
+    struct Lists<T> {
+        list: Vec<T>,
+    }
+
+    let data = string_list(24);
+
+    let err = from_bytes::<Lists<Section>>(&data).unwrap_err();
+    assert_eq!(Some("list"), err.path());
+    assert_eq!(
+        &ErrorKind::UnexpectedList {
+            element_tag: Tag::String,
+            size: 24,
+            expected: "struct Section".to_owned(),
+        },
+        err.kind()
+    );
+    assert!(
+        err.to_string().starts_with(
+            "list: expected a List of struct Section, found a List of String of length 24"
+        ),
+        "{}",
+        err
+    );
+
+    let err = from_bytes::<Lists<Vec<i32>>>(&data).unwrap_err();
+    assert!(
+        matches!(err.kind(), ErrorKind::UnexpectedList { expected, .. } if expected == "a sequence"),
+        "{}",
+        err
+    );
+
+    let err = from_bytes::<Lists<(i32, i32)>>(&data).unwrap_err();
+    assert!(
+        matches!(err.kind(), ErrorKind::UnexpectedList { expected, .. } if expected == "a tuple of size 2"),
+        "{}",
+        err
+    );
+
+    // Unit ignores the value, whatever it is.
+    assert_eq!(24, from_bytes::<Lists<()>>(&data).unwrap().list.len());
+
+    #[derive(Deserialize, Debug)]
+    enum Status {
+        #[allow(dead_code)]
+        Full,
+    }
+    let ints = Builder::new()
+        .start_compound("")
+        .start_list("list", Tag::Int, 2)
+        .int_payload(1)
+        .int_payload(2)
+        .end_compound()
+        .build();
+    let err = from_bytes::<Lists<Status>>(&ints).unwrap_err();
+    assert_eq!(Some("list"), err.path());
+    assert!(
+        matches!(
+            err.kind(),
+            ErrorKind::UnexpectedList { element_tag: Tag::Int, size: 2, expected }
+                if expected == "variant identifier"
+        ),
+        "{}",
+        err
+    );
+
+    // Elements of the right type are not blamed on the list.
+    let err = from_bytes::<Lists<u8>>(
+        &Builder::new()
+            .start_compound("")
+            .start_list("list", Tag::Int, 2)
+            .int_payload(1)
+            .int_payload(300)
+            .end_compound()
+            .build(),
+    )
+    .unwrap_err();
+    assert_eq!(Some("list[1]"), err.path());
+    assert_eq!(&ErrorKind::Other, err.kind());
+}
+
+#[test]
+fn mistyped_value_inside_list_element() {
+    // The sections are compounds as expected, the second has a field of the
+    // wrong type.
+    let payload = Builder::new()
+        .start_compound("")
+        .start_compound("Level")
+        .start_list("Sections", Tag::Compound, 2)
+        .byte("Y", 0)
+        .end_compound()
+        .string("Y", "1")
+        .end_compound()
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<Chunk>(&payload).unwrap_err();
+    assert_eq!(&ErrorKind::Other, err.kind());
+    assert_eq!(Some("Level.Sections[1].Y"), err.path());
+
+    // The same for values serde reads in full before looking at them.
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "id")]
+    enum Entity {
+        Creeper {
+            #[allow(dead_code)]
+            ignited: i8,
+        },
+    }
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("Entities", Tag::Compound, 1)
+        .string("id", "Creeper")
+        .string("ignited", "no")
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<HashMap<String, Vec<Entity>>>(&payload).unwrap_err();
+    assert_eq!(&ErrorKind::Other, err.kind());
+    assert_eq!(Some("Entities[0]"), err.path());
+}
+
+#[test]
+fn no_path_outside_root() {
+    let err = from_bytes::<Value>(&[Tag::Int as u8, 0, 0]).unwrap_err();
+    assert_eq!(None, err.path());
+}
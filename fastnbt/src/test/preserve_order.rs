@@ -0,0 +1,112 @@
+use std::borrow::Cow;
+
+use crate::{borrow, de::from_bytes, stream, to_bytes, Map, Value};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+/// The names of every value in the document, in the order they are written.
+fn names_in_order(data: &[u8]) -> Vec<String> {
+    use stream::Value as V;
+
+    let mut parser = stream::Parser::new(data);
+    let mut names = Vec::new();
+    loop {
+        let name = match parser.next() {
+            Ok(V::ListEnd) | Ok(V::CompoundEnd) => continue,
+            Ok(V::Byte(n, _))
+            | Ok(V::Short(n, _))
+            | Ok(V::Int(n, _))
+            | Ok(V::Long(n, _))
+            | Ok(V::Float(n, _))
+            | Ok(V::Double(n, _))
+            | Ok(V::String(n, _))
+            | Ok(V::ByteArray(n, _))
+            | Ok(V::IntArray(n, _))
+            | Ok(V::LongArray(n, _))
+            | Ok(V::List(n, _, _))
+            | Ok(V::Compound(n)) => n,
+            Err(e) if e.is_eof() => return names,
+            Err(e) => panic!("{}", e),
+        };
+        if let Some(name) = name {
+            names.push(name);
+        }
+    }
+}
+
+#[test]
+fn chunk_round_trips_in_original_order() {
+    let value: Value = from_bytes(CHUNK_RAW).unwrap();
+    let data = to_bytes(&value).unwrap();
+
+    let original = names_in_order(CHUNK_RAW);
+    assert!(original.len() > 100);
+    assert_eq!(original, names_in_order(&data));
+}
+
+#[test]
+fn keys_iterate_in_document_order() {
+    let value: Value = from_bytes(CHUNK_RAW).unwrap();
+    let level = match &value {
+        Value::Compound(root) => match &root["Level"] {
+            Value::Compound(level) => level,
+            _ => panic!(),
+        },
+        _ => panic!(),
+    };
+
+    // The first names after "Level" are its direct children, up to the first
+    // one that is itself a compound or list.
+    let original = names_in_order(CHUNK_RAW);
+    let start = original.iter().position(|n| n == "Level").unwrap() + 1;
+    let keys: Vec<&String> = level.keys().collect();
+    let first_nested = level
+        .values()
+        .position(|v| matches!(v, Value::Compound(_) | Value::List(_)))
+        .unwrap();
+    assert!(first_nested > 0);
+    assert_eq!(
+        &original[start..=start + first_nested],
+        &keys[..=first_nested]
+            .iter()
+            .map(|k| k.to_string())
+            .collect::<Vec<_>>()[..]
+    );
+}
+
+#[test]
+fn borrowed_value_keeps_order() {
+    let value: borrow::Value = from_bytes(CHUNK_RAW).unwrap();
+    let owned: Value = from_bytes(CHUNK_RAW).unwrap();
+    match (&value, &owned) {
+        (borrow::Value::Compound(b), Value::Compound(o)) => {
+            let b: Vec<&Cow<str>> = b.keys().collect();
+            let o: Vec<&String> = o.keys().collect();
+            assert_eq!(b, o);
+        }
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn borrowed_value_writes_the_original_bytes() {
+    let value: borrow::Value = from_bytes(CHUNK_RAW).unwrap();
+    assert_eq!(CHUNK_RAW, &to_bytes(&value).unwrap()[..]);
+}
+
+#[test]
+fn inserted_keys_are_written_in_order() {
+    let keys = ["zebra", "apple", "mango", "banana", "kiwi", "cherry"];
+    let mut compound = Map::new();
+    for (i, key) in keys.iter().enumerate() {
+        compound.insert(key.to_string(), Value::Int(i as i32));
+    }
+    let data = to_bytes(&Value::Compound(compound)).unwrap();
+    assert_eq!(keys.to_vec(), names_in_order(&data)[1..]);
+
+    let value: Value = from_bytes(&data).unwrap();
+    match value {
+        Value::Compound(c) => assert_eq!(keys.to_vec(), c.keys().collect::<Vec<_>>()),
+        _ => panic!(),
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    de::{from_bytes, from_reader},
+    error::ErrorKind,
+    to_bytes, ByteArray, IntArray, LongArray, Value,
+};
+
+use super::builder::Builder;
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[derive(Deserialize, Debug)]
+struct Chunk {
+    #[serde(rename = "Level")]
+    level: Level,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Level {
+    status: String,
+    #[serde(rename = "xPos")]
+    x_pos: i32,
+    #[serde(rename = "zPos")]
+    z_pos: i32,
+    sections: Vec<Section>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Section {
+    y: i8,
+    block_states: Option<LongArray>,
+    palette: Option<Vec<HashMap<String, Value>>>,
+}
+
+#[test]
+fn chunk_through_gz_decoder() {
+    let gz = gzip(CHUNK_RAW);
+
+    let streamed: Value = from_reader(GzDecoder::new(gz.as_slice())).unwrap();
+    let buffered: Value = from_bytes(CHUNK_RAW).unwrap();
+    assert_eq!(buffered, streamed);
+}
+
+#[test]
+fn chunk_struct_through_gz_decoder() {
+    let gz = gzip(CHUNK_RAW);
+    let chunk: Chunk = from_reader(GzDecoder::new(gz.as_slice())).unwrap();
+
+    assert_eq!("full", chunk.level.status);
+    assert_eq!(
+        (chunk.level.x_pos, chunk.level.z_pos),
+        from_bytes::<Chunk>(CHUNK_RAW)
+            .map(|c| (c.level.x_pos, c.level.z_pos))
+            .unwrap()
+    );
+
+    let section = chunk
+        .level
+        .sections
+        .iter()
+        .find(|s| s.block_states.is_some())
+        .unwrap();
+    assert!(section.y >= 0);
+    assert!(!section.block_states.as_ref().unwrap().is_empty());
+    assert!(!section.palette.as_ref().unwrap().is_empty());
+}
+
+#[test]
+fn owned_types() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Kind {
+        Small,
+        Large,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct V {
+        name: String,
+        unicode: String,
+        flag: bool,
+        kind: Kind,
+        uuid: u128,
+        maybe: Option<i16>,
+        missing: Option<i16>,
+        list: Vec<f64>,
+        longs: LongArray,
+        nested: HashMap<String, Vec<Vec<i32>>>,
+    }
+
+    let v = V {
+        name: "minecraft:stone".to_owned(),
+        unicode: "🦀 and ✓".to_owned(),
+        flag: true,
+        kind: Kind::Large,
+        uuid: 0x069a79f4_44e9_4726_a5be_fca90e38aaf5,
+        maybe: Some(-3),
+        missing: None,
+        list: vec![1.5, -2.0],
+        longs: LongArray::new(vec![i64::MAX]),
+        nested: vec![("a".to_owned(), vec![vec![1, 2], vec![]])]
+            .into_iter()
+            .collect(),
+    };
+
+    let bytes = to_bytes(&v).unwrap();
+    assert_eq!(v, from_reader(bytes.as_slice()).unwrap());
+}
+
+#[test]
+fn ignored_fields_are_skipped() {
+    #[derive(Deserialize)]
+    struct JustStatus {
+        #[serde(rename = "Level")]
+        level: StatusOnly,
+    }
+
+    #[derive(Deserialize)]
+    struct StatusOnly {
+        #[serde(rename = "Status")]
+        status: String,
+    }
+
+    let mut reader = CHUNK_RAW;
+    let chunk: JustStatus = from_reader(&mut reader).unwrap();
+    assert_eq!("full", chunk.level.status);
+    assert!(reader.is_empty());
+}
+
+#[test]
+fn reads_only_what_it_needs() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct V {
+        a: i32,
+    }
+
+    let mut data = to_bytes(&V { a: 1 }).unwrap();
+    data.extend(to_bytes(&V { a: 2 }).unwrap());
+    data.extend_from_slice(b"rest");
+
+    let mut reader = data.as_slice();
+    assert_eq!(V { a: 1 }, from_reader(&mut reader).unwrap());
+    assert_eq!(V { a: 2 }, from_reader(&mut reader).unwrap());
+
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();
+    assert_eq!("rest", rest);
+}
+
+#[test]
+fn truncated_input() {
+    for len in [1, 10, CHUNK_RAW.len() / 2, CHUNK_RAW.len() - 1] {
+        let err = from_reader::<_, Value>(&CHUNK_RAW[..len]).unwrap_err();
+        assert!(
+            matches!(err.kind(), ErrorKind::UnexpectedEof),
+            "{}: {}",
+            len,
+            err
+        );
+    }
+}
+
+#[test]
+fn huge_length_does_not_allocate_up_front() {
+    // A long array claiming i32::MAX elements, about 16 GiB, with no data.
+    let data = [10, 0, 0, 12, 0, 1, b'a', 0x7f, 0xff, 0xff, 0xff];
+    assert!(from_reader::<_, Value>(&data[..]).is_err());
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct Arrays {
+    bytes: ByteArray,
+    ints: IntArray,
+    longs: LongArray,
+}
+
+fn arrays(len: usize) -> (Arrays, Vec<u8>) {
+    let bytes: Vec<i8> = (0..len).map(|i| i as i8).collect();
+    let ints: Vec<i32> = (0..len)
+        .map(|i| (i as i32).wrapping_mul(-0x1010101))
+        .collect();
+    let longs: Vec<i64> = (0..len)
+        .map(|i| (i as i64).wrapping_mul(-0x101010101010101))
+        .collect();
+    let data = Builder::new()
+        .start_compound("")
+        .byte_array("bytes", &bytes)
+        .int_array("ints", &ints)
+        .long_array("longs", &longs)
+        .end_compound()
+        .build();
+
+    let expected = Arrays {
+        bytes: ByteArray::new(bytes),
+        ints: IntArray::new(ints),
+        longs: LongArray::new(longs),
+    };
+    (expected, data)
+}
+
+#[test]
+fn owned_arrays_of_many_lengths() {
+    // Either side of the lengths that are read at once.
+    for len in [0, 1, 1023, 1024, 1025, 2048, 2049, 8191, 8192, 8193, 20000] {
+        let (expected, data) = arrays(len);
+        assert_eq!(expected, from_reader(data.as_slice()).unwrap(), "{}", len);
+        assert_eq!(expected, from_bytes(&data).unwrap(), "{}", len);
+    }
+}
+
+#[test]
+fn owned_array_cut_short_after_first_chunk() {
+    let (_, data) = arrays(5000);
+    let err = from_reader::<_, Arrays>(&data[..data.len() - 100]).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::UnexpectedEof), "{}", err);
+    assert!(from_bytes::<Arrays>(&data[..data.len() - 100]).is_err());
+}
+
+#[test]
+fn owned_arrays_in_other_endians() {
+    let (expected, _) = arrays(3000);
+    let data = crate::to_bytes_le(&expected).unwrap();
+    assert_eq!(expected, crate::de::from_bytes_le(&data).unwrap());
+    let data = crate::to_bytes_bedrock_network(&expected).unwrap();
+    assert_eq!(
+        expected,
+        crate::de::from_bytes_bedrock_network(&data).unwrap()
+    );
+}
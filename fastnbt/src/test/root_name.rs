@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    de::{from_bytes, from_bytes_named, from_bytes_network, Deserializer},
+    error::ErrorKind,
+    ser::Serializer,
+    to_bytes, to_bytes_named, to_bytes_network, to_writer_named, Tag, Value,
+};
+
+use super::builder::Builder;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Entity {
+    id: String,
+    health: f32,
+}
+
+fn entity() -> Entity {
+    Entity {
+        id: "minecraft:pig".to_owned(),
+        health: 10.0,
+    }
+}
+
+#[test]
+fn empty_name() {
+    let data = to_bytes(&entity()).unwrap();
+    let (name, e): (String, Entity) = from_bytes_named(&data).unwrap();
+    assert_eq!("", name);
+    assert_eq!(entity(), e);
+}
+
+#[test]
+fn non_empty_name() {
+    let data = to_bytes_named("Entity", &entity()).unwrap();
+    assert_eq!(&[Tag::Compound as u8, 0, 6], &data[..3]);
+    assert_eq!(b"Entity", &data[3..9]);
+
+    let (name, e): (String, Entity) = from_bytes_named(&data).unwrap();
+    assert_eq!("Entity", name);
+    assert_eq!(entity(), e);
+
+    // The name is still ignored when not asked for.
+    assert_eq!(entity(), from_bytes::<Entity>(&data).unwrap());
+}
+
+#[test]
+fn non_ascii_name() {
+    // Written in Java's CESU-8, where the emoji is 6 bytes rather than 4.
+    let name = "Schématique 😈";
+    let data = to_bytes_named(name, &entity()).unwrap();
+    let encoded = cesu8::to_java_cesu8(name);
+    assert_eq!(&encoded[..], &data[3..3 + encoded.len()]);
+
+    let (read, _): (String, Value) = from_bytes_named(&data).unwrap();
+    assert_eq!(name, read);
+}
+
+#[test]
+fn name_from_builder() {
+    let data = Builder::new()
+        .start_compound("root")
+        .int("a", 1)
+        .end_compound()
+        .build();
+
+    let (name, map): (String, HashMap<String, i32>) = from_bytes_named(&data).unwrap();
+    assert_eq!("root", name);
+    assert_eq!(Some(&1), map.get("a"));
+}
+
+#[test]
+fn value_round_trip_keeps_name() {
+    let data = to_bytes_named("Data", &entity()).unwrap();
+    let (name, value): (String, Value) = from_bytes_named(&data).unwrap();
+
+    let written = to_bytes_named(&name, &value).unwrap();
+    let (name, e): (String, Entity) = from_bytes_named(&written).unwrap();
+    assert_eq!("Data", name);
+    assert_eq!(entity(), e);
+}
+
+#[test]
+fn deserializer_root_name() {
+    let data = to_bytes_named("Data", &entity()).unwrap();
+    let mut de = Deserializer::from_bytes(&data);
+    assert_eq!(None, de.root_name());
+    Entity::deserialize(&mut de).unwrap();
+    assert_eq!(Some("Data"), de.root_name());
+
+    // Network NBT has no name at all.
+    let data = to_bytes_network(&entity()).unwrap();
+    let mut de = Deserializer::from_bytes_network(&data);
+    Entity::deserialize(&mut de).unwrap();
+    assert_eq!(None, de.root_name());
+    assert_eq!(entity(), from_bytes_network::<Entity>(&data).unwrap());
+}
+
+#[test]
+fn serializer_with_root_name() {
+    let mut ser = Serializer::new(Cursor::new(Vec::new())).with_root_name("Named");
+    entity().serialize(&mut ser).unwrap();
+    let data = ser.into_inner().into_inner();
+
+    let mut written = Vec::new();
+    to_writer_named(&mut written, "Named", &entity()).unwrap();
+    assert_eq!(written, data);
+}
+
+#[test]
+fn named_needs_root_compound() {
+    let err = from_bytes_named::<Value>(&[Tag::Int as u8, 0, 0, 0, 0, 0, 1]).unwrap_err();
+    assert_eq!(&ErrorKind::NoRootCompound, err.kind());
+}
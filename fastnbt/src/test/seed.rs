@@ -0,0 +1,147 @@
+use std::{borrow::Cow, collections::HashSet, fmt};
+
+use serde::de::{DeserializeSeed, MapAccess, SeqAccess, Visitor};
+
+use crate::{
+    de::{from_bytes, Deserializer},
+    Value,
+};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+/// Walks any NBT, interning the strings of every `Name` entry, as the block
+/// names of a chunk's palettes are.
+struct Names<'a, 'de> {
+    interned: &'a mut HashSet<Cow<'de, str>>,
+    is_name: bool,
+}
+
+impl<'a, 'de> Names<'a, 'de> {
+    fn new(interned: &'a mut HashSet<Cow<'de, str>>) -> Self {
+        Self {
+            interned,
+            is_name: false,
+        }
+    }
+
+    fn intern(self, s: Cow<'de, str>) {
+        if self.is_name && !self.interned.contains(&s) {
+            self.interned.insert(s);
+        }
+    }
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for Names<'a, 'de> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for Names<'a, 'de> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any NBT")
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_bytes<E>(self, _: &[u8]) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<(), E> {
+        self.intern(Cow::Borrowed(v));
+        Ok(())
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<(), E> {
+        self.intern(Cow::Owned(v.to_owned()));
+        Ok(())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        while seq.next_element_seed(Names::new(self.interned))?.is_some() {}
+        Ok(())
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        while let Some(key) = map.next_key::<Cow<str>>()? {
+            map.next_value_seed(Names {
+                interned: self.interned,
+                is_name: key == "Name",
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// The same names found the slow way, from a `Value`.
+fn names_in(value: &Value, names: &mut HashSet<String>) {
+    match value {
+        Value::Compound(c) => {
+            for (k, v) in c {
+                match v {
+                    Value::String(s) if k == "Name" => {
+                        names.insert(s.clone());
+                    }
+                    v => names_in(v, names),
+                }
+            }
+        }
+        Value::List(l) => l.iter().for_each(|v| names_in(v, names)),
+        _ => {}
+    }
+}
+
+#[test]
+fn intern_block_names_from_bytes() {
+    let mut expected = HashSet::new();
+    names_in(&from_bytes(CHUNK_RAW).unwrap(), &mut expected);
+    assert!(expected.contains("minecraft:air"));
+
+    let mut interned = HashSet::new();
+    let mut de = Deserializer::from_bytes(CHUNK_RAW);
+    Names::new(&mut interned).deserialize(&mut de).unwrap();
+    de.end().unwrap();
+    drop(de);
+
+    // The names outlive the deserializer, borrowing from the input.
+    assert!(interned.iter().all(|name| matches!(name, Cow::Borrowed(_))));
+    let interned: HashSet<String> = interned.into_iter().map(Cow::into_owned).collect();
+    assert_eq!(expected, interned);
+}
+
+#[test]
+fn intern_block_names_from_reader() {
+    let mut expected = HashSet::new();
+    names_in(&from_bytes(CHUNK_RAW).unwrap(), &mut expected);
+
+    let mut interned = HashSet::new();
+    let mut de = Deserializer::from_reader(CHUNK_RAW);
+    Names::new(&mut interned).deserialize(&mut de).unwrap();
+
+    assert!(interned.iter().all(|name| matches!(name, Cow::Owned(_))));
+    assert_eq!(expected.len(), interned.len());
+}
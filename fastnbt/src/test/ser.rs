@@ -0,0 +1,505 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    de::from_bytes, error::ErrorKind, to_bytes, ByteArray, IntArray, LongArray, Map, Tag, Value,
+};
+
+use super::builder::Builder;
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+#[test]
+fn simple_values() {
+    #[derive(Serialize)]
+    struct V {
+        byte: i8,
+        short: i16,
+        int: i32,
+        long: i64,
+        float: f32,
+        double: f64,
+        string: String,
+        flag: bool,
+        unsigned: u16,
+        letter: char,
+    }
+
+    let v = V {
+        byte: 1,
+        short: 2,
+        int: 3,
+        long: 4,
+        float: 5.0,
+        double: 6.0,
+        string: "seven".to_owned(),
+        flag: true,
+        unsigned: 9,
+        letter: 'x',
+    };
+
+    let expected = Builder::new()
+        .start_compound("")
+        .byte("byte", 1)
+        .short("short", 2)
+        .int("int", 3)
+        .long("long", 4)
+        .float("float", 5.0)
+        .double("double", 6.0)
+        .string("string", "seven")
+        .byte("flag", 1)
+        .short("unsigned", 9)
+        .string("letter", "x")
+        .end_compound()
+        .build();
+
+    assert_eq!(expected, to_bytes(&v).unwrap());
+}
+
+#[test]
+fn nested_compounds_and_lists() {
+    #[derive(Serialize)]
+    struct V {
+        inner: Inner,
+        lists: Vec<Vec<i32>>,
+        empty: Vec<i64>,
+    }
+
+    #[derive(Serialize)]
+    struct Inner {
+        a: i8,
+    }
+
+    let v = V {
+        inner: Inner { a: 1 },
+        lists: vec![vec![2, 3], vec![]],
+        empty: vec![],
+    };
+
+    let expected = Builder::new()
+        .start_compound("")
+        .start_compound("inner")
+        .byte("a", 1)
+        .end_compound()
+        .start_list("lists", Tag::List, 2)
+        .tag(Tag::Int)
+        .int_payload(2)
+        .int_payload(2)
+        .int_payload(3)
+        .tag(Tag::End)
+        .int_payload(0)
+        .start_list("empty", Tag::End, 0)
+        .end_compound()
+        .build();
+
+    assert_eq!(expected, to_bytes(&v).unwrap());
+}
+
+#[test]
+fn arrays() {
+    let input = Builder::new()
+        .start_compound("")
+        .byte_array("bytes", &[1, 2, 3])
+        .int_array("ints", &[4, 5])
+        .long_array("longs", &[6])
+        .end_compound()
+        .build();
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct V {
+        bytes: ByteArray,
+        ints: IntArray,
+        longs: LongArray,
+    }
+
+    let v: V = from_bytes(&input).unwrap();
+    assert_eq!(input, to_bytes(&v).unwrap());
+}
+
+#[test]
+fn enums() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum E {
+        Unit,
+        Newtype(i32),
+        Tuple(i8, i8),
+        Struct { a: i8 },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct V {
+        unit: E,
+        newtype: E,
+        tuple: E,
+        structure: E,
+    }
+
+    let v = V {
+        unit: E::Unit,
+        newtype: E::Newtype(1),
+        tuple: E::Tuple(2, 3),
+        structure: E::Struct { a: 4 },
+    };
+
+    let expected = Builder::new()
+        .start_compound("")
+        .string("unit", "Unit")
+        .start_compound("newtype")
+        .int("Newtype", 1)
+        .end_compound()
+        .start_compound("tuple")
+        .start_list("Tuple", Tag::Byte, 2)
+        .byte_payload(2)
+        .byte_payload(3)
+        .end_compound()
+        .start_compound("structure")
+        .start_compound("Struct")
+        .byte("a", 4)
+        .end_compound()
+        .end_compound()
+        .end_compound()
+        .build();
+
+    assert_eq!(expected, to_bytes(&v).unwrap());
+    assert_eq!(v, from_bytes(&expected).unwrap());
+
+    // And in a list, where every variant is a compound but the unit one.
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct L {
+        list: Vec<E>,
+    }
+
+    let l = L {
+        list: vec![E::Newtype(1), E::Tuple(2, 3), E::Struct { a: 4 }],
+    };
+    assert_eq!(l, from_bytes(&to_bytes(&l).unwrap()).unwrap());
+}
+
+#[test]
+fn enum_compound_must_have_one_entry() {
+    #[derive(Deserialize, Debug)]
+    enum E {
+        #[allow(dead_code)]
+        Newtype(i32),
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct V {
+        #[allow(dead_code)]
+        e: E,
+    }
+
+    let empty = Builder::new()
+        .start_compound("")
+        .start_compound("e")
+        .end_compound()
+        .end_compound()
+        .build();
+    assert!(from_bytes::<V>(&empty).is_err());
+
+    let two = Builder::new()
+        .start_compound("")
+        .start_compound("e")
+        .int("Newtype", 1)
+        .int("Newtype", 2)
+        .end_compound()
+        .end_compound()
+        .build();
+    assert!(from_bytes::<V>(&two).is_err());
+}
+
+#[test]
+fn tuple_structs_round_trip() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Pos(f64, f64, f64);
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct V {
+        pos: Pos,
+        after: i32,
+    }
+
+    let v = V {
+        pos: Pos(1.5, -64.0, 3.25),
+        after: 1,
+    };
+    let expected = Builder::new()
+        .start_compound("")
+        .start_list("pos", Tag::Double, 3)
+        .double_payload(1.5)
+        .double_payload(-64.0)
+        .double_payload(3.25)
+        .int("after", 1)
+        .end_compound()
+        .build();
+
+    assert_eq!(expected, to_bytes(&v).unwrap());
+    assert_eq!(v, from_bytes(&expected).unwrap());
+
+    let too_long = Builder::new()
+        .start_compound("")
+        .start_list("pos", Tag::Double, 4)
+        .double_payload(1.5)
+        .double_payload(-64.0)
+        .double_payload(3.25)
+        .double_payload(0.0)
+        .int("after", 1)
+        .end_compound()
+        .build();
+    let err = from_bytes::<V>(&too_long).unwrap_err();
+    assert!(err.to_string().contains("list of 3 elements, found 4"), "{}", err);
+}
+
+#[test]
+fn value_chunk_round_trip() {
+    let value: Value = from_bytes(CHUNK_RAW).unwrap();
+    let bytes = to_bytes(&value).unwrap();
+    let back: Value = from_bytes(&bytes).unwrap();
+    assert_eq!(value, back);
+}
+
+#[test]
+fn level_flags_round_trip() {
+    // The flags of a level.dat are bytes of 0 or 1. Its game rules are
+    // strings, even the boolean ones, so stay as strings here.
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Data {
+        hardcore: bool,
+        #[serde(rename = "allowCommands")]
+        allow_commands: bool,
+        raining: bool,
+        thundering: bool,
+        initialized: bool,
+        #[serde(rename = "DifficultyLocked")]
+        difficulty_locked: bool,
+        #[serde(rename = "GameRules")]
+        game_rules: HashMap<String, String>,
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .byte("hardcore", 0)
+        .byte("allowCommands", 1)
+        .byte("raining", 1)
+        .byte("thundering", 0)
+        .byte("initialized", 1)
+        .byte("DifficultyLocked", 0)
+        .start_compound("GameRules")
+        .string("keepInventory", "true")
+        .string("doDaylightCycle", "false")
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let data: Data = from_bytes(&payload).unwrap();
+    assert!(!data.hardcore);
+    assert!(data.allow_commands);
+    assert!(data.raining);
+    assert!(!data.thundering);
+    assert!(data.initialized);
+    assert!(!data.difficulty_locked);
+    assert_eq!("true", data.game_rules["keepInventory"]);
+
+    // Flags are written back as bytes, not just any integral tag.
+    let bytes = to_bytes(&data).unwrap();
+    let value: Value = from_bytes(&bytes).unwrap();
+    match &value {
+        Value::Compound(c) => {
+            assert_eq!(Value::Byte(1), c["allowCommands"]);
+            assert_eq!(Value::Byte(0), c["hardcore"]);
+        }
+        _ => panic!("expected compound"),
+    }
+
+    let back: Data = from_bytes(&bytes).unwrap();
+    assert_eq!(data, back);
+}
+
+#[test]
+fn heterogeneous_list_is_an_error() {
+    let mut map = Map::new();
+    map.insert(
+        "list".to_owned(),
+        Value::List(vec![Value::Byte(1), Value::Int(2)]),
+    );
+
+    let err = to_bytes(&Value::Compound(map)).unwrap_err();
+    assert_eq!(
+        &ErrorKind::HeterogeneousList {
+            index: 1,
+            expected: Tag::Byte,
+            found: Tag::Int,
+        },
+        err.kind()
+    );
+    assert!(err.to_string().contains("list element 1"));
+}
+
+#[test]
+fn heterogeneous_nested_list_reports_inner_index() {
+    #[derive(Serialize)]
+    struct V {
+        list: Vec<Vec<Value>>,
+    }
+
+    let v = V {
+        list: vec![vec![
+            Value::Long(1),
+            Value::Long(2),
+            Value::String("3".into()),
+        ]],
+    };
+
+    let err = to_bytes(&v).unwrap_err();
+    assert_eq!(
+        &ErrorKind::HeterogeneousList {
+            index: 2,
+            expected: Tag::Long,
+            found: Tag::String,
+        },
+        err.kind()
+    );
+}
+
+#[test]
+fn root_must_be_compound() {
+    for err in [
+        to_bytes(&1i32).unwrap_err(),
+        to_bytes(&"string").unwrap_err(),
+        to_bytes(&vec![1, 2, 3]).unwrap_err(),
+        to_bytes(&Value::List(vec![])).unwrap_err(),
+        to_bytes(&Value::IntArray(IntArray::new(vec![1]))).unwrap_err(),
+    ] {
+        assert_eq!(&ErrorKind::RootMustBeCompound, err.kind());
+    }
+}
+
+#[test]
+fn unsigned_out_of_range_is_an_error() {
+    #[derive(Serialize)]
+    struct V {
+        a: u8,
+    }
+
+    assert!(to_bytes(&V { a: 127 }).is_ok());
+    assert!(to_bytes(&V { a: 128 }).is_err());
+}
+
+#[test]
+fn non_string_keys_are_an_error() {
+    let mut map = HashMap::new();
+    map.insert(true, 2);
+    let err = to_bytes(&map).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidKeyType(Tag::Byte), err.kind());
+    assert!(err.to_string().contains("found Byte"), "{}", err);
+
+    let mut map = HashMap::new();
+    map.insert((1, 2), 2);
+    let err = to_bytes(&map).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidKeyType(Tag::List), err.kind());
+
+    let map = HashMap::from([((), 2)]);
+    let err = to_bytes(&map).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidKeyType(Tag::End), err.kind());
+}
+
+#[test]
+fn integer_keys_are_strings() {
+    let map = HashMap::from([(-1i64, 2i8)]);
+    let expected = Builder::new()
+        .start_compound("")
+        .byte("-1", 2)
+        .end_compound()
+        .build();
+
+    assert_eq!(expected, to_bytes(&map).unwrap());
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct OptionalChunk {
+    heightmaps: Option<Heightmaps>,
+    status: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct Heightmaps {
+    motion_blocking: Option<LongArray>,
+}
+
+#[test]
+fn none_fields_are_skipped() {
+    let chunk = OptionalChunk {
+        heightmaps: None,
+        status: "full".to_owned(),
+    };
+
+    let expected = Builder::new()
+        .start_compound("")
+        .string("Status", "full")
+        .end_compound()
+        .build();
+
+    let bytes = to_bytes(&chunk).unwrap();
+    assert_eq!(expected, bytes);
+    assert_eq!(chunk, from_bytes(&bytes).unwrap());
+}
+
+#[test]
+fn some_fields_are_the_inner_value() {
+    let chunk = OptionalChunk {
+        heightmaps: Some(Heightmaps {
+            motion_blocking: Some(LongArray::new(vec![1, 2, 3])),
+        }),
+        status: "full".to_owned(),
+    };
+
+    let expected = Builder::new()
+        .start_compound("")
+        .start_compound("Heightmaps")
+        .long_array("MOTION_BLOCKING", &[1, 2, 3])
+        .end_compound()
+        .string("Status", "full")
+        .end_compound()
+        .build();
+
+    let bytes = to_bytes(&chunk).unwrap();
+    assert_eq!(expected, bytes);
+    assert_eq!(chunk, from_bytes(&bytes).unwrap());
+}
+
+#[test]
+fn none_map_values_are_skipped() {
+    let mut map = HashMap::new();
+    map.insert("a", None);
+    map.insert("b", Some(1i8));
+
+    let expected = Builder::new()
+        .start_compound("")
+        .byte("b", 1)
+        .end_compound()
+        .build();
+
+    assert_eq!(expected, to_bytes(&map).unwrap());
+}
+
+#[test]
+fn none_in_list_is_an_error() {
+    #[derive(Serialize)]
+    struct V {
+        list: Vec<Option<i32>>,
+    }
+
+    let v = V {
+        list: vec![Some(1), None, Some(3)],
+    };
+
+    let err = to_bytes(&v).unwrap_err();
+    assert!(
+        err.to_string().contains("list element 1 is None"),
+        "{}",
+        err
+    );
+}
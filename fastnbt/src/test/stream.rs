@@ -1,6 +1,11 @@
+use std::io::Read;
+
 use super::builder::Builder;
 use crate::stream::{ErrorKind, Name, Parser, Result, Value};
-use crate::Tag;
+use crate::{ByteArray, IntArray, LongArray, Map, Tag};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+const CHUNK_1_14_RAW: &[u8] = include_bytes!("resources/chunk1.14.nbt");
 
 fn name(n: &str) -> Name {
     Some(n.to_owned())
@@ -437,3 +442,242 @@ fn list_of_compounds() -> Result<()> {
     assert!(matches!(parser.next(), Err(e) if e.is_eof()));
     Ok(())
 }
+
+/// Build a [`crate::Value`] from the parser's values, for the value that has
+/// just been started by `first`.
+fn to_full_value<R: Read>(parser: &mut Parser<R>, first: Value) -> Result<crate::Value> {
+    use crate::Value as V;
+
+    Ok(match first {
+        Value::Byte(_, v) => V::Byte(v),
+        Value::Short(_, v) => V::Short(v),
+        Value::Int(_, v) => V::Int(v),
+        Value::Long(_, v) => V::Long(v),
+        Value::Float(_, v) => V::Float(v),
+        Value::Double(_, v) => V::Double(v),
+        Value::String(_, v) => V::String(v),
+        Value::ByteArray(_, v) => V::ByteArray(ByteArray::new(v)),
+        Value::IntArray(_, v) => V::IntArray(IntArray::new(v)),
+        Value::LongArray(_, v) => V::LongArray(LongArray::new(v)),
+        Value::List(..) => {
+            let mut list = vec![];
+            loop {
+                match parser.next()? {
+                    Value::ListEnd => break,
+                    v => list.push(to_full_value(parser, v)?),
+                }
+            }
+            V::List(list)
+        }
+        Value::Compound(_) => {
+            let mut compound = Map::new();
+            loop {
+                let v = parser.next()?;
+                let key = match &v {
+                    Value::CompoundEnd => break,
+                    Value::Byte(n, _)
+                    | Value::Short(n, _)
+                    | Value::Int(n, _)
+                    | Value::Long(n, _)
+                    | Value::Float(n, _)
+                    | Value::Double(n, _)
+                    | Value::String(n, _)
+                    | Value::ByteArray(n, _)
+                    | Value::IntArray(n, _)
+                    | Value::LongArray(n, _)
+                    | Value::List(n, _, _)
+                    | Value::Compound(n) => n.clone().unwrap(),
+                    Value::ListEnd => panic!("list end in compound"),
+                };
+                compound.insert(key, to_full_value(parser, v)?);
+            }
+            V::Compound(compound)
+        }
+        Value::CompoundEnd | Value::ListEnd => panic!("unexpected end"),
+    })
+}
+
+#[test]
+fn same_as_full_value() -> Result<()> {
+    for chunk in [CHUNK_RAW, CHUNK_1_14_RAW] {
+        let mut parser = Parser::new(chunk);
+        let first = parser.next()?;
+        let value = to_full_value(&mut parser, first)?;
+        assert!(matches!(parser.next(), Err(e) if e.is_eof()));
+
+        let expected: crate::Value = crate::de::from_bytes(chunk).unwrap();
+        assert_eq!(expected, value);
+    }
+    Ok(())
+}
+
+#[test]
+fn skip_value_of_every_tag() -> Result<()> {
+    let payload = Builder::new()
+        .start_compound("")
+        .start_compound("skipped")
+        .byte("b", 1)
+        .short("s", 2)
+        .int("i", 3)
+        .long("l", 4)
+        .float("f", 5.0)
+        .double("d", 6.0)
+        .string("str", "hello")
+        .byte_array("ba", &[1, 2, 3])
+        .int_array("ia", &[1, 2, 3])
+        .long_array("la", &[1, 2, 3])
+        .start_list("list", Tag::Compound, 2)
+        .start_compound("inner")
+        .end_compound()
+        .end_compound()
+        .end_compound()
+        .start_list("empty", Tag::End, 0)
+        .end_compound()
+        .byte("after", 7)
+        .end_compound()
+        .build();
+
+    let mut parser = Parser::new(payload.as_slice());
+    assert_eq!(parser.next()?, Value::Compound(name("")));
+    assert_eq!(parser.next()?, Value::Compound(name("skipped")));
+    parser.skip_value()?;
+    assert_eq!(parser.next()?, Value::Byte(name("after"), 7));
+    assert_eq!(parser.next()?, Value::CompoundEnd);
+    assert!(matches!(parser.next(), Err(e) if e.is_eof()));
+    Ok(())
+}
+
+#[test]
+fn skip_rest_of_list() -> Result<()> {
+    let payload = Builder::new()
+        .start_compound("")
+        .start_list("longs", Tag::Long, 3)
+        .long_payload(1)
+        .long_payload(2)
+        .long_payload(3)
+        .byte("after", 7)
+        .end_compound()
+        .build();
+
+    let mut parser = Parser::new(payload.as_slice());
+    assert_eq!(parser.next()?, Value::Compound(name("")));
+    assert_eq!(parser.next()?, Value::List(name("longs"), Tag::Long, 3));
+    assert_eq!(parser.next()?, Value::Long(None, 1));
+    parser.skip_value()?;
+    assert_eq!(parser.next()?, Value::Byte(name("after"), 7));
+    Ok(())
+}
+
+#[test]
+fn skip_value_outside_compound_is_an_error() {
+    let payload = Builder::new().start_compound("").end_compound().build();
+    let mut parser = Parser::new(payload.as_slice());
+    assert!(parser.skip_value().is_err());
+}
+
+#[test]
+fn skip_value_truncated() -> Result<()> {
+    let payload = Builder::new()
+        .start_compound("")
+        .long_array("la", &[1, 2, 3])
+        .end_compound()
+        .build();
+
+    let mut parser = Parser::new(&payload[..payload.len() - 5]);
+    assert_eq!(parser.next()?, Value::Compound(name("")));
+    let err = parser.skip_value().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::UnexpectedEof));
+    Ok(())
+}
+
+#[test]
+fn array_offsets_locate_elements_in_the_input() -> Result<()> {
+    // Find where the biomes of a real chunk are, then read them in place.
+    let mut parser = Parser::new(CHUNK_RAW);
+    let (biomes, offset) = loop {
+        match parser.next()? {
+            Value::IntArray(Some(n), biomes) if n == "Biomes" => {
+                break (biomes, parser.array_offset().unwrap() as usize);
+            }
+            _ => assert_eq!(None, parser.array_offset()),
+        }
+    };
+    assert_eq!(offset + biomes.len() * 4, parser.position() as usize);
+
+    #[derive(serde::Deserialize)]
+    struct Chunk<'a> {
+        #[serde(rename = "Level", borrow)]
+        level: Level<'a>,
+    }
+    #[derive(serde::Deserialize)]
+    struct Level<'a> {
+        #[serde(rename = "Biomes", borrow)]
+        biomes: crate::borrow::IntArray<'a>,
+    }
+    let chunk: Chunk = crate::de::from_bytes(CHUNK_RAW).unwrap();
+
+    let mut last = None;
+    for (i, (at, biome)) in chunk.level.biomes.iter_with_offsets().enumerate() {
+        assert_eq!(biomes[i], biome);
+        assert_eq!(biome.to_be_bytes(), CHUNK_RAW[offset + at..offset + at + 4]);
+        if let Some(last) = last {
+            assert_eq!(last + 4, at);
+        }
+        last = Some(at);
+    }
+    assert_eq!(Some(1023 * 4), last);
+
+    // The next value is no longer an array.
+    parser.next()?;
+    assert_eq!(None, parser.array_offset());
+    Ok(())
+}
+
+#[test]
+fn position_counts_skipped_input() -> Result<()> {
+    let mut parser = Parser::new(CHUNK_RAW);
+    parser.next()?;
+    parser.skip_value()?;
+    assert_eq!(CHUNK_RAW.len() as u64, parser.position());
+    Ok(())
+}
+
+#[test]
+fn absurd_array_sizes_run_out_of_input() {
+    for tag in [Tag::ByteArray, Tag::IntArray, Tag::LongArray] {
+        let payload = Builder::new()
+            .tag(tag)
+            .name("a")
+            .int_payload(i32::MAX)
+            .raw_bytes(&[0; 16])
+            .build();
+        let mut parser = Parser::new(payload.as_slice());
+        let err = parser.next().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnexpectedEof), "{:?}", tag);
+
+        let payload = Builder::new().tag(tag).name("a").int_payload(-1).build();
+        let mut parser = Parser::new(payload.as_slice());
+        assert!(parser.next().is_err());
+    }
+}
+
+#[test]
+fn absurd_array_size_in_a_chunk() {
+    // Give the first long array of a real chunk a length of 16 GiB.
+    let mut parser = Parser::new(CHUNK_1_14_RAW);
+    let offset = loop {
+        if let Value::LongArray(..) = parser.next().unwrap() {
+            break parser.array_offset().unwrap() as usize;
+        }
+    };
+    let mut data = CHUNK_1_14_RAW.to_vec();
+    data[offset - 4..offset].copy_from_slice(&i32::MAX.to_be_bytes());
+
+    let mut parser = Parser::new(data.as_slice());
+    let err = loop {
+        if let Err(e) = parser.next() {
+            break e;
+        }
+    };
+    assert!(matches!(err.kind(), ErrorKind::UnexpectedEof));
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    de::{from_bytes, from_bytes_with_opts, from_reader, DeOpts, Deserializer},
+    error::ErrorKind,
+    to_bytes, Map, Tag, Value,
+};
+
+use super::builder::Builder;
+
+fn contains(data: &[u8], needle: &[u8]) -> bool {
+    data.windows(needle.len()).any(|w| w == needle)
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Sign {
+    id: String,
+    #[serde(rename = "Text1")]
+    text1: String,
+}
+
+#[test]
+fn sign_with_emoji() {
+    let sign = Sign {
+        id: "minecraft:sign".to_owned(),
+        text1: r#"{"text":"hi 😈"}"#.to_owned(),
+    };
+
+    let data = to_bytes(&sign).unwrap();
+
+    // Java writes the emoji as a surrogate pair, each half as 3 bytes, rather
+    // than as the 4 bytes of UTF-8.
+    assert!(contains(&data, &[0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x88]));
+    assert!(!contains(&data, "😈".as_bytes()));
+
+    assert_eq!(sign, from_bytes(&data).unwrap());
+    assert_eq!(sign, from_reader(data.as_slice()).unwrap());
+}
+
+#[test]
+fn embedded_nul() {
+    let value = Value::Compound(Map::from([(
+        "a\0b".to_owned(),
+        Value::String("c\0d".to_owned()),
+    )]));
+
+    let data = to_bytes(&value).unwrap();
+
+    // Java writes NUL as two bytes, so that strings never contain a zero byte.
+    assert!(contains(&data, b"a\xc0\x80b"));
+    assert!(contains(&data, b"c\xc0\x80d"));
+
+    assert_eq!(value, from_bytes::<Value>(&data).unwrap());
+    assert_eq!(value, from_reader::<_, Value>(data.as_slice()).unwrap());
+}
+
+fn with_raw_string(raw: &[u8]) -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .tag(Tag::String)
+        .name("a")
+        .raw_len(raw.len())
+        .raw_bytes(raw)
+        .end_compound()
+        .build()
+}
+
+fn lossy(raw: &[u8]) -> String {
+    let data = with_raw_string(raw);
+    let opts = DeOpts::new().lossy_strings(true);
+
+    let value: HashMap<String, String> = from_bytes_with_opts(&data, opts).unwrap();
+
+    let mut de = Deserializer::from_reader(data.as_slice()).with_opts(opts);
+    let read = HashMap::<String, String>::deserialize(&mut de).unwrap();
+    assert_eq!(value, read);
+
+    value["a"].clone()
+}
+
+#[test]
+fn invalid_string_is_error_by_default() {
+    let data = with_raw_string(b"a\xffb");
+    let err = from_bytes::<Value>(&data).unwrap_err();
+    assert_eq!(&ErrorKind::Nonunicode(b"a\xffb".to_vec()), err.kind());
+}
+
+#[test]
+fn lossy_invalid_bytes() {
+    assert_eq!("a\u{fffd}b", lossy(b"a\xffb"));
+    // A truncated sequence, each byte replaced.
+    assert_eq!("a\u{fffd}\u{fffd}", lossy(b"a\xe0\x80"));
+    // An overlong encoding of '/'.
+    assert_eq!("\u{fffd}", lossy(b"\xc0\xaf"));
+}
+
+#[test]
+fn lossy_keeps_valid_cesu8() {
+    let mut raw = cesu8::to_java_cesu8("😈").into_owned();
+    raw.extend_from_slice(b"\xc0\x80\xff");
+    assert_eq!("😈\0\u{fffd}", lossy(&raw));
+}
+
+#[test]
+fn lossy_unpaired_surrogates() {
+    // A high surrogate without its low half, and the other way around.
+    assert_eq!("\u{fffd}x", lossy(b"\xed\xa0\xbdx"));
+    assert_eq!("x\u{fffd}", lossy(b"x\xed\xb8\x88"));
+}
+
+#[test]
+fn lossy_little_endian() {
+    // The root compound, then the string "a" with 0xff in it.
+    let data = [10, 0, 0, 8, 1, 0, b'a', 2, 0, b'x', 0xff, 0];
+
+    let mut de = Deserializer::from_bytes_le(&data);
+    assert!(HashMap::<String, String>::deserialize(&mut de).is_err());
+
+    let mut de = Deserializer::from_bytes_le(&data).with_opts(DeOpts::new().lossy_strings(true));
+    let value = HashMap::<String, String>::deserialize(&mut de).unwrap();
+    assert_eq!("x\u{fffd}", value["a"]);
+}
@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+use std::convert::TryFrom;
+
+use crate::{ByteArray, Map, Tag, Value};
+
+#[test]
+fn every_tag_round_trips_through_its_byte() {
+    let tags: Vec<Tag> = (0..=12).map(|b| Tag::try_from(b).unwrap()).collect();
+    for (b, &tag) in tags.iter().enumerate() {
+        assert_eq!(b as u8, u8::from(tag));
+    }
+    // Each byte gives a different tag.
+    assert_eq!(13, tags.iter().collect::<HashSet<_>>().len());
+}
+
+#[test]
+fn invalid_bytes_are_rejected() {
+    for b in 13..=u8::MAX {
+        assert_eq!(Err(()), Tag::try_from(b));
+    }
+}
+
+#[test]
+fn display_uses_spec_names() {
+    assert_eq!("TAG_End", Tag::End.to_string());
+    assert_eq!("TAG_Byte_Array", Tag::ByteArray.to_string());
+    assert_eq!("TAG_Compound", Tag::Compound.to_string());
+    assert_eq!("TAG_Long_Array", format!("{}", Tag::LongArray));
+    assert_eq!("TAG_Int_Array", Tag::IntArray.name());
+}
+
+#[test]
+fn classification() {
+    let numeric: Vec<Tag> = (0..=12)
+        .map(|b| Tag::try_from(b).unwrap())
+        .filter(|t| t.is_numeric())
+        .collect();
+    assert_eq!(
+        vec![
+            Tag::Byte,
+            Tag::Short,
+            Tag::Int,
+            Tag::Long,
+            Tag::Float,
+            Tag::Double
+        ],
+        numeric
+    );
+
+    let arrays: Vec<Tag> = (0..=12)
+        .map(|b| Tag::try_from(b).unwrap())
+        .filter(|t| t.is_array())
+        .collect();
+    assert_eq!(vec![Tag::ByteArray, Tag::IntArray, Tag::LongArray], arrays);
+    assert!(!Tag::List.is_array());
+}
+
+#[test]
+fn of_value() {
+    assert_eq!(Tag::Float, Tag::of(&Value::Float(1.0)));
+    assert_eq!(Tag::Double, Tag::of(&Value::Double(1.0)));
+    assert_eq!(Tag::List, Tag::of(&Value::List(vec![])));
+    assert_eq!(Tag::Compound, Tag::of(&Value::Compound(Map::new())));
+    assert_eq!(
+        Tag::ByteArray,
+        Tag::of(&Value::ByteArray(ByteArray::new(vec![])))
+    );
+}
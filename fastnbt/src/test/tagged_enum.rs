@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{de::from_bytes, to_bytes, ByteArray, IntArray, LongArray, Tag, Uuid};
+
+use super::builder::Builder;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct Item {
+    #[serde(rename = "Slot")]
+    slot: i8,
+    id: String,
+    #[serde(rename = "Count")]
+    count: i8,
+}
+
+// Block entities as found in a chunk's block_entities list, told apart by id.
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(tag = "id")]
+enum BlockEntity {
+    #[serde(rename = "minecraft:chest")]
+    Chest {
+        #[serde(rename = "Items")]
+        items: Vec<Item>,
+    },
+
+    #[serde(rename = "minecraft:shulker_box")]
+    ShulkerBox {
+        #[serde(rename = "keepPacked", with = "crate::flag")]
+        keep_packed: bool,
+    },
+
+    #[serde(rename = "minecraft:conduit")]
+    Conduit {
+        #[serde(rename = "Target")]
+        target: Uuid,
+    },
+
+    // Not a real block entity, but has every array type.
+    #[serde(rename = "example:arrays")]
+    Arrays {
+        bytes: ByteArray,
+        ints: IntArray,
+        longs: LongArray,
+    },
+
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize, Debug)]
+struct Chunk {
+    block_entities: Vec<BlockEntity>,
+}
+
+fn chunk() -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .start_list("block_entities", Tag::Compound, 5)
+        // The id is not always first.
+        .start_list("Items", Tag::Compound, 1)
+        .byte("Slot", 3)
+        .string("id", "minecraft:torch")
+        .byte("Count", 16)
+        .end_compound()
+        .int("x", 1)
+        .string("id", "minecraft:chest")
+        .end_compound()
+        .string("id", "minecraft:shulker_box")
+        .byte("keepPacked", 1)
+        .end_compound()
+        .string("id", "minecraft:conduit")
+        .int_array("Target", &[1, 2, 3, 4])
+        .end_compound()
+        .string("id", "example:arrays")
+        .byte_array("bytes", &[-1, 1])
+        .int_array("ints", &[i32::MIN, i32::MAX])
+        .long_array("longs", &[i64::MIN, 0, i64::MAX])
+        .end_compound()
+        .string("id", "minecraft:sign")
+        .string("Text1", "{\"text\":\"hello\"}")
+        .long_array("ignored", &[1, 2])
+        .end_compound()
+        .end_compound()
+        .build()
+}
+
+#[test]
+fn mixed_block_entities() {
+    let chunk: Chunk = from_bytes(&chunk()).unwrap();
+    let entities = &chunk.block_entities;
+
+    assert_eq!(5, entities.len());
+    assert_eq!(
+        BlockEntity::Chest {
+            items: vec![Item {
+                slot: 3,
+                id: "minecraft:torch".to_owned(),
+                count: 16,
+            }]
+        },
+        entities[0]
+    );
+    assert_eq!(BlockEntity::ShulkerBox { keep_packed: true }, entities[1]);
+    assert_eq!(
+        BlockEntity::Conduit {
+            target: Uuid::from_ints([1, 2, 3, 4])
+        },
+        entities[2]
+    );
+    assert_eq!(BlockEntity::Unknown, entities[4]);
+}
+
+#[test]
+fn arrays_survive_buffering() {
+    let chunk: Chunk = from_bytes(&chunk()).unwrap();
+
+    match &chunk.block_entities[3] {
+        BlockEntity::Arrays { bytes, ints, longs } => {
+            assert_eq!(&[-1, 1], bytes.as_slice());
+            assert_eq!(&[i32::MIN, i32::MAX], ints.as_slice());
+            assert_eq!(&[i64::MIN, 0, i64::MAX], longs.as_slice());
+        }
+        other => panic!("expected arrays, got {:?}", other),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let entities = vec![
+        BlockEntity::ShulkerBox { keep_packed: false },
+        BlockEntity::Conduit {
+            target: Uuid(u128::MAX),
+        },
+    ];
+
+    #[derive(Serialize, Deserialize)]
+    struct Entities {
+        block_entities: Vec<BlockEntity>,
+    }
+
+    let bytes = to_bytes(&Entities {
+        block_entities: entities,
+    })
+    .unwrap();
+    let back: Entities = from_bytes(&bytes).unwrap();
+
+    assert_eq!(
+        BlockEntity::ShulkerBox { keep_packed: false },
+        back.block_entities[0]
+    );
+    assert_eq!(
+        BlockEntity::Conduit {
+            target: Uuid(u128::MAX)
+        },
+        back.block_entities[1]
+    );
+}
+
+#[test]
+fn plain_bool_in_tagged_enum_is_an_error() {
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "id")]
+    enum E {
+        #[serde(rename = "minecraft:shulker_box")]
+        ShulkerBox {
+            #[serde(rename = "keepPacked")]
+            _keep_packed: bool,
+        },
+    }
+
+    let payload = Builder::new()
+        .start_compound("")
+        .string("id", "minecraft:shulker_box")
+        .byte("keepPacked", 1)
+        .end_compound()
+        .build();
+
+    // Documented as needing fastnbt::flag.
+    assert!(from_bytes::<E>(&payload).is_err());
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    de::{
+        from_bytes, from_bytes_le, from_bytes_network, from_bytes_partial, from_bytes_with_opts,
+        from_reader, DeOpts, Deserializer,
+    },
+    error::ErrorKind,
+    to_bytes, to_bytes_le, to_bytes_network, Value,
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Packet {
+    id: i32,
+    name: String,
+}
+
+fn packet(id: i32) -> Packet {
+    Packet {
+        id,
+        name: format!("packet {}", id),
+    }
+}
+
+#[test]
+fn concatenated_documents() {
+    let first = to_bytes(&packet(1)).unwrap();
+    let second = to_bytes(&packet(2)).unwrap();
+    let data = [first.as_slice(), &second, b"tail"].concat();
+
+    let mut de = Deserializer::from_bytes(&data);
+    assert_eq!(packet(1), Packet::deserialize(&mut de).unwrap());
+    let rest = de.into_inner();
+    assert_eq!(first.len(), data.len() - rest.len());
+
+    let mut de = Deserializer::from_bytes(rest);
+    assert_eq!(packet(2), Packet::deserialize(&mut de).unwrap());
+    assert_eq!(b"tail", de.into_inner());
+}
+
+#[test]
+fn partial_documents_of_different_shapes() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Borrowed<'a> {
+        name: &'a str,
+        values: Vec<i64>,
+    }
+
+    let data = [
+        to_bytes(&packet(1)).unwrap(),
+        to_bytes(&Borrowed {
+            name: "borrowed",
+            values: vec![1, 2],
+        })
+        .unwrap(),
+        to_bytes(&Value::Compound(Default::default())).unwrap(),
+    ]
+    .concat();
+
+    let (first, rest): (Packet, _) = from_bytes_partial(&data).unwrap();
+    assert_eq!(packet(1), first);
+
+    let (second, rest): (Borrowed, _) = from_bytes_partial(rest).unwrap();
+    assert_eq!("borrowed", second.name);
+    assert_eq!(vec![1, 2], second.values);
+
+    let (third, rest): (Value, _) = from_bytes_partial(rest).unwrap();
+    assert_eq!(Value::Compound(Default::default()), third);
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn partial_leaves_anything_after() {
+    let data = [to_bytes(&packet(1)).unwrap().as_slice(), b"tail"].concat();
+    let (v, rest): (Packet, _) = from_bytes_partial(&data).unwrap();
+    assert_eq!(packet(1), v);
+    assert_eq!(b"tail", rest);
+
+    // A document cut short is still an error.
+    assert!(from_bytes_partial::<Packet>(&data[..10]).is_err());
+}
+
+#[test]
+fn trailing_is_an_error_by_default() {
+    let mut data = to_bytes(&packet(1)).unwrap();
+    data.extend(b"tail");
+
+    let err = from_bytes::<Packet>(&data).unwrap_err();
+    assert_eq!(&ErrorKind::TrailingBytes(4), err.kind());
+
+    let err = from_bytes_with_opts::<Packet>(&data, DeOpts::new()).unwrap_err();
+    assert_eq!(&ErrorKind::TrailingBytes(4), err.kind());
+}
+
+#[test]
+fn trailing_allowed_with_opts() {
+    let mut data = to_bytes(&packet(1)).unwrap();
+    data.extend(b"tail");
+
+    let opts = DeOpts::new().allow_trailing(true);
+    assert_eq!(packet(1), from_bytes_with_opts(&data, opts).unwrap());
+    assert!(from_bytes_with_opts::<Value>(&data, opts).is_ok());
+}
+
+#[test]
+fn trailing_little_endian_and_network() {
+    let mut data = to_bytes_le(&packet(1)).unwrap();
+    data.push(0);
+    let err = from_bytes_le::<Packet>(&data).unwrap_err();
+    assert_eq!(&ErrorKind::TrailingBytes(1), err.kind());
+
+    let mut data = to_bytes_network(&packet(1)).unwrap();
+    data.push(0);
+    let err = from_bytes_network::<Packet>(&data).unwrap_err();
+    assert_eq!(&ErrorKind::TrailingBytes(1), err.kind());
+}
+
+#[test]
+fn end_of_exact_input() {
+    let data = to_bytes(&packet(1)).unwrap();
+    let mut de = Deserializer::from_bytes(&data);
+    Packet::deserialize(&mut de).unwrap();
+    de.end().unwrap();
+    assert!(de.into_inner().is_empty());
+}
+
+#[test]
+fn reader_left_after_document() {
+    // Reading from a stream stops at the end of the NBT, leaving the rest.
+    let data = [to_bytes(&packet(1)).unwrap().as_slice(), b"tail"].concat();
+    let mut reader = data.as_slice();
+
+    let mut de = Deserializer::from_reader(&mut reader);
+    assert_eq!(packet(1), Packet::deserialize(&mut de).unwrap());
+    drop(de);
+    assert_eq!(b"tail", reader);
+
+    assert_eq!(packet(1), from_reader(data.as_slice()).unwrap());
+}
@@ -0,0 +1,143 @@
+use serde::Deserialize;
+
+use crate::{
+    de::{from_bytes, from_reader},
+    Tag,
+};
+
+use super::builder::Builder;
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Strict {
+    #[allow(dead_code)]
+    known: i32,
+}
+
+fn with_unknown(unknown: impl FnOnce(Builder) -> Builder) -> Vec<u8> {
+    let b = Builder::new().start_compound("").int("known", 1);
+    unknown(b).end_compound().build()
+}
+
+fn every_unknown_tag() -> Vec<(Tag, Vec<u8>)> {
+    vec![
+        (Tag::Byte, with_unknown(|b| b.byte("unknown", 1))),
+        (Tag::Short, with_unknown(|b| b.short("unknown", 1))),
+        (Tag::Int, with_unknown(|b| b.int("unknown", 1))),
+        (Tag::Long, with_unknown(|b| b.long("unknown", 1))),
+        (Tag::Float, with_unknown(|b| b.float("unknown", 1.0))),
+        (Tag::Double, with_unknown(|b| b.double("unknown", 1.0))),
+        (Tag::String, with_unknown(|b| b.string("unknown", "1"))),
+        (
+            Tag::ByteArray,
+            with_unknown(|b| b.byte_array("unknown", &[1, 2])),
+        ),
+        (
+            Tag::IntArray,
+            with_unknown(|b| b.int_array("unknown", &[1, 2])),
+        ),
+        (
+            Tag::LongArray,
+            with_unknown(|b| b.long_array("unknown", &[1, 2])),
+        ),
+        (
+            Tag::List,
+            with_unknown(|b| b.start_list("unknown", Tag::Long, 1).long_payload(1)),
+        ),
+        (
+            Tag::Compound,
+            with_unknown(|b| b.start_compound("unknown").int("inner", 1).end_compound()),
+        ),
+    ]
+}
+
+#[test]
+fn unknown_field_of_every_tag_is_an_error() {
+    for (tag, data) in every_unknown_tag() {
+        let err = from_bytes::<Strict>(&data).unwrap_err();
+        let msg = err.to_string();
+        assert!(
+            msg.contains("unknown field `unknown`"),
+            "{:?}: {}",
+            tag,
+            msg
+        );
+        assert_eq!(Some("unknown"), err.path(), "{:?}", tag);
+
+        let err = from_reader::<_, Strict>(data.as_slice()).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown field `unknown`"),
+            "{:?}",
+            tag
+        );
+    }
+}
+
+#[test]
+fn unknown_field_first() {
+    let data = Builder::new()
+        .start_compound("")
+        .long_array("BlockStates", &[1, 2, 3])
+        .int("known", 1)
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<Strict>(&data).unwrap_err();
+    assert!(err.to_string().contains("unknown field `BlockStates`"));
+}
+
+#[test]
+fn known_fields_only() {
+    let data = with_unknown(|b| b);
+    assert!(from_bytes::<Strict>(&data).is_ok());
+}
+
+#[test]
+fn unknown_array_in_nested_struct() {
+    #[derive(Deserialize, Debug)]
+    #[serde(deny_unknown_fields)]
+    struct Chunk {
+        #[allow(dead_code)]
+        sections: Vec<Strict>,
+    }
+
+    let data = Builder::new()
+        .start_compound("")
+        .start_list("sections", Tag::Compound, 2)
+        .int("known", 1)
+        .end_compound()
+        .int("known", 2)
+        .long_array("unknown", &[1])
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<Chunk>(&data).unwrap_err();
+    assert!(err.to_string().contains("unknown field `unknown`"));
+    assert_eq!(Some("sections[1].unknown"), err.path());
+}
+
+#[test]
+fn unknown_array_in_tagged_enum() {
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "id", deny_unknown_fields)]
+    enum Entity {
+        #[serde(rename = "minecraft:bat")]
+        Bat {
+            #[serde(rename = "BatFlags")]
+            #[allow(dead_code)]
+            bat_flags: i8,
+        },
+    }
+
+    let data = Builder::new()
+        .start_compound("")
+        .string("id", "minecraft:bat")
+        .byte("BatFlags", 0)
+        .int_array("UUID", &[1, 2, 3, 4])
+        .end_compound()
+        .build();
+
+    let err = from_bytes::<Entity>(&data).unwrap_err();
+    assert!(err.to_string().contains("unknown field `UUID`"), "{}", err);
+}
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{de::from_bytes, to_bytes, ByteArray, IntArray, LongArray, Tag, Value};
+
+use super::builder::Builder;
+
+// A field whose type changed between versions, eg biomes moving from an int
+// array to a list of ints to a single int.
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(untagged)]
+enum Field {
+    Ints(IntArray),
+    Longs(LongArray),
+    Bytes(ByteArray),
+    List(Vec<i32>),
+    Num(i64),
+    Str(String),
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct V {
+    field: Field,
+}
+
+fn field_of(b: Builder) -> Vec<u8> {
+    b.end_compound().build()
+}
+
+fn start() -> Builder {
+    Builder::new().start_compound("")
+}
+
+#[test]
+fn arrays() {
+    let data = field_of(start().int_array("field", &[1, 2, 3]));
+    let v: V = from_bytes(&data).unwrap();
+    assert_eq!(Field::Ints(IntArray::new(vec![1, 2, 3])), v.field);
+
+    let data = field_of(start().long_array("field", &[1, -2]));
+    let v: V = from_bytes(&data).unwrap();
+    assert_eq!(Field::Longs(LongArray::new(vec![1, -2])), v.field);
+
+    let data = field_of(start().byte_array("field", &[1, -2]));
+    let v: V = from_bytes(&data).unwrap();
+    assert_eq!(Field::Bytes(ByteArray::new(vec![1, -2])), v.field);
+}
+
+#[test]
+fn empty_arrays() {
+    let data = field_of(start().int_array("field", &[]));
+    let v: V = from_bytes(&data).unwrap();
+    assert_eq!(Field::Ints(IntArray::new(vec![])), v.field);
+
+    let data = field_of(start().long_array("field", &[]));
+    let v: V = from_bytes(&data).unwrap();
+    assert_eq!(Field::Longs(LongArray::new(vec![])), v.field);
+}
+
+#[test]
+fn lists_and_scalars() {
+    let data = field_of(
+        start()
+            .start_list("field", Tag::Int, 2)
+            .int_payload(4)
+            .int_payload(5),
+    );
+    let v: V = from_bytes(&data).unwrap();
+    assert_eq!(Field::List(vec![4, 5]), v.field);
+
+    let data = field_of(start().int("field", 6));
+    let v: V = from_bytes(&data).unwrap();
+    assert_eq!(Field::Num(6), v.field);
+
+    let data = field_of(start().long("field", 1 << 40));
+    let v: V = from_bytes(&data).unwrap();
+    assert_eq!(Field::Num(1 << 40), v.field);
+
+    let data = field_of(start().string("field", "seven"));
+    let v: V = from_bytes(&data).unwrap();
+    assert_eq!(Field::Str("seven".to_owned()), v.field);
+}
+
+#[test]
+fn array_before_list_in_enum() {
+    // An int array is not mistaken for a list, nor a list for an array.
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(untagged)]
+    enum ListFirst {
+        List(Vec<i32>),
+        Ints(IntArray),
+    }
+
+    let data = field_of(start().int_array("field", &[1, 2]));
+    let v: std::collections::HashMap<String, ListFirst> = from_bytes(&data).unwrap();
+    assert_eq!(ListFirst::Ints(IntArray::new(vec![1, 2])), v["field"]);
+
+    let data = field_of(start().start_list("field", Tag::Int, 1).int_payload(3));
+    let v: std::collections::HashMap<String, ListFirst> = from_bytes(&data).unwrap();
+    assert_eq!(ListFirst::List(vec![3]), v["field"]);
+}
+
+#[test]
+fn round_trip() {
+    for field in [
+        Field::Ints(IntArray::new(vec![1, 2, 3])),
+        Field::Longs(LongArray::new(vec![i64::MIN])),
+        Field::Bytes(ByteArray::new(vec![-1])),
+        Field::List(vec![1]),
+        Field::Num(-5),
+        Field::Str("s".to_owned()),
+    ] {
+        let v = V { field };
+        let data = to_bytes(&v).unwrap();
+        assert_eq!(v, from_bytes(&data).unwrap());
+    }
+}
+
+#[test]
+fn in_a_list_of_compounds() {
+    let data = start()
+        .start_list("fields", Tag::Compound, 2)
+        .int_array("field", &[1])
+        .end_compound()
+        .long("field", 2)
+        .end_compound()
+        .end_compound()
+        .build();
+
+    #[derive(Deserialize)]
+    struct Fields {
+        fields: Vec<V>,
+    }
+
+    let v: Fields = from_bytes(&data).unwrap();
+    assert_eq!(Field::Ints(IntArray::new(vec![1])), v.fields[0].field);
+    assert_eq!(Field::Num(2), v.fields[1].field);
+}
+
+#[test]
+fn value_in_untagged_enum() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(untagged)]
+    enum Known {
+        Num(i64),
+        Other(Value),
+    }
+
+    let data = field_of(start().long_array("field", &[1, 2]));
+    let v: std::collections::HashMap<String, Known> = from_bytes(&data).unwrap();
+    assert_eq!(
+        Known::Other(Value::LongArray(LongArray::new(vec![1, 2]))),
+        v["field"]
+    );
+}
+
+#[test]
+fn other_inputs() {
+    let v = V {
+        field: Field::Longs(LongArray::new(vec![1, 2])),
+    };
+    let data = to_bytes(&v).unwrap();
+
+    assert_eq!(v, crate::de::from_reader(data.as_slice()).unwrap());
+
+    let value: Value = from_bytes(&data).unwrap();
+    assert_eq!(v, crate::from_value(&value).unwrap());
+
+    let data = crate::to_bytes_le(&v).unwrap();
+    assert_eq!(v, crate::de::from_bytes_le(&data).unwrap());
+
+    let data = crate::to_bytes_bedrock_network(&v).unwrap();
+    assert_eq!(v, crate::de::from_bytes_bedrock_network(&data).unwrap());
+}
@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{de::from_bytes, from_value, to_bytes, Uuid, Value};
+
+use super::builder::Builder;
+
+// Notch's UUID, 069a79f4-44e9-4726-a5be-fca90e38aaf5, as stored in the UUID
+// field of a player.dat. The third int is negative.
+const NOTCH: u128 = 0x069a79f4_44e94726_a5befca9_0e38aaf5;
+const NOTCH_INTS: [i32; 4] = [110787060, 1156138790, -1514210135, 238594805];
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Player {
+    #[serde(rename = "UUID")]
+    uuid: u128,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct SignedPlayer {
+    #[serde(rename = "UUID")]
+    uuid: i128,
+}
+
+fn player_nbt(ints: &[i32]) -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .int_array("UUID", ints)
+        .end_compound()
+        .build()
+}
+
+#[test]
+fn deserialize_u128() {
+    let player: Player = from_bytes(&player_nbt(&NOTCH_INTS)).unwrap();
+    assert_eq!(NOTCH, player.uuid);
+}
+
+#[test]
+fn deserialize_i128() {
+    let ints = [-1, -2, -3, -4];
+    let player: SignedPlayer = from_bytes(&player_nbt(&ints)).unwrap();
+
+    let expected = ((-1i128 as u128) << 96)
+        | ((-2i32 as u32 as u128) << 64)
+        | ((-3i32 as u32 as u128) << 32)
+        | (-4i32 as u32 as u128);
+    assert_eq!(expected as i128, player.uuid);
+    assert!(player.uuid < 0);
+}
+
+#[test]
+fn wrong_length_is_an_error() {
+    for ints in [&[1, 2, 3][..], &[1, 2, 3, 4, 5], &[]] {
+        let err = from_bytes::<Player>(&player_nbt(ints)).unwrap_err();
+        assert!(err.to_string().contains("length 4"), "{}", err);
+    }
+}
+
+#[test]
+fn fields_after_uuid_are_still_read() {
+    #[derive(Deserialize)]
+    struct V {
+        uuid: u128,
+        after: i32,
+    }
+
+    let input = Builder::new()
+        .start_compound("")
+        .int_array("uuid", &NOTCH_INTS)
+        .int("after", 123)
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&input).unwrap();
+    assert_eq!(NOTCH, v.uuid);
+    assert_eq!(123, v.after);
+}
+
+#[test]
+fn list_of_uuids() {
+    #[derive(Deserialize)]
+    struct V {
+        uuids: Vec<u128>,
+    }
+
+    let input = Builder::new()
+        .start_compound("")
+        .start_list("uuids", crate::Tag::IntArray, 2)
+        .int_payload(4)
+        .int_array_payload(&NOTCH_INTS)
+        .int_payload(4)
+        .int_array_payload(&[0, 0, 0, 1])
+        .end_compound()
+        .build();
+
+    let v: V = from_bytes(&input).unwrap();
+    assert_eq!(vec![NOTCH, 1], v.uuids);
+}
+
+#[test]
+fn serialize_round_trip() {
+    let input = player_nbt(&NOTCH_INTS);
+    let player: Player = from_bytes(&input).unwrap();
+    assert_eq!(input, to_bytes(&player).unwrap());
+
+    let signed = SignedPlayer { uuid: -2 };
+    let bytes = to_bytes(&signed).unwrap();
+    assert_eq!(player_nbt(&[-1, -1, -1, -2]), bytes);
+    assert_eq!(signed, from_bytes(&bytes).unwrap());
+}
+
+#[test]
+fn from_value_uuid() {
+    let value: Value = from_bytes(&player_nbt(&NOTCH_INTS)).unwrap();
+    let player: Player = from_value(&value).unwrap();
+    assert_eq!(NOTCH, player.uuid);
+
+    let value: Value = from_bytes(&player_nbt(&[1, 2])).unwrap();
+    assert!(from_value::<Player>(&value).is_err());
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Entity {
+    id: String,
+    #[serde(flatten, with = "crate::uuid::modern_or_legacy")]
+    uuid: Uuid,
+}
+
+#[test]
+fn uuid_display() {
+    assert_eq!(
+        "069a79f4-44e9-4726-a5be-fca90e38aaf5",
+        Uuid(NOTCH).to_string()
+    );
+    assert_eq!("00000000-0000-0000-0000-000000000001", Uuid(1).to_string());
+    assert_eq!(
+        "ffffffff-ffff-ffff-ffff-ffffffffffff",
+        Uuid(u128::MAX).to_string()
+    );
+}
+
+#[test]
+fn uuid_forms_agree() {
+    let uuid = Uuid::from_ints(NOTCH_INTS);
+    assert_eq!(NOTCH, uuid.0);
+    assert_eq!(NOTCH_INTS, uuid.to_ints());
+    assert_eq!(uuid, Uuid::from_most_least(uuid.most(), uuid.least()));
+    assert_eq!(0x069a79f4_44e94726, uuid.most());
+    assert!(uuid.least() < 0);
+}
+
+#[test]
+fn uuid_modern_form() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Player {
+        #[serde(rename = "UUID")]
+        uuid: Uuid,
+    }
+
+    let input = player_nbt(&NOTCH_INTS);
+    let player: Player = from_bytes(&input).unwrap();
+    assert_eq!(Uuid(NOTCH), player.uuid);
+    assert_eq!(input, to_bytes(&player).unwrap());
+}
+
+#[test]
+fn uuid_modern_or_legacy() {
+    let uuid = Uuid(NOTCH);
+
+    let modern = Builder::new()
+        .start_compound("")
+        .string("id", "minecraft:wolf")
+        .int_array("UUID", &NOTCH_INTS)
+        .end_compound()
+        .build();
+
+    let legacy = Builder::new()
+        .start_compound("")
+        .long("UUIDMost", uuid.most())
+        .string("id", "minecraft:wolf")
+        .long("UUIDLeast", uuid.least())
+        .end_compound()
+        .build();
+
+    for input in [&modern, &legacy] {
+        let entity: Entity = from_bytes(input).unwrap();
+        assert_eq!("minecraft:wolf", entity.id);
+        assert_eq!(uuid, entity.uuid);
+
+        // Always written back in the modern form.
+        assert_eq!(modern, to_bytes(&entity).unwrap());
+
+        let value: Value = from_bytes(input).unwrap();
+        let entity: Entity = from_value(&value).unwrap();
+        assert_eq!(uuid, entity.uuid);
+    }
+}
+
+#[test]
+fn uuid_missing_or_partial_is_an_error() {
+    let missing = Builder::new()
+        .start_compound("")
+        .string("id", "minecraft:wolf")
+        .end_compound()
+        .build();
+
+    let half = Builder::new()
+        .start_compound("")
+        .string("id", "minecraft:wolf")
+        .long("UUIDMost", 1)
+        .end_compound()
+        .build();
+
+    let short = Builder::new()
+        .start_compound("")
+        .string("id", "minecraft:wolf")
+        .int_array("UUID", &[1, 2, 3])
+        .end_compound()
+        .build();
+
+    for input in [missing, half, short] {
+        assert!(from_bytes::<Entity>(&input).is_err());
+    }
+}
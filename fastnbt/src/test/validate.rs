@@ -0,0 +1,258 @@
+use crate::{
+    de::{from_bytes, DeOpts},
+    error::ErrorKind,
+    to_bytes, validate, Tag, Value,
+};
+
+use super::{builder::Builder, with_stack};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+const CHUNK_1_14_RAW: &[u8] = include_bytes!("resources/chunk1.14.nbt");
+
+/// The stats worked out the slow way from a `Value`.
+#[derive(Default, Debug, PartialEq)]
+struct Expected {
+    counts: [usize; 13],
+    max_depth: usize,
+    string_bytes: usize,
+    largest_array: usize,
+}
+
+impl Expected {
+    fn of(value: &Value, depth: usize, e: &mut Expected) {
+        let tag = match value {
+            Value::Byte(_) => Tag::Byte,
+            Value::Short(_) => Tag::Short,
+            Value::Int(_) => Tag::Int,
+            Value::Long(_) => Tag::Long,
+            Value::Float(_) => Tag::Float,
+            Value::Double(_) => Tag::Double,
+            Value::String(s) => {
+                e.string_bytes += cesu8::to_java_cesu8(s).len();
+                Tag::String
+            }
+            Value::ByteArray(a) => {
+                e.largest_array = e.largest_array.max(a.len());
+                Tag::ByteArray
+            }
+            Value::IntArray(a) => {
+                e.largest_array = e.largest_array.max(a.len());
+                Tag::IntArray
+            }
+            Value::LongArray(a) => {
+                e.largest_array = e.largest_array.max(a.len());
+                Tag::LongArray
+            }
+            Value::List(l) => {
+                e.max_depth = e.max_depth.max(depth);
+                l.iter().for_each(|v| Expected::of(v, depth + 1, e));
+                Tag::List
+            }
+            Value::Compound(c) => {
+                e.max_depth = e.max_depth.max(depth);
+                c.values().for_each(|v| Expected::of(v, depth + 1, e));
+                Tag::Compound
+            }
+        };
+        e.counts[u8::from(tag) as usize] += 1;
+    }
+}
+
+fn check_against_value(data: &[u8]) {
+    let mut expected = Expected::default();
+    Expected::of(&from_bytes(data).unwrap(), 1, &mut expected);
+
+    let stats = validate(data).unwrap();
+    for tag in 0..13 {
+        let tag = std::convert::TryFrom::try_from(tag).unwrap();
+        assert_eq!(
+            expected.counts[u8::from(tag) as usize],
+            stats.count(tag),
+            "{:?}",
+            tag
+        );
+    }
+    assert_eq!(expected.max_depth, stats.max_depth);
+    assert_eq!(expected.string_bytes, stats.string_bytes);
+    assert_eq!(expected.largest_array, stats.largest_array);
+}
+
+#[test]
+fn real_chunks() {
+    check_against_value(CHUNK_RAW);
+    check_against_value(CHUNK_1_14_RAW);
+
+    let stats = validate(CHUNK_RAW).unwrap();
+    assert!(stats.count(Tag::Compound) > 1);
+    assert!(stats.largest_array >= 1024);
+}
+
+#[test]
+fn every_tag() {
+    let data = Builder::new()
+        .start_compound("")
+        .byte("b", 1)
+        .short("s", 2)
+        .int("i", 3)
+        .long("l", 4)
+        .float("f", 5.0)
+        .double("d", 6.0)
+        .string("str", "seven")
+        .string("🦀", "🦀")
+        .byte_array("ba", &[1, 2])
+        .int_array("ia", &[1, 2, 3])
+        .long_array("la", &[1])
+        .start_list("list", Tag::Compound, 2)
+        .start_list("inner", Tag::Short, 1)
+        .short_payload(1)
+        .end_compound()
+        .end_compound()
+        .start_list("empty", Tag::End, 0)
+        .end_compound()
+        .build();
+
+    let stats = validate(&data).unwrap();
+    assert_eq!(0, stats.count(Tag::End));
+    assert_eq!(2, stats.count(Tag::Short));
+    assert_eq!(3, stats.count(Tag::List));
+    assert_eq!(3, stats.count(Tag::Compound));
+    assert_eq!(4, stats.max_depth);
+    // The crab is a surrogate pair in CESU-8, 6 bytes.
+    assert_eq!(5 + 6, stats.string_bytes);
+    assert_eq!(3, stats.largest_array);
+
+    check_against_value(&data);
+}
+
+#[test]
+fn empty_root() {
+    let data = to_bytes(&Value::Compound(Default::default())).unwrap();
+    let stats = validate(&data).unwrap();
+    assert_eq!(1, stats.count(Tag::Compound));
+    assert_eq!(1, stats.max_depth);
+}
+
+#[test]
+fn truncated_anywhere() {
+    // Cutting the chunk short anywhere is an error and never a panic.
+    for len in (0..CHUNK_RAW.len()).step_by(97) {
+        let err = validate(&CHUNK_RAW[..len]).unwrap_err();
+        assert!(err.offset().is_some(), "{}", len);
+    }
+}
+
+#[test]
+fn invalid_tag() {
+    let data = Builder::new()
+        .start_compound("")
+        .start_compound("Level")
+        .tag(Tag::Int)
+        .name("ok")
+        .int_payload(1)
+        .raw_bytes(&[20])
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let err = validate(&data).unwrap_err();
+    assert_eq!(&ErrorKind::InvalidTag(20), err.kind());
+    assert_eq!(Some(data.len() - 3), err.offset());
+    assert_eq!(Some("Level"), err.path());
+}
+
+#[test]
+fn bad_sizes() {
+    let data = Builder::new()
+        .start_compound("")
+        .tag(Tag::IntArray)
+        .name("a")
+        .int_payload(-1)
+        .end_compound()
+        .build();
+    assert_eq!(
+        &ErrorKind::InvalidSize(-1),
+        validate(&data).unwrap_err().kind()
+    );
+
+    let data = Builder::new()
+        .start_compound("")
+        .start_list("a", Tag::Int, -1)
+        .end_compound()
+        .build();
+    assert_eq!(
+        &ErrorKind::InvalidSize(-1),
+        validate(&data).unwrap_err().kind()
+    );
+
+    let data = Builder::new()
+        .start_compound("")
+        .start_list("a", Tag::Long, 1000)
+        .long_payload(1)
+        .end_compound()
+        .build();
+    assert_eq!(
+        &ErrorKind::InvalidSize(1000),
+        validate(&data).unwrap_err().kind()
+    );
+
+    let data = Builder::new()
+        .start_compound("")
+        .start_list("a", Tag::End, 1)
+        .end_compound()
+        .build();
+    assert!(validate(&data).is_err());
+}
+
+#[test]
+fn invalid_string() {
+    let data = Builder::new()
+        .start_compound("")
+        .start_list("Sections", Tag::Compound, 2)
+        .end_compound()
+        .tag(Tag::String)
+        .name("Name")
+        .raw_len(2)
+        .raw_bytes(&[0xff, 0xfe])
+        .end_compound()
+        .end_compound()
+        .build();
+
+    let err = validate(&data).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Nonunicode(_)));
+    assert_eq!(Some("Sections[1].Name"), err.path());
+}
+
+#[test]
+fn not_a_document() {
+    let gzipped = [0x1f, 0x8b, 8, 0];
+    assert!(validate(&gzipped).is_err());
+
+    let data = Builder::new().int("not", 1).build();
+    assert_eq!(
+        &ErrorKind::NoRootCompound,
+        validate(&data).unwrap_err().kind()
+    );
+
+    let mut data = to_bytes(&Value::Compound(Default::default())).unwrap();
+    data.extend(b"more");
+    assert_eq!(
+        &ErrorKind::TrailingBytes(4),
+        validate(&data).unwrap_err().kind()
+    );
+}
+
+#[test]
+fn too_deep() {
+    with_stack(|| {
+        let mut b = Builder::new().start_compound("");
+        for _ in 0..DeOpts::DEFAULT_MAX_DEPTH {
+            b = b.start_compound("a");
+        }
+        for _ in 0..=DeOpts::DEFAULT_MAX_DEPTH {
+            b = b.end_compound();
+        }
+
+        let err = validate(&b.build()).unwrap_err();
+        assert_eq!(&ErrorKind::DepthLimitExceeded, err.kind());
+    });
+}
@@ -1,4 +1,6 @@
-use crate::{de::from_bytes, Tag, Value};
+use std::convert::TryFrom;
+
+use crate::{de::from_bytes, error::ErrorKind, to_bytes, ByteArray, IntArray, Map, Tag, Value};
 
 use super::builder::Builder;
 
@@ -104,6 +106,73 @@ fn distinguish_floats() {
     assert_contains!(v, "b", Value::Double(f), f == 3.21);
 }
 
+/// A zombie from an entity chunk, with its position and motion as Doubles and
+/// rotation as Floats, all in the same compound.
+fn zombie() -> Vec<u8> {
+    Builder::new()
+        .start_compound("")
+        .start_list("Pos", Tag::Double, 3)
+        .double_payload(-12.5)
+        .double_payload(64.0)
+        .double_payload(1.0e-3)
+        .start_list("Motion", Tag::Double, 3)
+        .double_payload(0.0)
+        .double_payload(-0.0784000015258789)
+        .double_payload(0.0)
+        .start_list("Rotation", Tag::Float, 2)
+        .float_payload(171.3)
+        .float_payload(-0.0)
+        .float("FallDistance", 0.0)
+        .double("Speed", 0.23000000417232513)
+        .string("id", "minecraft:zombie")
+        .end_compound()
+        .build()
+}
+
+#[test]
+fn entity_floats_and_doubles_round_trip() {
+    let input = zombie();
+    let v: Value = from_bytes(&input).unwrap();
+
+    let tags = |key: &str| match &v[key] {
+        Value::List(l) => l.iter().map(Value::tag).collect::<Vec<_>>(),
+        other => vec![other.tag()],
+    };
+    assert_eq!(vec![Tag::Double; 3], tags("Pos"));
+    assert_eq!(vec![Tag::Double; 3], tags("Motion"));
+    assert_eq!(vec![Tag::Float; 2], tags("Rotation"));
+    assert_eq!(vec![Tag::Float], tags("FallDistance"));
+    assert_eq!(vec![Tag::Double], tags("Speed"));
+
+    let bytes = to_bytes(&v).unwrap();
+    assert_eq!(input.len(), bytes.len());
+    assert!(v.bitwise_eq(&from_bytes(&bytes).unwrap()));
+    #[cfg(feature = "preserve_order")]
+    assert_eq!(input, bytes);
+
+    // The same through a Value used as a deserializer.
+    let again: Value = crate::from_value(&v).unwrap();
+    assert!(v.bitwise_eq(&again));
+}
+
+#[test]
+fn floats_keep_their_tags_when_buffered() {
+    // Flattening buffers the entries before they reach Value, a different
+    // path through serde to deserializing Value directly.
+    #[derive(serde::Deserialize)]
+    struct Entity {
+        id: String,
+        #[serde(flatten)]
+        rest: Map<String, Value>,
+    }
+
+    let entity: Entity = from_bytes(&zombie()).unwrap();
+    assert_eq!("minecraft:zombie", entity.id);
+    assert!(matches!(entity.rest["FallDistance"], Value::Float(_)));
+    assert!(matches!(entity.rest["Speed"], Value::Double(_)));
+    assert_eq!(Some(&Value::Float(171.3)), entity.rest["Rotation"].get(0));
+}
+
 #[test]
 fn distinguish_string() {
     let input = Builder::new()
@@ -202,3 +271,500 @@ fn distinguish_compound() {
     let v: Value = from_bytes(&input).unwrap();
     assert_contains!(v, "a", Value::Compound(_));
 }
+
+// Floats that == either can't compare or gets wrong, and that must survive a
+// round trip untouched.
+const UNUSUAL_FLOATS: &[u32] = &[
+    0x7fc0_0000, // NaN
+    0x7fc0_1234, // NaN with a payload
+    0xffc0_0000, // negative NaN
+    0x8000_0000, // -0.0
+    0x0000_0001, // smallest subnormal
+    0x807f_ffff, // largest negative subnormal
+];
+
+const UNUSUAL_DOUBLES: &[u64] = &[
+    0x7ff8_0000_0000_0000,
+    0x7ff8_0000_dead_beef,
+    0xfff8_0000_0000_0000,
+    0x8000_0000_0000_0000,
+    0x0000_0000_0000_0001,
+    0x800f_ffff_ffff_ffff,
+];
+
+#[test]
+fn unusual_floats_round_trip_exactly() {
+    for &bits in UNUSUAL_FLOATS {
+        let input = Builder::new()
+            .start_compound("")
+            .float("f", f32::from_bits(bits))
+            .end_compound()
+            .build();
+
+        let v: Value = from_bytes(&input).unwrap();
+        assert_contains!(v, "f", Value::Float(f), f.to_bits() == bits);
+        assert_eq!(input, to_bytes(&v).unwrap(), "float bits {:#x}", bits);
+    }
+}
+
+#[test]
+fn unusual_doubles_round_trip_exactly() {
+    for &bits in UNUSUAL_DOUBLES {
+        let input = Builder::new()
+            .start_compound("")
+            .double("d", f64::from_bits(bits))
+            .end_compound()
+            .build();
+
+        let v: Value = from_bytes(&input).unwrap();
+        assert_contains!(v, "d", Value::Double(d), d.to_bits() == bits);
+        assert_eq!(input, to_bytes(&v).unwrap(), "double bits {:#x}", bits);
+    }
+}
+
+#[test]
+fn unusual_floats_in_lists_round_trip_exactly() {
+    let mut builder =
+        Builder::new()
+            .start_compound("")
+            .start_list("l", Tag::Float, UNUSUAL_FLOATS.len() as i32);
+    for &bits in UNUSUAL_FLOATS {
+        builder = builder.float_payload(f32::from_bits(bits));
+    }
+    let input = builder.end_compound().build();
+
+    let v: Value = from_bytes(&input).unwrap();
+    assert_eq!(input, to_bytes(&v).unwrap());
+}
+
+#[test]
+fn bitwise_eq() {
+    let nan = Value::Double(f64::NAN);
+    assert_ne!(nan, nan);
+    assert!(nan.bitwise_eq(&nan));
+    assert!(!nan.bitwise_eq(&Value::Double(-f64::NAN)));
+
+    let zero = Value::Float(0.0);
+    let neg_zero = Value::Float(-0.0);
+    assert_eq!(zero, neg_zero);
+    assert!(!zero.bitwise_eq(&neg_zero));
+
+    // Same bits but different tags are still different.
+    assert!(!Value::Float(0.0).bitwise_eq(&Value::Double(0.0)));
+
+    let nested = |f: f32| {
+        let mut map = Map::new();
+        map.insert("l".to_owned(), Value::List(vec![Value::Float(f)]));
+        map.insert("i".to_owned(), Value::Int(1));
+        Value::Compound(map)
+    };
+    assert!(nested(f32::NAN).bitwise_eq(&nested(f32::NAN)));
+    assert!(!nested(0.0).bitwise_eq(&nested(-0.0)));
+    assert!(!nested(0.0).bitwise_eq(&Value::Compound(Map::new())));
+}
+
+#[test]
+fn total_cmp_orders_by_tag_then_contents() {
+    use std::cmp::Ordering::*;
+
+    assert_eq!(Less, Value::Byte(100).total_cmp(&Value::Short(-100)));
+    // Float has a lower tag ID than Double.
+    assert_eq!(Less, Value::Float(9.0).total_cmp(&Value::Double(1.0)));
+    assert_eq!(
+        Greater,
+        Value::Compound(Map::new()).total_cmp(&Value::List(vec![]))
+    );
+    assert_eq!(Less, Value::Int(-1).total_cmp(&Value::Int(0)));
+    assert_eq!(
+        Less,
+        Value::String("a".into()).total_cmp(&Value::String("b".into()))
+    );
+    assert_eq!(
+        Less,
+        Value::IntArray(IntArray::new(vec![1, 2]))
+            .total_cmp(&Value::IntArray(IntArray::new(vec![1, 3])))
+    );
+
+    let list = |v: Vec<i8>| Value::List(v.into_iter().map(Value::Byte).collect());
+    assert_eq!(Less, list(vec![1]).total_cmp(&list(vec![1, 0])));
+    assert_eq!(Greater, list(vec![2]).total_cmp(&list(vec![1, 5])));
+    assert_eq!(Equal, list(vec![1, 2]).total_cmp(&list(vec![1, 2])));
+}
+
+#[test]
+fn total_cmp_nan() {
+    use std::cmp::Ordering::*;
+
+    let nan = Value::Double(f64::NAN);
+    assert_eq!(Equal, nan.total_cmp(&nan));
+    assert_eq!(Greater, nan.total_cmp(&Value::Double(f64::INFINITY)));
+    assert_eq!(
+        Less,
+        Value::Float(-f32::NAN).total_cmp(&Value::Float(f32::NEG_INFINITY))
+    );
+    assert_eq!(Less, Value::Float(-0.0).total_cmp(&Value::Float(0.0)));
+
+    // Sorting values with NaNs in them is well defined.
+    let mut values = [
+        Value::Double(1.0),
+        Value::Double(f64::NAN),
+        Value::Double(-1.0),
+        Value::Double(f64::NAN),
+    ];
+    values.sort_by(Value::total_cmp);
+    assert_eq!(Value::Double(-1.0), values[0]);
+    assert_eq!(Value::Double(1.0), values[1]);
+    assert!(values[2].bitwise_eq(&values[3]));
+}
+
+#[test]
+fn total_cmp_nested_compounds() {
+    use std::cmp::Ordering::*;
+
+    let block = |name: &str, facing: Option<&str>| {
+        let mut map = Map::new();
+        map.insert("Name".to_owned(), Value::String(name.to_owned()));
+        if let Some(facing) = facing {
+            let mut props = Map::new();
+            props.insert("facing".to_owned(), Value::String(facing.to_owned()));
+            map.insert("Properties".to_owned(), Value::Compound(props));
+        }
+        Value::Compound(map)
+    };
+
+    // Entries are compared in order of key, so "Name" decides before
+    // "Properties" does.
+    let chest = block("minecraft:chest", Some("south"));
+    assert_eq!(
+        Less,
+        chest.total_cmp(&block("minecraft:dirt", Some("east")))
+    );
+    assert_eq!(
+        Less,
+        block("minecraft:chest", Some("east")).total_cmp(&chest)
+    );
+    assert_eq!(Less, block("minecraft:chest", None).total_cmp(&chest));
+    assert_eq!(Equal, chest.total_cmp(&chest.clone()));
+
+    // Deduplicating a palette.
+    let mut palette = vec![
+        block("minecraft:stone", None),
+        chest.clone(),
+        block("minecraft:air", None),
+        chest.clone(),
+        block("minecraft:stone", None),
+    ];
+    palette.sort_by(Value::total_cmp);
+    palette.dedup_by(|a, b| a.total_cmp(b) == Equal);
+    assert_eq!(
+        vec![
+            block("minecraft:air", None),
+            chest,
+            block("minecraft:stone", None)
+        ],
+        palette
+    );
+}
+
+#[test]
+fn total_cmp_is_stable() {
+    // The order does not depend on the order entries were inserted in.
+    let keys = ["a", "b", "c", "d", "e", "f", "g", "h"];
+    let forward: Map<_, _> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (k.to_string(), Value::Int(i as i32)))
+        .collect();
+    let backward: Map<_, _> = keys
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, k)| (k.to_string(), Value::Int(i as i32)))
+        .collect();
+    let (forward, backward) = (Value::Compound(forward), Value::Compound(backward));
+    assert_eq!(std::cmp::Ordering::Equal, forward.total_cmp(&backward));
+
+    let chunk: Value = from_bytes(include_bytes!("resources/chunk.nbt")).unwrap();
+    let mut sections = chunk["Level"]["Sections"].as_list().unwrap().to_vec();
+    let mut reversed = sections.clone();
+    reversed.reverse();
+    sections.sort_by(Value::total_cmp);
+    reversed.sort_by(Value::total_cmp);
+    assert_eq!(sections, reversed);
+    for pair in sections.windows(2) {
+        assert_ne!(std::cmp::Ordering::Greater, pair[0].total_cmp(&pair[1]));
+        assert_eq!(
+            pair[0].total_cmp(&pair[1]),
+            pair[1].total_cmp(&pair[0]).reverse()
+        );
+    }
+}
+
+#[test]
+fn content_hash_ignores_insertion_order() {
+    let keys = ["id", "x", "y", "z", "Items", "CustomName"];
+    let entity = |keys: &mut dyn Iterator<Item = &&str>| {
+        let map: Map<_, _> = keys
+            .map(|k| (k.to_string(), Value::Int(k.len() as i32)))
+            .collect();
+        Value::Compound(map)
+    };
+    let a = entity(&mut keys.iter());
+    let b = entity(&mut keys.iter().rev());
+    assert_eq!(a.content_hash(), b.content_hash());
+
+    let chunk: Value = from_bytes(include_bytes!("resources/chunk.nbt")).unwrap();
+    let bytes = to_bytes(&chunk).unwrap();
+    let again: Value = from_bytes(&bytes).unwrap();
+    assert_eq!(chunk.content_hash(), again.content_hash());
+}
+
+#[test]
+fn content_hash_changes_with_contents() {
+    let chunk: Value = from_bytes(include_bytes!("resources/chunk.nbt")).unwrap();
+    let hash = chunk.content_hash();
+
+    let mut changed = chunk.clone();
+    if let Value::IntArray(biomes) = &changed["Level"]["Biomes"] {
+        let mut biomes = biomes.to_vec();
+        biomes[100] += 1;
+        changed["Level"]["Biomes"] = Value::IntArray(biomes.into());
+    }
+    assert_ne!(hash, changed.content_hash());
+
+    // Same number, different tag.
+    assert_ne!(Value::Int(1).content_hash(), Value::Long(1).content_hash());
+    // Same contents, split differently.
+    let strings =
+        |a: &str, b: &str| Value::List(vec![Value::String(a.into()), Value::String(b.into())]);
+    assert_ne!(
+        strings("ab", "c").content_hash(),
+        strings("a", "bc").content_hash()
+    );
+}
+
+#[test]
+fn content_hash_floats() {
+    let nan = Value::Double(f64::NAN);
+    assert_eq!(nan.content_hash(), nan.clone().content_hash());
+    assert_ne!(
+        Value::Float(0.0).content_hash(),
+        Value::Float(-0.0).content_hash()
+    );
+}
+
+#[test]
+fn content_hash_is_stable() {
+    // The hash must not change between runs or releases, as it may be stored.
+    let value = Value::Compound(Map::from([
+        ("b".to_owned(), Value::List(vec![Value::Float(1.5)])),
+        ("a".to_owned(), Value::IntArray(IntArray::new(vec![1, -1]))),
+    ]));
+    assert_eq!(7188750828709644726, value.content_hash());
+}
+
+#[test]
+fn canonicalize() {
+    let keys = ["z", "a", "m"];
+    let build = |keys: &mut dyn Iterator<Item = &&str>| {
+        let inner: Map<_, _> = keys.map(|k| (k.to_string(), Value::Byte(1))).collect();
+        let mut map = Map::new();
+        map.insert(
+            "list".to_owned(),
+            Value::List(vec![Value::Compound(inner.clone())]),
+        );
+        map.insert("inner".to_owned(), Value::Compound(inner));
+        Value::Compound(map)
+    };
+    let mut a = build(&mut keys.iter());
+    let mut b = build(&mut keys.iter().rev());
+    a.canonicalize();
+    b.canonicalize();
+    assert_eq!(a, b);
+
+    // With an order to keep, canonical values serialize the same.
+    #[cfg(feature = "preserve_order")]
+    {
+        assert_eq!(to_bytes(&a).unwrap(), to_bytes(&b).unwrap());
+        let inner = a["inner"].as_compound().unwrap();
+        assert_eq!(vec!["a", "m", "z"], inner.keys().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn as_i64_widens_every_integer() {
+    assert_eq!(Some(-128), Value::Byte(i8::MIN).as_i64());
+    assert_eq!(Some(i16::MAX as i64), Value::Short(i16::MAX).as_i64());
+    assert_eq!(Some(i32::MIN as i64), Value::Int(i32::MIN).as_i64());
+    assert_eq!(Some(i64::MAX), Value::Long(i64::MAX).as_i64());
+    assert_eq!(None, Value::Float(1.0).as_i64());
+    assert_eq!(None, Value::Double(1.0).as_i64());
+    assert_eq!(None, Value::String("1".to_owned()).as_i64());
+}
+
+#[test]
+fn as_u64_and_usize_reject_negatives() {
+    assert_eq!(Some(127), Value::Byte(i8::MAX).as_u64());
+    assert_eq!(Some(0), Value::Short(0).as_u64());
+    assert_eq!(Some(i64::MAX as u64), Value::Long(i64::MAX).as_u64());
+    assert_eq!(None, Value::Byte(-1).as_u64());
+    assert_eq!(None, Value::Int(i32::MIN).as_u64());
+    assert_eq!(None, Value::Long(-1).as_u64());
+    assert_eq!(None, Value::Float(1.0).as_u64());
+
+    assert_eq!(Some(3), Value::Int(3).as_usize());
+    assert_eq!(None, Value::Short(-3).as_usize());
+    assert_eq!(None, Value::Double(3.0).as_usize());
+    assert_eq!(
+        usize::try_from(i64::MAX).ok(),
+        Value::Long(i64::MAX).as_usize()
+    );
+}
+
+#[test]
+fn as_bool_takes_byte_short_and_int() {
+    assert_eq!(Some(true), Value::Byte(1).as_bool());
+    assert_eq!(Some(false), Value::Byte(0).as_bool());
+    assert_eq!(Some(true), Value::Byte(-1).as_bool());
+    assert_eq!(Some(true), Value::Short(256).as_bool());
+    assert_eq!(Some(false), Value::Int(0).as_bool());
+    assert_eq!(None, Value::Long(1).as_bool());
+    assert_eq!(None, Value::Float(1.0).as_bool());
+    assert_eq!(None, Value::String("true".to_owned()).as_bool());
+
+    assert_eq!(Value::Byte(1), Value::from(true));
+    assert_eq!(Value::Byte(0), Value::from(false));
+    for b in [true, false] {
+        assert_eq!(Some(b), Value::from(b).as_bool());
+    }
+
+    let mut map = Map::new();
+    map.insert("flag".to_owned(), Value::from(true));
+    let bytes = to_bytes(&Value::Compound(map)).unwrap();
+    let expected = Builder::new()
+        .start_compound("")
+        .byte("flag", 1)
+        .end_compound()
+        .build();
+    assert_eq!(expected, bytes);
+}
+
+#[test]
+fn as_f64_takes_any_number() {
+    assert_eq!(Some(1.5), Value::Float(1.5).as_f64());
+    assert_eq!(Some(-2.25), Value::Double(-2.25).as_f64());
+    assert_eq!(Some(-3.0), Value::Byte(-3).as_f64());
+    assert_eq!(Some(7.0), Value::Int(7).as_f64());
+    // Longs beyond 2^53 are rounded.
+    assert_eq!(
+        Some(9007199254740992.0),
+        Value::Long((1 << 53) + 1).as_f64()
+    );
+    assert_eq!(None, Value::List(vec![]).as_f64());
+
+    assert!(Value::Short(1).is_number());
+    assert!(Value::Double(f64::NAN).is_number());
+    assert!(!Value::String("1".to_owned()).is_number());
+    assert!(!Value::IntArray(IntArray::new(vec![1])).is_number());
+}
+
+#[test]
+fn as_containers() {
+    let chunk: Value = from_bytes(include_bytes!("resources/chunk.nbt")).unwrap();
+    let level = chunk["Level"].as_compound().unwrap();
+    assert!(chunk.is_compound());
+    assert!(!chunk["Level"]["Sections"].is_compound());
+
+    assert_eq!(Some("full"), level["Status"].as_str());
+    assert_eq!(None, level["Status"].as_list());
+
+    let sections = level["Sections"].as_list().unwrap();
+    assert!(!sections.is_empty());
+    assert!(sections.iter().all(|s| s["Y"].as_i64().is_some()));
+
+    assert_eq!(1024, level["Biomes"].as_int_array().unwrap().len());
+    assert_eq!(None, level["Biomes"].as_long_array());
+    assert!(sections
+        .iter()
+        .filter_map(|s| s["BlockStates"].as_long_array())
+        .all(|states| !states.is_empty()));
+
+    let bytes = Value::ByteArray(ByteArray::new(vec![1, 2]));
+    assert_eq!(&[1, 2], bytes.as_byte_array().unwrap().as_slice());
+    assert_eq!(None, bytes.as_compound());
+    assert_eq!(None, bytes.as_str());
+}
+
+#[test]
+fn list_tag() {
+    let chunk: Value = from_bytes(include_bytes!("resources/chunk.nbt")).unwrap();
+    let level = &chunk["Level"];
+    assert_eq!(Some(Tag::Compound), level["Sections"].list_tag());
+    assert_eq!(None, level["TileEntities"].list_tag());
+    assert_eq!(None, level["Biomes"].list_tag());
+
+    let mixed = Value::List(vec![Value::Int(1), Value::Long(2)]);
+    assert_eq!(None, mixed.list_tag());
+    let lists = Value::List(vec![mixed, Value::List(vec![])]);
+    assert_eq!(Some(Tag::List), lists.list_tag());
+}
+
+#[test]
+fn is_valid_nbt_finds_heterogeneous_lists() {
+    let mut chunk: Value = from_bytes(include_bytes!("resources/chunk.nbt")).unwrap();
+    chunk.is_valid_nbt().unwrap();
+
+    let palette = match &mut chunk["Level"]["Sections"][3]["Palette"] {
+        Value::List(palette) => palette,
+        other => panic!("{:?}", other),
+    };
+    let index = palette.len();
+    palette.push(Value::String("minecraft:stone".to_owned()));
+
+    let err = chunk.is_valid_nbt().unwrap_err();
+    assert_eq!(Some("Level.Sections[3].Palette"), err.path());
+    assert!(matches!(
+        err.kind(),
+        &ErrorKind::HeterogeneousList {
+            index: i,
+            expected: Tag::Compound,
+            found: Tag::String,
+        } if i == index
+    ));
+    // The same list fails to serialize.
+    assert!(to_bytes(&chunk).is_err());
+}
+
+#[test]
+fn is_valid_nbt_checks_string_lengths() {
+    let mut value = Value::Compound(Map::new());
+    let long = "a".repeat(u16::MAX as usize);
+    value
+        .insert_path("a/b", Value::String(long.clone()))
+        .unwrap();
+    value.is_valid_nbt().unwrap();
+    to_bytes(&value).unwrap();
+
+    // NUL is two bytes in Java's CESU-8.
+    value
+        .insert_path("a/b", Value::String(long[1..].to_owned() + "\0"))
+        .unwrap();
+    let err = value.is_valid_nbt().unwrap_err();
+    assert_eq!(Some("a.b"), err.path());
+    assert!(to_bytes(&value).is_err());
+
+    let mut value = Value::Compound(Map::new());
+    value.insert_path("a", Value::List(vec![])).unwrap();
+    value
+        .insert_path("a/0", Value::Compound(Map::new()))
+        .unwrap();
+    value
+        .insert_path(&format!("a/0/{}", long), Value::Int(1))
+        .unwrap();
+    value.is_valid_nbt().unwrap();
+    value
+        .insert_path(&format!("a/0/{}b", long), Value::Int(1))
+        .unwrap();
+    let err = value.is_valid_nbt().unwrap_err();
+    assert_eq!(Some(format!("a[0].{}b", long).as_str()), err.path());
+}
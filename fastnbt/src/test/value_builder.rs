@@ -0,0 +1,223 @@
+use crate::{
+    de::from_bytes, error::ErrorKind, to_bytes, CompoundBuilder, IntArray, ListBuilder, Map, Tag,
+    Value,
+};
+
+use super::builder::Builder;
+
+fn pos(x: i32, y: i32, z: i32) -> Value {
+    ListBuilder::new().push(x).push(y).push(z).build().unwrap()
+}
+
+/// A structure block template of a single chest on a stone floor.
+fn structure() -> Value {
+    let palette = ListBuilder::new()
+        .push(CompoundBuilder::new().field("Name", "minecraft:stone"))
+        .push(
+            CompoundBuilder::new()
+                .field("Name", "minecraft:chest")
+                .field(
+                    "Properties",
+                    CompoundBuilder::new()
+                        .field("facing", "north")
+                        .field("waterlogged", "false"),
+                ),
+        )
+        .build()
+        .unwrap();
+
+    let blocks = ListBuilder::new()
+        .push(
+            CompoundBuilder::new()
+                .field("pos", pos(0, 0, 0))
+                .field("state", 0),
+        )
+        .push(
+            CompoundBuilder::new()
+                .field("pos", pos(0, 1, 0))
+                .field("state", 1)
+                .field(
+                    "nbt",
+                    CompoundBuilder::new()
+                        .field("id", "minecraft:chest")
+                        .field("Items", ListBuilder::new().build().unwrap()),
+                ),
+        )
+        .build()
+        .unwrap();
+
+    CompoundBuilder::new()
+        .field("DataVersion", 2586)
+        .field("size", pos(1, 2, 1))
+        .field("palette", palette)
+        .field("blocks", blocks)
+        .field("entities", ListBuilder::new().build().unwrap())
+        .build()
+}
+
+fn int_list(b: Builder, name: &str, ints: &[i32]) -> Builder {
+    ints.iter()
+        .fold(b.start_list(name, Tag::Int, ints.len() as i32), |b, &i| {
+            b.int_payload(i)
+        })
+}
+
+/// The same structure, written as the game would.
+fn structure_fixture() -> Vec<u8> {
+    let b = Builder::new().start_compound("").int("DataVersion", 2586);
+    let b = int_list(b, "size", &[1, 2, 1])
+        .start_list("palette", Tag::Compound, 2)
+        .string("Name", "minecraft:stone")
+        .end_compound()
+        .string("Name", "minecraft:chest")
+        .start_compound("Properties")
+        .string("facing", "north")
+        .string("waterlogged", "false")
+        .end_compound()
+        .end_compound()
+        .start_list("blocks", Tag::Compound, 2);
+    let b = int_list(b, "pos", &[0, 0, 0])
+        .int("state", 0)
+        .end_compound();
+    let b = int_list(b, "pos", &[0, 1, 0])
+        .int("state", 1)
+        .start_compound("nbt")
+        .string("id", "minecraft:chest")
+        .start_list("Items", Tag::End, 0)
+        .end_compound()
+        .end_compound();
+    b.start_list("entities", Tag::End, 0).end_compound().build()
+}
+
+#[test]
+fn structure_template_matches_fixture() {
+    let structure = structure();
+    let fixture = structure_fixture();
+    assert_eq!(from_bytes::<Value>(&fixture).unwrap(), structure);
+
+    let bytes = to_bytes(&structure).unwrap();
+    assert_eq!(fixture.len(), bytes.len());
+    assert_eq!(structure, from_bytes::<Value>(&bytes).unwrap());
+
+    // Entries are written in the order they were added.
+    #[cfg(feature = "preserve_order")]
+    assert_eq!(fixture, bytes);
+}
+
+#[test]
+fn scalars_convert_to_their_tags() {
+    let value = CompoundBuilder::new()
+        .field("b", 1i8)
+        .field("s", 2i16)
+        .field("i", 3)
+        .field("l", 4i64)
+        .field("f", 5.0f32)
+        .field("d", 6.0)
+        .field("flag", true)
+        .field("str", String::from("x"))
+        .field("ints", IntArray::new(vec![7]))
+        .build();
+
+    assert_eq!(Value::Byte(1), value["b"]);
+    assert_eq!(Value::Short(2), value["s"]);
+    assert_eq!(Value::Int(3), value["i"]);
+    assert_eq!(Value::Long(4), value["l"]);
+    assert_eq!(Value::Float(5.0), value["f"]);
+    assert_eq!(Value::Double(6.0), value["d"]);
+    assert_eq!(Value::Byte(1), value["flag"]);
+    assert_eq!(Value::String("x".to_owned()), value["str"]);
+    assert_eq!(Value::IntArray(IntArray::new(vec![7])), value["ints"]);
+}
+
+#[test]
+fn later_fields_replace_earlier() {
+    let value = CompoundBuilder::new()
+        .field("a", 1)
+        .field("a", "two")
+        .build();
+    assert_eq!(Some("two"), value["a"].as_str());
+    assert_eq!(1, value.as_compound().unwrap().len());
+}
+
+#[test]
+fn heterogeneous_list_names_first_offending_index() {
+    let err = ListBuilder::new()
+        .push(1)
+        .push(2)
+        .push(3i64)
+        .push("four")
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        &ErrorKind::HeterogeneousList {
+            index: 2,
+            expected: Tag::Int,
+            found: Tag::Long,
+        },
+        err.kind()
+    );
+
+    let err = ListBuilder::new()
+        .push(CompoundBuilder::new())
+        .push(ListBuilder::new().build().unwrap())
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("list element 1"), "{}", err);
+}
+
+#[test]
+fn list_from_iterator() {
+    let list: ListBuilder = (0..3i16).collect();
+    assert_eq!(
+        Value::List(vec![Value::Short(0), Value::Short(1), Value::Short(2)]),
+        list.build().unwrap()
+    );
+
+    let mut list = ListBuilder::new().push("a");
+    list.extend(vec!["b", "c"]);
+    assert_eq!(Some(3), list.build().unwrap().as_list().map(<[_]>::len));
+}
+
+#[test]
+fn conversions_serialize_with_their_tags() {
+    let inner: Map<String, Value> = Map::from([("x".to_owned(), Value::from(1))]);
+    let entries = vec![
+        ("str", Value::from("a"), Tag::String),
+        ("string", Value::from(String::from("b")), Tag::String),
+        ("byte", Value::from(1i8), Tag::Byte),
+        ("short", Value::from(2i16), Tag::Short),
+        ("int", Value::from(3i32), Tag::Int),
+        ("long", Value::from(4i64), Tag::Long),
+        ("float", Value::from(5.0f32), Tag::Float),
+        ("double", Value::from(6.0f64), Tag::Double),
+        ("bool", Value::from(true), Tag::Byte),
+        ("list", Value::from(vec![Value::from(1)]), Tag::List),
+        ("bytes", Value::from(vec![1i8]), Tag::ByteArray),
+        ("ints", Value::from(vec![1i32]), Tag::IntArray),
+        ("longs", Value::from(vec![1i64]), Tag::LongArray),
+        ("compound", Value::from(inner), Tag::Compound),
+    ];
+
+    let value: Value = entries
+        .iter()
+        .map(|(name, value, _)| (name.to_string(), value.clone()))
+        .collect();
+    let value: Value = from_bytes(&to_bytes(&value).unwrap()).unwrap();
+
+    for (name, expected, tag) in &entries {
+        assert_eq!(*tag, Tag::of(&value[*name]), "{}", name);
+        assert_eq!(expected, &value[*name]);
+    }
+}
+
+#[test]
+fn compound_from_iterator_keeps_last_entry() {
+    let value: Value = vec![
+        ("a".to_owned(), Value::from(1)),
+        ("a".to_owned(), Value::from("two")),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(Some("two"), value["a"].as_str());
+    assert_eq!(1, value.as_compound().unwrap().len());
+}
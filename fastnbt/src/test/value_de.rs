@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{de::from_bytes, from_value, ByteArray, IntArray, LongArray, Value};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct Chunk {
+    level: Level,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct Level {
+    #[serde(rename = "xPos")]
+    x_pos: i32,
+    #[serde(rename = "zPos")]
+    z_pos: i32,
+    biomes: IntArray,
+    sections: Vec<Section>,
+    status: Status,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct Section {
+    y: i8,
+    block_states: Option<LongArray>,
+    sky_light: Option<ByteArray>,
+    palette: Option<Vec<Block>>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct Block {
+    name: String,
+    properties: Option<HashMap<String, String>>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Full,
+    Heightmaps,
+}
+
+fn compound(entries: Vec<(&str, Value)>) -> Value {
+    Value::Compound(
+        entries
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    )
+}
+
+#[test]
+fn chunk_value_to_typed_chunk_matches_direct() {
+    let value: Value = from_bytes(CHUNK_RAW).unwrap();
+    let via_value: Chunk = from_value(&value).unwrap();
+    let direct: Chunk = from_bytes(CHUNK_RAW).unwrap();
+
+    assert_eq!(direct, via_value);
+    assert_eq!(Status::Full, via_value.level.status);
+    assert_eq!(1024, via_value.level.biomes.len());
+}
+
+#[test]
+fn section_arrays_survive_value() {
+    let value: Value = from_bytes(CHUNK_RAW).unwrap();
+    let sections = match &value {
+        Value::Compound(root) => match &root["Level"] {
+            Value::Compound(level) => &level["Sections"],
+            _ => panic!("expected Level compound"),
+        },
+        _ => panic!("expected root compound"),
+    };
+
+    let sections: Vec<Section> = from_value(sections).unwrap();
+    let section = sections.iter().find(|s| s.y == 0).unwrap();
+
+    assert_eq!(342, section.block_states.as_ref().unwrap().len());
+    assert_eq!(2048, section.sky_light.as_ref().unwrap().len());
+    assert_eq!(
+        "minecraft:air",
+        section.palette.as_ref().unwrap()[0].name.as_str()
+    );
+}
+
+#[test]
+fn array_does_not_deserialize_as_other_array() {
+    let value = Value::LongArray(LongArray::new(vec![1, 2, 3]));
+
+    assert_eq!(
+        LongArray::new(vec![1, 2, 3]),
+        from_value::<LongArray>(&value).unwrap()
+    );
+    assert!(from_value::<IntArray>(&value).is_err());
+    assert!(from_value::<ByteArray>(&value).is_err());
+}
+
+#[test]
+fn list_is_not_an_array() {
+    let value = Value::List(vec![Value::Long(1), Value::Long(2)]);
+
+    assert_eq!(vec![1i64, 2], from_value::<Vec<i64>>(&value).unwrap());
+    assert!(from_value::<LongArray>(&value).is_err());
+}
+
+#[test]
+fn value_round_trips_through_itself() {
+    let value: Value = from_bytes(CHUNK_RAW).unwrap();
+    let again: Value = from_value(&value).unwrap();
+    assert_eq!(value, again);
+}
+
+#[test]
+fn borrows_strings_from_value() {
+    #[derive(Deserialize)]
+    struct Block<'a> {
+        #[serde(rename = "Name")]
+        name: &'a str,
+    }
+
+    let value = compound(vec![("Name", Value::String("minecraft:dirt".to_string()))]);
+    let block: Block = from_value(&value).unwrap();
+    assert_eq!("minecraft:dirt", block.name);
+}
+
+#[test]
+fn integral_values_as_bool_and_option() {
+    #[derive(Deserialize)]
+    struct V {
+        a: bool,
+        b: bool,
+        c: Option<i32>,
+        d: Option<i32>,
+    }
+
+    let value = compound(vec![
+        ("a", Value::Byte(1)),
+        ("b", Value::Int(0)),
+        ("c", Value::Int(7)),
+    ]);
+    let v: V = from_value(&value).unwrap();
+    assert!(v.a);
+    assert!(!v.b);
+    assert_eq!(Some(7), v.c);
+    assert_eq!(None, v.d);
+}
+
+#[test]
+fn enum_variants() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum E {
+        Unit,
+        Newtype(i32),
+        Struct { x: i8 },
+    }
+
+    assert_eq!(
+        E::Unit,
+        from_value(&Value::String("Unit".to_string())).unwrap()
+    );
+    assert_eq!(
+        E::Newtype(5),
+        from_value(&compound(vec![("Newtype", Value::Int(5))])).unwrap()
+    );
+    assert_eq!(
+        E::Struct { x: 3 },
+        from_value(&compound(vec![(
+            "Struct",
+            compound(vec![("x", Value::Byte(3))])
+        )]))
+        .unwrap()
+    );
+}
+
+#[test]
+fn transcode_chunk_to_json() {
+    let value: Value = from_bytes(CHUNK_RAW).unwrap();
+
+    let mut out = vec![];
+    let mut ser = serde_json::Serializer::new(&mut out);
+    serde_transcode::transcode(&value, &mut ser).unwrap();
+
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let level = &json["Level"];
+    assert_eq!(16, level["xPos"]);
+    assert_eq!(13, level["zPos"]);
+    assert_eq!("full", level["Status"]);
+
+    // Arrays are presented in the same tag/data form as the main deserializer.
+    assert_eq!(11, level["Biomes"]["tag"]);
+    assert_eq!(1024, level["Biomes"]["data"].as_array().unwrap().len());
+
+    let sections = level["Sections"].as_array().unwrap();
+    assert_eq!(8, sections.len());
+    assert_eq!(-1, sections[0]["Y"]);
+}
+
+#[test]
+fn typed_iter_over_palette() {
+    let value: Value = from_bytes(CHUNK_RAW).unwrap();
+    let direct: Chunk = from_bytes(CHUNK_RAW).unwrap();
+    let section = direct
+        .level
+        .sections
+        .iter()
+        .position(|s| s.palette.is_some())
+        .unwrap();
+
+    let palette = &value["Level"]["Sections"][section]["Palette"];
+    let blocks: Vec<Block> = palette.typed_iter().collect::<Result<_, _>>().unwrap();
+    assert_eq!(direct.level.sections[section].palette, Some(blocks));
+
+    let sections = value["Level"]["Sections"].typed_iter::<Section>();
+    assert_eq!(direct.level.sections.len(), sections.count());
+}
+
+#[test]
+fn typed_iter_errors() {
+    // Each element that is not a Block is an error of its own.
+    let palette = Value::List(vec![
+        compound(vec![("Name", Value::String("minecraft:air".into()))]),
+        compound(vec![("Name", Value::Int(1))]),
+        compound(vec![("Name", Value::String("minecraft:stone".into()))]),
+    ]);
+    let blocks: Vec<_> = palette.typed_iter::<Block>().collect();
+    assert_eq!(3, blocks.len());
+    assert!(blocks[0].is_ok());
+    assert!(blocks[1].is_err());
+    assert_eq!("minecraft:stone", blocks[2].as_ref().unwrap().name);
+
+    // Mistyped target.
+    let mut names = palette.typed_iter::<i32>();
+    assert!(names.next().unwrap().is_err());
+
+    // Not a list at all.
+    let errors: Vec<_> = Value::Int(1).typed_iter::<Block>().collect();
+    assert_eq!(1, errors.len());
+    assert!(errors[0]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("an Int"));
+}
+
+#[test]
+fn typed_get() {
+    let value: Value = from_bytes(CHUNK_RAW).unwrap();
+    let level = &value["Level"];
+
+    assert_eq!(Some(Status::Full), level.typed_get("Status").unwrap());
+    let biomes: IntArray = level.typed_get("Biomes").unwrap().unwrap();
+    assert_eq!(1024, biomes.len());
+    let first: Option<Section> = level["Sections"].typed_get(0).unwrap();
+    assert!(first.is_some());
+
+    assert_eq!(None, level.typed_get::<i32, _>("Missing").unwrap());
+    assert!(level.typed_get::<i32, _>("Status").is_err());
+    assert!(level.typed_get::<Vec<Block>, _>("Sections").is_err());
+}
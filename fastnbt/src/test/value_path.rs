@@ -0,0 +1,350 @@
+use std::borrow::Cow;
+
+use crate::{de::from_bytes, ByteArray, IntArray, LongArray, Map, Value};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+fn chunk() -> Value {
+    from_bytes(CHUNK_RAW).unwrap()
+}
+
+#[test]
+fn list_indices() {
+    let chunk = chunk();
+    let sections = match &chunk["Level"]["Sections"] {
+        Value::List(sections) => sections,
+        other => panic!("{:?}", other),
+    };
+    for (i, section) in sections.iter().enumerate() {
+        let path = format!("Level/Sections/{}", i);
+        assert!(matches!(chunk.get_path(&path), Some(Cow::Borrowed(s)) if s == section));
+        assert_eq!(
+            Some(&section["Y"]),
+            chunk.get_path(&format!("{}/Y", path)).as_deref()
+        );
+    }
+    assert_eq!(
+        None,
+        chunk.get_path(&format!("Level/Sections/{}", sections.len()))
+    );
+    assert_eq!(None, chunk.get_path("Level/Sections/-1"));
+    assert_eq!(None, chunk.get_path("Level/Sections/Y"));
+}
+
+#[test]
+fn array_elements() {
+    let value = Value::Compound(Map::from([
+        (
+            "b".to_owned(),
+            Value::ByteArray(ByteArray::new(vec![1, -2])),
+        ),
+        ("i".to_owned(), Value::IntArray(IntArray::new(vec![3, 4]))),
+        (
+            "l".to_owned(),
+            Value::LongArray(LongArray::new(vec![5, i64::MIN])),
+        ),
+    ]));
+
+    assert_eq!(Some(Cow::Owned(Value::Byte(-2))), value.get_path("b/1"));
+    assert_eq!(Some(Cow::Owned(Value::Int(3))), value.get_path("i/0"));
+    assert_eq!(
+        Some(Cow::Owned(Value::Long(i64::MIN))),
+        value.get_path("l/1")
+    );
+
+    // Out of range, not an index, or indexing into the element.
+    assert_eq!(None, value.get_path("b/2"));
+    assert_eq!(None, value.get_path("i/x"));
+    assert_eq!(None, value.get_path("l/0/0"));
+
+    // The elements are not values, so can't be changed in place.
+    let mut value = value;
+    assert_eq!(None, value.get_path_mut("l/0"));
+    assert!(matches!(value.get_path_mut("l"), Some(Value::LongArray(_))));
+}
+
+#[test]
+fn real_chunk_arrays() {
+    let chunk = chunk();
+    let biomes = match &chunk["Level"]["Biomes"] {
+        Value::IntArray(biomes) => biomes,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(
+        Some(Value::Int(biomes[biomes.len() - 1])),
+        chunk
+            .get_path(&format!("Level/Biomes/{}", biomes.len() - 1))
+            .map(Cow::into_owned)
+    );
+}
+
+#[test]
+fn missing_paths() {
+    let chunk = chunk();
+    assert_eq!(None, chunk.get_path("Missing"));
+    assert_eq!(None, chunk.get_path("Level/Missing/Deeper"));
+    assert_eq!(None, chunk.get_path("Level/Status/Deeper"));
+    assert_eq!(None, chunk.get_path("0"));
+    assert_eq!(None, chunk.get_path("Level/"));
+    assert_eq!(None, chunk.get_path("/Level"));
+}
+
+#[test]
+fn empty_path_is_the_value() {
+    let mut chunk = chunk();
+    let expected = chunk.clone();
+    assert!(matches!(chunk.get_path(""), Some(Cow::Borrowed(v)) if *v == expected));
+    assert_eq!(Some(&expected), chunk.get_path_mut("").as_deref());
+}
+
+#[test]
+fn escaped_keys() {
+    let value = Value::Compound(Map::from([
+        ("a/b".to_owned(), Value::Int(1)),
+        ("c~d".to_owned(), Value::Int(2)),
+        ("~1".to_owned(), Value::Int(3)),
+        (
+            "".to_owned(),
+            Value::Compound(Map::from([("e".to_owned(), Value::Int(4))])),
+        ),
+        ("3".to_owned(), Value::Int(5)),
+    ]));
+
+    assert_eq!(Some(&Value::Int(1)), value.get_path("a~1b").as_deref());
+    assert_eq!(Some(&Value::Int(2)), value.get_path("c~0d").as_deref());
+    assert_eq!(Some(&Value::Int(3)), value.get_path("~01").as_deref());
+    assert_eq!(Some(&Value::Int(4)), value.get_path("/e").as_deref());
+    // Numeric segments are keys in a compound.
+    assert_eq!(Some(&Value::Int(5)), value.get_path("3").as_deref());
+
+    assert_eq!(None, value.get_path("a/b"));
+    // Invalid escapes match nothing.
+    assert_eq!(None, value.get_path("c~d"));
+    assert_eq!(None, value.get_path("c~2d"));
+    assert_eq!(None, value.get_path("a~"));
+}
+
+#[test]
+fn get_path_mut() {
+    let mut chunk = chunk();
+    *chunk.get_path_mut("Level/Sections/0/Y").unwrap() = Value::Byte(-4);
+    assert_eq!(Value::Byte(-4), chunk["Level"]["Sections"][0]["Y"]);
+
+    if let Some(Value::String(status)) = chunk.get_path_mut("Level/Status") {
+        status.push_str("er");
+    }
+    assert_eq!(Value::String("fuller".to_owned()), chunk["Level"]["Status"]);
+
+    assert_eq!(None, chunk.get_path_mut("Level/Missing"));
+    assert_eq!(None, chunk.get_path_mut("Level/Sections/10000"));
+}
+
+#[test]
+fn insert_path_creates_compounds() {
+    let mut value = Value::Compound(Map::new());
+    assert_eq!(
+        None,
+        value
+            .insert_path("Data/GameRules/keepInventory", Value::String("true".into()))
+            .unwrap()
+    );
+    assert_eq!(
+        Some("true"),
+        value["Data"]["GameRules"]["keepInventory"].as_str()
+    );
+
+    // Existing compounds are kept, and the old value is returned.
+    value.insert_path("Data/Version", Value::Int(1)).unwrap();
+    assert_eq!(
+        Some(Value::Int(1)),
+        value.insert_path("Data/Version", Value::Int(2)).unwrap()
+    );
+    assert_eq!(Some(2), value["Data"]["Version"].as_i64());
+    assert!(value["Data"]["GameRules"].is_compound());
+
+    value.insert_path("a~1b/~0", Value::Byte(3)).unwrap();
+    assert_eq!(Some(3), value["a/b"]["~"].as_i64());
+}
+
+#[test]
+fn insert_path_into_lists() {
+    let mut chunk = chunk();
+    let len = chunk["Level"]["Sections"].as_list().unwrap().len();
+    let section = Value::Compound(Map::from([("Y".to_owned(), Value::Byte(20))]));
+
+    // Appending at the length is fine, past it is an error.
+    let path = format!("Level/Sections/{}", len);
+    assert_eq!(None, chunk.insert_path(&path, section.clone()).unwrap());
+    assert_eq!(Some(&section), chunk.get_path(&path).as_deref());
+    let path = format!("Level/Sections/{}", len + 2);
+    assert!(chunk.insert_path(&path, section).is_err());
+    assert_eq!(len + 1, chunk["Level"]["Sections"].as_list().unwrap().len());
+
+    // Replacing an element, and going through one to a compound.
+    let old = chunk.insert_path("Level/Sections/0/Y", Value::Byte(-4));
+    assert!(matches!(old, Ok(Some(Value::Byte(_)))));
+    chunk
+        .insert_path("Level/Sections/0/Extra/Flag", Value::Byte(1))
+        .unwrap();
+    assert_eq!(
+        Some(1),
+        chunk["Level"]["Sections"][0]["Extra"]["Flag"].as_i64()
+    );
+
+    let err = chunk
+        .insert_path("Level/Sections/100/Y", Value::Byte(0))
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("`Level/Sections` is a List of length"));
+
+    // Elements must match the rest of the list.
+    let err = chunk
+        .insert_path("Level/Sections/0", Value::Int(1))
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("already holds a Compound"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn insert_path_through_a_scalar_is_an_error() {
+    let mut chunk = chunk();
+    let before = chunk.clone();
+
+    let err = chunk
+        .insert_path("Level/Status/Deeper/Key", Value::Int(1))
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("`Level/Status` is a String"),
+        "{}",
+        err
+    );
+
+    assert!(chunk.insert_path("Level/Biomes/0", Value::Int(1)).is_err());
+    assert!(chunk
+        .insert_path("Level/Sections/Y", Value::Int(1))
+        .is_err());
+    assert!(chunk.insert_path("Level/a~2", Value::Int(1)).is_err());
+    assert_eq!(before, chunk);
+
+    let mut scalar = Value::Int(1);
+    assert!(scalar.insert_path("a", Value::Int(2)).is_err());
+    assert_eq!(
+        Some(Value::Int(1)),
+        scalar.insert_path("", Value::Int(2)).unwrap()
+    );
+    assert_eq!(Value::Int(2), scalar);
+}
+
+#[test]
+fn remove_path() {
+    let mut chunk = chunk();
+    let sections = chunk["Level"]["Sections"].as_list().unwrap().to_vec();
+
+    // Later elements of the list move down.
+    assert_eq!(
+        Some(&sections[0]),
+        chunk.remove_path("Level/Sections/0").as_ref()
+    );
+    assert_eq!(
+        &sections[1..],
+        chunk["Level"]["Sections"].as_list().unwrap()
+    );
+    assert_eq!(
+        None,
+        chunk.remove_path(&format!("Level/Sections/{}", sections.len()))
+    );
+
+    assert_eq!(
+        Some(Value::String("full".to_owned())),
+        chunk.remove_path("Level/Status")
+    );
+    assert_eq!(None, chunk.get_path("Level/Status"));
+    assert_eq!(None, chunk.remove_path("Level/Status"));
+    assert_eq!(None, chunk.remove_path("Level/Biomes/0"));
+    assert_eq!(None, chunk.remove_path(""));
+}
+
+#[test]
+fn take() {
+    let mut chunk = chunk();
+    let level = chunk.take("Level").unwrap();
+    assert!(level.is_compound());
+    assert_eq!(None, chunk.get("Level"));
+    assert_eq!(None, chunk.take("Level"));
+
+    let mut status = level["Status"].clone();
+    assert_eq!(None, status.take("Level"));
+}
+
+#[test]
+fn find_key_in_structures() {
+    let chunk = chunk();
+    let found = chunk.find_key("id");
+
+    // The mineshaft's start, and each of its pieces in a list.
+    let start = "Level/Structures/Starts/mineshaft";
+    assert!(found.iter().any(|(path, _)| path == start));
+    let pieces: Vec<_> = found
+        .iter()
+        .filter(|(path, _)| path.starts_with(&format!("{}/Children/", start)))
+        .collect();
+    assert!(!pieces.is_empty());
+
+    // Every path leads back to the compound it was found with.
+    for (path, value) in &found {
+        assert_eq!(Some(*value), chunk.get_path(path).as_deref());
+        assert!(value["id"].as_str().unwrap().starts_with("minecraft:"));
+    }
+
+    assert!(chunk.find_key("NoSuchKey").is_empty());
+}
+
+#[test]
+fn find_block_entities() {
+    let mut chunk = chunk();
+    let chest = |x: i32| {
+        Value::Compound(Map::from([
+            ("id".to_owned(), Value::String("minecraft:chest".to_owned())),
+            ("x".to_owned(), Value::Int(x)),
+            ("Items".to_owned(), Value::List(vec![])),
+        ]))
+    };
+    chunk.insert_path("Level/TileEntities/0", chest(1)).unwrap();
+    chunk.insert_path("Level/TileEntities/1", chest(2)).unwrap();
+
+    let chests =
+        chunk.find_compounds(|c| c.get("id").and_then(Value::as_str) == Some("minecraft:chest"));
+    let mut paths: Vec<_> = chests.iter().map(|(path, _)| path.as_str()).collect();
+    paths.sort_unstable();
+    assert_eq!(vec!["Level/TileEntities/0", "Level/TileEntities/1"], paths);
+
+    // Compounds are found before the compounds inside them.
+    let all = chunk.find_compounds(|_| true);
+    assert_eq!("", all[0].0);
+    let level = all.iter().position(|(p, _)| p == "Level").unwrap();
+    let sections = all
+        .iter()
+        .position(|(p, _)| p == "Level/Sections/0")
+        .unwrap();
+    assert!(level < sections);
+}
+
+#[test]
+fn find_paths_are_escaped() {
+    let value = Value::Compound(Map::from([(
+        "a/b".to_owned(),
+        Value::List(vec![Value::Compound(Map::from([(
+            "~".to_owned(),
+            Value::Compound(Map::from([("id".to_owned(), Value::Int(1))])),
+        )]))]),
+    )]));
+
+    let found = value.find_key("id");
+    assert_eq!(1, found.len());
+    assert_eq!("a~1b/0/~0", found[0].0);
+    assert_eq!(Some(found[0].1), value.get_path(&found[0].0).as_deref());
+}
@@ -0,0 +1,128 @@
+use serde::Deserialize;
+
+use crate::{de::from_bytes, from_value, Map, Value};
+
+use super::builder::Builder;
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Widened {
+    #[serde(deserialize_with = "crate::widen::i32")]
+    a: i32,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct WidenedAll {
+    #[serde(deserialize_with = "crate::widen::i16")]
+    short: i16,
+    #[serde(deserialize_with = "crate::widen::i64")]
+    long: i64,
+    #[serde(deserialize_with = "crate::widen::f64")]
+    double: f64,
+}
+
+#[test]
+fn byte_into_i32() {
+    let payload = Builder::new()
+        .start_compound("")
+        .byte("a", -3)
+        .end_compound()
+        .build();
+
+    assert_eq!(Widened { a: -3 }, from_bytes(&payload).unwrap());
+}
+
+#[test]
+fn short_into_i32() {
+    let payload = Builder::new()
+        .start_compound("")
+        .short("a", 300)
+        .end_compound()
+        .build();
+
+    assert_eq!(Widened { a: 300 }, from_bytes(&payload).unwrap());
+}
+
+#[test]
+fn int_into_i32() {
+    let payload = Builder::new()
+        .start_compound("")
+        .int("a", i32::MIN)
+        .end_compound()
+        .build();
+
+    assert_eq!(Widened { a: i32::MIN }, from_bytes(&payload).unwrap());
+}
+
+#[test]
+fn long_into_i32_rejected_even_if_it_fits() {
+    let payload = Builder::new()
+        .start_compound("")
+        .long("a", 5)
+        .end_compound()
+        .build();
+
+    assert!(from_bytes::<Widened>(&payload).is_err());
+}
+
+#[test]
+fn float_into_i32_rejected() {
+    let payload = Builder::new()
+        .start_compound("")
+        .float("a", 5.0)
+        .end_compound()
+        .build();
+
+    assert!(from_bytes::<Widened>(&payload).is_err());
+}
+
+#[test]
+fn widen_each_type() {
+    let payload = Builder::new()
+        .start_compound("")
+        .byte("short", 1)
+        .int("long", 2)
+        .float("double", 0.5)
+        .end_compound()
+        .build();
+
+    let expected = WidenedAll {
+        short: 1,
+        long: 2,
+        double: 0.5,
+    };
+    assert_eq!(expected, from_bytes(&payload).unwrap());
+}
+
+#[test]
+fn int_into_f64_rejected() {
+    let payload = Builder::new()
+        .start_compound("")
+        .byte("short", 1)
+        .int("long", 2)
+        .int("double", 3)
+        .end_compound()
+        .build();
+
+    assert!(from_bytes::<WidenedAll>(&payload).is_err());
+}
+
+#[test]
+fn from_value_widens_but_value_stays_strict() {
+    let value = Value::Compound(Map::from([("a".to_owned(), Value::Short(7))]));
+    assert_eq!(Widened { a: 7 }, from_value(&value).unwrap());
+
+    let value = Value::Compound(Map::from([("a".to_owned(), Value::Long(7))]));
+    assert!(from_value::<Widened>(&value).is_err());
+
+    // Value keeps the tag it was stored with.
+    let payload = Builder::new()
+        .start_compound("")
+        .byte("a", 1)
+        .end_compound()
+        .build();
+    let value: Value = from_bytes(&payload).unwrap();
+    assert_eq!(
+        Value::Compound(Map::from([("a".to_owned(), Value::Byte(1))])),
+        value
+    );
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{
+    borrow,
+    de::{from_bytes, from_bytes_le},
+    ser::Serializer as NbtSerializer,
+    test::builder::Builder,
+    to_bytes, to_bytes_bedrock_network, to_bytes_le, ByteArray, IntArray, LongArray, Value,
+};
+
+const CHUNK_RAW: &[u8] = include_bytes!("resources/chunk.nbt");
+
+/// The data of the borrowed arrays in a value.
+fn arrays<'a>(value: &borrow::Value<'a>, out: &mut Vec<&'a [u8]>) {
+    match value {
+        borrow::Value::ByteArray(a) => out.push(a.as_be_bytes()),
+        borrow::Value::IntArray(a) => out.push(a.as_be_bytes().unwrap()),
+        borrow::Value::LongArray(a) => out.push(a.as_be_bytes().unwrap()),
+        borrow::Value::List(list) => list.iter().for_each(|v| arrays(v, out)),
+        borrow::Value::Compound(c) => c.values().for_each(|v| arrays(v, out)),
+        _ => {}
+    }
+}
+
+#[test]
+fn edited_chunk_writes_the_original_arrays() {
+    let mut chunk: borrow::Value = from_bytes(CHUNK_RAW).unwrap();
+
+    let mut found = Vec::new();
+    arrays(&chunk, &mut found);
+    assert!(found.iter().any(|a| a.len() == 256 * 8), "no heightmaps");
+    let input = CHUNK_RAW.as_ptr_range();
+    assert!(found.iter().all(|a| input.contains(&a.as_ptr())));
+
+    chunk["Level"]["Status"] = borrow::Value::String("edited".into());
+    let written = to_bytes(&chunk).unwrap();
+
+    let mut expected: Value = from_bytes(CHUNK_RAW).unwrap();
+    expected["Level"]["Status"] = Value::String("edited".to_owned());
+    assert_eq!(expected, from_bytes::<Value>(&written).unwrap());
+
+    // Every array is written exactly as it was read.
+    let back: borrow::Value = from_bytes(&written).unwrap();
+    let mut written_arrays = Vec::new();
+    arrays(&back, &mut written_arrays);
+    found.sort();
+    written_arrays.sort();
+    assert_eq!(found, written_arrays);
+
+    // Keeping the order of the compounds, the only change is the string.
+    #[cfg(feature = "preserve_order")]
+    assert_eq!(
+        crate::patch::set_scalar(
+            CHUNK_RAW,
+            &["Level", "Status"],
+            crate::patch::ScalarValue::String("edited".to_owned())
+        )
+        .unwrap(),
+        written
+    );
+}
+
+#[derive(Deserialize, Serialize)]
+struct Borrowed<'a> {
+    #[serde(borrow)]
+    bytes: borrow::ByteArray<'a>,
+    #[serde(borrow)]
+    ints: borrow::IntArray<'a>,
+    #[serde(borrow)]
+    longs: borrow::LongArray<'a>,
+}
+
+#[derive(Serialize)]
+struct Owned {
+    bytes: ByteArray,
+    ints: IntArray,
+    longs: LongArray,
+}
+
+#[test]
+fn borrowed_arrays_write_the_same_as_owned() {
+    let payload = Builder::new()
+        .start_compound("")
+        .byte_array("bytes", &[1, -2, 3])
+        .int_array("ints", &[1, -2, i32::MAX, 0x01020304])
+        .long_array("longs", &[i64::MIN, 2, 0x0102030405060708])
+        .end_compound()
+        .build();
+    let borrowed: Borrowed = from_bytes(&payload).unwrap();
+    let owned = Owned {
+        bytes: borrowed.bytes.to_owned(),
+        ints: borrowed.ints.to_owned(),
+        longs: borrowed.longs.to_owned(),
+    };
+
+    assert_eq!(payload, to_bytes(&borrowed).unwrap());
+    assert_eq!(
+        to_bytes_le(&owned).unwrap(),
+        to_bytes_le(&borrowed).unwrap()
+    );
+    assert_eq!(
+        to_bytes_bedrock_network(&owned).unwrap(),
+        to_bytes_bedrock_network(&borrowed).unwrap()
+    );
+
+    // Our serializer copies the bytes whether or not it is human-readable.
+    let mut ser = NbtSerializer::new(Vec::new()).human_readable(true);
+    borrowed.serialize(&mut ser).unwrap();
+    assert_eq!(payload, ser.into_inner());
+
+    // As do arrays read from little-endian NBT.
+    let le = to_bytes_le(&owned).unwrap();
+    let borrowed: Borrowed = from_bytes_le(&le).unwrap();
+    assert_eq!(payload, to_bytes(&borrowed).unwrap());
+    assert_eq!(le, to_bytes_le(&borrowed).unwrap());
+}
+
+#[test]
+fn array_bytes_must_be_whole_elements() {
+    struct Bytes(&'static [u8]);
+
+    impl Serialize for Bytes {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+
+    #[derive(Serialize)]
+    struct V {
+        #[serde(serialize_with = "int_array")]
+        ints: Bytes,
+    }
+
+    fn int_array<S: Serializer>(data: &Bytes, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(crate::INT_ARRAY_TOKEN, data)
+    }
+
+    let v = V {
+        ints: Bytes(&[0, 0, 0, 1, 0, 0, 0, 2]),
+    };
+    let value: Value = from_bytes(&to_bytes(&v).unwrap()).unwrap();
+    assert_eq!(Value::IntArray(IntArray::new(vec![1, 2])), value["ints"]);
+
+    let v = V {
+        ints: Bytes(&[0, 0, 0, 1, 0, 0]),
+    };
+    let err = to_bytes(&v).unwrap_err();
+    assert!(err.to_string().contains("6 bytes"), "{}", err);
+}
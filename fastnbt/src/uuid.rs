@@ -0,0 +1,174 @@
+//! UUIDs, as used for entities, players, and the owners of things like pets
+//! and projectiles.
+//!
+//! Since 1.16 these are stored as an `IntArray` of 4 ints, most significant
+//! first. Before that they were stored as a pair of longs, usually called
+//! `UUIDMost` and `UUIDLeast`, sitting directly in the compound that the UUID
+//! belongs to.
+//!
+//! [`Uuid`] deserializes from the modern form on its own. To accept either
+//! form, flatten it into the containing struct with [`modern_or_legacy`]:
+//!
+//! ```
+//! use fastnbt::Uuid;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Entity {
+//!     #[serde(flatten, with = "fastnbt::uuid::modern_or_legacy")]
+//!     uuid: Uuid,
+//! }
+//! ```
+//!
+//! A `Uuid` always serializes to the modern form, including when flattened
+//! like this.
+
+use core::convert::TryFrom;
+use core::fmt::Display;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::arrays::{int_array_to_u128, u128_array_len_error, u128_to_int_array};
+use crate::IntArray;
+
+/// A UUID. Displays in the canonical hyphenated form, eg
+/// `069a79f4-44e9-4726-a5be-fca90e38aaf5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Uuid(pub u128);
+
+impl Uuid {
+    /// Create from the modern form, 4 ints with the most significant first.
+    pub fn from_ints(ints: [i32; 4]) -> Self {
+        Self(int_array_to_u128(ints))
+    }
+
+    /// The modern form of this UUID, 4 ints with the most significant first.
+    pub fn to_ints(&self) -> [i32; 4] {
+        u128_to_int_array(self.0)
+    }
+
+    /// Create from the legacy form, a pair of longs.
+    pub fn from_most_least(most: i64, least: i64) -> Self {
+        Self((most as u64 as u128) << 64 | least as u64 as u128)
+    }
+
+    /// The most significant half of this UUID, as stored in `UUIDMost`.
+    pub fn most(&self) -> i64 {
+        (self.0 >> 64) as i64
+    }
+
+    /// The least significant half of this UUID, as stored in `UUIDLeast`.
+    pub fn least(&self) -> i64 {
+        self.0 as i64
+    }
+}
+
+impl Display for Uuid {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let v = self.0;
+        write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            v >> 96,
+            (v >> 80) & 0xffff,
+            (v >> 64) & 0xffff,
+            (v >> 48) & 0xffff,
+            v & 0xffff_ffff_ffff
+        )
+    }
+}
+
+impl From<u128> for Uuid {
+    fn from(v: u128) -> Self {
+        Self(v)
+    }
+}
+
+impl From<Uuid> for u128 {
+    fn from(uuid: Uuid) -> Self {
+        uuid.0
+    }
+}
+
+impl Serialize for Uuid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u128(self.0)
+    }
+}
+
+// Read through IntArray rather than u128, since serde cannot buffer 128-bit
+// integers. This lets a Uuid be used in flattened structs and internally
+// tagged enums.
+impl<'de> Deserialize<'de> for Uuid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let ints = IntArray::deserialize(deserializer)?;
+        let ints = <[i32; 4]>::try_from(ints.as_slice())
+            .map_err(|_| de::Error::custom(u128_array_len_error(ints.len())))?;
+        Ok(Self::from_ints(ints))
+    }
+}
+
+/// Read a [`Uuid`] from either a `UUID` IntArray or a `UUIDMost`/`UUIDLeast`
+/// pair, and write it as a `UUID` IntArray. Use with
+/// `#[serde(flatten, with = "fastnbt::uuid::modern_or_legacy")]`, see the
+/// [module documentation][`crate::uuid`]. If both forms are present the
+/// modern one wins.
+pub mod modern_or_legacy {
+    use core::convert::TryFrom;
+
+    use serde::ser::SerializeMap;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    use super::Uuid;
+    use crate::IntArray;
+
+    /// The fields either form of UUID might be stored in.
+    #[derive(Deserialize)]
+    struct EitherForm {
+        #[serde(rename = "UUID")]
+        modern: Option<IntArray>,
+        #[serde(rename = "UUIDMost")]
+        most: Option<i64>,
+        #[serde(rename = "UUIDLeast")]
+        least: Option<i64>,
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Uuid, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let either = EitherForm::deserialize(deserializer)?;
+
+        match either {
+            EitherForm {
+                modern: Some(ints), ..
+            } => {
+                let ints = <[i32; 4]>::try_from(ints.as_slice()).map_err(|_| {
+                    de::Error::invalid_length(ints.len(), &"an IntArray of length 4")
+                })?;
+                Ok(Uuid::from_ints(ints))
+            }
+            EitherForm {
+                most: Some(most),
+                least: Some(least),
+                ..
+            } => Ok(Uuid::from_most_least(most, least)),
+            _ => Err(de::Error::missing_field("UUID")),
+        }
+    }
+
+    pub fn serialize<S>(uuid: &Uuid, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("UUID", uuid)?;
+        map.end()
+    }
+}
@@ -0,0 +1,272 @@
+//! Check that some NBT is well-formed without deserializing it.
+//!
+//! [`validate`] walks a whole document, checking every tag, length and
+//! string, and counts what it finds. Nothing is allocated for the values, so it
+//! is much cheaper than deserializing into a [`Value`][`crate::Value`], which
+//! makes it suitable for checking large numbers of generated chunks.
+//!
+//! ```
+//! # use fastnbt::{Map, Tag, Value};
+//! # let level = Map::from([("xPos".to_string(), Value::Int(3))]);
+//! # let chunk = Map::from([("Level".to_string(), Value::Compound(level))]);
+//! # let data = fastnbt::to_bytes(&chunk).unwrap();
+//! let stats = fastnbt::validate(&data).unwrap();
+//! assert_eq!(2, stats.count(Tag::Compound));
+//! assert_eq!(1, stats.count(Tag::Int));
+//! assert_eq!(2, stats.max_depth);
+//!
+//! // Cut short part way through.
+//! assert!(fastnbt::validate(&data[..data.len() - 1]).is_err());
+//! ```
+//!
+//! Only big-endian NBT with a named root is supported.
+
+use std::borrow::Cow;
+use std::io::Write;
+
+use crate::de::{DeOpts, InputHelper};
+use crate::error::{Error, Result};
+use crate::{Endian, Tag};
+
+/// What [`validate`] found in some NBT.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    counts: [usize; 13],
+
+    /// The deepest compounds and lists were nested, counting the root compound
+    /// as 1, the same as [`DeOpts::max_depth`].
+    pub max_depth: usize,
+
+    /// The total length of every string value in bytes, as encoded in the
+    /// NBT. The names of compound entries are not included.
+    pub string_bytes: usize,
+
+    /// The most elements in any Byte, Int or Long Array.
+    pub largest_array: usize,
+}
+
+impl Stats {
+    /// How many values there were with the given tag, including the root
+    /// compound and the elements of lists. There are never any
+    /// [`Tag::End`] values.
+    pub fn count(&self, tag: Tag) -> usize {
+        self.counts[u8::from(tag) as usize]
+    }
+}
+
+/// Check that some NBT is well-formed, and count what it contains. See the
+/// [`validate`](self) module for more information.
+///
+/// The whole input must be a single document. It is an error if a tag is
+/// invalid, the input ends early, a size is negative or larger than the rest of
+/// the input, a string is not valid CESU-8, compounds and lists are nested
+/// deeper than [`DeOpts::DEFAULT_MAX_DEPTH`], or there is anything after the
+/// end of the document. Errors have the offset and path of the problem.
+pub fn validate(input: &[u8]) -> Result<Stats> {
+    Validator::new(input, None).run()
+}
+
+enum Segment<'a> {
+    Name(Cow<'a, str>),
+    Index(i32),
+}
+
+/// Walks a document, checking and counting everything in it. Optionally writes
+/// a line for each value as it goes, see [`crate::debug::trace`].
+pub(crate) struct Validator<'a, 'w> {
+    input: InputHelper<&'a [u8]>,
+    stats: Stats,
+    /// Where the value being checked is, for errors.
+    path: Vec<Segment<'a>>,
+    root_name: Cow<'a, str>,
+    trace: Option<&'w mut dyn Write>,
+}
+
+impl<'a, 'w> Validator<'a, 'w> {
+    pub(crate) fn new(input: &'a [u8], trace: Option<&'w mut dyn Write>) -> Self {
+        Self {
+            input: InputHelper::new(input, Endian::Big),
+            stats: Stats::default(),
+            path: Vec::new(),
+            root_name: Cow::Borrowed(""),
+            trace,
+        }
+    }
+
+    pub(crate) fn run(mut self) -> Result<Stats> {
+        let result = match self.root() {
+            Err(e) => Err(e.at_offset(self.input.offset()).at_path(self.path())),
+            Ok(()) => match self.input.0.len() {
+                0 => Ok(()),
+                n => Err(Error::trailing_bytes(n).at_offset(self.input.offset())),
+            },
+        };
+
+        match result {
+            Ok(()) => Ok(self.stats),
+            Err(e) => {
+                if let Some(out) = self.trace.as_mut() {
+                    writeln!(out, "{:>8}  error: {}", e.offset().unwrap_or(0), e)?;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn root(&mut self) -> Result<()> {
+        if self.input.consume_tag()? != Tag::Compound {
+            return Err(Error::no_root_compound());
+        }
+        self.root_name = self.input.consume_name()?;
+        self.value(Tag::Compound, 1, 0)
+    }
+
+    /// Check the payload of a value with the given tag, nested `depth` deep
+    /// if it is a compound or list. The value starts at `offset`, which is
+    /// its tag, or its payload for elements of lists.
+    fn value(&mut self, tag: Tag, depth: usize, offset: usize) -> Result<()> {
+        self.stats.counts[u8::from(tag) as usize] += 1;
+
+        match tag {
+            Tag::Byte | Tag::Short | Tag::Int | Tag::Long | Tag::Float | Tag::Double => {
+                self.trace(offset, depth, tag, None)?;
+                let len = self.input.min_payload_len(tag);
+                self.input.skip_bytes(1, len)?;
+            }
+            Tag::String => {
+                if self.trace.is_some() {
+                    // Peek at the length, so that it is shown even if the
+                    // string turns out to be cut short or invalid.
+                    let len = match *self.input.0 {
+                        [a, b, ..] => Some(format!("{} bytes", u16::from_be_bytes([a, b]))),
+                        _ => None,
+                    };
+                    self.trace(offset, depth, tag, len)?;
+                }
+
+                let start = self.input.offset();
+                self.input.consume_size_prefixed_string()?;
+                // Less the u16 length.
+                self.stats.string_bytes += self.input.offset() - start - 2;
+            }
+            Tag::ByteArray | Tag::IntArray | Tag::LongArray => {
+                let element_len = crate::de_arrays::element_size(tag);
+                let start = self.input.offset();
+                let size = self.input.consume_list_size()?;
+                self.trace(offset, depth, tag, Some(format!("{} elements", size)))?;
+
+                let size = self.input.check_size(size, element_len, start)?;
+                self.input.skip_bytes(size, element_len)?;
+                self.stats.largest_array = self.stats.largest_array.max(size as usize);
+            }
+            Tag::List => {
+                self.enter(depth)?;
+
+                let element_tag = self.input.consume_tag()?;
+                let start = self.input.offset();
+                let size = self.input.consume_list_size()?;
+                let details = format!("{} {:?} elements", size, element_tag);
+                self.trace(offset, depth, tag, Some(details))?;
+
+                let element_len = self.input.min_payload_len(element_tag);
+                let size = self.input.check_size(size, element_len, start)?;
+                if size < 0 {
+                    return Err(Error::invalid_size(size).at_offset(start));
+                }
+                if element_tag == Tag::End && size > 0 {
+                    return Err(Error::bespoke(
+                        "unexpected list of type 'end', which is not supported".into(),
+                    ));
+                }
+
+                for i in 0..size {
+                    self.path.push(Segment::Index(i));
+                    let offset = self.input.offset();
+                    self.value(element_tag, depth + 1, offset)?;
+                    self.path.pop();
+                }
+            }
+            Tag::Compound => {
+                self.enter(depth)?;
+                self.trace(offset, depth, tag, None)?;
+
+                loop {
+                    let offset = self.input.offset();
+                    let tag = self.input.consume_tag()?;
+                    if tag == Tag::End {
+                        break;
+                    }
+
+                    let name = self.input.consume_name()?;
+                    self.path.push(Segment::Name(name));
+                    self.value(tag, depth + 1, offset)?;
+                    self.path.pop();
+                }
+            }
+            Tag::End => unreachable!("End is never a value"),
+        }
+
+        Ok(())
+    }
+
+    /// If tracing, write a line for a value, indented by its depth.
+    fn trace(
+        &mut self,
+        offset: usize,
+        depth: usize,
+        tag: Tag,
+        details: Option<String>,
+    ) -> Result<()> {
+        let out = match self.trace.as_mut() {
+            Some(out) => out,
+            None => return Ok(()),
+        };
+
+        let label = match self.path.last() {
+            Some(Segment::Name(name)) => format!("{:?}", name),
+            Some(Segment::Index(i)) => format!("[{}]", i),
+            None => format!("{:?}", self.root_name),
+        };
+        write!(
+            out,
+            "{:>8}  {:indent$}{:?} ({}) {}",
+            offset,
+            "",
+            tag,
+            u8::from(tag),
+            label,
+            indent = 2 * (depth - 1)
+        )?;
+        match details {
+            Some(details) => writeln!(out, ", {}", details)?,
+            None => writeln!(out)?,
+        }
+        Ok(())
+    }
+
+    fn enter(&mut self, depth: usize) -> Result<()> {
+        if depth > DeOpts::DEFAULT_MAX_DEPTH {
+            return Err(Error::depth_limit_exceeded(DeOpts::DEFAULT_MAX_DEPTH));
+        }
+        self.stats.max_depth = self.stats.max_depth.max(depth);
+        Ok(())
+    }
+
+    /// The path to the value being checked, in the same form as the
+    /// deserializer's errors, eg `Level.Sections[3].BlockStates`.
+    fn path(&self) -> String {
+        let mut path = String::new();
+        for segment in &self.path {
+            match segment {
+                Segment::Name(name) => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(name);
+                }
+                Segment::Index(i) => path.push_str(&format!("[{}]", i)),
+            }
+        }
+        path
+    }
+}
@@ -0,0 +1,137 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+use core::convert::TryFrom;
+
+use crate::{borrow, ByteArray, IntArray, LongArray, Map, Value};
+
+/// Implement the `as_*` and `is_*` methods for one of the value types.
+macro_rules! accessors {
+    ($lt:lifetime, $value:ty, $($enum:ident)::+, $key:ty, $byte:ty, $int:ty, $long:ty) => {
+        impl<$lt> $value {
+            /// The value of a Byte, Short, Int or Long. None for anything
+            /// else, including floats.
+            pub fn as_i64(&self) -> Option<i64> {
+                match *self {
+                    $($enum)::+::Byte(v) => Some(v.into()),
+                    $($enum)::+::Short(v) => Some(v.into()),
+                    $($enum)::+::Int(v) => Some(v.into()),
+                    $($enum)::+::Long(v) => Some(v),
+                    _ => None,
+                }
+            }
+
+            /// The value of a Byte, Short, Int or Long, if it is not
+            /// negative.
+            pub fn as_u64(&self) -> Option<u64> {
+                self.as_i64().and_then(|v| u64::try_from(v).ok())
+            }
+
+            /// The value of a Byte, Short, Int or Long, if it is not negative
+            /// and fits in a `usize`, such as for a count or an index.
+            pub fn as_usize(&self) -> Option<usize> {
+                self.as_i64().and_then(|v| usize::try_from(v).ok())
+            }
+
+            /// A flag, stored by the game as a Byte of 0 or 1. Any other value
+            /// of a Byte, Short or Int is also true if it is not zero. None
+            /// for anything else, including a Long.
+            pub fn as_bool(&self) -> Option<bool> {
+                match *self {
+                    $($enum)::+::Byte(v) => Some(v != 0),
+                    $($enum)::+::Short(v) => Some(v != 0),
+                    $($enum)::+::Int(v) => Some(v != 0),
+                    _ => None,
+                }
+            }
+
+            /// The value of a Float or Double, or of an integer converted to a
+            /// float. A Long with a magnitude over 2^53 is rounded to the
+            /// nearest `f64`, use [`as_i64`][Self::as_i64] first to avoid
+            /// this. None for anything that is not a number.
+            pub fn as_f64(&self) -> Option<f64> {
+                match *self {
+                    $($enum)::+::Float(v) => Some(v.into()),
+                    $($enum)::+::Double(v) => Some(v),
+                    _ => self.as_i64().map(|v| v as f64),
+                }
+            }
+
+            /// The value of a String.
+            pub fn as_str(&self) -> Option<&str> {
+                match self {
+                    $($enum)::+::String(v) => Some(v),
+                    _ => None,
+                }
+            }
+
+            /// The entries of a Compound.
+            pub fn as_compound(&self) -> Option<&Map<$key, Self>> {
+                match self {
+                    $($enum)::+::Compound(v) => Some(v),
+                    _ => None,
+                }
+            }
+
+            /// The elements of a List.
+            pub fn as_list(&self) -> Option<&[Self]> {
+                match self {
+                    $($enum)::+::List(v) => Some(v),
+                    _ => None,
+                }
+            }
+
+            /// The value of a Byte Array.
+            pub fn as_byte_array(&self) -> Option<&$byte> {
+                match self {
+                    $($enum)::+::ByteArray(v) => Some(v),
+                    _ => None,
+                }
+            }
+
+            /// The value of an Int Array.
+            pub fn as_int_array(&self) -> Option<&$int> {
+                match self {
+                    $($enum)::+::IntArray(v) => Some(v),
+                    _ => None,
+                }
+            }
+
+            /// The value of a Long Array.
+            pub fn as_long_array(&self) -> Option<&$long> {
+                match self {
+                    $($enum)::+::LongArray(v) => Some(v),
+                    _ => None,
+                }
+            }
+
+            /// Whether this is a Byte, Short, Int, Long, Float or Double.
+            pub fn is_number(&self) -> bool {
+                self.as_f64().is_some()
+            }
+
+            /// Whether this is a Compound.
+            pub fn is_compound(&self) -> bool {
+                self.as_compound().is_some()
+            }
+        }
+
+        /// A flag as a Byte of 1 for true, or 0 for false, like the game
+        /// writes them.
+        impl<$lt> From<bool> for $value {
+            fn from(v: bool) -> Self {
+                $($enum)::+::Byte(v.into())
+            }
+        }
+    };
+}
+
+accessors!('a, Value, Value, String, ByteArray, IntArray, LongArray);
+accessors!(
+    'a,
+    borrow::Value<'a>,
+    borrow::Value,
+    Cow<'a, str>,
+    borrow::ByteArray<'a>,
+    borrow::IntArray<'a>,
+    borrow::LongArray<'a>
+);
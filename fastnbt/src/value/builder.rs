@@ -0,0 +1,147 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+
+use crate::error::{Error, Result};
+use crate::{ByteArray, IntArray, LongArray, Map, Value};
+
+/// Builds a [`Value::Compound`] one entry at a time, for writing NBT without
+/// defining a type for it. Anything that converts into a [`Value`] can be an
+/// entry, including another builder.
+///
+/// ```
+/// # use fastnbt::{CompoundBuilder, ListBuilder, Value};
+/// let block = CompoundBuilder::new()
+///     .field("Name", "minecraft:chest")
+///     .field(
+///         "Properties",
+///         CompoundBuilder::new().field("facing", "north"),
+///     )
+///     .build();
+///
+/// let palette = ListBuilder::new().push(block).build()?;
+/// assert_eq!(Some("north"), palette[0]["Properties"]["facing"].as_str());
+/// # Ok::<(), fastnbt::error::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CompoundBuilder {
+    entries: Map<String, Value>,
+}
+
+impl CompoundBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry, replacing any earlier entry with the same name.
+    pub fn field(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.entries.insert(name.to_owned(), value.into());
+        self
+    }
+
+    pub fn build(self) -> Value {
+        Value::Compound(self.entries)
+    }
+}
+
+impl From<CompoundBuilder> for Value {
+    fn from(builder: CompoundBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Builds a [`Value::List`]. NBT lists can only contain one type, so
+/// [`build`][Self::build] checks that every element has the same tag as the
+/// first.
+#[derive(Debug, Clone, Default)]
+pub struct ListBuilder {
+    elements: Vec<Value>,
+}
+
+impl ListBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(mut self, value: impl Into<Value>) -> Self {
+        self.elements.push(value.into());
+        self
+    }
+
+    /// The list, or a [`HeterogeneousList`] error for the first element with
+    /// a different tag to the first.
+    ///
+    /// Only the elements themselves are checked, a list within a list is
+    /// assumed to have been built with its own `ListBuilder`.
+    ///
+    /// [`HeterogeneousList`]: crate::error::ErrorKind::HeterogeneousList
+    pub fn build(self) -> Result<Value> {
+        if let Some(first) = self.elements.first() {
+            let expected = first.tag();
+            let mismatch = self
+                .elements
+                .iter()
+                .map(Value::tag)
+                .enumerate()
+                .find(|&(_, found)| found != expected);
+            if let Some((index, found)) = mismatch {
+                return Err(Error::heterogeneous_list(index, expected, found));
+            }
+        }
+        Ok(Value::List(self.elements))
+    }
+}
+
+impl<V: Into<Value>> Extend<V> for ListBuilder {
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        self.elements.extend(iter.into_iter().map(Into::into))
+    }
+}
+
+impl<V: Into<Value>> FromIterator<V> for ListBuilder {
+    fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
+        let mut builder = Self::new();
+        builder.extend(iter);
+        builder
+    }
+}
+
+macro_rules! from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(v: $ty) -> Self {
+                    Value::$variant(v.into())
+                }
+            }
+        )*
+    };
+}
+
+from! {
+    i8 => Byte,
+    i16 => Short,
+    i32 => Int,
+    i64 => Long,
+    f32 => Float,
+    f64 => Double,
+    String => String,
+    &str => String,
+    ByteArray => ByteArray,
+    IntArray => IntArray,
+    LongArray => LongArray,
+    Vec<i8> => ByteArray,
+    Vec<i32> => IntArray,
+    Vec<i64> => LongArray,
+    Vec<Value> => List,
+    Map<String, Value> => Compound,
+}
+
+/// Collects entries into a [`Value::Compound`], replacing any earlier entry
+/// with the same name.
+impl FromIterator<(String, Value)> for Value {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
+        Value::Compound(iter.into_iter().collect())
+    }
+}
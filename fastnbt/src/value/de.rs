@@ -0,0 +1,391 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::convert::TryFrom;
+
+use serde::de::{self, IntoDeserializer};
+use serde::forward_to_deserialize_any;
+
+use crate::arrays::{int_array_to_u128, u128_array_len_error};
+use crate::de::KeyDeserializer;
+use crate::error::{Error, Result};
+use crate::{map, Tag, Value, ValueIndex};
+
+/// Deserialize a `T` from a [`Value`]. This lets you parse part of a document
+/// into a `Value`, and later pull typed data out of it.
+///
+/// The array types are presented to `T` in the same way the main deserializer
+/// presents them, so fields of type [`IntArray`][`crate::IntArray`] etc still
+/// work. Borrowed types can borrow from the `Value`, with the exception of the
+/// [`borrow`][`crate::borrow`] array types which need the original NBT bytes.
+///
+/// ```
+/// # use fastnbt::{from_value, Map, Value};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Block<'a> {
+///     #[serde(rename = "Name")]
+///     name: &'a str,
+/// }
+///
+/// let mut compound = Map::new();
+/// compound.insert("Name".to_string(), Value::String("minecraft:stone".to_string()));
+/// let value = Value::Compound(compound);
+///
+/// let block: Block = from_value(&value).unwrap();
+/// assert_eq!("minecraft:stone", block.name);
+/// ```
+pub fn from_value<'de, T>(value: &'de Value) -> Result<T>
+where
+    T: de::Deserialize<'de>,
+{
+    T::deserialize(value)
+}
+
+impl Value {
+    /// Deserialize each element of a list as a `T`, such as the compounds of a
+    /// chunk's sections or entities. An element that is not a valid `T` gives
+    /// an error, but iteration can carry on past it. If this is not a list
+    /// there is a single error.
+    ///
+    /// ```
+    /// # use fastnbt::{Map, Value};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Block<'a> {
+    ///     #[serde(rename = "Name")]
+    ///     name: &'a str,
+    /// }
+    ///
+    /// # let block = |name: &str| Value::Compound(Map::from([("Name".to_owned(), Value::String(name.to_owned()))]));
+    /// # let section = Value::Compound(Map::from([("Palette".to_owned(), Value::List(vec![block("minecraft:air"), block("minecraft:stone")]))]));
+    /// let palette = section["Palette"]
+    ///     .typed_iter::<Block>()
+    ///     .collect::<fastnbt::error::Result<Vec<_>>>()?;
+    /// assert_eq!("minecraft:stone", palette[1].name);
+    /// # Ok::<(), fastnbt::error::Error>(())
+    /// ```
+    pub fn typed_iter<'de, T>(&'de self) -> impl Iterator<Item = Result<T>> + 'de
+    where
+        T: de::Deserialize<'de> + 'de,
+    {
+        let (list, err) = match self {
+            Value::List(list) => (&list[..], None),
+            other => (
+                &[][..],
+                Some(Error::bespoke(format!(
+                    "expected a List to iterate, found {}",
+                    kind!(Value, other)
+                ))),
+            ),
+        };
+        err.map(Err).into_iter().chain(list.iter().map(from_value))
+    }
+
+    /// Deserialize the value of a key in a compound, or an element of a list,
+    /// as a `T`. `Ok(None)` if it is not there, like [`get`][Self::get].
+    pub fn typed_get<'de, T, I>(&'de self, index: I) -> Result<Option<T>>
+    where
+        T: de::Deserialize<'de>,
+        I: ValueIndex<Value>,
+    {
+        self.get(index).map(from_value).transpose()
+    }
+}
+
+fn int_array_u128(ints: &[i32]) -> Result<u128> {
+    let ints = <[i32; 4]>::try_from(ints).map_err(|_| u128_array_len_error(ints.len()))?;
+    Ok(int_array_to_u128(ints))
+}
+
+impl<'de> de::Deserializer<'de> for &'de Value {
+    type Error = Error;
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::Byte(v) => visitor.visit_i8(*v),
+            Value::Short(v) => visitor.visit_i16(*v),
+            Value::Int(v) => visitor.visit_i32(*v),
+            Value::Long(v) => visitor.visit_i64(*v),
+            Value::Double(v) => visitor.visit_f64(*v),
+            Value::Float(v) => visitor.visit_f32(*v),
+            Value::String(v) => visitor.visit_borrowed_str(v),
+            Value::ByteArray(v) => visitor.visit_map(ArrayAccess::new(Tag::ByteArray, v)),
+            Value::IntArray(v) => visitor.visit_map(ArrayAccess::new(Tag::IntArray, v)),
+            Value::LongArray(v) => visitor.visit_map(ArrayAccess::new(Tag::LongArray, v)),
+            Value::List(v) => visitor.visit_seq(ListAccess(v.iter())),
+            Value::Compound(v) => visitor.visit_map(CompoundAccess {
+                iter: v.iter(),
+                value: None,
+            }),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Mirror the main deserializer, where any integral value can be a bool.
+        match self {
+            Value::Byte(v) => visitor.visit_bool(*v != 0),
+            Value::Short(v) => visitor.visit_bool(*v != 0),
+            Value::Int(v) => visitor.visit_bool(*v != 0),
+            Value::Long(v) => visitor.visit_bool(*v != 0),
+            _ => Err(Error::bespoke(
+                "deserialize bool: expected integral value".to_string(),
+            )),
+        }
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::IntArray(ints) => visitor.visit_i128(int_array_u128(ints)? as i128),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::IntArray(ints) => visitor.visit_u128(int_array_u128(ints)?),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // NBT has no null, if the value exists then it is Some.
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            // Unit variants are stored as just their name.
+            Value::String(s) => visitor.visit_enum(s.as_str().into_deserializer()),
+            // Other variants are a compound with a single key of the variant
+            // name.
+            Value::Compound(c) if c.len() == 1 => {
+                let (variant, value) = c.iter().next().expect("checked len above");
+                visitor.visit_enum(EnumAccess { variant, value })
+            }
+            _ => Err(Error::bespoke(
+                "expected enum, found a value that is not a string or single-key compound"
+                    .to_string(),
+            )),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for &'de Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+struct ListAccess<'de>(core::slice::Iter<'de, Value>);
+
+impl<'de> de::SeqAccess<'de> for ListAccess<'de> {
+    type Error = Error;
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        self.0.next().map(|v| seed.deserialize(v)).transpose()
+    }
+}
+
+struct CompoundAccess<'de> {
+    iter: map::Iter<'de, String, Value>,
+    value: Option<&'de Value>,
+}
+
+impl<'de> de::MapAccess<'de> for CompoundAccess<'de> {
+    type Error = Error;
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((k, v)) => {
+                self.value = Some(v);
+                seed.deserialize(KeyDeserializer(k.into())).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| Error::bespoke("value requested before key".to_string()))?;
+        seed.deserialize(value)
+    }
+}
+
+enum ArrayStage {
+    Tag,
+    Data,
+    Done,
+}
+
+/// Presents an array value as the `{ tag, data }` map that the main
+/// deserializer uses, see the [`de`][`crate::de`] module.
+struct ArrayAccess<'de, T> {
+    tag: Tag,
+    data: &'de [T],
+    stage: ArrayStage,
+}
+
+impl<'de, T> ArrayAccess<'de, T> {
+    fn new(tag: Tag, data: &'de [T]) -> Self {
+        Self {
+            tag,
+            data,
+            stage: ArrayStage::Tag,
+        }
+    }
+}
+
+impl<'de, T> de::MapAccess<'de> for ArrayAccess<'de, T>
+where
+    T: IntoDeserializer<'de, Error> + Copy,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.stage {
+            ArrayStage::Tag => seed.deserialize("tag".into_deserializer()).map(Some),
+            ArrayStage::Data => seed.deserialize("data".into_deserializer()).map(Some),
+            ArrayStage::Done => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        match self.stage {
+            ArrayStage::Tag => {
+                self.stage = ArrayStage::Data;
+                let tag: u8 = self.tag.into();
+                seed.deserialize(tag.into_deserializer())
+            }
+            ArrayStage::Data => {
+                self.stage = ArrayStage::Done;
+                seed.deserialize(de::value::SeqDeserializer::new(self.data.iter().copied()))
+            }
+            ArrayStage::Done => Err(Error::bespoke("array has no more keys".to_string())),
+        }
+    }
+}
+
+struct EnumAccess<'de> {
+    variant: &'de str,
+    value: &'de Value,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'de> {
+    type Error = Error;
+    type Variant = &'de Value;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(de::value::BorrowedStrDeserializer::<Error>::new(
+            self.variant,
+        ))?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &'de Value {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
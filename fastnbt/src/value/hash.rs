@@ -0,0 +1,84 @@
+use crate::Value;
+
+use super::sorted;
+
+impl Value {
+    /// A hash of this value's contents, for finding duplicates or as a cache
+    /// key. Unlike hashing with the standard library's `HashMap`, the hash is
+    /// the same every time the program runs, and on every platform.
+    ///
+    /// Values have the same hash if they are
+    /// [`bitwise_eq`][Self::bitwise_eq], so the order of the entries in a
+    /// compound does not matter, and floats are hashed by their bit pattern.
+    /// The tag of each value is part of the hash, so an Int and a Long of the
+    /// same number differ.
+    ///
+    /// The hash is 64-bit FNV-1a, which is quick but not resistant to data
+    /// crafted to collide, so don't rely on it alone to tell apart values
+    /// from an untrusted source.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv(FNV_OFFSET);
+        self.hash_into(&mut hasher);
+        hasher.0
+    }
+
+    fn hash_into(&self, h: &mut Fnv) {
+        h.write(&[u8::from(self.tag())]);
+        match self {
+            Value::Byte(v) => h.write(&v.to_le_bytes()),
+            Value::Short(v) => h.write(&v.to_le_bytes()),
+            Value::Int(v) => h.write(&v.to_le_bytes()),
+            Value::Long(v) => h.write(&v.to_le_bytes()),
+            Value::Float(v) => h.write(&v.to_bits().to_le_bytes()),
+            Value::Double(v) => h.write(&v.to_bits().to_le_bytes()),
+            Value::String(v) => h.write_str(v),
+            Value::ByteArray(v) => {
+                h.write_len(v.len());
+                v.iter().for_each(|v| h.write(&v.to_le_bytes()));
+            }
+            Value::IntArray(v) => {
+                h.write_len(v.len());
+                v.iter().for_each(|v| h.write(&v.to_le_bytes()));
+            }
+            Value::LongArray(v) => {
+                h.write_len(v.len());
+                v.iter().for_each(|v| h.write(&v.to_le_bytes()));
+            }
+            Value::List(v) => {
+                h.write_len(v.len());
+                v.iter().for_each(|v| v.hash_into(h));
+            }
+            Value::Compound(v) => {
+                h.write_len(v.len());
+                for (key, value) in sorted(v) {
+                    h.write_str(key);
+                    value.hash_into(h);
+                }
+            }
+        }
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+struct Fnv(u64);
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ u64::from(b)).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Lengths are written before variable length contents, so that eg the
+    /// strings "ab", "c" and "a", "bc" hash differently.
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+
+    fn write_str(&mut self, s: &str) {
+        self.write_len(s.len());
+        self.write(s.as_bytes());
+    }
+}
@@ -0,0 +1,165 @@
+use alloc::borrow::{Cow, ToOwned};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops;
+
+use crate::{borrow, Map, Value};
+
+/// What a value can be indexed by: a `str` or `String` for the keys of a
+/// compound, or a `usize` for the elements of a list. Used by the `get`
+/// methods and the `[]` operator of [`Value`] and [`borrow::Value`].
+///
+/// This is sealed, it cannot be implemented outside of fastnbt.
+pub trait ValueIndex<V>: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v>(&self, v: &'v V) -> Option<&'v V>;
+
+    #[doc(hidden)]
+    fn index_into_mut<'v>(&self, v: &'v mut V) -> Option<&'v mut V>;
+
+    /// Panics if `v` cannot be indexed this way, or for a list index that is
+    /// out of range.
+    #[doc(hidden)]
+    fn index_or_insert<'v>(&self, v: &'v mut V) -> &'v mut V;
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for alloc::string::String {}
+    impl<T: ?Sized + Sealed> Sealed for &T {}
+}
+
+/// What `[]` gives for anything that is not there. An empty list can be
+/// indexed further, always giving itself, so a chain through a missing path
+/// ends up here rather than panicking.
+static EMPTY: Value = Value::List(Vec::new());
+static EMPTY_BORROWED: borrow::Value<'static> = borrow::Value::List(Vec::new());
+
+/// Implement indexing for one of the value types. `$key` turns a `&str` into a
+/// key of its compounds.
+macro_rules! index_value {
+    ($lt:lifetime, $value:ty, $($enum:ident)::+, $empty:ident, |$s:ident| $key:expr) => {
+        impl<$lt> ValueIndex<$value> for usize {
+            fn index_into<'v>(&self, v: &'v $value) -> Option<&'v $value> {
+                match v {
+                    $($enum)::+::List(list) => list.get(*self),
+                    _ => None,
+                }
+            }
+
+            fn index_into_mut<'v>(&self, v: &'v mut $value) -> Option<&'v mut $value> {
+                match v {
+                    $($enum)::+::List(list) => list.get_mut(*self),
+                    _ => None,
+                }
+            }
+
+            fn index_or_insert<'v>(&self, v: &'v mut $value) -> &'v mut $value {
+                match v {
+                    $($enum)::+::List(list) => {
+                        let len = list.len();
+                        list.get_mut(*self).unwrap_or_else(|| {
+                            panic!(
+                                "cannot index a List of length {} with {}",
+                                len, self
+                            )
+                        })
+                    }
+                    other => panic!("cannot index {} with {}", kind!($($enum)::+, other), self),
+                }
+            }
+        }
+
+        impl<$lt> ValueIndex<$value> for str {
+            fn index_into<'v>(&self, v: &'v $value) -> Option<&'v $value> {
+                match v {
+                    $($enum)::+::Compound(map) => map.get(self),
+                    _ => None,
+                }
+            }
+
+            fn index_into_mut<'v>(&self, v: &'v mut $value) -> Option<&'v mut $value> {
+                match v {
+                    $($enum)::+::Compound(map) => map.get_mut(self),
+                    _ => None,
+                }
+            }
+
+            fn index_or_insert<'v>(&self, v: &'v mut $value) -> &'v mut $value {
+                match v {
+                    $($enum)::+::Compound(map) => {
+                        if !map.contains_key(self) {
+                            let $s = self;
+                            map.insert($key, $($enum)::+::Compound(Map::new()));
+                        }
+                        map.get_mut(self).unwrap()
+                    }
+                    other => panic!("cannot index {} with {:?}", kind!($($enum)::+, other), self),
+                }
+            }
+        }
+
+        impl<$lt> $value {
+            /// The value of a key in a compound, or an element of a list.
+            /// `None` if it is not there, or this is the wrong type of value.
+            pub fn get<I: ValueIndex<Self>>(&self, index: I) -> Option<&Self> {
+                index.index_into(self)
+            }
+
+            /// Like [`get`][Self::get], but mutable.
+            pub fn get_mut<I: ValueIndex<Self>>(&mut self, index: I) -> Option<&mut Self> {
+                index.index_into_mut(self)
+            }
+        }
+
+        impl<$lt, I: ValueIndex<$value>> ops::Index<I> for $value {
+            type Output = $value;
+
+            fn index(&self, index: I) -> &$value {
+                index.index_into(self).unwrap_or(&$empty)
+            }
+        }
+
+        impl<$lt, I: ValueIndex<$value>> ops::IndexMut<I> for $value {
+            fn index_mut(&mut self, index: I) -> &mut $value {
+                index.index_or_insert(self)
+            }
+        }
+    };
+}
+
+impl<V, T: ?Sized + ValueIndex<V>> ValueIndex<V> for &T {
+    fn index_into<'v>(&self, v: &'v V) -> Option<&'v V> {
+        (**self).index_into(v)
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut V) -> Option<&'v mut V> {
+        (**self).index_into_mut(v)
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut V) -> &'v mut V {
+        (**self).index_or_insert(v)
+    }
+}
+
+impl<V> ValueIndex<V> for String
+where
+    str: ValueIndex<V>,
+{
+    fn index_into<'v>(&self, v: &'v V) -> Option<&'v V> {
+        self.as_str().index_into(v)
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut V) -> Option<&'v mut V> {
+        self.as_str().index_into_mut(v)
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut V) -> &'v mut V {
+        self.as_str().index_or_insert(v)
+    }
+}
+
+index_value!('a, Value, Value, EMPTY, |key| key.to_owned());
+index_value!('a, borrow::Value<'a>, borrow::Value, EMPTY_BORROWED, |key| Cow::Owned(key.to_owned()));
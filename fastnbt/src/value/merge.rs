@@ -0,0 +1,186 @@
+use alloc::format;
+use alloc::string::String;
+use core::mem;
+
+use super::path::push_segment;
+use crate::error::{Error, Result};
+use crate::Value;
+
+/// How [`Value::merge`] combines a patch with a value. Compounds are always
+/// merged key by key, this decides what happens to everything else.
+///
+/// The default replaces lists, and replaces values of the wrong type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergeStrategy {
+    lists: ListMerge,
+    on_conflict: MergeConflict,
+}
+
+/// What [`Value::merge`] does with a list in both the value and the patch. See
+/// [`MergeStrategy::lists`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListMerge {
+    /// Replace the whole list with the patch's.
+    Replace,
+
+    /// Append the patch's elements to the list.
+    Concat,
+}
+
+/// What [`Value::merge`] does when the patch has a different type of value
+/// than the one it would be merged into. See [`MergeStrategy::on_conflict`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeConflict {
+    /// Replace the value with the patch's.
+    Replace,
+
+    /// Fail without changing anything.
+    Error,
+}
+
+impl MergeStrategy {
+    pub fn new() -> Self {
+        Self {
+            lists: ListMerge::Replace,
+            on_conflict: MergeConflict::Replace,
+        }
+    }
+
+    /// What to do with a list in both the value and the patch. Elements are
+    /// never merged with each other, even when they are compounds.
+    ///
+    /// Concatenating lists with different types of element would make a list
+    /// that cannot be written as NBT, so that is a conflict.
+    pub fn lists(mut self, lists: ListMerge) -> Self {
+        self.lists = lists;
+        self
+    }
+
+    /// What to do when the patch has a different type of value than the one
+    /// it would be merged into, such as an Int in place of a Compound.
+    pub fn on_conflict(mut self, on_conflict: MergeConflict) -> Self {
+        self.on_conflict = on_conflict;
+        self
+    }
+}
+
+impl Default for MergeStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Value {
+    /// Recursively merge `patch` into this value. Entries of a compound in the
+    /// patch are merged into the entry with the same key, or added if there
+    /// is none, so entries only in this value are kept. Everything else in the
+    /// patch replaces what is here, except for lists which are handled by the
+    /// `strategy`. The NBT arrays are single values, and are never combined.
+    ///
+    /// A type conflict is only an error with [`MergeConflict::Error`], in
+    /// which case this value is left unchanged.
+    ///
+    /// ```
+    /// # use fastnbt::{Map, Value};
+    /// use fastnbt::{MergeConflict, MergeStrategy};
+    ///
+    /// # let rules = Map::from([("doDaylightCycle".to_owned(), Value::String("true".to_owned()))]);
+    /// # let data = Map::from([("GameRules".to_owned(), Value::Compound(rules))]);
+    /// # let mut level = Value::Compound(Map::from([("Data".to_owned(), Value::Compound(data))]));
+    /// let rules = Map::from([("keepInventory".to_owned(), Value::String("true".to_owned()))]);
+    /// let data = Map::from([("GameRules".to_owned(), Value::Compound(rules))]);
+    /// let patch = Value::Compound(Map::from([("Data".to_owned(), Value::Compound(data))]));
+    ///
+    /// let strategy = MergeStrategy::new().on_conflict(MergeConflict::Error);
+    /// level.merge(&patch, strategy)?;
+    /// assert_eq!(Some("true"), level["Data"]["GameRules"]["keepInventory"].as_str());
+    /// assert_eq!(Some("true"), level["Data"]["GameRules"]["doDaylightCycle"].as_str());
+    /// # Ok::<(), fastnbt::error::Error>(())
+    /// ```
+    pub fn merge(&mut self, patch: &Value, strategy: MergeStrategy) -> Result<()> {
+        if strategy.on_conflict == MergeConflict::Error {
+            let mut path = String::new();
+            if let Some(conflict) = self.find_conflict(patch, strategy, &mut path) {
+                return Err(Error::bespoke(format!(
+                    "cannot merge {} at `{}`",
+                    conflict, path
+                )));
+            }
+        }
+
+        self.merge_unchecked(patch, strategy);
+        Ok(())
+    }
+
+    /// Apply a patch, replacing any conflicts.
+    fn merge_unchecked(&mut self, patch: &Value, strategy: MergeStrategy) {
+        match (self, patch) {
+            (Value::Compound(map), Value::Compound(patch)) => {
+                for (key, value) in patch {
+                    match map.get_mut(key) {
+                        Some(existing) => existing.merge_unchecked(value, strategy),
+                        None => {
+                            map.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+            }
+            (Value::List(list), Value::List(patch))
+                if strategy.lists == ListMerge::Concat && elements_match(list, patch) =>
+            {
+                list.extend_from_slice(patch);
+            }
+            (this, patch) => *this = patch.clone(),
+        }
+    }
+
+    /// Describe the first place a merge would replace a value with a different
+    /// type of value, leaving `path` pointing at it.
+    fn find_conflict(
+        &self,
+        patch: &Value,
+        strategy: MergeStrategy,
+        path: &mut String,
+    ) -> Option<String> {
+        match (self, patch) {
+            (Value::Compound(map), Value::Compound(patch)) => {
+                for (key, value) in patch {
+                    if let Some(existing) = map.get(key) {
+                        let len = push_segment(path, key);
+
+                        let conflict = existing.find_conflict(value, strategy, path);
+                        if conflict.is_some() {
+                            return conflict;
+                        }
+                        path.truncate(len);
+                    }
+                }
+                None
+            }
+            (Value::List(list), Value::List(patch))
+                if strategy.lists == ListMerge::Concat && !elements_match(list, patch) =>
+            {
+                Some(format!(
+                    "a List holding {} into a List holding {}",
+                    kind!(Value, &patch[0]),
+                    kind!(Value, &list[0])
+                ))
+            }
+            (this, patch) if mem::discriminant(this) != mem::discriminant(patch) => Some(format!(
+                "{} into {}",
+                kind!(Value, patch),
+                kind!(Value, this)
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the elements of two lists are the same type, so they can be
+/// concatenated.
+fn elements_match(list: &[Value], patch: &[Value]) -> bool {
+    match (list.first(), patch.first()) {
+        (Some(a), Some(b)) => mem::discriminant(a) == mem::discriminant(b),
+        _ => true,
+    }
+}
@@ -0,0 +1,377 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ByteArray, IntArray, LongArray, Map, Tag};
+
+/// The variant of a value with its article, for messages.
+macro_rules! kind {
+    ($($enum:ident)::+, $v:expr) => {
+        match $v {
+            $($enum)::+::Byte(_) => "a Byte",
+            $($enum)::+::Short(_) => "a Short",
+            $($enum)::+::Int(_) => "an Int",
+            $($enum)::+::Long(_) => "a Long",
+            $($enum)::+::Double(_) => "a Double",
+            $($enum)::+::Float(_) => "a Float",
+            $($enum)::+::String(_) => "a String",
+            $($enum)::+::ByteArray(_) => "a ByteArray",
+            $($enum)::+::IntArray(_) => "an IntArray",
+            $($enum)::+::LongArray(_) => "a LongArray",
+            $($enum)::+::List(_) => "a List",
+            $($enum)::+::Compound(_) => "a Compound",
+        }
+    };
+}
+
+mod accessors;
+mod builder;
+mod de;
+mod hash;
+mod index;
+mod merge;
+mod path;
+mod size;
+mod valid;
+
+pub use builder::{CompoundBuilder, ListBuilder};
+pub use de::from_value;
+pub use index::ValueIndex;
+pub use merge::{ListMerge, MergeConflict, MergeStrategy};
+
+/// Value is a complete NBT value. It owns its data. Compounds and Lists are
+/// resursively deserialized. This type takes care to preserve all the
+/// information from the original NBT, with the exception of the name of the
+/// root compound (which is usually the empty string).
+///
+/// ```
+/// # use fastnbt::{Map, Value};
+/// # let level = Map::from([("Status".to_owned(), Value::String("full".to_owned()))]);
+/// # let root = Map::from([
+/// #     ("DataVersion".to_owned(), Value::Int(2586)),
+/// #     ("Level".to_owned(), Value::Compound(level)),
+/// # ]);
+/// # let data = fastnbt::to_bytes(&root).unwrap();
+/// let chunk: Value = fastnbt::de::from_bytes(&data)?;
+/// let version = chunk["DataVersion"].as_i64();
+/// let status = chunk["Level"]["Status"].as_str();
+///
+/// assert_eq!(Some(2586), version);
+/// assert_eq!(Some("full"), status);
+/// # Ok::<(), fastnbt::error::Error>(())
+/// ```
+///
+/// The `as_*` methods give the contents of a value if it is of that type, and
+/// None otherwise. The numeric ones accept more than one type:
+///
+/// | Method                        | Accepts                                              |
+/// |-------------------------------|------------------------------------------------------|
+/// | [`as_bool`][Value::as_bool]   | Byte, Short, Int, true if not zero                   |
+/// | [`as_i64`][Value::as_i64]     | Byte, Short, Int, Long                               |
+/// | [`as_u64`][Value::as_u64]     | Byte, Short, Int, Long, if not negative              |
+/// | [`as_usize`][Value::as_usize] | Byte, Short, Int, Long, if not negative and in range |
+/// | [`as_f64`][Value::as_f64]     | Byte, Short, Int, Long, Float, Double                |
+///
+/// Nothing else is converted, so a String of `"1"` is not a number. Going the
+/// other way, a `bool` converts to a Byte of 0 or 1 with `Value::from`.
+///
+/// # Indexing
+///
+/// Like `serde_json::Value`, compounds can be indexed by key and lists by
+/// position, and indexes can be chained:
+///
+/// ```
+/// # use fastnbt::{Map, Value};
+/// # let section = Value::Compound(Map::from([("Y".to_owned(), Value::Byte(3))]));
+/// # let level = Value::Compound(Map::from([("Sections".to_owned(), Value::List(vec![section]))]));
+/// # let mut chunk = Value::Compound(Map::from([("Level".to_owned(), level)]));
+/// assert_eq!(Value::Byte(3), chunk["Level"]["Sections"][0]["Y"]);
+///
+/// // Anything that is not there is an empty list, which can be indexed further.
+/// assert_eq!(Value::List(vec![]), chunk["Level"]["Entities"][3]["id"]);
+///
+/// // Use get to tell a missing value apart from an empty list.
+/// assert_eq!(None, chunk["Level"].get("Entities"));
+///
+/// // Assigning to a missing key inserts it, creating compounds on the way.
+/// chunk["Level"]["Heightmaps"]["OCEAN_FLOOR"] = Value::LongArray(fastnbt::LongArray::new(vec![]));
+/// assert!(chunk["Level"].get("Heightmaps").is_some());
+/// ```
+///
+/// Indexing with `[]` never panics: a missing key, an index out of range, or
+/// indexing the wrong type of value (such as a list by key) gives an empty
+/// list. Mutable indexing panics if the value is the wrong type, or if a list
+/// index is out of range, since there is nothing to assign to.
+///
+/// For paths given as a string, such as from a user, see
+/// [`get_path`][Value::get_path].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Value {
+    #[serde(deserialize_with = "strict_i8")]
+    Byte(i8),
+    #[serde(deserialize_with = "strict_i16")]
+    Short(i16),
+    #[serde(deserialize_with = "strict_i32")]
+    Int(i32),
+    Long(i64),
+    #[serde(deserialize_with = "strict_f64")]
+    Double(f64),
+    #[serde(deserialize_with = "strict_f32")]
+    Float(f32),
+    String(String),
+    ByteArray(ByteArray),
+    IntArray(IntArray),
+    LongArray(LongArray),
+    List(Vec<Value>),
+    Compound(Map<String, Value>),
+}
+
+impl Value {
+    /// Compare two values exactly. This is like `==`, except floats are
+    /// compared by their bit patterns. A NaN is equal to a NaN with the same
+    /// bits, and `0.0` is not equal to `-0.0`.
+    ///
+    /// Useful for checking that data has not changed after a round trip, where
+    /// `==` would report a difference for any NaN.
+    pub fn bitwise_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Float(a), Value::Float(b)) => a.to_bits() == b.to_bits(),
+            (Value::Double(a), Value::Double(b)) => a.to_bits() == b.to_bits(),
+            (Value::List(a), Value::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.bitwise_eq(b))
+            }
+            (Value::Compound(a), Value::Compound(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(k, v)| b.get(k).is_some_and(|other| v.bitwise_eq(other)))
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// A total order over values, for sorting them or removing duplicates.
+    /// Values of different types are ordered by their tag ID, so every Byte is
+    /// less than every Short. Values of the same type are ordered by their
+    /// contents:
+    ///
+    /// * Floats by [`f64::total_cmp`], so NaNs sort after infinity (or before,
+    ///   if negative) and `-0.0` before `0.0`.
+    /// * Strings, lists and arrays lexicographically.
+    /// * Compounds by their entries sorted by key, compared as a list of key
+    ///   and value pairs.
+    ///
+    /// Two values compare as equal exactly when they are
+    /// [`bitwise_eq`][Self::bitwise_eq].
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Byte(a), Value::Byte(b)) => a.cmp(b),
+            (Value::Short(a), Value::Short(b)) => a.cmp(b),
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::Long(a), Value::Long(b)) => a.cmp(b),
+            (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+            (Value::Double(a), Value::Double(b)) => a.total_cmp(b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::ByteArray(a), Value::ByteArray(b)) => a.cmp(b),
+            (Value::IntArray(a), Value::IntArray(b)) => a.cmp(b),
+            (Value::LongArray(a), Value::LongArray(b)) => a.cmp(b),
+            (Value::List(a), Value::List(b)) => {
+                for (a, b) in a.iter().zip(b) {
+                    match a.total_cmp(b) {
+                        Ordering::Equal => {}
+                        ord => return ord,
+                    }
+                }
+                a.len().cmp(&b.len())
+            }
+            (Value::Compound(a), Value::Compound(b)) => {
+                let a = sorted(a);
+                let b = sorted(b);
+                for (&(ak, av), &(bk, bv)) in a.iter().zip(&b) {
+                    match ak.cmp(bk).then_with(|| av.total_cmp(bv)) {
+                        Ordering::Equal => {}
+                        ord => return ord,
+                    }
+                }
+                a.len().cmp(&b.len())
+            }
+            (a, b) => u8::from(a.tag()).cmp(&u8::from(b.tag())),
+        }
+    }
+
+    /// Sort the entries of every compound in this value by key, where the
+    /// [`Map`] type keeps an order. Serializing the value then gives the same
+    /// bytes as serializing any other value it is `==` to, which is useful
+    /// for hashing or comparing NBT as bytes.
+    ///
+    /// This only does anything with the `preserve_order` feature. Without it,
+    /// compounds are either a `HashMap` which has no order to change, so the
+    /// order they are written in can differ between two equal values, or a
+    /// `BTreeMap` when without `std` which is always sorted.
+    pub fn canonicalize(&mut self) {
+        match self {
+            Value::List(list) => list.iter_mut().for_each(Value::canonicalize),
+            Value::Compound(map) => {
+                #[cfg(feature = "preserve_order")]
+                map.sort_keys();
+                map.values_mut().for_each(Value::canonicalize);
+            }
+            _ => {}
+        }
+    }
+
+    /// The tag this value is written with.
+    pub(crate) fn tag(&self) -> Tag {
+        match self {
+            Value::Byte(_) => Tag::Byte,
+            Value::Short(_) => Tag::Short,
+            Value::Int(_) => Tag::Int,
+            Value::Long(_) => Tag::Long,
+            Value::Double(_) => Tag::Double,
+            Value::Float(_) => Tag::Float,
+            Value::String(_) => Tag::String,
+            Value::ByteArray(_) => Tag::ByteArray,
+            Value::IntArray(_) => Tag::IntArray,
+            Value::LongArray(_) => Tag::LongArray,
+            Value::List(_) => Tag::List,
+            Value::Compound(_) => Tag::Compound,
+        }
+    }
+}
+
+/// The entries of a compound, sorted by key.
+fn sorted(map: &Map<String, Value>) -> Vec<(&String, &Value)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+fn strict_i8<'de, D>(de: D) -> core::result::Result<i8, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    struct StrictI8Visitor;
+    impl<'de> serde::de::Visitor<'de> for StrictI8Visitor {
+        type Value = i8;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            write!(formatter, "expecting exactly i8")
+        }
+
+        fn visit_i8<E>(self, v: i8) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v)
+        }
+    }
+
+    de.deserialize_i8(StrictI8Visitor)
+}
+
+fn strict_i16<'de, D>(de: D) -> core::result::Result<i16, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    struct Stricti16Visitor;
+    impl<'de> serde::de::Visitor<'de> for Stricti16Visitor {
+        type Value = i16;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            write!(formatter, "expecting exactly i16")
+        }
+
+        fn visit_i16<E>(self, v: i16) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v)
+        }
+    }
+
+    de.deserialize_i16(Stricti16Visitor)
+}
+
+fn strict_i32<'de, D>(de: D) -> core::result::Result<i32, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    struct Stricti32Visitor;
+    impl<'de> serde::de::Visitor<'de> for Stricti32Visitor {
+        type Value = i32;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            write!(formatter, "expecting exactly i32")
+        }
+
+        fn visit_i32<E>(self, v: i32) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v)
+        }
+    }
+
+    de.deserialize_i32(Stricti32Visitor)
+}
+
+// Without these, the f64 visitor for Double would also accept an f32, turning
+// every Float into a Double. A visitor's `visit_f32` defaults to passing the
+// value on to `visit_f64`, so the f64 visitor has to reject it explicitly.
+fn strict_f32<'de, D>(de: D) -> core::result::Result<f32, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    struct StrictF32Visitor;
+    impl<'de> serde::de::Visitor<'de> for StrictF32Visitor {
+        type Value = f32;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            write!(formatter, "expecting exactly f32")
+        }
+
+        fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v)
+        }
+    }
+
+    de.deserialize_f32(StrictF32Visitor)
+}
+
+fn strict_f64<'de, D>(de: D) -> core::result::Result<f64, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    struct StrictF64Visitor;
+    impl<'de> serde::de::Visitor<'de> for StrictF64Visitor {
+        type Value = f64;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            write!(formatter, "expecting exactly f64")
+        }
+
+        fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Err(E::invalid_type(
+                serde::de::Unexpected::Float(v.into()),
+                &self,
+            ))
+        }
+
+        fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v)
+        }
+    }
+
+    de.deserialize_f64(StrictF64Visitor)
+}
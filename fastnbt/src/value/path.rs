@@ -0,0 +1,347 @@
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem;
+
+use crate::error::{Error, Result};
+use crate::{Map, Value};
+
+impl Value {
+    /// Look up the value at a `/` separated path, such as
+    /// `"Level/Sections/2/Palette/0/Name"`. Each segment is the key of an
+    /// entry in a compound, or the index of an element of a list or array.
+    /// Returns None if there is no value at the path, rather than panicking.
+    ///
+    /// Keys containing a `/` or `~` are escaped as in a JSON pointer: `~1` for
+    /// `/` and `~0` for `~`. An empty path is this value itself.
+    ///
+    /// The elements of the NBT arrays are not stored as values, so indexing
+    /// into an array gives an owned scalar, eg a [`Value::Long`] for a
+    /// [`LongArray`][`crate::LongArray`]. Everything else is borrowed.
+    ///
+    /// ```
+    /// # use fastnbt::{LongArray, Map, Value};
+    /// # let palette = Value::List(vec![Value::Compound(Map::from([("Name".to_owned(), Value::String("minecraft:air".to_owned()))]))]);
+    /// # let section = Value::Compound(Map::from([
+    /// #     ("Palette".to_owned(), palette),
+    /// #     ("BlockStates".to_owned(), Value::LongArray(LongArray::new(vec![0, 7]))),
+    /// # ]));
+    /// # let level = Value::Compound(Map::from([
+    /// #     ("Sections".to_owned(), Value::List(vec![section])),
+    /// #     ("a/b".to_owned(), Value::Int(1)),
+    /// # ]));
+    /// # let chunk = Value::Compound(Map::from([("Level".to_owned(), level)]));
+    /// let name = chunk.get_path("Level/Sections/0/Palette/0/Name");
+    /// assert_eq!(Some(&Value::String("minecraft:air".to_owned())), name.as_deref());
+    ///
+    /// let state = chunk.get_path("Level/Sections/0/BlockStates/1");
+    /// assert_eq!(Some(&Value::Long(7)), state.as_deref());
+    ///
+    /// assert_eq!(Some(&Value::Int(1)), chunk.get_path("Level/a~1b").as_deref());
+    /// assert_eq!(None, chunk.get_path("Level/Sections/5"));
+    /// ```
+    pub fn get_path(&self, path: &str) -> Option<Cow<'_, Value>> {
+        let mut value = self;
+        let mut segments = segments(path);
+
+        while let Some(segment) = segments.next() {
+            let segment = segment?;
+            let element = match value {
+                Value::Compound(map) => {
+                    value = map.get(&*segment)?;
+                    continue;
+                }
+                Value::List(list) => {
+                    value = list.get(index(&segment)?)?;
+                    continue;
+                }
+                Value::ByteArray(a) => Value::Byte(*a.get(index(&segment)?)?),
+                Value::IntArray(a) => Value::Int(*a.get(index(&segment)?)?),
+                Value::LongArray(a) => Value::Long(*a.get(index(&segment)?)?),
+                _ => return None,
+            };
+
+            // Array elements are scalars, there is nothing further to index.
+            return match segments.next() {
+                None => Some(Cow::Owned(element)),
+                Some(_) => None,
+            };
+        }
+
+        Some(Cow::Borrowed(value))
+    }
+
+    /// Like [`get_path`][Self::get_path], but mutable. The elements of the NBT
+    /// arrays are not values, so a path into an array gives None; get the
+    /// array itself instead.
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut Value> {
+        self.walk_mut(&parse(path)?)
+    }
+
+    /// Insert `value` at a path in the form used by
+    /// [`get_path`][Self::get_path], returning the value it replaced. Missing
+    /// compounds along the path are created. A list index can be an existing
+    /// element, or the length of the list to append to it, but anything past
+    /// the end is an error rather than padding the list.
+    ///
+    /// It is also an error for the path to go through anything other than a
+    /// compound or list, such as a string or an NBT array, or to insert a
+    /// value of a different type to the rest of a list's elements. An empty
+    /// path replaces this whole value.
+    ///
+    /// ```
+    /// # use fastnbt::{Map, Value};
+    /// let mut level = Value::Compound(Map::new());
+    /// level.insert_path("Data/GameRules/keepInventory", Value::String("true".to_owned()))?;
+    /// assert_eq!(Some("true"), level["Data"]["GameRules"]["keepInventory"].as_str());
+    ///
+    /// assert!(level.insert_path("Data/GameRules/keepInventory/x", Value::Int(1)).is_err());
+    /// # Ok::<(), fastnbt::error::Error>(())
+    /// ```
+    pub fn insert_path(&mut self, path: &str, value: Value) -> Result<Option<Value>> {
+        let segments = parse(path)
+            .ok_or_else(|| Error::bespoke(format!("invalid escape in path `{}`", path)))?;
+        let (last, parents) = match segments.split_last() {
+            Some(split) => split,
+            None => return Ok(Some(mem::replace(self, value))),
+        };
+
+        let mut target = self;
+        for (depth, segment) in parents.iter().enumerate() {
+            target = match target {
+                Value::Compound(map) => {
+                    if !map.contains_key(&**segment) {
+                        map.insert(segment.clone().into_owned(), Value::Compound(Map::new()));
+                    }
+                    map.get_mut(&**segment).unwrap()
+                }
+                Value::List(list) => {
+                    let len = list.len();
+                    index(segment)
+                        .and_then(move |i| list.get_mut(i))
+                        .ok_or_else(|| out_of_range(path, depth, len))?
+                }
+                other => return Err(conflict(path, depth, kind!(Value, other))),
+            };
+        }
+
+        match target {
+            Value::Compound(map) => Ok(map.insert(last.clone().into_owned(), value)),
+            Value::List(list) => {
+                let i = index(last)
+                    .filter(|&i| i <= list.len())
+                    .ok_or_else(|| out_of_range(path, parents.len(), list.len()))?;
+                let mismatch = list.iter().enumerate().find(|&(j, element)| {
+                    j != i && mem::discriminant(element) != mem::discriminant(&value)
+                });
+                if let Some((_, element)) = mismatch {
+                    return Err(Error::bespoke(format!(
+                        "cannot insert {} at `{}`: the list already holds {}",
+                        kind!(Value, &value),
+                        path,
+                        kind!(Value, element),
+                    )));
+                }
+
+                if i == list.len() {
+                    list.push(value);
+                    Ok(None)
+                } else {
+                    Ok(Some(mem::replace(&mut list[i], value)))
+                }
+            }
+            other => Err(conflict(path, parents.len(), kind!(Value, other))),
+        }
+    }
+
+    /// Remove the value at a path in the form used by
+    /// [`get_path`][Self::get_path], returning it. None if there is nothing
+    /// there to remove. Removing an element of a list moves the elements after
+    /// it down by one.
+    pub fn remove_path(&mut self, path: &str) -> Option<Value> {
+        let segments = parse(path)?;
+        let (last, parents) = segments.split_last()?;
+        match self.walk_mut(parents)? {
+            Value::Compound(map) => remove_key(map, last),
+            Value::List(list) => {
+                let i = index(last).filter(|&i| i < list.len())?;
+                Some(list.remove(i))
+            }
+            _ => None,
+        }
+    }
+
+    /// Remove an entry from a compound, returning its value. None if there is
+    /// no such entry, or this is not a compound.
+    pub fn take(&mut self, key: &str) -> Option<Value> {
+        match self {
+            Value::Compound(map) => remove_key(map, key),
+            _ => None,
+        }
+    }
+
+    /// Every compound in this value that `pred` accepts, including this value
+    /// itself, and any inside lists or other compounds. Each is given with its
+    /// path in the form used by [`get_path`][Self::get_path]. Compounds are
+    /// listed before those inside them.
+    ///
+    /// ```
+    /// # use fastnbt::{Map, Value};
+    /// # let chest = Value::Compound(Map::from([("id".to_owned(), Value::String("minecraft:chest".to_owned()))]));
+    /// # let level = Value::Compound(Map::from([("TileEntities".to_owned(), Value::List(vec![chest]))]));
+    /// # let chunk = Value::Compound(Map::from([("Level".to_owned(), level)]));
+    /// let chests = chunk.find_compounds(|c| {
+    ///     c.get("id").and_then(Value::as_str) == Some("minecraft:chest")
+    /// });
+    /// for (path, _chest) in &chests {
+    ///     assert_eq!("Level/TileEntities/0", path);
+    /// }
+    /// ```
+    pub fn find_compounds(
+        &self,
+        pred: impl Fn(&Map<String, Value>) -> bool,
+    ) -> Vec<(String, &Value)> {
+        let mut found = Vec::new();
+        self.find_compounds_at(&pred, &mut String::new(), &mut found);
+        found
+    }
+
+    /// Every compound in this value with an entry for `key`, such as `"id"` to
+    /// find entities and block entities. See
+    /// [`find_compounds`][Self::find_compounds].
+    pub fn find_key(&self, key: &str) -> Vec<(String, &Value)> {
+        self.find_compounds(|c| c.contains_key(key))
+    }
+
+    fn find_compounds_at<'v>(
+        &'v self,
+        pred: &dyn Fn(&Map<String, Value>) -> bool,
+        path: &mut String,
+        found: &mut Vec<(String, &'v Value)>,
+    ) {
+        match self {
+            Value::Compound(map) => {
+                if pred(map) {
+                    found.push((path.clone(), self));
+                }
+                for (key, value) in map {
+                    let len = push_segment(path, key);
+                    value.find_compounds_at(pred, path, found);
+                    path.truncate(len);
+                }
+            }
+            Value::List(list) => {
+                for (i, value) in list.iter().enumerate() {
+                    let len = push_segment(path, &format!("{}", i));
+                    value.find_compounds_at(pred, path, found);
+                    path.truncate(len);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn walk_mut(&mut self, segments: &[Cow<'_, str>]) -> Option<&mut Value> {
+        let mut value = self;
+        for segment in segments {
+            value = match value {
+                Value::Compound(map) => map.get_mut(&**segment)?,
+                Value::List(list) => list.get_mut(index(segment)?)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+}
+
+// Removing from an IndexMap would otherwise move its last entry into the gap.
+#[cfg(feature = "preserve_order")]
+fn remove_key(map: &mut Map<String, Value>, key: &str) -> Option<Value> {
+    map.shift_remove(key)
+}
+
+#[cfg(not(feature = "preserve_order"))]
+fn remove_key(map: &mut Map<String, Value>, key: &str) -> Option<Value> {
+    map.remove(key)
+}
+
+/// The part of `path` made of its first `depth` segments.
+fn prefix(path: &str, depth: usize) -> &str {
+    match depth.checked_sub(1) {
+        Some(n) => path
+            .match_indices('/')
+            .nth(n)
+            .map_or(path, |(i, _)| &path[..i]),
+        None => "",
+    }
+}
+
+fn conflict(path: &str, depth: usize, kind: &str) -> Error {
+    Error::bespoke(format!(
+        "cannot insert at `{}`: `{}` is {}",
+        path,
+        prefix(path, depth),
+        kind
+    ))
+}
+
+fn out_of_range(path: &str, depth: usize, len: usize) -> Error {
+    Error::bespoke(format!(
+        "cannot insert at `{}`: `{}` is a List of length {}",
+        path,
+        prefix(path, depth),
+        len
+    ))
+}
+
+/// Add a key or index to the end of a path, escaping it. Returns the length of
+/// the path before, to truncate it back to.
+pub(super) fn push_segment(path: &mut String, segment: &str) -> usize {
+    let len = path.len();
+    if len > 0 {
+        path.push('/');
+    }
+    for c in segment.chars() {
+        match c {
+            '~' => path.push_str("~0"),
+            '/' => path.push_str("~1"),
+            c => path.push(c),
+        }
+    }
+    len
+}
+
+/// The index of a list or array element.
+fn index(segment: &str) -> Option<usize> {
+    segment.parse().ok()
+}
+
+/// The unescaped segments of a path. A segment is None if it has an invalid
+/// escape, which nothing can match.
+fn parse(path: &str) -> Option<Vec<Cow<'_, str>>> {
+    segments(path).collect()
+}
+
+fn segments(path: &str) -> impl Iterator<Item = Option<Cow<'_, str>>> {
+    let path = Some(path).filter(|p| !p.is_empty());
+    path.into_iter().flat_map(|p| p.split('/')).map(unescape)
+}
+
+fn unescape(segment: &str) -> Option<Cow<'_, str>> {
+    if !segment.contains('~') {
+        return Some(Cow::Borrowed(segment));
+    }
+
+    let mut parts = segment.split('~');
+    let mut key = String::from(parts.next()?);
+    for part in parts {
+        match part.as_bytes().first() {
+            Some(b'0') => key.push('~'),
+            Some(b'1') => key.push('/'),
+            _ => return None,
+        }
+        key.push_str(&part[1..]);
+    }
+    Some(Cow::Owned(key))
+}
@@ -0,0 +1,103 @@
+use alloc::string::String;
+use core::mem;
+
+use crate::{Map, Value};
+
+impl Value {
+    /// An estimate of the memory used by this value, in bytes. Meant as the
+    /// weight of a value in a cache, it is not exact, but the same value
+    /// always gives the same estimate. It is the size of the `Value` itself,
+    /// plus everything it owns on the heap:
+    ///
+    /// * the capacity of strings, including the keys of compounds,
+    /// * the capacity of lists, as whole `Value`s,
+    /// * the length of the NBT arrays, at the size of their elements,
+    /// * an entry with a key and a `Value` for each unit of a compound's
+    ///   capacity, plus the overhead of the map type used for [`Map`].
+    ///
+    /// Allocations are assumed to be exactly the size asked for, so any
+    /// overhead of the allocator itself is not counted.
+    pub fn estimated_size(&self) -> usize {
+        mem::size_of::<Value>() + self.heap_size()
+    }
+
+    /// Release any spare capacity held by this value and everything in it,
+    /// for values that will be kept for a long time, such as in a cache.
+    /// Deserialized values are already sized exactly, so this is for values
+    /// that have been built or edited since.
+    ///
+    /// Compounds are rebuilt to do this, as the keys of a map can't be
+    /// changed in place, so this is not free for large values.
+    pub fn shrink_to_fit(&mut self) {
+        match self {
+            Value::String(s) => s.shrink_to_fit(),
+            Value::ByteArray(a) => a.shrink_to_fit(),
+            Value::IntArray(a) => a.shrink_to_fit(),
+            Value::LongArray(a) => a.shrink_to_fit(),
+            Value::List(list) => {
+                list.shrink_to_fit();
+                list.iter_mut().for_each(Value::shrink_to_fit);
+            }
+            Value::Compound(map) => {
+                *map = mem::take(map)
+                    .into_iter()
+                    .map(|(mut k, mut v)| {
+                        k.shrink_to_fit();
+                        v.shrink_to_fit();
+                        (k, v)
+                    })
+                    .collect();
+                // Collecting can reserve more than it needs, and a B-tree has
+                // no capacity to shrink.
+                #[cfg(feature = "std")]
+                map.shrink_to_fit();
+            }
+            _ => {}
+        }
+    }
+
+    fn heap_size(&self) -> usize {
+        match self {
+            Value::String(s) => s.capacity(),
+            Value::ByteArray(a) => mem::size_of_val::<[i8]>(a),
+            Value::IntArray(a) => mem::size_of_val::<[i32]>(a),
+            Value::LongArray(a) => mem::size_of_val::<[i64]>(a),
+            Value::List(list) => {
+                list.capacity() * mem::size_of::<Value>()
+                    + list.iter().map(Value::heap_size).sum::<usize>()
+            }
+            Value::Compound(map) => {
+                map_size(map)
+                    + map
+                        .iter()
+                        .map(|(k, v)| k.capacity() + v.heap_size())
+                        .sum::<usize>()
+            }
+            _ => 0,
+        }
+    }
+}
+
+type Entry = (String, Value);
+
+/// A hash table has a control byte for each slot, and keeps some spare slots
+/// beyond its capacity which this does not try to count.
+#[cfg(all(feature = "std", not(feature = "preserve_order")))]
+fn map_size(map: &Map<String, Value>) -> usize {
+    map.capacity() * (mem::size_of::<Entry>() + 1)
+}
+
+/// An `IndexMap` is a vec of entries with their hashes, and a hash table of
+/// indices into it.
+#[cfg(feature = "preserve_order")]
+fn map_size(map: &Map<String, Value>) -> usize {
+    let entry = mem::size_of::<Entry>() + mem::size_of::<u64>();
+    map.capacity() * (entry + mem::size_of::<usize>() + 1)
+}
+
+/// A B-tree has no capacity, but nodes are usually between half and fully
+/// occupied, so count room for half as many entries again.
+#[cfg(not(feature = "std"))]
+fn map_size(map: &Map<String, Value>) -> usize {
+    map.len() * mem::size_of::<Entry>() * 3 / 2
+}
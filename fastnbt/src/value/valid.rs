@@ -0,0 +1,115 @@
+use alloc::format;
+use alloc::string::String;
+
+use crate::error::{Error, Result};
+use crate::{Map, Tag, Value};
+
+impl Value {
+    /// The tag every element of a list has, which is the tag the list would
+    /// be written with. None if this is not a list, or if the list is empty
+    /// or holds more than one type, as neither says which tag to use.
+    pub fn list_tag(&self) -> Option<Tag> {
+        let list = self.as_list()?;
+        let tag = list.first()?.tag();
+        list.iter().all(|v| v.tag() == tag).then_some(tag)
+    }
+
+    /// Check that this value could be serialized as NBT, so edits to it can
+    /// be validated as they are made rather than when writing it out. The
+    /// first problem found is returned, with its [`path`][Error::path] in the
+    /// value:
+    ///
+    /// * a list holding more than one type, as
+    ///   [`HeterogeneousList`][crate::error::ErrorKind::HeterogeneousList],
+    /// * a string or compound key longer than the 65535 bytes NBT allows, as
+    ///   Java CESU-8,
+    /// * a list or array with more elements than an NBT length can hold.
+    ///
+    /// Any value can be nested in another, so this does not check that the
+    /// value is a compound, which [`to_bytes`][crate::to_bytes] needs at the
+    /// root.
+    pub fn is_valid_nbt(&self) -> Result<()> {
+        let mut path = String::new();
+        self.check(&mut path).map_err(|e| e.at_path(path))
+    }
+
+    /// On error, `path` is left pointing at the offending value.
+    fn check(&self, path: &mut String) -> Result<()> {
+        match self {
+            Value::String(s) => check_str(s),
+            Value::ByteArray(a) => check_len(a.len()),
+            Value::IntArray(a) => check_len(a.len()),
+            Value::LongArray(a) => check_len(a.len()),
+            Value::List(list) => {
+                check_len(list.len())?;
+                if let Some(first) = list.first() {
+                    let expected = first.tag();
+                    let mismatch = list.iter().position(|v| v.tag() != expected);
+                    if let Some(index) = mismatch {
+                        return Err(Error::heterogeneous_list(
+                            index,
+                            expected,
+                            list[index].tag(),
+                        ));
+                    }
+                }
+                for (i, v) in list.iter().enumerate() {
+                    let len = path.len();
+                    path.push_str(&format!("[{}]", i));
+                    v.check(path)?;
+                    path.truncate(len);
+                }
+                Ok(())
+            }
+            Value::Compound(map) => check_compound(map, path),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn check_compound(map: &Map<String, Value>, path: &mut String) -> Result<()> {
+    for (k, v) in map {
+        let len = path.len();
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(k);
+        check_str(k)?;
+        v.check(path)?;
+        path.truncate(len);
+    }
+    Ok(())
+}
+
+/// The length of a string as Java CESU-8 is never less than as UTF-8, so this
+/// is the stricter limit. NUL takes two bytes rather than one, and characters
+/// outside the BMP take six rather than four.
+fn check_str(s: &str) -> Result<()> {
+    let extra = s
+        .bytes()
+        .map(|b| match b {
+            0 => 1,
+            0xf0.. => 2,
+            _ => 0,
+        })
+        .sum::<usize>();
+    let len = s.len() + extra;
+    if len > u16::MAX as usize {
+        return Err(Error::bespoke(format!(
+            "string of {} bytes is too long for NBT, the maximum is {}",
+            len,
+            u16::MAX
+        )));
+    }
+    Ok(())
+}
+
+fn check_len(len: usize) -> Result<()> {
+    if len > i32::MAX as usize {
+        return Err(Error::bespoke(format!(
+            "length {} is too large for an NBT list or array",
+            len
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,81 @@
+//! Helpers for `#[serde(deserialize_with)]` that accept numbers stored with a
+//! smaller tag than expected, but never a larger one.
+//!
+//! A plain integer field accepts any integer tag whose value fits, so an `i32`
+//! field will take a Long of `5` but fail on a Long of `5000000000`. Data that
+//! is sloppy about widths, such as from mods, then only fails for some values.
+//! These helpers decide by the tag instead: an `i32` field accepts a Byte,
+//! Short or Int, and always rejects a Long. Integers and floats are never
+//! mixed.
+//!
+//! ```
+//! # use fastnbt::{de::from_bytes, to_bytes, Value};
+//! # use serde::Deserialize;
+//! # use std::collections::HashMap;
+//! #[derive(Deserialize)]
+//! struct Machine {
+//!     #[serde(deserialize_with = "fastnbt::widen::i32")]
+//!     energy: i32,
+//! }
+//!
+//! let data = to_bytes(&HashMap::from([("energy", Value::Byte(12))])).unwrap();
+//! let machine: Machine = from_bytes(&data).unwrap();
+//! assert_eq!(12, machine.energy);
+//!
+//! let data = to_bytes(&HashMap::from([("energy", Value::Long(12))])).unwrap();
+//! assert!(from_bytes::<Machine>(&data).is_err());
+//! ```
+//!
+//! These work with any deserializer, including [`from_value`][`crate::from_value`].
+
+use core::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+
+/// Define a function deserializing `$ty` from the listed visitor methods only.
+/// Any other value falls through to the default visitor methods, which reject
+/// it as the wrong type.
+macro_rules! widen {
+    ($(#[$doc:meta])* $name:ident -> $ty:ty, $expecting:literal, $($visit:ident($from:ty)),*) => {
+        $(#[$doc])*
+        pub fn $name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<$ty, D::Error> {
+            struct WidenVisitor;
+
+            impl<'de> Visitor<'de> for WidenVisitor {
+                type Value = $ty;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str($expecting)
+                }
+
+                $(
+                    fn $visit<E: de::Error>(self, v: $from) -> Result<$ty, E> {
+                        Ok(v.into())
+                    }
+                )*
+            }
+
+            deserializer.deserialize_any(WidenVisitor)
+        }
+    };
+}
+
+widen! {
+    /// Deserialize an `i16` from a Byte or Short.
+    i16 -> i16, "a Byte or Short", visit_i8(i8), visit_i16(i16)
+}
+
+widen! {
+    /// Deserialize an `i32` from a Byte, Short or Int.
+    i32 -> i32, "a Byte, Short or Int", visit_i8(i8), visit_i16(i16), visit_i32(i32)
+}
+
+widen! {
+    /// Deserialize an `i64` from any integer tag.
+    i64 -> i64, "a Byte, Short, Int or Long", visit_i8(i8), visit_i16(i16), visit_i32(i32), visit_i64(i64)
+}
+
+widen! {
+    /// Deserialize an `f64` from a Float or Double.
+    f64 -> f64, "a Float or Double", visit_f32(f32), visit_f64(f64)
+}
@@ -4,7 +4,7 @@ use fastanvil::{
     pre18, render_region, CCoord, HeightMode, JavaChunk, RCoord, RegionLoader, Rgba,
     TopShadeRenderer,
 };
-use fastanvil::{Dimension, RenderedPalette};
+use fastanvil::{Dimension, DimensionId, RenderedPalette, WorldBorder, WorldMeta};
 
 use fastanvil::RegionFileLoader;
 use flate2::read::GzDecoder;
@@ -119,13 +119,9 @@ fn render(args: &ArgMatches) -> Result<()> {
         false => HeightMode::Trust,
     };
 
-    let subpath = match dim {
-        "end" => "DIM1/region",
-        "nether" => "DIM-1/region",
-        _ => "region",
-    };
+    let region_dir = dim.parse::<DimensionId>()?.region_dir(&world);
 
-    let loader = RegionFileLoader::<JavaChunk>::new(world.join(subpath));
+    let loader = RegionFileLoader::<JavaChunk>::new(region_dir.clone());
 
     let coords = loader.list()?;
 
@@ -149,7 +145,7 @@ fn render(args: &ArgMatches) -> Result<()> {
     let region_maps: Vec<_> = coords
         .into_par_iter()
         .filter_map(|coord| {
-            let loader = RegionFileLoader::<pre18::JavaChunk>::new(world.join(subpath));
+            let loader = RegionFileLoader::<pre18::JavaChunk>::new(region_dir.clone());
             let dimension = Dimension::new(Box::new(loader));
 
             let (x, z) = coord;
@@ -194,10 +190,46 @@ fn render(args: &ArgMatches) -> Result<()> {
         }
     }
 
+    if args.is_present("border") {
+        if dim.parse::<DimensionId>()? == DimensionId::Overworld {
+            let border = WorldMeta::discover(&world)?.level_dat()?.world_border();
+            let origin = (
+                x_range.start.0 * region_len as isize,
+                z_range.start.0 * region_len as isize,
+            );
+            draw_border(&mut img, origin, &border);
+        } else {
+            info!("world border is only drawn for the overworld");
+        }
+    }
+
     img.save("map.png").unwrap();
     Ok(())
 }
 
+/// Draw the edge of the world border onto a map whose top left pixel is the
+/// block at `origin`.
+fn draw_border(img: &mut image::RgbaImage, origin: (isize, isize), border: &WorldBorder) {
+    let (width, height) = img.dimensions();
+
+    for pz in 0..height {
+        for px in 0..width {
+            let x = origin.0 + px as isize;
+            let z = origin.1 + pz as isize;
+
+            let on_edge = border.contains(x, z)
+                && !(border.contains(x - 1, z)
+                    && border.contains(x + 1, z)
+                    && border.contains(x, z - 1)
+                    && border.contains(x, z + 1));
+
+            if on_edge {
+                img.put_pixel(px, pz, image::Rgba([255, 0, 0, 255]));
+            }
+        }
+    }
+}
+
 fn tiles(args: &ArgMatches) -> Result<()> {
     let world: PathBuf = args.value_of("world").unwrap().parse().unwrap();
     let dim: &str = args.value_of("dimension").unwrap();
@@ -207,16 +239,12 @@ fn tiles(args: &ArgMatches) -> Result<()> {
         false => HeightMode::Trust,
     };
 
-    let subpath = match dim {
-        "end" => "DIM1/region",
-        "nether" => "DIM-1/region",
-        _ => "region",
-    };
+    let region_dir = dim.parse::<DimensionId>()?.region_dir(&world);
 
     // don't care if dir already exists.
     std::fs::DirBuilder::new().create(out).unwrap_or_default();
 
-    let loader = RegionFileLoader::<JavaChunk>::new(world.join(subpath));
+    let loader = RegionFileLoader::<JavaChunk>::new(region_dir.clone());
 
     let coords = loader.list()?;
 
@@ -240,7 +268,7 @@ fn tiles(args: &ArgMatches) -> Result<()> {
     let regions_processed = coords
         .into_par_iter()
         .map(|coord| {
-            let loader = RegionFileLoader::<JavaChunk>::new(world.join(subpath));
+            let loader = RegionFileLoader::<JavaChunk>::new(region_dir.clone());
             let dimension = Dimension::new(Box::new(loader));
 
             let (x, z) = coord;
@@ -330,6 +358,13 @@ fn main() -> Result<()> {
                         .long("calculate-heights")
                         .takes_value(false)
                         .required(false),
+                )
+                .arg(
+                    Arg::with_name("border")
+                        .long("border")
+                        .help("draw the world border from level.dat")
+                        .takes_value(false)
+                        .required(false),
                 ),
         )
         .subcommand(